@@ -0,0 +1,610 @@
+//! A tamper-evident writer for audit logging.
+//!
+//! [`AuditAppender`] wraps any [`io::Write`] implementation — typically a
+//! [`RollingFileAppender`] or a plain file — and turns each write into a
+//! length-prefixed _record_ carrying a SHA-256 hash chained to the previous
+//! record. Modifying, reordering, or deleting a record breaks the chain for
+//! every record that follows, so the [`verify`] utility can detect after the
+//! fact whether a log file has been tampered with.
+//!
+//! A hash chain alone cannot detect an attacker who truncates the file at a
+//! record boundary and regenerates the chain from that point. To defend
+//! against this, [`AuditAppender::with_signer`] periodically invokes a
+//! user-provided callback with the current chain head and embeds the returned
+//! signature as a record of its own. Verifiers holding the corresponding
+//! public key can then check the signatures with [`verify_with`]. The signing
+//! scheme is entirely up to the caller (for example Ed25519 via a dedicated
+//! crypto crate, or an HMAC with a key the writer cannot read back).
+//!
+//! # Record format
+//!
+//! Each record is laid out as:
+//!
+//! ```text
+//! kind (1 byte) | length (4 bytes, big-endian) | payload | hash (32 bytes)
+//! ```
+//!
+//! where `kind` is `0x01` for a log line or `0x02` for a signature, and
+//! `hash` is `SHA-256(previous hash || kind || length || payload)`. The
+//! previous hash of the first record is 32 zero bytes.
+//!
+//! Each call to [`io::Write::write`] produces one record. When used through
+//! [`MakeWriter`] with `tracing_subscriber::fmt`, each formatted event is
+//! written with a single call, so one event corresponds to one record.
+//!
+//! # Examples
+//!
+//! Writing an audit trail:
+//!
+//! ```rust
+//! # fn docs() -> std::io::Result<()> {
+//! let file = std::fs::File::create("/var/log/audit.log")?;
+//! let appender = tracing_appender::audit::AuditAppender::new(file);
+//! tracing_subscriber::fmt()
+//!     .with_writer(appender)
+//!     .init();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Verifying it later:
+//!
+//! ```rust
+//! # fn docs() -> Result<(), tracing_appender::audit::VerifyError> {
+//! let file = std::fs::File::open("/var/log/audit.log")?;
+//! let summary = tracing_appender::audit::verify(file)?;
+//! println!("verified {} records", summary.records);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`RollingFileAppender`]: crate::rolling::RollingFileAppender
+//! [`MakeWriter`]: tracing_subscriber::fmt::MakeWriter
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+use std::io::{Read, Write};
+use std::sync::{Mutex, PoisonError};
+use thiserror::Error;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// A record containing a log line.
+const KIND_LINE: u8 = 0x01;
+/// A record containing a signature over the chain head.
+const KIND_SIGNATURE: u8 = 0x02;
+/// The chain value hashed into the first record.
+const GENESIS: [u8; 32] = [0; 32];
+
+/// A writer that wraps each written line in a hash-chained record.
+///
+/// See the [module documentation][self] for the record format and an overview
+/// of the guarantees this provides. This type implements [`MakeWriter`], so
+/// it can be passed to [`tracing_subscriber::fmt`][fmt] like any other
+/// writer; writes from multiple threads are serialized internally so that the
+/// chain stays consistent.
+///
+/// [`MakeWriter`]: tracing_subscriber::fmt::MakeWriter
+/// [fmt]: mod@tracing_subscriber::fmt
+pub struct AuditAppender<W> {
+    state: Mutex<AuditState<W>>,
+}
+
+struct AuditState<W> {
+    writer: W,
+    chain: [u8; 32],
+    signer: Option<Signer>,
+    sign_every: usize,
+    lines_since_signature: usize,
+}
+
+type Signer = Box<dyn FnMut(&[u8; 32]) -> Vec<u8> + Send>;
+
+/// A summary of a successfully verified audit log.
+///
+/// Returned by [`verify`] and [`verify_with`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct AuditSummary {
+    /// The total number of records in the log, including signature records.
+    pub records: u64,
+    /// The number of signature records in the log.
+    pub signatures: u64,
+    /// The hash of the final record.
+    ///
+    /// This matches [`AuditAppender::chain_head`] at the time the last record
+    /// was written, and can be anchored externally (printed, signed, or
+    /// stored elsewhere) to detect truncation of the whole file.
+    pub head: [u8; 32],
+}
+
+/// Errors returned by [`verify`] and [`verify_with`].
+#[derive(Debug, Error)]
+pub enum VerifyError {
+    /// An IO error occurred while reading the log.
+    #[error("error reading the audit log: {0}")]
+    Io(#[from] io::Error),
+
+    /// The log ends in the middle of a record.
+    #[error("audit log is truncated in the middle of record {record}")]
+    Truncated {
+        /// The index of the incomplete record.
+        record: u64,
+    },
+
+    /// A record's hash does not match the chain.
+    ///
+    /// The record, or some record before it, has been modified, reordered,
+    /// or removed.
+    #[error("record {record} does not match the hash chain")]
+    ChainMismatch {
+        /// The index of the first record that fails to verify.
+        record: u64,
+    },
+
+    /// A signature record was rejected by the verification callback.
+    #[error("signature record {record} failed verification")]
+    BadSignature {
+        /// The index of the rejected signature record.
+        record: u64,
+    },
+
+    /// A record has a kind byte this version does not understand.
+    #[error("record {record} has unknown kind {kind:#04x}")]
+    UnknownKind {
+        /// The index of the unrecognized record.
+        record: u64,
+        /// The unrecognized kind byte.
+        kind: u8,
+    },
+}
+
+// === impl AuditAppender ===
+
+impl<W: Write> AuditAppender<W> {
+    /// Returns a new `AuditAppender` wrapping the provided `writer`.
+    ///
+    /// The returned appender chains records but embeds no signatures; use
+    /// [`with_signer`][Self::with_signer] to add them.
+    pub fn new(writer: W) -> Self {
+        Self {
+            state: Mutex::new(AuditState {
+                writer,
+                chain: GENESIS,
+                signer: None,
+                sign_every: 0,
+                lines_since_signature: 0,
+            }),
+        }
+    }
+
+    /// Returns a new `AuditAppender` that embeds a signature record after
+    /// every `every` log lines.
+    ///
+    /// The `signer` callback is invoked with the current chain head and must
+    /// return the signature bytes to embed; these are checked during
+    /// [`verify_with`]. An `every` of zero is treated as one.
+    pub fn with_signer<S>(writer: W, every: usize, signer: S) -> Self
+    where
+        S: FnMut(&[u8; 32]) -> Vec<u8> + Send + 'static,
+    {
+        Self {
+            state: Mutex::new(AuditState {
+                writer,
+                chain: GENESIS,
+                signer: Some(Box::new(signer)),
+                sign_every: every.max(1),
+                lines_since_signature: 0,
+            }),
+        }
+    }
+
+    /// Returns the hash of the most recently written record.
+    ///
+    /// Anchoring this value externally (for example, logging it elsewhere at
+    /// shutdown) allows truncation of the whole file to be detected by
+    /// comparing it against [`AuditSummary::head`].
+    pub fn chain_head(&self) -> [u8; 32] {
+        self.lock().chain
+    }
+
+    /// Unwraps the appender, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .writer
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, AuditState<W>> {
+        // ignore poisoning, like the writers in `crate::sync`: a panic
+        // elsewhere should not turn off audit logging.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<W: Write> io::Write for AuditAppender<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::Write::write(&mut &*self, buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::Write::flush(&mut &*self)
+    }
+}
+
+impl<W: Write> io::Write for &AuditAppender<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut state = self.lock();
+        state.write_record(KIND_LINE, buf)?;
+        state.lines_since_signature += 1;
+        if let Some(mut signer) = state.signer.take() {
+            if state.lines_since_signature >= state.sign_every {
+                let signature = signer(&state.chain);
+                state.write_record(KIND_SIGNATURE, &signature)?;
+                state.lines_since_signature = 0;
+            }
+            state.signer = Some(signer);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().writer.flush()
+    }
+}
+
+impl<'a, W: Write + 'a> MakeWriter<'a> for AuditAppender<W> {
+    type Writer = &'a AuditAppender<W>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self
+    }
+}
+
+impl<W> fmt::Debug for AuditAppender<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditAppender").finish()
+    }
+}
+
+// === impl AuditState ===
+
+impl<W: Write> AuditState<W> {
+    /// Writes a single record and advances the hash chain.
+    fn write_record(&mut self, kind: u8, payload: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(payload.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record too large"))?;
+        let hash = record_hash(&self.chain, kind, len, payload);
+        self.writer.write_all(&[kind])?;
+        self.writer.write_all(&len.to_be_bytes())?;
+        self.writer.write_all(payload)?;
+        self.writer.write_all(&hash)?;
+        self.chain = hash;
+        Ok(())
+    }
+}
+
+/// Computes the chained hash of a record.
+fn record_hash(chain: &[u8; 32], kind: u8, len: u32, payload: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(chain);
+    hasher.update(&[kind]);
+    hasher.update(&len.to_be_bytes());
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+// === verification ===
+
+/// Verifies the hash chain of an audit log, without checking signatures.
+///
+/// Returns a summary of the log if every record matches the chain, and a
+/// [`VerifyError`] describing the first problem otherwise. Signature records
+/// are chained like any other record, but their contents are not checked;
+/// use [`verify_with`] for that.
+pub fn verify<R: Read>(reader: R) -> Result<AuditSummary, VerifyError> {
+    verify_with(reader, |_, _| true)
+}
+
+/// Verifies the hash chain of an audit log, checking each signature record
+/// with the provided callback.
+///
+/// The callback receives the chain head that was signed — the hash of the
+/// record preceding the signature — and the signature bytes, and returns
+/// whether the signature is valid. This mirrors what the signer callback
+/// passed to [`AuditAppender::with_signer`] saw when the log was written.
+pub fn verify_with<R, F>(
+    mut reader: R,
+    mut verify_signature: F,
+) -> Result<AuditSummary, VerifyError>
+where
+    R: Read,
+    F: FnMut(&[u8; 32], &[u8]) -> bool,
+{
+    let mut chain = GENESIS;
+    let mut records = 0u64;
+    let mut signatures = 0u64;
+    loop {
+        let mut kind = [0u8; 1];
+        match reader.read(&mut kind) {
+            // a clean end of the log.
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(ref error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        }
+        let mut len_bytes = [0u8; 4];
+        read_field(&mut reader, &mut len_bytes, records)?;
+        let len = u32::from_be_bytes(len_bytes);
+        let mut payload = vec![0u8; len as usize];
+        read_field(&mut reader, &mut payload, records)?;
+        let mut hash = [0u8; 32];
+        read_field(&mut reader, &mut hash, records)?;
+
+        if record_hash(&chain, kind[0], len, &payload) != hash {
+            return Err(VerifyError::ChainMismatch { record: records });
+        }
+        match kind[0] {
+            KIND_LINE => {}
+            KIND_SIGNATURE => {
+                if !verify_signature(&chain, &payload) {
+                    return Err(VerifyError::BadSignature { record: records });
+                }
+                signatures += 1;
+            }
+            kind => {
+                return Err(VerifyError::UnknownKind {
+                    record: records,
+                    kind,
+                })
+            }
+        }
+        chain = hash;
+        records += 1;
+    }
+    Ok(AuditSummary {
+        records,
+        signatures,
+        head: chain,
+    })
+}
+
+/// Reads part of a record, mapping an early end of the log to
+/// [`VerifyError::Truncated`].
+fn read_field<R: Read>(reader: &mut R, buf: &mut [u8], record: u64) -> Result<(), VerifyError> {
+    reader.read_exact(buf).map_err(|error| {
+        if error.kind() == io::ErrorKind::UnexpectedEof {
+            VerifyError::Truncated { record }
+        } else {
+            error.into()
+        }
+    })
+}
+
+// === SHA-256 ===
+//
+// A minimal SHA-256 (FIPS 180-4) implementation. Audit logs only need a
+// collision-resistant hash, and implementing the ~100 lines here avoids
+// taking on a cryptography dependency for the whole crate.
+
+#[rustfmt::skip]
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+struct Sha256 {
+    state: [u32; 8],
+    buf: [u8; 64],
+    buf_len: usize,
+    len: u64,
+}
+
+impl Sha256 {
+    fn new() -> Self {
+        Self {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            buf: [0; 64],
+            buf_len: 0,
+            len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.len = self.len.wrapping_add(data.len() as u64);
+        if self.buf_len > 0 {
+            let take = (64 - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+            self.buf_len += take;
+            data = &data[take..];
+            if self.buf_len == 64 {
+                let block = self.buf;
+                self.compress(&block);
+                self.buf_len = 0;
+            }
+        }
+        while data.len() >= 64 {
+            let mut block = [0u8; 64];
+            block.copy_from_slice(&data[..64]);
+            self.compress(&block);
+            data = &data[64..];
+        }
+        if !data.is_empty() {
+            self.buf[..data.len()].copy_from_slice(data);
+            self.buf_len = data.len();
+        }
+    }
+
+    fn finalize(mut self) -> [u8; 32] {
+        let bit_len = self.len.wrapping_mul(8);
+        self.update(&[0x80]);
+        while self.buf_len != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bit_len.to_be_bytes());
+        let mut out = [0; 32];
+        for (chunk, word) in out.chunks_exact_mut(4).zip(&self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+
+    fn compress(&mut self, block: &[u8; 64]) {
+        let mut w = [0u32; 64];
+        for (word, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        for t in 16..64 {
+            let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+            let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+            w[t] = w[t - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[t - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for t in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[t])
+                .wrapping_add(w[t]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+        for (word, value) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn sha256_matches_known_vectors() {
+        assert_eq!(
+            hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // two blocks, exercising buffering in `update`.
+        assert_eq!(
+            hex(&sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn verify_round_trip() {
+        let mut appender = AuditAppender::new(Vec::new());
+        appender.write_all(b"one\n").unwrap();
+        appender.write_all(b"two\n").unwrap();
+        appender.write_all(b"three\n").unwrap();
+
+        let head = appender.chain_head();
+        let log = appender.into_inner();
+        let summary = verify(&log[..]).expect("chain should verify");
+        assert_eq!(summary.records, 3);
+        assert_eq!(summary.signatures, 0);
+        assert_eq!(summary.head, head);
+    }
+
+    #[test]
+    fn tampering_breaks_the_chain() {
+        let mut appender = AuditAppender::new(Vec::new());
+        appender.write_all(b"one\n").unwrap();
+        appender.write_all(b"two\n").unwrap();
+        let mut log = appender.into_inner();
+
+        // flip a byte inside the second record's payload.
+        let second_record = 1 + 4 + 4 + 32;
+        log[second_record + 5] ^= 0x01;
+
+        match verify(&log[..]) {
+            Err(VerifyError::ChainMismatch { record: 1 }) => {}
+            other => panic!("expected a chain mismatch on record 1, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn truncation_is_detected() {
+        let mut appender = AuditAppender::new(Vec::new());
+        appender.write_all(b"one\n").unwrap();
+        appender.write_all(b"two\n").unwrap();
+        let log = appender.into_inner();
+
+        match verify(&log[..log.len() - 10]) {
+            Err(VerifyError::Truncated { record: 1 }) => {}
+            other => panic!("expected truncation of record 1, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn signatures_are_embedded_and_checked() {
+        // a toy "signature": the chain head with every byte inverted. A real
+        // application would use an actual signature scheme here.
+        let mut appender = AuditAppender::with_signer(Vec::new(), 2, |head| {
+            head.iter().map(|byte| !byte).collect()
+        });
+        for line in &["one\n", "two\n", "three\n", "four\n", "five\n"] {
+            appender.write_all(line.as_bytes()).unwrap();
+        }
+        let log = appender.into_inner();
+
+        // five lines with a signature after every second line.
+        let summary = verify_with(&log[..], |head, signature| {
+            signature
+                .iter()
+                .zip(head)
+                .all(|(byte, head_byte)| *byte == !head_byte)
+        })
+        .expect("chain and signatures should verify");
+        assert_eq!(summary.records, 7);
+        assert_eq!(summary.signatures, 2);
+
+        match verify_with(&log[..], |_, _| false) {
+            Err(VerifyError::BadSignature { record: 2 }) => {}
+            other => panic!("expected a bad signature on record 2, got {:?}", other),
+        }
+    }
+}
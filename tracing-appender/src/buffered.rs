@@ -0,0 +1,369 @@
+//! A writer that batches output to amortize syscalls.
+//!
+//! [`BufferedWriter`] wraps any [`io::Write`] implementation and accumulates
+//! formatted output in a memory buffer, writing it to the underlying writer
+//! in a single call once the buffer holds a configurable number of bytes or
+//! its oldest contents reach a configurable age. For services emitting many
+//! events per second, this replaces one syscall per event with one per
+//! batch.
+//!
+//! Unlike [`std::io::BufWriter`], this type implements [`MakeWriter`], can be
+//! shared between threads, and never delays `ERROR`-level events: when an
+//! event with the `ERROR` level is written, any buffered output is flushed
+//! first and the event is written through immediately, so the most important
+//! diagnostics are on disk even if the program dies before the next batch.
+//!
+//! There is no background thread; the age threshold is checked when events
+//! are written. If the application stops logging entirely, the final batch
+//! remains buffered until [`flush`](BufferedWriter::flush) is called or the
+//! writer is dropped, both of which write it out.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! # fn docs() {
+//! let appender = tracing_appender::rolling::hourly("/some/directory", "prefix.log");
+//! let buffered = tracing_appender::buffered::BufferedWriterBuilder::default()
+//!     .max_buffer_bytes(16 * 1024)
+//!     .max_buffer_age(Duration::from_millis(500))
+//!     .finish(appender);
+//! tracing_subscriber::fmt()
+//!     .with_writer(buffered)
+//!     .init();
+//! # }
+//! ```
+//!
+//! [`MakeWriter`]: tracing_subscriber::fmt::MakeWriter
+use std::io;
+use std::io::Write;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use tracing_core::{Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// The default number of bytes buffered before a batch is written out.
+pub const DEFAULT_MAX_BUFFER_BYTES: usize = 8 * 1024;
+
+/// The default age the oldest buffered bytes may reach before a batch is
+/// written out.
+pub const DEFAULT_MAX_BUFFER_AGE: Duration = Duration::from_millis(100);
+
+/// A writer that batches output in memory before writing it out.
+///
+/// See the [module documentation][self] for details. This type implements
+/// [`MakeWriter`], so it can be passed to [`tracing_subscriber::fmt`][fmt]
+/// like any other writer.
+///
+/// [`MakeWriter`]: tracing_subscriber::fmt::MakeWriter
+/// [fmt]: mod@tracing_subscriber::fmt
+#[derive(Debug)]
+pub struct BufferedWriter<W: Write> {
+    state: Mutex<BufferState<W>>,
+    max_bytes: usize,
+    max_age: Duration,
+}
+
+/// A builder for [`BufferedWriter`].
+#[derive(Debug, Clone)]
+pub struct BufferedWriterBuilder {
+    max_bytes: usize,
+    max_age: Duration,
+}
+
+/// A [writer] checked out from a [`BufferedWriter`].
+///
+/// This is the type returned by the [`MakeWriter`] implementation. Writers
+/// made for `ERROR`-level events bypass the buffer as described in the
+/// [module documentation][self].
+///
+/// [writer]: std::io::Write
+/// [`MakeWriter`]: tracing_subscriber::fmt::MakeWriter
+#[derive(Debug)]
+pub struct Writer<'a, W: Write> {
+    buffered: &'a BufferedWriter<W>,
+    bypass: bool,
+}
+
+#[derive(Debug)]
+struct BufferState<W> {
+    writer: W,
+    buf: Vec<u8>,
+    /// When the oldest byte currently in `buf` was written; `None` when the
+    /// buffer is empty.
+    oldest: Option<Instant>,
+}
+
+// === impl BufferedWriter ===
+
+impl<W: Write> BufferedWriter<W> {
+    /// Returns a new `BufferedWriter` wrapping the provided `writer`, with
+    /// the default thresholds.
+    ///
+    /// The defaults are [`DEFAULT_MAX_BUFFER_BYTES`] and
+    /// [`DEFAULT_MAX_BUFFER_AGE`]; other values can be configured with the
+    /// [builder][BufferedWriterBuilder].
+    pub fn new(writer: W) -> Self {
+        BufferedWriterBuilder::default().finish(writer)
+    }
+
+    /// Writes any buffered output to the underlying writer and flushes it.
+    ///
+    /// This can be called at any time to bound how long output sits in the
+    /// buffer — for example, from a periodic task, or before a checkpoint
+    /// the application wants persisted.
+    pub fn flush(&self) -> io::Result<()> {
+        let mut state = self.lock();
+        state.write_batch()?;
+        state.writer.flush()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BufferState<W>> {
+        // ignore poisoning: a panic elsewhere should not turn off logging.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<'a, W: Write + 'a> MakeWriter<'a> for BufferedWriter<W> {
+    type Writer = Writer<'a, W>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        Writer {
+            buffered: self,
+            bypass: false,
+        }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        Writer {
+            buffered: self,
+            bypass: meta.level() == &Level::ERROR,
+        }
+    }
+}
+
+// === impl BufferedWriterBuilder ===
+
+impl BufferedWriterBuilder {
+    /// Sets the number of buffered bytes at which a batch is written out.
+    ///
+    /// A single write larger than this limit is written out as a batch of
+    /// its own. By default, this is [`DEFAULT_MAX_BUFFER_BYTES`].
+    pub fn max_buffer_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes.max(1);
+        self
+    }
+
+    /// Sets the age the oldest buffered bytes may reach before a batch is
+    /// written out.
+    ///
+    /// The age is checked when events are written; see the
+    /// [module documentation][self] for the idle-application caveat. By
+    /// default, this is [`DEFAULT_MAX_BUFFER_AGE`].
+    pub fn max_buffer_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Completes the builder, returning a [`BufferedWriter`] wrapping the
+    /// provided `writer`.
+    pub fn finish<W: Write>(self, writer: W) -> BufferedWriter<W> {
+        BufferedWriter {
+            state: Mutex::new(BufferState {
+                writer,
+                // don't preallocate absurdly for a large (or unlimited) byte
+                // threshold; the buffer grows as needed.
+                buf: Vec::with_capacity(self.max_bytes.min(DEFAULT_MAX_BUFFER_BYTES)),
+                oldest: None,
+            }),
+            max_bytes: self.max_bytes,
+            max_age: self.max_age,
+        }
+    }
+}
+
+impl Default for BufferedWriterBuilder {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_BUFFER_BYTES,
+            max_age: DEFAULT_MAX_BUFFER_AGE,
+        }
+    }
+}
+
+// === impl Writer ===
+
+impl<W: Write> io::Write for Writer<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.buffered.lock();
+        if self.bypass {
+            // write out the batch first so that output stays in order, then
+            // put the event straight on disk.
+            state.write_batch()?;
+            state.writer.write_all(buf)?;
+            state.writer.flush()?;
+            return Ok(buf.len());
+        }
+        if state.buf.is_empty() {
+            state.oldest = Some(Instant::now());
+        }
+        state.buf.extend_from_slice(buf);
+        let over_size = state.buf.len() >= self.buffered.max_bytes;
+        let over_age = state
+            .oldest
+            .map(|oldest| oldest.elapsed() >= self.buffered.max_age)
+            .unwrap_or(false);
+        if over_size || over_age {
+            state.write_batch()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut state = self.buffered.lock();
+        state.write_batch()?;
+        state.writer.flush()
+    }
+}
+
+// === impl BufferState ===
+
+impl<W: Write> BufferState<W> {
+    /// Writes the buffered batch to the underlying writer, if there is one.
+    fn write_batch(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        self.writer.write_all(&self.buf)?;
+        self.buf.clear();
+        self.oldest = None;
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for BufferedWriter<W> {
+    fn drop(&mut self) {
+        // write out the final batch; there is nowhere to report errors here.
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct MockWriter {
+        data: Arc<Mutex<Vec<u8>>>,
+        writes: Arc<AtomicUsize>,
+    }
+
+    impl MockWriter {
+        fn contents(&self) -> String {
+            String::from_utf8(self.data.lock().unwrap().clone()).unwrap()
+        }
+
+        fn writes(&self) -> usize {
+            self.writes.load(Ordering::Relaxed)
+        }
+    }
+
+    impl io::Write for MockWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.data.lock().unwrap().extend_from_slice(buf);
+            self.writes.fetch_add(1, Ordering::Relaxed);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn batches_until_byte_threshold() {
+        let mock = MockWriter::default();
+        let buffered = BufferedWriterBuilder::default()
+            .max_buffer_bytes(10)
+            .max_buffer_age(Duration::from_secs(3600))
+            .finish(mock.clone());
+
+        let mut writer = buffered.make_writer();
+        writer.write_all(b"one\n").unwrap();
+        writer.write_all(b"two\n").unwrap();
+        assert_eq!(mock.writes(), 0, "small writes should be buffered");
+
+        // this write pushes the buffer over 10 bytes, so the whole batch is
+        // written out in a single call.
+        writer.write_all(b"three\n").unwrap();
+        assert_eq!(mock.writes(), 1);
+        assert_eq!(mock.contents(), "one\ntwo\nthree\n");
+    }
+
+    #[test]
+    fn batches_until_age_threshold() {
+        let mock = MockWriter::default();
+        let buffered = BufferedWriterBuilder::default()
+            .max_buffer_bytes(usize::MAX)
+            .max_buffer_age(Duration::from_millis(0))
+            .finish(mock.clone());
+
+        // with a zero age threshold, every write is already over age.
+        let mut writer = buffered.make_writer();
+        writer.write_all(b"one\n").unwrap();
+        assert_eq!(mock.writes(), 1);
+        writer.write_all(b"two\n").unwrap();
+        assert_eq!(mock.writes(), 2);
+        assert_eq!(mock.contents(), "one\ntwo\n");
+    }
+
+    #[test]
+    fn explicit_flush_writes_pending_output() {
+        let mock = MockWriter::default();
+        let buffered = BufferedWriter::new(mock.clone());
+
+        buffered.make_writer().write_all(b"one\n").unwrap();
+        assert_eq!(mock.writes(), 0);
+
+        buffered.flush().unwrap();
+        assert_eq!(mock.contents(), "one\n");
+    }
+
+    #[test]
+    fn drop_writes_pending_output() {
+        let mock = MockWriter::default();
+        let buffered = BufferedWriter::new(mock.clone());
+
+        buffered.make_writer().write_all(b"one\n").unwrap();
+        drop(buffered);
+        assert_eq!(mock.contents(), "one\n");
+    }
+
+    #[test]
+    fn error_events_bypass_the_buffer() {
+        let mock = MockWriter::default();
+        let buffered = BufferedWriter::new(mock.clone());
+
+        let collector = tracing_subscriber::fmt()
+            .with_writer(buffered)
+            .with_ansi(false)
+            .finish();
+        tracing::collect::with_default(collector, || {
+            tracing::info!("buffered");
+            assert_eq!(mock.writes(), 0, "INFO events should be buffered");
+
+            tracing::error!("urgent");
+            // the buffered INFO event is written first, then the ERROR event
+            // goes straight through.
+            assert_eq!(mock.writes(), 2);
+        });
+
+        let contents = mock.contents();
+        let buffered_at = contents.find("buffered").expect("INFO event written");
+        let urgent_at = contents.find("urgent").expect("ERROR event written");
+        assert!(buffered_at < urgent_at, "output should stay in order");
+    }
+}
@@ -39,6 +39,10 @@
 //! [`tracing_appender::rolling::never(/*...*/)`](rolling::never) or
 //! [`Rotation::NEVER`](rolling::Rotation::NEVER).
 //!
+//! Log files may also be rotated once they reach a maximum size, using
+//! [`Rotation::size`](rolling::Rotation::size) or
+//! [`Rotation::with_max_size`](rolling::Rotation::with_max_size).
+//!
 //! The following example creates an hourly rotating file appender that writes to
 //! `/some/directory/prefix.log.YYYY-MM-DD-HH`:
 //!
@@ -55,6 +59,20 @@
 //! See the [`rolling` module][rolling]'s documentation for more detail on how to use this file
 //! appender.
 //!
+//! To write events to *different* files selected by a key derived from each
+//! event — its target, or the value of a designated field — see the
+//! [`routing` module][mod@routing]'s [`RoutingFileAppender`](routing::RoutingFileAppender).
+//!
+//! For high-event-rate services, the [`buffered` module][mod@buffered]'s
+//! [`BufferedWriter`](buffered::BufferedWriter) batches output in memory and
+//! writes it out by size or age thresholds, amortizing syscalls without
+//! delaying `ERROR`-level events.
+//!
+//! For audit logging, the [`audit` module][mod@audit]'s
+//! [`AuditAppender`](audit::AuditAppender) wraps any writer in tamper-evident,
+//! hash-chained records that can be checked later with
+//! [`audit::verify`](audit::verify).
+//!
 //! ## Non-Blocking Writer
 //!
 //! The example below demonstrates the construction of a `non_blocking` writer with `std::io::stdout()`,
@@ -102,6 +120,12 @@
 //!
 //! The [`non_blocking` module][non_blocking]'s documentation provides more detail on how to use `non_blocking`.
 //!
+//! Applications that already run a `tokio` runtime can instead enable the
+//! `tokio` feature flag and use the `tracing_appender::tokio` module, which
+//! forwards log lines to a worker _task_ writing to any
+//! [`tokio::io::AsyncWrite`](https://docs.rs/tokio/1/tokio/io/trait.AsyncWrite.html)
+//! sink — including TCP and Unix sockets — without spawning an extra thread.
+//!
 //! [write]: std::io::Write
 //! [non_blocking]: mod@non_blocking
 //! [guard]: non_blocking::WorkerGuard
@@ -166,10 +190,19 @@ use crate::non_blocking::{NonBlocking, WorkerGuard};
 
 use std::io::Write;
 
+pub mod audit;
+
+pub mod buffered;
+
 pub mod non_blocking;
 
 pub mod rolling;
 
+pub mod routing;
+
+#[cfg(feature = "tokio")]
+pub mod tokio;
+
 mod worker;
 
 pub(crate) mod sync;
@@ -196,5 +229,6 @@ pub fn non_blocking<T: Write + Send + 'static>(writer: T) -> (NonBlocking, Worke
 #[derive(Debug)]
 pub(crate) enum Msg {
     Line(Vec<u8>),
+    Flush(crossbeam_channel::Sender<()>),
     Shutdown,
 }
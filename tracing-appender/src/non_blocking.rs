@@ -48,14 +48,16 @@
 //! ```
 use crate::worker::Worker;
 use crate::Msg;
-use crossbeam_channel::{bounded, SendTimeoutError, Sender};
+use crossbeam_channel::{
+    bounded, Receiver, RecvTimeoutError, SendTimeoutError, Sender, TrySendError,
+};
 use std::io;
 use std::io::Write;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::thread::JoinHandle;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing_subscriber::fmt::MakeWriter;
 
 /// The default maximum number of buffered log lines.
@@ -106,6 +108,33 @@ pub struct WorkerGuard {
     handle: Option<JoinHandle<()>>,
     sender: Sender<Msg>,
     shutdown: Sender<()>,
+    error_counter: ErrorCounter,
+}
+
+/// A policy determining what happens when a line is written while the line
+/// buffer is full.
+///
+/// This is configured with [`NonBlockingBuilder::policy`]. The default is
+/// [`Policy::DropNewest`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Policy {
+    /// Block the writing thread until buffer capacity is available.
+    ///
+    /// This never loses log lines, at the cost of exerting backpressure on
+    /// the threads producing them.
+    Block,
+    /// Drop the line being written, keeping the lines already buffered.
+    ///
+    /// This is the default, and preserves the oldest events. Dropped lines
+    /// are counted by [`ErrorCounter::dropped_newest`].
+    DropNewest,
+    /// Drop the oldest buffered line to make room for the line being
+    /// written.
+    ///
+    /// This preserves the most recent events, which are often the ones
+    /// relevant when diagnosing a failure. Dropped lines are counted by
+    /// [`ErrorCounter::dropped_oldest`].
+    DropOldest,
 }
 
 /// A non-blocking writer.
@@ -126,17 +155,27 @@ pub struct WorkerGuard {
 pub struct NonBlocking {
     error_counter: ErrorCounter,
     channel: Sender<Msg>,
-    is_lossy: bool,
+    policy: Policy,
+    /// A receiver for discarding buffered lines; only present when the
+    /// policy is [`Policy::DropOldest`].
+    receiver: Option<Receiver<Msg>>,
 }
 
-/// Tracks the number of times a log line was dropped by the background thread.
+/// Tracks the number of log lines dropped by a [`NonBlocking`] writer.
 ///
-/// If the non-blocking writer is not configured in [lossy mode], the error
-/// count should always be 0.
-///
-/// [lossy mode]: NonBlockingBuilder::lossy
+/// Depending on the configured [`Policy`], lines may be dropped from the
+/// front or the back of the queue; each kind is counted separately. If the
+/// policy is [`Policy::Block`], both counts should always be 0.
 #[derive(Clone, Debug)]
-pub struct ErrorCounter(Arc<AtomicUsize>);
+pub struct ErrorCounter(Arc<Counters>);
+
+#[derive(Debug, Default)]
+struct Counters {
+    /// Lines dropped because they were written while the buffer was full.
+    dropped_newest: AtomicUsize,
+    /// Buffered lines discarded to make room for newer ones.
+    dropped_oldest: AtomicUsize,
+}
 
 impl NonBlocking {
     /// Returns a new `NonBlocking` writer wrapping the provided `writer`.
@@ -153,25 +192,42 @@ impl NonBlocking {
     fn create<T: Write + Send + 'static>(
         writer: T,
         buffered_lines_limit: usize,
-        is_lossy: bool,
+        policy: Policy,
         thread_name: String,
+        report_interval: Option<Duration>,
     ) -> (NonBlocking, WorkerGuard) {
         let (sender, receiver) = bounded(buffered_lines_limit);
 
         let (shutdown_sender, shutdown_receiver) = bounded(0);
 
-        let worker = Worker::new(receiver, writer, shutdown_receiver);
+        // dropping the oldest lines requires a second receiver, so that
+        // writers can discard buffered lines to make room for new ones.
+        let drop_oldest_receiver = match policy {
+            Policy::DropOldest => Some(receiver.clone()),
+            _ => None,
+        };
+
+        let error_counter = ErrorCounter::new();
+        let worker = Worker::new(
+            receiver,
+            writer,
+            shutdown_receiver,
+            error_counter.clone(),
+            report_interval,
+        );
         let worker_guard = WorkerGuard::new(
             worker.worker_thread(thread_name),
             sender.clone(),
             shutdown_sender,
+            error_counter.clone(),
         );
 
         (
             Self {
                 channel: sender,
-                error_counter: ErrorCounter(Arc::new(AtomicUsize::new(0))),
-                is_lossy,
+                error_counter,
+                policy,
+                receiver: drop_oldest_receiver,
             },
             worker_guard,
         )
@@ -182,6 +238,17 @@ impl NonBlocking {
     pub fn error_counter(&self) -> ErrorCounter {
         self.error_counter.clone()
     }
+
+    /// Blocks until all log lines enqueued before this call have been written
+    /// and flushed to the underlying writer, or until `timeout` elapses.
+    ///
+    /// This is useful at shutdown, when the program must not exit before its
+    /// remaining output has been written. If the timeout elapses first, an
+    /// error of kind [`io::ErrorKind::TimedOut`] is returned and the worker
+    /// thread continues writing in the background.
+    pub fn flush_timeout(&self, timeout: Duration) -> io::Result<()> {
+        flush_timeout(&self.channel, timeout)
+    }
 }
 
 /// A builder for [`NonBlocking`][non-blocking].
@@ -190,8 +257,9 @@ impl NonBlocking {
 #[derive(Debug)]
 pub struct NonBlockingBuilder {
     buffered_lines_limit: usize,
-    is_lossy: bool,
+    policy: Policy,
     thread_name: String,
+    report_interval: Option<Duration>,
 }
 
 impl NonBlockingBuilder {
@@ -206,9 +274,27 @@ impl NonBlockingBuilder {
     /// If set to `true`, logs will be dropped when the buffered limit is reached. If `false`, backpressure
     /// will be exerted on senders, blocking them until the buffer has capacity again.
     ///
+    /// This is a shorthand for [`policy`] with [`Policy::DropNewest`]
+    /// (`true`) or [`Policy::Block`] (`false`).
+    ///
     /// By default, the built `NonBlocking` will be lossy.
+    ///
+    /// [`policy`]: Self::policy
     pub fn lossy(mut self, is_lossy: bool) -> NonBlockingBuilder {
-        self.is_lossy = is_lossy;
+        self.policy = if is_lossy {
+            Policy::DropNewest
+        } else {
+            Policy::Block
+        };
+        self
+    }
+
+    /// Sets the [`Policy`] applied when a line is written while the buffer
+    /// is full.
+    ///
+    /// By default, this is [`Policy::DropNewest`].
+    pub fn policy(mut self, policy: Policy) -> NonBlockingBuilder {
+        self.policy = policy;
         self
     }
 
@@ -220,13 +306,28 @@ impl NonBlockingBuilder {
         self
     }
 
+    /// Periodically reports dropped log lines as a meta-event.
+    ///
+    /// When enabled, the worker thread writes a line such as
+    /// `tracing-appender: 42 log lines were dropped` to the underlying writer
+    /// whenever lines have been dropped since the previous report, checking at
+    /// most once per `interval`. The exact count of dropped lines remains
+    /// queryable through [`NonBlocking::error_counter`].
+    ///
+    /// By default, no such report is emitted.
+    pub fn report_dropped_lines(mut self, interval: Duration) -> NonBlockingBuilder {
+        self.report_interval = Some(interval);
+        self
+    }
+
     /// Completes the builder, returning the configured `NonBlocking`.
     pub fn finish<T: Write + Send + 'static>(self, writer: T) -> (NonBlocking, WorkerGuard) {
         NonBlocking::create(
             writer,
             self.buffered_lines_limit,
-            self.is_lossy,
+            self.policy,
             self.thread_name,
+            self.report_interval,
         )
     }
 }
@@ -235,8 +336,9 @@ impl Default for NonBlockingBuilder {
     fn default() -> Self {
         NonBlockingBuilder {
             buffered_lines_limit: DEFAULT_BUFFERED_LINES_LIMIT,
-            is_lossy: true,
+            policy: Policy::DropNewest,
             thread_name: "tracing-appender".to_string(),
+            report_interval: None,
         }
     }
 }
@@ -244,15 +346,50 @@ impl Default for NonBlockingBuilder {
 impl std::io::Write for NonBlocking {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         let buf_size = buf.len();
-        if self.is_lossy {
-            if self.channel.try_send(Msg::Line(buf.to_vec())).is_err() {
-                self.error_counter.incr_saturating();
+        match self.policy {
+            Policy::Block => {
+                return match self.channel.send(Msg::Line(buf.to_vec())) {
+                    Ok(_) => Ok(buf_size),
+                    Err(_) => Err(io::Error::from(io::ErrorKind::Other)),
+                };
+            }
+            Policy::DropNewest => {
+                if self.channel.try_send(Msg::Line(buf.to_vec())).is_err() {
+                    self.error_counter.incr_dropped_newest();
+                }
+            }
+            Policy::DropOldest => {
+                let receiver = self
+                    .receiver
+                    .as_ref()
+                    .expect("a `DropOldest` writer always holds a receiver");
+                let mut msg = Msg::Line(buf.to_vec());
+                loop {
+                    match self.channel.try_send(msg) {
+                        Ok(()) => break,
+                        // the worker thread has terminated; there is nowhere
+                        // for this line to go.
+                        Err(TrySendError::Disconnected(_)) => break,
+                        Err(TrySendError::Full(returned)) => {
+                            msg = returned;
+                            // discard the oldest buffered line to make room,
+                            // then try again.
+                            match receiver.try_recv() {
+                                Ok(Msg::Line(_)) => self.error_counter.incr_dropped_oldest(),
+                                // never discard a flush or shutdown request;
+                                // re-enqueue it (blocking if necessary, which
+                                // should be rare since we just made room).
+                                Ok(other) => {
+                                    let _ = self.channel.send(other);
+                                }
+                                // the worker drained the queue in the
+                                // meantime; just retry the send.
+                                Err(_) => {}
+                            }
+                        }
+                    }
+                }
             }
-        } else {
-            return match self.channel.send(Msg::Line(buf.to_vec())) {
-                Ok(_) => Ok(buf_size),
-                Err(_) => Err(io::Error::from(io::ErrorKind::Other)),
-            };
         }
         Ok(buf_size)
     }
@@ -276,12 +413,69 @@ impl<'a> MakeWriter<'a> for NonBlocking {
 }
 
 impl WorkerGuard {
-    fn new(handle: JoinHandle<()>, sender: Sender<Msg>, shutdown: Sender<()>) -> Self {
+    fn new(
+        handle: JoinHandle<()>,
+        sender: Sender<Msg>,
+        shutdown: Sender<()>,
+        error_counter: ErrorCounter,
+    ) -> Self {
         WorkerGuard {
             handle: Some(handle),
             sender,
             shutdown,
+            error_counter,
+        }
+    }
+
+    /// Returns a counter for the number of log lines that have been dropped.
+    ///
+    /// This is equivalent to [`NonBlocking::error_counter`], for use when
+    /// only the guard is kept around.
+    pub fn error_counter(&self) -> ErrorCounter {
+        self.error_counter.clone()
+    }
+
+    /// Blocks until all log lines enqueued before this call have been written
+    /// and flushed to the underlying writer, or until `timeout` elapses.
+    ///
+    /// This is equivalent to [`NonBlocking::flush_timeout`], for use when only
+    /// the guard is kept around.
+    pub fn flush_timeout(&self, timeout: Duration) -> io::Result<()> {
+        flush_timeout(&self.sender, timeout)
+    }
+}
+
+/// Enqueues a flush request and waits for the worker thread to acknowledge
+/// it. Since the worker processes messages in order, the acknowledgement
+/// implies that all previously enqueued lines have been written.
+fn flush_timeout(sender: &Sender<Msg>, timeout: Duration) -> io::Result<()> {
+    let deadline = Instant::now() + timeout;
+    let (ack, acked) = bounded(1);
+    match sender.send_timeout(Msg::Flush(ack), timeout) {
+        Ok(()) => {}
+        Err(SendTimeoutError::Timeout(_)) => {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "timed out enqueueing the flush request",
+            ))
         }
+        Err(SendTimeoutError::Disconnected(_)) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "the logging worker thread has terminated",
+            ))
+        }
+    }
+    match acked.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(()) => Ok(()),
+        Err(RecvTimeoutError::Timeout) => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "timed out waiting for queued output to be written",
+        )),
+        Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(
+            io::ErrorKind::Other,
+            "the logging worker thread has terminated",
+        )),
     }
 }
 
@@ -324,36 +518,59 @@ impl Drop for WorkerGuard {
 // === impl ErrorCounter ===
 
 impl ErrorCounter {
-    /// Returns the number of log lines that have been dropped.
-    ///
-    /// If the non-blocking writer is not configured in [lossy mode], the error
-    /// count should always be 0.
+    pub(crate) fn new() -> Self {
+        Self(Arc::new(Counters::default()))
+    }
+
+    pub(crate) fn incr_dropped_newest(&self) {
+        incr_saturating(&self.0.dropped_newest);
+    }
+
+    pub(crate) fn incr_dropped_oldest(&self) {
+        incr_saturating(&self.0.dropped_oldest);
+    }
+
+    /// Returns the total number of log lines that have been dropped.
     ///
-    /// [lossy mode]: NonBlockingBuilder::lossy
+    /// If the non-blocking writer is configured with [`Policy::Block`], the
+    /// error count should always be 0.
     pub fn dropped_lines(&self) -> usize {
-        self.0.load(Ordering::Acquire)
+        self.dropped_newest().saturating_add(self.dropped_oldest())
     }
 
-    fn incr_saturating(&self) {
-        let mut curr = self.0.load(Ordering::Acquire);
-        // We don't need to enter the CAS loop if the current value is already
-        // `usize::MAX`.
-        if curr == usize::MAX {
-            return;
-        }
+    /// Returns the number of log lines dropped because they were written
+    /// while the buffer was full.
+    ///
+    /// Lines are only dropped this way under [`Policy::DropNewest`].
+    pub fn dropped_newest(&self) -> usize {
+        self.0.dropped_newest.load(Ordering::Acquire)
+    }
 
-        // This is implemented as a CAS loop rather than as a simple
-        // `fetch_add`, because we don't want to wrap on overflow. Instead, we
-        // need to ensure that saturating addition is performed.
-        loop {
-            let val = curr.saturating_add(1);
-            match self
-                .0
-                .compare_exchange(curr, val, Ordering::AcqRel, Ordering::Acquire)
-            {
-                Ok(_) => return,
-                Err(actual) => curr = actual,
-            }
+    /// Returns the number of buffered log lines discarded to make room for
+    /// newer ones.
+    ///
+    /// Lines are only dropped this way under [`Policy::DropOldest`].
+    pub fn dropped_oldest(&self) -> usize {
+        self.0.dropped_oldest.load(Ordering::Acquire)
+    }
+}
+
+fn incr_saturating(counter: &AtomicUsize) {
+    let mut curr = counter.load(Ordering::Acquire);
+    // We don't need to enter the CAS loop if the current value is already
+    // `usize::MAX`.
+    if curr == usize::MAX {
+        return;
+    }
+
+    // This is implemented as a CAS loop rather than as a simple
+    // `fetch_add`, because we don't want to wrap on overflow. Instead, we
+    // need to ensure that saturating addition is performed.
+    loop {
+        let val = curr.saturating_add(1);
+        match counter.compare_exchange(curr, val, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => return,
+            Err(actual) => curr = actual,
         }
     }
 }
@@ -472,6 +689,105 @@ mod test {
         assert_eq!(1, error_count.dropped_lines());
     }
 
+    #[test]
+    fn lossy_configures_policy() {
+        let builder = self::NonBlockingBuilder::default().lossy(true);
+        assert_eq!(builder.policy, Policy::DropNewest);
+        let builder = self::NonBlockingBuilder::default().lossy(false);
+        assert_eq!(builder.policy, Policy::Block);
+    }
+
+    #[test]
+    fn oldest_lines_dropped_when_policy_is_drop_oldest() {
+        let (mock_writer, rx) = MockWriter::new(0);
+
+        let (mut non_blocking, guard) = self::NonBlockingBuilder::default()
+            .policy(Policy::DropOldest)
+            .buffered_lines_limit(1)
+            .finish(mock_writer);
+
+        // The worker picks up the first line and blocks writing it to the
+        // zero-capacity mock writer...
+        write_non_blocking(&mut non_blocking, b"one");
+        // ...so the second line sits in the channel...
+        write_non_blocking(&mut non_blocking, b"two");
+        // ...and the third line displaces it.
+        write_non_blocking(&mut non_blocking, b"three");
+
+        let error_count = guard.error_counter();
+        assert_eq!(1, error_count.dropped_oldest());
+        assert_eq!(0, error_count.dropped_newest());
+        assert_eq!(1, error_count.dropped_lines());
+
+        assert_eq!(rx.recv().unwrap(), "one");
+        assert_eq!(rx.recv().unwrap(), "three");
+    }
+
+    #[test]
+    fn flush_timeout_flushes_queued_lines() {
+        let (mock_writer, rx) = MockWriter::new(1);
+
+        let (mut non_blocking, _guard) = self::NonBlockingBuilder::default().finish(mock_writer);
+
+        non_blocking.write_all(b"Hello").expect("Failed to write");
+        non_blocking
+            .flush_timeout(Duration::from_secs(5))
+            .expect("Failed to flush");
+
+        // Since the flush has been acknowledged, the line must already have
+        // been written.
+        assert_eq!(rx.try_recv().unwrap(), "Hello");
+    }
+
+    #[test]
+    fn flush_timeout_times_out_when_output_blocks() {
+        let (mock_writer, rx) = MockWriter::new(0);
+
+        let (mut non_blocking, _guard) = self::NonBlockingBuilder::default().finish(mock_writer);
+
+        non_blocking.write_all(b"Hello").expect("Failed to write");
+
+        // The mock writer accepts no lines until we read from `rx`, so the
+        // queued line cannot be written before the timeout expires.
+        let err = non_blocking
+            .flush_timeout(Duration::from_millis(100))
+            .expect_err("flush should time out");
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+        // Unblock the worker so that the guard can shut it down.
+        assert_eq!(rx.recv().unwrap(), "Hello");
+    }
+
+    #[test]
+    fn dropped_lines_are_reported() {
+        let (mock_writer, rx) = MockWriter::new(0);
+
+        let (mut non_blocking, _guard) = self::NonBlockingBuilder::default()
+            .lossy(true)
+            .buffered_lines_limit(1)
+            .report_dropped_lines(Duration::from_millis(0))
+            .finish(mock_writer);
+
+        // The worker picks up the first line and blocks writing it to the
+        // zero-capacity mock writer...
+        write_non_blocking(&mut non_blocking, b"one");
+        // ...so the second line sits in the channel, and the third is dropped.
+        write_non_blocking(&mut non_blocking, b"two");
+        write_non_blocking(&mut non_blocking, b"three");
+        assert_eq!(1, non_blocking.error_counter().dropped_lines());
+
+        assert_eq!(rx.recv().unwrap(), "one");
+        assert_eq!(rx.recv().unwrap(), "two");
+
+        // Once the queue is drained, the worker reports the dropped line.
+        let report = rx.recv().unwrap();
+        assert!(
+            report.contains("1 log lines were dropped"),
+            "unexpected report: {:?}",
+            report
+        );
+    }
+
     #[test]
     fn multi_threaded_writes() {
         let (mock_writer, rx) = MockWriter::new(DEFAULT_BUFFERED_LINES_LIMIT);
@@ -17,6 +17,21 @@
 //!   will be created daily
 //! - [`Rotation::never()`][never()]: This will result in log file located at `some_directory/log_file_name`
 //!
+//! Log files may additionally (or instead) be rolled over once they exceed a
+//! maximum size in bytes; see [`Rotation::size`] and
+//! [`Rotation::with_max_size`].
+//!
+//! When and how the log file is flushed to disk can be configured with
+//! [`Builder::flush_every`], [`Builder::flush_interval`], and
+//! [`Builder::sync_on_flush`].
+//!
+//! If several processes append to the same log files, rotation can be
+//! coordinated between them with [`Builder::multi_process`].
+//!
+//! When log files are instead rotated by an external tool such as
+//! `logrotate`, the appender can follow the rotation with
+//! [`RollingFileAppender::reopen`] or [`Builder::reopen_on_sighup`].
+//!
 //!
 //! # Examples
 //!
@@ -32,7 +47,7 @@ use std::{
     fs::{self, File, OpenOptions},
     io::{self, Write},
     path::{Path, PathBuf},
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
 };
 use time::{format_description, Date, Duration, OffsetDateTime, Time};
 
@@ -97,7 +112,10 @@ pub struct RollingFileAppender {
 /// [writer]: std::io::Write
 /// [`MakeWriter`]: tracing_subscriber::fmt::writer::MakeWriter
 #[derive(Debug)]
-pub struct RollingWriter<'a>(RwLockReadGuard<'a, File>);
+pub struct RollingWriter<'a> {
+    file: RwLockReadGuard<'a, File>,
+    inner: &'a Inner,
+}
 
 #[derive(Debug)]
 struct Inner {
@@ -107,7 +125,186 @@ struct Inner {
     date_format: Vec<format_description::FormatItem<'static>>,
     rotation: Rotation,
     next_date: AtomicUsize,
+    /// The number of bytes written to the current log file.
+    current_size: AtomicU64,
+    /// The index of the current log file within its time period; non-zero
+    /// when a size limit has forced additional files within one period.
+    current_index: AtomicUsize,
+    retention: Retention,
+    /// A custom naming template, replacing the default date-suffix scheme.
+    template: Option<Template>,
+    /// When the current log file is flushed (and optionally synced) to disk.
+    flush_policy: FlushPolicy,
+    /// The number of writes since the current log file was last flushed.
+    writes_since_flush: AtomicUsize,
+    /// When the last flush occurred, in milliseconds since `flush_epoch`.
+    last_flush_ms: AtomicU64,
+    /// The instant the appender was created; used to timestamp flushes.
+    flush_epoch: std::time::Instant,
+    /// Whether rotation is coordinated with other processes appending to
+    /// the same log files.
+    multi_process: bool,
+    /// Whether the file is reopened when the process receives `SIGHUP`.
+    reopen_on_sighup: bool,
+    /// The value of [`SIGHUP_GENERATION`] when the file was last (re)opened.
+    last_sighup: AtomicUsize,
+}
+
+/// Limits on how many rotated log files are kept on disk.
+///
+/// Retention is enforced whenever a new log file is created: the oldest
+/// files are deleted until every configured limit is satisfied.
+#[derive(Debug, Default, Clone)]
+struct Retention {
+    /// The maximum number of log files kept on disk.
     max_files: Option<usize>,
+    /// The maximum age of a log file, based on its creation timestamp.
+    max_age: Option<std::time::Duration>,
+    /// The maximum total size of all log files, in bytes.
+    max_total_size: Option<u64>,
+}
+
+impl Retention {
+    /// Returns whether any retention limit is configured.
+    fn is_limited(&self) -> bool {
+        self.max_files.is_some() || self.max_age.is_some() || self.max_total_size.is_some()
+    }
+}
+
+/// Controls when the log file is flushed (and optionally synced) to disk.
+#[derive(Debug, Default, Clone)]
+struct FlushPolicy {
+    /// Flush the file after every `n` writes.
+    every: Option<usize>,
+    /// Flush the file once this much time has passed since the last flush.
+    interval: Option<std::time::Duration>,
+    /// Whether to `fsync` the file as part of each flush.
+    sync: bool,
+}
+
+impl FlushPolicy {
+    /// Returns whether any flush threshold is configured.
+    fn is_scheduled(&self) -> bool {
+        self.every.is_some() || self.interval.is_some()
+    }
+}
+
+/// A parsed log file naming template.
+///
+/// See [`Builder::filename_template`] for the template syntax.
+#[derive(Debug, Clone)]
+struct Template {
+    segments: Vec<TemplateSegment>,
+}
+
+#[derive(Debug, Clone)]
+enum TemplateSegment {
+    /// Literal text, copied into the file name as-is.
+    Literal(String),
+    /// The configured filename prefix.
+    Prefix,
+    /// The configured filename suffix.
+    Suffix,
+    /// The index of the file within its time period.
+    Index,
+    /// The start of the time period the file covers, with an optional
+    /// format description.
+    Date(Option<format_description::OwnedFormatItem>),
+    /// The time at which the file was created, with an optional format
+    /// description.
+    Now(Option<format_description::OwnedFormatItem>),
+}
+
+// === impl Template ===
+
+impl Template {
+    fn parse(source: &str) -> Result<Self, InitError> {
+        let invalid = |message: String| {
+            InitError::ctx("invalid filename template")(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                message,
+            ))
+        };
+
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.chars();
+        while let Some(ch) = chars.next() {
+            if ch != '{' {
+                literal.push(ch);
+                continue;
+            }
+            if !literal.is_empty() {
+                segments.push(TemplateSegment::Literal(std::mem::take(&mut literal)));
+            }
+
+            let mut placeholder = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(ch) => placeholder.push(ch),
+                    None => {
+                        return Err(invalid(format!("unclosed placeholder '{{{}'", placeholder)))
+                    }
+                }
+            }
+
+            let (name, format) = match placeholder.split_once(':') {
+                Some((name, format)) => (name, Some(format)),
+                None => (placeholder.as_str(), None),
+            };
+            // parse any date format eagerly, so that errors surface when the
+            // appender is built rather than being reparsed on every rotation.
+            let format = format
+                .map(|format| {
+                    format_description::parse_owned::<2>(format).map_err(|error| {
+                        invalid(format!(
+                            "bad date format in '{{{}}}': {}",
+                            placeholder, error
+                        ))
+                    })
+                })
+                .transpose()?;
+
+            let segment = match name {
+                "date" => TemplateSegment::Date(format),
+                "now" => TemplateSegment::Now(format),
+                "prefix" | "suffix" | "index" if format.is_some() => {
+                    return Err(invalid(format!(
+                        "placeholder '{{{}}}' does not accept a format",
+                        name
+                    )))
+                }
+                "prefix" => TemplateSegment::Prefix,
+                "suffix" => TemplateSegment::Suffix,
+                "index" => TemplateSegment::Index,
+                name => return Err(invalid(format!("unknown placeholder '{{{}}}'", name))),
+            };
+            segments.push(segment);
+        }
+        if !literal.is_empty() {
+            segments.push(TemplateSegment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+/// Why the log file should roll over, as observed by [`Inner::should_rollover`].
+///
+/// Each variant carries the value observed when making the decision, so
+/// that the rollover itself can be claimed with a `compare_exchange` on the
+/// corresponding counter.
+#[derive(Debug, Clone, Copy)]
+enum Roll {
+    /// The current time period elapsed; carries the observed `next_date`.
+    Date(usize),
+    /// The size limit was reached; carries the observed `current_index`.
+    Size(usize),
+    /// A `SIGHUP` was received since the file was last (re)opened; carries
+    /// the observed `last_sighup` generation.
+    #[cfg(unix)]
+    Sighup(usize),
 }
 
 // === impl RollingFileAppender ===
@@ -190,6 +387,14 @@ impl RollingFileAppender {
             ref prefix,
             ref suffix,
             ref max_files,
+            ref max_age,
+            ref max_total_size,
+            ref template,
+            ref flush_every,
+            ref flush_interval,
+            ref sync_on_flush,
+            ref multi_process,
+            ref reopen_on_sighup,
         } = builder;
         let directory = directory.as_ref().to_path_buf();
         let now = OffsetDateTime::now_utc();
@@ -199,7 +404,19 @@ impl RollingFileAppender {
             directory,
             prefix.clone(),
             suffix.clone(),
-            *max_files,
+            Retention {
+                max_files: *max_files,
+                max_age: *max_age,
+                max_total_size: *max_total_size,
+            },
+            template.as_deref(),
+            FlushPolicy {
+                every: *flush_every,
+                interval: *flush_interval,
+                sync: *sync_on_flush,
+            },
+            *multi_process,
+            *reopen_on_sighup,
         )?;
         Ok(Self {
             state,
@@ -209,6 +426,62 @@ impl RollingFileAppender {
         })
     }
 
+    /// Flushes buffered data to the current log file.
+    ///
+    /// If [`Builder::sync_on_flush`] is enabled, the file is also
+    /// synchronized to disk. Unlike [`io::Write::flush`], this method takes a
+    /// shared reference, so it can be called on an appender that has been
+    /// wrapped in [`NonBlocking`] or another writer.
+    ///
+    /// [`NonBlocking`]: super::non_blocking::NonBlocking
+    pub fn flush(&self) -> io::Result<()> {
+        let file = self.writer.read();
+        self.state.flush_file(&file, self.state.flush_policy.sync)
+    }
+
+    /// Closes the current log file and reopens it at its expected path.
+    ///
+    /// When log files are rotated by an external tool such as `logrotate`,
+    /// the file this appender has open is renamed out from under it, and
+    /// the appender would otherwise keep writing to the renamed file
+    /// forever. Calling this method — typically from a `SIGHUP` handler —
+    /// makes the appender recreate the file at the path it expects and
+    /// write there instead.
+    ///
+    /// Alternatively, [`Builder::reopen_on_sighup`] installs a `SIGHUP`
+    /// handler that triggers a reopen automatically.
+    pub fn reopen(&self) -> io::Result<()> {
+        let now = self.now();
+        let index = self.state.current_index.load(Ordering::Acquire);
+        let filename = self.state.join_date(&now, index);
+        let new_file = create_writer(&self.state.log_directory, &filename)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+
+        let mut file = self.writer.write();
+        if let Err(error) = self.state.flush_file(&file, self.state.flush_policy.sync) {
+            eprintln!("Couldn't flush previous writer: {}", error);
+        }
+        *file = new_file;
+        // The reopened file is usually empty, but may contain data if the
+        // path was not actually rotated away.
+        if self.state.rotation.max_size.is_some() {
+            let len = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            self.state.current_size.store(len, Ordering::Release);
+        }
+        Ok(())
+    }
+
+    /// Flushes buffered data and synchronizes the current log file to disk.
+    ///
+    /// Unlike [`flush`](Self::flush), this *always* calls [`File::sync_all`]
+    /// (`fsync`), regardless of whether [`Builder::sync_on_flush`] is
+    /// enabled, so shutdown paths can guarantee that data has reached the
+    /// disk before the process exits.
+    pub fn sync(&self) -> io::Result<()> {
+        let file = self.writer.read();
+        self.state.flush_file(&file, true)
+    }
+
     #[inline]
     fn now(&self) -> OffsetDateTime {
         #[cfg(test)]
@@ -223,16 +496,20 @@ impl io::Write for RollingFileAppender {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         let now = self.now();
         let writer = self.writer.get_mut();
-        if let Some(current_time) = self.state.should_rollover(now) {
-            let _did_cas = self.state.advance_date(now, current_time);
-            debug_assert!(_did_cas, "if we have &mut access to the appender, no other thread can have advanced the timestamp...");
+        if let Some(roll) = self.state.should_rollover(now) {
+            let _did_cas = self.state.claim_rollover(now, roll);
+            debug_assert!(_did_cas, "if we have &mut access to the appender, no other thread can have rolled the file over...");
             self.state.refresh_writer(now, writer);
         }
-        writer.write(buf)
+        let written = writer.write(buf)?;
+        self.state.record_write(writer, written);
+        self.state.flush_after_write(writer);
+        Ok(written)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        self.writer.get_mut().flush()
+        let sync = self.state.flush_policy.sync;
+        self.state.flush_file(self.writer.get_mut(), sync)
     }
 }
 
@@ -242,14 +519,17 @@ impl<'a> tracing_subscriber::fmt::writer::MakeWriter<'a> for RollingFileAppender
         let now = self.now();
 
         // Should we try to roll over the log file?
-        if let Some(current_time) = self.state.should_rollover(now) {
+        if let Some(roll) = self.state.should_rollover(now) {
             // Did we get the right to lock the file? If not, another thread
             // did it and we can just make a writer.
-            if self.state.advance_date(now, current_time) {
+            if self.state.claim_rollover(now, roll) {
                 self.state.refresh_writer(now, &mut self.writer.write());
             }
         }
-        RollingWriter(self.writer.read())
+        RollingWriter {
+            file: self.writer.read(),
+            inner: &self.state,
+        }
     }
 }
 
@@ -436,8 +716,34 @@ pub fn never(directory: impl AsRef<Path>, file_name: impl AsRef<Path>) -> Rollin
 /// let rotation = tracing_appender::rolling::Rotation::NEVER;
 /// # }
 /// ```
+///
+/// ### Size-Based Rotation
+///
+/// In addition to (or instead of) a time period, a log file may be rolled
+/// over once it reaches a maximum size in bytes:
+///
+/// ```rust
+/// # fn docs() {
+/// use tracing_appender::rolling::Rotation;
+///
+/// // Roll over whenever the current log file exceeds 100 MB.
+/// let rotation = Rotation::size(100 * 1024 * 1024);
+///
+/// // Roll over every day, *and* whenever the current day's file
+/// // exceeds 100 MB.
+/// let rotation = Rotation::DAILY.with_max_size(100 * 1024 * 1024);
+/// # }
+/// ```
+///
+/// When the size limit produces more than one file within a single time
+/// period, the additional files are numbered sequentially: `app.2020-02-01.log`,
+/// `app.2020-02-01.1.log`, `app.2020-02-01.2.log`, and so on. The numbering
+/// restarts when the time period rolls over.
 #[derive(Clone, Eq, PartialEq, Debug)]
-pub struct Rotation(RotationKind);
+pub struct Rotation {
+    kind: RotationKind,
+    max_size: Option<u64>,
+}
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 enum RotationKind {
@@ -449,55 +755,106 @@ enum RotationKind {
 
 impl Rotation {
     /// Provides an minutely rotation
-    pub const MINUTELY: Self = Self(RotationKind::Minutely);
+    pub const MINUTELY: Self = Self {
+        kind: RotationKind::Minutely,
+        max_size: None,
+    };
     /// Provides an hourly rotation
-    pub const HOURLY: Self = Self(RotationKind::Hourly);
+    pub const HOURLY: Self = Self {
+        kind: RotationKind::Hourly,
+        max_size: None,
+    };
     /// Provides a daily rotation
-    pub const DAILY: Self = Self(RotationKind::Daily);
+    pub const DAILY: Self = Self {
+        kind: RotationKind::Daily,
+        max_size: None,
+    };
     /// Provides a rotation that never rotates.
-    pub const NEVER: Self = Self(RotationKind::Never);
+    pub const NEVER: Self = Self {
+        kind: RotationKind::Never,
+        max_size: None,
+    };
+
+    /// Returns a rotation that rolls the log file over once it exceeds
+    /// `max_size` bytes, with no time-based rotation.
+    ///
+    /// The first log file keeps the un-numbered file name, and each
+    /// subsequent file appends a sequence number: `app.log`, `app.1.log`,
+    /// `app.2.log`, and so on.
+    #[must_use]
+    pub const fn size(max_size: u64) -> Self {
+        Self {
+            kind: RotationKind::Never,
+            max_size: Some(max_size),
+        }
+    }
+
+    /// Adds a maximum size in bytes to this rotation.
+    ///
+    /// The log file rolls over whenever the time period elapses *or* the
+    /// current file exceeds `max_size` bytes, whichever comes first. Files
+    /// produced by the size limit within one time period are numbered
+    /// sequentially, and the numbering restarts in each new period.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn docs() {
+    /// use tracing_appender::rolling::Rotation;
+    /// let rotation = Rotation::HOURLY.with_max_size(10 * 1024 * 1024);
+    /// # }
+    /// ```
+    #[must_use]
+    pub const fn with_max_size(self, max_size: u64) -> Self {
+        Self {
+            max_size: Some(max_size),
+            ..self
+        }
+    }
 
     pub(crate) fn next_date(&self, current_date: &OffsetDateTime) -> Option<OffsetDateTime> {
-        let unrounded_next_date = match *self {
-            Rotation::MINUTELY => *current_date + Duration::minutes(1),
-            Rotation::HOURLY => *current_date + Duration::hours(1),
-            Rotation::DAILY => *current_date + Duration::days(1),
-            Rotation::NEVER => return None,
+        let unrounded_next_date = match self.kind {
+            RotationKind::Minutely => *current_date + Duration::minutes(1),
+            RotationKind::Hourly => *current_date + Duration::hours(1),
+            RotationKind::Daily => *current_date + Duration::days(1),
+            RotationKind::Never => return None,
         };
         Some(self.round_date(&unrounded_next_date))
     }
 
     // note that this method will panic if passed a `Rotation::NEVER`.
     pub(crate) fn round_date(&self, date: &OffsetDateTime) -> OffsetDateTime {
-        match *self {
-            Rotation::MINUTELY => {
+        match self.kind {
+            RotationKind::Minutely => {
                 let time = Time::from_hms(date.hour(), date.minute(), 0)
                     .expect("Invalid time; this is a bug in tracing-appender");
                 date.replace_time(time)
             }
-            Rotation::HOURLY => {
+            RotationKind::Hourly => {
                 let time = Time::from_hms(date.hour(), 0, 0)
                     .expect("Invalid time; this is a bug in tracing-appender");
                 date.replace_time(time)
             }
-            Rotation::DAILY => {
+            RotationKind::Daily => {
                 let time = Time::from_hms(0, 0, 0)
                     .expect("Invalid time; this is a bug in tracing-appender");
                 date.replace_time(time)
             }
             // Rotation::NEVER is impossible to round.
-            Rotation::NEVER => {
+            RotationKind::Never => {
                 unreachable!("Rotation::NEVER is impossible to round.")
             }
         }
     }
 
     fn date_format(&self) -> Vec<format_description::FormatItem<'static>> {
-        match *self {
-            Rotation::MINUTELY => format_description::parse("[year]-[month]-[day]-[hour]-[minute]"),
-            Rotation::HOURLY => format_description::parse("[year]-[month]-[day]-[hour]"),
-            Rotation::DAILY => format_description::parse("[year]-[month]-[day]"),
-            Rotation::NEVER => format_description::parse("[year]-[month]-[day]"),
+        match self.kind {
+            RotationKind::Minutely => {
+                format_description::parse("[year]-[month]-[day]-[hour]-[minute]")
+            }
+            RotationKind::Hourly => format_description::parse("[year]-[month]-[day]-[hour]"),
+            RotationKind::Daily => format_description::parse("[year]-[month]-[day]"),
+            RotationKind::Never => format_description::parse("[year]-[month]-[day]"),
         }
         .expect("Unable to create a formatter; this is a bug in tracing-appender")
     }
@@ -507,28 +864,42 @@ impl Rotation {
 
 impl io::Write for RollingWriter<'_> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        (&*self.0).write(buf)
+        let written = (&*self.file).write(buf)?;
+        self.inner.record_write(&self.file, written);
+        self.inner.flush_after_write(&self.file);
+        Ok(written)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        (&*self.0).flush()
+        self.inner
+            .flush_file(&self.file, self.inner.flush_policy.sync)
     }
 }
 
 // === impl Inner ===
 
 impl Inner {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         now: OffsetDateTime,
         rotation: Rotation,
         directory: impl AsRef<Path>,
         log_filename_prefix: Option<String>,
         log_filename_suffix: Option<String>,
-        max_files: Option<usize>,
+        retention: Retention,
+        template: Option<&str>,
+        flush_policy: FlushPolicy,
+        multi_process: bool,
+        reopen_on_sighup: bool,
     ) -> Result<(Self, RwLock<File>), builder::InitError> {
+        #[cfg(unix)]
+        if reopen_on_sighup {
+            install_sighup_handler();
+        }
         let log_directory = directory.as_ref().to_path_buf();
         let date_format = rotation.date_format();
         let next_date = rotation.next_date(&now);
+        let template = template.map(Template::parse).transpose()?;
 
         let inner = Inner {
             log_directory,
@@ -540,27 +911,67 @@ impl Inner {
                     .map(|date| date.unix_timestamp() as usize)
                     .unwrap_or(0),
             ),
+            current_size: AtomicU64::new(0),
+            current_index: AtomicUsize::new(0),
             rotation,
-            max_files,
+            retention,
+            template,
+            flush_policy,
+            writes_since_flush: AtomicUsize::new(0),
+            last_flush_ms: AtomicU64::new(0),
+            flush_epoch: std::time::Instant::now(),
+            multi_process,
+            reopen_on_sighup,
+            #[cfg(unix)]
+            last_sighup: AtomicUsize::new(SIGHUP_GENERATION.load(Ordering::Acquire)),
+            #[cfg(not(unix))]
+            last_sighup: AtomicUsize::new(0),
         };
-        let filename = inner.join_date(&now);
-        let writer = RwLock::new(create_writer(inner.log_directory.as_ref(), &filename)?);
-        Ok((inner, writer))
+        let filename = inner.join_date(&now, 0);
+        let writer = create_writer(inner.log_directory.as_ref(), &filename)?;
+        // If the initial log file already exists (the appender opens files in
+        // append mode), its current length counts towards the size limit.
+        if inner.rotation.max_size.is_some() {
+            if let Ok(metadata) = writer.metadata() {
+                inner.current_size.store(metadata.len(), Ordering::Release);
+            }
+        }
+        Ok((inner, RwLock::new(writer)))
     }
 
-    pub(crate) fn join_date(&self, date: &OffsetDateTime) -> String {
+    pub(crate) fn join_date(&self, date: &OffsetDateTime, index: usize) -> String {
+        if let Some(template) = &self.template {
+            return self.render_template(template, date, index);
+        }
+
         let date = date
             .format(&self.date_format)
             .expect("Unable to format OffsetDateTime; this is a bug in tracing-appender");
 
+        // When the size limit has produced additional files within one time
+        // period, all files after the first are numbered.
+        let date = match index {
+            0 => date,
+            index => format!("{}.{}", date, index),
+        };
+
         match (
-            &self.rotation,
+            &self.rotation.kind,
             &self.log_filename_prefix,
             &self.log_filename_suffix,
         ) {
-            (&Rotation::NEVER, Some(filename), None) => filename.to_string(),
-            (&Rotation::NEVER, Some(filename), Some(suffix)) => format!("{}.{}", filename, suffix),
-            (&Rotation::NEVER, None, Some(suffix)) => suffix.to_string(),
+            (RotationKind::Never, Some(filename), None) => match index {
+                0 => filename.to_string(),
+                index => format!("{}.{}", filename, index),
+            },
+            (RotationKind::Never, Some(filename), Some(suffix)) => match index {
+                0 => format!("{}.{}", filename, suffix),
+                index => format!("{}.{}.{}", filename, index, suffix),
+            },
+            (RotationKind::Never, None, Some(suffix)) => match index {
+                0 => suffix.to_string(),
+                index => format!("{}.{}", index, suffix),
+            },
             (_, Some(filename), Some(suffix)) => format!("{}.{}.{}", filename, date, suffix),
             (_, Some(filename), None) => format!("{}.{}", filename, date),
             (_, None, Some(suffix)) => format!("{}.{}", date, suffix),
@@ -568,7 +979,57 @@ impl Inner {
         }
     }
 
-    fn prune_old_logs(&self, max_files: usize) {
+    /// Renders a custom naming template for the file created at `now`.
+    fn render_template(&self, template: &Template, now: &OffsetDateTime, index: usize) -> String {
+        // `{date}` is the start of the period the file covers, while `{now}`
+        // is the actual rotation time; these differ when a file is created
+        // partway through a period.
+        let period_start = match self.rotation.kind {
+            RotationKind::Never => *now,
+            _ => self.rotation.round_date(now),
+        };
+
+        let mut out = String::new();
+        for segment in &template.segments {
+            match segment {
+                TemplateSegment::Literal(text) => out.push_str(text),
+                TemplateSegment::Prefix => {
+                    if let Some(prefix) = &self.log_filename_prefix {
+                        out.push_str(prefix);
+                    }
+                }
+                TemplateSegment::Suffix => {
+                    if let Some(suffix) = &self.log_filename_suffix {
+                        out.push_str(suffix);
+                    }
+                }
+                TemplateSegment::Index => out.push_str(&index.to_string()),
+                TemplateSegment::Date(format) => {
+                    out.push_str(&self.format_timestamp(&period_start, format.as_ref()))
+                }
+                TemplateSegment::Now(format) => {
+                    out.push_str(&self.format_timestamp(now, format.as_ref()))
+                }
+            }
+        }
+        out
+    }
+
+    /// Formats a template timestamp, falling back to the rotation's default
+    /// date format.
+    fn format_timestamp(
+        &self,
+        date: &OffsetDateTime,
+        format: Option<&format_description::OwnedFormatItem>,
+    ) -> String {
+        match format {
+            Some(format) => date.format(format),
+            None => date.format(&self.date_format),
+        }
+        .expect("Unable to format OffsetDateTime; this is a bug in tracing-appender")
+    }
+
+    fn prune_old_logs(&self) {
         let files = fs::read_dir(&self.log_directory).map(|dir| {
             dir.filter_map(|entry| {
                 let entry = entry.ok()?;
@@ -603,7 +1064,7 @@ impl Inner {
                 }
 
                 let created = metadata.created().ok()?;
-                Some((entry, created))
+                Some((entry, created, metadata.len()))
             })
             .collect::<Vec<_>>()
         });
@@ -615,15 +1076,53 @@ impl Inner {
                 return;
             }
         };
-        if files.len() < max_files {
-            return;
-        }
 
-        // sort the files by their creation timestamps.
-        files.sort_by_key(|(_, created_at)| *created_at);
+        // sort the files by their creation timestamps, oldest first.
+        files.sort_by_key(|(_, created_at, _)| *created_at);
+
+        let mut prune = vec![false; files.len()];
+
+        // delete files older than the maximum age.
+        if let Some(max_age) = self.retention.max_age {
+            let now = std::time::SystemTime::now();
+            for (i, (_, created_at, _)) in files.iter().enumerate() {
+                match now.duration_since(*created_at) {
+                    Ok(age) if age > max_age => prune[i] = true,
+                    _ => {}
+                }
+            }
+        }
 
         // delete files, so that (n-1) files remain, because we will create another log file
-        for (file, _) in files.iter().take(files.len() - (max_files - 1)) {
+        if let Some(max_files) = self.retention.max_files {
+            if files.len() >= max_files {
+                for flag in prune.iter_mut().take(files.len() - (max_files - 1)) {
+                    *flag = true;
+                }
+            }
+        }
+
+        // delete the oldest files until the remaining files fit within the
+        // total size limit.
+        if let Some(max_total_size) = self.retention.max_total_size {
+            let mut total: u64 = files
+                .iter()
+                .zip(prune.iter())
+                .filter(|(_, pruned)| !**pruned)
+                .map(|((_, _, len), _)| *len)
+                .sum();
+            for (i, (_, _, len)) in files.iter().enumerate() {
+                if total <= max_total_size {
+                    break;
+                }
+                if !prune[i] {
+                    prune[i] = true;
+                    total -= len;
+                }
+            }
+        }
+
+        for ((file, _, _), _) in files.iter().zip(prune).filter(|(_, prune)| *prune) {
             if let Err(error) = fs::remove_file(file.path()) {
                 eprintln!(
                     "Failed to remove old log file {}: {}",
@@ -635,57 +1134,273 @@ impl Inner {
     }
 
     fn refresh_writer(&self, now: OffsetDateTime, file: &mut File) {
-        let filename = self.join_date(&now);
+        // In multi-process mode, hold the advisory lock while rotating, so
+        // that pruning and the on-disk index scan below cannot race with
+        // another process's rotation.
+        let _lock = if self.multi_process {
+            match lock_rotation(&self.log_directory) {
+                Ok(lock) => Some(lock),
+                Err(error) => {
+                    eprintln!("Couldn't lock log directory for rotation: {}", error);
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
-        if let Some(max_files) = self.max_files {
-            self.prune_old_logs(max_files);
+        let mut index = self.current_index.load(Ordering::Acquire);
+        if self.multi_process {
+            if let Some(max_size) = self.rotation.max_size {
+                // Another process may already have rotated past our index;
+                // append to the first file that is still under the size
+                // limit, rather than creating a separate sequence of files.
+                while let Ok(metadata) =
+                    fs::metadata(self.log_directory.join(self.join_date(&now, index)))
+                {
+                    if metadata.len() < max_size {
+                        break;
+                    }
+                    index += 1;
+                }
+                self.current_index.store(index, Ordering::Release);
+            }
+        }
+        let filename = self.join_date(&now, index);
+
+        if self.retention.is_limited() {
+            self.prune_old_logs();
         }
 
         match create_writer(&self.log_directory, &filename) {
             Ok(new_file) => {
-                if let Err(err) = file.flush() {
+                if let Err(err) = self.flush_file(file, self.flush_policy.sync) {
                     eprintln!("Couldn't flush previous writer: {}", err);
                 }
                 *file = new_file;
+                // The new file may already contain data written by another
+                // process, which counts towards the size limit.
+                if self.multi_process && self.rotation.max_size.is_some() {
+                    if let Ok(metadata) = file.metadata() {
+                        self.current_size.store(metadata.len(), Ordering::Release);
+                    }
+                }
             }
             Err(err) => eprintln!("Couldn't create writer for logs: {}", err),
         }
     }
 
+    /// Records `written` additional bytes in the current log file's size.
+    ///
+    /// In multi-process mode, the on-disk size is authoritative, since
+    /// other processes may be appending to the same file.
+    fn record_write(&self, file: &File, written: usize) {
+        if self.multi_process {
+            if let Ok(metadata) = file.metadata() {
+                self.current_size.store(metadata.len(), Ordering::Release);
+                return;
+            }
+        }
+        self.current_size
+            .fetch_add(written as u64, Ordering::AcqRel);
+    }
+
+    /// Called after each successful write; flushes the file if one of the
+    /// flush policy's thresholds has been reached.
+    fn flush_after_write(&self, file: &File) {
+        if !self.flush_policy.is_scheduled() {
+            return;
+        }
+        let writes = self.writes_since_flush.fetch_add(1, Ordering::AcqRel) + 1;
+        let due_by_count = matches!(self.flush_policy.every, Some(every) if writes >= every);
+        let due_by_time = match self.flush_policy.interval {
+            Some(interval) => {
+                let elapsed = self.flush_epoch.elapsed().as_millis() as u64;
+                let last_flush = self.last_flush_ms.load(Ordering::Acquire);
+                elapsed.saturating_sub(last_flush) >= interval.as_millis() as u64
+            }
+            None => false,
+        };
+        if due_by_count || due_by_time {
+            if let Err(error) = self.flush_file(file, self.flush_policy.sync) {
+                eprintln!("Couldn't flush log file: {}", error);
+            }
+        }
+    }
+
+    /// Flushes `file`, synchronizing it to disk if `sync` is set, and resets
+    /// the flush policy's thresholds.
+    fn flush_file(&self, mut file: &File, sync: bool) -> io::Result<()> {
+        file.flush()?;
+        if sync {
+            file.sync_all()?;
+        }
+        self.writes_since_flush.store(0, Ordering::Release);
+        self.last_flush_ms.store(
+            self.flush_epoch.elapsed().as_millis() as u64,
+            Ordering::Release,
+        );
+        Ok(())
+    }
+
     /// Checks whether or not it's time to roll over the log file.
     ///
-    /// Rather than returning a `bool`, this returns the current value of
-    /// `next_date` so that we can perform a `compare_exchange` operation with
-    /// that value when setting the next rollover time.
+    /// Rather than returning a `bool`, this returns the counter value
+    /// observed when making the decision, so that the rollover can be
+    /// claimed with a `compare_exchange` operation on that counter.
     ///
     /// If this method returns `Some`, we should roll to a new log file.
     /// Otherwise, if this returns we should not rotate the log file.
-    fn should_rollover(&self, date: OffsetDateTime) -> Option<usize> {
+    fn should_rollover(&self, date: OffsetDateTime) -> Option<Roll> {
         let next_date = self.next_date.load(Ordering::Acquire);
-        // if the next date is 0, this appender *never* rotates log files.
-        if next_date == 0 {
-            return None;
+        // if the next date is 0, this appender *never* rotates log files
+        // based on time.
+        if next_date != 0 && date.unix_timestamp() as usize >= next_date {
+            return Some(Roll::Date(next_date));
+        }
+
+        // if a `SIGHUP` has been received since the file was last (re)opened,
+        // the file should be reopened at its expected path.
+        #[cfg(unix)]
+        if self.reopen_on_sighup {
+            let seen = self.last_sighup.load(Ordering::Acquire);
+            if seen != SIGHUP_GENERATION.load(Ordering::Acquire) {
+                return Some(Roll::Sighup(seen));
+            }
         }
 
-        if date.unix_timestamp() as usize >= next_date {
-            return Some(next_date);
+        if let Some(max_size) = self.rotation.max_size {
+            if self.current_size.load(Ordering::Acquire) >= max_size {
+                return Some(Roll::Size(self.current_index.load(Ordering::Acquire)));
+            }
         }
 
         None
     }
 
+    /// Attempts to claim the right to perform the rollover observed by
+    /// [`should_rollover`], returning `true` if this thread won the race
+    /// and should refresh the writer.
+    ///
+    /// [`should_rollover`]: Inner::should_rollover
+    fn claim_rollover(&self, now: OffsetDateTime, roll: Roll) -> bool {
+        match roll {
+            Roll::Date(current) => self.advance_date(now, current),
+            Roll::Size(index) => self.advance_index(index),
+            #[cfg(unix)]
+            Roll::Sighup(seen) => self.advance_sighup(seen),
+        }
+    }
+
     fn advance_date(&self, now: OffsetDateTime, current: usize) -> bool {
         let next_date = self
             .rotation
             .next_date(&now)
             .map(|date| date.unix_timestamp() as usize)
             .unwrap_or(0);
-        self.next_date
+        let did_cas = self
+            .next_date
             .compare_exchange(current, next_date, Ordering::AcqRel, Ordering::Acquire)
-            .is_ok()
+            .is_ok();
+        if did_cas {
+            // A new time period restarts the size accounting and the
+            // within-period file numbering.
+            self.current_index.store(0, Ordering::Release);
+            self.current_size.store(0, Ordering::Release);
+        }
+        did_cas
+    }
+
+    #[cfg(unix)]
+    fn advance_sighup(&self, seen: usize) -> bool {
+        let current = SIGHUP_GENERATION.load(Ordering::Acquire);
+        let did_cas = self
+            .last_sighup
+            .compare_exchange(seen, current, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok();
+        if did_cas {
+            // The file recreated at the expected path starts out empty.
+            self.current_size.store(0, Ordering::Release);
+        }
+        did_cas
+    }
+
+    fn advance_index(&self, current: usize) -> bool {
+        let did_cas = self
+            .current_index
+            .compare_exchange(current, current + 1, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok();
+        if did_cas {
+            self.current_size.store(0, Ordering::Release);
+        }
+        did_cas
     }
 }
 
+/// Counts the `SIGHUP` signals received by the process.
+///
+/// Appenders configured with [`Builder::reopen_on_sighup`] compare this
+/// against the generation at which they last (re)opened their log file, and
+/// reopen it when the two differ.
+#[cfg(unix)]
+static SIGHUP_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(unix)]
+extern "C" fn on_sighup(_signal: std::os::raw::c_int) {
+    // Only async-signal-safe operations are permitted here; a lock-free
+    // atomic increment is, and the actual reopening happens on the next
+    // write instead.
+    SIGHUP_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+/// Installs the process-wide `SIGHUP` handler, once.
+#[cfg(unix)]
+fn install_sighup_handler() {
+    use std::sync::Once;
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| unsafe {
+        libc::signal(
+            libc::SIGHUP,
+            on_sighup as extern "C" fn(std::os::raw::c_int) as libc::sighandler_t,
+        );
+    });
+}
+
+/// The name of the lock file used to coordinate rotation between processes.
+const LOCK_FILENAME: &str = ".tracing-appender.lock";
+
+/// Acquires an exclusive advisory lock on the log directory's lock file,
+/// blocking until the lock is available.
+///
+/// The lock is released when the returned file is dropped.
+fn lock_rotation(directory: &Path) -> io::Result<File> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(directory.join(LOCK_FILENAME))?;
+    lock_exclusive(&file)?;
+    Ok(file)
+}
+
+#[cfg(unix)]
+fn lock_exclusive(file: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    // Advisory lock; released automatically when the file is closed.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn lock_exclusive(_file: &File) -> io::Result<()> {
+    // `flock(2)` is not available. Rotation remains safe without the lock,
+    // because all processes converge on the same file names, but old log
+    // files may be pruned by more than one process at a time.
+    Ok(())
+}
+
 fn create_writer(directory: &Path, filename: &str) -> Result<File, InitError> {
     let path = directory.join(filename);
     let mut open_options = OpenOptions::new();
@@ -828,10 +1543,14 @@ mod test {
                 directory.path(),
                 prefix.map(ToString::to_string),
                 suffix.map(ToString::to_string),
+                Retention::default(),
                 None,
+                FlushPolicy::default(),
+                false,
+                false,
             )
             .unwrap();
-            let path = inner.join_date(&now);
+            let path = inner.join_date(&now, 0);
             assert_eq!(
                 expected, path,
                 "rotation = {:?}, prefix = {:?}, suffix = {:?}",
@@ -921,6 +1640,459 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_size_rotation_numbering() {
+        let format = format_description::parse(
+            "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour \
+         sign:mandatory]:[offset_minute]:[offset_second]",
+        )
+        .unwrap();
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+
+        let now = OffsetDateTime::parse("2020-02-01 10:01:00 +00:00:00", &format).unwrap();
+
+        struct TestCase {
+            expected: &'static str,
+            rotation: Rotation,
+            prefix: Option<&'static str>,
+            suffix: Option<&'static str>,
+            index: usize,
+        }
+
+        let test = |TestCase {
+                        expected,
+                        rotation,
+                        prefix,
+                        suffix,
+                        index,
+                    }| {
+            let (inner, _) = Inner::new(
+                now,
+                rotation.clone(),
+                directory.path(),
+                prefix.map(ToString::to_string),
+                suffix.map(ToString::to_string),
+                Retention::default(),
+                None,
+                FlushPolicy::default(),
+                false,
+                false,
+            )
+            .unwrap();
+            let path = inner.join_date(&now, index);
+            assert_eq!(
+                expected, path,
+                "rotation = {:?}, prefix = {:?}, suffix = {:?}, index = {}",
+                rotation, prefix, suffix, index
+            );
+        };
+
+        let test_cases = vec![
+            // size-only rotation: the first file is un-numbered
+            TestCase {
+                expected: "app.log",
+                rotation: Rotation::size(1024),
+                prefix: Some("app.log"),
+                suffix: None,
+                index: 0,
+            },
+            TestCase {
+                expected: "app.log.2",
+                rotation: Rotation::size(1024),
+                prefix: Some("app.log"),
+                suffix: None,
+                index: 2,
+            },
+            TestCase {
+                expected: "app.1.log",
+                rotation: Rotation::size(1024),
+                prefix: Some("app"),
+                suffix: Some("log"),
+                index: 1,
+            },
+            TestCase {
+                expected: "1.log",
+                rotation: Rotation::size(1024),
+                prefix: None,
+                suffix: Some("log"),
+                index: 1,
+            },
+            // combined time and size rotation: the index follows the date
+            TestCase {
+                expected: "app.2020-02-01-10.1.log",
+                rotation: Rotation::HOURLY.with_max_size(1024),
+                prefix: Some("app"),
+                suffix: Some("log"),
+                index: 1,
+            },
+            TestCase {
+                expected: "app.log.2020-02-01.3",
+                rotation: Rotation::DAILY.with_max_size(1024),
+                prefix: Some("app.log"),
+                suffix: None,
+                index: 3,
+            },
+            TestCase {
+                expected: "2020-02-01-10-01.1.log",
+                rotation: Rotation::MINUTELY.with_max_size(1024),
+                prefix: None,
+                suffix: Some("log"),
+                index: 1,
+            },
+        ];
+        for test_case in test_cases {
+            test(test_case)
+        }
+    }
+
+    #[test]
+    fn test_filename_template() {
+        let format = format_description::parse(
+            "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour \
+         sign:mandatory]:[offset_minute]:[offset_second]",
+        )
+        .unwrap();
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+
+        let now = OffsetDateTime::parse("2020-02-01 10:01:00 +00:00:00", &format).unwrap();
+
+        let join = |template: &str, index: usize| {
+            let (inner, _) = Inner::new(
+                now,
+                Rotation::HOURLY,
+                directory.path(),
+                Some("app".to_string()),
+                Some("log".to_string()),
+                Retention::default(),
+                Some(template),
+                FlushPolicy::default(),
+                false,
+                false,
+            )
+            .unwrap();
+            inner.join_date(&now, index)
+        };
+
+        assert_eq!(
+            join("{prefix}-{date:[year][month][day]}.{index}.{suffix}", 0),
+            "app-20200201.0.log"
+        );
+        assert_eq!(
+            join("{prefix}-{date:[year][month][day]}.{index}.{suffix}", 2),
+            "app-20200201.2.log"
+        );
+        // without an explicit format, the rotation's default date format is
+        // used.
+        assert_eq!(join("{date}", 0), "2020-02-01-10");
+        // `{date}` is the start of the period; `{now}` is the rotation time.
+        assert_eq!(
+            join("{date:[hour]-[minute]}_{now:[hour]-[minute]}", 0),
+            "10-00_10-01"
+        );
+    }
+
+    #[test]
+    fn test_invalid_filename_template() {
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let build = |template: &str| {
+            RollingFileAppender::builder()
+                .filename_prefix("app")
+                .filename_template(template)
+                .build(directory.path())
+                .map(drop)
+                .expect_err("template should have been rejected")
+                .to_string()
+        };
+
+        assert!(build("{bogus}").contains("unknown placeholder"));
+        assert!(build("{prefix").contains("unclosed placeholder"));
+        assert!(build("{date:[bogus]}").contains("bad date format"));
+        assert!(build("{index:04}").contains("does not accept a format"));
+    }
+
+    #[test]
+    fn test_flush_every() {
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let mut appender = RollingFileAppender::builder()
+            .filename_prefix("flush.log")
+            .flush_every(2)
+            .sync_on_flush(true)
+            .build(directory.path())
+            .expect("failed to build appender");
+
+        // the write counter resets whenever the file is flushed.
+        appender.write_all(b"one\n").expect("failed to write");
+        assert_eq!(appender.state.writes_since_flush.load(Ordering::Acquire), 1);
+        appender.write_all(b"two\n").expect("failed to write");
+        assert_eq!(appender.state.writes_since_flush.load(Ordering::Acquire), 0);
+        appender.write_all(b"three\n").expect("failed to write");
+        assert_eq!(appender.state.writes_since_flush.load(Ordering::Acquire), 1);
+
+        // an explicit sync flushes regardless of the thresholds.
+        appender.sync().expect("failed to sync");
+        assert_eq!(appender.state.writes_since_flush.load(Ordering::Acquire), 0);
+
+        let file = fs::read_to_string(directory.path().join("flush.log"))
+            .expect("failed to read log file");
+        assert_eq!(file, "one\ntwo\nthree\n");
+    }
+
+    #[test]
+    fn test_flush_interval() {
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let mut appender = RollingFileAppender::builder()
+            .filename_prefix("flush.log")
+            // a zero-length interval has always elapsed, so every write
+            // triggers a flush.
+            .flush_interval(std::time::Duration::from_millis(0))
+            .build(directory.path())
+            .expect("failed to build appender");
+
+        appender.write_all(b"one\n").expect("failed to write");
+        assert_eq!(appender.state.writes_since_flush.load(Ordering::Acquire), 0);
+        appender.write_all(b"two\n").expect("failed to write");
+        assert_eq!(appender.state.writes_since_flush.load(Ordering::Acquire), 0);
+    }
+
+    #[test]
+    fn test_size_rotation() {
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let mut appender =
+            RollingFileAppender::new(Rotation::size(10), directory.path(), "size.log");
+
+        // each message is five bytes, so the file rolls over once two
+        // messages have been written to it.
+        write_to_log(&mut appender, "aaaaa");
+        write_to_log(&mut appender, "bbbbb");
+        write_to_log(&mut appender, "ccccc");
+        write_to_log(&mut appender, "ddddd");
+        write_to_log(&mut appender, "eeeee");
+
+        let mut files = std::collections::HashMap::new();
+        for entry in fs::read_dir(directory.path()).expect("Failed to read directory") {
+            let path = entry.expect("Expected dir entry").path();
+            let file = fs::read_to_string(&path).expect("Failed to read file");
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .expect("filename should be UTF8")
+                .to_string();
+            files.insert(name, file);
+        }
+
+        assert_eq!(
+            files.get("size.log").map(String::as_str),
+            Some("aaaaabbbbb")
+        );
+        assert_eq!(
+            files.get("size.log.1").map(String::as_str),
+            Some("cccccddddd")
+        );
+        assert_eq!(files.get("size.log.2").map(String::as_str), Some("eeeee"));
+        assert_eq!(files.len(), 3, "{:?}", files);
+    }
+
+    #[test]
+    fn test_reopen() {
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let mut appender = RollingFileAppender::builder()
+            .filename_prefix("reopen.log")
+            .build(directory.path())
+            .expect("failed to build appender");
+        let path = directory.path().join("reopen.log");
+        let rotated = directory.path().join("reopen.log.rotated");
+
+        write_to_log(&mut appender, "one\n");
+        // simulate external rotation: the log file is renamed out from
+        // under the appender, which keeps writing to the renamed file...
+        fs::rename(&path, &rotated).expect("failed to rename log file");
+        write_to_log(&mut appender, "two\n");
+        // ...until it is told to reopen the path it expects.
+        appender.reopen().expect("failed to reopen log file");
+        write_to_log(&mut appender, "three\n");
+
+        assert_eq!(
+            fs::read_to_string(&rotated).expect("failed to read rotated file"),
+            "one\ntwo\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&path).expect("failed to read log file"),
+            "three\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_reopen_on_sighup() {
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let mut appender = RollingFileAppender::builder()
+            .filename_prefix("sighup.log")
+            .reopen_on_sighup(true)
+            .build(directory.path())
+            .expect("failed to build appender");
+        let path = directory.path().join("sighup.log");
+        let rotated = directory.path().join("sighup.log.rotated");
+
+        write_to_log(&mut appender, "one\n");
+        fs::rename(&path, &rotated).expect("failed to rename log file");
+        // building the appender installed the handler, so raising `SIGHUP`
+        // bumps the generation counter rather than killing the process; the
+        // next write then recreates the file at the expected path.
+        unsafe {
+            libc::raise(libc::SIGHUP);
+        }
+        write_to_log(&mut appender, "two\n");
+
+        assert_eq!(
+            fs::read_to_string(&rotated).expect("failed to read rotated file"),
+            "one\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&path).expect("failed to read log file"),
+            "two\n"
+        );
+    }
+
+    #[test]
+    fn test_multi_process_size_rotation() {
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let build = || {
+            RollingFileAppender::builder()
+                .rotation(Rotation::size(10))
+                .filename_prefix("shared.log")
+                .multi_process(true)
+                .build(directory.path())
+                .expect("failed to build appender")
+        };
+        // two appenders in one process stand in for two processes: they
+        // share no state other than the files on disk.
+        let mut first = build();
+        let mut second = build();
+
+        write_to_log(&mut first, "aaaaa");
+        write_to_log(&mut second, "bbbbb");
+        // the file now holds ten bytes. `first` doesn't notice until after
+        // its next write, but both appenders then roll over to the *same*
+        // numbered file rather than to two different ones.
+        write_to_log(&mut first, "ccccc");
+        write_to_log(&mut second, "ddddd");
+        write_to_log(&mut first, "eeeee");
+
+        let mut files = std::collections::HashMap::new();
+        for entry in fs::read_dir(directory.path()).expect("Failed to read directory") {
+            let path = entry.expect("Expected dir entry").path();
+            let file = fs::read_to_string(&path).expect("Failed to read file");
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .expect("filename should be UTF8")
+                .to_string();
+            files.insert(name, file);
+        }
+
+        assert_eq!(
+            files.get("shared.log").map(String::as_str),
+            Some("aaaaabbbbbccccc")
+        );
+        assert_eq!(
+            files.get("shared.log.1").map(String::as_str),
+            Some("dddddeeeee")
+        );
+        // the two log files, plus the lock file used to coordinate rotation.
+        assert!(files.contains_key(".tracing-appender.lock"), "{:?}", files);
+        assert_eq!(files.len(), 3, "{:?}", files);
+    }
+
+    #[test]
+    fn test_time_and_size_rotation() {
+        use std::sync::{Arc, Mutex};
+        use tracing_subscriber::prelude::*;
+
+        let format = format_description::parse(
+            "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour \
+         sign:mandatory]:[offset_minute]:[offset_second]",
+        )
+        .unwrap();
+
+        let now = OffsetDateTime::parse("2020-02-01 10:01:00 +00:00:00", &format).unwrap();
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let (state, writer) = Inner::new(
+            now,
+            Rotation::HOURLY.with_max_size(10),
+            directory.path(),
+            Some("test_time_and_size".to_string()),
+            None,
+            Retention::default(),
+            None,
+            FlushPolicy::default(),
+            false,
+            false,
+        )
+        .unwrap();
+
+        let clock = Arc::new(Mutex::new(now));
+        let now = {
+            let clock = clock.clone();
+            Box::new(move || *clock.lock().unwrap())
+        };
+        let appender = RollingFileAppender { state, writer, now };
+        let default = tracing_subscriber::fmt()
+            .without_time()
+            .with_level(false)
+            .with_target(false)
+            .with_max_level(tracing_subscriber::filter::LevelFilter::TRACE)
+            .with_writer(appender)
+            .finish()
+            .set_default();
+
+        // each line is seven bytes, so the file rolls over once two lines
+        // have been written to it.
+        tracing::info!("file 1");
+        tracing::info!("file 1");
+        // this event exceeds the size limit, producing a second, numbered
+        // file within the same hour.
+        tracing::info!("file 2");
+
+        // advance time by one hour; the numbering starts over.
+        (*clock.lock().unwrap()) += Duration::hours(1);
+
+        tracing::info!("file 3");
+
+        drop(default);
+
+        let mut files = std::collections::HashMap::new();
+        for entry in fs::read_dir(directory.path()).expect("Failed to read directory") {
+            let path = entry.expect("Expected dir entry").path();
+            let file = fs::read_to_string(&path).expect("Failed to read file");
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .expect("filename should be UTF8")
+                .to_string();
+            files.insert(name, file);
+        }
+
+        assert_eq!(
+            files
+                .get("test_time_and_size.2020-02-01-10")
+                .map(String::as_str),
+            Some("file 1\nfile 1\n")
+        );
+        assert_eq!(
+            files
+                .get("test_time_and_size.2020-02-01-10.1")
+                .map(String::as_str),
+            Some("file 2\n")
+        );
+        assert_eq!(
+            files
+                .get("test_time_and_size.2020-02-01-11")
+                .map(String::as_str),
+            Some("file 3\n")
+        );
+        assert_eq!(files.len(), 3, "{:?}", files);
+    }
+
     #[test]
     fn test_make_writer() {
         use std::sync::{Arc, Mutex};
@@ -940,7 +2112,11 @@ mod test {
             directory.path(),
             Some("test_make_writer".to_string()),
             None,
+            Retention::default(),
             None,
+            FlushPolicy::default(),
+            false,
+            false,
         )
         .unwrap();
 
@@ -1022,7 +2198,14 @@ mod test {
             directory.path(),
             Some("test_max_log_files".to_string()),
             None,
-            Some(2),
+            Retention {
+                max_files: Some(2),
+                ..Default::default()
+            },
+            None,
+            FlushPolicy::default(),
+            false,
+            false,
         )
         .unwrap();
 
@@ -1106,4 +2289,121 @@ mod test {
             }
         }
     }
+
+    /// Runs a clock-driven appender through three hourly rotations with the
+    /// given retention policy, then returns the names of the files remaining
+    /// on disk.
+    fn test_retention(prefix: &str, retention: Retention) -> Vec<String> {
+        use std::sync::{Arc, Mutex};
+        use tracing_subscriber::prelude::*;
+
+        let format = format_description::parse(
+            "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour \
+         sign:mandatory]:[offset_minute]:[offset_second]",
+        )
+        .unwrap();
+
+        let now = OffsetDateTime::parse("2020-02-01 10:01:00 +00:00:00", &format).unwrap();
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let (state, writer) = Inner::new(
+            now,
+            Rotation::HOURLY,
+            directory.path(),
+            Some(prefix.to_string()),
+            None,
+            retention,
+            None,
+            FlushPolicy::default(),
+            false,
+            false,
+        )
+        .unwrap();
+
+        let clock = Arc::new(Mutex::new(now));
+        let now = {
+            let clock = clock.clone();
+            Box::new(move || *clock.lock().unwrap())
+        };
+        let appender = RollingFileAppender { state, writer, now };
+        let default = tracing_subscriber::fmt()
+            .without_time()
+            .with_level(false)
+            .with_target(false)
+            .with_max_level(tracing_subscriber::filter::LevelFilter::TRACE)
+            .with_writer(appender)
+            .finish()
+            .set_default();
+
+        for file in 1..=3 {
+            tracing::info!("file {}", file);
+
+            // advance time by one hour
+            (*clock.lock().unwrap()) += Duration::hours(1);
+
+            // depending on the filesystem, the creation timestamp's resolution
+            // may be as coarse as one second, so we need to wait a bit here to
+            // ensure that the next file actually is newer than the old one.
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+
+        tracing::info!("file 4");
+
+        drop(default);
+
+        let mut files = fs::read_dir(directory.path())
+            .expect("Failed to read directory")
+            .map(|entry| {
+                entry
+                    .expect("Expected dir entry")
+                    .file_name()
+                    .into_string()
+                    .expect("filename should be UTF8")
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        directory
+            .close()
+            .expect("Failed to explicitly close TempDir. TempDir should delete once out of scope.");
+        files
+    }
+
+    #[test]
+    fn test_max_file_age() {
+        // each rotation sleeps for one second, so by the time `file 4` is
+        // created, the first two files are more than a second old.
+        let files = test_retention(
+            "test_max_file_age",
+            Retention {
+                max_age: Some(std::time::Duration::from_millis(1500)),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            files,
+            vec![
+                "test_max_file_age.2020-02-01-12".to_string(),
+                "test_max_file_age.2020-02-01-13".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_total_size() {
+        // each file is seven bytes; a ten-byte limit retains only the most
+        // recent existing file when the next one is created.
+        let files = test_retention(
+            "test_max_total_size",
+            Retention {
+                max_total_size: Some(10),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            files,
+            vec![
+                "test_max_total_size.2020-02-01-12".to_string(),
+                "test_max_total_size.2020-02-01-13".to_string(),
+            ]
+        );
+    }
 }
@@ -1,16 +1,24 @@
 use super::{RollingFileAppender, Rotation};
-use std::{io, path::Path};
+use std::{io, path::Path, time::Duration};
 use thiserror::Error;
 
 /// A [builder] for configuring [`RollingFileAppender`]s.
 ///
 /// [builder]: https://rust-unofficial.github.io/patterns/patterns/creational/builder.html
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Builder {
     pub(super) rotation: Rotation,
     pub(super) prefix: Option<String>,
     pub(super) suffix: Option<String>,
     pub(super) max_files: Option<usize>,
+    pub(super) max_age: Option<Duration>,
+    pub(super) max_total_size: Option<u64>,
+    pub(super) template: Option<String>,
+    pub(super) flush_every: Option<usize>,
+    pub(super) flush_interval: Option<Duration>,
+    pub(super) sync_on_flush: bool,
+    pub(super) multi_process: bool,
+    pub(super) reopen_on_sighup: bool,
 }
 
 /// Errors returned by [`Builder::build`].
@@ -42,11 +50,27 @@ impl Builder {
     /// | [`filename_prefix`] | `""` | By default, log file names will not have a prefix. |
     /// | [`filename_suffix`] | `""` | By default, log file names will not have a suffix. |
     /// | [`max_log_files`] | `None` | By default, there is no limit for maximum log file count. |
+    /// | [`max_file_age`] | `None` | By default, log files are kept regardless of their age. |
+    /// | [`max_total_size`] | `None` | By default, there is no limit on total log size on disk. |
+    /// | [`filename_template`] | `None` | By default, the date-suffix naming scheme is used. |
+    /// | [`flush_every`] | `None` | By default, flushing is left to the operating system. |
+    /// | [`flush_interval`] | `None` | By default, flushing is left to the operating system. |
+    /// | [`sync_on_flush`] | `false` | By default, flushes do not `fsync` the log file. |
+    /// | [`multi_process`] | `false` | By default, a single process is assumed to own the log files. |
+    /// | [`reopen_on_sighup`] | `false` | By default, no `SIGHUP` handler is installed. |
     ///
     /// [`rotation`]: Self::rotation
     /// [`filename_prefix`]: Self::filename_prefix
     /// [`filename_suffix`]: Self::filename_suffix
     /// [`max_log_files`]: Self::max_log_files
+    /// [`max_file_age`]: Self::max_file_age
+    /// [`max_total_size`]: Self::max_total_size
+    /// [`filename_template`]: Self::filename_template
+    /// [`flush_every`]: Self::flush_every
+    /// [`flush_interval`]: Self::flush_interval
+    /// [`sync_on_flush`]: Self::sync_on_flush
+    /// [`multi_process`]: Self::multi_process
+    /// [`reopen_on_sighup`]: Self::reopen_on_sighup
     #[must_use]
     pub const fn new() -> Self {
         Self {
@@ -54,6 +78,14 @@ impl Builder {
             prefix: None,
             suffix: None,
             max_files: None,
+            max_age: None,
+            max_total_size: None,
+            template: None,
+            flush_every: None,
+            flush_interval: None,
+            sync_on_flush: false,
+            multi_process: false,
+            reopen_on_sighup: false,
         }
     }
 
@@ -233,6 +265,346 @@ impl Builder {
         }
     }
 
+    /// Deletes log files older than `age` when a new log file is created.
+    ///
+    /// A file's age is determined from its filesystem creation timestamp.
+    /// Files are considered candidates for deletion based on the same
+    /// criteria as [`max_log_files`]. If no value is supplied, log files are
+    /// kept regardless of their age.
+    ///
+    /// [`max_log_files`]: Self::max_log_files
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tracing_appender::rolling::RollingFileAppender;
+    /// use std::time::Duration;
+    ///
+    /// # fn docs() {
+    /// let appender = RollingFileAppender::builder()
+    ///     .max_file_age(Duration::from_secs(7 * 24 * 60 * 60)) // keep one week of logs
+    ///     // ...
+    ///     .build("/var/log")
+    ///     .expect("failed to initialize rolling file appender");
+    /// # drop(appender)
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn max_file_age(self, age: Duration) -> Self {
+        Self {
+            max_age: Some(age),
+            ..self
+        }
+    }
+
+    /// Limits the total size of log files on disk to `n` bytes.
+    ///
+    /// When a new log file is created, the oldest log files are deleted
+    /// until the remaining files fit within `n` bytes. Files are considered
+    /// candidates for deletion based on the same criteria as
+    /// [`max_log_files`]. If no value is supplied, there is no limit on the
+    /// total size of log files.
+    ///
+    /// [`max_log_files`]: Self::max_log_files
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tracing_appender::rolling::RollingFileAppender;
+    ///
+    /// # fn docs() {
+    /// let appender = RollingFileAppender::builder()
+    ///     .max_total_size(1024 * 1024 * 1024) // keep at most 1 GB of logs on disk
+    ///     // ...
+    ///     .build("/var/log")
+    ///     .expect("failed to initialize rolling file appender");
+    /// # drop(appender)
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn max_total_size(self, n: u64) -> Self {
+        Self {
+            max_total_size: Some(n),
+            ..self
+        }
+    }
+
+    /// Sets a template that controls how rotated log files are named,
+    /// overriding the default date-suffix naming scheme.
+    ///
+    /// The template is a string in which the following placeholders are
+    /// substituted when each log file is created:
+    ///
+    /// | Placeholder | Replaced with |
+    /// | :---------- | :------------ |
+    /// | `{prefix}` | The configured [`filename_prefix`], or nothing if unset. |
+    /// | `{suffix}` | The configured [`filename_suffix`], or nothing if unset. |
+    /// | `{index}` | The index of the file within its time period, starting at `0`. |
+    /// | `{date}` | The start of the time period the file covers. |
+    /// | `{now}` | The time at which the file was created. |
+    ///
+    /// `{date}` and `{now}` differ when a file is created partway through a
+    /// time period — for example, when the appender starts up, or when a
+    /// size limit forces an extra rotation: `{date}` is truncated to the
+    /// start of the period, while `{now}` is the actual rotation time. Both
+    /// accept an optional format in the `time` crate's [format description]
+    /// syntax, such as `{date:[year][month][day]}`; without one, the
+    /// rotation's default date format is used.
+    ///
+    /// All other characters in the template are copied into the file name
+    /// literally. [`Builder::build`] returns an error if the template
+    /// contains an unknown placeholder or an invalid date format.
+    ///
+    /// Note that the retention policies ([`max_log_files`], [`max_file_age`],
+    /// and [`max_total_size`]) identify old log files by the configured
+    /// prefix and suffix, so a template that does not begin with `{prefix}`
+    /// or end with `{suffix}` may exempt its files from pruning.
+    ///
+    /// [`filename_prefix`]: Self::filename_prefix
+    /// [`filename_suffix`]: Self::filename_suffix
+    /// [`max_log_files`]: Self::max_log_files
+    /// [`max_file_age`]: Self::max_file_age
+    /// [`max_total_size`]: Self::max_total_size
+    /// [format description]: https://time-rs.github.io/book/api/format-description.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tracing_appender::rolling::{RollingFileAppender, Rotation};
+    ///
+    /// # fn docs() {
+    /// let appender = RollingFileAppender::builder()
+    ///     .rotation(Rotation::DAILY)
+    ///     .filename_prefix("myapp")
+    ///     .filename_suffix("log")
+    ///     // log files will have names like "myapp-20190101.0.log"
+    ///     .filename_template("{prefix}-{date:[year][month][day]}.{index}.{suffix}")
+    ///     .build("/var/log")
+    ///     .expect("failed to initialize rolling file appender");
+    /// # drop(appender)
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn filename_template(self, template: impl Into<String>) -> Self {
+        Self {
+            template: Some(template.into()),
+            ..self
+        }
+    }
+
+    /// Flushes the log file after every `n` writes.
+    ///
+    /// Each call to [`write`] counts as one write; when the appender is used
+    /// through `tracing-subscriber`'s [`MakeWriter`] interface, that is one
+    /// formatted event. If a [`flush_interval`] is also configured, the file
+    /// is flushed when *either* threshold is reached.
+    ///
+    /// By default, flushing is left to the operating system, and data may be
+    /// lost if the process terminates abruptly. Combine this with
+    /// [`sync_on_flush`] for stronger durability guarantees.
+    ///
+    /// [`write`]: std::io::Write::write
+    /// [`MakeWriter`]: tracing_subscriber::fmt::writer::MakeWriter
+    /// [`flush_interval`]: Self::flush_interval
+    /// [`sync_on_flush`]: Self::sync_on_flush
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tracing_appender::rolling::RollingFileAppender;
+    ///
+    /// # fn docs() {
+    /// let appender = RollingFileAppender::builder()
+    ///     .flush_every(100) // flush the log file after every 100 events
+    ///     // ...
+    ///     .build("/var/log")
+    ///     .expect("failed to initialize rolling file appender");
+    /// # drop(appender)
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn flush_every(self, n: usize) -> Self {
+        Self {
+            // flushing after every 0 writes means flushing after every write.
+            flush_every: Some(n.max(1)),
+            ..self
+        }
+    }
+
+    /// Flushes the log file if at least `interval` has elapsed since the
+    /// last flush.
+    ///
+    /// The elapsed time is checked when events are written, so an idle
+    /// appender will not flush until its next write; to guarantee buffered
+    /// data reaches the disk at a specific point (such as shutdown), call
+    /// [`RollingFileAppender::flush`] or [`RollingFileAppender::sync`]
+    /// instead. If a [`flush_every`] count is also configured, the file is
+    /// flushed when *either* threshold is reached.
+    ///
+    /// By default, flushing is left to the operating system.
+    ///
+    /// [`flush_every`]: Self::flush_every
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tracing_appender::rolling::RollingFileAppender;
+    /// use std::time::Duration;
+    ///
+    /// # fn docs() {
+    /// let appender = RollingFileAppender::builder()
+    ///     .flush_interval(Duration::from_millis(500)) // flush at least every half-second
+    ///     // ...
+    ///     .build("/var/log")
+    ///     .expect("failed to initialize rolling file appender");
+    /// # drop(appender)
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn flush_interval(self, interval: Duration) -> Self {
+        Self {
+            flush_interval: Some(interval),
+            ..self
+        }
+    }
+
+    /// Synchronizes the log file to disk (`fsync`) as part of every flush.
+    ///
+    /// This applies to flushes triggered by [`flush_every`] and
+    /// [`flush_interval`], as well as explicit calls to
+    /// [`RollingFileAppender::flush`] or [`std::io::Write::flush`].
+    /// Syncing ensures the data has reached the physical disk rather than
+    /// the operating system's caches, at the cost of considerably slower
+    /// flushes; it is intended for audit-grade durability requirements.
+    ///
+    /// [`RollingFileAppender::sync`] always syncs the file, regardless of
+    /// this setting.
+    ///
+    /// By default, flushes do not sync the log file.
+    ///
+    /// [`flush_every`]: Self::flush_every
+    /// [`flush_interval`]: Self::flush_interval
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tracing_appender::rolling::RollingFileAppender;
+    ///
+    /// # fn docs() {
+    /// let appender = RollingFileAppender::builder()
+    ///     .flush_every(1) // flush after every event...
+    ///     .sync_on_flush(true) // ...and fsync as part of each flush
+    ///     // ...
+    ///     .build("/var/log")
+    ///     .expect("failed to initialize rolling file appender");
+    /// # drop(appender)
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn sync_on_flush(self, sync: bool) -> Self {
+        Self {
+            sync_on_flush: sync,
+            ..self
+        }
+    }
+
+    /// Coordinates rotation with other processes appending to the same log
+    /// files.
+    ///
+    /// When multiple processes (such as the workers of a prefork server)
+    /// write to the same rolling log files, each process tracks the current
+    /// file's size and rotation index independently, so size-based rotation
+    /// can race: processes may rotate to different files, or prune old logs
+    /// concurrently. With this option enabled, the appender instead:
+    ///
+    /// * holds an exclusive advisory lock on a `.tracing-appender.lock`
+    ///   file in the log directory while rotating, so rotation and pruning
+    ///   run in one process at a time;
+    /// * re-checks the log files on disk when rotating, so all processes
+    ///   converge on the same sequence of files rather than each creating
+    ///   its own;
+    /// * tracks the current file's size from the file's on-disk metadata
+    ///   rather than a process-local counter, at the cost of a metadata
+    ///   query per write.
+    ///
+    /// Log files are always opened in append mode, and each formatted event
+    /// is written with a single `write` call, so concurrent writers never
+    /// interleave within a single record.
+    ///
+    /// Advisory locking is currently only implemented on Unix-like
+    /// platforms. On other platforms rotation remains safe — all processes
+    /// converge on the same file names — but old log files may be pruned by
+    /// more than one process at a time.
+    ///
+    /// By default, this is `false`, and a single process is assumed to own
+    /// the log files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tracing_appender::rolling::{RollingFileAppender, Rotation};
+    ///
+    /// # fn docs() {
+    /// let appender = RollingFileAppender::builder()
+    ///     .rotation(Rotation::size(100 * 1024 * 1024))
+    ///     .filename_prefix("myapp.log")
+    ///     .multi_process(true) // other processes append to the same files
+    ///     .build("/var/log")
+    ///     .expect("failed to initialize rolling file appender");
+    /// # drop(appender)
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn multi_process(self, multi_process: bool) -> Self {
+        Self {
+            multi_process,
+            ..self
+        }
+    }
+
+    /// Reopens the log file when the process receives `SIGHUP`.
+    ///
+    /// Deployments that rotate log files with an external tool such as
+    /// `logrotate` typically rename the current log file and then send the
+    /// process a `SIGHUP`. Without special handling, the appender would keep
+    /// writing to the renamed file forever. With this option enabled, the
+    /// appender installs a `SIGHUP` handler, and recreates the file at the
+    /// path it expects on the first write after a signal is received.
+    ///
+    /// Because the reopen happens on the next write, a completely idle
+    /// appender holds the renamed file open until it writes again.
+    /// Applications that integrate their own signal handling can instead
+    /// call [`RollingFileAppender::reopen`] directly.
+    ///
+    /// **Note**: the handler installed by this option replaces any `SIGHUP`
+    /// handler previously installed by the process, and is shared by all
+    /// appenders configured with this option. This option has no effect on
+    /// non-Unix platforms, which have no `SIGHUP` signal.
+    ///
+    /// By default, no `SIGHUP` handler is installed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tracing_appender::rolling::RollingFileAppender;
+    ///
+    /// # fn docs() {
+    /// let appender = RollingFileAppender::builder()
+    ///     .filename_prefix("myapp.log")
+    ///     .reopen_on_sighup(true) // follow external logrotate-style rotation
+    ///     .build("/var/log")
+    ///     .expect("failed to initialize rolling file appender");
+    /// # drop(appender)
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn reopen_on_sighup(self, reopen: bool) -> Self {
+        Self {
+            reopen_on_sighup: reopen,
+            ..self
+        }
+    }
+
     /// Builds a new [`RollingFileAppender`] with the configured parameters,
     /// emitting log files to the provided directory.
     ///
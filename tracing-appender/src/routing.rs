@@ -0,0 +1,588 @@
+//! An appender that routes events to different log files.
+//!
+//! The [`RoutingFileAppender`] writes each event to a file selected by a key
+//! derived from the event — its target, or the value of a designated field —
+//! rather than writing everything to a single file. A [`RollingFileAppender`]
+//! is created lazily for each distinct key, so every routed file gets the
+//! full set of rolling features: rotation, size limits, retention, and flush
+//! control. A bounded LRU cache of open appenders keeps the number of open
+//! file handles under control, and routing keys are sanitized before they
+//! are used in file names, so untrusted event data cannot escape the log
+//! directory.
+//!
+//! # Examples
+//!
+//! Writing each job's events to its own daily-rotating log file:
+//!
+//! ```rust
+//! # fn docs() {
+//! use tracing_appender::rolling;
+//! use tracing_appender::routing::RoutingFileAppender;
+//!
+//! let appender = RoutingFileAppender::builder()
+//!     .route_by_field("job_id") // one file per `job_id` value
+//!     .appender(
+//!         rolling::Builder::new()
+//!             .rotation(rolling::Rotation::DAILY)
+//!             .filename_suffix("log"),
+//!     )
+//!     .build("/var/log/jobs")
+//!     .expect("failed to initialize routing file appender");
+//!
+//! let collector = tracing_subscriber::fmt().with_writer(appender);
+//! tracing::collect::with_default(collector.finish(), || {
+//!     // written to `/var/log/jobs/job-42.<date>.log`:
+//!     tracing::info!(job_id = "job-42", "spawned");
+//!     // written to `/var/log/jobs/unknown.<date>.log`:
+//!     tracing::info!("no job here");
+//! });
+//! # }
+//! ```
+use crate::rolling::{self, InitError, RollingFileAppender};
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+use tracing_core::{field, Event, Metadata};
+use tracing_subscriber::fmt::writer::MakeWriter;
+
+/// A [`MakeWriter`] that routes events to per-key [`RollingFileAppender`]s.
+///
+/// The routing key is derived from each event — by default its target, or
+/// the value of a designated field (see [`Builder::route_by_field`]). The
+/// first time a key is seen, a [`RollingFileAppender`] is created for it,
+/// configured by the [`Builder::appender`] template with the sanitized key
+/// as its filename prefix; subsequent events with the same key reuse the
+/// cached appender. When more than [`Builder::max_open_files`] appenders are
+/// open, the least recently used one is closed (and transparently reopened
+/// if its key is seen again).
+///
+/// Events from which no key can be derived — as well as output that is not
+/// associated with an event, such as synthesized span lifecycle output —
+/// are written to the `"unknown"` file.
+///
+/// # Examples
+///
+/// Splitting a shared log by subsystem, using event targets as keys:
+///
+/// ```rust
+/// # fn docs() {
+/// use tracing_appender::routing::RoutingFileAppender;
+/// use tracing_appender::rolling;
+///
+/// let appender = RoutingFileAppender::builder()
+///     .filename_prefix("myapp")
+///     .appender(rolling::Builder::new().filename_suffix("log"))
+///     .build("/var/log/myapp")
+///     .expect("failed to initialize routing file appender");
+///
+/// let collector = tracing_subscriber::fmt().with_writer(appender);
+/// tracing::collect::with_default(collector.finish(), || {
+///     // written to `/var/log/myapp/myapp.billing.log`:
+///     tracing::info!(target: "billing", "invoice created");
+/// });
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RoutingFileAppender {
+    directory: PathBuf,
+    prefix: Option<String>,
+    appender_config: rolling::Builder,
+    key: RouteKey,
+    max_open_files: usize,
+    routes: Mutex<Routes>,
+}
+
+/// A [builder] for configuring [`RoutingFileAppender`]s.
+///
+/// [builder]: https://rust-unofficial.github.io/patterns/patterns/creational/builder.html
+#[derive(Debug)]
+pub struct Builder {
+    prefix: Option<String>,
+    appender: rolling::Builder,
+    key: RouteKey,
+    max_open_files: usize,
+}
+
+/// A [writer] that writes to one of a [`RoutingFileAppender`]'s log files.
+///
+/// [writer]: std::io::Write
+#[derive(Debug)]
+pub struct RouteWriter {
+    // `None` if the appender for this writer's key could not be created; in
+    // that case, writes are silently discarded.
+    appender: Option<Arc<RollingFileAppender>>,
+}
+
+/// How a [`RoutingFileAppender`] derives the routing key from an event.
+#[derive(Debug)]
+enum RouteKey {
+    /// Route on the event's target.
+    Target,
+    /// Route on the value of the named field.
+    Field(String),
+}
+
+/// The cache of per-key appenders, in least-recently-used order.
+#[derive(Debug)]
+struct Routes {
+    entries: HashMap<String, Route>,
+    /// A logical clock, bumped on every cache access, used to find the
+    /// least recently used entry.
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Route {
+    appender: Arc<RollingFileAppender>,
+    last_used: u64,
+}
+
+/// The routing key used when no key can be derived from an event.
+const FALLBACK_KEY: &str = "unknown";
+
+/// The maximum length of a sanitized routing key, in characters.
+const MAX_KEY_LEN: usize = 64;
+
+// === impl RoutingFileAppender ===
+
+impl RoutingFileAppender {
+    /// Returns a new [`Builder`] for configuring a `RoutingFileAppender`.
+    #[must_use]
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    /// Returns a [writer] that writes to the log file for `key`.
+    ///
+    /// The key is sanitized in the same way as keys derived from events.
+    /// This can be used to route output whose key is computed by the caller,
+    /// rather than derived from an event's target or fields.
+    ///
+    /// [writer]: std::io::Write
+    pub fn writer_for(&self, key: &str) -> RouteWriter {
+        RouteWriter {
+            appender: self.appender_for(key),
+        }
+    }
+
+    /// Returns the cached appender for `key`, creating it if necessary.
+    fn appender_for(&self, key: &str) -> Option<Arc<RollingFileAppender>> {
+        let key = sanitize_key(key);
+        let mut routes = self.routes.lock().unwrap_or_else(PoisonError::into_inner);
+        routes.clock += 1;
+        let clock = routes.clock;
+        if let Some(route) = routes.entries.get_mut(&key) {
+            route.last_used = clock;
+            return Some(route.appender.clone());
+        }
+
+        let prefix = match &self.prefix {
+            Some(prefix) => format!("{}.{}", prefix, key),
+            None => key.clone(),
+        };
+        let appender = match self
+            .appender_config
+            .clone()
+            .filename_prefix(prefix)
+            .build(&self.directory)
+        {
+            Ok(appender) => Arc::new(appender),
+            Err(error) => {
+                eprintln!(
+                    "Couldn't create log file for routing key {:?}: {}",
+                    key, error
+                );
+                return None;
+            }
+        };
+
+        // Close the least recently used appenders to stay within the limit
+        // on open file handles. A closed appender is reopened (in append
+        // mode) if its key is seen again, so no data is lost.
+        while routes.entries.len() >= self.max_open_files {
+            let oldest = routes
+                .entries
+                .iter()
+                .min_by_key(|(_, route)| route.last_used)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(oldest) => {
+                    routes.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+        routes.entries.insert(
+            key,
+            Route {
+                appender: appender.clone(),
+                last_used: clock,
+            },
+        );
+        Some(appender)
+    }
+
+    /// Derives the routing key from an event and returns its writer.
+    fn writer_for_event(&self, event: &Event<'_>) -> RouteWriter {
+        match &self.key {
+            RouteKey::Target => self.writer_for(&target_key(event.metadata().target())),
+            RouteKey::Field(name) => {
+                let mut visitor = RoutingKeyVisitor {
+                    field: name.as_str(),
+                    value: None,
+                };
+                event.record(&mut visitor);
+                self.writer_for(visitor.value.as_deref().unwrap_or(FALLBACK_KEY))
+            }
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for RoutingFileAppender {
+    type Writer = RouteWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        // without an event, there is nothing to derive a key from.
+        self.writer_for(FALLBACK_KEY)
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        match &self.key {
+            RouteKey::Target => self.writer_for(&target_key(meta.target())),
+            // field values are not part of an event's metadata.
+            RouteKey::Field(_) => self.writer_for(FALLBACK_KEY),
+        }
+    }
+
+    fn make_writer_for_event(&'a self, event: &Event<'_>) -> Self::Writer {
+        self.writer_for_event(event)
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Returns a new `Builder` for configuring a [`RoutingFileAppender`],
+    /// with the default parameters.
+    ///
+    /// # Default Values
+    ///
+    /// The default values for the builder are:
+    ///
+    /// | Parameter | Default Value | Notes |
+    /// | :-------- | :------------ | :---- |
+    /// | [`route_by_target`] | — | By default, events are routed on their targets. |
+    /// | [`filename_prefix`] | `""` | By default, file names start with the routing key. |
+    /// | [`appender`] | [`rolling::Builder::new`] | By default, routed files are never rotated. |
+    /// | [`max_open_files`] | `128` | The maximum number of concurrently open log files. |
+    ///
+    /// [`route_by_target`]: Self::route_by_target
+    /// [`filename_prefix`]: Self::filename_prefix
+    /// [`appender`]: Self::appender
+    /// [`max_open_files`]: Self::max_open_files
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            prefix: None,
+            appender: rolling::Builder::new(),
+            key: RouteKey::Target,
+            max_open_files: 128,
+        }
+    }
+
+    /// Routes events on their targets.
+    ///
+    /// Each distinct target is written to its own log file; the `::`
+    /// separators in module-path targets are replaced with `-` in file
+    /// names. This is the default.
+    ///
+    /// **Note**: unless events are recorded with explicit targets (such as
+    /// `info!(target: "billing", ...)`), the target defaults to the module
+    /// path where the event occurred, which may produce one log file per
+    /// module.
+    #[must_use]
+    pub fn route_by_target(self) -> Self {
+        Self {
+            key: RouteKey::Target,
+            ..self
+        }
+    }
+
+    /// Routes events on the value of the field named `field`.
+    ///
+    /// Each distinct value of the field is written to its own log file.
+    /// String values are used as recorded; values of other types are
+    /// formatted with their [`fmt::Debug`] implementations. Events that do
+    /// not record the field — as well as output that is not associated with
+    /// an event, such as synthesized span lifecycle output — are written to
+    /// the `"unknown"` file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tracing_appender::routing::RoutingFileAppender;
+    ///
+    /// # fn docs() {
+    /// let appender = RoutingFileAppender::builder()
+    ///     .route_by_field("job_id") // one log file per `job_id` value
+    ///     .build("/var/log/jobs")
+    ///     .expect("failed to initialize routing file appender");
+    /// # drop(appender)
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn route_by_field(self, field: impl Into<String>) -> Self {
+        Self {
+            key: RouteKey::Field(field.into()),
+            ..self
+        }
+    }
+
+    /// Sets a common prefix for all routed log files.
+    ///
+    /// If a prefix is set, file names take the form `<prefix>.<key>`, with
+    /// any suffix or date configured by the [`appender`] template appended
+    /// after that; otherwise file names start with the routing key.
+    ///
+    /// [`appender`]: Self::appender
+    #[must_use]
+    pub fn filename_prefix(self, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        // If the configured prefix is the empty string, then don't include a
+        // separator character.
+        let prefix = if prefix.is_empty() {
+            None
+        } else {
+            Some(prefix)
+        };
+        Self { prefix, ..self }
+    }
+
+    /// Sets the [`rolling::Builder`] used to configure the appender created
+    /// for each routing key.
+    ///
+    /// This controls the rotation schedule, filename suffix, size limits,
+    /// retention, and flushing of every routed log file. The template's own
+    /// filename prefix is ignored; each appender's prefix is the routing
+    /// key (combined with [`filename_prefix`], if one is set).
+    ///
+    /// By default, routed files are never rotated.
+    ///
+    /// [`filename_prefix`]: Self::filename_prefix
+    #[must_use]
+    pub fn appender(self, appender: rolling::Builder) -> Self {
+        Self { appender, ..self }
+    }
+
+    /// Limits the number of concurrently open log files to `n`.
+    ///
+    /// When an event's key would open a file beyond this limit, the least
+    /// recently used file is closed first. A closed file is reopened in
+    /// append mode if its key is seen again, so no data is lost.
+    ///
+    /// By default, up to 128 files are kept open.
+    #[must_use]
+    pub fn max_open_files(self, n: usize) -> Self {
+        Self {
+            // a cache of zero files would thrash on every write.
+            max_open_files: n.max(1),
+            ..self
+        }
+    }
+
+    /// Builds a new [`RoutingFileAppender`] with the configured parameters,
+    /// emitting log files to the provided directory.
+    ///
+    /// The individual log files are created lazily, as routing keys are
+    /// seen; this only creates the directory itself.
+    pub fn build(&self, directory: impl AsRef<Path>) -> Result<RoutingFileAppender, InitError> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory).map_err(InitError::ctx("failed to create log directory"))?;
+        Ok(RoutingFileAppender {
+            directory,
+            prefix: self.prefix.clone(),
+            appender_config: self.appender.clone(),
+            key: match &self.key {
+                RouteKey::Target => RouteKey::Target,
+                RouteKey::Field(field) => RouteKey::Field(field.clone()),
+            },
+            max_open_files: self.max_open_files,
+            routes: Mutex::new(Routes {
+                entries: HashMap::new(),
+                clock: 0,
+            }),
+        })
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// === impl RouteWriter ===
+
+impl io::Write for RouteWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &self.appender {
+            Some(appender) => appender.make_writer().write(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &self.appender {
+            Some(appender) => appender.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Extracts the value of the field a [`RoutingFileAppender`] routes on.
+struct RoutingKeyVisitor<'a> {
+    field: &'a str,
+    value: Option<String>,
+}
+
+impl field::Visit for RoutingKeyVisitor<'_> {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == self.field {
+            self.value = Some(value.to_owned());
+        }
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn fmt::Debug) {
+        if field.name() == self.field {
+            self.value = Some(format!("{:?}", value));
+        }
+    }
+}
+
+/// Converts an event's target into a routing key.
+fn target_key(target: &str) -> String {
+    // module-path separators would otherwise be sanitized to `__`.
+    target.replace("::", "-")
+}
+
+/// Replaces characters that are unsafe in file names, so that routing keys
+/// derived from event data cannot escape the log directory or produce
+/// invalid file names.
+fn sanitize_key(key: &str) -> String {
+    let sanitized: String = key
+        .chars()
+        .take(MAX_KEY_LEN)
+        .map(|ch| match ch {
+            ch if ch.is_ascii_alphanumeric() => ch,
+            '-' | '_' | '.' => ch,
+            _ => '_',
+        })
+        .collect();
+    // a name of leading dots (such as `..`) could traverse out of the log
+    // directory.
+    let sanitized = sanitized.trim_start_matches('.');
+    if sanitized.is_empty() {
+        FALLBACK_KEY.to_owned()
+    } else {
+        sanitized.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_sanitize_key() {
+        assert_eq!(sanitize_key("job-42"), "job-42");
+        assert_eq!(sanitize_key("tmp/../../etc/passwd"), "tmp_.._.._etc_passwd");
+        assert_eq!(sanitize_key("../escape"), "_escape");
+        assert_eq!(sanitize_key("..."), "unknown");
+        assert_eq!(sanitize_key(""), "unknown");
+        assert_eq!(sanitize_key("a b\tc"), "a_b_c");
+        // long keys are truncated.
+        let long = "x".repeat(MAX_KEY_LEN + 10);
+        assert_eq!(sanitize_key(&long).len(), MAX_KEY_LEN);
+    }
+
+    #[test]
+    fn test_route_by_target() {
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let appender = RoutingFileAppender::builder()
+            .appender(rolling::Builder::new().filename_suffix("log"))
+            .build(directory.path())
+            .expect("failed to build appender");
+
+        let collector = tracing_subscriber::fmt().with_writer(appender).finish();
+        tracing::collect::with_default(collector, || {
+            tracing::info!(target: "alpha", "to alpha");
+            tracing::info!(target: "beta", "to beta");
+        });
+
+        let alpha = fs::read_to_string(directory.path().join("alpha.log"))
+            .expect("failed to read alpha.log");
+        assert!(alpha.contains("to alpha"), "{:?}", alpha);
+        let beta =
+            fs::read_to_string(directory.path().join("beta.log")).expect("failed to read beta.log");
+        assert!(beta.contains("to beta"), "{:?}", beta);
+    }
+
+    #[test]
+    fn test_route_by_field() {
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let appender = RoutingFileAppender::builder()
+            .route_by_field("job_id")
+            .appender(rolling::Builder::new().filename_suffix("log"))
+            .build(directory.path())
+            .expect("failed to build appender");
+
+        let collector = tracing_subscriber::fmt().with_writer(appender).finish();
+        tracing::collect::with_default(collector, || {
+            tracing::info!(job_id = "job-1", "first job");
+            tracing::info!(job_id = "job-2", "second job");
+            tracing::info!("no job");
+        });
+
+        let first = fs::read_to_string(directory.path().join("job-1.log"))
+            .expect("failed to read job-1.log");
+        assert!(first.contains("first job"), "{:?}", first);
+        let second = fs::read_to_string(directory.path().join("job-2.log"))
+            .expect("failed to read job-2.log");
+        assert!(second.contains("second job"), "{:?}", second);
+        let unknown = fs::read_to_string(directory.path().join("unknown.log"))
+            .expect("failed to read unknown.log");
+        assert!(unknown.contains("no job"), "{:?}", unknown);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let directory = tempfile::tempdir().expect("failed to create tempdir");
+        let appender = RoutingFileAppender::builder()
+            .max_open_files(2)
+            .build(directory.path())
+            .expect("failed to build appender");
+
+        let mut writers = 0;
+        for key in ["a", "b", "c"] {
+            writers += 1;
+            writeln!(appender.writer_for(key), "line {}", writers).expect("failed to write");
+        }
+        // only the two most recently used appenders remain open...
+        let routes = appender.routes.lock().expect("lock poisoned");
+        assert_eq!(routes.entries.len(), 2, "{:?}", routes.entries.keys());
+        assert!(routes.entries.contains_key("b"));
+        assert!(routes.entries.contains_key("c"));
+        drop(routes);
+
+        // ...but the evicted file is still on disk, and is reopened (in
+        // append mode) when its key is seen again.
+        writeln!(appender.writer_for("a"), "line 4").expect("failed to write");
+        let contents =
+            fs::read_to_string(directory.path().join("a")).expect("failed to read evicted file");
+        assert_eq!(contents, "line 1\nline 4\n");
+    }
+}
@@ -0,0 +1,498 @@
+//! A non-blocking writer backed by a task on a [`tokio`] runtime.
+//!
+//! This module provides an alternative to the thread-based writer in the
+//! [`non_blocking` module][mod@crate::non_blocking] for applications that
+//! already run a `tokio` runtime. Instead of spawning a dedicated OS thread,
+//! log lines are forwarded over a channel to a worker _task_ that writes them
+//! to any [`AsyncWrite`] sink — a file, a TCP or Unix socket, or anything else
+//! that implements the trait. This avoids the extra thread, and allows logs to
+//! be shipped over network connections with the sink's own backpressure.
+//!
+//! This module requires the `tokio` feature flag, which is not enabled by
+//! default:
+//!
+//! ```toml
+//! tracing-appender = { version = "0.2", features = ["tokio"] }
+//! ```
+//!
+//! The returned [`NonBlocking`] implements [`MakeWriter`], so it plugs into
+//! `tracing_subscriber::fmt` exactly like its thread-based counterpart.
+//! Note that the worker task is spawned with [`tokio::spawn`], so
+//! [`NonBlocking::new`] and [`NonBlockingBuilder::finish`] must be called from
+//! within a runtime.
+//!
+//! # Examples
+//!
+//! Shipping formatted logs over a TCP connection:
+//!
+//! ```rust
+//! # async fn docs() -> std::io::Result<()> {
+//! let stream = tokio::net::TcpStream::connect("127.0.0.1:9000").await?;
+//! let (non_blocking, _guard) = tracing_appender::tokio::NonBlocking::new(stream);
+//! tracing_subscriber::fmt()
+//!     .with_writer(non_blocking)
+//!     .init();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! As with the thread-based writer, the returned [`WorkerGuard`] must be held
+//! for as long as logs should keep flowing; dropping it shuts the worker task
+//! down after flushing any buffered lines.
+//!
+//! # Backpressure
+//!
+//! By default the writer is lossy: if lines are produced faster than the sink
+//! accepts them and the buffer fills up, new lines are dropped and counted by
+//! the [`ErrorCounter`]. Calling [`NonBlockingBuilder::lossy`]`(false)`
+//! instead makes writers wait for buffer capacity. Since spans and events are
+//! written from synchronous [`io::Write`] contexts, this waiting blocks the
+//! current OS thread; on a `current_thread` runtime that would prevent the
+//! worker task from draining the buffer at all, so a non-lossy writer should
+//! only be used with a multi-threaded runtime.
+//!
+//! The [`Policy::DropOldest`] policy offered by the thread-based writer is not
+//! supported here, as the underlying channel does not allow writers to discard
+//! buffered lines.
+//!
+//! [`MakeWriter`]: tracing_subscriber::fmt::MakeWriter
+//! [`Policy::DropOldest`]: crate::non_blocking::Policy::DropOldest
+use crate::non_blocking::{ErrorCounter, Policy, DEFAULT_BUFFERED_LINES_LIMIT};
+use crate::Msg;
+use crossbeam_channel::RecvTimeoutError;
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc::{channel, error::TrySendError, Receiver, Sender};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// A non-blocking writer that forwards lines to a task on a `tokio` runtime.
+///
+/// This is the async counterpart of [`non_blocking::NonBlocking`]: writes
+/// enqueue the line on a channel and return immediately, and a worker task
+/// writes the queued lines to the underlying [`AsyncWrite`] sink. It
+/// implements [`MakeWriter`], so it can be passed to
+/// [`tracing_subscriber::fmt`][fmt] like any other writer.
+///
+/// [`non_blocking::NonBlocking`]: crate::non_blocking::NonBlocking
+/// [`MakeWriter`]: tracing_subscriber::fmt::MakeWriter
+/// [fmt]: mod@tracing_subscriber::fmt
+#[derive(Clone, Debug)]
+pub struct NonBlocking {
+    error_counter: ErrorCounter,
+    channel: Sender<Msg>,
+    policy: Policy,
+}
+
+/// A guard that shuts down the worker task when dropped.
+///
+/// Like the thread-based [`non_blocking::WorkerGuard`], this should be held in
+/// `main` (or wherever the runtime outlives the logging) so that buffered
+/// lines are flushed when the program terminates. Dropping the guard blocks
+/// the current thread briefly while the worker task flushes and shuts down the
+/// sink; prefer dropping it from outside the runtime, or from a
+/// multi-threaded runtime.
+///
+/// If the runtime is shut down before the guard is dropped, the worker task is
+/// cancelled and any lines still buffered are lost.
+///
+/// [`non_blocking::WorkerGuard`]: crate::non_blocking::WorkerGuard
+#[must_use]
+#[derive(Debug)]
+pub struct WorkerGuard {
+    sender: Sender<Msg>,
+    // Held by the worker task until it completes; `recv` on this channel
+    // returning `Disconnected` means the worker has flushed and shut down.
+    done: crossbeam_channel::Receiver<()>,
+    error_counter: ErrorCounter,
+}
+
+/// A builder for [`NonBlocking`].
+#[derive(Debug)]
+pub struct NonBlockingBuilder {
+    buffered_lines_limit: usize,
+    policy: Policy,
+}
+
+// === impl NonBlocking ===
+
+impl NonBlocking {
+    /// Returns a new `NonBlocking` writer wrapping the provided `writer`.
+    ///
+    /// The returned `NonBlocking` writer will have the default configuration
+    /// values. Other configurations can be specified using the
+    /// [builder][NonBlockingBuilder] interface.
+    ///
+    /// # Panics
+    ///
+    /// This spawns the worker task with [`tokio::spawn`], and therefore panics
+    /// if called outside of a `tokio` runtime.
+    pub fn new<T: AsyncWrite + Unpin + Send + 'static>(writer: T) -> (NonBlocking, WorkerGuard) {
+        NonBlockingBuilder::default().finish(writer)
+    }
+
+    fn create<T: AsyncWrite + Unpin + Send + 'static>(
+        writer: T,
+        buffered_lines_limit: usize,
+        policy: Policy,
+    ) -> (NonBlocking, WorkerGuard) {
+        let (sender, receiver) = channel(buffered_lines_limit);
+        let (done_sender, done_receiver) = crossbeam_channel::bounded(0);
+
+        let error_counter = ErrorCounter::new();
+        tokio::spawn(worker(writer, receiver, done_sender));
+
+        let worker_guard = WorkerGuard {
+            sender: sender.clone(),
+            done: done_receiver,
+            error_counter: error_counter.clone(),
+        };
+
+        (
+            Self {
+                channel: sender,
+                error_counter,
+                policy,
+            },
+            worker_guard,
+        )
+    }
+
+    /// Returns a counter for the number of times logs where dropped. This will always return zero if
+    /// `NonBlocking` is not lossy.
+    pub fn error_counter(&self) -> ErrorCounter {
+        self.error_counter.clone()
+    }
+
+    /// Blocks until all log lines enqueued before this call have been written
+    /// and flushed to the underlying writer, or until `timeout` elapses.
+    ///
+    /// This is the async writer's equivalent of
+    /// [`non_blocking::NonBlocking::flush_timeout`]. Note that it blocks the
+    /// current OS thread while waiting, so it should not be called from within
+    /// a `current_thread` runtime.
+    ///
+    /// [`non_blocking::NonBlocking::flush_timeout`]: crate::non_blocking::NonBlocking::flush_timeout
+    pub fn flush_timeout(&self, timeout: Duration) -> io::Result<()> {
+        flush_timeout(&self.channel, timeout)
+    }
+}
+
+impl io::Write for NonBlocking {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let buf_size = buf.len();
+        match self.policy {
+            Policy::Block => {
+                let mut msg = Msg::Line(buf.to_vec());
+                loop {
+                    match self.channel.try_send(msg) {
+                        Ok(()) => break,
+                        // the worker task has terminated; there is nowhere
+                        // for this line to go.
+                        Err(TrySendError::Closed(_)) => {
+                            return Err(io::Error::from(io::ErrorKind::Other))
+                        }
+                        // `Sender::blocking_send` panics when called from
+                        // within a runtime, and writes typically happen on
+                        // runtime threads, so block by polling instead.
+                        Err(TrySendError::Full(returned)) => {
+                            msg = returned;
+                            thread::sleep(Duration::from_micros(100));
+                        }
+                    }
+                }
+            }
+            // `DropOldest` is rejected by the builder, so any other policy
+            // drops the newest line when the buffer is full.
+            _ => {
+                if self.channel.try_send(Msg::Line(buf.to_vec())).is_err() {
+                    self.error_counter.incr_dropped_newest();
+                }
+            }
+        }
+        Ok(buf_size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.write(buf).map(|_| ())
+    }
+}
+
+impl<'a> MakeWriter<'a> for NonBlocking {
+    type Writer = NonBlocking;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+// === impl NonBlockingBuilder ===
+
+impl NonBlockingBuilder {
+    /// Sets the number of lines to buffer before dropping logs or exerting backpressure on senders
+    pub fn buffered_lines_limit(mut self, buffered_lines_limit: usize) -> NonBlockingBuilder {
+        self.buffered_lines_limit = buffered_lines_limit;
+        self
+    }
+
+    /// Sets whether `NonBlocking` should be lossy or not.
+    ///
+    /// If set to `true`, logs will be dropped when the buffered limit is
+    /// reached. If `false`, writers block the current OS thread until the
+    /// buffer has capacity again; see the [module docs][backpressure] for why
+    /// this requires a multi-threaded runtime.
+    ///
+    /// By default, the built `NonBlocking` will be lossy.
+    ///
+    /// [backpressure]: self#backpressure
+    pub fn lossy(mut self, is_lossy: bool) -> NonBlockingBuilder {
+        self.policy = if is_lossy {
+            Policy::DropNewest
+        } else {
+            Policy::Block
+        };
+        self
+    }
+
+    /// Completes the builder, returning the configured `NonBlocking`.
+    ///
+    /// # Panics
+    ///
+    /// This spawns the worker task with [`tokio::spawn`], and therefore panics
+    /// if called outside of a `tokio` runtime.
+    pub fn finish<T: AsyncWrite + Unpin + Send + 'static>(
+        self,
+        writer: T,
+    ) -> (NonBlocking, WorkerGuard) {
+        NonBlocking::create(writer, self.buffered_lines_limit, self.policy)
+    }
+}
+
+impl Default for NonBlockingBuilder {
+    fn default() -> Self {
+        NonBlockingBuilder {
+            buffered_lines_limit: DEFAULT_BUFFERED_LINES_LIMIT,
+            policy: Policy::DropNewest,
+        }
+    }
+}
+
+// === impl WorkerGuard ===
+
+impl WorkerGuard {
+    /// Returns a counter for the number of log lines that have been dropped.
+    ///
+    /// This is equivalent to [`NonBlocking::error_counter`], for use when
+    /// only the guard is kept around.
+    pub fn error_counter(&self) -> ErrorCounter {
+        self.error_counter.clone()
+    }
+
+    /// Blocks until all log lines enqueued before this call have been written
+    /// and flushed to the underlying writer, or until `timeout` elapses.
+    ///
+    /// This is equivalent to [`NonBlocking::flush_timeout`], for use when only
+    /// the guard is kept around.
+    pub fn flush_timeout(&self, timeout: Duration) -> io::Result<()> {
+        flush_timeout(&self.sender, timeout)
+    }
+}
+
+impl Drop for WorkerGuard {
+    fn drop(&mut self) {
+        // Enqueue the shutdown request, making room by waiting briefly if the
+        // buffer is full.
+        let deadline = Instant::now() + Duration::from_millis(100);
+        let mut msg = Msg::Shutdown;
+        loop {
+            match self.sender.try_send(msg) {
+                Ok(()) => break,
+                // the worker task has already terminated.
+                Err(TrySendError::Closed(_)) => return,
+                Err(TrySendError::Full(returned)) => {
+                    if Instant::now() >= deadline {
+                        eprintln!(
+                            "Sending shutdown signal to logging worker timed out after 100ms"
+                        );
+                        return;
+                    }
+                    msg = returned;
+                    thread::sleep(Duration::from_micros(100));
+                }
+            }
+        }
+        // Wait for the worker task to flush and drop its end of the `done`
+        // channel, with a timeout so that drop is not blocked indefinitely.
+        let timeout = Duration::from_millis(1000);
+        if let Err(RecvTimeoutError::Timeout) = self.done.recv_timeout(timeout) {
+            eprintln!(
+                "Shutting down logging worker timed out after {:?}.",
+                timeout
+            );
+        }
+    }
+}
+
+// === worker task ===
+
+/// Writes queued lines to the sink until shut down.
+///
+/// The `_done` sender is dropped when this task completes, which is how
+/// [`WorkerGuard`] observes that the final flush has happened.
+async fn worker<T: AsyncWrite + Unpin + Send + 'static>(
+    mut writer: T,
+    mut receiver: Receiver<Msg>,
+    _done: crossbeam_channel::Sender<()>,
+) {
+    while let Some(msg) = receiver.recv().await {
+        match msg {
+            Msg::Line(line) => {
+                // TODO: Expose a metric for IO Errors, or print to stderr
+                if writer.write_all(&line).await.is_err() {
+                    break;
+                }
+            }
+            Msg::Flush(ack) => {
+                let _ = writer.flush().await;
+                let _ = ack.send(());
+            }
+            Msg::Shutdown => break,
+        }
+    }
+    // All senders are gone or a shutdown was requested; flush what was
+    // written and close the sink (for sockets, this shuts down the write
+    // half of the connection).
+    let _ = writer.flush().await;
+    let _ = writer.shutdown().await;
+}
+
+/// Enqueues a flush request and waits for the worker task to acknowledge
+/// it. Since the worker processes messages in order, the acknowledgement
+/// implies that all previously enqueued lines have been written.
+fn flush_timeout(sender: &Sender<Msg>, timeout: Duration) -> io::Result<()> {
+    let deadline = Instant::now() + timeout;
+    let (ack, acked) = crossbeam_channel::bounded(1);
+    let mut msg = Msg::Flush(ack);
+    loop {
+        match sender.try_send(msg) {
+            Ok(()) => break,
+            Err(TrySendError::Closed(_)) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "the logging worker task has terminated",
+                ))
+            }
+            Err(TrySendError::Full(returned)) => {
+                if Instant::now() >= deadline {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "timed out enqueueing the flush request",
+                    ));
+                }
+                msg = returned;
+                thread::sleep(Duration::from_micros(100));
+            }
+        }
+    }
+    match acked.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(()) => Ok(()),
+        Err(RecvTimeoutError::Timeout) => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "timed out waiting for queued output to be written",
+        )),
+        Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(
+            io::ErrorKind::Other,
+            "the logging worker task has terminated",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn lines_are_forwarded_to_the_sink() {
+        let (client, mut server) = tokio::io::duplex(4096);
+
+        let (mut non_blocking, _guard) = NonBlocking::new(client);
+
+        non_blocking.write_all(b"Hello\n").expect("Failed to write");
+
+        let mut buf = [0u8; 6];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"Hello\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn flush_timeout_flushes_queued_lines() {
+        let (client, mut server) = tokio::io::duplex(4096);
+
+        let (mut non_blocking, _guard) = NonBlockingBuilder::default().finish(client);
+
+        non_blocking.write_all(b"Hello\n").expect("Failed to write");
+        non_blocking
+            .flush_timeout(Duration::from_secs(5))
+            .expect("Failed to flush");
+
+        // Since the flush has been acknowledged, the line must already have
+        // been written to the duplex stream's buffer.
+        let mut buf = [0u8; 6];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"Hello\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn shutdown_flushes_and_closes_the_sink() {
+        let (client, mut server) = tokio::io::duplex(4096);
+
+        let (mut non_blocking, guard) = NonBlocking::new(client);
+
+        non_blocking.write_all(b"Hello\n").expect("Failed to write");
+        drop(guard);
+
+        // The guard has waited for the worker task, so the line has been
+        // written and the write half of the stream has been shut down.
+        let mut buf = Vec::new();
+        server.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"Hello\n");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn multi_threaded_writes() {
+        let (client, mut server) = tokio::io::duplex(64 * 1024);
+
+        let (non_blocking, guard) = NonBlockingBuilder::default().lossy(true).finish(client);
+
+        let mut join_handles = Vec::with_capacity(10);
+        for _ in 0..10 {
+            let cloned_non_blocking = non_blocking.clone();
+            join_handles.push(std::thread::spawn(move || {
+                let collector = tracing_subscriber::fmt().with_writer(cloned_non_blocking);
+                tracing::collect::with_default(collector.finish(), || {
+                    tracing::event!(tracing::Level::INFO, "Hello");
+                });
+            }));
+        }
+
+        for handle in join_handles {
+            handle.join().expect("Failed to join thread");
+        }
+
+        assert_eq!(0, non_blocking.error_counter().dropped_lines());
+        drop(non_blocking);
+        drop(guard);
+
+        let mut output = String::new();
+        server.read_to_string(&mut output).await.unwrap();
+        assert_eq!(10, output.matches("Hello").count());
+    }
+}
@@ -1,13 +1,19 @@
+use crate::non_blocking::ErrorCounter;
 use crate::Msg;
 use crossbeam_channel::{Receiver, RecvError, TryRecvError};
 use std::fmt::Debug;
 use std::io::Write;
+use std::time::{Duration, Instant};
 use std::{io, thread};
 
 pub(crate) struct Worker<T: Write + Send + 'static> {
     writer: T,
     receiver: Receiver<Msg>,
     shutdown: Receiver<()>,
+    error_counter: ErrorCounter,
+    report_interval: Option<Duration>,
+    last_report: Instant,
+    reported_lines: usize,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -19,11 +25,21 @@ pub(crate) enum WorkerState {
 }
 
 impl<T: Write + Send + 'static> Worker<T> {
-    pub(crate) fn new(receiver: Receiver<Msg>, writer: T, shutdown: Receiver<()>) -> Worker<T> {
+    pub(crate) fn new(
+        receiver: Receiver<Msg>,
+        writer: T,
+        shutdown: Receiver<()>,
+        error_counter: ErrorCounter,
+        report_interval: Option<Duration>,
+    ) -> Worker<T> {
         Self {
             writer,
             receiver,
             shutdown,
+            error_counter,
+            report_interval,
+            last_report: Instant::now(),
+            reported_lines: 0,
         }
     }
 
@@ -33,6 +49,11 @@ impl<T: Write + Send + 'static> Worker<T> {
                 self.writer.write_all(msg)?;
                 Ok(WorkerState::Continue)
             }
+            Ok(Msg::Flush(ack)) => {
+                self.writer.flush()?;
+                let _ = ack.send(());
+                Ok(WorkerState::Continue)
+            }
             Ok(Msg::Shutdown) => Ok(WorkerState::Shutdown),
             Err(_) => Ok(WorkerState::Disconnected),
         }
@@ -44,12 +65,42 @@ impl<T: Write + Send + 'static> Worker<T> {
                 self.writer.write_all(msg)?;
                 Ok(WorkerState::Continue)
             }
+            Ok(Msg::Flush(ack)) => {
+                self.writer.flush()?;
+                let _ = ack.send(());
+                Ok(WorkerState::Continue)
+            }
             Ok(Msg::Shutdown) => Ok(WorkerState::Shutdown),
             Err(TryRecvError::Empty) => Ok(WorkerState::Empty),
             Err(TryRecvError::Disconnected) => Ok(WorkerState::Disconnected),
         }
     }
 
+    /// Writes a meta-event reporting any log lines dropped since the last
+    /// report, if periodic reporting is enabled and the report interval has
+    /// elapsed.
+    fn report_dropped_lines(&mut self) -> io::Result<()> {
+        let interval = match self.report_interval {
+            Some(interval) => interval,
+            None => return Ok(()),
+        };
+        if self.last_report.elapsed() < interval {
+            return Ok(());
+        }
+        let dropped = self.error_counter.dropped_lines();
+        if dropped > self.reported_lines {
+            let report = format!(
+                "tracing-appender: {} log lines were dropped\n",
+                dropped - self.reported_lines
+            );
+            self.writer.write_all(report.as_bytes())?;
+            self.writer.flush()?;
+            self.reported_lines = dropped;
+        }
+        self.last_report = Instant::now();
+        Ok(())
+    }
+
     /// Blocks on the first recv of each batch of logs, unless the
     /// channel is disconnected. Afterwards, grabs as many logs as
     /// it can off the channel, buffers them and attempts a flush.
@@ -63,6 +114,7 @@ impl<T: Write + Send + 'static> Worker<T> {
             worker_state = handle_result?;
         }
         self.writer.flush()?;
+        self.report_dropped_lines()?;
         Ok(worker_state)
     }
 
@@ -12,6 +12,9 @@ use syn::parse::{Parse, ParseStream};
 pub(crate) struct EventArgs {
     level: Option<Level>,
     pub(crate) mode: FormatMode,
+    /// Fields to record on the event instead of the default
+    /// `return`/`error` value, projected from the returned value.
+    pub(crate) fields: Option<Fields>,
 }
 
 #[derive(Clone, Default, Debug)]
@@ -22,9 +25,17 @@ pub(crate) struct InstrumentArgs {
     pub(crate) parent: Option<Expr>,
     pub(crate) follows_from: Option<Expr>,
     pub(crate) skips: HashSet<Ident>,
+    pub(crate) redacts: HashSet<Ident>,
     pub(crate) fields: Option<Fields>,
     pub(crate) err_args: Option<EventArgs>,
     pub(crate) ret_args: Option<EventArgs>,
+    /// Record the concrete types of generic type parameters as span fields.
+    pub(crate) generics: bool,
+    /// Defer recording field values until the span is known to be enabled.
+    pub(crate) lazy_fields: bool,
+    /// Keep the span active across items produced by a returned
+    /// `Iterator`/`Stream`, rather than closing it when the function returns.
+    pub(crate) follows_stream: bool,
     /// Errors describing any unrecognized parse inputs that we skipped.
     parse_warnings: Vec<syn::Error>,
 }
@@ -121,11 +132,26 @@ impl Parse for InstrumentArgs {
                 }
                 let Skips(skips) = input.parse()?;
                 args.skips = skips;
+            } else if lookahead.peek(kw::redact) {
+                if !args.redacts.is_empty() {
+                    return Err(input.error("expected only a single `redact` argument"));
+                }
+                let Redacts(redacts) = input.parse()?;
+                args.redacts = redacts;
             } else if lookahead.peek(kw::fields) {
                 if args.fields.is_some() {
                     return Err(input.error("expected only a single `fields` argument"));
                 }
                 args.fields = Some(input.parse()?);
+            } else if lookahead.peek(kw::generics) {
+                let _ = input.parse::<kw::generics>()?;
+                args.generics = true;
+            } else if lookahead.peek(kw::lazy_fields) {
+                let _ = input.parse::<kw::lazy_fields>()?;
+                args.lazy_fields = true;
+            } else if lookahead.peek(kw::follows_stream) {
+                let _ = input.parse::<kw::follows_stream>()?;
+                args.follows_stream = true;
             } else if lookahead.peek(kw::err) {
                 let _ = input.parse::<kw::err>();
                 let err_args = EventArgs::parse(input)?;
@@ -173,6 +199,16 @@ impl Parse for EventArgs {
                         return Err(content.error("expected only a single `level` argument"));
                     }
                     result.level = Some(content.parse()?);
+                } else if lookahead.peek(kw::fields) {
+                    if result.fields.is_some() {
+                        return Err(content.error("expected only a single `fields` argument"));
+                    }
+                    if result.mode != FormatMode::default() {
+                        return Err(
+                            content.error("`fields` cannot be combined with a format argument")
+                        );
+                    }
+                    result.fields = Some(content.parse()?);
                 } else if result.mode != FormatMode::default() {
                     return Err(content.error("expected only a single format argument"));
                 } else if let Some(ident) = content.parse::<Option<Ident>>()? {
@@ -184,14 +220,21 @@ impl Parse for EventArgs {
                             "unknown event formatting mode, expected either `Debug` or `Display`",
                         )),
                     }
+                    if result.fields.is_some() {
+                        return Err(
+                            content.error("`fields` cannot be combined with a format argument")
+                        );
+                    }
                 }
                 Ok(())
             };
-        parse_one_arg()?;
-        if !content.is_empty() {
+        while !content.is_empty() {
+            parse_one_arg()?;
+            if content.is_empty() {
+                break;
+            }
             if content.lookahead1().peek(Token![,]) {
                 let _ = content.parse::<Token![,]>()?;
-                parse_one_arg()?;
             } else {
                 return Err(content.error("expected `,` or `)`"));
             }
@@ -281,6 +324,29 @@ impl Parse for Skips {
     }
 }
 
+struct Redacts(HashSet<Ident>);
+
+impl Parse for Redacts {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let _ = input.parse::<kw::redact>();
+        let content;
+        let _ = syn::parenthesized!(content in input);
+        let names = content.parse_terminated(Ident::parse_any, Token![,])?;
+        let mut redacts = HashSet::new();
+        for name in names {
+            if redacts.contains(&name) {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "tried to redact the same field twice",
+                ));
+            } else {
+                redacts.insert(name);
+            }
+        }
+        Ok(Self(redacts))
+    }
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Default)]
 pub(crate) enum FormatMode {
     #[default]
@@ -454,6 +520,7 @@ impl ToTokens for Level {
 mod kw {
     syn::custom_keyword!(fields);
     syn::custom_keyword!(skip);
+    syn::custom_keyword!(redact);
     syn::custom_keyword!(level);
     syn::custom_keyword!(target);
     syn::custom_keyword!(parent);
@@ -461,4 +528,7 @@ mod kw {
     syn::custom_keyword!(name);
     syn::custom_keyword!(err);
     syn::custom_keyword!(ret);
+    syn::custom_keyword!(generics);
+    syn::custom_keyword!(lazy_fields);
+    syn::custom_keyword!(follows_stream);
 }
@@ -10,7 +10,7 @@ use syn::{
 };
 
 use crate::{
-    attr::{Field, Fields, FormatMode, InstrumentArgs, Level},
+    attr::{Field, FieldKind, Fields, FormatMode, InstrumentArgs, Level},
     MaybeItemFn, MaybeItemFnRef,
 };
 
@@ -81,9 +81,18 @@ pub(crate) fn gen_function<'a, B: ToTokens + 'a>(
         }
     };
 
+    let type_params: Vec<Ident> = gen_params
+        .iter()
+        .filter_map(|param| match param {
+            syn::GenericParam::Type(param) => Some(param.ident.clone()),
+            _ => None,
+        })
+        .collect();
+
     let body = gen_block(
         &block,
         params,
+        &type_params,
         asyncness.is_some(),
         args,
         instrumented_function_name,
@@ -103,9 +112,11 @@ pub(crate) fn gen_function<'a, B: ToTokens + 'a>(
 }
 
 /// Instrument a block
+#[allow(clippy::too_many_arguments)]
 fn gen_block<B: ToTokens>(
     block: &B,
     params: &Punctuated<FnArg, Token![,]>,
+    type_params: &[Ident],
     async_context: bool,
     mut args: InstrumentArgs,
     instrumented_function_name: &str,
@@ -130,7 +141,10 @@ fn gen_block<B: ToTokens>(
     };
 
     // generate this inside a closure, so we can return early on errors.
-    let span = (|| {
+    // the closure also produces the statements that lazily record field
+    // values once the span is known to be enabled, when `lazy_fields` is
+    // set; otherwise, the second element of the tuple is empty.
+    let (span, lazy_record) = (|| {
         // Pull out the arguments-to-be-skipped first, so we can filter results
         // below.
         let param_names: Vec<(Ident, (Ident, RecordType))> = params
@@ -168,9 +182,31 @@ fn gen_block<B: ToTokens>(
 
         for skip in &args.skips {
             if !param_names.iter().map(|(user, _)| user).any(|y| y == skip) {
-                return quote_spanned! {skip.span()=>
-                    compile_error!("attempting to skip non-existent parameter")
-                };
+                return (
+                    quote_spanned! {skip.span()=>
+                        compile_error!("attempting to skip non-existent parameter")
+                    },
+                    quote!(),
+                );
+            }
+        }
+
+        for redact in &args.redacts {
+            if !param_names.iter().map(|(user, _)| user).any(|y| y == redact) {
+                return (
+                    quote_spanned! {redact.span()=>
+                        compile_error!("attempting to redact non-existent parameter")
+                    },
+                    quote!(),
+                );
+            }
+            if args.skips.contains(redact) {
+                return (
+                    quote_spanned! {redact.span()=>
+                        compile_error!("a parameter cannot be both skipped and redacted")
+                    },
+                    quote!(),
+                );
             }
         }
 
@@ -179,7 +215,7 @@ fn gen_block<B: ToTokens>(
         let parent = args.parent.iter();
 
         // filter out skipped fields
-        let quoted_fields: Vec<_> = param_names
+        let param_fields: Vec<_> = param_names
             .iter()
             .filter(|(param, _)| {
                 if args.skips.contains(param) {
@@ -197,16 +233,55 @@ fn gen_block<B: ToTokens>(
                     true
                 }
             })
-            .map(|(user_name, (real_name, record_type))| match record_type {
-                RecordType::Value => quote!(#user_name = #real_name),
-                RecordType::Debug => quote!(#user_name = tracing::field::debug(&#real_name)),
+            .collect();
+
+        let quoted_fields: Vec<_> = param_fields
+            .iter()
+            .map(|(user_name, (real_name, record_type))| {
+                // Redacted parameters are recorded as a masked placeholder,
+                // so the actual value never enters the tracing pipeline.
+                if args.redacts.contains(user_name) {
+                    return quote!(#user_name = "[redacted]");
+                }
+
+                // When fields are recorded lazily, the span is opened with
+                // all of its fields empty, and the values are recorded
+                // afterwards, only if the span was enabled.
+                if args.lazy_fields {
+                    return quote!(#user_name = tracing::field::Empty);
+                }
+
+                match record_type {
+                    RecordType::Value => quote!(#user_name = #real_name),
+                    RecordType::Debug => quote!(#user_name = tracing::field::debug(&#real_name)),
+                }
             })
             .collect();
 
+        // if requested, record the concrete types of the function's generic
+        // type parameters as fields, using their declared names.
+        let generic_fields: Vec<_> = if args.generics {
+            type_params
+                .iter()
+                .map(|ident| {
+                    if args.lazy_fields {
+                        quote!(#ident = tracing::field::Empty)
+                    } else {
+                        quote!(#ident = ::core::any::type_name::<#ident>())
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         // replace every use of a variable with its original name
         if let Some(Fields(ref mut fields)) = args.fields {
             let mut replacer = IdentAndTypesRenamer {
-                idents: param_names.into_iter().map(|(a, (b, _))| (a, b)).collect(),
+                idents: param_names
+                    .iter()
+                    .map(|(a, (b, _))| (a.clone(), b.clone()))
+                    .collect(),
                 types: Vec::new(),
             };
 
@@ -223,15 +298,110 @@ fn gen_block<B: ToTokens>(
 
         let custom_fields = &args.fields;
 
-        quote!(tracing::span!(
-            target: #target,
-            #(parent: #parent,)*
-            #level,
-            #span_name,
-            #(#quoted_fields,)*
-            #custom_fields
+        if !args.lazy_fields {
+            return (
+                quote!(tracing::span!(
+                    target: #target,
+                    #(parent: #parent,)*
+                    #level,
+                    #span_name,
+                    #(#quoted_fields,)*
+                    #(#generic_fields,)*
+                    #custom_fields
+
+                )),
+                quote!(),
+            );
+        }
+
+        // In lazy mode, declare the custom fields as empty as well, and
+        // generate a `record` call for each field that has a value to
+        // evaluate. Fields that would be empty anyway don't need to be
+        // recorded.
+        let custom_field_decls = custom_fields.as_ref().map(|Fields(fields)| {
+            let decls = fields.iter().map(|Field { name, .. }| {
+                quote!(#name = tracing::field::Empty)
+            });
+            quote!(#(#decls),*)
+        });
+
+        let mut records = Vec::new();
+        for (user_name, (real_name, record_type)) in &param_fields {
+            // Redacted parameters already have their placeholder recorded.
+            if args.redacts.contains(user_name) {
+                continue;
+            }
+            let name = user_name.to_string();
+            let value = match record_type {
+                RecordType::Value => quote!(&#real_name),
+                RecordType::Debug => quote!(tracing::field::debug(&#real_name)),
+            };
+            records.push(quote!(__tracing_attr_span.record(#name, #value);));
+        }
 
-        ))
+        if args.generics {
+            for ident in type_params {
+                let name = ident.to_string();
+                records.push(quote!(
+                    __tracing_attr_span.record(#name, ::core::any::type_name::<#ident>());
+                ));
+            }
+        }
+
+        if let Some(Fields(ref fields)) = args.fields {
+            for field in fields {
+                let name = field
+                    .name
+                    .iter()
+                    .map(Ident::to_string)
+                    .collect::<Vec<_>>()
+                    .join(".");
+                let value = match (&field.value, &field.kind) {
+                    (Some(value), FieldKind::Value) => quote!(&(#value)),
+                    (Some(value), FieldKind::Debug) => {
+                        quote!(tracing::field::debug(&(#value)))
+                    }
+                    (Some(value), FieldKind::Display) => {
+                        quote!(tracing::field::display(&(#value)))
+                    }
+                    // A field without a value is empty either way; there's
+                    // nothing to record.
+                    (None, FieldKind::Value) => continue,
+                    (None, kind) => {
+                        let shorthand = &field.name;
+                        match kind {
+                            FieldKind::Debug => quote!(tracing::field::debug(&#shorthand)),
+                            _ => quote!(tracing::field::display(&#shorthand)),
+                        }
+                    }
+                };
+                records.push(quote!(__tracing_attr_span.record(#name, #value);));
+            }
+        }
+
+        let lazy_record = if records.is_empty() {
+            quote!()
+        } else {
+            quote!(
+                if !__tracing_attr_span.is_disabled() {
+                    #(#records)*
+                }
+            )
+        };
+
+        (
+            quote!(tracing::span!(
+                target: #target,
+                #(parent: #parent,)*
+                #level,
+                #span_name,
+                #(#quoted_fields,)*
+                #(#generic_fields,)*
+                #custom_field_decls
+
+            )),
+            lazy_record,
+        )
     })();
 
     let target = args.target();
@@ -239,13 +409,21 @@ fn gen_block<B: ToTokens>(
     let err_event = match args.err_args {
         Some(event_args) => {
             let level_tokens = event_args.level(Level::Error);
-            match event_args.mode {
-                FormatMode::Default | FormatMode::Display => Some(quote!(
-                    tracing::event!(target: #target, #level_tokens, error = %e)
-                )),
-                FormatMode::Debug => Some(quote!(
-                    tracing::event!(target: #target, #level_tokens, error = ?e)
+            match event_args.fields {
+                // If a field projection was given, record the declared
+                // expressions instead of the error value itself. The error is
+                // bound as `e` inside the field expressions.
+                Some(ref fields) => Some(quote!(
+                    tracing::event!(target: #target, #level_tokens, #fields)
                 )),
+                None => match event_args.mode {
+                    FormatMode::Default | FormatMode::Display => Some(quote!(
+                        tracing::event!(target: #target, #level_tokens, error = %e)
+                    )),
+                    FormatMode::Debug => Some(quote!(
+                        tracing::event!(target: #target, #level_tokens, error = ?e)
+                    )),
+                },
             }
         }
         _ => None,
@@ -254,18 +432,59 @@ fn gen_block<B: ToTokens>(
     let ret_event = match args.ret_args {
         Some(event_args) => {
             let level_tokens = event_args.level(args_level);
-            match event_args.mode {
-                FormatMode::Display => Some(quote!(
-                    tracing::event!(target: #target, #level_tokens, return = %x)
-                )),
-                FormatMode::Default | FormatMode::Debug => Some(quote!(
-                    tracing::event!(target: #target, #level_tokens, return = ?x)
+            match event_args.fields {
+                // As above, a field projection replaces the default `return`
+                // field. The return value is bound as `r` inside the field
+                // expressions.
+                Some(ref fields) => Some(quote!(
+                    {
+                        #[allow(unused_variables)]
+                        let r = &x;
+                        tracing::event!(target: #target, #level_tokens, #fields)
+                    }
                 )),
+                None => match event_args.mode {
+                    FormatMode::Display => Some(quote!(
+                        tracing::event!(target: #target, #level_tokens, return = %x)
+                    )),
+                    FormatMode::Default | FormatMode::Debug => Some(quote!(
+                        tracing::event!(target: #target, #level_tokens, return = ?x)
+                    )),
+                },
             }
         }
         _ => None,
     };
 
+    // If the returned value is an `Iterator` or `Stream`, wrap it so that the
+    // span remains active while the caller produces items from it, instead of
+    // closing the span as soon as the constructor returns.
+    if args.follows_stream {
+        if async_context {
+            return quote_spanned! {block.span()=>
+                compile_error!("`follows_stream` cannot be used with `async` functions")
+            };
+        }
+        if err_event.is_some() || ret_event.is_some() {
+            return quote_spanned! {block.span()=>
+                compile_error!("`follows_stream` cannot be combined with `err` or `ret`")
+            };
+        }
+        return quote_spanned!(block.span()=>
+            let __tracing_attr_span = #span;
+            #lazy_record
+            if !__tracing_attr_span.is_disabled() {
+                #follows_from
+            }
+            let __tracing_attr_ret = {
+                let __tracing_attr_guard = __tracing_attr_span.enter();
+                #[allow(clippy::redundant_closure_call)]
+                (move || #block)()
+            };
+            tracing::Instrument::instrument(__tracing_attr_ret, __tracing_attr_span)
+        );
+    }
+
     // Generate the instrumented function body.
     // If the function is an `async fn`, this will wrap it in an async block,
     // which is `instrument`ed using `tracing-futures`. Otherwise, this will
@@ -317,6 +536,7 @@ fn gen_block<B: ToTokens>(
 
         return quote!(
             let __tracing_attr_span = #span;
+            #lazy_record
             let __tracing_instrument_future = #mk_fut;
             if !__tracing_attr_span.is_disabled() {
                 #follows_from
@@ -346,6 +566,7 @@ fn gen_block<B: ToTokens>(
         let __tracing_attr_guard;
         if tracing::level_enabled!(#level) || tracing::if_log_enabled!(#level, {true} else {false}) {
             __tracing_attr_span = #span;
+            #lazy_record
             #follows_from
             __tracing_attr_guard = __tracing_attr_span.enter();
         }
@@ -713,9 +934,17 @@ impl<'block> AsyncInfo<'block> {
                     async_expr,
                     pinned_box,
                 } => {
+                    let type_params: Vec<Ident> = self
+                        .input
+                        .sig
+                        .generics
+                        .type_params()
+                        .map(|param| param.ident.clone())
+                        .collect();
                     let instrumented_block = gen_block(
                         &async_expr.block,
                         &self.input.sig.inputs,
+                        &type_params,
                         true,
                         args,
                         instrumented_function_name,
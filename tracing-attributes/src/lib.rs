@@ -210,6 +210,32 @@ mod expand;
 /// }
 /// ```
 ///
+/// For generic functions, adding the `generics` argument will record the
+/// concrete type of each generic type parameter (as reported by
+/// [`core::any::type_name`]) as a span field named after the parameter:
+///
+/// ```
+/// # use tracing_attributes::instrument;
+/// // Records a field `T` with values like `"u8"` or `"alloc::string::String"`.
+/// #[instrument(generics, skip(input))]
+/// fn handle<T>(input: T) {
+///     // ...
+/// }
+/// ```
+///
+/// If an argument's value is sensitive but its presence is still worth
+/// recording, pass its name to `redact` instead of `skip`. The span will
+/// contain the field, but its value will be replaced with the `"[redacted]"`
+/// placeholder, so the secret never enters the tracing pipeline:
+///
+/// ```
+/// # use tracing_attributes::instrument;
+/// #[instrument(redact(password))]
+/// fn login(user: &str, password: &str) {
+///     // ...
+/// }
+/// ```
+///
 /// To add additional context to the span, pass key-value pairs to `fields`:
 ///
 /// ```
@@ -220,6 +246,54 @@ mod expand;
 /// }
 /// ```
 ///
+/// By default, field values (including function arguments recorded with their
+/// `Debug` implementations and expressions passed to `fields`) are evaluated
+/// when the span is created, even if the span is then disabled by the current
+/// collector. If evaluating a field is expensive, adding the `lazy_fields`
+/// argument will instead open the span with all of its fields [empty], and
+/// [record] their values only if the span is enabled:
+///
+/// ```
+/// # use tracing_attributes::instrument;
+/// # fn expensive_summary(_: &[u8]) -> String { String::new() }
+/// #[instrument(lazy_fields, fields(summary = expensive_summary(data)))]
+/// fn my_function(data: &[u8]) {
+///     // ...
+/// }
+/// ```
+///
+/// Note that when `lazy_fields` is enabled, collectors will observe the
+/// field values via [`record`] calls immediately after the span is created,
+/// rather than as part of the span's initial set of values.
+///
+/// [empty]: https://docs.rs/tracing/latest/tracing/field/struct.Empty.html
+/// [record]: https://docs.rs/tracing/latest/tracing/struct.Span.html#method.record
+/// [`record`]: https://docs.rs/tracing/latest/tracing/struct.Span.html#method.record
+///
+/// For functions returning an [`Iterator`] or a `Stream`, the span generated
+/// by `#[instrument]` normally closes as soon as the constructor returns,
+/// before any items are produced. Adding the `follows_stream` argument will
+/// instead wrap the returned value in an [`Instrumented`], so that the span
+/// is entered every time an item is produced, and remains open until the
+/// iterator or stream is dropped:
+///
+/// ```
+/// # use tracing_attributes::instrument;
+/// #[instrument(follows_stream)]
+/// fn count_to(n: usize) -> impl Iterator<Item = usize> {
+///     0..n
+/// }
+/// ```
+///
+/// Note that this requires the function to return `impl Iterator` (or
+/// `impl Stream`) rather than a concrete type, since the returned value is
+/// wrapped. Instrumenting a `Stream` constructor this way additionally
+/// requires the "futures-core" feature of the `tracing` crate, which provides
+/// the `Stream` implementation for [`Instrumented`]. `follows_stream` cannot
+/// be combined with `async` functions, `err`, or `ret`.
+///
+/// [`Instrumented`]: https://docs.rs/tracing/latest/tracing/struct.Instrumented.html
+///
 /// Adding the `ret` argument to `#[instrument]` will emit an event with the function's
 /// return value when the function returns:
 ///
@@ -260,6 +334,25 @@ mod expand;
 /// }
 /// ```
 ///
+/// Instead of recording the whole return value, `ret(fields(...))` can be
+/// used to project it into a set of fields. The return value is bound as `r`
+/// inside the field expressions, and the declared fields replace the default
+/// `return` field on the emitted event:
+///
+/// ```
+/// # use tracing_attributes::instrument;
+/// # struct Response { status: u16, body: String }
+/// #[instrument(ret(fields(status = r.status, bytes = r.body.len())))]
+/// fn my_function() -> Response {
+///     // ...
+///     # Response { status: 200, body: String::new() }
+/// }
+/// ```
+///
+/// Similarly, `err(fields(...))` projects the error value, which is bound as
+/// `e` inside the field expressions. A field projection cannot be combined
+/// with the `Debug` or `Display` format arguments.
+///
 /// If the function returns a `Result<T, E>` and `E` implements `std::fmt::Display`, adding
 /// `err` or `err(Display)` will emit error events when the function returns `Err`:
 ///
@@ -326,3 +326,32 @@ fn test_err_warn_info() {
     with_default(collector, || err_warn_info().ok());
     handle.assert_finished();
 }
+
+#[instrument(err(fields(code = 22, msg = %e)))]
+fn err_fields() -> Result<u8, TryFromIntError> {
+    u8::try_from(1234)
+}
+
+#[test]
+fn test_err_fields() {
+    let span = expect::span().named("err_fields");
+    let (collector, handle) = collector::mock()
+        .new_span(span.clone())
+        .enter(span.clone())
+        .event(
+            expect::event()
+                .with_fields(
+                    expect::field("code")
+                        .with_value(&22)
+                        .and(expect::field("msg"))
+                        .only(),
+                )
+                .at_level(Level::ERROR),
+        )
+        .exit(span.clone())
+        .drop_span(span)
+        .only()
+        .run_with_handle();
+    with_default(collector, || err_fields().ok());
+    handle.assert_finished();
+}
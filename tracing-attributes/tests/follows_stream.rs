@@ -0,0 +1,39 @@
+use tracing::collect::with_default;
+use tracing_attributes::instrument;
+use tracing_mock::{collector, expect};
+
+#[instrument(follows_stream)]
+fn count_to(n: usize) -> impl Iterator<Item = usize> {
+    0..n
+}
+
+#[test]
+fn span_stays_open_across_items() {
+    let span = expect::span().named("count_to");
+    let (collector, handle) = collector::mock()
+        // constructing the iterator enters and exits the span once...
+        .new_span(span.clone())
+        .enter(span.clone())
+        .exit(span.clone())
+        // ...and then each call to `next()` re-enters it: two items, plus the
+        // final call returning `None`...
+        .enter(span.clone())
+        .exit(span.clone())
+        .enter(span.clone())
+        .exit(span.clone())
+        .enter(span.clone())
+        .exit(span.clone())
+        // ...and the span is only closed when the iterator is dropped.
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .only()
+        .run_with_handle();
+
+    with_default(collector, || {
+        let iter = count_to(2);
+        assert_eq!(iter.collect::<Vec<_>>(), vec![0, 1]);
+    });
+
+    handle.assert_finished();
+}
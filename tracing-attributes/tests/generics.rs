@@ -0,0 +1,53 @@
+use tracing::collect::with_default;
+use tracing_attributes::instrument;
+use tracing_mock::{collector, expect};
+
+#[instrument(generics)]
+fn generic_fn<T: core::fmt::Debug>(arg: T) {
+    let _ = arg;
+}
+
+#[instrument(generics, lazy_fields, skip(arg))]
+fn lazy_generic_fn<T>(arg: T) {
+    let _ = arg;
+}
+
+#[test]
+fn type_parameters_are_recorded() {
+    let span = expect::span().named("generic_fn");
+    let (collector, handle) = collector::mock()
+        .new_span(
+            span.clone().with_fields(
+                expect::field("arg")
+                    .with_value(&tracing::field::debug(1u8))
+                    .and(expect::field("T").with_value(&"u8"))
+                    .only(),
+            ),
+        )
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .only()
+        .run_with_handle();
+
+    with_default(collector, || generic_fn(1u8));
+
+    handle.assert_finished();
+}
+
+#[test]
+fn type_parameters_are_recorded_lazily() {
+    let span = expect::span().named("lazy_generic_fn");
+    let (collector, handle) = collector::mock()
+        .new_span(span.clone())
+        .record(span.clone(), expect::field("T").with_value(&"alloc::string::String"))
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .only()
+        .run_with_handle();
+
+    with_default(collector, || lazy_generic_fn(String::new()));
+
+    handle.assert_finished();
+}
@@ -0,0 +1,67 @@
+use tracing::collect::with_default;
+use tracing_attributes::instrument;
+use tracing_mock::{collector, expect};
+
+#[instrument(lazy_fields)]
+fn fn_arg(arg: usize) {}
+
+#[instrument(lazy_fields, fields(len = s.len()))]
+fn fn_expr_field(s: &str) {}
+
+fn expensive_field() -> usize {
+    panic!("field expressions should not be evaluated when the span is disabled")
+}
+
+#[instrument(lazy_fields, fields(val = expensive_field()))]
+fn fn_expensive_field() {}
+
+#[test]
+fn args_are_recorded_after_span_creation() {
+    let span = expect::span().named("fn_arg");
+    let (collector, handle) = collector::mock()
+        .new_span(span.clone())
+        .record(span.clone(), expect::field("arg").with_value(&2usize).only())
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .only()
+        .run_with_handle();
+
+    with_default(collector, || fn_arg(2));
+
+    handle.assert_finished();
+}
+
+#[test]
+fn expr_fields_are_recorded_after_span_creation() {
+    let span = expect::span().named("fn_expr_field");
+    let (collector, handle) = collector::mock()
+        .new_span(span.clone())
+        .record(span.clone(), expect::field("s").with_value(&"hello world"))
+        .record(
+            span.clone(),
+            expect::field("len").with_value(&"hello world".len()),
+        )
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .only()
+        .run_with_handle();
+
+    with_default(collector, || fn_expr_field("hello world"));
+
+    handle.assert_finished();
+}
+
+#[test]
+fn fields_are_not_evaluated_when_disabled() {
+    let (collector, handle) = collector::mock()
+        .with_filter(|meta| !meta.is_span())
+        .only()
+        .run_with_handle();
+
+    // If the field expression were evaluated eagerly, this would panic.
+    with_default(collector, fn_expensive_field);
+
+    handle.assert_finished();
+}
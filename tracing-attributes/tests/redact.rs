@@ -0,0 +1,58 @@
+use tracing::collect::with_default;
+use tracing_attributes::instrument;
+use tracing_mock::{collector, expect};
+
+#[instrument(redact(password))]
+fn login(user: &str, password: &str) {
+    let _ = password;
+}
+
+#[instrument(lazy_fields, redact(token))]
+fn refresh(token: &str, attempt: usize) {
+    let _ = token;
+}
+
+#[test]
+fn redacted_field_records_placeholder() {
+    let span = expect::span().named("login");
+    let (collector, handle) = collector::mock()
+        .new_span(
+            span.clone().with_fields(
+                expect::field("user")
+                    .with_value(&"alice")
+                    .and(expect::field("password").with_value(&"[redacted]"))
+                    .only(),
+            ),
+        )
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .only()
+        .run_with_handle();
+
+    with_default(collector, || login("alice", "hunter2"));
+
+    handle.assert_finished();
+}
+
+#[test]
+fn redaction_works_with_lazy_fields() {
+    let span = expect::span().named("refresh");
+    let (collector, handle) = collector::mock()
+        // The placeholder is part of the span's initial values; only the
+        // non-redacted argument is recorded lazily.
+        .new_span(
+            span.clone()
+                .with_fields(expect::field("token").with_value(&"[redacted]")),
+        )
+        .record(span.clone(), expect::field("attempt").with_value(&3usize))
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .only()
+        .run_with_handle();
+
+    with_default(collector, || refresh("secret", 3));
+
+    handle.assert_finished();
+}
@@ -306,3 +306,76 @@ fn test_dbg_warn() {
     with_default(collector, ret_dbg_warn);
     handle.assert_finished();
 }
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+#[instrument(ret(fields(status = r.status, bytes = r.body.len())))]
+fn ret_fields() -> Response {
+    Response {
+        status: 200,
+        body: "hello world".to_string(),
+    }
+}
+
+#[test]
+fn test_ret_fields() {
+    let span = expect::span().named("ret_fields");
+    let (collector, handle) = collector::mock()
+        .new_span(span.clone())
+        .enter(span.clone())
+        .event(
+            expect::event()
+                .with_fields(
+                    expect::field("status")
+                        .with_value(&200u16)
+                        .and(expect::field("bytes").with_value(&"hello world".len()))
+                        .only(),
+                )
+                .at_level(Level::INFO),
+        )
+        .exit(span.clone())
+        .drop_span(span)
+        .only()
+        .run_with_handle();
+
+    with_default(collector, || {
+        ret_fields();
+    });
+    handle.assert_finished();
+}
+
+#[instrument(ret(fields(status = r.status)))]
+async fn ret_fields_async() -> Response {
+    Response {
+        status: 204,
+        body: String::new(),
+    }
+}
+
+#[test]
+fn test_ret_fields_async() {
+    let span = expect::span().named("ret_fields_async");
+    let (collector, handle) = collector::mock()
+        .new_span(span.clone())
+        .enter(span.clone())
+        .event(
+            expect::event()
+                .with_fields(expect::field("status").with_value(&204u16).only())
+                .at_level(Level::INFO),
+        )
+        .exit(span.clone())
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .only()
+        .run_with_handle();
+
+    with_default(collector, || {
+        block_on_future(async { ret_fields_async().await });
+    });
+    handle.assert_finished();
+}
@@ -288,6 +288,36 @@ mod tests {
         });
     }
 
+    #[test]
+    fn display_includes_field_values() {
+        let collector = Registry::default().with(ErrorSubscriber::default());
+
+        with_default(collector, || {
+            let span = span!(
+                Level::ERROR,
+                "test span",
+                request_id = 42,
+                late = tracing::field::Empty
+            );
+            span.record("late", "ok");
+            let _guard = span.enter();
+
+            let span_trace = SpanTrace::capture();
+            let display = format!("{}", span_trace);
+
+            assert!(
+                display.contains("request_id=42"),
+                "span trace should include fields recorded on creation: {}",
+                display
+            );
+            assert!(
+                display.contains("late=\"ok\""),
+                "span trace should include fields recorded after creation: {}",
+                display
+            );
+        });
+    }
+
     #[test]
     fn capture_empty() {
         let collector = Registry::default().with(ErrorSubscriber::default());
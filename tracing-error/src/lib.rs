@@ -16,6 +16,10 @@
 //!
 //! * [`ErrorSubscriber`], a [subscriber] which enables capturing `SpanTrace`s
 //!
+//! * [`SpanTraceReport`], which renders a `SpanTrace` together with a stack
+//!   backtrace, annotating backtrace frames with the spans entered at the
+//!   same call depth
+//!
 //! **Note**: This crate is currently experimental.
 //!
 //! *Compiler support: [requires `rustc` 1.63+][msrv]*
@@ -209,11 +213,13 @@
 mod backtrace;
 #[cfg(feature = "traced-error")]
 mod error;
+mod report;
 mod subscriber;
 
 pub use self::backtrace::{SpanTrace, SpanTraceStatus};
 #[cfg(feature = "traced-error")]
 pub use self::error::{ExtractSpanTrace, InstrumentError, InstrumentResult, TracedError};
+pub use self::report::SpanTraceReport;
 pub use self::subscriber::ErrorSubscriber;
 
 #[cfg(feature = "traced-error")]
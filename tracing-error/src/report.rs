@@ -0,0 +1,282 @@
+use crate::SpanTrace;
+use std::fmt;
+use tracing::Metadata;
+
+/// A combined report of a stack backtrace and a [`SpanTrace`].
+///
+/// When an error carries both a backtrace and a span trace, displaying them
+/// separately leaves the reader to correlate the two by hand. This type
+/// renders them together: the backtrace is printed frame by frame, and each
+/// frame that corresponds to an instrumented function is annotated in place
+/// with the span entered at that call depth — including its recorded
+/// [fields]. Spans that cannot be attributed to a stack frame (for example,
+/// spans with custom names, or frames optimized out of the backtrace) are
+/// listed after the backtrace so no context is lost.
+///
+/// The backtrace may be any type whose `Display` output uses the standard
+/// frame layout — [`std::backtrace::Backtrace`], the [`backtrace` crate]'s
+/// `Backtrace`, or a pre-rendered string. A frame is matched to a span when
+/// the frame's symbol contains the span's `target::name` path, which holds
+/// for spans generated by [`#[instrument]`][instrument].
+///
+/// # Examples
+///
+/// ```rust
+/// use std::backtrace::Backtrace;
+/// use tracing_error::{SpanTrace, SpanTraceReport};
+///
+/// # fn docs() {
+/// let report = SpanTraceReport::new(SpanTrace::capture(), Backtrace::capture());
+/// eprintln!("{}", report);
+/// # }
+/// ```
+///
+/// This produces output such as:
+///
+/// ```text
+///    4: myapp::load_config
+///         in span myapp::load_config with path="/etc/myapp.toml"
+///              at src/config.rs:12:18
+///    5: myapp::main
+///              at src/main.rs:5:9
+/// ```
+///
+/// [fields]: tracing::field
+/// [`backtrace` crate]: https://docs.rs/backtrace/
+/// [instrument]: https://docs.rs/tracing/latest/tracing/attr.instrument.html
+pub struct SpanTraceReport<B> {
+    span_trace: SpanTrace,
+    backtrace: B,
+}
+
+// === impl SpanTraceReport ===
+
+impl<B: fmt::Display> SpanTraceReport<B> {
+    /// Returns a new report combining the given `span_trace` and `backtrace`.
+    pub fn new(span_trace: SpanTrace, backtrace: B) -> Self {
+        Self {
+            span_trace,
+            backtrace,
+        }
+    }
+
+    /// Returns a reference to the captured [`SpanTrace`].
+    pub fn span_trace(&self) -> &SpanTrace {
+        &self.span_trace
+    }
+
+    /// Returns a reference to the captured backtrace.
+    pub fn backtrace(&self) -> &B {
+        &self.backtrace
+    }
+}
+
+/// A span frame collected from the [`SpanTrace`], innermost first.
+struct SpanFrame {
+    metadata: &'static Metadata<'static>,
+    fields: String,
+    matched: bool,
+}
+
+impl SpanFrame {
+    /// Returns whether `symbol` names the function this span instruments.
+    fn matches(&self, symbol: &str) -> bool {
+        let metadata = self.metadata;
+        // `#[instrument]` names the span after the function and targets the
+        // module path, so the symbolized frame contains `target::name`
+        // (possibly followed by a closure or hash suffix).
+        symbol.contains(metadata.target())
+            && symbol.split("::").any(|segment| segment == metadata.name())
+    }
+}
+
+/// Extracts the symbol from a backtrace frame line such as
+/// `"   4: myapp::main"`, or returns `None` for other lines (`at` locations,
+/// headers, and so on).
+fn frame_symbol(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let (index, symbol) = trimmed.split_once(": ")?;
+    if index.is_empty() || !index.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    Some(symbol.trim())
+}
+
+impl<B: fmt::Display> fmt::Display for SpanTraceReport<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut spans = Vec::new();
+        self.span_trace.with_spans(|metadata, fields| {
+            spans.push(SpanFrame {
+                metadata,
+                fields: fields.to_string(),
+                matched: false,
+            });
+            true
+        });
+
+        // Both the backtrace and the span trace run from the innermost frame
+        // outwards, so each frame need only consider spans at or below the
+        // deepest one matched so far.
+        let mut next_span = 0;
+        let backtrace = self.backtrace.to_string();
+        for line in backtrace.lines() {
+            writeln!(f, "{}", line)?;
+            let symbol = match frame_symbol(line) {
+                Some(symbol) => symbol,
+                None => continue,
+            };
+            if let Some(offset) = spans[next_span..]
+                .iter()
+                .position(|span| span.matches(symbol))
+            {
+                let span = &mut spans[next_span + offset];
+                span.matched = true;
+                write!(
+                    f,
+                    "        in span {}::{}",
+                    span.metadata.target(),
+                    span.metadata.name()
+                )?;
+                if !span.fields.is_empty() {
+                    write!(f, " with {}", span.fields)?;
+                }
+                writeln!(f)?;
+                next_span += offset + 1;
+            }
+        }
+
+        // Spans that did not line up with any frame are still part of the
+        // context; list them the way `SpanTrace` does.
+        let mut unmatched = spans.iter().filter(|span| !span.matched).peekable();
+        if unmatched.peek().is_some() {
+            writeln!(f, "spans without a matching stack frame:")?;
+            for (index, span) in unmatched.enumerate() {
+                let metadata = span.metadata;
+                write!(
+                    f,
+                    "{:>4}: {}::{}",
+                    index,
+                    metadata.target(),
+                    metadata.name()
+                )?;
+                if !span.fields.is_empty() {
+                    write!(f, "\n           with {}", span.fields)?;
+                }
+                if let Some((file, line)) = metadata
+                    .file()
+                    .and_then(|file| metadata.line().map(|line| (file, line)))
+                {
+                    write!(f, "\n             at {}:{}", file, line)?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<B: fmt::Display> fmt::Debug for SpanTraceReport<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpanTraceReport")
+            .field("span_trace", &self.span_trace)
+            .field("backtrace", &format_args!("{}", self.backtrace))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorSubscriber;
+    use tracing::collect::with_default;
+    use tracing::{span, Level};
+    use tracing_subscriber::{prelude::*, registry::Registry};
+
+    const BACKTRACE: &str = "\
+   0: myapp::inner
+             at src/inner.rs:10:5
+   1: myapp::outer
+             at src/outer.rs:5:9
+   2: std::rt::lang_start
+             at library/std/src/rt.rs:166:17
+";
+
+    fn capture() -> SpanTrace {
+        SpanTrace::capture()
+    }
+
+    #[test]
+    fn frames_are_annotated_with_spans() {
+        let collector = Registry::default().with(ErrorSubscriber::default());
+
+        with_default(collector, || {
+            let outer = span!(target: "myapp", Level::ERROR, "outer");
+            let _outer = outer.enter();
+            let inner = span!(target: "myapp", Level::ERROR, "inner", answer = 42);
+            let _inner = inner.enter();
+
+            let report = SpanTraceReport::new(capture(), BACKTRACE);
+            let display = format!("{}", report);
+
+            let inner_at = display
+                .find("in span myapp::inner with answer=42")
+                .expect("inner frame should be annotated");
+            let outer_at = display
+                .find("in span myapp::outer")
+                .expect("outer frame should be annotated");
+            assert!(
+                inner_at < outer_at,
+                "annotations should follow frame order:\n{}",
+                display
+            );
+            assert!(
+                !display.contains("spans without a matching stack frame"),
+                "all spans should be matched:\n{}",
+                display
+            );
+        });
+    }
+
+    #[test]
+    fn unmatched_spans_are_listed() {
+        let collector = Registry::default().with(ErrorSubscriber::default());
+
+        with_default(collector, || {
+            let span = span!(Level::ERROR, "a custom name", request_id = 7);
+            let _guard = span.enter();
+
+            let report = SpanTraceReport::new(capture(), BACKTRACE);
+            let display = format!("{}", report);
+
+            assert!(
+                display.contains("spans without a matching stack frame:"),
+                "unmatched spans should be listed:\n{}",
+                display
+            );
+            assert!(
+                display.contains("a custom name") && display.contains("request_id=7"),
+                "the unmatched span and its fields should be shown:\n{}",
+                display
+            );
+        });
+    }
+
+    #[test]
+    fn backtrace_is_reproduced_verbatim() {
+        let collector = Registry::default().with(ErrorSubscriber::default());
+
+        with_default(collector, || {
+            let report = SpanTraceReport::new(capture(), BACKTRACE);
+            let display = format!("{}", report);
+
+            for line in BACKTRACE.lines() {
+                assert!(
+                    display.contains(line),
+                    "line {:?} missing from report:\n{}",
+                    line,
+                    display
+                );
+            }
+        });
+    }
+}
@@ -0,0 +1,71 @@
+use crate::error::{Error, Kind};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+/// Writes the differential folded output of two recording windows.
+///
+/// Both inputs are folded stack samples as produced by
+/// [`FlameSubscriber`] — one line per sample, with a stack and a count
+/// separated by the final space. Identical stacks in each input are
+/// summed, and the output contains one line per stack seen in *either*
+/// window, with both counts:
+///
+/// ```text
+/// <stack> <count-before> <count-after>
+/// ```
+///
+/// This is the two-column format consumed by `inferno-diff-folded` and
+/// `flamegraph.pl --negate`, which render growth in one color and
+/// shrinkage in another — a "what changed after the deploy" flamegraph.
+/// Combined with [`FlameSubscriber::with_rotating_file`], a long-running
+/// service can diff two rotated files without ever buffering a full run:
+///
+/// ```
+/// # fn docs() -> Result<(), Box<dyn std::error::Error>> {
+/// let before = std::fs::File::open("./tracing.folded.2")?;
+/// let after = std::fs::File::open("./tracing.folded.1")?;
+/// let out = std::fs::File::create("./tracing.diff.folded")?;
+/// tracing_flame::write_diff_folded(before, after, out)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`FlameSubscriber`]: crate::FlameSubscriber
+/// [`FlameSubscriber::with_rotating_file`]: crate::FlameSubscriber::with_rotating_file
+pub fn write_diff_folded(
+    before: impl Read,
+    after: impl Read,
+    out: impl Write,
+) -> Result<(), Error> {
+    let mut stacks: BTreeMap<String, [u128; 2]> = BTreeMap::new();
+    collect_folded(before, &mut stacks, 0)?;
+    collect_folded(after, &mut stacks, 1)?;
+
+    let mut out = io::BufWriter::new(out);
+    for (stack, [before, after]) in &stacks {
+        writeln!(out, "{} {} {}", stack, before, after)
+            .map_err(Kind::WriteFolded)
+            .map_err(Error)?;
+    }
+    out.flush().map_err(Kind::WriteFolded).map_err(Error)
+}
+
+/// Sums the samples of one folded input into `stacks` at `window`.
+fn collect_folded(
+    input: impl Read,
+    stacks: &mut BTreeMap<String, [u128; 2]>,
+    window: usize,
+) -> Result<(), Error> {
+    for line in BufReader::new(input).lines() {
+        let line = line.map_err(Kind::ReadFolded).map_err(Error)?;
+        if line.is_empty() {
+            continue;
+        }
+        let (stack, count) = line
+            .rsplit_once(' ')
+            .and_then(|(stack, count)| Some((stack, count.parse::<u128>().ok()?)))
+            .ok_or_else(|| Error(Kind::ParseFolded { line: line.clone() }))?;
+        stacks.entry(stack.to_owned()).or_default()[window] += count;
+    }
+    Ok(())
+}
@@ -32,6 +32,9 @@ impl std::error::Error for Error {
         match &self.0 {
             Kind::CreateFile { ref source, .. } => Some(source),
             Kind::FlushFile(ref source) => Some(source),
+            Kind::ReadFolded(ref source) => Some(source),
+            Kind::WriteFolded(ref source) => Some(source),
+            Kind::ParseFolded { .. } => None,
         }
     }
 }
@@ -43,6 +46,11 @@ pub(crate) enum Kind {
         path: PathBuf,
     },
     FlushFile(std::io::Error),
+    ReadFolded(std::io::Error),
+    WriteFolded(std::io::Error),
+    ParseFolded {
+        line: String,
+    },
 }
 
 impl fmt::Display for Kind {
@@ -52,6 +60,11 @@ impl fmt::Display for Kind {
                 write!(f, "cannot create output file. path={}", path.display())
             }
             Self::FlushFile { .. } => write!(f, "cannot flush output buffer"),
+            Self::ReadFolded { .. } => write!(f, "cannot read folded input"),
+            Self::WriteFolded { .. } => write!(f, "cannot write folded output"),
+            Self::ParseFolded { line } => {
+                write!(f, "malformed folded stack sample. line={:?}", line)
+            }
         }
     }
 }
@@ -134,7 +134,6 @@
     while_true
 )]
 
-use error::Error;
 use error::Kind;
 use once_cell::sync::Lazy;
 use std::cell::Cell;
@@ -155,7 +154,13 @@ use tracing_subscriber::registry::SpanRef;
 use tracing_subscriber::subscribe::Context;
 use tracing_subscriber::Subscribe;
 
+mod diff;
 mod error;
+mod rotate;
+
+pub use diff::write_diff_folded;
+pub use error::Error;
+pub use rotate::RotatingWriter;
 
 static START: Lazy<Instant> = Lazy::new(Instant::now);
 
@@ -381,6 +386,37 @@ where
     }
 }
 
+impl<C> FlameSubscriber<C, RotatingWriter>
+where
+    C: Collect + for<'span> LookupSpan<'span>,
+{
+    /// Constructs a `FlameSubscriber` that streams folded stack samples to the
+    /// given path, rotating the file once it exceeds `max_bytes` and keeping at
+    /// most `max_files` rotated files (`<path>.1` is the most recent) alongside
+    /// the active one, plus a `FlushGuard` to ensure the writer is flushed.
+    ///
+    /// This bounds the disk space used by a long-running service, and the
+    /// rotated files delimit recording windows that can be compared with
+    /// [`write_diff_folded`]. Rotation only happens between samples, so every
+    /// file can be fed to `inferno-flamegraph` on its own.
+    pub fn with_rotating_file(
+        path: impl AsRef<Path>,
+        max_bytes: u64,
+        max_files: usize,
+    ) -> Result<(Self, FlushGuard<RotatingWriter>), Error> {
+        let path = path.as_ref();
+        let writer = RotatingWriter::create(path.into(), max_bytes, max_files)
+            .map_err(|source| Kind::CreateFile {
+                path: path.into(),
+                source,
+            })
+            .map_err(Error)?;
+        let subscriber = Self::new(writer);
+        let guard = subscriber.flush_on_drop();
+        Ok((subscriber, guard))
+    }
+}
+
 impl<C, W> Subscribe<C> for FlameSubscriber<C, W>
 where
     C: Collect + for<'span> LookupSpan<'span>,
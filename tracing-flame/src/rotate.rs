@@ -0,0 +1,91 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+/// A writer that rotates the folded output file once it grows past a size
+/// limit, bounding the disk space a long-running service can consume.
+///
+/// When a write would push the current file past the configured size, the
+/// file is closed and renamed to `<path>.1` (shifting any existing
+/// `<path>.N` to `<path>.N+1`, and deleting the oldest file beyond the
+/// configured count), and a fresh file is started at `<path>`. Rotation
+/// only ever happens on a line boundary, so every file holds complete
+/// folded stack samples and can be fed to `inferno-flamegraph` — or to
+/// [`write_diff_folded`] to compare two recording windows — on its own.
+///
+/// Constructed by [`FlameSubscriber::with_rotating_file`].
+///
+/// [`write_diff_folded`]: crate::write_diff_folded
+/// [`FlameSubscriber::with_rotating_file`]: crate::FlameSubscriber::with_rotating_file
+#[derive(Debug)]
+pub struct RotatingWriter {
+    path: PathBuf,
+    file: BufWriter<File>,
+    written: u64,
+    max_bytes: u64,
+    max_files: usize,
+    /// Holds any trailing partial line until its newline arrives, so
+    /// rotation never splits a sample across files.
+    partial: Vec<u8>,
+}
+
+impl RotatingWriter {
+    /// Returns a new `RotatingWriter` writing to `path`, rotating once a
+    /// file exceeds `max_bytes` and keeping at most `max_files` rotated
+    /// files alongside the active one.
+    pub(crate) fn create(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(&path)?);
+        Ok(Self {
+            path,
+            file,
+            written: 0,
+            max_bytes: max_bytes.max(1),
+            max_files: max_files.max(1),
+            partial: Vec::new(),
+        })
+    }
+
+    /// Writes one complete line, rotating first if it would not fit.
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.written > 0 && self.written + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    /// Closes the current file, shifts the rotated files, and starts a
+    /// fresh one.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let rotated = |index: usize| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{}", index));
+            PathBuf::from(path)
+        };
+        let _ = std::fs::remove_file(rotated(self.max_files));
+        for index in (1..self.max_files).rev() {
+            let _ = std::fs::rename(rotated(index), rotated(index + 1));
+        }
+        std::fs::rename(&self.path, rotated(1))?;
+        self.file = BufWriter::new(File::create(&self.path)?);
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.partial.extend_from_slice(buf);
+        while let Some(newline) = self.partial.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=newline).collect();
+            self.write_line(&line)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
@@ -0,0 +1,67 @@
+use tracing_flame::{write_diff_folded, FlameSubscriber};
+use tracing_subscriber::{prelude::*, registry::Registry};
+
+#[test]
+fn rotation_bounds_file_size_and_keeps_windows() {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("tracing-flamegraph-test-")
+        .tempdir()
+        .expect("failed to create tempdir");
+    let path = tmp_dir.path().join("tracing.folded");
+
+    let (flame_layer, guard) =
+        FlameSubscriber::with_rotating_file(&path, 256, 2).expect("failed to create writer");
+    let subscriber = Registry::default().with(flame_layer);
+
+    tracing::collect::with_default(subscriber, || {
+        for _ in 0..100 {
+            let span = tracing::span!(tracing::Level::ERROR, "outer");
+            let _guard = span.enter();
+            let span = tracing::span!(tracing::Level::ERROR, "inner");
+            let _guard = span.enter();
+        }
+    });
+    guard.flush().expect("failed to flush");
+
+    // The active file stayed within the limit (plus at most one sample),
+    // and rotated windows were produced and capped at `max_files`.
+    let active = std::fs::read_to_string(&path).expect("active file missing");
+    let rotated = std::fs::read_to_string(path.with_extension("folded.1"))
+        .expect("first rotated file missing");
+    assert!(path.with_extension("folded.2").exists());
+    assert!(!path.with_extension("folded.3").exists());
+
+    // Every file holds only complete samples: each line ends in a count.
+    for file in [&active, &rotated] {
+        for line in file.lines() {
+            let (_, count) = line.rsplit_once(' ').expect("line has no count");
+            count.parse::<u128>().expect("count is not a number");
+        }
+    }
+
+    tmp_dir.close().expect("failed to delete tempdir");
+}
+
+#[test]
+fn diff_folded_merges_both_windows() {
+    let before = "main;a 10\nmain;a 5\nmain;b 1\n";
+    let after = "main;a 30\nmain;c 2\n";
+
+    let mut out = Vec::new();
+    write_diff_folded(before.as_bytes(), after.as_bytes(), &mut out)
+        .expect("failed to diff folded output");
+
+    let out = String::from_utf8(out).expect("output is not utf8");
+    let lines: Vec<&str> = out.lines().collect();
+    // Identical stacks are summed per window, stacks missing from one
+    // window get a zero count, and the output is sorted.
+    assert_eq!(lines, ["main;a 15 30", "main;b 1 0", "main;c 0 2"]);
+}
+
+#[test]
+fn diff_folded_rejects_malformed_input() {
+    let mut out = Vec::new();
+    let error = write_diff_folded("no count here".as_bytes(), "".as_bytes(), &mut out)
+        .expect_err("malformed input should be rejected");
+    assert!(error.to_string().contains("malformed"));
+}
@@ -79,18 +79,28 @@ mod socket;
 /// each of the event's parent spans.
 ///
 /// User-defined fields other than the event `message` field have a prefix applied by default to
-/// prevent collision with standard fields.
+/// prevent collision with standard fields. How event fields map to journal keys can be customized
+/// further with [`Subscriber::with_field_renames`], [`Subscriber::with_field_name_transform`], and
+/// [`Subscriber::with_json_fields`].
 ///
 /// [journald conventions]: https://www.freedesktop.org/software/systemd/man/systemd.journal-fields.html
 pub struct Subscriber {
     #[cfg(unix)]
     socket: UnixDatagram,
+    #[cfg(unix)]
+    journal_path: String,
     field_prefix: Option<String>,
+    field_renames: Vec<(String, String)>,
+    name_transform: Option<Box<NameTransform>>,
+    json_fields: Option<String>,
     syslog_identifier: String,
     additional_fields: Vec<u8>,
     priority_mappings: PriorityMappings,
 }
 
+/// A user-provided rule mapping a tracing field name to a journal field name.
+type NameTransform = dyn Fn(&str) -> String + Send + Sync;
+
 #[cfg(unix)]
 const JOURNALD_PATH: &str = "/run/systemd/journal/socket";
 
@@ -102,24 +112,7 @@ impl Subscriber {
     pub fn new() -> io::Result<Self> {
         #[cfg(unix)]
         {
-            let socket = UnixDatagram::unbound()?;
-            let sub = Self {
-                socket,
-                field_prefix: Some("F".into()),
-                syslog_identifier: std::env::current_exe()
-                    .ok()
-                    .as_ref()
-                    .and_then(|p| p.file_name())
-                    .map(|n| n.to_string_lossy().into_owned())
-                    // If we fail to get the name of the current executable fall back to an empty string.
-                    .unwrap_or_default(),
-                additional_fields: Vec::new(),
-                priority_mappings: PriorityMappings::new(),
-            };
-            // Check that we can talk to journald, by sending empty payload which journald discards.
-            // However if the socket didn't exist or if none listened we'd get an error here.
-            sub.send_payload(&[])?;
-            Ok(sub)
+            Self::connect(JOURNALD_PATH.to_owned())
         }
         #[cfg(not(unix))]
         Err(io::Error::new(
@@ -128,6 +121,59 @@ impl Subscriber {
         ))
     }
 
+    /// Construct a journald subscriber sending to a [journal namespace].
+    ///
+    /// Journal namespaces are independent journald instances (started as
+    /// `systemd-journald@<namespace>.service`) with their own storage and rotation; their entries
+    /// are read with `journalctl --namespace=<namespace>`. This connects to the namespace
+    /// instance's native socket instead of the default journal's.
+    ///
+    /// Fails if the namespace's socket couldn't be opened, which usually means its journald
+    /// instance is not running. Returns a `NotFound` error unconditionally in non-Unix
+    /// environments.
+    ///
+    /// [journal namespace]: https://www.freedesktop.org/software/systemd/man/systemd-journald.service.html#Journal%20Namespaces
+    pub fn in_namespace(namespace: &str) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            Self::connect(format!("/run/systemd/journal.{}/socket", namespace))
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = namespace;
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "journald does not exist in this environment",
+            ))
+        }
+    }
+
+    #[cfg(unix)]
+    fn connect(journal_path: String) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        let sub = Self {
+            socket,
+            journal_path,
+            field_prefix: Some("F".into()),
+            field_renames: Vec::new(),
+            name_transform: None,
+            json_fields: None,
+            syslog_identifier: std::env::current_exe()
+                .ok()
+                .as_ref()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().into_owned())
+                // If we fail to get the name of the current executable fall back to an empty string.
+                .unwrap_or_default(),
+            additional_fields: Vec::new(),
+            priority_mappings: PriorityMappings::new(),
+        };
+        // Check that we can talk to journald, by sending empty payload which journald discards.
+        // However if the socket didn't exist or if none listened we'd get an error here.
+        sub.send_payload(&[])?;
+        Ok(sub)
+    }
+
     /// Sets the prefix to apply to names of user-defined fields other than the event `message`
     /// field. Defaults to `Some("F")`.
     pub fn with_field_prefix(mut self, x: Option<String>) -> Self {
@@ -135,6 +181,83 @@ impl Subscriber {
         self
     }
 
+    /// Maps event fields to explicit journal field names.
+    ///
+    /// The input values of this function are interpreted as `(tracing field, journal field)`
+    /// pairs. A renamed field is emitted under the given journal name verbatim (after the usual
+    /// sanitization): the field prefix, the name transform, and [`with_json_fields`] do not apply
+    /// to it. This is the way to route a field to a standard journal field, for example mapping an
+    /// application's `file` field to `CODE_FILE`.
+    ///
+    /// ```no_run
+    /// # use tracing_journald::Subscriber;
+    /// let sub = Subscriber::new()
+    ///     .unwrap()
+    ///     .with_field_renames([("file", "CODE_FILE"), ("unit", "UNIT")]);
+    /// ```
+    ///
+    /// Renames apply to event fields only; span fields keep the default mapping.
+    ///
+    /// [`with_json_fields`]: Self::with_json_fields
+    pub fn with_field_renames<T: AsRef<str>, U: Into<String>>(
+        mut self,
+        renames: impl IntoIterator<Item = (T, U)>,
+    ) -> Self {
+        self.field_renames.extend(
+            renames
+                .into_iter()
+                .map(|(from, to)| (from.as_ref().to_owned(), to.into())),
+        );
+        self
+    }
+
+    /// Sets a custom rule for deriving journal field names from event field names.
+    ///
+    /// The transform replaces the default prefixing: it is given each user-defined event field
+    /// name (other than `message`, which always maps to `MESSAGE`, and fields covered by
+    /// [`with_field_renames`]) and returns the journal field name to use. The returned name is
+    /// still sanitized into journald-compliant form, so a transform cannot produce a field
+    /// journald would discard.
+    ///
+    /// ```no_run
+    /// # use tracing_journald::Subscriber;
+    /// let sub = Subscriber::new()
+    ///     .unwrap()
+    ///     .with_field_name_transform(|name| format!("APP_{}", name.to_uppercase()));
+    /// ```
+    ///
+    /// The transform applies to event fields only; span fields keep the default mapping.
+    ///
+    /// [`with_field_renames`]: Self::with_field_renames
+    pub fn with_field_name_transform(
+        mut self,
+        transform: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.name_transform = Some(Box::new(transform));
+        self
+    }
+
+    /// Serializes user-defined event fields into a single JSON journal field.
+    ///
+    /// Instead of one journal field per event field, the fields (other than `message` and fields
+    /// covered by [`with_field_renames`]) are gathered into one JSON object emitted under the
+    /// given journal name. This keeps events with many, nested, or large fields from bloating the
+    /// journal's field index, at the cost of making the individual values opaque to
+    /// `journalctl`'s field matches.
+    ///
+    /// ```no_run
+    /// # use tracing_journald::Subscriber;
+    /// let sub = Subscriber::new().unwrap().with_json_fields("FIELDS");
+    /// ```
+    ///
+    /// This applies to event fields only; span fields keep the default mapping.
+    ///
+    /// [`with_field_renames`]: Self::with_field_renames
+    pub fn with_json_fields(mut self, name: impl Into<String>) -> Self {
+        self.json_fields = Some(name.into());
+        self
+    }
+
     /// Sets how [`tracing_core::Level`]s are mapped to [journald priorities](Priority).
     ///
     /// # Examples
@@ -241,7 +364,7 @@ impl Subscriber {
     #[cfg(unix)]
     fn send_payload(&self, payload: &[u8]) -> io::Result<usize> {
         self.socket
-            .send_to(payload, JOURNALD_PATH)
+            .send_to(payload, &self.journal_path)
             .or_else(|error| {
                 if Some(libc::EMSGSIZE) == error.raw_os_error() {
                     self.send_large_payload(payload)
@@ -271,7 +394,7 @@ impl Subscriber {
         // Fully seal the memfd to signal journald that its backing data won't resize anymore
         // and so is safe to mmap.
         memfd::seal_fully(mem.as_raw_fd())?;
-        socket::send_one_fd_to(&self.socket, mem.as_raw_fd(), JOURNALD_PATH)
+        socket::send_one_fd_to(&self.socket, mem.as_raw_fd(), &self.journal_path)
     }
 
     fn put_priority(&self, buf: &mut Vec<u8>, meta: &Metadata) {
@@ -348,10 +471,19 @@ where
         });
         buf.extend_from_slice(&self.additional_fields);
 
-        event.record(&mut EventVisitor::new(
+        let mut visitor = EventVisitor::new(
             &mut buf,
             self.field_prefix.as_deref(),
-        ));
+            &self.field_renames,
+            self.name_transform.as_deref(),
+            self.json_fields.is_some(),
+        );
+        event.record(&mut visitor);
+        if let (Some(name), Some(object)) = (self.json_fields.as_deref(), visitor.into_json()) {
+            put_field_length_encoded(&mut buf, name, |buf| {
+                buf.extend_from_slice(object.as_bytes())
+            });
+        }
 
         // At this point we can't handle the error anymore so just ignore it.
         let _ = self.send_payload(&buf);
@@ -395,11 +527,96 @@ impl Visit for SpanVisitor<'_> {
 struct EventVisitor<'a> {
     buf: &'a mut Vec<u8>,
     prefix: Option<&'a str>,
+    renames: &'a [(String, String)],
+    transform: Option<&'a NameTransform>,
+    /// The in-progress JSON object for `with_json_fields`, if enabled.
+    json: Option<JsonObject>,
+}
+
+/// An in-progress JSON object of event fields.
+struct JsonObject {
+    object: String,
+    first: bool,
+}
+
+impl JsonObject {
+    /// Appends the `"name":` part of a member, with separators.
+    fn member(&mut self, name: &str) -> &mut String {
+        if !self.first {
+            self.object.push(',');
+        }
+        self.first = false;
+        self.object.push('"');
+        json_escape(&mut self.object, name);
+        self.object.push_str("\":");
+        &mut self.object
+    }
+}
+
+/// Appends `value` to `out` with JSON string escaping.
+fn json_escape(out: &mut String, value: &str) {
+    use fmt::Write;
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                write!(out, "\\u{:04x}", c as u32).unwrap();
+            }
+            c => out.push(c),
+        }
+    }
 }
 
 impl<'a> EventVisitor<'a> {
-    fn new(buf: &'a mut Vec<u8>, prefix: Option<&'a str>) -> Self {
-        Self { buf, prefix }
+    fn new(
+        buf: &'a mut Vec<u8>,
+        prefix: Option<&'a str>,
+        renames: &'a [(String, String)],
+        transform: Option<&'a NameTransform>,
+        json: bool,
+    ) -> Self {
+        Self {
+            buf,
+            prefix,
+            renames,
+            transform,
+            json: json.then(|| JsonObject {
+                object: String::from("{"),
+                first: true,
+            }),
+        }
+    }
+
+    /// Returns the JSON object accumulated by `with_json_fields`, if any
+    /// fields were recorded into it.
+    fn into_json(self) -> Option<String> {
+        let mut json = self.json?;
+        if json.first {
+            return None;
+        }
+        json.object.push('}');
+        Some(json.object)
+    }
+
+    /// Returns the explicit journal name `field` is renamed to, if any.
+    fn rename(&self, field: &Field) -> Option<&'a str> {
+        self.renames
+            .iter()
+            .find(|(from, _)| from == field.name())
+            .map(|(_, to)| to.as_str())
+    }
+
+    /// Returns the JSON object if `field` should be serialized into it
+    /// rather than emitted as its own journal field.
+    fn json_for(&mut self, field: &Field) -> Option<&mut JsonObject> {
+        if field.name() == "message" || self.rename(field).is_some() {
+            return None;
+        }
+        self.json.as_mut()
     }
 
     fn put_prefix(&mut self, field: &Field) {
@@ -411,21 +628,83 @@ impl<'a> EventVisitor<'a> {
             }
         }
     }
+
+    /// Emits one journal field for `field`, resolving renames and the name
+    /// transform.
+    fn put_field(&mut self, field: &Field, write_value: impl FnOnce(&mut Vec<u8>)) {
+        if let Some(name) = self.rename(field) {
+            put_field_length_encoded(self.buf, name, write_value);
+        } else if let Some(transform) = self.transform.filter(|_| field.name() != "message") {
+            put_field_length_encoded(self.buf, &transform(field.name()), write_value);
+        } else {
+            self.put_prefix(field);
+            put_field_length_encoded(self.buf, field.name(), write_value);
+        }
+    }
 }
 
 impl Visit for EventVisitor<'_> {
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if let Some(json) = self.json_for(field) {
+            use fmt::Write;
+            write!(json.member(field.name()), "{}", value).unwrap();
+            return;
+        }
+        self.record_debug(field, &value);
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if let Some(json) = self.json_for(field) {
+            use fmt::Write;
+            write!(json.member(field.name()), "{}", value).unwrap();
+            return;
+        }
+        self.record_debug(field, &value);
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if let Some(json) = self.json_for(field) {
+            use fmt::Write;
+            write!(json.member(field.name()), "{}", value).unwrap();
+            return;
+        }
+        self.record_debug(field, &value);
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if value.is_finite() {
+            if let Some(json) = self.json_for(field) {
+                use fmt::Write;
+                write!(json.member(field.name()), "{}", value).unwrap();
+                return;
+            }
+        }
+        // Non-finite values have no JSON representation and are rendered as
+        // strings, like any other field outside the JSON object.
+        self.record_debug(field, &value);
+    }
+
     fn record_str(&mut self, field: &Field, value: &str) {
-        self.put_prefix(field);
-        put_field_length_encoded(self.buf, field.name(), |buf| {
-            buf.extend_from_slice(value.as_bytes())
-        });
+        if let Some(json) = self.json_for(field) {
+            let object = json.member(field.name());
+            object.push('"');
+            json_escape(object, value);
+            object.push('"');
+            return;
+        }
+        self.put_field(field, |buf| buf.extend_from_slice(value.as_bytes()));
     }
 
     fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
-        self.put_prefix(field);
-        put_field_length_encoded(self.buf, field.name(), |buf| {
-            write!(buf, "{:?}", value).unwrap()
-        });
+        if let Some(json) = self.json_for(field) {
+            let rendered = format!("{:?}", value);
+            let object = json.member(field.name());
+            object.push('"');
+            json_escape(object, &rendered);
+            object.push('"');
+            return;
+        }
+        self.put_field(field, |buf| write!(buf, "{:?}", value).unwrap());
     }
 }
 
@@ -303,6 +303,85 @@ fn journal_fields() {
     });
 }
 
+#[test]
+fn field_renames() {
+    let sub = Subscriber::new()
+        .unwrap()
+        .with_field_prefix(None)
+        .with_field_renames([("file", "CODE_FILE"), ("unit", "MAPPED_UNIT")]);
+    with_journald_subscriber(sub, || {
+        info!(
+            test.name = "field_renames",
+            file = "app.rs",
+            unit = "worker",
+            other = "stays",
+            "Hello World"
+        );
+
+        let message = retry_read_one_line_from_journal("field_renames");
+        assert_eq!(message["MESSAGE"], "Hello World");
+        // `file` joins the CODE_FILE emitted from the event's metadata.
+        assert_eq!(message["CODE_FILE"].as_array().unwrap().len(), 2);
+        assert_eq!(message["MAPPED_UNIT"], "worker");
+        // Unrenamed fields keep the default mapping.
+        assert_eq!(message["OTHER"], "stays");
+        assert!(!message.contains_key("UNIT"));
+    });
+}
+
+#[test]
+fn field_name_transform() {
+    let sub = Subscriber::new()
+        .unwrap()
+        // Renames take precedence over the transform, keeping the filter
+        // field queryable.
+        .with_field_renames([("test.name", "TEST_NAME")])
+        .with_field_name_transform(|name| format!("APP_{}", name.to_uppercase()));
+    with_journald_subscriber(sub, || {
+        info!(
+            test.name = "field_name_transform",
+            request = "abc",
+            "Hello World"
+        );
+
+        let message = retry_read_one_line_from_journal("field_name_transform");
+        assert_eq!(message["MESSAGE"], "Hello World");
+        // The transform replaces the default prefix.
+        assert_eq!(message["APP_REQUEST"], "abc");
+        assert!(!message.contains_key("F_REQUEST"));
+    });
+}
+
+#[test]
+fn json_fields() {
+    let sub = Subscriber::new()
+        .unwrap()
+        // Renames take precedence over the JSON object, keeping the filter
+        // field queryable.
+        .with_field_renames([("test.name", "TEST_NAME")])
+        .with_json_fields("FIELDS");
+    with_journald_subscriber(sub, || {
+        info!(
+            test.name = "json_fields",
+            user = "alice",
+            attempts = 3,
+            "Hello World"
+        );
+
+        let message = retry_read_one_line_from_journal("json_fields");
+        assert_eq!(message["MESSAGE"], "Hello World");
+        let fields: serde_json::Value =
+            serde_json::from_str(message["FIELDS"].as_text().unwrap()).unwrap();
+        assert_eq!(fields["user"], "alice");
+        assert_eq!(fields["attempts"], 3);
+        // The renamed filter field stays out of the JSON object.
+        assert!(fields.get("test.name").is_none());
+        // No per-field entries are emitted.
+        assert!(!message.contains_key("F_USER"));
+        assert!(!message.contains_key("F_ATTEMPTS"));
+    });
+}
+
 #[test]
 fn span_metadata() {
     with_journald(|| {
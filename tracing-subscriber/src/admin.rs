@@ -0,0 +1,187 @@
+//! A control endpoint for changing an [`EnvFilter`] on a running process.
+//!
+//! This module serves a small line-based protocol over a TCP or Unix domain
+//! socket, wired to a [`reload::Handle`]`<`[`EnvFilter`]`>`. It allows
+//! operators to inspect and change a service's filtering directives at
+//! runtime without the service embedding its own control plumbing.
+//!
+//! # Protocol
+//!
+//! Each command is a single line, and each command produces a single line in
+//! response. Responses begin with either `OK` or `ERR`, followed by a space
+//! and the response body. The commands are:
+//!
+//! - `get`: responds with the current filter's directives.
+//! - `set <directives>`: parses `<directives>` as an [`EnvFilter`] and
+//!   replaces the current filter with it, responding with the new directives.
+//!   If the directives are invalid, responds with an error and leaves the
+//!   current filter unchanged.
+//! - `reset`: restores the filter that was active when the endpoint was
+//!   started.
+//!
+//! Command names are case-insensitive. For example, using `netcat`:
+//!
+//! ```text
+//! $ echo "set mycrate=debug" | nc localhost 6669
+//! OK mycrate=debug
+//! ```
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{admin, filter::EnvFilter, reload, prelude::*};
+//!
+//! let (filter, handle) = reload::Subscriber::new(EnvFilter::new("info"));
+//! let collector = tracing_subscriber::registry().with(filter);
+//! tracing::collect::set_global_default(collector)
+//!     .expect("failed to set global default collector");
+//!
+//! admin::serve_tcp(handle, "127.0.0.1:6669")
+//!     .expect("failed to bind the admin endpoint");
+//! ```
+//!
+//! # Security
+//!
+//! The endpoint performs no authentication; anyone who can connect to it can
+//! change the process' filter. Bind it to a loopback address or a Unix socket
+//! with appropriate permissions, never to a publicly reachable address.
+//!
+//! [`reload::Handle`]: crate::reload::Handle
+use crate::{filter::EnvFilter, reload};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, ToSocketAddrs},
+    thread,
+};
+
+/// A running admin endpoint.
+///
+/// This is returned by [`serve_tcp`] and [`serve_unix`]. The endpoint is
+/// served on a background thread, which runs until the process exits;
+/// dropping the `Server` does not shut it down.
+#[derive(Debug)]
+pub struct Server {
+    addr: Option<SocketAddr>,
+}
+
+/// Starts serving the admin endpoint on a TCP socket bound to `addr`,
+/// controlling the filter behind `handle`.
+///
+/// The returned [`Server`]'s [`local_addr`] method returns the address the
+/// listener was actually bound to, which is useful when binding to port 0.
+///
+/// [`local_addr`]: Server::local_addr
+pub fn serve_tcp(handle: reload::Handle<EnvFilter>, addr: impl ToSocketAddrs) -> io::Result<Server> {
+    let listener = TcpListener::bind(addr)?;
+    let addr = listener.local_addr().ok();
+    let initial = initial_filter(&handle)?;
+    spawn_server(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = handle_connection(&handle, &initial, stream);
+        }
+    })?;
+    Ok(Server { addr })
+}
+
+/// Starts serving the admin endpoint on a Unix domain socket bound to `path`,
+/// controlling the filter behind `handle`.
+///
+/// If a socket file already exists at `path`, it is removed before binding.
+#[cfg(unix)]
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+pub fn serve_unix(
+    handle: reload::Handle<EnvFilter>,
+    path: impl AsRef<std::path::Path>,
+) -> io::Result<Server> {
+    let path = path.as_ref();
+    // Binding fails if a previous instance's socket file was left behind, so
+    // remove it first. A failure here is ignored; if the file still exists,
+    // `bind` will return the more descriptive error.
+    let _ = std::fs::remove_file(path);
+    let listener = std::os::unix::net::UnixListener::bind(path)?;
+    let initial = initial_filter(&handle)?;
+    spawn_server(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = handle_connection(&handle, &initial, stream);
+        }
+    })?;
+    Ok(Server { addr: None })
+}
+
+impl Server {
+    /// Returns the local address of the TCP listener, or `None` if the
+    /// endpoint is served on a Unix domain socket.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+}
+
+/// Captures the current filter's directives, so that `reset` can restore
+/// them later.
+fn initial_filter(handle: &reload::Handle<EnvFilter>) -> io::Result<String> {
+    handle
+        .with_current(ToString::to_string)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
+}
+
+fn spawn_server(serve: impl FnOnce() + Send + 'static) -> io::Result<()> {
+    thread::Builder::new()
+        .name("tracing-admin".into())
+        .spawn(serve)?;
+    Ok(())
+}
+
+/// Reads commands from `stream` line by line, writing a response line for
+/// each, until the peer closes the connection.
+fn handle_connection(
+    handle: &reload::Handle<EnvFilter>,
+    initial: &str,
+    stream: impl Read + Write,
+) -> io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if stream.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let response = match respond(handle, initial, line.trim()) {
+            Ok(msg) => format!("OK {}\n", msg),
+            Err(msg) => format!("ERR {}\n", msg),
+        };
+        stream.get_mut().write_all(response.as_bytes())?;
+    }
+}
+
+fn respond(
+    handle: &reload::Handle<EnvFilter>,
+    initial: &str,
+    line: &str,
+) -> Result<String, String> {
+    let mut parts = line.splitn(2, char::is_whitespace);
+    let command = parts.next().unwrap_or("");
+    let rest = parts.next().unwrap_or("").trim();
+    if command.eq_ignore_ascii_case("get") && rest.is_empty() {
+        handle
+            .with_current(ToString::to_string)
+            .map_err(|e| e.to_string())
+    } else if command.eq_ignore_ascii_case("set") && !rest.is_empty() {
+        set_filter(handle, rest)
+    } else if command.eq_ignore_ascii_case("reset") && rest.is_empty() {
+        set_filter(handle, initial)
+    } else {
+        Err(format!(
+            "unknown command {:?}; expected `get`, `set <directives>`, or `reset`",
+            line
+        ))
+    }
+}
+
+fn set_filter(handle: &reload::Handle<EnvFilter>, directives: &str) -> Result<String, String> {
+    let filter = directives
+        .parse::<EnvFilter>()
+        .map_err(|e| e.to_string())?;
+    let display = filter.to_string();
+    handle.reload(filter).map_err(|e| e.to_string())?;
+    Ok(display)
+}
@@ -0,0 +1,344 @@
+//! Baggage: propagated key-value context, distinct from span fields.
+//!
+//! Span fields describe the span that declares them; baggage describes the
+//! *request*, and flows down to everything that happens on its behalf — an
+//! MDC in logging terms. Users keep simulating this with a span holding a
+//! single field; this module provides the real thing: values set in the
+//! current context are visible to all descendant spans and events (and so
+//! to filters and exporters), and can be rendered to and parsed from the
+//! [W3C Baggage] header for cross-process propagation.
+//!
+//! Baggage lives in a thread-local scope established with [`with_value`]
+//! or [`with_baggage`] and read with [`get`] or [`current`]. The optional
+//! [`Subscriber`] snapshots the baggage in scope when a span is created
+//! and re-establishes it whenever that span is entered, so baggage follows
+//! spans across threads and detached executions.
+//!
+//! # Examples
+//!
+//! ```
+//! use tracing_subscriber::baggage;
+//!
+//! baggage::with_value("tenant", "acme", || {
+//!     // Everything in here — including descendant spans and events —
+//!     // sees the tenant.
+//!     assert_eq!(baggage::get("tenant").as_deref(), Some("acme"));
+//!
+//!     // Pass it on to the next service.
+//!     let header = baggage::header();
+//!     assert_eq!(header, "tenant=acme");
+//! });
+//! assert_eq!(baggage::get("tenant"), None);
+//! ```
+//!
+//! [W3C Baggage]: https://www.w3.org/TR/baggage/
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{cell::RefCell, fmt::Write as _};
+use tracing_core::{span, Collect};
+
+thread_local! {
+    /// The baggage entries in scope on this thread, outermost first.
+    static STACK: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A snapshot of baggage entries, as carried by spans and parsed from
+/// headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baggage {
+    entries: Vec<(String, String)>,
+}
+
+/// A [`Subscribe`] implementation that makes baggage follow spans.
+///
+/// Each new span snapshots the baggage in scope at its creation; entering
+/// the span re-establishes that snapshot, so baggage set around a span's
+/// creation is visible wherever the span is later entered — including on
+/// other threads.
+#[derive(Debug, Default)]
+pub struct Subscriber {
+    _private: (),
+}
+
+// === impl Baggage ===
+
+impl Baggage {
+    /// Returns an empty baggage snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value for `key`, if present.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Adds an entry, shadowing any earlier entry with the same key.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.entries.push((key.into(), value.into()));
+    }
+
+    /// Returns the entries in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Returns whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Renders the entries as a W3C `baggage` header value, such as
+    /// `tenant=acme,run=42`.
+    ///
+    /// Values are percent-encoded where the header syntax requires it;
+    /// keys are written as-is and should be plain tokens.
+    pub fn to_header(&self) -> String {
+        let mut header = String::new();
+        for (key, value) in &self.entries {
+            if !header.is_empty() {
+                header.push(',');
+            }
+            header.push_str(key);
+            header.push('=');
+            percent_encode_into(&mut header, value);
+        }
+        header
+    }
+
+    /// Parses a W3C `baggage` header value, skipping malformed entries.
+    ///
+    /// Entry properties (anything after a `;`) are dropped, as this
+    /// implementation does not interpret them.
+    pub fn parse(header: &str) -> Self {
+        let mut baggage = Self::new();
+        for entry in header.split(',') {
+            let entry = entry.split(';').next().unwrap_or("").trim();
+            if let Some((key, value)) = entry.split_once('=') {
+                let (key, value) = (key.trim(), value.trim());
+                if !key.is_empty() {
+                    baggage.insert(key, percent_decode(value));
+                }
+            }
+        }
+        baggage
+    }
+}
+
+/// Runs `f` with `key` set to `value` in the current baggage scope.
+///
+/// The entry shadows any outer entry with the same key for the duration of
+/// the closure, and is removed afterwards.
+pub fn with_value<T>(key: impl Into<String>, value: impl Into<String>, f: impl FnOnce() -> T) -> T {
+    let _guard = push(vec![(key.into(), value.into())]);
+    f()
+}
+
+/// Runs `f` with every entry of `baggage` in scope, typically after
+/// parsing an incoming request's header with [`Baggage::parse`].
+pub fn with_baggage<T>(baggage: Baggage, f: impl FnOnce() -> T) -> T {
+    let _guard = push(baggage.entries);
+    f()
+}
+
+/// Returns the value for `key` in the current scope, innermost entry
+/// winning.
+pub fn get(key: &str) -> Option<String> {
+    STACK.with(|stack| {
+        stack
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.clone())
+    })
+}
+
+/// Returns a snapshot of the baggage in the current scope.
+pub fn current() -> Baggage {
+    STACK.with(|stack| Baggage {
+        entries: stack.borrow().clone(),
+    })
+}
+
+/// Renders the baggage in the current scope as a W3C `baggage` header
+/// value.
+pub fn header() -> String {
+    current().to_header()
+}
+
+/// Pushes entries onto the thread's scope, popping them when the returned
+/// guard drops.
+fn push(entries: Vec<(String, String)>) -> impl Drop {
+    struct Guard(usize);
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            STACK.with(|stack| {
+                let mut stack = stack.borrow_mut();
+                let keep = stack.len().saturating_sub(self.0);
+                stack.truncate(keep);
+            });
+        }
+    }
+
+    let count = entries.len();
+    STACK.with(|stack| stack.borrow_mut().extend(entries));
+    Guard(count)
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new baggage-carrying subscriber.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let baggage = current();
+        if baggage.is_empty() {
+            return;
+        }
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        span.extensions_mut().insert(baggage);
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let extensions = span.extensions();
+        if let Some(baggage) = extensions.get::<Baggage>() {
+            let entries = baggage.entries.clone();
+            STACK.with(|stack| stack.borrow_mut().extend(entries));
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let extensions = span.extensions();
+        if let Some(baggage) = extensions.get::<Baggage>() {
+            let count = baggage.entries.len();
+            STACK.with(|stack| {
+                let mut stack = stack.borrow_mut();
+                let keep = stack.len().saturating_sub(count);
+                stack.truncate(keep);
+            });
+        }
+    }
+}
+
+/// Writes `value` into `out`, percent-encoding bytes the baggage header
+/// syntax does not allow in values.
+fn percent_encode_into(out: &mut String, value: &str) {
+    for byte in value.bytes() {
+        match byte {
+            b'%' | b',' | b';' | b'=' | b'"' | b'\\' => {
+                let _ = write!(out, "%{:02X}", byte);
+            }
+            b'!'..=b'~' => out.push(byte as char),
+            byte => {
+                let _ = write!(out, "%{:02X}", byte);
+            }
+        }
+    }
+}
+
+/// Decodes percent-escapes in a baggage value, leaving malformed escapes
+/// as-is.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let (Some(high), Some(low)) = (
+                bytes.get(i + 1).and_then(|b| (*b as char).to_digit(16)),
+                bytes.get(i + 2).and_then(|b| (*b as char).to_digit(16)),
+            ) {
+                out.push((high * 16 + low) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn values_scope_to_the_closure() {
+        assert_eq!(get("tenant"), None);
+        with_value("tenant", "acme", || {
+            assert_eq!(get("tenant").as_deref(), Some("acme"));
+            with_value("tenant", "globex", || {
+                assert_eq!(get("tenant").as_deref(), Some("globex"));
+            });
+            assert_eq!(get("tenant").as_deref(), Some("acme"));
+        });
+        assert_eq!(get("tenant"), None);
+    }
+
+    #[test]
+    fn headers_round_trip() {
+        let header = with_value("tenant", "acme corp", || {
+            with_value("note", "a,b=c;d%", header)
+        });
+        assert_eq!(header, "tenant=acme%20corp,note=a%2Cb%3Dc%3Bd%25");
+
+        let parsed = Baggage::parse(&header);
+        assert_eq!(parsed.get("tenant"), Some("acme corp"));
+        assert_eq!(parsed.get("note"), Some("a,b=c;d%"));
+    }
+
+    #[test]
+    fn malformed_entries_are_skipped() {
+        let parsed = Baggage::parse("tenant=acme, bare , run=42;prop=1, =empty");
+        assert_eq!(parsed.get("tenant"), Some("acme"));
+        assert_eq!(parsed.get("run"), Some("42"));
+        assert_eq!(parsed.iter().count(), 2);
+    }
+
+    #[test]
+    fn parsed_baggage_can_be_established() {
+        let parsed = Baggage::parse("tenant=acme,region=eu");
+        with_baggage(parsed, || {
+            assert_eq!(get("tenant").as_deref(), Some("acme"));
+            assert_eq!(get("region").as_deref(), Some("eu"));
+        });
+        assert_eq!(get("tenant"), None);
+    }
+
+    #[test]
+    fn baggage_follows_spans_across_scopes() {
+        let collector = crate::registry().with(Subscriber::new());
+        with_default(collector, || {
+            let span = with_value("tenant", "acme", || tracing::info_span!("job"));
+            // The creating scope has ended, but the span still carries it.
+            assert_eq!(get("tenant"), None);
+
+            let entered = span.enter();
+            assert_eq!(get("tenant").as_deref(), Some("acme"));
+            drop(entered);
+            assert_eq!(get("tenant"), None);
+        });
+    }
+}
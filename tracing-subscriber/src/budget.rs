@@ -0,0 +1,529 @@
+//! Per-tenant logging budgets, for noisy-neighbor protection.
+//!
+//! In a multi-tenant service, one misbehaving tenant can flood the
+//! telemetry pipeline and drown out — or price out — everyone else. This
+//! module provides a [`Subscriber`] that tracks event volume per value of
+//! a configured field (such as `tenant_id`), and starts dropping events
+//! for values that exceed their budget within a sliding time window. When
+//! events are being dropped, a background thread periodically emits a
+//! `WARN` summary reporting how many, so the enforcement itself is
+//! visible without adding to the flood. (The summaries cannot be emitted
+//! inline: dispatching an event from within event dispatch is a no-op.)
+//!
+//! The tenant value is taken from the event's own fields if present, or
+//! from the nearest span in scope that recorded the field; events that
+//! carry no tenant value at all are never limited. Dropped events are
+//! vetoed for the entire collector, so they cost neither formatting nor
+//! export.
+//!
+//! The window uses the standard sliding-window approximation: the
+//! previous window's count is weighted by how much of it still overlaps
+//! the sliding window, so budgets cannot be doubled by bursting across a
+//! window boundary.
+//!
+//! # Examples
+//!
+//! ```
+//! use tracing_subscriber::{budget, prelude::*};
+//!
+//! let collector = tracing_subscriber::registry()
+//!     .with(
+//!         budget::Subscriber::builder("tenant_id")
+//!             .with_budget(500)
+//!             .with_window(std::time::Duration::from_secs(1))
+//!             .with_budget_for("trusted-internal", 50_000)
+//!             .finish(),
+//!     )
+//!     .with(tracing_subscriber::fmt::subscriber());
+//! # let _ = collector;
+//! ```
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+use tracing_core::{dispatch, field, span, Collect, Event};
+
+/// The default number of events allowed per tenant per window.
+const DEFAULT_BUDGET: u64 = 500;
+/// The default window length.
+const DEFAULT_WINDOW: Duration = Duration::from_secs(1);
+/// The default minimum interval between budget-exceeded summaries.
+const DEFAULT_SUMMARY_INTERVAL: Duration = Duration::from_secs(10);
+
+thread_local! {
+    /// Set while emitting summary events, so the summaries themselves are
+    /// not budget-checked.
+    static IN_SUMMARY: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A [`Subscribe`] implementation that enforces per-tenant event budgets.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    field: &'static str,
+    budget: u64,
+    budgets: HashMap<String, u64>,
+    window: Duration,
+    shared: Arc<Shared>,
+}
+
+/// State shared between the subscriber and the summary thread.
+#[derive(Debug, Default)]
+struct Shared {
+    tenants: Mutex<HashMap<String, Tenant>>,
+    /// The dispatch the subscriber is installed in, captured when the
+    /// collector is registered, so the summary thread can emit through it.
+    dispatch: Mutex<Option<dispatch::WeakDispatch>>,
+}
+
+/// Configures a budget [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    field: &'static str,
+    budget: u64,
+    budgets: HashMap<String, u64>,
+    window: Duration,
+    summary_interval: Duration,
+}
+
+/// Per-tenant accounting state.
+#[derive(Debug)]
+struct Tenant {
+    window_start: Instant,
+    current: u64,
+    previous: u64,
+    /// Events dropped since the last summary.
+    dropped: u64,
+}
+
+/// The tenant value recorded on a span, stored in its extensions.
+#[derive(Debug, Clone)]
+struct TenantTag(String);
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new budget subscriber keyed on `field`, with the default
+    /// configuration: 500 events per tenant per one-second window, and a
+    /// summary at most every ten seconds.
+    pub fn new(field: &'static str) -> Self {
+        Self::builder(field).finish()
+    }
+
+    /// Returns a [`Builder`] for configuring a budget subscriber keyed on
+    /// `field`.
+    pub fn builder(field: &'static str) -> Builder {
+        Builder {
+            field,
+            budget: DEFAULT_BUDGET,
+            budgets: HashMap::new(),
+            window: DEFAULT_WINDOW,
+            summary_interval: DEFAULT_SUMMARY_INTERVAL,
+        }
+    }
+
+    /// Returns the budget for `tenant`, honoring per-tenant overrides.
+    fn budget_for(&self, tenant: &str) -> u64 {
+        self.budgets.get(tenant).copied().unwrap_or(self.budget)
+    }
+
+    /// Charges one event to `tenant`'s budget, returning whether the event
+    /// is allowed.
+    fn charge(&self, tenant: &str, now: Instant) -> bool {
+        let budget = self.budget_for(tenant);
+        let mut tenants = self.shared.tenants.lock().expect("budget state poisoned");
+        let state = tenants.entry(tenant.to_string()).or_insert_with(|| Tenant {
+            window_start: now,
+            current: 0,
+            previous: 0,
+            dropped: 0,
+        });
+
+        // Roll the window forward to contain `now`.
+        let mut elapsed = now.saturating_duration_since(state.window_start);
+        if elapsed >= self.window * 2 {
+            state.previous = 0;
+            state.current = 0;
+            state.window_start = now;
+            elapsed = Duration::ZERO;
+        } else if elapsed >= self.window {
+            state.previous = state.current;
+            state.current = 0;
+            state.window_start += self.window;
+            elapsed -= self.window;
+        }
+
+        // Weight the previous window by how much of it the sliding window
+        // still overlaps.
+        let overlap = 1.0 - elapsed.as_secs_f64() / self.window.as_secs_f64();
+        let estimate = state.previous as f64 * overlap + state.current as f64;
+        if estimate < budget as f64 {
+            state.current += 1;
+            true
+        } else {
+            state.dropped += 1;
+            false
+        }
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_register_dispatch(&self, collector: &dispatch::Dispatch) {
+        // Keep a weak handle to the dispatch, so the summary thread can
+        // emit events through the collector this subscriber is part of.
+        let mut slot = self.shared.dispatch.lock().expect("budget state poisoned");
+        *slot = Some(collector.downgrade());
+    }
+
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let mut visitor = TenantVisitor {
+            field: self.field,
+            value: None,
+        };
+        attrs.record(&mut visitor);
+        if let Some(value) = visitor.value {
+            let span = ctx.span(id).expect("Span not found, this is a bug");
+            span.extensions_mut().insert(TenantTag(value));
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        let mut visitor = TenantVisitor {
+            field: self.field,
+            value: None,
+        };
+        values.record(&mut visitor);
+        if let Some(value) = visitor.value {
+            let span = ctx.span(id).expect("Span not found, this is a bug");
+            span.extensions_mut().insert(TenantTag(value));
+        }
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, ctx: Context<'_, C>) -> bool {
+        if IN_SUMMARY.with(Cell::get) {
+            return true;
+        }
+
+        // The event's own field wins; otherwise take the nearest span in
+        // scope that recorded one.
+        let mut visitor = TenantVisitor {
+            field: self.field,
+            value: None,
+        };
+        event.record(&mut visitor);
+        let tenant = visitor.value.or_else(|| {
+            ctx.event_scope(event)?.find_map(|span| {
+                let extensions = span.extensions();
+                extensions.get::<TenantTag>().map(|tag| tag.0.clone())
+            })
+        });
+        let tenant = match tenant {
+            Some(tenant) => tenant,
+            // No tenant attribution; never limited.
+            None => return true,
+        };
+
+        self.charge(&tenant, Instant::now())
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the number of events each tenant may record per window.
+    ///
+    /// The default is 500.
+    pub fn with_budget(self, budget: u64) -> Self {
+        Self {
+            budget: budget.max(1),
+            ..self
+        }
+    }
+
+    /// Overrides the budget for one specific tenant value.
+    pub fn with_budget_for(mut self, tenant: impl Into<String>, budget: u64) -> Self {
+        self.budgets.insert(tenant.into(), budget.max(1));
+        self
+    }
+
+    /// Sets the length of the sliding window.
+    ///
+    /// The default is one second.
+    pub fn with_window(self, window: Duration) -> Self {
+        Self {
+            window: window.max(Duration::from_millis(1)),
+            ..self
+        }
+    }
+
+    /// Sets how often budget-exceeded summaries are emitted.
+    ///
+    /// Every interval, the summary thread emits one `WARN` event for each
+    /// tenant that had events dropped since the previous summary. The
+    /// default is ten seconds.
+    pub fn with_summary_interval(self, interval: Duration) -> Self {
+        Self {
+            summary_interval: interval.max(Duration::from_millis(1)),
+            ..self
+        }
+    }
+
+    /// Returns the configured budget [`Subscriber`], spawning its summary
+    /// thread.
+    pub fn finish(self) -> Subscriber {
+        let shared = Arc::new(Shared::default());
+        let worker = SummaryWorker {
+            shared: shared.clone(),
+            budget: self.budget,
+            budgets: self.budgets.clone(),
+            window: self.window,
+            interval: self.summary_interval,
+        };
+        // If the thread cannot be spawned, budgets are still enforced;
+        // only the summaries are lost.
+        let _ = thread::Builder::new()
+            .name("tracing-budget".into())
+            .spawn(move || worker.run());
+        Subscriber {
+            field: self.field,
+            budget: self.budget,
+            budgets: self.budgets,
+            window: self.window,
+            shared,
+        }
+    }
+}
+
+/// Emits periodic summaries of dropped-event counts.
+struct SummaryWorker {
+    shared: Arc<Shared>,
+    budget: u64,
+    budgets: HashMap<String, u64>,
+    window: Duration,
+    interval: Duration,
+}
+
+// === impl SummaryWorker ===
+
+impl SummaryWorker {
+    fn run(&self) {
+        loop {
+            thread::sleep(self.interval);
+            let weak = self
+                .shared
+                .dispatch
+                .lock()
+                .expect("budget state poisoned")
+                .clone();
+            let dispatch = match weak.and_then(|weak| weak.upgrade()) {
+                Some(dispatch) => dispatch,
+                // The collector is gone (or no event has been seen yet);
+                // the counts keep accumulating until it is available.
+                None => continue,
+            };
+            let mut pending = Vec::new();
+            {
+                let mut tenants = self.shared.tenants.lock().expect("budget state poisoned");
+                for (tenant, state) in tenants.iter_mut() {
+                    if state.dropped > 0 {
+                        pending.push((tenant.clone(), state.dropped));
+                        state.dropped = 0;
+                    }
+                }
+            }
+            if pending.is_empty() {
+                continue;
+            }
+            IN_SUMMARY.with(|flag| flag.set(true));
+            dispatch::with_default(&dispatch, || {
+                for (tenant, dropped) in pending {
+                    let budget = self.budgets.get(&tenant).copied().unwrap_or(self.budget);
+                    tracing::warn!(
+                        target: "tracing_subscriber::budget",
+                        tenant = %tenant,
+                        dropped,
+                        budget,
+                        window_ms = self.window.as_millis() as u64,
+                        "tenant exceeded its logging budget; events were dropped"
+                    );
+                }
+            });
+            IN_SUMMARY.with(|flag| flag.set(false));
+        }
+    }
+}
+
+/// Extracts the configured tenant field's value as a string.
+struct TenantVisitor {
+    field: &'static str,
+    value: Option<String>,
+}
+
+impl field::Visit for TenantVisitor {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == self.field {
+            self.value = Some(value.to_string());
+        }
+    }
+
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        if field.name() == self.field {
+            self.value = Some(value.to_string());
+        }
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        if field.name() == self.field {
+            self.value = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == self.field {
+            self.value = Some(format!("{:?}", value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::collect::with_default;
+
+    /// Records the target and rendered fields of every event it sees.
+    #[derive(Clone, Default)]
+    struct Sink(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl<C> Subscribe<C> for Sink
+    where
+        C: Collect + for<'a> LookupSpan<'a>,
+    {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+            let mut fields = String::new();
+            event.record(&mut |field: &field::Field, value: &dyn std::fmt::Debug| {
+                use std::fmt::Write as _;
+                let _ = write!(fields, "{}={:?} ", field.name(), value);
+            });
+            self.0
+                .lock()
+                .unwrap()
+                .push((event.metadata().target().to_string(), fields));
+        }
+    }
+
+    impl Sink {
+        fn events(&self) -> Vec<(String, String)> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn tenants_over_budget_are_dropped_and_summarized() {
+        let sink = Sink::default();
+        let budget = Subscriber::builder("tenant_id")
+            .with_budget(2)
+            .with_summary_interval(Duration::from_millis(20))
+            .finish();
+        let collector = crate::registry().with(budget).with(sink.clone());
+        with_default(collector, || {
+            for i in 0..5 {
+                tracing::info!(tenant_id = "acme", i, "hello");
+            }
+            // Give the summary thread a couple of intervals to report.
+            std::thread::sleep(Duration::from_millis(100));
+        });
+
+        let events = sink.events();
+        let delivered = events.iter().filter(|(_, f)| f.contains("hello")).count();
+        assert_eq!(delivered, 2, "{:?}", events);
+        let summaries: Vec<_> = events
+            .iter()
+            .filter(|(target, _)| target == "tracing_subscriber::budget")
+            .collect();
+        assert_eq!(summaries.len(), 1, "{:?}", events);
+        assert!(summaries[0].1.contains("tenant=acme"), "{:?}", summaries);
+        assert!(summaries[0].1.contains("dropped=3"), "{:?}", summaries);
+    }
+
+    #[test]
+    fn events_without_the_field_are_not_limited() {
+        let sink = Sink::default();
+        let budget = Subscriber::builder("tenant_id").with_budget(1).finish();
+        let collector = crate::registry().with(budget).with(sink.clone());
+        with_default(collector, || {
+            for _ in 0..10 {
+                tracing::info!("untenanted");
+            }
+        });
+        assert_eq!(sink.events().len(), 10);
+    }
+
+    #[test]
+    fn the_tenant_is_inherited_from_the_span_scope() {
+        let sink = Sink::default();
+        let budget = Subscriber::builder("tenant_id").with_budget(2).finish();
+        let collector = crate::registry().with(budget).with(sink.clone());
+        with_default(collector, || {
+            let span = tracing::info_span!("request", tenant_id = "acme");
+            let _entered = span.enter();
+            for _ in 0..5 {
+                tracing::info!("from inside the span");
+            }
+        });
+        let delivered = sink
+            .events()
+            .iter()
+            .filter(|(_, f)| f.contains("from inside"))
+            .count();
+        assert_eq!(delivered, 2);
+    }
+
+    #[test]
+    fn per_tenant_overrides_take_precedence() {
+        let sink = Sink::default();
+        let budget = Subscriber::builder("tenant_id")
+            .with_budget(1)
+            .with_budget_for("trusted", 100)
+            .finish();
+        let collector = crate::registry().with(budget).with(sink.clone());
+        with_default(collector, || {
+            for _ in 0..5 {
+                tracing::info!(tenant_id = "trusted", "plenty of room");
+            }
+        });
+        let delivered = sink
+            .events()
+            .iter()
+            .filter(|(_, f)| f.contains("plenty"))
+            .count();
+        assert_eq!(delivered, 5);
+    }
+
+    #[test]
+    fn budgets_recover_after_the_window_slides_past() {
+        let budget = Subscriber::builder("t")
+            .with_budget(1)
+            .with_window(Duration::from_millis(10))
+            .finish();
+        let start = Instant::now();
+        assert!(budget.charge("a", start));
+        assert!(!budget.charge("a", start));
+        // Two full windows later, both buckets have aged out entirely.
+        assert!(budget.charge("a", start + Duration::from_millis(25)));
+    }
+}
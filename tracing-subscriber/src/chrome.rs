@@ -0,0 +1,430 @@
+//! Chrome Trace Event output for spans and events.
+//!
+//! The [Chrome Trace Event format] is a JSON format understood by the
+//! `chrome://tracing` viewer and by [Perfetto] (`ui.perfetto.dev`). This
+//! module provides a [`Subscriber`] that streams spans and events to a
+//! writer in that format, so a trace captured from any `tracing`-instrumented
+//! program can be dropped straight into those UIs and explored on a
+//! per-thread timeline.
+//!
+//! Entering a span writes a `B` (begin) event and exiting it writes an `E`
+//! (end) event, so a span entered several times appears as several slices.
+//! Events are written as `i` (instant) events. Both carry their `tracing`
+//! fields in `args`, where the viewers display them when a slice is
+//! selected. Each OS thread is assigned a `tid` in order of first
+//! appearance, along with a `thread_name` metadata event, so the viewers
+//! label timelines with thread names.
+//!
+//! The output is a JSON array, which must be closed before the viewers will
+//! accept it: keep the [`Guard`] returned by the constructors alive for the
+//! duration of the trace, and drop it to write the closing bracket and
+//! flush the writer.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{chrome, prelude::*};
+//!
+//! let (chrome, guard) = chrome::Subscriber::with_file("trace.json")
+//!     .expect("failed to create the trace file");
+//! let collector = tracing_subscriber::registry().with(chrome);
+//! tracing::collect::set_global_default(collector)
+//!     .expect("failed to set global default collector");
+//!
+//! // ... run the program ...
+//!
+//! // Closes the JSON array so the file can be loaded in the viewers.
+//! drop(guard);
+//! ```
+//!
+//! [Chrome Trace Event format]:
+//!     https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+//! [Perfetto]: https://ui.perfetto.dev
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    cell::Cell,
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+use thread_local::ThreadLocal;
+use tracing_core::{field, span, Collect, Event};
+
+/// A [`Subscribe`] implementation that writes spans and events in the Chrome
+/// Trace Event format.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    shared: Arc<Shared>,
+}
+
+/// Closes the trace when dropped.
+///
+/// Dropping the guard writes the closing `]` of the trace's JSON array and
+/// flushes the writer; without it, the viewers will reject the truncated
+/// file. Spans and events recorded after the guard is dropped are silently
+/// discarded.
+#[derive(Debug)]
+#[must_use = "dropping the guard closes the trace file"]
+pub struct Guard {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    writer: Mutex<State>,
+    start: Instant,
+    pid: u32,
+    tids: ThreadLocal<Cell<u64>>,
+    next_tid: AtomicU64,
+}
+
+struct State {
+    writer: Box<dyn Write + Send>,
+    /// Whether any event has been written yet, for comma placement.
+    empty: bool,
+    /// Set once the [`Guard`] has closed the array.
+    closed: bool,
+}
+
+/// The rendered `args` object for a span, stored in its extensions.
+struct SpanArgs(String);
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new `Subscriber` writing the trace to `writer`, and the
+    /// [`Guard`] that closes the trace.
+    pub fn new(writer: impl Write + Send + 'static) -> (Self, Guard) {
+        let shared = Arc::new(Shared {
+            writer: Mutex::new(State {
+                writer: Box::new(writer),
+                empty: true,
+                closed: false,
+            }),
+            start: Instant::now(),
+            pid: std::process::id(),
+            tids: ThreadLocal::new(),
+            next_tid: AtomicU64::new(0),
+        });
+        let guard = Guard {
+            shared: shared.clone(),
+        };
+        (Self { shared }, guard)
+    }
+
+    /// Returns a new `Subscriber` writing the trace to a file created at
+    /// `path`, and the [`Guard`] that closes the trace.
+    ///
+    /// If a file already exists at `path`, it is truncated.
+    pub fn with_file(path: impl AsRef<Path>) -> io::Result<(Self, Guard)> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+
+    /// Returns the `tid` for the current thread, assigning one (and writing
+    /// its `thread_name` metadata event) on the thread's first appearance.
+    fn tid(&self) -> u64 {
+        let cell = self.shared.tids.get_or(|| {
+            let tid = self.shared.next_tid.fetch_add(1, Ordering::Relaxed);
+            let thread = std::thread::current();
+            let mut args = String::from("\"name\":");
+            escape_into(&mut args, thread.name().unwrap_or("<unnamed>"));
+            self.shared.write(&format!(
+                "{{\"ph\":\"M\",\"pid\":{},\"tid\":{},\"name\":\"thread_name\",\"args\":{{{}}}}}",
+                self.shared.pid, tid, args
+            ));
+            Cell::new(tid)
+        });
+        cell.get()
+    }
+
+    /// Writes one non-metadata trace event with the given phase and name.
+    fn write_event(&self, phase: char, name: &str, args: Option<&str>) {
+        let mut record = String::new();
+        let _ = write!(
+            record,
+            "{{\"ph\":\"{}\",\"ts\":{:.3},\"pid\":{},\"tid\":{},\"name\":",
+            phase,
+            self.shared.start.elapsed().as_nanos() as f64 / 1_000.0,
+            self.shared.pid,
+            self.tid(),
+        );
+        escape_into(&mut record, name);
+        if phase == 'i' {
+            // Instant events are scoped to their thread's timeline.
+            record.push_str(",\"s\":\"t\"");
+        }
+        if let Some(args) = args {
+            let _ = write!(record, ",\"args\":{{{}}}", args);
+        }
+        record.push('}');
+        self.shared.write(&record);
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut args = String::new();
+        attrs.record(&mut ArgsVisitor { args: &mut args });
+        span.extensions_mut().insert(SpanArgs(args));
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(SpanArgs(args)) = extensions.get_mut::<SpanArgs>() {
+            values.record(&mut ArgsVisitor { args });
+        }
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let extensions = span.extensions();
+        let args = extensions.get::<SpanArgs>().map(|SpanArgs(args)| &**args);
+        self.write_event('B', span.name(), args.filter(|args| !args.is_empty()));
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        self.write_event('E', span.name(), None);
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        let mut args = String::new();
+        event.record(&mut ArgsVisitor { args: &mut args });
+        self.write_event(
+            'i',
+            event.metadata().name(),
+            Some(args.as_str()).filter(|args| !args.is_empty()),
+        );
+    }
+}
+
+// === impl Shared ===
+
+impl Shared {
+    /// Appends one record to the trace array, unless it has been closed.
+    fn write(&self, record: &str) {
+        let mut state = self.writer.lock().expect("chrome trace writer poisoned");
+        if state.closed {
+            return;
+        }
+        let separator = if state.empty { "[\n" } else { ",\n" };
+        state.empty = false;
+        let _ = state
+            .writer
+            .write_all(separator.as_bytes())
+            .and_then(|()| state.writer.write_all(record.as_bytes()));
+    }
+}
+
+impl std::fmt::Debug for Shared {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Shared")
+            .field("pid", &self.pid)
+            .field("start", &self.start)
+            .finish()
+    }
+}
+
+// === impl Guard ===
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let mut state = self
+            .shared
+            .writer
+            .lock()
+            .expect("chrome trace writer poisoned");
+        if state.closed {
+            return;
+        }
+        state.closed = true;
+        let footer: &[u8] = if state.empty { b"[]\n" } else { b"\n]\n" };
+        let _ = state
+            .writer
+            .write_all(footer)
+            .and_then(|()| state.writer.flush());
+    }
+}
+
+/// Renders fields as members of a JSON `args` object.
+struct ArgsVisitor<'a> {
+    args: &'a mut String,
+}
+
+impl ArgsVisitor<'_> {
+    fn key(&mut self, field: &field::Field) {
+        if !self.args.is_empty() {
+            self.args.push(',');
+        }
+        escape_into(self.args, field.name());
+        self.args.push(':');
+    }
+}
+
+impl field::Visit for ArgsVisitor<'_> {
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.key(field);
+        let _ = write!(self.args, "{}", value);
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.key(field);
+        let _ = write!(self.args, "{}", value);
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.key(field);
+        if value.is_finite() {
+            let _ = write!(self.args, "{}", value);
+        } else {
+            // JSON has no NaN or infinity literals.
+            escape_into(self.args, &value.to_string());
+        }
+    }
+
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.key(field);
+        let _ = write!(self.args, "{}", value);
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.key(field);
+        escape_into(self.args, value);
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.key(field);
+        escape_into(self.args, &format!("{:?}", value));
+    }
+}
+
+/// Appends `value` to `out` as a JSON string literal.
+fn escape_into(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    /// A writer appending to a shared buffer.
+    #[derive(Clone)]
+    struct Buf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn trace(f: impl FnOnce()) -> String {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let (chrome, guard) = Subscriber::new(Buf(buf.clone()));
+        let collector = crate::registry().with(chrome);
+        with_default(collector, f);
+        drop(guard);
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        output
+    }
+
+    #[test]
+    fn spans_produce_begin_and_end_events() {
+        let output = trace(|| {
+            let span = tracing::info_span!("my_span", answer = 42);
+            let _entered = span.enter();
+            tracing::info!(detail = "inner", "something happened");
+        });
+
+        assert!(
+            output.contains("\"ph\":\"M\"") && output.contains("\"thread_name\""),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("\"ph\":\"B\"")
+                && output.contains("\"my_span\",\"args\":{\"answer\":42}"),
+            "{}",
+            output
+        );
+        assert!(output.contains("\"ph\":\"E\""), "{}", output);
+        assert!(
+            output.contains("\"ph\":\"i\"") && output.contains("\"detail\":\"inner\""),
+            "{}",
+            output
+        );
+        assert!(output.ends_with("\n]\n"), "{}", output);
+    }
+
+    #[test]
+    fn output_is_valid_json() {
+        let output = trace(|| {
+            let span = tracing::info_span!("span", text = "with \"quotes\"\n");
+            let _entered = span.enter();
+            tracing::info!("event");
+        });
+
+        // The output must parse as a JSON array of objects.
+        serde_json::from_str::<Vec<serde_json::Value>>(&output)
+            .unwrap_or_else(|error| panic!("invalid JSON ({}): {}", error, output));
+    }
+
+    #[test]
+    fn empty_traces_are_closed() {
+        let output = trace(|| {});
+        assert_eq!(output, "[]\n");
+    }
+
+    #[test]
+    fn nothing_is_written_after_the_guard_is_dropped() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let (chrome, guard) = Subscriber::new(Buf(buf.clone()));
+        let collector = crate::registry().with(chrome);
+        with_default(collector, || {
+            tracing::info!("before");
+            drop(guard);
+            tracing::info!("after");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("\"message\":\"before\""), "{}", output);
+        assert!(!output.contains("\"message\":\"after\""), "{}", output);
+        assert!(output.ends_with("\n]\n"), "{}", output);
+    }
+}
@@ -0,0 +1,773 @@
+//! Ships formatted events to [AWS CloudWatch Logs].
+//!
+//! CloudWatch Logs is AWS's hosted log store: events are written to a *log
+//! stream* inside a *log group* with the `PutLogEvents` API, which imposes
+//! strict limits on batch size and demands a sequence token from the
+//! previous call. This module provides a [`Subscriber`] that handles those
+//! mechanics — group and stream creation, sequence-token tracking, and
+//! batching under the API limits — using only the standard library,
+//! including a local implementation of [AWS Signature Version 4] request
+//! signing.
+//!
+//! Events are formatted as single lines (level, target, message, and
+//! fields) and buffered on a background thread. A batch is sent when it
+//! reaches the API's count or size limits, or when the batch timeout
+//! elapses. Credentials are taken from the builder or, by default, from the
+//! standard `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, and
+//! `AWS_SESSION_TOKEN` environment variables.
+//!
+//! # Limitations
+//!
+//! - Connections are plaintext HTTP without TLS, so events must be sent to
+//!   a local emulator such as [LocalStack] or through a proxy rather than
+//!   directly to the public CloudWatch endpoint.
+//! - Credentials are read once at startup; rotating session credentials
+//!   are not refreshed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{cloudwatch, prelude::*};
+//!
+//! let shipper = cloudwatch::Subscriber::builder()
+//!     .with_endpoint("127.0.0.1:4566")
+//!     .with_region("eu-west-1")
+//!     .with_log_group("my-service")
+//!     .with_log_stream("api")
+//!     .with_credentials("AKIDEXAMPLE", "secret")
+//!     .finish()
+//!     .expect("failed to start the CloudWatch shipper");
+//! let collector = tracing_subscriber::registry().with(shipper);
+//! # let _ = collector;
+//! ```
+//!
+//! [AWS CloudWatch Logs]: https://docs.aws.amazon.com/AmazonCloudWatch/latest/logs/WhatIsCloudWatchLogs.html
+//! [AWS Signature Version 4]: https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_aws-signing.html
+//! [LocalStack]: https://localstack.cloud/
+use crate::sha256::{hmac_sha256, sha256};
+use crate::subscribe::Subscribe;
+use std::{
+    env,
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, SystemTime},
+};
+use tracing_core::{field, Collect, Event};
+
+/// The most events `PutLogEvents` accepts in one call.
+const MAX_BATCH_EVENTS: usize = 10_000;
+/// The largest batch `PutLogEvents` accepts, in bytes, where each event
+/// counts as its message length plus a fixed overhead.
+const MAX_BATCH_BYTES: usize = 1_048_576;
+/// The per-event byte overhead CloudWatch charges against the batch size.
+const EVENT_OVERHEAD: usize = 26;
+
+/// A [`Subscribe`] implementation that ships events to CloudWatch Logs.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    // The standard library's channel sender is not `Sync` on our minimum
+    // supported Rust version, so sends are serialized through a mutex.
+    sender: Mutex<mpsc::Sender<LogEvent>>,
+}
+
+/// Configures a CloudWatch Logs [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    endpoint: String,
+    region: String,
+    log_group: String,
+    log_stream: String,
+    credentials: Option<Credentials>,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+/// A set of AWS credentials.
+#[derive(Debug, Clone)]
+struct Credentials {
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+}
+
+/// One formatted event, as handed to the shipping thread.
+#[derive(Debug)]
+struct LogEvent {
+    /// Milliseconds since the Unix epoch.
+    timestamp: u64,
+    message: String,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a [`Builder`] for configuring a CloudWatch Logs shipper.
+    pub fn builder() -> Builder {
+        Builder {
+            endpoint: "127.0.0.1:4566".to_owned(),
+            region: "us-east-1".to_owned(),
+            log_group: "tracing".to_owned(),
+            log_stream: "events".to_owned(),
+            credentials: None,
+            batch_timeout: Duration::from_secs(5),
+            max_retries: 3,
+        }
+    }
+}
+
+impl<C: Collect> Subscribe<C> for Subscriber {
+    fn on_event(&self, event: &Event<'_>, _ctx: crate::subscribe::Context<'_, C>) {
+        let metadata = event.metadata();
+        let mut message = format!("{} {}:", metadata.level(), metadata.target());
+        event.record(&mut LineVisitor { line: &mut message });
+        // The only send error is a disconnected worker; events are dropped
+        // in that case, as there is nowhere to ship them to.
+        let _ = self
+            .sender
+            .lock()
+            .expect("cloudwatch sender poisoned")
+            .send(LogEvent {
+                timestamp: unix_millis(),
+                message,
+            });
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the `host:port` of the CloudWatch Logs endpoint.
+    ///
+    /// The default is `127.0.0.1:4566`, the LocalStack edge port. Any
+    /// `http://` prefix and trailing slash are ignored.
+    pub fn with_endpoint(self, endpoint: impl Into<String>) -> Self {
+        let mut endpoint = endpoint.into();
+        if let Some(stripped) = endpoint.strip_prefix("http://") {
+            endpoint = stripped.to_owned();
+        }
+        if let Some(stripped) = endpoint.strip_suffix('/') {
+            endpoint = stripped.to_owned();
+        }
+        Self { endpoint, ..self }
+    }
+
+    /// Sets the region named in the request signature.
+    ///
+    /// The default is `us-east-1`.
+    pub fn with_region(self, region: impl Into<String>) -> Self {
+        Self {
+            region: region.into(),
+            ..self
+        }
+    }
+
+    /// Sets the log group events are written to, creating it on startup if
+    /// it does not exist.
+    ///
+    /// The default is `tracing`.
+    pub fn with_log_group(self, log_group: impl Into<String>) -> Self {
+        Self {
+            log_group: log_group.into(),
+            ..self
+        }
+    }
+
+    /// Sets the log stream events are written to, creating it on startup
+    /// if it does not exist.
+    ///
+    /// The default is `events`.
+    pub fn with_log_stream(self, log_stream: impl Into<String>) -> Self {
+        Self {
+            log_stream: log_stream.into(),
+            ..self
+        }
+    }
+
+    /// Sets static credentials, overriding the environment.
+    ///
+    /// Without this, [`finish`](Self::finish) reads `AWS_ACCESS_KEY_ID`,
+    /// `AWS_SECRET_ACCESS_KEY`, and optionally `AWS_SESSION_TOKEN` from the
+    /// environment, as the AWS SDKs do.
+    pub fn with_credentials(
+        self,
+        access_key: impl Into<String>,
+        secret_key: impl Into<String>,
+    ) -> Self {
+        Self {
+            credentials: Some(Credentials {
+                access_key: access_key.into(),
+                secret_key: secret_key.into(),
+                session_token: None,
+            }),
+            ..self
+        }
+    }
+
+    /// Sets how long a partial batch waits before being sent.
+    ///
+    /// Full batches — ones that reach the API's count or size limits — are
+    /// sent immediately. The default is 5 seconds.
+    pub fn with_batch_timeout(self, batch_timeout: Duration) -> Self {
+        Self {
+            batch_timeout,
+            ..self
+        }
+    }
+
+    /// Sets how many times a failed batch is retried before being dropped.
+    ///
+    /// Retries back off exponentially, starting at 100 milliseconds. The
+    /// default is 3 retries.
+    pub fn with_max_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Returns the configured [`Subscriber`], spawning its shipping
+    /// thread.
+    ///
+    /// Fails if no credentials were configured and none are present in the
+    /// environment. The thread creates the log group and stream if needed,
+    /// and runs until the `Subscriber` is dropped; any events still
+    /// buffered at that point are sent before it exits.
+    pub fn finish(self) -> io::Result<Subscriber> {
+        let credentials = match self.credentials.or_else(Credentials::from_env) {
+            Some(credentials) => credentials,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "no AWS credentials were configured or found in the environment",
+                ))
+            }
+        };
+        let (sender, receiver) = mpsc::channel();
+        let worker = Worker {
+            endpoint: self.endpoint,
+            region: self.region,
+            log_group: self.log_group,
+            log_stream: self.log_stream,
+            credentials,
+            batch_timeout: self.batch_timeout,
+            max_retries: self.max_retries,
+            sequence_token: None,
+        };
+        thread::Builder::new()
+            .name("tracing-cloudwatch".into())
+            .spawn(move || worker.run(receiver))?;
+        Ok(Subscriber {
+            sender: Mutex::new(sender),
+        })
+    }
+}
+
+// === impl Credentials ===
+
+impl Credentials {
+    /// Reads credentials from the standard AWS environment variables.
+    fn from_env() -> Option<Self> {
+        Some(Self {
+            access_key: env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret_key: env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+}
+
+// === impl Worker ===
+
+/// The shipping thread: batches events under the API limits and calls
+/// `PutLogEvents`.
+struct Worker {
+    endpoint: String,
+    region: String,
+    log_group: String,
+    log_stream: String,
+    credentials: Credentials,
+    batch_timeout: Duration,
+    max_retries: u32,
+    /// The token expected by the next `PutLogEvents` call, from the
+    /// previous call's response.
+    sequence_token: Option<String>,
+}
+
+impl Worker {
+    fn run(mut self, receiver: mpsc::Receiver<LogEvent>) {
+        self.ensure_stream();
+
+        let mut batch = Vec::new();
+        let mut batch_bytes = 0;
+        loop {
+            match receiver.recv_timeout(self.batch_timeout) {
+                Ok(event) => {
+                    if !fits(batch.len(), batch_bytes, &event) {
+                        self.ship(&mut batch);
+                        batch_bytes = 0;
+                    }
+                    batch_bytes += event.message.len() + EVENT_OVERHEAD;
+                    batch.push(event);
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    self.ship(&mut batch);
+                    batch_bytes = 0;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    self.ship(&mut batch);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Creates the log group and stream, ignoring failures: both already
+    /// existing is the common case, and anything else surfaces on the
+    /// first `PutLogEvents` call anyway.
+    fn ensure_stream(&self) {
+        let mut body = String::from("{\"logGroupName\":\"");
+        escape_into(&mut body, &self.log_group);
+        body.push_str("\"}");
+        let _ = self.call("CreateLogGroup", &body);
+
+        let mut body = String::from("{\"logGroupName\":\"");
+        escape_into(&mut body, &self.log_group);
+        body.push_str("\",\"logStreamName\":\"");
+        escape_into(&mut body, &self.log_stream);
+        body.push_str("\"}");
+        let _ = self.call("CreateLogStream", &body);
+    }
+
+    /// Sends the batch, retrying with exponential backoff; the batch is
+    /// cleared either way.
+    fn ship(&mut self, batch: &mut Vec<LogEvent>) {
+        if batch.is_empty() {
+            return;
+        }
+        let mut backoff = Duration::from_millis(100);
+        for attempt in 0..=self.max_retries {
+            if self.put_log_events(batch).is_ok() {
+                break;
+            }
+            if attempt < self.max_retries {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+        batch.clear();
+    }
+
+    /// Makes one `PutLogEvents` call, updating the sequence token from the
+    /// response — including from sequence-token errors, so that the next
+    /// attempt can succeed.
+    fn put_log_events(&mut self, batch: &[LogEvent]) -> io::Result<()> {
+        let mut body = String::from("{\"logGroupName\":\"");
+        escape_into(&mut body, &self.log_group);
+        body.push_str("\",\"logStreamName\":\"");
+        escape_into(&mut body, &self.log_stream);
+        body.push_str("\",\"logEvents\":[");
+        for (i, event) in batch.iter().enumerate() {
+            if i != 0 {
+                body.push(',');
+            }
+            let _ = write!(body, "{{\"timestamp\":{},\"message\":\"", event.timestamp);
+            escape_into(&mut body, &event.message);
+            body.push_str("\"}");
+        }
+        body.push(']');
+        if let Some(token) = &self.sequence_token {
+            body.push_str(",\"sequenceToken\":\"");
+            escape_into(&mut body, token);
+            body.push('"');
+        }
+        body.push('}');
+
+        let (status, response) = self.call("PutLogEvents", &body)?;
+        if status / 100 == 2 {
+            self.sequence_token = json_str(&response, "nextSequenceToken");
+            return Ok(());
+        }
+        if response.contains("InvalidSequenceTokenException")
+            || response.contains("DataAlreadyAcceptedException")
+        {
+            self.sequence_token = json_str(&response, "expectedSequenceToken");
+        } else if response.contains("ResourceNotFoundException") {
+            self.ensure_stream();
+        }
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("PutLogEvents failed with status {}: {}", status, response),
+        ))
+    }
+
+    /// Makes one signed CloudWatch Logs API call, returning the response
+    /// status and body.
+    fn call(&self, action: &str, body: &str) -> io::Result<(u32, String)> {
+        let target = format!("Logs_20140328.{}", action);
+        let (amz_date, date) = amz_dates(unix_millis() / 1000);
+
+        // The canonical request and signed-header list, with headers in
+        // alphabetical order as SigV4 requires.
+        let mut canonical_headers = format!(
+            "content-type:application/x-amz-json-1.1\nhost:{}\nx-amz-date:{}\n",
+            self.endpoint, amz_date,
+        );
+        let mut signed_headers = String::from("content-type;host;x-amz-date");
+        if let Some(token) = &self.credentials.session_token {
+            let _ = writeln!(canonical_headers, "x-amz-security-token:{}", token);
+            signed_headers.push_str(";x-amz-security-token");
+        }
+        let _ = writeln!(canonical_headers, "x-amz-target:{}", target);
+        signed_headers.push_str(";x-amz-target");
+        let canonical_request = format!(
+            "POST\n/\n\n{}\n{}\n{}",
+            canonical_headers,
+            signed_headers,
+            hex(&sha256(body.as_bytes())),
+        );
+
+        let scope = format!("{}/{}/logs/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&sha256(canonical_request.as_bytes())),
+        );
+        let key = signing_key(&self.credentials.secret_key, &date, &self.region, "logs");
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        let mut request = format!(
+            "POST / HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Content-Type: application/x-amz-json-1.1\r\n\
+             X-Amz-Date: {}\r\n",
+            self.endpoint, amz_date,
+        );
+        if let Some(token) = &self.credentials.session_token {
+            let _ = write!(request, "X-Amz-Security-Token: {}\r\n", token);
+        }
+        let _ = write!(
+            request,
+            "X-Amz-Target: {}\r\n\
+             Authorization: AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n",
+            target,
+            self.credentials.access_key,
+            scope,
+            signed_headers,
+            signature,
+            body.len(),
+        );
+
+        let mut stream = TcpStream::connect(&self.endpoint)?;
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body.as_bytes())?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        // "HTTP/1.1 200 OK" => 200
+        let code = status
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .unwrap_or(0);
+        let mut content_length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line)?;
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(length) = header
+                .to_ascii_lowercase()
+                .strip_prefix("content-length:")
+                .map(str::trim)
+            {
+                content_length = length.parse().unwrap_or(0);
+            }
+        }
+        let mut response = vec![0; content_length];
+        reader.read_exact(&mut response)?;
+        let response = String::from_utf8_lossy(&response).into_owned();
+        Ok((code, response))
+    }
+}
+
+/// Returns whether one more event fits in the batch under the API limits.
+fn fits(batch_len: usize, batch_bytes: usize, event: &LogEvent) -> bool {
+    batch_len < MAX_BATCH_EVENTS
+        && batch_bytes + event.message.len() + EVENT_OVERHEAD <= MAX_BATCH_BYTES
+}
+
+/// Derives the SigV4 signing key for one day, region, and service.
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> [u8; 32] {
+    let key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, service.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+/// Formats a Unix second timestamp as SigV4's `YYYYMMDDTHHMMSSZ` datetime
+/// and `YYYYMMDD` date pair.
+fn amz_dates(secs: u64) -> (String, String) {
+    let (year, month, day) = civil_date(secs / 86_400);
+    let rest = secs % 86_400;
+    let date = format!("{:04}{:02}{:02}", year, month, day);
+    let amz_date = format!(
+        "{}T{:02}{:02}{:02}Z",
+        date,
+        rest / 3600,
+        rest / 60 % 60,
+        rest % 60,
+    );
+    (amz_date, date)
+}
+
+/// Converts days since the Unix epoch to a `(year, month, day)` civil
+/// date.
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let (year, month) = if month < 10 {
+        (year_of_era + era * 400, month + 3)
+    } else {
+        (year_of_era + era * 400 + 1, month - 9)
+    };
+    (year, month, day)
+}
+
+/// Returns the current wall-clock time in milliseconds since the Unix
+/// epoch.
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Returns the value of the first `"key":"..."` member in `json`, if any.
+fn json_str(json: &str, key: &str) -> Option<String> {
+    let marker = format!("\"{}\":\"", key);
+    let start = json.find(&marker)? + marker.len();
+    json[start..].split('"').next().map(str::to_owned)
+}
+
+/// Formats a byte string as lowercase hex.
+fn hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(out, "{:02x}", byte);
+    }
+    out
+}
+
+/// Writes `value` into `out` with JSON string escaping.
+fn escape_into(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+/// Appends event fields to the formatted line, message first.
+struct LineVisitor<'a> {
+    line: &'a mut String,
+}
+
+impl field::Visit for LineVisitor<'_> {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == "message" {
+            let _ = write!(self.line, " {}", value);
+        } else {
+            let _ = write!(self.line, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.line, " {:?}", value);
+        } else {
+            let _ = write!(self.line, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::net::TcpListener;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn signing_keys_match_the_reference_vector() {
+        // The worked example from the AWS SigV4 documentation.
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex(&key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d",
+        );
+    }
+
+    #[test]
+    fn amz_dates_format_correctly() {
+        assert_eq!(
+            amz_dates(0),
+            ("19700101T000000Z".to_owned(), "19700101".to_owned())
+        );
+        assert_eq!(
+            amz_dates(1_788_009_255),
+            ("20260829T131415Z".to_owned(), "20260829".to_owned()),
+        );
+    }
+
+    #[test]
+    fn batches_respect_the_api_limits() {
+        let event = LogEvent {
+            timestamp: 0,
+            message: "x".repeat(100),
+        };
+        assert!(fits(0, 0, &event));
+        assert!(fits(MAX_BATCH_EVENTS - 1, 0, &event));
+        assert!(!fits(MAX_BATCH_EVENTS, 0, &event));
+        assert!(fits(0, MAX_BATCH_BYTES - 126, &event));
+        assert!(!fits(0, MAX_BATCH_BYTES - 125, &event));
+    }
+
+    #[test]
+    fn missing_credentials_are_rejected() {
+        // Guard against ambient credentials in the test environment.
+        if Credentials::from_env().is_some() {
+            return;
+        }
+        let error = Subscriber::builder()
+            .finish()
+            .expect_err("finish should require credentials");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    /// Accepts one API call and returns its `X-Amz-Target`, its
+    /// `Authorization` header, and its body.
+    fn accept_call(listener: &TcpListener, response: &str) -> (String, String, String) {
+        let (stream, _) = listener.accept().expect("no call received");
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).expect("failed to read request");
+        let mut target = String::new();
+        let mut authorization = String::new();
+        let mut content_length = 0;
+        loop {
+            line.clear();
+            reader.read_line(&mut line).expect("failed to read header");
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            let lowered = header.to_ascii_lowercase();
+            if let Some(value) = lowered.strip_prefix("x-amz-target:") {
+                target = value.trim().to_owned();
+            } else if let Some(value) = header.strip_prefix("Authorization:") {
+                authorization = value.trim().to_owned();
+            } else if let Some(value) = lowered.strip_prefix("content-length:") {
+                content_length = value.trim().parse().expect("invalid content-length");
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).expect("failed to read body");
+        let reply = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+            response.len(),
+            response,
+        );
+        reader
+            .get_mut()
+            .write_all(reply.as_bytes())
+            .expect("failed to respond");
+        (
+            target,
+            authorization,
+            String::from_utf8(body).expect("body was not UTF-8"),
+        )
+    }
+
+    #[test]
+    fn events_ship_with_sequence_tokens() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let shipper = Subscriber::builder()
+            .with_endpoint(format!("http://{}/", addr))
+            .with_log_group("ship-test")
+            .with_log_stream("api")
+            .with_credentials("AKIDEXAMPLE", "secret")
+            .with_batch_timeout(Duration::from_millis(100))
+            .finish()
+            .expect("failed to start shipper");
+        let collector = crate::registry().with(shipper);
+
+        with_default(collector, || {
+            let (group, _, body) = accept_call(&listener, "{}");
+            assert_eq!(group, "logs_20140328.createloggroup");
+            assert!(body.contains("\"logGroupName\":\"ship-test\""));
+            let (stream, _, body) = accept_call(&listener, "{}");
+            assert_eq!(stream, "logs_20140328.createlogstream");
+            assert!(body.contains("\"logStreamName\":\"api\""));
+
+            tracing::info!(user = "ferris", "logged in");
+            let (target, authorization, body) =
+                accept_call(&listener, "{\"nextSequenceToken\":\"tok-1\"}");
+            assert_eq!(target, "logs_20140328.putlogevents");
+            assert!(
+                authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"),
+                "unexpected authorization: {}",
+                authorization,
+            );
+            assert!(
+                authorization.contains("SignedHeaders=content-type;host;x-amz-date;x-amz-target")
+            );
+            assert!(body.contains("INFO"));
+            assert!(body.contains("logged in user=ferris"));
+            assert!(!body.contains("sequenceToken"));
+
+            tracing::warn!("disk is filling up");
+            let (_, _, body) = accept_call(&listener, "{\"nextSequenceToken\":\"tok-2\"}");
+            assert!(body.contains("disk is filling up"));
+            assert!(
+                body.contains("\"sequenceToken\":\"tok-1\""),
+                "missing sequence token: {}",
+                body,
+            );
+        });
+    }
+}
@@ -0,0 +1,331 @@
+//! Building a subscriber stack from a declarative configuration.
+//!
+//! This module provides a [`Config`] type that describes a [`fmt`]
+//! subscriber, its output, and an [`EnvFilter`] as plain data. `Config`
+//! implements [`serde::Deserialize`], so it can be loaded from a TOML, YAML,
+//! or JSON file with the format crate of your choice, then turned into a
+//! ready-to-use subscriber with [`Config::build`]. This replaces the
+//! hand-rolled mapping from a settings file to builder calls that most
+//! applications otherwise carry.
+//!
+//! # Configuration format
+//!
+//! All sections and fields are optional, and default to the corresponding
+//! [`fmt`] defaults. In TOML, a complete configuration looks like this:
+//!
+//! ```toml
+//! [filter]
+//! directives = "info,my_crate=debug"
+//!
+//! [fmt]
+//! format = "compact"  # "full", "compact", "columns", "pretty", "logfmt", or "json"
+//! ansi = true
+//! target = true
+//! level = true
+//! thread_ids = false
+//! thread_names = false
+//! file = false
+//! line_number = false
+//!
+//! [output]
+//! kind = "file"       # "stdout", "stderr", or "file"
+//! path = "/var/log/my-app.log"
+//! ```
+//!
+//! # Examples
+//!
+//! Loading the file is left to the application, since it picks the format;
+//! with the `toml` crate, that looks like:
+//!
+//! ```ignore
+//! let config: tracing_subscriber::config::Config =
+//!     toml::from_str(&std::fs::read_to_string("tracing.toml")?)?;
+//! ```
+//!
+//! The configuration can also be built in code, since all of its fields are
+//! public:
+//!
+//! ```no_run
+//! use tracing_subscriber::{config::Config, prelude::*};
+//!
+//! let mut config = Config::default();
+//! config.filter.directives = Some("info,my_crate=debug".into());
+//! config.fmt.format = "compact".into();
+//!
+//! let subscriber = config.build().expect("configuration is valid");
+//! tracing_subscriber::registry().with(subscriber).init();
+//! ```
+//!
+//! # Validation
+//!
+//! Field *names* and types are checked during deserialization, by serde.
+//! Field *values* are checked by [`Config::build`], which reports the first
+//! invalid value as an [`Error`] naming the offending field by its path, such
+//! as `filter.directives` or `output.path`.
+//!
+//! [`fmt`]: mod@crate::fmt
+//! [`EnvFilter`]: crate::filter::EnvFilter
+use crate::{
+    filter::EnvFilter,
+    fmt::{self, writer::BoxMakeWriter},
+    registry::LookupSpan,
+    subscribe::Subscribe,
+};
+use serde::Deserialize;
+use std::{fmt as std_fmt, fs::OpenOptions, io, path::PathBuf};
+use tracing_core::Collect;
+
+/// A declarative description of a subscriber stack.
+///
+/// See the [module-level documentation](self) for the configuration file
+/// format, and [`build`](Config::build) for turning a `Config` into a
+/// subscriber.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Filtering configuration; see [`FilterConfig`].
+    pub filter: FilterConfig,
+    /// Formatting configuration; see [`FmtConfig`].
+    pub fmt: FmtConfig,
+    /// Output configuration; see [`OutputConfig`].
+    pub output: OutputConfig,
+}
+
+/// The `[filter]` section of a [`Config`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterConfig {
+    /// [`EnvFilter`] directives, such as `"info,my_crate=debug"`.
+    ///
+    /// When this is `None`, no filter is installed and every span and event
+    /// reaches the subscriber.
+    ///
+    /// [`EnvFilter`]: crate::filter::EnvFilter
+    pub directives: Option<String>,
+}
+
+/// The `[fmt]` section of a [`Config`].
+///
+/// Each field corresponds to the [`fmt::Subscriber`] builder method of the
+/// same name, and defaults to the builder's default.
+///
+/// [`fmt::Subscriber`]: crate::fmt::Subscriber
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FmtConfig {
+    /// The event format: `"full"`, `"compact"`, `"columns"`, `"pretty"`,
+    /// `"logfmt"`, or `"json"`.
+    ///
+    /// The `"json"` format requires the "json" feature flag.
+    pub format: String,
+    /// Whether to use ANSI terminal escape codes for colors.
+    pub ansi: bool,
+    /// Whether to display the event's target.
+    pub target: bool,
+    /// Whether to display the event's level.
+    pub level: bool,
+    /// Whether to display the ID of the current thread.
+    pub thread_ids: bool,
+    /// Whether to display the name of the current thread.
+    pub thread_names: bool,
+    /// Whether to display the source file that the event originated from.
+    pub file: bool,
+    /// Whether to display the source line number that the event originated
+    /// from.
+    pub line_number: bool,
+}
+
+/// The `[output]` section of a [`Config`].
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    /// Where formatted output is written: `"stdout"`, `"stderr"`, or
+    /// `"file"`.
+    pub kind: String,
+    /// The file to append output to, when `kind` is `"file"`.
+    ///
+    /// The file is created if it does not exist. This field must not be set
+    /// for the other output kinds.
+    pub path: Option<PathBuf>,
+}
+
+/// An invalid value in a [`Config`].
+///
+/// Returned by [`Config::build`]. The [`path`](Error::path) method names the
+/// field holding the invalid value.
+#[derive(Debug)]
+pub struct Error {
+    path: &'static str,
+    message: String,
+}
+
+// === impl Config ===
+
+impl Config {
+    /// Builds the configured subscriber stack.
+    ///
+    /// The returned subscriber combines the configured [`fmt`] subscriber
+    /// and, if [`filter.directives`] is set, an [`EnvFilter`] attached to it
+    /// with [`with_filter`]. Add it to a collector with
+    /// [`with`](crate::subscribe::CollectExt::with).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] naming the offending field if any configured
+    /// value is invalid, or if the output file cannot be opened.
+    ///
+    /// [`fmt`]: mod@crate::fmt
+    /// [`filter.directives`]: FilterConfig::directives
+    /// [`EnvFilter`]: crate::filter::EnvFilter
+    /// [`with_filter`]: crate::subscribe::Subscribe::with_filter
+    pub fn build<C>(&self) -> Result<Box<dyn Subscribe<C> + Send + Sync + 'static>, Error>
+    where
+        C: Collect + for<'a> LookupSpan<'a>,
+    {
+        let writer = self.output.make_writer()?;
+        let base = fmt::subscriber()
+            .with_ansi(self.fmt.ansi)
+            .with_target(self.fmt.target)
+            .with_level(self.fmt.level)
+            .with_thread_ids(self.fmt.thread_ids)
+            .with_thread_names(self.fmt.thread_names)
+            .with_file(self.fmt.file)
+            .with_line_number(self.fmt.line_number)
+            .with_writer(writer);
+        let subscriber: Box<dyn Subscribe<C> + Send + Sync + 'static> = match &self.fmt.format[..] {
+            "full" => base.boxed(),
+            "compact" => base.compact().boxed(),
+            "columns" => base.columns().boxed(),
+            "pretty" => base.pretty().boxed(),
+            "logfmt" => base.logfmt().boxed(),
+            #[cfg(feature = "json")]
+            "json" => base.json().boxed(),
+            #[cfg(not(feature = "json"))]
+            "json" => {
+                return Err(Error::new(
+                    "fmt.format",
+                    "the \"json\" format requires the \"json\" feature flag".to_string(),
+                ))
+            }
+            other => {
+                return Err(Error::new(
+                    "fmt.format",
+                    format!(
+                        "unknown format {:?}; expected one of \"full\", \"compact\", \
+                             \"columns\", \"pretty\", \"logfmt\", or \"json\"",
+                        other
+                    ),
+                ))
+            }
+        };
+        match &self.filter.directives {
+            Some(directives) => {
+                let filter = EnvFilter::try_new(directives)
+                    .map_err(|error| Error::new("filter.directives", error.to_string()))?;
+                Ok(subscriber.with_filter(filter).boxed())
+            }
+            None => Ok(subscriber),
+        }
+    }
+}
+
+// === impl FmtConfig ===
+
+impl Default for FmtConfig {
+    fn default() -> Self {
+        Self {
+            format: String::from("full"),
+            ansi: true,
+            target: true,
+            level: true,
+            thread_ids: false,
+            thread_names: false,
+            file: false,
+            line_number: false,
+        }
+    }
+}
+
+// === impl OutputConfig ===
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            kind: String::from("stdout"),
+            path: None,
+        }
+    }
+}
+
+impl OutputConfig {
+    /// Builds the writer for the configured output.
+    fn make_writer(&self) -> Result<BoxMakeWriter, Error> {
+        match &self.kind[..] {
+            "file" => {
+                let path = self.path.as_ref().ok_or_else(|| {
+                    Error::new(
+                        "output.path",
+                        "a path is required when `output.kind` is \"file\"".to_string(),
+                    )
+                })?;
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|error| {
+                        Error::new(
+                            "output.path",
+                            format!("failed to open {}: {}", path.display(), error),
+                        )
+                    })?;
+                Ok(BoxMakeWriter::new(file))
+            }
+            "stdout" | "stderr" => {
+                if self.path.is_some() {
+                    return Err(Error::new(
+                        "output.path",
+                        format!(
+                            "a path may only be set when `output.kind` is \"file\", not {:?}",
+                            self.kind
+                        ),
+                    ));
+                }
+                if self.kind == "stdout" {
+                    Ok(BoxMakeWriter::new(io::stdout))
+                } else {
+                    Ok(BoxMakeWriter::new(io::stderr))
+                }
+            }
+            other => Err(Error::new(
+                "output.kind",
+                format!(
+                    "unknown output kind {:?}; expected one of \"stdout\", \"stderr\", or \
+                     \"file\"",
+                    other
+                ),
+            )),
+        }
+    }
+}
+
+// === impl Error ===
+
+impl Error {
+    fn new(path: &'static str, message: String) -> Self {
+        Self { path, message }
+    }
+
+    /// Returns the path of the configuration field holding the invalid
+    /// value, such as `filter.directives`.
+    pub fn path(&self) -> &str {
+        self.path
+    }
+}
+
+impl std_fmt::Display for Error {
+    fn fmt(&self, f: &mut std_fmt::Formatter<'_>) -> std_fmt::Result {
+        write!(f, "invalid value for `{}`: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
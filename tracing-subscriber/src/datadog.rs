@@ -0,0 +1,685 @@
+//! Exports completed span trees to the [Datadog] agent as native traces.
+//!
+//! The Datadog agent accepts traces on a local port in its own msgpack
+//! format, one array of spans per trace. Reaching it from `tracing` has
+//! meant routing through the OpenTelemetry bridge and the OTel Datadog
+//! exporter; this module instead provides a [`Subscriber`] that speaks the
+//! agent's trace API directly, using only the standard library.
+//!
+//! Spans are buffered per trace and exported as a tree when their root
+//! span closes. Each span's operation *name* comes from the span's name,
+//! its *resource* from a `resource` field (falling back to the name), and
+//! its *service* from a `service` field (falling back to the configured
+//! default); remaining fields become tags. A sampling priority is decided
+//! once per trace at the root and propagated to every span in it, so the
+//! agent can drop or keep whole traces consistently.
+//!
+//! # Limitations
+//!
+//! - Spans are held until their root closes; long-lived root spans delay
+//!   the export of their whole trace, and traces whose root has not closed
+//!   within the flush interval are exported incomplete.
+//! - The agent is assumed to be local and unauthenticated, as in the
+//!   standard sidecar or host-agent deployments.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{datadog, prelude::*};
+//!
+//! let exporter = datadog::Subscriber::builder()
+//!     .with_agent("127.0.0.1:8126")
+//!     .with_service("my-service")
+//!     .with_sample_rate(0.5)
+//!     .finish()
+//!     .expect("failed to start the Datadog exporter");
+//! let collector = tracing_subscriber::registry().with(exporter);
+//! # let _ = collector;
+//! ```
+//!
+//! [Datadog]: https://docs.datadoghq.com/tracing/
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+use tracing_core::{field, span, Collect};
+
+/// The metrics key the agent reads the trace's sampling priority from.
+const PRIORITY_KEY: &str = "_sampling_priority_v1";
+
+/// A [`Subscribe`] implementation that exports traces to the Datadog
+/// agent.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    // The standard library's channel sender is not `Sync` on our minimum
+    // supported Rust version, so sends are serialized through a mutex.
+    sender: Mutex<mpsc::Sender<FinishedSpan>>,
+    service: String,
+    sample_rate: f64,
+}
+
+/// Configures a Datadog exporter [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    agent: String,
+    service: String,
+    sample_rate: f64,
+    flush_interval: Duration,
+    max_retries: u32,
+}
+
+/// The in-progress data for a span, stored in its extensions.
+struct DatadogSpan {
+    trace_id: u64,
+    span_id: u64,
+    parent_id: u64,
+    /// The trace's sampling priority, decided at the root and inherited.
+    priority: i32,
+    start: Instant,
+    start_nanos: u64,
+    resource: Option<String>,
+    service: Option<String>,
+    error: bool,
+    meta: Vec<(&'static str, String)>,
+}
+
+/// A closed span, as handed to the export thread.
+#[derive(Debug)]
+struct FinishedSpan {
+    trace_id: u64,
+    span_id: u64,
+    parent_id: u64,
+    priority: i32,
+    name: &'static str,
+    resource: String,
+    service: String,
+    start_nanos: u64,
+    duration_nanos: u64,
+    error: bool,
+    meta: Vec<(&'static str, String)>,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a [`Builder`] for configuring a Datadog exporter.
+    pub fn builder() -> Builder {
+        Builder {
+            agent: "127.0.0.1:8126".to_owned(),
+            service: "tracing".to_owned(),
+            sample_rate: 1.0,
+            flush_interval: Duration::from_secs(5),
+            max_retries: 3,
+        }
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+
+        let parent = if attrs.is_root() {
+            None
+        } else if attrs.is_contextual() {
+            ctx.lookup_current()
+        } else {
+            attrs.parent().and_then(|id| ctx.span(id))
+        };
+        let (trace_id, parent_id, priority) = match parent {
+            Some(parent) => match parent.extensions().get::<DatadogSpan>() {
+                Some(parent) => (parent.trace_id, parent.span_id, parent.priority),
+                None => (random_id(), 0, self.priority()),
+            },
+            None => (random_id(), 0, self.priority()),
+        };
+
+        let mut data = DatadogSpan {
+            trace_id,
+            span_id: random_id(),
+            parent_id,
+            priority,
+            start: Instant::now(),
+            start_nanos: unix_nanos(),
+            resource: None,
+            service: None,
+            error: false,
+            meta: Vec::new(),
+        };
+        attrs.record(&mut TagVisitor { data: &mut data });
+        span.extensions_mut().insert(data);
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(data) = extensions.get_mut::<DatadogSpan>() {
+            values.record(&mut TagVisitor { data });
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(&id).expect("Span not found, this is a bug");
+        let data = match span.extensions_mut().remove::<DatadogSpan>() {
+            Some(data) => data,
+            None => return,
+        };
+        let finished = FinishedSpan {
+            trace_id: data.trace_id,
+            span_id: data.span_id,
+            parent_id: data.parent_id,
+            priority: data.priority,
+            name: span.name(),
+            resource: data.resource.unwrap_or_else(|| span.name().to_owned()),
+            service: data.service.unwrap_or_else(|| self.service.clone()),
+            start_nanos: data.start_nanos,
+            duration_nanos: data.start.elapsed().as_nanos() as u64,
+            error: data.error,
+            meta: data.meta,
+        };
+        // The only send error is a disconnected worker; spans are dropped in
+        // that case, as there is nowhere to export them to.
+        let _ = self
+            .sender
+            .lock()
+            .expect("datadog sender poisoned")
+            .send(finished);
+    }
+}
+
+impl Subscriber {
+    /// Rolls the sampling priority for a new trace.
+    fn priority(&self) -> i32 {
+        if self.sample_rate >= 1.0 {
+            return 1;
+        }
+        let roll = random_id() as f64;
+        if roll < self.sample_rate * (u64::MAX as f64) {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the `host:port` the Datadog agent accepts traces on.
+    ///
+    /// The default is `127.0.0.1:8126`, the agent's standard trace port.
+    pub fn with_agent(self, agent: impl Into<String>) -> Self {
+        Self {
+            agent: agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the service name for spans that do not carry a `service`
+    /// field.
+    ///
+    /// The default is `tracing`.
+    pub fn with_service(self, service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            ..self
+        }
+    }
+
+    /// Sets the fraction of traces given a keep priority.
+    ///
+    /// The priority is decided once per trace at its root span and
+    /// inherited by every span in the trace; traces rolled out are still
+    /// sent, but marked for the agent to drop, so its trace counts stay
+    /// accurate. The rate is clamped between 0.0 and 1.0; the default of
+    /// 1.0 keeps everything.
+    pub fn with_sample_rate(self, sample_rate: f64) -> Self {
+        Self {
+            sample_rate: sample_rate.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Sets how often traces whose root span has not yet closed are
+    /// flushed anyway.
+    ///
+    /// The default is 5 seconds.
+    pub fn with_flush_interval(self, flush_interval: Duration) -> Self {
+        Self {
+            flush_interval,
+            ..self
+        }
+    }
+
+    /// Sets how many times a failed export is retried before being
+    /// dropped.
+    ///
+    /// Retries back off exponentially, starting at 100 milliseconds. The
+    /// default is 3 retries.
+    pub fn with_max_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Returns the configured exporter [`Subscriber`], spawning its export
+    /// thread.
+    ///
+    /// The thread runs until the `Subscriber` is dropped; any traces still
+    /// buffered at that point are exported before it exits.
+    pub fn finish(self) -> io::Result<Subscriber> {
+        let (sender, receiver) = mpsc::channel();
+        let worker = Worker {
+            agent: self.agent,
+            flush_interval: self.flush_interval,
+            max_retries: self.max_retries,
+        };
+        thread::Builder::new()
+            .name("tracing-datadog".into())
+            .spawn(move || worker.run(receiver))?;
+        Ok(Subscriber {
+            sender: Mutex::new(sender),
+            service: self.service,
+            sample_rate: self.sample_rate,
+        })
+    }
+}
+
+// === impl Worker ===
+
+/// The export thread: groups spans into traces and sends each trace when
+/// its root closes.
+struct Worker {
+    agent: String,
+    flush_interval: Duration,
+    max_retries: u32,
+}
+
+impl Worker {
+    fn run(&self, receiver: mpsc::Receiver<FinishedSpan>) {
+        let mut pending: HashMap<u64, Vec<FinishedSpan>> = HashMap::new();
+        loop {
+            match receiver.recv_timeout(self.flush_interval) {
+                Ok(span) => {
+                    let is_root = span.parent_id == 0;
+                    let trace_id = span.trace_id;
+                    pending.entry(trace_id).or_default().push(span);
+                    if is_root {
+                        let trace = pending.remove(&trace_id).expect("just inserted");
+                        self.export(vec![trace]);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    if !pending.is_empty() {
+                        self.export(pending.drain().map(|(_, trace)| trace).collect());
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    if !pending.is_empty() {
+                        self.export(pending.drain().map(|(_, trace)| trace).collect());
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Sends a payload of traces, retrying with exponential backoff before
+    /// dropping it.
+    fn export(&self, traces: Vec<Vec<FinishedSpan>>) {
+        let count = traces.len();
+        let body = encode_payload(&traces);
+        let mut backoff = Duration::from_millis(100);
+        for attempt in 0..=self.max_retries {
+            if self.put(&body, count).is_ok() {
+                return;
+            }
+            if attempt < self.max_retries {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+    }
+
+    /// Sends one payload, returning an error unless the agent responds
+    /// with a success status.
+    fn put(&self, body: &[u8], trace_count: usize) -> io::Result<()> {
+        let mut stream = TcpStream::connect(&self.agent)?;
+        let request = format!(
+            "PUT /v0.4/traces HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Content-Type: application/msgpack\r\n\
+             X-Datadog-Trace-Count: {}\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n",
+            self.agent,
+            trace_count,
+            body.len(),
+        );
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body)?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        // "HTTP/1.1 200 OK" => "200"
+        let code = status.split_whitespace().nth(1).unwrap_or("");
+        if code.starts_with('2') {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Datadog agent responded with status {:?}", status.trim()),
+            ))
+        }
+    }
+}
+
+/// Encodes a payload of traces in the agent's msgpack format.
+fn encode_payload(traces: &[Vec<FinishedSpan>]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    mp_array(&mut buf, traces.len());
+    for trace in traces {
+        mp_array(&mut buf, trace.len());
+        for span in trace {
+            encode_span(&mut buf, span);
+        }
+    }
+    buf
+}
+
+/// Encodes one span as the agent's eleven-member span map.
+fn encode_span(buf: &mut Vec<u8>, span: &FinishedSpan) {
+    mp_map(buf, 11);
+    mp_str(buf, "trace_id");
+    mp_u64(buf, span.trace_id);
+    mp_str(buf, "span_id");
+    mp_u64(buf, span.span_id);
+    mp_str(buf, "parent_id");
+    mp_u64(buf, span.parent_id);
+    mp_str(buf, "name");
+    mp_str(buf, span.name);
+    mp_str(buf, "resource");
+    mp_str(buf, &span.resource);
+    mp_str(buf, "service");
+    mp_str(buf, &span.service);
+    mp_str(buf, "start");
+    mp_i64(buf, span.start_nanos as i64);
+    mp_str(buf, "duration");
+    mp_i64(buf, span.duration_nanos.max(1) as i64);
+    mp_str(buf, "error");
+    mp_i64(buf, i64::from(span.error));
+    mp_str(buf, "meta");
+    mp_map(buf, span.meta.len());
+    for (name, value) in &span.meta {
+        mp_str(buf, name);
+        mp_str(buf, value);
+    }
+    mp_str(buf, "metrics");
+    mp_map(buf, 1);
+    mp_str(buf, PRIORITY_KEY);
+    mp_f64(buf, f64::from(span.priority));
+}
+
+/// Writes a msgpack array header for `len` elements.
+fn mp_array(buf: &mut Vec<u8>, len: usize) {
+    if len < 16 {
+        buf.push(0x90 | len as u8);
+    } else {
+        buf.push(0xdc);
+        buf.extend_from_slice(&(len as u16).to_be_bytes());
+    }
+}
+
+/// Writes a msgpack map header for `len` pairs.
+fn mp_map(buf: &mut Vec<u8>, len: usize) {
+    if len < 16 {
+        buf.push(0x80 | len as u8);
+    } else {
+        buf.push(0xde);
+        buf.extend_from_slice(&(len as u16).to_be_bytes());
+    }
+}
+
+/// Writes a msgpack string.
+fn mp_str(buf: &mut Vec<u8>, value: &str) {
+    let bytes = value.as_bytes();
+    if bytes.len() < 32 {
+        buf.push(0xa0 | bytes.len() as u8);
+    } else if bytes.len() < 256 {
+        buf.push(0xd9);
+        buf.push(bytes.len() as u8);
+    } else {
+        buf.push(0xda);
+        buf.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    }
+    buf.extend_from_slice(bytes);
+}
+
+/// Writes a msgpack `uint 64`.
+fn mp_u64(buf: &mut Vec<u8>, value: u64) {
+    buf.push(0xcf);
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Writes a msgpack `int 64`.
+fn mp_i64(buf: &mut Vec<u8>, value: i64) {
+    buf.push(0xd3);
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Writes a msgpack `float 64`.
+fn mp_f64(buf: &mut Vec<u8>, value: f64) {
+    buf.push(0xcb);
+    buf.extend_from_slice(&value.to_bits().to_be_bytes());
+}
+
+/// Returns a pseudo-random non-zero 64-bit identifier.
+///
+/// This uses the standard library's randomly-seeded hasher rather than a
+/// cryptographic RNG; IDs are unique in practice but not unpredictable.
+fn random_id() -> u64 {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+        sync::atomic::{AtomicU64, Ordering},
+    };
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish().max(1)
+}
+
+/// Returns the current wall-clock time in nanoseconds since the Unix
+/// epoch.
+fn unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Records span fields, routing `resource`, `service`, and `error` to
+/// their dedicated span members and everything else to tags.
+struct TagVisitor<'a> {
+    data: &'a mut DatadogSpan,
+}
+
+impl TagVisitor<'_> {
+    fn record(&mut self, field: &field::Field, value: String) {
+        match field.name() {
+            "resource" => self.data.resource = Some(value),
+            "service" => self.data.service = Some(value),
+            "error" => {
+                self.data.error = true;
+                self.data.meta.push(("error.message", value));
+            }
+            name => match self.data.meta.iter_mut().find(|(meta, _)| *meta == name) {
+                Some((_, existing)) => *existing = value,
+                None => self.data.meta.push((name, value)),
+            },
+        }
+    }
+}
+
+impl field::Visit for TagVisitor<'_> {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.record(field, value.to_owned());
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.record(field, format!("{:?}", value));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::net::TcpListener;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn msgpack_scalars_encode_correctly() {
+        let mut buf = Vec::new();
+        mp_str(&mut buf, "abc");
+        assert_eq!(buf, [0xa3, b'a', b'b', b'c']);
+
+        buf.clear();
+        mp_u64(&mut buf, 5);
+        assert_eq!(buf, [0xcf, 0, 0, 0, 0, 0, 0, 0, 5]);
+
+        buf.clear();
+        mp_f64(&mut buf, 1.0);
+        assert_eq!(buf, [0xcb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0]);
+
+        buf.clear();
+        mp_array(&mut buf, 2);
+        mp_map(&mut buf, 3);
+        assert_eq!(buf, [0x92, 0x83]);
+    }
+
+    /// Returns whether `haystack` contains `needle`.
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    /// Accepts one payload and returns its request line, headers, and
+    /// body.
+    fn accept_traces(listener: &TcpListener) -> (String, String, Vec<u8>) {
+        let (stream, _) = listener.accept().expect("no traces received");
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader
+            .read_line(&mut request_line)
+            .expect("failed to read request line");
+        let mut headers = String::new();
+        let mut content_length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).expect("failed to read header");
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            headers.push_str(&header.to_ascii_lowercase());
+            headers.push('\n');
+            if let Some(length) = header
+                .to_ascii_lowercase()
+                .strip_prefix("content-length:")
+                .map(str::trim)
+            {
+                content_length = length.parse().expect("invalid content-length");
+            }
+        }
+        let mut body = vec![0; content_length];
+        io::Read::read_exact(&mut reader, &mut body).expect("failed to read body");
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+            .expect("failed to respond");
+        (request_line.trim().to_owned(), headers, body)
+    }
+
+    #[test]
+    fn traces_export_when_their_root_closes() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let exporter = Subscriber::builder()
+            .with_agent(addr.to_string())
+            .with_service("trace-test")
+            .with_flush_interval(Duration::from_secs(30))
+            .finish()
+            .expect("failed to start exporter");
+        let collector = crate::registry().with(exporter);
+
+        with_default(collector, || {
+            let parent = tracing::info_span!("handle_request", resource = "GET /users");
+            let _entered = parent.enter();
+            tracing::info_span!("query_db", table = "users").in_scope(|| {});
+        });
+
+        let (request_line, headers, body) = accept_traces(&listener);
+        assert_eq!(request_line, "PUT /v0.4/traces HTTP/1.1");
+        assert!(headers.contains("content-type: application/msgpack"));
+        assert!(
+            headers.contains("x-datadog-trace-count: 1"),
+            "unexpected trace count: {}",
+            headers,
+        );
+        // One payload, one trace, two spans.
+        assert_eq!(&body[..2], [0x91, 0x92]);
+        assert!(contains(&body, b"handle_request"));
+        assert!(contains(&body, b"query_db"));
+        assert!(contains(&body, b"GET /users"), "missing resource");
+        assert!(contains(&body, b"trace-test"), "missing default service");
+        assert!(contains(&body, b"table"), "missing tag");
+        assert!(
+            contains(&body, PRIORITY_KEY.as_bytes()),
+            "missing sampling priority",
+        );
+    }
+
+    #[test]
+    fn service_fields_override_the_default() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let exporter = Subscriber::builder()
+            .with_agent(addr.to_string())
+            .with_service("trace-test")
+            .with_flush_interval(Duration::from_secs(30))
+            .finish()
+            .expect("failed to start exporter");
+        let collector = crate::registry().with(exporter);
+
+        with_default(collector, || {
+            tracing::info_span!("call_billing", service = "billing-client").in_scope(|| {});
+        });
+
+        let (_, _, body) = accept_traces(&listener);
+        assert!(contains(&body, b"billing-client"));
+        assert!(!contains(&body, b"trace-test"), "default not overridden");
+    }
+}
@@ -0,0 +1,427 @@
+//! Failure-only verbose logging, buffered per root span.
+//!
+//! Always-on verbose output is too expensive for production, but a failed
+//! request is exactly when the `DEBUG`-level detail is wanted. This module
+//! provides a [`Subscriber`] that buffers every event occurring inside a
+//! root span instead of writing it anywhere. When the root span closes,
+//! the buffer is either discarded — the common, successful case, costing
+//! only the buffering — or, if the span failed, written out in full, so
+//! the log contains the complete verbose history of exactly the requests
+//! that went wrong.
+//!
+//! A root span is considered failed if any event recorded inside it
+//! reached the threshold set with [`Builder::flush_on`] ([`ERROR`] by
+//! default), or if an `error` field was recorded on any span inside it.
+//! No other application-side configuration is needed beyond having a root
+//! span that delimits the unit of work, which well-instrumented request
+//! handlers already do.
+//!
+//! Events recorded outside any span are not buffered and not written;
+//! they are expected to be handled by the rest of the subscriber stack.
+//!
+//! # Limitations
+//!
+//! Buffered events are re-rendered as plain text when flushed, rather
+//! than being replayed through other subscribers in the stack; a
+//! formatting subscriber's filter and format do not apply to them. The
+//! buffer for a root span grows without bound while the span is open, so
+//! very long-lived root spans with verbose interiors hold their whole
+//! history in memory.
+//!
+//! # Examples
+//!
+//! ```
+//! use tracing_subscriber::{deferred, filter::LevelFilter, prelude::*};
+//!
+//! let collector = tracing_subscriber::registry()
+//!     // Print only INFO and above as it happens...
+//!     .with(tracing_subscriber::fmt::subscriber().with_filter(LevelFilter::INFO))
+//!     // ...and keep the full history of failed requests.
+//!     .with(deferred::Subscriber::new());
+//! # let _ = collector;
+//! ```
+//!
+//! Note that this subscriber can only buffer events that are enabled by
+//! the collector as a whole: a global `INFO` filter would discard `DEBUG`
+//! events before they reach any subscriber. Filter the *other*
+//! subscribers in the stack (as above) rather than the whole collector.
+//!
+//! [`ERROR`]: tracing_core::Level::ERROR
+use crate::{
+    filter::LevelFilter,
+    fmt::MakeWriter,
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    fmt::Write as _,
+    io::{self, Write as _},
+    time::Instant,
+};
+use tracing_core::{field, span, Collect, Event, Level};
+
+/// A [`Subscribe`] implementation that buffers each root span's events,
+/// writing them out only if the span failed.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber<W = fn() -> io::Stderr> {
+    make_writer: W,
+    flush_on: LevelFilter,
+}
+
+/// Configures a deferred-capture [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    flush_on: LevelFilter,
+}
+
+/// The event buffer held in a capturing root span's extensions.
+#[derive(Debug)]
+struct Capture {
+    opened: Instant,
+    failed: bool,
+    events: Vec<BufferedEvent>,
+}
+
+#[derive(Debug)]
+struct BufferedEvent {
+    at: Instant,
+    level: Level,
+    target: &'static str,
+    scope: String,
+    fields: String,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new deferred-capture subscriber with the default
+    /// configuration: failed root spans are written to standard error, and
+    /// an [`ERROR`] event marks its root span failed.
+    ///
+    /// [`ERROR`]: tracing_core::Level::ERROR
+    pub fn new() -> Self {
+        Self::builder().finish(io::stderr as fn() -> io::Stderr)
+    }
+
+    /// Returns a [`Builder`] for configuring a deferred-capture
+    /// subscriber.
+    pub fn builder() -> Builder {
+        Builder {
+            flush_on: LevelFilter::ERROR,
+        }
+    }
+}
+
+impl Default for Subscriber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W> Subscriber<W>
+where
+    W: for<'a> MakeWriter<'a>,
+{
+    /// Writes a failed capture's buffered events to the writer.
+    fn flush(&self, name: &str, capture: &Capture) -> io::Result<()> {
+        let mut writer = self.make_writer.make_writer();
+        writeln!(
+            writer,
+            "deferred capture for {:?} ({} events):",
+            name,
+            capture.events.len()
+        )?;
+        for event in &capture.events {
+            let offset = event.at.saturating_duration_since(capture.opened);
+            write!(writer, "[+{:>10.1?}] {:>5} ", offset, event.level)?;
+            if !event.scope.is_empty() {
+                write!(writer, "{}: ", event.scope)?;
+            }
+            writeln!(writer, "{}: {}", event.target, event.fields)?;
+        }
+        writer.flush()
+    }
+}
+
+impl<C, W> Subscribe<C> for Subscriber<W>
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        if span.parent().is_none() {
+            span.extensions_mut().insert(Capture {
+                opened: Instant::now(),
+                failed: false,
+                events: Vec::new(),
+            });
+        }
+        let mut visitor = ErrorVisitor { failed: false };
+        attrs.record(&mut visitor);
+        if visitor.failed {
+            mark_failed(&span);
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        let mut visitor = ErrorVisitor { failed: false };
+        values.record(&mut visitor);
+        if visitor.failed {
+            let span = ctx.span(id).expect("Span not found, this is a bug");
+            mark_failed(&span);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let scope = match ctx.event_scope(event) {
+            Some(scope) => scope,
+            // Not inside a span; the rest of the stack handles it.
+            None => return,
+        };
+        let mut names = String::new();
+        let mut root = None;
+        for span in scope.from_root() {
+            if root.is_none() {
+                root = Some(span.id());
+            } else {
+                names.push(':');
+            }
+            names.push_str(span.name());
+        }
+        let root = root.and_then(|id| ctx.span(&id));
+        let root = match root {
+            Some(root) => root,
+            None => return,
+        };
+
+        let metadata = event.metadata();
+        let mut fields = String::new();
+        event.record(&mut FieldVisitor {
+            fields: &mut fields,
+        });
+        let mut extensions = root.extensions_mut();
+        if let Some(capture) = extensions.get_mut::<Capture>() {
+            if *metadata.level() <= self.flush_on {
+                capture.failed = true;
+            }
+            capture.events.push(BufferedEvent {
+                at: Instant::now(),
+                level: *metadata.level(),
+                target: metadata.target(),
+                scope: names,
+                fields,
+            });
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(&id).expect("Span not found, this is a bug");
+        let capture = span.extensions_mut().remove::<Capture>();
+        if let Some(capture) = capture {
+            if capture.failed {
+                let _ = self.flush(span.name(), &capture);
+            }
+        }
+    }
+}
+
+/// Marks the capture at the root of `span`'s scope as failed.
+fn mark_failed<'a, R>(span: &crate::registry::SpanRef<'a, R>)
+where
+    R: LookupSpan<'a>,
+{
+    if let Some(root) = span.scope().from_root().next() {
+        let mut extensions = root.extensions_mut();
+        if let Some(capture) = extensions.get_mut::<Capture>() {
+            capture.failed = true;
+        }
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the event severity that marks a root span as failed.
+    ///
+    /// Buffering an event at or above this level causes the capture to be
+    /// written out when its root span closes. The default is [`ERROR`];
+    /// use [`LevelFilter::OFF`] to flush only when an `error` field is
+    /// recorded.
+    ///
+    /// [`ERROR`]: tracing_core::Level::ERROR
+    pub fn flush_on(self, level: LevelFilter) -> Self {
+        Self { flush_on: level }
+    }
+
+    /// Returns a deferred-capture [`Subscriber`] that writes failed
+    /// captures to `make_writer`.
+    pub fn finish<W>(self, make_writer: W) -> Subscriber<W>
+    where
+        W: for<'a> MakeWriter<'a>,
+    {
+        Subscriber {
+            make_writer,
+            flush_on: self.flush_on,
+        }
+    }
+}
+
+/// Renders an event's fields into a single line of text.
+struct FieldVisitor<'a> {
+    fields: &'a mut String,
+}
+
+impl field::Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+        if field.name() == "message" {
+            let _ = write!(self.fields, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, "{}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Detects an `error` field being recorded on a span.
+struct ErrorVisitor {
+    failed: bool,
+}
+
+impl field::Visit for ErrorVisitor {
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        if field.name() == "error" {
+            self.failed |= value;
+        }
+    }
+
+    fn record_debug(&mut self, field: &field::Field, _value: &dyn std::fmt::Debug) {
+        if field.name() == "error" {
+            self.failed = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::collect::with_default;
+
+    /// A [`MakeWriter`] that appends to a shared buffer.
+    #[derive(Clone)]
+    struct MakeBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for MakeBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for MakeBuf {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn capture() -> (Subscriber<MakeBuf>, Arc<Mutex<Vec<u8>>>) {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = Subscriber::builder().finish(MakeBuf(buf.clone()));
+        (subscriber, buf)
+    }
+
+    fn output(buf: &Arc<Mutex<Vec<u8>>>) -> String {
+        String::from_utf8(buf.lock().unwrap().clone()).unwrap()
+    }
+
+    #[test]
+    fn successful_spans_discard_their_events() {
+        let (deferred, buf) = capture();
+        let collector = crate::registry().with(deferred);
+        with_default(collector, || {
+            let span = tracing::info_span!("request");
+            let _entered = span.enter();
+            tracing::debug!("looked up the user");
+            tracing::debug!("rendered the page");
+        });
+        assert_eq!(output(&buf), "");
+    }
+
+    #[test]
+    fn failed_spans_flush_their_full_history() {
+        let (deferred, buf) = capture();
+        let collector = crate::registry().with(deferred);
+        with_default(collector, || {
+            let span = tracing::info_span!("request");
+            let _entered = span.enter();
+            tracing::trace!(user = 42, "looked up the user");
+            tracing::error!("the database went away");
+        });
+        let output = output(&buf);
+        assert!(
+            output.starts_with("deferred capture for \"request\" (2 events):"),
+            "{}",
+            output
+        );
+        assert!(output.contains("looked up the user user=42"), "{}", output);
+        assert!(output.contains("the database went away"), "{}", output);
+    }
+
+    #[test]
+    fn error_fields_mark_spans_failed() {
+        let (deferred, buf) = capture();
+        let collector = crate::registry().with(deferred);
+        with_default(collector, || {
+            let span = tracing::info_span!("request", error = tracing::field::Empty);
+            let _entered = span.enter();
+            tracing::debug!("all quiet");
+            span.record("error", "deadline exceeded");
+        });
+        let output = output(&buf);
+        assert!(output.contains("all quiet"), "{}", output);
+    }
+
+    #[test]
+    fn nested_spans_buffer_into_the_root() {
+        let (deferred, buf) = capture();
+        let collector = crate::registry().with(deferred);
+        with_default(collector, || {
+            let root = tracing::info_span!("request");
+            let _root = root.enter();
+            let child = tracing::info_span!("query");
+            let _child = child.enter();
+            tracing::debug!("fetching rows");
+            drop(_child);
+            drop(child);
+            tracing::error!("rendering failed");
+        });
+        let output = output(&buf);
+        assert!(output.contains("(2 events):"), "{}", output);
+        assert!(output.contains("query: "), "{}", output);
+        assert!(output.contains("fetching rows"), "{}", output);
+    }
+
+    #[test]
+    fn events_outside_spans_are_ignored() {
+        let (deferred, buf) = capture();
+        let collector = crate::registry().with(deferred);
+        with_default(collector, || {
+            tracing::error!("no span in sight");
+        });
+        assert_eq!(output(&buf), "");
+    }
+}
@@ -0,0 +1,670 @@
+//! Ships JSON-formatted events to Elasticsearch or OpenSearch in bulk.
+//!
+//! Teams that centralize their logs in [Elasticsearch] (or its OpenSearch
+//! fork) commonly run a log-shipper sidecar whose only job is to tail a file
+//! and forward the lines. This module cuts out the intermediary: a
+//! [`Subscriber`] formats each event as a JSON document, batches documents in
+//! a background thread, and posts them to the cluster's [`_bulk`] endpoint
+//! directly, with no dependencies beyond the standard library.
+//!
+//! Documents are written to *daily indices* named after the event's
+//! timestamp — `tracing-2026.08.29` by default — which is the conventional
+//! layout for time-series log data and lets retention be managed by deleting
+//! whole indices. On startup the shipper installs a matching [index
+//! template] so that the daily indices are created with `@timestamp` mapped
+//! as a `date` field.
+//!
+//! Events are handed to the shipper through a bounded in-memory queue. If
+//! the cluster cannot keep up and the queue fills, new events are dropped
+//! rather than blocking the threads that record them; the number of events
+//! dropped this way is available from [`Subscriber::dropped_events`]. Failed
+//! bulk requests are retried with exponential backoff before the batch is
+//! dropped.
+//!
+//! # Limitations
+//!
+//! - Only plaintext `http://` endpoints are supported; there is no TLS and
+//!   no authentication, so the shipper is suited to clusters reached over a
+//!   private network or a local proxy.
+//! - The bulk response body is not parsed, so documents rejected
+//!   individually by the cluster (for example, by a mapping conflict) are
+//!   not retried or counted as dropped.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{elasticsearch, prelude::*};
+//!
+//! let shipper = elasticsearch::Subscriber::builder()
+//!     .with_endpoint("127.0.0.1:9200")
+//!     .with_index_prefix("my-service")
+//!     .finish()
+//!     .expect("failed to start the Elasticsearch shipper");
+//! let collector = tracing_subscriber::registry().with(shipper);
+//! # let _ = collector;
+//! ```
+//!
+//! [Elasticsearch]: https://www.elastic.co/elasticsearch
+//! [`_bulk`]: https://www.elastic.co/guide/en/elasticsearch/reference/current/docs-bulk.html
+//! [index template]: https://www.elastic.co/guide/en/elasticsearch/reference/current/index-templates.html
+use crate::subscribe::{Context, Subscribe};
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+use tracing_core::{field, Collect, Event};
+
+/// A [`Subscribe`] implementation that ships events to an Elasticsearch or
+/// OpenSearch cluster.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    sender: mpsc::SyncSender<Document>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Configures an Elasticsearch shipper [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    endpoint: String,
+    index_prefix: String,
+    queue_capacity: usize,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+/// The default endpoint, on the conventional Elasticsearch port.
+const DEFAULT_ENDPOINT: &str = "127.0.0.1:9200";
+
+/// The default daily index prefix.
+const DEFAULT_INDEX_PREFIX: &str = "tracing";
+
+/// A formatted event, as handed to the shipper thread.
+struct Document {
+    /// The event time in milliseconds since the Unix epoch; this selects the
+    /// daily index the document is routed to.
+    millis: u64,
+    /// The document body, as a single line of JSON.
+    json: String,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a [`Builder`] for configuring an Elasticsearch shipper.
+    pub fn builder() -> Builder {
+        Builder {
+            endpoint: DEFAULT_ENDPOINT.to_owned(),
+            index_prefix: DEFAULT_INDEX_PREFIX.to_owned(),
+            queue_capacity: 4096,
+            batch_size: 512,
+            batch_timeout: Duration::from_secs(5),
+            max_retries: 3,
+        }
+    }
+
+    /// Returns the number of events dropped because the queue was full.
+    ///
+    /// The counter is cumulative over the life of the shipper. A steadily
+    /// growing value means the cluster is not keeping up with the event
+    /// rate; batches dropped after exhausting their retries are *not*
+    /// included, as those events had already left the queue.
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<C: Collect> Subscribe<C> for Subscriber {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        let millis = unix_millis();
+        let metadata = event.metadata();
+        let mut json = String::from("{\"@timestamp\":\"");
+        format_timestamp(&mut json, millis);
+        json.push_str("\",\"level\":\"");
+        json.push_str(metadata.level().as_str());
+        json.push_str("\",\"target\":\"");
+        escape_into(&mut json, metadata.target());
+        json.push('"');
+
+        let mut visitor = FieldVisitor::new();
+        event.record(&mut visitor);
+        let (message, fields) = visitor.finish();
+        if let Some(message) = message {
+            json.push_str(",\"message\":\"");
+            escape_into(&mut json, &message);
+            json.push('"');
+        }
+        if fields != "{}" {
+            json.push_str(",\"fields\":");
+            json.push_str(&fields);
+        }
+        json.push('}');
+
+        // A full queue means the shipper is behind; drop the event rather
+        // than blocking the recording thread, and count the loss. A
+        // disconnected queue means the shipper thread has exited, in which
+        // case there is nowhere to ship to.
+        if let Err(mpsc::TrySendError::Full(_)) = self.sender.try_send(Document { millis, json }) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the `host:port` of the cluster to ship to.
+    ///
+    /// An `http://` scheme prefix is accepted and ignored. The default is
+    /// `127.0.0.1:9200`, the conventional Elasticsearch port.
+    pub fn with_endpoint(self, endpoint: impl Into<String>) -> Self {
+        let endpoint = endpoint.into();
+        let endpoint = endpoint
+            .strip_prefix("http://")
+            .map(String::from)
+            .unwrap_or(endpoint);
+        let endpoint = endpoint.trim_end_matches('/').to_owned();
+        Self { endpoint, ..self }
+    }
+
+    /// Sets the prefix for the daily indices documents are written to.
+    ///
+    /// An event recorded on the 29th of August 2026 with the prefix
+    /// `my-service` is indexed into `my-service-2026.08.29`. The default
+    /// prefix is `tracing`.
+    pub fn with_index_prefix(self, index_prefix: impl Into<String>) -> Self {
+        Self {
+            index_prefix: index_prefix.into(),
+            ..self
+        }
+    }
+
+    /// Sets how many events may be queued for shipping before new events
+    /// are dropped.
+    ///
+    /// Dropped events are counted by [`Subscriber::dropped_events`]. The
+    /// default is 4096 events.
+    pub fn with_queue_capacity(self, queue_capacity: usize) -> Self {
+        Self {
+            queue_capacity: queue_capacity.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the number of events that triggers a bulk request.
+    ///
+    /// A batch is shipped when it reaches this size, or when
+    /// [`batch_timeout`](Self::with_batch_timeout) elapses, whichever comes
+    /// first. The default is 512 events.
+    pub fn with_batch_size(self, batch_size: usize) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            ..self
+        }
+    }
+
+    /// Sets how long events may be buffered before being shipped.
+    ///
+    /// The default is 5 seconds.
+    pub fn with_batch_timeout(self, batch_timeout: Duration) -> Self {
+        Self {
+            batch_timeout,
+            ..self
+        }
+    }
+
+    /// Sets how many times a failed bulk request is retried before the
+    /// batch is dropped.
+    ///
+    /// Retries back off exponentially, starting at 100 milliseconds. The
+    /// default is 3 retries.
+    pub fn with_max_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Returns the configured shipper [`Subscriber`], spawning its shipping
+    /// thread.
+    ///
+    /// The thread runs until the `Subscriber` is dropped; any events still
+    /// buffered at that point are flushed before it exits.
+    pub fn finish(self) -> io::Result<Subscriber> {
+        let (sender, receiver) = mpsc::sync_channel(self.queue_capacity);
+        let worker = Worker {
+            endpoint: self.endpoint,
+            index_prefix: self.index_prefix,
+            batch_size: self.batch_size,
+            batch_timeout: self.batch_timeout,
+            max_retries: self.max_retries,
+        };
+        thread::Builder::new()
+            .name("tracing-elasticsearch".into())
+            .spawn(move || worker.run(receiver))?;
+        Ok(Subscriber {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+}
+
+// === impl Worker ===
+
+/// The shipping thread: installs the index template, then batches documents
+/// and posts them to the `_bulk` endpoint.
+struct Worker {
+    endpoint: String,
+    index_prefix: String,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+impl Worker {
+    fn run(&self, receiver: mpsc::Receiver<Document>) {
+        self.put_index_template();
+        let mut batch = Vec::new();
+        loop {
+            match receiver.recv_timeout(self.batch_timeout) {
+                Ok(document) => {
+                    batch.push(document);
+                    if batch.len() >= self.batch_size {
+                        self.ship(&mut batch);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    if !batch.is_empty() {
+                        self.ship(&mut batch);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    if !batch.is_empty() {
+                        self.ship(&mut batch);
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Installs the index template covering the daily indices, so that they
+    /// are created with `@timestamp` mapped as a `date`.
+    ///
+    /// This is best-effort: a cluster that is unreachable at startup, or one
+    /// that rejects the request, still dynamically maps documents usably.
+    fn put_index_template(&self) {
+        let body = format!(
+            "{{\"index_patterns\":[\"{}-*\"],\
+             \"template\":{{\"mappings\":{{\"properties\":{{\
+             \"@timestamp\":{{\"type\":\"date\"}}}}}}}}}}",
+            self.index_prefix,
+        );
+        let path = format!("/_index_template/{}", self.index_prefix);
+        let _ = self.send("PUT", &path, body.as_bytes());
+    }
+
+    /// Ships `batch` as one bulk request, retrying with exponential
+    /// backoff; the batch is dropped either way.
+    fn ship(&self, batch: &mut Vec<Document>) {
+        let body = encode_bulk(&self.index_prefix, batch);
+        batch.clear();
+        let mut backoff = Duration::from_millis(100);
+        for attempt in 0..=self.max_retries {
+            if self.send("POST", "/_bulk", body.as_bytes()).is_ok() {
+                return;
+            }
+            if attempt < self.max_retries {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+    }
+
+    /// Sends one HTTP request, returning an error unless the cluster
+    /// responds with a success status.
+    fn send(&self, method: &str, path: &str, body: &[u8]) -> io::Result<()> {
+        let mut stream = TcpStream::connect(&self.endpoint)?;
+        let request = format!(
+            "{} {} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Content-Type: application/x-ndjson\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n",
+            method,
+            path,
+            self.endpoint,
+            body.len(),
+        );
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body)?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        // "HTTP/1.1 200 OK" => "200"
+        let code = status.split_whitespace().nth(1).unwrap_or("");
+        if code.starts_with('2') {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("cluster responded with status {:?}", status.trim()),
+            ))
+        }
+    }
+}
+
+/// Encodes `batch` as an NDJSON `_bulk` request body: an `index` action
+/// line naming the daily index, then the document, for each event.
+fn encode_bulk(index_prefix: &str, batch: &[Document]) -> String {
+    let mut body = String::new();
+    for document in batch {
+        body.push_str("{\"index\":{\"_index\":\"");
+        body.push_str(index_prefix);
+        body.push('-');
+        let (year, month, day) = civil_date(document.millis / 86_400_000);
+        let _ = write!(body, "{:04}.{:02}.{:02}", year, month, day);
+        body.push_str("\"}}\n");
+        body.push_str(&document.json);
+        body.push('\n');
+    }
+    body
+}
+
+/// Returns the current wall-clock time in milliseconds since the Unix
+/// epoch.
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Converts a count of days since the Unix epoch to a `(year, month, day)`
+/// calendar date, using the standard civil-from-days algorithm.
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let year = year_of_era + era * 400;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = if month <= 2 { year + 1 } else { year };
+    (year, month, day)
+}
+
+/// Formats `millis` as an ISO 8601 UTC timestamp
+/// (`2026-08-29T12:34:56.789Z`).
+fn format_timestamp(out: &mut String, millis: u64) {
+    let (year, month, day) = civil_date(millis / 86_400_000);
+    let time_of_day = millis % 86_400_000;
+    let _ = write!(
+        out,
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        time_of_day / 3_600_000,
+        time_of_day / 60_000 % 60,
+        time_of_day / 1000 % 60,
+        time_of_day % 1000,
+    );
+}
+
+/// Writes `value` into `out` with JSON string escaping.
+fn escape_into(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+/// Records event fields as a JSON object, extracting the `message` field.
+struct FieldVisitor {
+    message: Option<String>,
+    json: String,
+    first: bool,
+}
+
+impl FieldVisitor {
+    fn new() -> Self {
+        Self {
+            message: None,
+            json: String::from("{"),
+            first: true,
+        }
+    }
+
+    fn finish(mut self) -> (Option<String>, String) {
+        self.json.push('}');
+        (self.message, self.json)
+    }
+
+    fn member(&mut self, field: &field::Field) {
+        if !self.first {
+            self.json.push(',');
+        }
+        self.first = false;
+        self.json.push('"');
+        escape_into(&mut self.json, field.name());
+        self.json.push_str("\":");
+    }
+}
+
+impl field::Visit for FieldVisitor {
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.member(field);
+        if value.is_finite() {
+            let _ = write!(self.json, "{}", value);
+        } else {
+            let _ = write!(self.json, "\"{}\"", value);
+        }
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_owned());
+            return;
+        }
+        self.member(field);
+        self.json.push('"');
+        escape_into(&mut self.json, value);
+        self.json.push('"');
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        let rendered = format!("{:?}", value);
+        if field.name() == "message" {
+            self.message = Some(rendered);
+            return;
+        }
+        self.member(field);
+        self.json.push('"');
+        escape_into(&mut self.json, &rendered);
+        self.json.push('"');
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::net::TcpListener;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn civil_dates_convert_correctly() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(31), (1970, 2, 1));
+        // 2000-02-29: a leap day in a century year divisible by 400.
+        assert_eq!(civil_date(11_016), (2000, 2, 29));
+        assert_eq!(civil_date(20_694), (2026, 8, 29));
+    }
+
+    #[test]
+    fn timestamps_format_correctly() {
+        let mut out = String::new();
+        format_timestamp(&mut out, 0);
+        assert_eq!(out, "1970-01-01T00:00:00.000Z");
+        out.clear();
+        // 2026-08-29 13:14:15.678 UTC.
+        format_timestamp(&mut out, 1_788_009_255_678);
+        assert_eq!(out, "2026-08-29T13:14:15.678Z");
+    }
+
+    #[test]
+    fn bulk_bodies_route_to_daily_indices() {
+        let batch = [
+            Document {
+                millis: 0,
+                json: "{\"message\":\"one\"}".to_owned(),
+            },
+            Document {
+                millis: 20_694 * 86_400_000,
+                json: "{\"message\":\"two\"}".to_owned(),
+            },
+        ];
+        let body = encode_bulk("my-service", &batch);
+        assert_eq!(
+            body,
+            "{\"index\":{\"_index\":\"my-service-1970.01.01\"}}\n\
+             {\"message\":\"one\"}\n\
+             {\"index\":{\"_index\":\"my-service-2026.08.29\"}}\n\
+             {\"message\":\"two\"}\n",
+        );
+    }
+
+    #[test]
+    fn full_queues_drop_and_count() {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let shipper = Subscriber {
+            sender,
+            dropped: dropped.clone(),
+        };
+        let collector = crate::registry().with(shipper);
+        with_default(collector, || {
+            tracing::info!("fits in the queue");
+            tracing::info!("dropped");
+            tracing::info!("also dropped");
+        });
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+        drop(receiver);
+    }
+
+    /// Accepts one HTTP request and returns its request line and body.
+    fn accept_request(listener: &TcpListener) -> (String, String) {
+        let (stream, _) = listener.accept().expect("no request received");
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader
+            .read_line(&mut request_line)
+            .expect("failed to read request line");
+        let mut content_length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).expect("failed to read header");
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(length) = header
+                .to_ascii_lowercase()
+                .strip_prefix("content-length:")
+                .map(str::trim)
+            {
+                content_length = length.parse().expect("invalid content-length");
+            }
+        }
+        let mut body = vec![0; content_length];
+        io::Read::read_exact(&mut reader, &mut body).expect("failed to read body");
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .expect("failed to respond");
+        (
+            request_line.trim().to_owned(),
+            String::from_utf8(body).expect("body was not UTF-8"),
+        )
+    }
+
+    #[test]
+    fn ships_events_in_bulk() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let shipper = Subscriber::builder()
+            .with_endpoint(format!("http://{}", addr))
+            .with_index_prefix("bulk-test")
+            .finish()
+            .expect("failed to start shipper");
+        let collector = crate::registry().with(shipper);
+
+        with_default(collector, || {
+            tracing::info!(user = "alice", "logged in");
+            tracing::warn!(attempts = 3, "retrying");
+        });
+        // Dropping the collector disconnects the queue, flushing the batch.
+
+        let (template_line, template_body) = accept_request(&listener);
+        assert_eq!(template_line, "PUT /_index_template/bulk-test HTTP/1.1");
+        assert!(
+            template_body.contains("\"index_patterns\":[\"bulk-test-*\"]"),
+            "unexpected template body: {}",
+            template_body,
+        );
+
+        let (bulk_line, bulk_body) = accept_request(&listener);
+        assert_eq!(bulk_line, "POST /_bulk HTTP/1.1");
+        let lines: Vec<&str> = bulk_body.lines().collect();
+        assert_eq!(lines.len(), 4, "unexpected bulk body: {}", bulk_body);
+        assert!(lines[0].starts_with("{\"index\":{\"_index\":\"bulk-test-"));
+        assert!(lines[1].contains("\"level\":\"INFO\""));
+        assert!(lines[1].contains("\"message\":\"logged in\""));
+        assert!(lines[1].contains("\"fields\":{\"user\":\"alice\"}"));
+        assert!(lines[3].contains("\"level\":\"WARN\""));
+        assert!(lines[3].contains("\"fields\":{\"attempts\":3}"));
+    }
+}
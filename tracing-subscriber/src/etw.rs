@@ -0,0 +1,593 @@
+//! Emits spans and events as Windows [ETW] TraceLogging records.
+//!
+//! Event Tracing for Windows is the kernel-backed tracing infrastructure
+//! consumed by Windows performance tooling such as WPA and PerfView. This
+//! module provides a [`Subscriber`] that registers an ETW provider and
+//! emits spans as ETW *activities* — a start record when the span is
+//! created and a stop record when it closes, correlated by an activity ID
+//! the kernel generates — and events as [TraceLogging] records carrying
+//! their fields as typed ETW fields. Records from spans nest via related
+//! activity IDs, so WPA's activity view reconstructs the span tree.
+//!
+//! TraceLogging is the self-describing flavor of ETW: each record carries
+//! its own field metadata, so no manifest needs to be registered or
+//! compiled. As with the [`otlp`] and [`perfetto`] modules, the binary
+//! metadata blobs are encoded by hand here rather than through the
+//! TraceLogging SDK headers.
+//!
+//! Providers are identified by a GUID. By default the GUID is derived from
+//! the provider name using the same one-way hash as .NET's `EventSource`
+//! and `tracelog`'s `#name` syntax, so tools can subscribe by name alone:
+//!
+//! ```text
+//! wpr -start GeneralProfile -start "MyCompany.MyComponent"
+//! ```
+//!
+//! The [`Subscriber`] type is only available on Windows; the GUID
+//! derivation in [`Guid::from_name`] is available everywhere, for tooling
+//! that needs to compute provider GUIDs off-box.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use tracing_subscriber::{etw, prelude::*};
+//!
+//! let subscriber = etw::Subscriber::builder()
+//!     .with_provider_name("MyCompany.MyComponent")
+//!     .register()
+//!     .expect("failed to register the ETW provider");
+//! tracing_subscriber::registry().with(subscriber).init();
+//! ```
+//!
+//! [ETW]: https://learn.microsoft.com/windows/win32/etw/about-event-tracing
+//! [TraceLogging]: https://learn.microsoft.com/windows/win32/tracelogging/trace-logging-portal
+//! [`otlp`]: mod@crate::otlp
+//! [`perfetto`]: mod@crate::perfetto
+#![cfg_attr(not(windows), allow(dead_code))]
+// The module docs link to `Subscriber`, which only exists on Windows.
+#![cfg_attr(not(windows), allow(rustdoc::broken_intra_doc_links))]
+use crate::sha1::sha1;
+use std::fmt;
+use tracing_core::field;
+
+/// TraceLogging field in-types (`TlgIn_t`).
+const IN_ANSISTRING: u8 = 2;
+const IN_INT64: u8 = 9;
+const IN_UINT64: u8 = 10;
+const IN_DOUBLE: u8 = 12;
+const IN_BOOL32: u8 = 13;
+
+/// A provider GUID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Guid {
+    data1: u32,
+    data2: u16,
+    data3: u16,
+    data4: [u8; 8],
+}
+
+// === impl Guid ===
+
+impl Guid {
+    /// Returns a `Guid` from its grouped fields, as they appear in the
+    /// usual `{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}` rendering.
+    pub const fn new(data1: u32, data2: u16, data3: u16, data4: [u8; 8]) -> Self {
+        Self {
+            data1,
+            data2,
+            data3,
+            data4,
+        }
+    }
+
+    /// Derives a provider GUID from a provider name.
+    ///
+    /// This uses the same one-way hash as .NET's `EventSource` and
+    /// `tracelog`'s `#name` syntax (an RFC 4122 version-5 UUID of the
+    /// upper-cased UTF-16 name in the EventSource namespace), so tools
+    /// given only the provider name compute the same GUID.
+    pub fn from_name(name: &str) -> Self {
+        // The EventSource name-hashing namespace,
+        // {482C2DB2-C390-47C8-87F8-1A15BFC130FB}, in big-endian layout.
+        const NAMESPACE: [u8; 16] = [
+            0x48, 0x2c, 0x2d, 0xb2, 0xc3, 0x90, 0x47, 0xc8, 0x87, 0xf8, 0x1a, 0x15, 0xbf, 0xc1,
+            0x30, 0xfb,
+        ];
+        let mut input = NAMESPACE.to_vec();
+        for unit in name.to_uppercase().encode_utf16() {
+            input.extend_from_slice(&unit.to_be_bytes());
+        }
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&sha1(&input)[..16]);
+        // Stamp the UUID version, as EventSource does.
+        bytes[7] = (bytes[7] & 0x0f) | 0x50;
+        Self {
+            data1: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            data2: u16::from_le_bytes([bytes[4], bytes[5]]),
+            data3: u16::from_le_bytes([bytes[6], bytes[7]]),
+            data4: [
+                bytes[8], bytes[9], bytes[10], bytes[11], bytes[12], bytes[13], bytes[14],
+                bytes[15],
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+            self.data1,
+            self.data2,
+            self.data3,
+            self.data4[0],
+            self.data4[1],
+            self.data4[2],
+            self.data4[3],
+            self.data4[4],
+            self.data4[5],
+            self.data4[6],
+            self.data4[7],
+        )
+    }
+}
+
+/// Encodes the provider traits blob set via `EventSetInformation`: a
+/// little-endian total size followed by the NUL-terminated provider name.
+fn provider_traits(name: &str) -> Vec<u8> {
+    let size = (2 + name.len() + 1) as u16;
+    let mut traits = Vec::with_capacity(size as usize);
+    traits.extend_from_slice(&size.to_le_bytes());
+    traits.extend_from_slice(name.as_bytes());
+    traits.push(0);
+    traits
+}
+
+/// Encodes a TraceLogging event metadata blob: a little-endian total size,
+/// a tags byte, the NUL-terminated event name, and one NUL-terminated name
+/// plus in-type byte per field.
+fn event_metadata(name: &str, fields: &[(String, u8)]) -> Vec<u8> {
+    let mut metadata = vec![0u8; 2]; // size, patched below
+    metadata.push(0); // tags
+    metadata.extend_from_slice(name.as_bytes());
+    metadata.push(0);
+    for (field, in_type) in fields {
+        metadata.extend_from_slice(field.as_bytes());
+        metadata.push(0);
+        metadata.push(*in_type);
+    }
+    let size = metadata.len() as u16;
+    metadata[..2].copy_from_slice(&size.to_le_bytes());
+    metadata
+}
+
+/// Records fields as TraceLogging field descriptors plus a packed payload.
+#[derive(Default)]
+struct FieldVisitor {
+    fields: Vec<(String, u8)>,
+    payload: Vec<u8>,
+}
+
+impl field::Visit for FieldVisitor {
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.fields.push((field.name().to_owned(), IN_INT64));
+        self.payload.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.fields.push((field.name().to_owned(), IN_UINT64));
+        self.payload.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.fields.push((field.name().to_owned(), IN_DOUBLE));
+        self.payload.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.fields.push((field.name().to_owned(), IN_BOOL32));
+        self.payload
+            .extend_from_slice(&(value as u32).to_le_bytes());
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.fields.push((field.name().to_owned(), IN_ANSISTRING));
+        // ANSISTRING payloads are NUL-terminated.
+        self.payload
+            .extend_from_slice(value.replace('\0', " ").as_bytes());
+        self.payload.push(0);
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn fmt::Debug) {
+        let rendered = format!("{:?}", value);
+        self.fields.push((field.name().to_owned(), IN_ANSISTRING));
+        self.payload
+            .extend_from_slice(rendered.replace('\0', " ").as_bytes());
+        self.payload.push(0);
+    }
+}
+
+#[cfg(windows)]
+pub use self::windows::{Builder, Subscriber};
+
+#[cfg(windows)]
+mod windows {
+    use super::*;
+    use crate::{
+        registry::LookupSpan,
+        subscribe::{Context, Subscribe},
+    };
+    use std::io;
+    use tracing_core::{span, Collect, Event, Level, Metadata};
+
+    /// `EVENT_DESCRIPTOR`.
+    #[repr(C)]
+    struct EventDescriptor {
+        id: u16,
+        version: u8,
+        channel: u8,
+        level: u8,
+        opcode: u8,
+        task: u16,
+        keyword: u64,
+    }
+
+    /// `EVENT_DATA_DESCRIPTOR`; `reserved` distinguishes provider and
+    /// event metadata descriptors from payload ones.
+    #[repr(C)]
+    struct EventDataDescriptor {
+        ptr: u64,
+        size: u32,
+        reserved: u32,
+    }
+
+    /// The TraceLogging channel; marks records as self-describing.
+    const CHANNEL_TRACELOGGING: u8 = 11;
+    /// `EVENT_DATA_DESCRIPTOR` types for the two metadata descriptors.
+    const DESCRIPTOR_EVENT_METADATA: u32 = 1;
+    const DESCRIPTOR_PROVIDER_METADATA: u32 = 2;
+    /// `EventSetInformation` class for provider traits.
+    const EVENT_PROVIDER_SET_TRAITS: u32 = 2;
+    /// `EventActivityIdControl` code to create a new activity ID.
+    const EVENT_ACTIVITY_CTRL_CREATE_ID: u32 = 3;
+    /// Activity opcodes.
+    const OPCODE_INFO: u8 = 0;
+    const OPCODE_START: u8 = 1;
+    const OPCODE_STOP: u8 = 2;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn EventRegister(
+            provider_id: *const Guid,
+            callback: *const core::ffi::c_void,
+            context: *const core::ffi::c_void,
+            handle: *mut u64,
+        ) -> u32;
+        fn EventUnregister(handle: u64) -> u32;
+        fn EventSetInformation(
+            handle: u64,
+            class: u32,
+            information: *const core::ffi::c_void,
+            length: u32,
+        ) -> u32;
+        fn EventActivityIdControl(code: u32, activity_id: *mut Guid) -> u32;
+        fn EventWriteTransfer(
+            handle: u64,
+            descriptor: *const EventDescriptor,
+            activity_id: *const Guid,
+            related_activity_id: *const Guid,
+            count: u32,
+            data: *mut EventDataDescriptor,
+        ) -> u32;
+    }
+
+    /// Configures and registers an ETW [`Subscriber`].
+    #[derive(Debug)]
+    pub struct Builder {
+        name: String,
+        guid: Option<Guid>,
+    }
+
+    /// A [`Subscribe`] implementation that emits spans and events as ETW
+    /// TraceLogging records.
+    ///
+    /// See the [module-level documentation](super) for details.
+    #[derive(Debug)]
+    pub struct Subscriber {
+        handle: u64,
+        traits: Vec<u8>,
+    }
+
+    /// A span's activity ID and recorded fields, stored in its extensions.
+    struct SpanActivity {
+        id: Guid,
+        fields: FieldVisitor,
+    }
+
+    // === impl Builder ===
+
+    impl Builder {
+        /// Sets the provider name under which records are emitted.
+        ///
+        /// Defaults to `"tracing"`.
+        pub fn with_provider_name(self, name: impl Into<String>) -> Self {
+            Self {
+                name: name.into(),
+                ..self
+            }
+        }
+
+        /// Sets an explicit provider GUID.
+        ///
+        /// By default the GUID is derived from the provider name with
+        /// [`Guid::from_name`].
+        pub fn with_provider_guid(self, guid: Guid) -> Self {
+            Self {
+                guid: Some(guid),
+                ..self
+            }
+        }
+
+        /// Registers the provider, returning the `Subscriber`.
+        pub fn register(self) -> io::Result<Subscriber> {
+            let guid = self.guid.unwrap_or_else(|| Guid::from_name(&self.name));
+            let mut handle = 0u64;
+            // Safety: `guid` and `handle` are live for the duration of the
+            // call; no callback is registered.
+            let rc =
+                unsafe { EventRegister(&guid, std::ptr::null(), std::ptr::null(), &mut handle) };
+            if rc != 0 {
+                return Err(io::Error::from_raw_os_error(rc as i32));
+            }
+            let traits = provider_traits(&self.name);
+            // Safety: the traits blob outlives the registration; it is
+            // stored in the returned subscriber.
+            unsafe {
+                EventSetInformation(
+                    handle,
+                    EVENT_PROVIDER_SET_TRAITS,
+                    traits.as_ptr().cast(),
+                    traits.len() as u32,
+                );
+            }
+            Ok(Subscriber { handle, traits })
+        }
+    }
+
+    // === impl Subscriber ===
+
+    impl Subscriber {
+        /// Returns a builder for configuring the provider.
+        pub fn builder() -> Builder {
+            Builder {
+                name: "tracing".to_owned(),
+                guid: None,
+            }
+        }
+
+        /// Writes one TraceLogging record.
+        fn emit(
+            &self,
+            metadata: &Metadata<'_>,
+            name: &str,
+            opcode: u8,
+            activity: Option<&Guid>,
+            related: Option<&Guid>,
+            fields: &FieldVisitor,
+        ) {
+            let event_metadata = event_metadata(name, &fields.fields);
+            let descriptor = EventDescriptor {
+                id: 0,
+                version: 0,
+                channel: CHANNEL_TRACELOGGING,
+                level: match *metadata.level() {
+                    Level::ERROR => 2,
+                    Level::WARN => 3,
+                    Level::INFO => 4,
+                    _ => 5,
+                },
+                opcode,
+                task: 0,
+                keyword: 0,
+            };
+            let mut data = [
+                EventDataDescriptor {
+                    ptr: self.traits.as_ptr() as u64,
+                    size: self.traits.len() as u32,
+                    reserved: DESCRIPTOR_PROVIDER_METADATA,
+                },
+                EventDataDescriptor {
+                    ptr: event_metadata.as_ptr() as u64,
+                    size: event_metadata.len() as u32,
+                    reserved: DESCRIPTOR_EVENT_METADATA,
+                },
+                EventDataDescriptor {
+                    ptr: fields.payload.as_ptr() as u64,
+                    size: fields.payload.len() as u32,
+                    reserved: 0,
+                },
+            ];
+            let activity = activity
+                .map(|id| id as *const Guid)
+                .unwrap_or(std::ptr::null());
+            let related = related
+                .map(|id| id as *const Guid)
+                .unwrap_or(std::ptr::null());
+            // Safety: every descriptor points to a live buffer;
+            // `EventWriteTransfer` copies them before returning.
+            unsafe {
+                EventWriteTransfer(
+                    self.handle,
+                    &descriptor,
+                    activity,
+                    related,
+                    data.len() as u32,
+                    data.as_mut_ptr(),
+                );
+            }
+        }
+    }
+
+    impl Drop for Subscriber {
+        fn drop(&mut self) {
+            // Safety: the handle came from a successful `EventRegister`.
+            unsafe {
+                EventUnregister(self.handle);
+            }
+        }
+    }
+
+    impl<C> Subscribe<C> for Subscriber
+    where
+        C: Collect + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+            let span = ctx.span(id).expect("Span not found, this is a bug");
+            let mut activity_id = Guid::new(0, 0, 0, [0; 8]);
+            // Safety: `activity_id` is live for the duration of the call.
+            unsafe {
+                EventActivityIdControl(EVENT_ACTIVITY_CTRL_CREATE_ID, &mut activity_id);
+            }
+            let mut fields = FieldVisitor::default();
+            attrs.record(&mut fields);
+            let related = span.parent().and_then(|parent| {
+                parent
+                    .extensions()
+                    .get::<SpanActivity>()
+                    .map(|activity| activity.id)
+            });
+            self.emit(
+                span.metadata(),
+                span.name(),
+                OPCODE_START,
+                Some(&activity_id),
+                related.as_ref(),
+                &fields,
+            );
+            span.extensions_mut().insert(SpanActivity {
+                id: activity_id,
+                fields,
+            });
+        }
+
+        fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+            let span = ctx.span(id).expect("Span not found, this is a bug");
+            let mut extensions = span.extensions_mut();
+            if let Some(activity) = extensions.get_mut::<SpanActivity>() {
+                values.record(&mut activity.fields);
+            }
+        }
+
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+            let mut fields = FieldVisitor::default();
+            event.record(&mut fields);
+            let activity = ctx.event_span(event).and_then(|span| {
+                span.extensions()
+                    .get::<SpanActivity>()
+                    .map(|activity| activity.id)
+            });
+            self.emit(
+                event.metadata(),
+                event.metadata().name(),
+                OPCODE_INFO,
+                activity.as_ref(),
+                None,
+                &fields,
+            );
+        }
+
+        fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+            let span = ctx.span(&id).expect("Span not found, this is a bug");
+            let extensions = span.extensions();
+            if let Some(activity) = extensions.get::<SpanActivity>() {
+                self.emit(
+                    span.metadata(),
+                    span.name(),
+                    OPCODE_STOP,
+                    Some(&activity.id),
+                    None,
+                    &FieldVisitor::default(),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn provider_guids_match_the_eventsource_derivation() {
+        // The worked example from the EventSource documentation.
+        let guid = Guid::from_name("MyCompany.MyComponent");
+        assert_eq!(guid.to_string(), "ce5fa4ea-ab00-5402-8b76-9f76ac858fb5");
+        // The derivation is case-insensitive.
+        assert_eq!(Guid::from_name("mycompany.mycomponent"), guid);
+    }
+
+    #[test]
+    fn metadata_blobs_are_laid_out_correctly() {
+        let traits = provider_traits("prov");
+        assert_eq!(traits, [7, 0, b'p', b'r', b'o', b'v', 0]);
+
+        let metadata = event_metadata("ev", &[("answer".to_owned(), IN_INT64)]);
+        let expected: &[u8] = &[
+            14, 0, // total size
+            0, // tags
+            b'e', b'v', 0, // event name
+            b'a', b'n', b's', b'w', b'e', b'r', 0, IN_INT64,
+        ];
+        assert_eq!(metadata, expected);
+    }
+
+    #[test]
+    fn field_payloads_are_packed_and_typed() {
+        use tracing_core::field::Visit;
+
+        let field = make_field("a");
+        let mut visitor = FieldVisitor::default();
+        visitor.record_u64(&field, 42);
+        visitor.record_bool(&field, true);
+        visitor.record_str(&field, "hi");
+        assert_eq!(
+            visitor.fields,
+            [
+                ("a".to_owned(), IN_UINT64),
+                ("a".to_owned(), IN_BOOL32),
+                ("a".to_owned(), IN_ANSISTRING),
+            ]
+        );
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&42u64.to_le_bytes());
+        expected.extend_from_slice(&1u32.to_le_bytes());
+        expected.extend_from_slice(b"hi\0");
+        assert_eq!(visitor.payload, expected);
+    }
+
+    /// Returns a real `Field` named `name` from a probe callsite.
+    fn make_field(name: &str) -> field::Field {
+        use tracing_core::Callsite;
+        struct TestCallsite;
+        static CALLSITE: TestCallsite = TestCallsite;
+        static METADATA: tracing_core::Metadata<'static> = tracing_core::metadata! {
+            name: "etw_test",
+            target: "etw_test",
+            level: tracing_core::Level::INFO,
+            fields: &["a"],
+            callsite: &CALLSITE,
+            kind: tracing_core::metadata::Kind::EVENT,
+        };
+        impl Callsite for TestCallsite {
+            fn set_interest(&self, _: tracing_core::collect::Interest) {}
+            fn metadata(&self) -> &tracing_core::Metadata<'_> {
+                &METADATA
+            }
+        }
+        METADATA
+            .fields()
+            .field(name)
+            .expect("field should exist in the probe callsite")
+    }
+}
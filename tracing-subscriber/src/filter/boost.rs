@@ -0,0 +1,217 @@
+//! A [`Filter`] that temporarily raises verbosity for a target, reverting
+//! automatically after a deadline.
+//!
+//! See the [`BoostFilter`] documentation for details.
+//!
+//! [`Filter`]: crate::subscribe::Filter
+use crate::{
+    filter::LevelFilter,
+    subscribe::{Context, Filter, Subscribe},
+};
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+use tracing_core::{callsite, Collect, Interest, Metadata};
+
+/// A [`Filter`] that can temporarily raise verbosity for a target.
+///
+/// Turning debug logging on to investigate an incident is easy; remembering
+/// to turn it back off afterwards is not. A `BoostFilter` wraps a base
+/// filter and accepts *boosts* through a [`BoostHandle`]: each boost enables
+/// spans and events up to a given level for a given target prefix, and
+/// expires on its own after a duration. Anything not enabled by an active
+/// boost falls through to the base filter, so once the last boost expires,
+/// filtering reverts to exactly what it was before.
+///
+/// Boosts expire lazily: no background timer is involved, and an expired
+/// boost simply stops matching the next time the filter is consulted.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tracing_subscriber::{filter::{BoostFilter, LevelFilter}, prelude::*};
+///
+/// let (filter, handle) = BoostFilter::new(LevelFilter::INFO);
+///
+/// tracing_subscriber::registry()
+///     .with(tracing_subscriber::fmt::subscriber().with_filter(filter))
+///     .init();
+///
+/// // Later, while investigating: trace the problem area for a minute.
+/// handle.boost("my_crate::broken_module", LevelFilter::TRACE, Duration::from_secs(60));
+/// ```
+///
+/// # Notes
+///
+/// Adding a boost rebuilds the callsite interest cache, like
+/// [`reload::Handle::reload`] does. Expiry does not: callsites that only an
+/// expired boost would have enabled remain registered with
+/// [`Interest::sometimes`], so they are rejected per event rather than
+/// skipped outright, until the next rebuild.
+///
+/// [`Filter`]: crate::subscribe::Filter
+/// [`reload::Handle::reload`]: crate::reload::Handle::reload
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug)]
+pub struct BoostFilter<F> {
+    inner: F,
+    shared: Arc<Shared>,
+}
+
+/// Adds temporary verbosity boosts to a [`BoostFilter`].
+///
+/// The handle may be cloned and boosts may be added from any thread.
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug, Clone)]
+pub struct BoostHandle {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    boosts: RwLock<Vec<Boost>>,
+}
+
+/// A single active verbosity boost.
+#[derive(Debug)]
+struct Boost {
+    target: String,
+    level: LevelFilter,
+    expires_at: Instant,
+}
+
+// === impl BoostFilter ===
+
+impl<F> BoostFilter<F> {
+    /// Wraps `inner`, returning the new filter and a [`BoostHandle`] that
+    /// adds temporary boosts to it.
+    pub fn new(inner: F) -> (Self, BoostHandle) {
+        let shared = Arc::new(Shared {
+            boosts: RwLock::new(Vec::new()),
+        });
+        let handle = BoostHandle {
+            shared: shared.clone(),
+        };
+        (Self { inner, shared }, handle)
+    }
+
+    /// Returns whether an active boost enables `metadata`.
+    fn boosted(&self, metadata: &Metadata<'_>) -> bool {
+        self.shared.active(|boost| {
+            metadata.level() <= &boost.level && metadata.target().starts_with(&boost.target[..])
+        })
+    }
+
+    /// Returns the level hint accounting for any active boosts.
+    fn hint(&self, inner: Option<LevelFilter>) -> Option<LevelFilter> {
+        let inner = inner?;
+        let mut hint = inner;
+        self.shared.active(|boost| {
+            hint = core::cmp::max(hint, boost.level);
+            false
+        });
+        Some(hint)
+    }
+
+    /// Returns the interest for `metadata`, given the inner filter's
+    /// interest.
+    ///
+    /// Whether a boost applies can change at any time, so anything the inner
+    /// filter does not always enable must be re-checked per span or event.
+    fn interest(inner: Interest) -> Interest {
+        if inner.is_always() {
+            inner
+        } else {
+            Interest::sometimes()
+        }
+    }
+}
+
+// === impl BoostHandle ===
+
+impl BoostHandle {
+    /// Enables spans and events up to `level` for targets beginning with
+    /// `target`, for the next `duration`.
+    ///
+    /// Boosting a target that already has an active boost replaces it. The
+    /// boost expires on its own once `duration` has elapsed; nothing needs
+    /// to be called to revert it.
+    pub fn boost(
+        &self,
+        target: impl Into<String>,
+        level: impl Into<LevelFilter>,
+        duration: Duration,
+    ) {
+        let target = target.into();
+        let boost = Boost {
+            level: level.into(),
+            expires_at: Instant::now() + duration,
+            target,
+        };
+        {
+            let mut boosts = match self.shared.boosts.write() {
+                Ok(boosts) => boosts,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let now = Instant::now();
+            boosts.retain(|existing| existing.expires_at > now && existing.target != boost.target);
+            boosts.push(boost);
+        }
+
+        // Newly boosted callsites may have been cached as disabled; rebuild
+        // the interest cache so that they are re-evaluated.
+        callsite::rebuild_interest_cache();
+    }
+}
+
+// === impl Shared ===
+
+impl Shared {
+    /// Returns whether `f` returns `true` for any unexpired boost.
+    fn active(&self, f: impl FnMut(&Boost) -> bool) -> bool {
+        let boosts = match self.boosts.read() {
+            Ok(boosts) => boosts,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let now = Instant::now();
+        boosts.iter().filter(|boost| boost.expires_at > now).any(f)
+    }
+}
+
+impl<C, F> Filter<C> for BoostFilter<F>
+where
+    C: Collect,
+    F: Filter<C>,
+{
+    fn enabled(&self, metadata: &Metadata<'_>, cx: &Context<'_, C>) -> bool {
+        self.boosted(metadata) || self.inner.enabled(metadata, cx)
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        Self::interest(self.inner.callsite_enabled(metadata))
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        self.hint(self.inner.max_level_hint())
+    }
+}
+
+impl<C, F> Subscribe<C> for BoostFilter<F>
+where
+    C: Collect,
+    F: Subscribe<C>,
+{
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, C>) -> bool {
+        self.boosted(metadata) || self.inner.enabled(metadata, ctx)
+    }
+
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        Self::interest(self.inner.register_callsite(metadata))
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        self.hint(self.inner.max_level_hint())
+    }
+}
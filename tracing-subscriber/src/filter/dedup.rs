@@ -0,0 +1,250 @@
+//! A [`Filter`] that collapses identical consecutive events.
+//!
+//! See the [`DedupFilter`] documentation for details.
+//!
+//! [`Filter`]: crate::subscribe::Filter
+use crate::subscribe::{Context, Filter, Subscribe};
+use std::{
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tracing_core::{
+    callsite::{self, Callsite},
+    field::{Field, FieldSet, Visit},
+    identify_callsite, Collect, Event, Interest, Kind, Level, Metadata,
+};
+
+/// A [`Filter`] that collapses identical consecutive events into one.
+///
+/// A retry loop that logs the same warning on every attempt records many
+/// events that differ only in when they happened. A `DedupFilter` compares
+/// each event to the previous one, and suppresses it if both came from the
+/// same callsite and recorded the same field values, as long as no more
+/// than `window` has passed since the last occurrence. When a different
+/// event arrives (or the window expires), the suppressed run is reported
+/// with a single summary event — much like syslog's "last message repeated
+/// N times".
+///
+/// Summary events are emitted at the `WARN` level, with the target
+/// `tracing_subscriber::filter::dedup` and a `repeated` field recording
+/// how many duplicates were suppressed. Summary events are never
+/// themselves deduplicated.
+///
+/// Spans are not deduplicated; only events are compared.
+///
+/// Like [`RateLimitFilter`], a `DedupFilter` may be used as a
+/// [`Subscribe`] layered over an entire subscriber stack, or as a
+/// per-subscriber [`Filter`] (with [`with_filter`][plf]). Summary events
+/// are dispatched through [`Context::event`], so they are recorded by the
+/// subscribers *below* the filter in the stack; when deduplicating a
+/// single subscriber, prefer the [`Subscribe`] form layered directly above
+/// it.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tracing_subscriber::{filter::DedupFilter, prelude::*};
+///
+/// tracing_subscriber::registry()
+///     .with(tracing_subscriber::fmt::subscriber())
+///     // Collapse runs of identical events less than a second apart.
+///     .with(DedupFilter::new(Duration::from_secs(1)))
+///     .init();
+/// ```
+///
+/// [`RateLimitFilter`]: crate::filter::RateLimitFilter
+/// [`Filter`]: crate::subscribe::Filter
+/// [`Subscribe`]: crate::subscribe::Subscribe
+/// [`Context::event`]: crate::subscribe::Context::event
+/// [plf]: crate::subscribe::Subscribe::with_filter
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug)]
+pub struct DedupFilter {
+    window: Duration,
+    last: Mutex<Option<LastEvent>>,
+}
+
+/// The identity of the most recently enabled event, and how many exact
+/// duplicates of it have been suppressed since.
+#[derive(Debug)]
+struct LastEvent {
+    callsite: callsite::Identifier,
+    values: Vec<(&'static str, String)>,
+    last_seen: Instant,
+    repeated: u64,
+}
+
+static SUMMARY_FIELDS: &[&str] = &["message", "repeated"];
+struct SummaryCallsite;
+static SUMMARY_CALLSITE: SummaryCallsite = SummaryCallsite;
+static SUMMARY_META: Metadata<'static> = Metadata::new(
+    "duplicate event summary",
+    "tracing_subscriber::filter::dedup",
+    Level::WARN,
+    None,
+    None,
+    None,
+    FieldSet::new(SUMMARY_FIELDS, identify_callsite!(&SUMMARY_CALLSITE)),
+    Kind::EVENT,
+);
+
+impl Callsite for SummaryCallsite {
+    fn set_interest(&self, _: Interest) {}
+
+    fn metadata(&self) -> &'static Metadata<'static> {
+        &SUMMARY_META
+    }
+}
+
+// === impl DedupFilter ===
+
+impl DedupFilter {
+    /// Returns a new `DedupFilter` that suppresses an event if it is
+    /// identical to the previous event and occurred within `window` of it.
+    ///
+    /// Two events are identical if they come from the same callsite and
+    /// record the same values for all of their fields. Each suppressed
+    /// duplicate extends the window, so an unbroken run of duplicates is
+    /// collapsed regardless of its total length.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Decides whether `event` is enabled, suppressing it if it duplicates
+    /// the previous event and emitting a summary through `cx` if it ends a
+    /// run of suppressed duplicates.
+    fn dedup<C: Collect>(&self, event: &Event<'_>, cx: &Context<'_, C>) -> bool {
+        let meta = event.metadata();
+        // The filter's own summary events are exempt, so that they can
+        // never be suppressed (or recurse).
+        if core::ptr::eq::<Metadata<'_>>(meta, &SUMMARY_META) {
+            return true;
+        }
+
+        let mut visitor = ValuesVisitor(Vec::new());
+        event.record(&mut visitor);
+        let values = visitor.0;
+        let callsite = meta.callsite();
+
+        let now = Instant::now();
+        let repeated = {
+            let mut last = match self.last.lock() {
+                Ok(last) => last,
+                // A panic while the lock was held can't have left the state
+                // inconsistent, so keep going with it as it is.
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            if let Some(last) = last.as_mut() {
+                if last.callsite == callsite
+                    && last.values == values
+                    && now.saturating_duration_since(last.last_seen) <= self.window
+                {
+                    last.repeated += 1;
+                    last.last_seen = now;
+                    return false;
+                }
+            }
+            let repeated = last.as_ref().map(|last| last.repeated).unwrap_or(0);
+            *last = Some(LastEvent {
+                callsite,
+                values,
+                last_seen: now,
+                repeated: 0,
+            });
+            repeated
+        };
+
+        if repeated > 0 {
+            Self::emit_summary(cx, repeated);
+        }
+        true
+    }
+
+    /// Returns the interest for `meta`, shared by the `Subscribe` and
+    /// `Filter` implementations.
+    fn interest(meta: &'static Metadata<'static>) -> Interest {
+        if meta.is_event() {
+            // Whether an event is enabled depends on the event before it,
+            // so the decision can never be cached.
+            Interest::sometimes()
+        } else {
+            Interest::always()
+        }
+    }
+
+    /// Emits the summary event recording that the last enabled event was
+    /// repeated `repeated` times.
+    ///
+    /// The summary is dispatched through the [`Context`] rather than the
+    /// default dispatcher: this is called while the triggering event is being
+    /// dispatched, and the dispatcher guards against re-entrancy by handing
+    /// out a no-op `Dispatch` for the duration.
+    fn emit_summary<C: Collect>(cx: &Context<'_, C>, repeated: u64) {
+        if !cx.enabled(&SUMMARY_META) {
+            return;
+        }
+        let fields = SUMMARY_META.fields();
+        let message = fields.field("message").expect("message field must exist");
+        let count = fields.field("repeated").expect("repeated field must exist");
+        cx.event(&Event::new(
+            &SUMMARY_META,
+            &fields.value_set(&[
+                (
+                    &message,
+                    Some(&format_args!("last event repeated")
+                        as &dyn tracing_core::field::Value),
+                ),
+                (&count, Some(&repeated as &dyn tracing_core::field::Value)),
+            ]),
+        ));
+    }
+}
+
+impl<C: Collect> Filter<C> for DedupFilter {
+    fn enabled(&self, _meta: &Metadata<'_>, _: &Context<'_, C>) -> bool {
+        // Only events are compared, and they are compared in
+        // `event_enabled`, once per event that would otherwise be recorded.
+        true
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, cx: &Context<'_, C>) -> bool {
+        self.dedup(event, cx)
+    }
+
+    fn callsite_enabled(&self, meta: &'static Metadata<'static>) -> Interest {
+        Self::interest(meta)
+    }
+}
+
+impl<C: Collect> Subscribe<C> for DedupFilter {
+    #[inline]
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        Self::interest(metadata)
+    }
+
+    #[inline]
+    fn event_enabled(&self, event: &Event<'_>, cx: Context<'_, C>) -> bool {
+        self.dedup(event, &cx)
+    }
+}
+
+// === impl ValuesVisitor ===
+
+/// Records every field's value as a string, for comparison with the
+/// previous event.
+struct ValuesVisitor(Vec<(&'static str, String)>);
+
+impl Visit for ValuesVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name(), value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push((field.name(), format!("{:?}", value)));
+    }
+}
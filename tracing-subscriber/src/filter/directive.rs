@@ -1,6 +1,6 @@
 use crate::filter::level::{self, LevelFilter};
 use alloc::{string::String, vec::Vec};
-use core::{cmp::Ordering, fmt, iter::FromIterator, slice, str::FromStr};
+use core::{cmp::Ordering, fmt, iter::FromIterator, mem, slice, str::FromStr};
 use tracing_core::{Level, Metadata};
 /// Indicates that a string could not be parsed as a filtering directive.
 #[derive(Debug)]
@@ -14,10 +14,45 @@ pub struct ParseError {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub(crate) struct StaticDirective {
     pub(in crate::filter) target: Option<String>,
+    pub(in crate::filter) location: Option<LocationGlob>,
     pub(in crate::filter) field_names: Vec<String>,
     pub(in crate::filter) level: LevelFilter,
 }
 
+/// A glob pattern matching a callsite's source location (its file path or
+/// module path), rather than its target.
+///
+/// Location globs are written with a `file:` or `module:` prefix in place of
+/// a directive's target (e.g. `file:src/net/**=trace` or
+/// `module:myapp::db::*=debug`), and are compiled when the directive is
+/// parsed, so matching a callsite does not re-parse the pattern.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct LocationGlob {
+    kind: LocationKind,
+    pattern: String,
+    segments: Vec<GlobSegment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum LocationKind {
+    /// Matches the callsite's file path (`file:` directives).
+    File,
+    /// Matches the callsite's module path (`module:` directives).
+    Module,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum GlobSegment {
+    /// Matches a literal string exactly.
+    Literal(String),
+    /// `?`: matches any single character other than the path separator.
+    AnyChar,
+    /// `*`: matches any sequence of characters other than the path separator.
+    Star,
+    /// `**`: matches any sequence of characters, including the separator.
+    GlobStar,
+}
+
 #[cfg(feature = "smallvec")]
 pub(crate) type FilterVec<T> = smallvec::SmallVec<[T; 8]>;
 #[cfg(not(feature = "smallvec"))]
@@ -26,12 +61,59 @@ pub(crate) type FilterVec<T> = Vec<T>;
 #[derive(Debug, PartialEq, Clone)]
 pub(in crate::filter) struct DirectiveSet<T> {
     directives: FilterVec<T>,
+    /// A pre-compiled index over the directives' targets, updated as
+    /// directives are added.
+    index: TargetTrie,
     pub(in crate::filter) max_level: LevelFilter,
 }
 
+/// A prefix trie over directive targets.
+///
+/// When every directive in a [`DirectiveSet`] filters on its target alone,
+/// the set's directives are pre-compiled into a trie when they are added, so
+/// that the steady-state cost of matching a callsite is proportional to the
+/// length of its target rather than to the number of directives. A single
+/// walk of the trie finds the most specific (longest) directive target
+/// prefixing the callsite's target, which is the same directive a linear
+/// scan of the set (ordered most specific first) would find.
+///
+/// If any directive in the set matches on more than its target (such as a
+/// location glob or field names), the trie is unusable and the set falls
+/// back to a linear scan.
+#[derive(Debug, Clone)]
+pub(in crate::filter) struct TargetTrie {
+    nodes: Vec<TrieNode>,
+    usable: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+struct TrieNode {
+    children: Vec<(u8, usize)>,
+    /// The level of a directive whose target ends at this node, if any.
+    level: Option<LevelFilter>,
+}
+
+/// How a directive may be indexed by a [`TargetTrie`].
+pub(in crate::filter) enum IndexEntry<'a> {
+    /// The directive matches on its target (or on all targets) alone, and
+    /// enables the given level.
+    Target(Option<&'a str>, LevelFilter),
+    /// The directive matches on more than its target, and must be checked
+    /// with a linear scan.
+    Complex,
+}
+
 pub(in crate::filter) trait Match {
     fn cares_about(&self, meta: &Metadata<'_>) -> bool;
     fn level(&self) -> &LevelFilter;
+
+    /// Returns how this directive may be indexed by a [`TargetTrie`].
+    ///
+    /// By default, directives are not indexed; sets of such directives are
+    /// always matched with a linear scan.
+    fn index_entry(&self) -> IndexEntry<'_> {
+        IndexEntry::Complex
+    }
 }
 
 #[derive(Debug)]
@@ -60,6 +142,7 @@ impl<T: Ord> Default for DirectiveSet<T> {
     fn default() -> Self {
         Self {
             directives: FilterVec::new(),
+            index: TargetTrie::default(),
             max_level: LevelFilter::OFF,
         }
     }
@@ -89,8 +172,16 @@ impl<T: Match + Ord> DirectiveSet<T> {
         // ensures that, when finding a directive to match a span or event, we
         // search the directive set in most specific first order.
         match self.directives.binary_search(&directive) {
-            Ok(i) => self.directives[i] = directive,
-            Err(i) => self.directives.insert(i, directive),
+            Ok(i) => {
+                // Replacing a directive with an equal sort key overwrites its
+                // terminal node in the trie, so the index stays consistent.
+                self.index.insert(directive.index_entry());
+                self.directives[i] = directive;
+            }
+            Err(i) => {
+                self.index.insert(directive.index_entry());
+                self.directives.insert(i, directive);
+            }
         }
     }
 
@@ -129,11 +220,119 @@ impl<T> IntoIterator for DirectiveSet<T> {
     }
 }
 
+// === impl TargetTrie ===
+
+impl PartialEq for TargetTrie {
+    fn eq(&self, other: &Self) -> bool {
+        // Node indices depend on the order directives were added, so the
+        // tries are compared structurally rather than node-by-node.
+        if self.usable != other.usable {
+            return false;
+        }
+        if !self.usable {
+            return true;
+        }
+        self.node_eq(0, other, 0)
+    }
+}
+
+impl Default for TargetTrie {
+    fn default() -> Self {
+        Self {
+            // node 0 is the root, and holds the level of a directive with no
+            // target (which matches every callsite).
+            nodes: alloc::vec![TrieNode::default()],
+            usable: true,
+        }
+    }
+}
+
+impl TargetTrie {
+    /// Adds a directive to the index.
+    ///
+    /// If the directive cannot be indexed by its target alone, the entire
+    /// index becomes unusable, and the set falls back to linear scans.
+    fn insert(&mut self, entry: IndexEntry<'_>) {
+        if !self.usable {
+            return;
+        }
+        let (target, level) = match entry {
+            IndexEntry::Target(target, level) => (target, level),
+            IndexEntry::Complex => {
+                self.usable = false;
+                self.nodes.clear();
+                return;
+            }
+        };
+        let mut node = 0;
+        for &byte in target.unwrap_or("").as_bytes() {
+            node = match self.nodes[node].children.iter().find(|(b, _)| *b == byte) {
+                Some(&(_, child)) => child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(TrieNode::default());
+                    self.nodes[node].children.push((byte, child));
+                    child
+                }
+            };
+        }
+        self.nodes[node].level = Some(level);
+    }
+
+    /// Returns whether a callsite with the given target and level is enabled,
+    /// or `None` if the index is unusable and the set must be scanned
+    /// linearly.
+    ///
+    /// The deepest node with a level found while walking `target`'s bytes
+    /// corresponds to the longest — most specific — directive target
+    /// prefixing it, so it takes precedence over any shallower match.
+    fn enabled(&self, target: &str, level: &Level) -> Option<bool> {
+        if !self.usable {
+            return None;
+        }
+        let mut best = self.nodes[0].level;
+        let mut node = 0;
+        for &byte in target.as_bytes() {
+            node = match self.nodes[node].children.iter().find(|(b, _)| *b == byte) {
+                Some(&(_, child)) => child,
+                None => break,
+            };
+            if let Some(found) = self.nodes[node].level {
+                best = Some(found);
+            }
+        }
+        Some(match best {
+            Some(best) => best >= *level,
+            None => false,
+        })
+    }
+
+    /// Returns whether the subtries rooted at `self.nodes[a]` and
+    /// `other.nodes[b]` match the same targets to the same levels.
+    fn node_eq(&self, a: usize, other: &Self, b: usize) -> bool {
+        let (node_a, node_b) = (&self.nodes[a], &other.nodes[b]);
+        if node_a.level != node_b.level || node_a.children.len() != node_b.children.len() {
+            return false;
+        }
+        node_a.children.iter().all(|&(byte, child_a)| {
+            node_b
+                .children
+                .iter()
+                .find(|(other_byte, _)| *other_byte == byte)
+                .map(|&(_, child_b)| self.node_eq(child_a, other, child_b))
+                .unwrap_or(false)
+        })
+    }
+}
+
 // === impl Statics ===
 
 impl DirectiveSet<StaticDirective> {
     pub(crate) fn enabled(&self, meta: &Metadata<'_>) -> bool {
         let level = meta.level();
+        if let Some(enabled) = self.index.enabled(meta.target(), level) {
+            return enabled;
+        }
         match self.directives_for(meta).next() {
             Some(d) => d.level >= *level,
             None => false,
@@ -142,6 +341,9 @@ impl DirectiveSet<StaticDirective> {
 
     /// Same as `enabled` above, but skips `Directive`'s with fields.
     pub(crate) fn target_enabled(&self, target: &str, level: &Level) -> bool {
+        if let Some(enabled) = self.index.enabled(target, level) {
+            return enabled;
+        }
         match self.directives_for_target(target).next() {
             Some(d) => d.level >= *level,
             None => false,
@@ -157,16 +359,161 @@ impl DirectiveSet<StaticDirective> {
     }
 }
 
+// === impl LocationGlob ===
+
+impl LocationGlob {
+    /// If `target` begins with `file:` or `module:`, compiles the remainder as
+    /// a glob pattern matching the callsite's source location; otherwise,
+    /// returns `None` so the string is treated as an ordinary target filter.
+    pub(in crate::filter) fn from_target(target: &str) -> Option<Result<Self, ParseError>> {
+        let (kind, pattern) = if let Some(pattern) = target.strip_prefix("file:") {
+            (LocationKind::File, pattern)
+        } else if let Some(pattern) = target.strip_prefix("module:") {
+            (LocationKind::Module, pattern)
+        } else {
+            return None;
+        };
+        if pattern.is_empty() {
+            return Some(Err(ParseError::msg(
+                "expected a glob pattern following `file:` or `module:`",
+            )));
+        }
+        Some(Ok(Self::compile(kind, pattern)))
+    }
+
+    fn compile(kind: LocationKind, pattern: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            let segment = match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    GlobSegment::GlobStar
+                }
+                '*' => GlobSegment::Star,
+                '?' => GlobSegment::AnyChar,
+                c => {
+                    literal.push(c);
+                    continue;
+                }
+            };
+            if !literal.is_empty() {
+                segments.push(GlobSegment::Literal(mem::take(&mut literal)));
+            }
+            segments.push(segment);
+        }
+        if !literal.is_empty() {
+            segments.push(GlobSegment::Literal(literal));
+        }
+        Self {
+            kind,
+            pattern: String::from(pattern),
+            segments,
+        }
+    }
+
+    pub(in crate::filter) fn matches_metadata(&self, meta: &Metadata<'_>) -> bool {
+        let location = match self.kind {
+            LocationKind::File => meta.file(),
+            LocationKind::Module => meta.module_path(),
+        };
+        match location {
+            Some(location) => glob_matches(&self.segments, location, self.kind),
+            // A callsite without a recorded location cannot be matched by a
+            // location glob.
+            None => false,
+        }
+    }
+
+    pub(in crate::filter) fn pattern_len(&self) -> usize {
+        self.pattern.len()
+    }
+}
+
+impl fmt::Display for LocationGlob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = match self.kind {
+            LocationKind::File => "file",
+            LocationKind::Module => "module",
+        };
+        write!(f, "{}:{}", prefix, self.pattern)
+    }
+}
+
+impl LocationKind {
+    fn is_separator(self, c: char) -> bool {
+        match self {
+            // `file!()` uses the host platform's path separator, so accept
+            // both Unix- and Windows-style paths.
+            LocationKind::File => c == '/' || c == '\\',
+            LocationKind::Module => c == ':',
+        }
+    }
+}
+
+fn glob_matches(segments: &[GlobSegment], input: &str, kind: LocationKind) -> bool {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return input.is_empty(),
+    };
+    match segment {
+        GlobSegment::Literal(literal) => match input.strip_prefix(literal.as_str()) {
+            Some(input) => glob_matches(rest, input, kind),
+            None => false,
+        },
+        GlobSegment::AnyChar => {
+            let mut chars = input.chars();
+            match chars.next() {
+                Some(c) if !kind.is_separator(c) => glob_matches(rest, chars.as_str(), kind),
+                _ => false,
+            }
+        }
+        GlobSegment::Star | GlobSegment::GlobStar => {
+            // Match the shortest possible prefix first, consuming one
+            // character at a time until the remaining segments match (or the
+            // glob can consume no further characters).
+            let mut input = input;
+            loop {
+                if glob_matches(rest, input, kind) {
+                    return true;
+                }
+                let mut chars = input.chars();
+                match chars.next() {
+                    Some(c) if *segment == GlobSegment::GlobStar || !kind.is_separator(c) => {
+                        input = chars.as_str();
+                    }
+                    _ => return false,
+                }
+            }
+        }
+    }
+}
+
+/// Splits a directive's target portion into either an ordinary target filter
+/// or a compiled location glob.
+pub(in crate::filter) fn parse_target(
+    s: &str,
+) -> Result<(Option<String>, Option<LocationGlob>), ParseError> {
+    match LocationGlob::from_target(s) {
+        Some(location) => Ok((None, Some(location?))),
+        None if s.is_empty() => Ok((None, None)),
+        None => Ok((Some(String::from(s)), None)),
+    }
+}
+
 // === impl StaticDirective ===
 
 impl StaticDirective {
     pub(in crate::filter) fn new(
         target: Option<String>,
+        location: Option<LocationGlob>,
         field_names: Vec<String>,
         level: LevelFilter,
     ) -> Self {
         Self {
             target,
+            location,
             field_names,
             level,
         }
@@ -181,6 +528,11 @@ impl StaticDirective {
             }
         }
 
+        // A location glob cannot be checked against a target alone.
+        if self.location.is_some() {
+            return false;
+        }
+
         if !self.field_names.is_empty() {
             return false;
         }
@@ -202,6 +554,14 @@ impl Ord for StaticDirective {
             .as_ref()
             .map(String::len)
             .cmp(&other.target.as_ref().map(String::len))
+            // A location glob's specificity is approximated by the length of
+            // its pattern, like a target.
+            .then_with(|| {
+                self.location
+                    .as_ref()
+                    .map(LocationGlob::pattern_len)
+                    .cmp(&other.location.as_ref().map(LocationGlob::pattern_len))
+            })
             // Then we compare how many field names are matched by each directive.
             .then_with(|| self.field_names.len().cmp(&other.field_names.len()))
             // Finally, we fall back to lexicographical ordering if the directives are
@@ -211,6 +571,7 @@ impl Ord for StaticDirective {
             .then_with(|| {
                 self.target
                     .cmp(&other.target)
+                    .then_with(|| self.location.cmp(&other.location))
                     .then_with(|| self.field_names[..].cmp(&other.field_names[..]))
             })
             .reverse();
@@ -222,6 +583,10 @@ impl Ord for StaticDirective {
                     self.target, other.target,
                     "invariant violated: Ordering::Equal must imply a.target == b.target"
                 );
+                debug_assert_eq!(
+                    self.location, other.location,
+                    "invariant violated: Ordering::Equal must imply a.location == b.location"
+                );
                 debug_assert_eq!(
                     self.field_names, other.field_names,
                     "invariant violated: Ordering::Equal must imply a.field_names == b.field_names"
@@ -249,6 +614,14 @@ impl Match for StaticDirective {
             }
         }
 
+        // Does this directive have a location glob, and does it match the
+        // metadata's source location?
+        if let Some(ref location) = self.location {
+            if !location.matches_metadata(meta) {
+                return false;
+            }
+        }
+
         if meta.is_event() && !self.field_names.is_empty() {
             let fields = meta.fields();
             for name in &self.field_names {
@@ -264,12 +637,22 @@ impl Match for StaticDirective {
     fn level(&self) -> &LevelFilter {
         &self.level
     }
+
+    fn index_entry(&self) -> IndexEntry<'_> {
+        // A location glob or field names cannot be checked against a target
+        // alone, so the directive cannot be indexed.
+        if self.location.is_some() || !self.field_names.is_empty() {
+            return IndexEntry::Complex;
+        }
+        IndexEntry::Target(self.target.as_deref(), self.level)
+    }
 }
 
 impl Default for StaticDirective {
     fn default() -> Self {
         StaticDirective {
             target: None,
+            location: None,
             field_names: Vec::new(),
             level: LevelFilter::ERROR,
         }
@@ -284,6 +667,11 @@ impl fmt::Display for StaticDirective {
             wrote_any = true;
         }
 
+        if let Some(ref location) = self.location {
+            fmt::Display::fmt(location, f)?;
+            wrote_any = true;
+        }
+
         if !self.field_names.is_empty() {
             f.write_str("[")?;
 
@@ -336,7 +724,10 @@ impl FromStr for StaticDirective {
             }
 
             let mut split = part0.split("[{");
-            let target = split.next().map(String::from);
+            let (target, location) = match split.next() {
+                Some(target) => parse_target(target)?,
+                None => (None, None),
+            };
             let mut field_names = Vec::new();
             // Directive includes fields:
             // * `foo[{bar}]=trace`
@@ -364,6 +755,7 @@ impl FromStr for StaticDirective {
                 level,
                 field_names,
                 target,
+                location,
             });
         }
 
@@ -375,13 +767,18 @@ impl FromStr for StaticDirective {
             Ok(level) => Self {
                 level,
                 target: None,
+                location: None,
                 field_names: Vec::new(),
             },
-            Err(_) => Self {
-                target: Some(String::from(part0)),
-                level: LevelFilter::TRACE,
-                field_names: Vec::new(),
-            },
+            Err(_) => {
+                let (target, location) = parse_target(part0)?;
+                Self {
+                    target,
+                    location,
+                    level: LevelFilter::TRACE,
+                    field_names: Vec::new(),
+                }
+            }
         })
     }
 }
@@ -446,3 +843,99 @@ impl From<level::ParseError> for ParseError {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn glob(kind: LocationKind, pattern: &str, input: &str) -> bool {
+        let glob = LocationGlob::compile(kind, pattern);
+        glob_matches(&glob.segments, input, kind)
+    }
+
+    #[test]
+    fn file_globs() {
+        use LocationKind::File;
+        assert!(glob(File, "src/net/**", "src/net/tcp.rs"));
+        assert!(glob(File, "src/net/**", "src/net/ip/v4.rs"));
+        assert!(glob(File, "src/net/*", "src/net/tcp.rs"));
+        assert!(!glob(File, "src/net/*", "src/net/ip/v4.rs"));
+        assert!(!glob(File, "src/net/**", "src/other/tcp.rs"));
+        assert!(glob(File, "**/tcp.rs", "a/long/path/to/tcp.rs"));
+        assert!(glob(File, "src/?et/*.rs", "src/net/tcp.rs"));
+        assert!(!glob(File, "src/?et/*.rs", "src/inet/tcp.rs"));
+    }
+
+    #[test]
+    fn module_globs() {
+        use LocationKind::Module;
+        assert!(glob(Module, "myapp::db::*", "myapp::db::users"));
+        assert!(!glob(Module, "myapp::db::*", "myapp::db::users::by_id"));
+        assert!(glob(Module, "myapp::db::**", "myapp::db::users::by_id"));
+        assert!(!glob(Module, "myapp::db::*", "myapp::web"));
+        assert!(glob(Module, "**::db", "myapp::db"));
+    }
+
+    #[test]
+    fn parse_location_directives() {
+        let directive = "file:src/net/**=trace"
+            .parse::<StaticDirective>()
+            .expect("directive should parse");
+        assert_eq!(directive.target, None);
+        assert_eq!(directive.level, LevelFilter::TRACE);
+        assert!(directive.location.is_some());
+        assert_eq!(directive.to_string(), "file:src/net/**=trace");
+
+        let directive = "module:myapp::db::*=debug"
+            .parse::<StaticDirective>()
+            .expect("directive should parse");
+        assert_eq!(directive.target, None);
+        assert_eq!(directive.level, LevelFilter::DEBUG);
+        assert_eq!(directive.to_string(), "module:myapp::db::*=debug");
+
+        // An empty glob pattern is an error.
+        assert!("file:=trace".parse::<StaticDirective>().is_err());
+    }
+
+    #[test]
+    fn target_trie_finds_most_specific_directive() {
+        let set = ["tokio=info", "tokio::net=debug", "tokio::net::tcp=trace"]
+            .iter()
+            .map(|d| d.parse::<StaticDirective>().expect("directive should parse"))
+            .collect::<DirectiveSet<_>>();
+        assert!(set.index.usable);
+
+        assert!(set.target_enabled("tokio::net::tcp::listener", &Level::TRACE));
+        assert!(set.target_enabled("tokio::net::udp", &Level::DEBUG));
+        assert!(!set.target_enabled("tokio::net::udp", &Level::TRACE));
+        assert!(set.target_enabled("tokio::time", &Level::INFO));
+        assert!(!set.target_enabled("tokio::time", &Level::DEBUG));
+        assert!(!set.target_enabled("hyper", &Level::ERROR));
+    }
+
+    #[test]
+    fn target_trie_matches_directive_with_no_target() {
+        let set = ["info", "tokio=debug"]
+            .iter()
+            .map(|d| d.parse::<StaticDirective>().expect("directive should parse"))
+            .collect::<DirectiveSet<_>>();
+        assert!(set.index.usable);
+
+        assert!(set.target_enabled("tokio::net", &Level::DEBUG));
+        assert!(set.target_enabled("hyper", &Level::INFO));
+        assert!(!set.target_enabled("hyper", &Level::DEBUG));
+    }
+
+    #[test]
+    fn target_trie_is_unusable_with_complex_directives() {
+        let set = ["tokio=info", "file:src/net/**=trace"]
+            .iter()
+            .map(|d| d.parse::<StaticDirective>().expect("directive should parse"))
+            .collect::<DirectiveSet<_>>();
+        assert!(!set.index.usable);
+
+        // The set still matches correctly, via a linear scan.
+        assert!(set.target_enabled("tokio::net", &Level::INFO));
+        assert!(!set.target_enabled("hyper", &Level::ERROR));
+    }
+}
@@ -313,20 +313,39 @@ impl Builder {
             ));
         }
 
+        // Negated directives disable what they match rather than enabling it,
+        // so they are kept out of the static/dynamic tables. Those with value
+        // predicates are checked when the event is recorded; the rest are
+        // checked against metadata alone.
+        let (negations, directives): (Vec<_>, Vec<_>) =
+            directives.into_iter().partition(Directive::is_negated);
+        let (event_exclusions, exclusions): (Vec<_>, Vec<_>) = negations
+            .into_iter()
+            .partition(Directive::has_value_fields);
+
+        // Directives that match event field values are checked when the event
+        // is recorded, so they are kept out of the static/dynamic tables.
+        let (event_directives, directives): (Vec<_>, Vec<_>) = directives
+            .into_iter()
+            .partition(Directive::is_event_matcher);
         let (dynamics, statics) = Directive::make_tables(directives);
         let has_dynamics = !dynamics.is_empty();
 
         let mut filter = EnvFilter {
             statics,
             dynamics,
+            event_directives,
+            exclusions,
+            event_exclusions,
             has_dynamics,
             by_id: RwLock::new(Default::default()),
             by_cs: RwLock::new(Default::default()),
+            static_verdicts: RwLock::new(Default::default()),
             scope: ThreadLocal::new(),
             regex: self.regex,
         };
 
-        if !has_dynamics && filter.statics.is_empty() {
+        if !has_dynamics && filter.statics.is_empty() && filter.event_directives.is_empty() {
             if let Some(ref default) = self.default_directive {
                 filter = filter.add_directive(default.clone());
             }
@@ -1,7 +1,7 @@
 pub(crate) use crate::filter::directive::{FilterVec, ParseError, StaticDirective};
 use crate::filter::{
-    directive::{DirectiveSet, Match},
-    env::{field, FieldMap},
+    directive::{DirectiveSet, LocationGlob, Match},
+    env::field,
     level::LevelFilter,
 };
 use std::{cmp::Ordering, fmt, iter::FromIterator, str::FromStr};
@@ -14,7 +14,14 @@ use tracing_core::{span, Level, Metadata};
 pub struct Directive {
     in_span: Option<String>,
     fields: Vec<field::Match>,
+    /// A boolean expression combining the field predicates in `fields`. When
+    /// this is `None`, the predicates are an implicit conjunction.
+    expr: Option<field::Expr>,
+    /// Negated directives *disable* the spans and events they match, rather
+    /// than enabling them.
+    negated: bool,
     pub(crate) target: Option<String>,
+    location: Option<LocationGlob>,
     pub(crate) level: LevelFilter,
 }
 
@@ -53,21 +60,60 @@ impl Directive {
 
         Some(StaticDirective::new(
             self.target.clone(),
+            self.location.clone(),
             field_names,
             self.level,
         ))
     }
 
     fn is_static(&self) -> bool {
-        !self.has_name() && !self.fields.iter().any(field::Match::has_value)
+        // An expression combining predicates with `or` or `not` cannot be
+        // reduced to a static list of required field names, even when none of
+        // the predicates match a value.
+        !self.has_name() && self.expr.is_none() && !self.fields.iter().any(field::Match::has_value)
+    }
+
+    /// Returns `true` if this directive disables the spans and events it
+    /// matches, rather than enabling them.
+    pub(super) fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// Returns `true` if any of this directive's field predicates match a
+    /// value, rather than just requiring the field to be present.
+    pub(super) fn has_value_fields(&self) -> bool {
+        self.fields.iter().any(field::Match::has_value)
     }
 
     pub(super) fn is_dynamic(&self) -> bool {
         self.has_name() || self.has_fields()
     }
 
+    /// Returns `true` if this directive must be matched against events when
+    /// they are recorded, because it matches field values with `=~` patterns
+    /// and does not name an enclosing span.
+    pub(super) fn is_event_matcher(&self) -> bool {
+        !self.has_name()
+            && self
+                .fields
+                .iter()
+                .any(|field| matches!(field.value, Some(field::ValueMatch::EventPat(_))))
+    }
+
     pub(crate) fn field_matcher(&self, meta: &Metadata<'_>) -> Option<field::CallsiteMatch> {
         let fieldset = meta.fields();
+        if let Some(ref expr) = self.expr {
+            // Missing fields don't disqualify an expression directive the way
+            // they do a plain list --- a disjunction may still match via
+            // another branch --- so they are resolved to constants instead.
+            let mut fields = Vec::new();
+            let expr = expr.resolve(&self.fields, fieldset, &mut fields);
+            return Some(field::CallsiteMatch {
+                fields,
+                expr: Some(expr),
+                level: self.level,
+            });
+        }
         let fields = self
             .fields
             .iter()
@@ -84,10 +130,11 @@ impl Directive {
                     }
                 },
             )
-            .collect::<Result<FieldMap<_>, ()>>()
+            .collect::<Result<Vec<_>, ()>>()
             .ok()?;
         Some(field::CallsiteMatch {
             fields,
+            expr: None,
             level: self.level,
         })
     }
@@ -112,17 +159,29 @@ impl Directive {
                 Some(field::ValueMatch::Pat(pat)) => {
                     Some(field::ValueMatch::Debug(pat.into_debug_match()))
                 }
+                // `=~` matches are explicitly regular expressions, so they
+                // are left as-is.
                 x => x,
             }
         }
     }
 
     pub(super) fn parse(from: &str, regex: bool) -> Result<Self, ParseError> {
+        let from = from.trim();
+        // A leading `!` negates the directive: the spans and events it
+        // matches are disabled instead of enabled.
+        let (from, negated) = match from.strip_prefix('!') {
+            Some(from) => (from, true),
+            None => (from, false),
+        };
         let mut cur = Self {
             level: LevelFilter::TRACE,
             target: None,
+            location: None,
             in_span: None,
             fields: Vec::new(),
+            expr: None,
+            negated,
         };
 
         #[derive(Debug)]
@@ -182,19 +241,20 @@ impl Directive {
                 }
                 (state @ Span { .. }, _) => state,
                 (Field { field_start }, '}') => {
-                    cur.fields.push(match &from[field_start..i] {
+                    // The entire `{}` filter is parsed at once, so that the
+                    // expression grammar (`and`/`or`/`not` and parenthesized
+                    // groups) can see all of its tokens; a plain
+                    // comma-separated list of predicates parses as before.
+                    match &from[field_start..i] {
                         "" => return Err(ParseError::new()),
-                        field => field::Match::parse(field, regex)?,
-                    });
+                        fields => {
+                            let (fields, expr) = field::parse_expression(fields, regex)?;
+                            cur.fields = fields;
+                            cur.expr = expr;
+                        }
+                    }
                     Fields
                 }
-                (Field { field_start }, ',') => {
-                    cur.fields.push(match &from[field_start..i] {
-                        "" => return Err(ParseError::new()),
-                        field => field::Match::parse(field, regex)?,
-                    });
-                    Field { field_start: i + 1 }
-                }
                 (state @ Field { .. }, _) => state,
                 (Fields, ']') => Target,
                 (Level { level_start }, ',') => {
@@ -233,6 +293,23 @@ impl Directive {
             _ => return Err(ParseError::new()),
         };
 
+        // A target beginning with `file:` or `module:` is a location glob
+        // matching the callsite's source location instead.
+        if let Some(target) = cur.target.take() {
+            match LocationGlob::from_target(&target) {
+                Some(location) => cur.location = Some(location?),
+                None => cur.target = Some(target),
+            }
+        }
+
+        // Negated directives are applied at callsite registration or event
+        // time; matching them against dynamic span scopes is not supported.
+        if cur.negated && cur.in_span.is_some() {
+            return Err(ParseError::msg(
+                "negated directives cannot match on span names",
+            ));
+        }
+
         Ok(cur)
     }
 }
@@ -247,6 +324,14 @@ impl Match for Directive {
             }
         }
 
+        // Does this directive have a location glob, and does it match the
+        // metadata's source location?
+        if let Some(ref location) = self.location {
+            if !location.matches_metadata(meta) {
+                return false;
+            }
+        }
+
         // Do we have a name filter, and does it match the metadata's name?
         // TODO(eliza): put name globbing here?
         if let Some(ref name) = self.in_span {
@@ -255,13 +340,21 @@ impl Match for Directive {
             }
         }
 
-        // Does the metadata define all the fields that this directive cares about?
         let actual_fields = meta.fields();
-        for expected_field in &self.fields {
-            // Does the actual field set (from the metadata) contain this field?
-            if actual_fields.field(&expected_field.name).is_none() {
+        if let Some(ref expr) = self.expr {
+            // Could the expression match, given which of its fields the
+            // metadata defines?
+            if !expr.could_match(&|i| actual_fields.field(&self.fields[i].name).is_some()) {
                 return false;
             }
+        } else {
+            // Does the metadata define all the fields that this directive cares about?
+            for expected_field in &self.fields {
+                // Does the actual field set (from the metadata) contain this field?
+                if actual_fields.field(&expected_field.name).is_none() {
+                    return false;
+                }
+            }
         }
 
         true
@@ -284,8 +377,11 @@ impl Default for Directive {
         Directive {
             level: LevelFilter::OFF,
             target: None,
+            location: None,
             in_span: None,
             fields: Vec::new(),
+            expr: None,
+            negated: false,
         }
     }
 }
@@ -309,6 +405,14 @@ impl Ord for Directive {
             .as_ref()
             .map(String::len)
             .cmp(&other.target.as_ref().map(String::len))
+            // A location glob's specificity is approximated by the length of
+            // its pattern, like a target.
+            .then_with(|| {
+                self.location
+                    .as_ref()
+                    .map(LocationGlob::pattern_len)
+                    .cmp(&other.location.as_ref().map(LocationGlob::pattern_len))
+            })
             // Next compare based on the presence of span names.
             .then_with(|| self.in_span.is_some().cmp(&other.in_span.is_some()))
             // Then we compare how many fields are defined by each
@@ -321,8 +425,11 @@ impl Ord for Directive {
             .then_with(|| {
                 self.target
                     .cmp(&other.target)
+                    .then_with(|| self.location.cmp(&other.location))
                     .then_with(|| self.in_span.cmp(&other.in_span))
                     .then_with(|| self.fields[..].cmp(&other.fields[..]))
+                    .then_with(|| self.expr.cmp(&other.expr))
+                    .then_with(|| self.negated.cmp(&other.negated))
             })
             .reverse();
 
@@ -333,6 +440,10 @@ impl Ord for Directive {
                     self.target, other.target,
                     "invariant violated: Ordering::Equal must imply a.target == b.target"
                 );
+                debug_assert_eq!(
+                    self.location, other.location,
+                    "invariant violated: Ordering::Equal must imply a.location == b.location"
+                );
                 debug_assert_eq!(
                     self.in_span, other.in_span,
                     "invariant violated: Ordering::Equal must imply a.in_span == b.in_span"
@@ -341,6 +452,14 @@ impl Ord for Directive {
                     self.fields, other.fields,
                     "invariant violated: Ordering::Equal must imply a.fields == b.fields"
                 );
+                debug_assert_eq!(
+                    self.expr, other.expr,
+                    "invariant violated: Ordering::Equal must imply a.expr == b.expr"
+                );
+                debug_assert_eq!(
+                    self.negated, other.negated,
+                    "invariant violated: Ordering::Equal must imply a.negated == b.negated"
+                );
             }
         }
 
@@ -350,12 +469,21 @@ impl Ord for Directive {
 
 impl fmt::Display for Directive {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negated {
+            f.write_str("!")?;
+        }
+
         let mut wrote_any = false;
         if let Some(ref target) = self.target {
             fmt::Display::fmt(target, f)?;
             wrote_any = true;
         }
 
+        if let Some(ref location) = self.location {
+            fmt::Display::fmt(location, f)?;
+            wrote_any = true;
+        }
+
         if self.in_span.is_some() || !self.fields.is_empty() {
             f.write_str("[")?;
 
@@ -363,13 +491,19 @@ impl fmt::Display for Directive {
                 fmt::Display::fmt(span, f)?;
             }
 
-            let mut fields = self.fields.iter();
-            if let Some(field) = fields.next() {
-                write!(f, "{{{}", field)?;
-                for field in fields {
-                    write!(f, ",{}", field)?;
-                }
+            if let Some(ref expr) = self.expr {
+                f.write_str("{")?;
+                expr.fmt_with(f, &self.fields)?;
                 f.write_str("}")?;
+            } else {
+                let mut fields = self.fields.iter();
+                if let Some(field) = fields.next() {
+                    write!(f, "{{{}", field)?;
+                    for field in fields {
+                        write!(f, ",{}", field)?;
+                    }
+                    f.write_str("}")?;
+                }
             }
 
             f.write_str("]")?;
@@ -646,6 +780,105 @@ mod test {
         assert_eq!(dirs[3].in_span, None);
     }
 
+    #[test]
+    fn parse_directives_with_location_globs() {
+        let dirs = expect_parse("file:src/net/**=trace,module:myapp::db::*=debug");
+        assert_eq!(dirs.len(), 2, "\nparsed: {:#?}", dirs);
+        assert_eq!(dirs[0].target, None);
+        assert!(dirs[0].location.is_some());
+        assert_eq!(dirs[0].level, LevelFilter::TRACE);
+        assert_eq!(dirs[0].to_string(), "file:src/net/**=trace");
+        // Location-glob directives are static unless they also name a span or
+        // match field values.
+        assert!(dirs[0].to_static().is_some());
+
+        assert_eq!(dirs[1].target, None);
+        assert_eq!(dirs[1].level, LevelFilter::DEBUG);
+        assert_eq!(dirs[1].to_string(), "module:myapp::db::*=debug");
+
+        // A span name makes a location-glob directive dynamic, like any other.
+        let dirs = expect_parse("file:src/net/**[request]=debug");
+        assert_eq!(dirs.len(), 1, "\nparsed: {:#?}", dirs);
+        assert!(dirs[0].location.is_some());
+        assert_eq!(dirs[0].in_span, Some("request".to_string()));
+        assert!(dirs[0].to_static().is_none());
+    }
+
+    #[test]
+    fn parse_negated_directives() {
+        let dirs = expect_parse("!hyper=debug");
+        assert_eq!(dirs.len(), 1, "\nparsed: {:#?}", dirs);
+        assert!(dirs[0].negated);
+        assert_eq!(dirs[0].target, Some("hyper".to_string()));
+        assert_eq!(dirs[0].level, LevelFilter::DEBUG);
+        assert_eq!(dirs[0].to_string(), "!hyper=debug");
+
+        let dirs = expect_parse("!hyper[{tenant=acme}]=info");
+        assert_eq!(dirs.len(), 1, "\nparsed: {:#?}", dirs);
+        assert!(dirs[0].negated);
+        assert!(dirs[0].has_value_fields());
+        assert_eq!(dirs[0].to_string(), "!hyper[{tenant=acme}]=info");
+
+        // Negated directives cannot match on span names, since negation is
+        // not applied to dynamic span scopes.
+        assert!("!hyper[request]=info".parse::<Directive>().is_err());
+    }
+
+    #[test]
+    fn parse_directives_with_expressions() {
+        // NOTE: `expect_parse` would split this directive on the `,` inside
+        // its braces, so it is parsed directly.
+        let dir = "[{(a=1,b=2) or c=3}]=debug"
+            .parse::<Directive>()
+            .expect("directive should parse");
+        assert_eq!(dir.fields.len(), 3);
+        assert!(dir.expr.is_some());
+        // A `,` between predicates is normalized to `and`, and the redundant
+        // parentheses are dropped, since `and` binds tighter than `or`.
+        assert_eq!(dir.to_string(), "[{a=1 and b=2 or c=3}]=debug");
+        let reparsed = dir
+            .to_string()
+            .parse::<Directive>()
+            .expect("display output should reparse");
+        assert_eq!(reparsed, dir);
+
+        // Expressions cannot be reduced to a static list of required fields.
+        assert!(dir.to_static().is_none());
+
+        let dirs = expect_parse("app[{tenant=acme and not latency_ms<250}]=debug");
+        assert_eq!(dirs.len(), 1, "\nparsed: {:#?}", dirs);
+        assert_eq!(
+            dirs[0].to_string(),
+            "app[{tenant=acme and not latency_ms<250}]=debug"
+        );
+    }
+
+    #[test]
+    fn parse_invalid_expressions() {
+        // A dangling operator reports the error's position within the
+        // expression.
+        let err = "[{a=1 or}]=debug"
+            .parse::<Directive>()
+            .expect_err("a dangling `or` should not parse");
+        let msg = err.to_string();
+        assert!(
+            msg.contains("expected a field predicate"),
+            "unexpected error: {}",
+            msg
+        );
+        assert!(msg.contains("character 6"), "unexpected error: {}", msg);
+
+        let err = "[{(a=1 and b=2}]=debug"
+            .parse::<Directive>()
+            .expect_err("an unclosed group should not parse");
+        let msg = err.to_string();
+        assert!(
+            msg.contains("expected a closing `)`"),
+            "unexpected error: {}",
+            msg
+        );
+    }
+
     #[test]
 
     fn parse_level_directives() {
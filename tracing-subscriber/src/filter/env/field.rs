@@ -3,6 +3,7 @@ use std::{
     cmp::Ordering,
     error::Error,
     fmt::{self, Write},
+    ops::Range,
     str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering::*},
@@ -10,8 +11,8 @@ use std::{
     },
 };
 
-use super::{FieldMap, LevelFilter};
-use tracing_core::field::{Field, Visit};
+use super::LevelFilter;
+use tracing_core::field::{Field, FieldSet, Visit};
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub(crate) struct Match {
@@ -19,15 +20,47 @@ pub(crate) struct Match {
     pub(crate) value: Option<ValueMatch>,
 }
 
+/// A boolean combination of field predicates in a filter directive.
+///
+/// `Pred` leaves index into a list of [`Match`]es stored alongside the
+/// expression: the directive's own field list at parse time, or a
+/// [`CallsiteMatch`]'s resolved value matchers once a callsite has been
+/// registered.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub(crate) enum Expr {
+    /// A single field predicate.
+    Pred(usize),
+    /// A constant value, produced when a predicate's field presence is
+    /// resolved at callsite registration.
+    Const(bool),
+    /// Negates the inner expression.
+    Not(Box<Expr>),
+    /// True if every inner expression is true.
+    And(Vec<Expr>),
+    /// True if any inner expression is true.
+    Or(Vec<Expr>),
+}
+
+/// Indicates that a field predicate expression in a filter directive was
+/// invalid, and the location of the error within the expression.
+#[derive(Debug, Clone)]
+pub(crate) struct ExprError {
+    expression: String,
+    message: String,
+    span: Range<usize>,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub(crate) struct CallsiteMatch {
-    pub(crate) fields: FieldMap<ValueMatch>,
+    pub(crate) fields: Vec<(Field, ValueMatch)>,
+    pub(crate) expr: Option<Expr>,
     pub(crate) level: LevelFilter,
 }
 
 #[derive(Debug)]
 pub(crate) struct SpanMatch {
-    fields: FieldMap<(ValueMatch, AtomicBool)>,
+    fields: Vec<(Field, ValueMatch, AtomicBool)>,
+    expr: Option<Expr>,
     level: LevelFilter,
     has_matched: AtomicBool,
 }
@@ -53,6 +86,16 @@ pub(crate) enum ValueMatch {
     /// Matches any field whose `fmt::Debug` output matches a regular expression
     /// pattern.
     Pat(Box<MatchPattern>),
+    /// Matches any field whose `fmt::Debug` output *contains* a match for a
+    /// regular expression pattern.
+    ///
+    /// Unlike `Pat`, this match is unanchored, and is checked when the value
+    /// is recorded (at event time) rather than when the callsite is
+    /// registered.
+    EventPat(Box<MatchPattern>),
+    /// Matches numeric values against a comparison bound (`>`, `>=`, `<`,
+    /// `<=`).
+    Cmp(MatchCmp),
 }
 
 impl Eq for ValueMatch {}
@@ -72,6 +115,8 @@ impl PartialEq for ValueMatch {
             (I64(a), I64(b)) => a.eq(b),
             (NaN, NaN) => true,
             (Pat(a), Pat(b)) => a.eq(b),
+            (EventPat(a), EventPat(b)) => a.eq(b),
+            (Cmp(a), Cmp(b)) => a.eq(b),
             _ => false,
         }
     }
@@ -104,6 +149,14 @@ impl Ord for ValueMatch {
             }
             (I64(_), _) => Ordering::Less,
 
+            (Cmp(this), Cmp(that)) => this.cmp(that),
+            (Cmp(_), EventPat(_)) | (Cmp(_), Pat(_)) | (Cmp(_), Debug(_)) => Ordering::Less,
+            (Cmp(_), _) => Ordering::Greater,
+
+            (EventPat(this), EventPat(that)) => this.cmp(that),
+            (EventPat(_), Pat(_)) | (EventPat(_), Debug(_)) => Ordering::Less,
+            (EventPat(_), _) => Ordering::Greater,
+
             (Pat(this), Pat(that)) => this.cmp(that),
             (Pat(_), _) => Ordering::Greater,
 
@@ -138,6 +191,30 @@ pub(crate) struct MatchDebug {
     pattern: Arc<str>,
 }
 
+/// Matches a numeric field value against a comparison bound.
+#[derive(Debug, Clone)]
+pub(crate) struct MatchCmp {
+    pub(crate) op: CmpOp,
+    pub(crate) bound: CmpBound,
+}
+
+/// A comparison operator in a field value directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// The numeric bound of a comparison directive.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum CmpBound {
+    U64(u64),
+    I64(i64),
+    F64(f64),
+}
+
 /// Indicates that a field name specified in a filter directive was invalid.
 #[derive(Clone, Debug)]
 #[cfg_attr(docsrs, doc(cfg(feature = "env-filter")))]
@@ -145,6 +222,12 @@ pub struct BadName {
     name: String,
 }
 
+/// Indicates that a comparison in a filter directive was invalid.
+#[derive(Clone, Debug)]
+struct BadComparison {
+    value: String,
+}
+
 // === impl Match ===
 
 impl Match {
@@ -158,6 +241,33 @@ impl Match {
     }
 
     pub(crate) fn parse(s: &str, regex: bool) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        // Event-time pattern directives (`field=~pattern`) are checked before
+        // any other form, since the `=~` separator would otherwise parse as
+        // an ordinary equality match against a value beginning with `~`.
+        if let Some((name, pattern)) = s.split_once("=~") {
+            if !regex {
+                return Err("`=~` matches require regular expressions to be enabled".into());
+            }
+            let pattern = MatchPattern::new_unanchored(pattern.trim_matches('"'))?;
+            return Ok(Match {
+                name: name.to_string(),
+                value: Some(ValueMatch::EventPat(Box::new(pattern))),
+            });
+        }
+        // Comparison directives (`field>10`, `field>=10`, `field<10`,
+        // `field<=10`) are checked before splitting on `=`, since `>=` and
+        // `<=` contain an `=` as well. An `=` *preceding* any `<` or `>` is
+        // an ordinary equality match (e.g. `field=<some debug output>`).
+        if let Some(idx) = s.find(['<', '>', '=']) {
+            if s.as_bytes()[idx] != b'=' {
+                let name = s[..idx].to_string();
+                let (op, bound) = CmpOp::parse_prefixed(&s[idx..])?;
+                return Ok(Match {
+                    name,
+                    value: Some(ValueMatch::Cmp(MatchCmp { op, bound })),
+                });
+            }
+        }
         let mut parts = s.split('=');
         let name = parts
             .next()
@@ -180,8 +290,12 @@ impl Match {
 impl fmt::Display for Match {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt::Display::fmt(&self.name, f)?;
-        if let Some(ref value) = self.value {
-            write!(f, "={}", value)?;
+        match self.value {
+            // Comparison matchers include their own operator.
+            Some(ValueMatch::Cmp(ref value)) => write!(f, "{}", value)?,
+            Some(ValueMatch::EventPat(ref value)) => write!(f, "=~{}", value)?,
+            Some(ref value) => write!(f, "={}", value)?,
+            None => {}
         }
         Ok(())
     }
@@ -217,6 +331,372 @@ impl PartialOrd for Match {
     }
 }
 
+// === impl Expr ===
+
+/// Parses the contents of a `{}` field filter as either a comma-separated
+/// list of predicates (the original syntax, where the list is an implicit
+/// conjunction), or a boolean expression combining predicates with `and`,
+/// `or`, `not`, and parenthesized groups.
+///
+/// Returns the flat list of predicates referenced by the filter, and the
+/// expression combining them. The expression is `None` when the plain list
+/// syntax was used, so that existing directives match exactly as before.
+pub(crate) fn parse_expression(
+    s: &str,
+    regex: bool,
+) -> Result<(Vec<Match>, Option<Expr>), Box<dyn Error + Send + Sync>> {
+    let tokens = tokenize(s);
+    let is_expression = tokens.iter().any(|token| {
+        matches!(
+            token.kind,
+            TokenKind::LParen | TokenKind::RParen | TokenKind::And | TokenKind::Or | TokenKind::Not
+        )
+    });
+    if !is_expression {
+        // The plain list syntax. Split on commas rather than reusing the
+        // tokens, since unquoted values may contain whitespace.
+        let fields = s
+            .split(',')
+            .map(|field| {
+                if field.is_empty() {
+                    Err("unexpected `,` in field filter".into())
+                } else {
+                    Match::parse(field, regex)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok((fields, None));
+    }
+    let parser = ExprParser {
+        source: s,
+        tokens,
+        pos: 0,
+        preds: Vec::new(),
+        regex,
+    };
+    let (preds, expr) = parser.parse()?;
+    Ok((preds, Some(expr)))
+}
+
+impl Expr {
+    /// Resolves each predicate leaf against a callsite's fields, pushing the
+    /// value matchers for present fields into `values` and replacing
+    /// presence-only or absent predicates with constants.
+    pub(crate) fn resolve(
+        &self,
+        preds: &[Match],
+        fieldset: &FieldSet,
+        values: &mut Vec<(Field, ValueMatch)>,
+    ) -> Expr {
+        match self {
+            Expr::Pred(i) => {
+                let pred = &preds[*i];
+                match (fieldset.field(&pred.name), pred.value.as_ref()) {
+                    (Some(field), Some(value)) => {
+                        values.push((field, value.clone()));
+                        Expr::Pred(values.len() - 1)
+                    }
+                    // A presence-only predicate; the callsite defines the
+                    // field, so it matches unconditionally.
+                    (Some(_), None) => Expr::Const(true),
+                    // The callsite doesn't define the field, so the predicate
+                    // can never match.
+                    (None, _) => Expr::Const(false),
+                }
+            }
+            Expr::Const(value) => Expr::Const(*value),
+            Expr::Not(inner) => Expr::Not(Box::new(inner.resolve(preds, fieldset, values))),
+            Expr::And(inner) => Expr::And(
+                inner
+                    .iter()
+                    .map(|expr| expr.resolve(preds, fieldset, values))
+                    .collect(),
+            ),
+            Expr::Or(inner) => Expr::Or(
+                inner
+                    .iter()
+                    .map(|expr| expr.resolve(preds, fieldset, values))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns whether the expression could match a callsite whose field
+    /// presence is described by `present`. Negated subexpressions are
+    /// conservatively assumed to be satisfiable.
+    pub(crate) fn could_match(&self, present: &impl Fn(usize) -> bool) -> bool {
+        match self {
+            Expr::Pred(i) => present(*i),
+            Expr::Const(value) => *value,
+            Expr::Not(_) => true,
+            Expr::And(inner) => inner.iter().all(|expr| expr.could_match(present)),
+            Expr::Or(inner) => inner.iter().any(|expr| expr.could_match(present)),
+        }
+    }
+
+    /// Evaluates the expression, with `matched` returning whether the
+    /// predicate with a given index has matched a recorded value.
+    fn eval(&self, matched: &impl Fn(usize) -> bool) -> bool {
+        match self {
+            Expr::Pred(i) => matched(*i),
+            Expr::Const(value) => *value,
+            Expr::Not(inner) => !inner.eval(matched),
+            Expr::And(inner) => inner.iter().all(|expr| expr.eval(matched)),
+            Expr::Or(inner) => inner.iter().any(|expr| expr.eval(matched)),
+        }
+    }
+
+    /// Returns `true` if the expression contains no negation, and thus can
+    /// only change from unmatched to matched as more fields are recorded.
+    fn is_monotonic(&self) -> bool {
+        match self {
+            Expr::Pred(_) | Expr::Const(_) => true,
+            Expr::Not(_) => false,
+            Expr::And(inner) | Expr::Or(inner) => inner.iter().all(Expr::is_monotonic),
+        }
+    }
+
+    /// Formats the expression, resolving predicate leaves against `preds`.
+    pub(crate) fn fmt_with(&self, f: &mut fmt::Formatter<'_>, preds: &[Match]) -> fmt::Result {
+        match self {
+            Expr::Pred(i) => fmt::Display::fmt(&preds[*i], f),
+            Expr::Const(value) => fmt::Display::fmt(value, f),
+            Expr::Not(inner) => {
+                f.write_str("not ")?;
+                inner.fmt_grouped(f, preds)
+            }
+            Expr::And(inner) => {
+                for (i, expr) in inner.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" and ")?;
+                    }
+                    expr.fmt_grouped(f, preds)?;
+                }
+                Ok(())
+            }
+            Expr::Or(inner) => {
+                for (i, expr) in inner.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" or ")?;
+                    }
+                    expr.fmt_with(f, preds)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Like `fmt_with`, but parenthesizes `and`/`or` expressions so that
+    /// precedence survives a round trip through the parser.
+    fn fmt_grouped(&self, f: &mut fmt::Formatter<'_>, preds: &[Match]) -> fmt::Result {
+        match self {
+            Expr::And(_) | Expr::Or(_) => {
+                f.write_str("(")?;
+                self.fmt_with(f, preds)?;
+                f.write_str(")")
+            }
+            _ => self.fmt_with(f, preds),
+        }
+    }
+}
+
+/// A token in a field predicate expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind<'a> {
+    LParen,
+    RParen,
+    Comma,
+    And,
+    Or,
+    Not,
+    Pred(&'a str),
+}
+
+#[derive(Debug, Clone)]
+struct Token<'a> {
+    kind: TokenKind<'a>,
+    span: Range<usize>,
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                let kind = match c {
+                    '(' => TokenKind::LParen,
+                    ')' => TokenKind::RParen,
+                    _ => TokenKind::Comma,
+                };
+                tokens.push(Token {
+                    kind,
+                    span: start..start + 1,
+                });
+            }
+            _ => {
+                // A word: a predicate or a keyword. Words end at whitespace,
+                // parentheses, or commas, except within double quotes.
+                let mut end = start;
+                let mut quoted = false;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !quoted && (c.is_whitespace() || c == '(' || c == ')' || c == ',') {
+                        break;
+                    }
+                    if c == '"' {
+                        quoted = !quoted;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let word = &s[start..end];
+                let kind = match word {
+                    "and" => TokenKind::And,
+                    "or" => TokenKind::Or,
+                    "not" => TokenKind::Not,
+                    _ => TokenKind::Pred(word),
+                };
+                tokens.push(Token {
+                    kind,
+                    span: start..end,
+                });
+            }
+        }
+    }
+    tokens
+}
+
+/// A recursive-descent parser for field predicate expressions.
+///
+/// The grammar, from lowest to highest precedence:
+///
+/// ```text
+/// expr   = term *("or" term)
+/// term   = factor *(("and" / ",") factor)
+/// factor = "not" factor / "(" expr ")" / predicate
+/// ```
+///
+/// A `,` separator is equivalent to `and`, matching the original list
+/// syntax.
+struct ExprParser<'a> {
+    source: &'a str,
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    preds: Vec<Match>,
+    regex: bool,
+}
+
+impl<'a> ExprParser<'a> {
+    fn parse(mut self) -> Result<(Vec<Match>, Expr), ExprError> {
+        let expr = self.expr()?;
+        if let Some(token) = self.tokens.get(self.pos) {
+            return Err(self.error("unexpected token after expression", token.span.clone()));
+        }
+        Ok((self.preds, expr))
+    }
+
+    fn expr(&mut self) -> Result<Expr, ExprError> {
+        let mut terms = vec![self.term()?];
+        while self.eat(TokenKind::Or) {
+            terms.push(self.term()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().expect("length was just checked")
+        } else {
+            Expr::Or(terms)
+        })
+    }
+
+    fn term(&mut self) -> Result<Expr, ExprError> {
+        let mut factors = vec![self.factor()?];
+        while self.eat(TokenKind::And) || self.eat(TokenKind::Comma) {
+            factors.push(self.factor()?);
+        }
+        Ok(if factors.len() == 1 {
+            factors.pop().expect("length was just checked")
+        } else {
+            Expr::And(factors)
+        })
+    }
+
+    fn factor(&mut self) -> Result<Expr, ExprError> {
+        if self.eat(TokenKind::Not) {
+            return Ok(Expr::Not(Box::new(self.factor()?)));
+        }
+        if self.eat(TokenKind::LParen) {
+            let expr = self.expr()?;
+            if !self.eat(TokenKind::RParen) {
+                return Err(self.error_here("expected a closing `)`"));
+            }
+            return Ok(expr);
+        }
+        match self.tokens.get(self.pos).cloned() {
+            Some(Token {
+                kind: TokenKind::Pred(pred),
+                span,
+            }) => {
+                self.pos += 1;
+                let pred = Match::parse(pred, self.regex)
+                    .map_err(|e| self.error(&e.to_string(), span))?;
+                self.preds.push(pred);
+                Ok(Expr::Pred(self.preds.len() - 1))
+            }
+            _ => Err(self.error_here("expected a field predicate")),
+        }
+    }
+
+    fn eat(&mut self, kind: TokenKind<'a>) -> bool {
+        if matches!(self.tokens.get(self.pos), Some(token) if token.kind == kind) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str, span: Range<usize>) -> ExprError {
+        ExprError {
+            expression: self.source.to_string(),
+            message: message.to_string(),
+            span,
+        }
+    }
+
+    fn error_here(&self, message: &str) -> ExprError {
+        let span = match self.tokens.get(self.pos) {
+            Some(token) => token.span.clone(),
+            None => self.source.len()..self.source.len(),
+        };
+        self.error(message, span)
+    }
+}
+
+// === impl ExprError ===
+
+impl Error for ExprError {}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid filter expression `{}`: {}",
+            self.expression, self.message
+        )?;
+        match self.expression.get(self.span.clone()) {
+            Some(snippet) if !snippet.is_empty() => write!(
+                f,
+                " (at `{}`, characters {}..{})",
+                snippet, self.span.start, self.span.end
+            ),
+            _ => write!(f, " (at character {})", self.span.start),
+        }
+    }
+}
+
 // === impl ValueMatch ===
 
 fn value_match_f64(v: f64) -> ValueMatch {
@@ -272,10 +752,171 @@ impl fmt::Display for ValueMatch {
             ValueMatch::U64(ref inner) => fmt::Display::fmt(inner, f),
             ValueMatch::Debug(ref inner) => fmt::Display::fmt(inner, f),
             ValueMatch::Pat(ref inner) => fmt::Display::fmt(inner, f),
+            ValueMatch::EventPat(ref inner) => fmt::Display::fmt(inner, f),
+            ValueMatch::Cmp(ref inner) => fmt::Display::fmt(inner, f),
+        }
+    }
+}
+
+// === impl MatchCmp ===
+
+impl MatchCmp {
+    fn matches_u64(&self, value: u64) -> bool {
+        use std::convert::TryFrom;
+
+        match self.bound {
+            CmpBound::U64(bound) => self.op.matches(value, bound),
+            CmpBound::I64(bound) => match u64::try_from(bound) {
+                Ok(bound) => self.op.matches(value, bound),
+                // The bound is negative; any `u64` value is greater than it.
+                Err(_) => matches!(self.op, CmpOp::Gt | CmpOp::Ge),
+            },
+            CmpBound::F64(bound) => self.op.matches(value as f64, bound),
+        }
+    }
+
+    fn matches_i64(&self, value: i64) -> bool {
+        use std::convert::TryFrom;
+
+        match self.bound {
+            CmpBound::U64(bound) => match i64::try_from(bound) {
+                Ok(bound) => self.op.matches(value, bound),
+                // The bound exceeds `i64::MAX`; any `i64` value is below it.
+                Err(_) => matches!(self.op, CmpOp::Lt | CmpOp::Le),
+            },
+            CmpBound::I64(bound) => self.op.matches(value, bound),
+            CmpBound::F64(bound) => self.op.matches(value as f64, bound),
+        }
+    }
+
+    fn matches_f64(&self, value: f64) -> bool {
+        let bound = match self.bound {
+            CmpBound::U64(bound) => bound as f64,
+            CmpBound::I64(bound) => bound as f64,
+            CmpBound::F64(bound) => bound,
+        };
+        self.op.matches(value, bound)
+    }
+}
+
+impl fmt::Display for MatchCmp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.op, self.bound)
+    }
+}
+
+impl PartialEq for MatchCmp {
+    fn eq(&self, other: &Self) -> bool {
+        self.op == other.op && self.bound.cmp(&other.bound) == Ordering::Equal
+    }
+}
+
+impl Eq for MatchCmp {}
+
+impl PartialOrd for MatchCmp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MatchCmp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.op.cmp(&other.op).then(self.bound.cmp(&other.bound))
+    }
+}
+
+// === impl CmpOp ===
+
+impl CmpOp {
+    /// Parse a comparison operator and its numeric bound from the remainder
+    /// of a field directive (e.g. `>=10`).
+    fn parse_prefixed(s: &str) -> Result<(Self, CmpBound), Box<dyn Error + Send + Sync>> {
+        let (op, bound) = if let Some(rest) = s.strip_prefix(">=") {
+            (CmpOp::Ge, rest)
+        } else if let Some(rest) = s.strip_prefix("<=") {
+            (CmpOp::Le, rest)
+        } else if let Some(rest) = s.strip_prefix('>') {
+            (CmpOp::Gt, rest)
+        } else if let Some(rest) = s.strip_prefix('<') {
+            (CmpOp::Lt, rest)
+        } else {
+            return Err(Box::new(BadComparison {
+                value: s.to_string(),
+            }));
+        };
+        let bound = bound
+            .parse::<u64>()
+            .map(CmpBound::U64)
+            .or_else(|_| bound.parse::<i64>().map(CmpBound::I64))
+            .or_else(|_| bound.parse::<f64>().map(CmpBound::F64))
+            .map_err(|_| BadComparison {
+                value: bound.to_string(),
+            })?;
+        Ok((op, bound))
+    }
+
+    fn matches<T: PartialOrd>(self, value: T, bound: T) -> bool {
+        match self {
+            CmpOp::Lt => value < bound,
+            CmpOp::Le => value <= bound,
+            CmpOp::Gt => value > bound,
+            CmpOp::Ge => value >= bound,
+        }
+    }
+}
+
+impl fmt::Display for CmpOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+        })
+    }
+}
+
+// === impl CmpBound ===
+
+impl CmpBound {
+    fn cmp(&self, other: &Self) -> Ordering {
+        use CmpBound::*;
+        match (self, other) {
+            (U64(a), U64(b)) => a.cmp(b),
+            (U64(_), _) => Ordering::Less,
+            (I64(a), I64(b)) => a.cmp(b),
+            (I64(_), U64(_)) => Ordering::Greater,
+            (I64(_), _) => Ordering::Less,
+            (F64(a), F64(b)) => a.total_cmp(b),
+            (F64(_), _) => Ordering::Greater,
         }
     }
 }
 
+impl fmt::Display for CmpBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CmpBound::U64(ref inner) => fmt::Display::fmt(inner, f),
+            CmpBound::I64(ref inner) => fmt::Display::fmt(inner, f),
+            CmpBound::F64(ref inner) => fmt::Display::fmt(inner, f),
+        }
+    }
+}
+
+// === impl BadComparison ===
+
+impl Error for BadComparison {}
+
+impl fmt::Display for BadComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid comparison `{}`, expected a numeric bound",
+            self.value
+        )
+    }
+}
+
 // === impl MatchPattern ===
 
 impl FromStr for MatchPattern {
@@ -304,6 +945,21 @@ impl AsRef<str> for MatchPattern {
 }
 
 impl MatchPattern {
+    /// Returns a new `MatchPattern` that matches anywhere in a value's
+    /// output, rather than requiring the pattern to match the entire value.
+    pub(super) fn new_unanchored(s: &str) -> Result<Self, matchers::Error> {
+        // `Pattern`s are matched by running a DFA over the value's output, and
+        // match only if the DFA ends in a match state once the output has been
+        // exhausted. `Pattern::new` leaves the *start* of the match
+        // unanchored; appending `.*` allows it to end anywhere in the output
+        // as well.
+        let matcher = Pattern::new(&format!("(?:{})(?s:.*)", s))?;
+        Ok(Self {
+            matcher,
+            pattern: s.to_owned().into(),
+        })
+    }
+
     #[inline]
     fn str_matches(&self, s: &impl AsRef<str>) -> bool {
         self.matcher.matches(s)
@@ -333,7 +989,7 @@ impl Eq for MatchPattern {}
 impl PartialOrd for MatchPattern {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.pattern.cmp(&other.pattern))
+        Some(self.cmp(other))
     }
 }
 
@@ -429,7 +1085,7 @@ impl Eq for MatchDebug {}
 impl PartialOrd for MatchDebug {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.pattern.cmp(&other.pattern))
+        Some(self.cmp(other))
     }
 }
 
@@ -455,10 +1111,11 @@ impl CallsiteMatch {
         let fields = self
             .fields
             .iter()
-            .map(|(k, v)| (k.clone(), (v.clone(), AtomicBool::new(false))))
+            .map(|(k, v)| (k.clone(), v.clone(), AtomicBool::new(false)))
             .collect();
         SpanMatch {
             fields,
+            expr: self.expr.clone(),
             level: self.level,
             has_matched: AtomicBool::new(false),
         }
@@ -480,11 +1137,17 @@ impl SpanMatch {
 
     #[inline(never)]
     fn is_matched_slow(&self) -> bool {
-        let matched = self
-            .fields
-            .values()
-            .all(|(_, matched)| matched.load(Acquire));
-        if matched {
+        let matched = match self.expr {
+            Some(ref expr) => expr.eval(&|i| self.fields[i].2.load(Acquire)),
+            None => self
+                .fields
+                .iter()
+                .all(|(_, _, matched)| matched.load(Acquire)),
+        };
+        // An expression containing negation may become unmatched again as
+        // more fields are recorded, so a match can only be cached for
+        // monotonic expressions.
+        if matched && self.expr.as_ref().map_or(true, Expr::is_monotonic) {
             self.has_matched.store(true, Release);
         }
         matched
@@ -502,72 +1165,108 @@ impl SpanMatch {
 
 impl Visit for MatchVisitor<'_> {
     fn record_f64(&mut self, field: &Field, value: f64) {
-        match self.inner.fields.get(field) {
-            Some((ValueMatch::NaN, ref matched)) if value.is_nan() => {
-                matched.store(true, Release);
+        for (f, matcher, matched) in &self.inner.fields {
+            if f != field {
+                continue;
             }
-            Some((ValueMatch::F64(ref e), ref matched))
-                if (value - *e).abs() < f64::EPSILON =>
-            {
-                matched.store(true, Release);
+            match matcher {
+                ValueMatch::NaN if value.is_nan() => {
+                    matched.store(true, Release);
+                }
+                ValueMatch::F64(ref e) if (value - *e).abs() < f64::EPSILON => {
+                    matched.store(true, Release);
+                }
+                ValueMatch::Cmp(ref e) if e.matches_f64(value) => {
+                    matched.store(true, Release);
+                }
+                _ => {}
             }
-            _ => {}
         }
     }
 
     fn record_i64(&mut self, field: &Field, value: i64) {
         use std::convert::TryInto;
 
-        match self.inner.fields.get(field) {
-            Some((ValueMatch::I64(ref e), ref matched)) if value == *e => {
-                matched.store(true, Release);
+        for (f, matcher, matched) in &self.inner.fields {
+            if f != field {
+                continue;
             }
-            Some((ValueMatch::U64(ref e), ref matched)) if Ok(value) == (*e).try_into() => {
-                matched.store(true, Release);
+            match matcher {
+                ValueMatch::I64(ref e) if value == *e => {
+                    matched.store(true, Release);
+                }
+                ValueMatch::U64(ref e) if Ok(value) == (*e).try_into() => {
+                    matched.store(true, Release);
+                }
+                ValueMatch::Cmp(ref e) if e.matches_i64(value) => {
+                    matched.store(true, Release);
+                }
+                _ => {}
             }
-            _ => {}
         }
     }
 
     fn record_u64(&mut self, field: &Field, value: u64) {
-        match self.inner.fields.get(field) {
-            Some((ValueMatch::U64(ref e), ref matched)) if value == *e => {
-                matched.store(true, Release);
+        for (f, matcher, matched) in &self.inner.fields {
+            if f != field {
+                continue;
+            }
+            match matcher {
+                ValueMatch::U64(ref e) if value == *e => {
+                    matched.store(true, Release);
+                }
+                ValueMatch::Cmp(ref e) if e.matches_u64(value) => {
+                    matched.store(true, Release);
+                }
+                _ => {}
             }
-            _ => {}
         }
     }
 
     fn record_bool(&mut self, field: &Field, value: bool) {
-        match self.inner.fields.get(field) {
-            Some((ValueMatch::Bool(ref e), ref matched)) if value == *e => {
-                matched.store(true, Release);
+        for (f, matcher, matched) in &self.inner.fields {
+            if f != field {
+                continue;
+            }
+            if let ValueMatch::Bool(ref e) = matcher {
+                if value == *e {
+                    matched.store(true, Release);
+                }
             }
-            _ => {}
         }
     }
 
     fn record_str(&mut self, field: &Field, value: &str) {
-        match self.inner.fields.get(field) {
-            Some((ValueMatch::Pat(ref e), ref matched)) if e.str_matches(&value) => {
-                matched.store(true, Release);
+        for (f, matcher, matched) in &self.inner.fields {
+            if f != field {
+                continue;
             }
-            Some((ValueMatch::Debug(ref e), ref matched)) if e.debug_matches(&value) => {
-                matched.store(true, Release)
+            match matcher {
+                ValueMatch::Pat(ref e) | ValueMatch::EventPat(ref e) if e.str_matches(&value) => {
+                    matched.store(true, Release);
+                }
+                ValueMatch::Debug(ref e) if e.debug_matches(&value) => {
+                    matched.store(true, Release)
+                }
+                _ => {}
             }
-            _ => {}
         }
     }
 
     fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
-        match self.inner.fields.get(field) {
-            Some((ValueMatch::Pat(ref e), ref matched)) if e.debug_matches(&value) => {
-                matched.store(true, Release);
+        for (f, matcher, matched) in &self.inner.fields {
+            if f != field {
+                continue;
             }
-            Some((ValueMatch::Debug(ref e), ref matched)) if e.debug_matches(&value) => {
-                matched.store(true, Release)
+            match matcher {
+                ValueMatch::Pat(ref e) | ValueMatch::EventPat(ref e) if e.debug_matches(&value) => {
+                    matched.store(true, Release);
+                }
+                ValueMatch::Debug(ref e) if e.debug_matches(&value) => {
+                    matched.store(true, Release)
+                }
+                _ => {}
             }
-            _ => {}
         }
     }
 }
@@ -623,4 +1322,141 @@ mod tests {
         };
         assert!(!matcher.debug_matches(&my_struct))
     }
+
+    #[test]
+    fn comparison_directives_parse() {
+        let m = Match::parse("latency>100", false).expect("should parse");
+        assert_eq!(m.name, "latency");
+        assert_eq!(m.to_string(), "latency>100");
+        match m.value {
+            Some(ValueMatch::Cmp(ref cmp)) => {
+                assert_eq!(cmp.op, CmpOp::Gt);
+                assert!(cmp.matches_u64(101));
+                assert!(!cmp.matches_u64(100));
+            }
+            ref value => panic!("expected a comparison matcher, got {:?}", value),
+        }
+
+        let m = Match::parse("latency>=2.5", true).expect("should parse");
+        assert_eq!(m.to_string(), "latency>=2.5");
+        match m.value {
+            Some(ValueMatch::Cmp(ref cmp)) => {
+                assert_eq!(cmp.op, CmpOp::Ge);
+                assert!(cmp.matches_f64(2.5));
+                assert!(!cmp.matches_f64(2.4));
+            }
+            ref value => panic!("expected a comparison matcher, got {:?}", value),
+        }
+
+        assert!(Match::parse("latency>not_a_number", false).is_err());
+    }
+
+    #[test]
+    fn comparison_bounds_convert_between_types() {
+        let m = Match::parse("level<=-1", false).expect("should parse");
+        match m.value {
+            Some(ValueMatch::Cmp(ref cmp)) => {
+                assert_eq!(cmp.op, CmpOp::Le);
+                assert!(cmp.matches_i64(-1));
+                assert!(!cmp.matches_i64(0));
+                // any `u64` value is greater than a negative bound.
+                assert!(!cmp.matches_u64(0));
+            }
+            ref value => panic!("expected a comparison matcher, got {:?}", value),
+        }
+
+        let m = Match::parse("count<10", false).expect("should parse");
+        match m.value {
+            Some(ValueMatch::Cmp(ref cmp)) => {
+                assert!(cmp.matches_i64(-3));
+                assert!(cmp.matches_f64(9.99));
+                assert!(!cmp.matches_u64(10));
+            }
+            ref value => panic!("expected a comparison matcher, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn event_pattern_directives_parse() {
+        let m = Match::parse("message=~\"timeout|refused\"", true).expect("should parse");
+        assert_eq!(m.name, "message");
+        assert_eq!(m.to_string(), "message=~timeout|refused");
+        match m.value {
+            Some(ValueMatch::EventPat(ref pat)) => {
+                assert!(pat.debug_matches(&"connection refused by peer"));
+                assert!(pat.str_matches(&"request timeout after 30s"));
+                assert!(!pat.debug_matches(&"connection reset"));
+            }
+            ref value => panic!("expected an event pattern matcher, got {:?}", value),
+        }
+
+        // `=~` matches are always regular expressions, so they cannot be
+        // parsed when regular expressions are disabled.
+        assert!(Match::parse("message=~\"timeout\"", false).is_err());
+    }
+
+    #[test]
+    fn expression_parse() {
+        // The plain list syntax parses without an expression, so existing
+        // directives match exactly as before.
+        let (preds, expr) = parse_expression("a=1,b=2", true).expect("should parse");
+        assert_eq!(preds.len(), 2);
+        assert_eq!(expr, None);
+
+        let (preds, expr) = parse_expression("(a=1 and b=2) or c=3", true).expect("should parse");
+        assert_eq!(preds.len(), 3);
+        assert_eq!(
+            expr,
+            Some(Expr::Or(vec![
+                Expr::And(vec![Expr::Pred(0), Expr::Pred(1)]),
+                Expr::Pred(2),
+            ]))
+        );
+
+        let (preds, expr) = parse_expression("not a=1", true).expect("should parse");
+        assert_eq!(preds.len(), 1);
+        assert_eq!(expr, Some(Expr::Not(Box::new(Expr::Pred(0)))));
+    }
+
+    #[test]
+    fn expression_parse_errors_have_spans() {
+        let err = parse_expression("a=1 or or b=2", true).expect_err("should not parse");
+        let msg = err.to_string();
+        assert!(
+            msg.contains("expected a field predicate"),
+            "unexpected error: {}",
+            msg
+        );
+        assert!(
+            msg.contains("(at `or`, characters 7..9)"),
+            "unexpected error: {}",
+            msg
+        );
+
+        let err = parse_expression("a=1 and (b=2", true).expect_err("should not parse");
+        let msg = err.to_string();
+        assert!(
+            msg.contains("expected a closing `)`"),
+            "unexpected error: {}",
+            msg
+        );
+    }
+
+    #[test]
+    fn expression_quoted_values_may_contain_keywords() {
+        // A quoted value containing a keyword or whitespace is a single
+        // predicate, not an operator.
+        let (preds, expr) =
+            parse_expression("name=\"alice and bob\" or count=2", true).expect("should parse");
+        assert_eq!(preds.len(), 2);
+        assert_eq!(expr, Some(Expr::Or(vec![Expr::Pred(0), Expr::Pred(1)])));
+        assert_eq!(preds[0].name, "name");
+    }
+
+    #[test]
+    fn equality_with_angle_brackets_is_not_a_comparison() {
+        let m = Match::parse("field=<foo>", false).expect("should parse");
+        assert_eq!(m.name, "field");
+        assert!(matches!(m.value, Some(ValueMatch::Debug(_))));
+    }
 }
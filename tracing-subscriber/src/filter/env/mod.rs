@@ -14,14 +14,14 @@ use crate::{
     subscribe::{Context, Subscribe},
     sync::RwLock,
 };
+use crate::filter::directive::Match as _;
 use directive::ParseError;
 use std::{cell::RefCell, collections::HashMap, env, error::Error, fmt, str::FromStr};
 use thread_local::ThreadLocal;
 use tracing_core::{
     callsite,
     collect::{Collect, Interest},
-    field::Field,
-    span, Metadata,
+    span, Event, Metadata,
 };
 
 /// A [`Subscriber`] which filters spans and events based on a set of filter
@@ -57,6 +57,14 @@ use tracing_core::{
 /// - `target` matches the event or span's target. In general, this is the module path and/or crate name.
 ///   Examples of targets `h2`, `tokio::net`, or `tide::server`. For more information on targets,
 ///   please refer to [`Metadata`]'s documentation.
+/// - In place of a `target`, a span or event's source location may be matched
+///   with a glob pattern prefixed by `file:` or `module:`. For example,
+///   `file:src/net/**=trace` enables everything recorded in files under
+///   `src/net`, and `module:myapp::db::*=debug` enables everything in modules
+///   directly inside `myapp::db`. In these patterns, `*` matches any sequence
+///   of characters except the path separator, `**` also matches separators,
+///   and `?` matches a single character. Globs are compiled when the
+///   directive is parsed.
 /// - `span` matches on the span's name. If a `span` directive is provided alongside a `target`,
 ///   the `span` directive will match on spans _within_ the `target`.
 /// - `field` matches on [fields] within spans. Field names can also be supplied without a `value`
@@ -65,7 +73,30 @@ use tracing_core::{
 /// - `value` matches on the value of a span's field. If a value is a numeric literal or a bool,
 ///   it will match _only_ on that value. Otherwise, this filter matches the
 ///   [`std::fmt::Debug`] output from the value.
+/// - Numeric field values may also be matched against a bound using the
+///   comparison operators `>`, `>=`, `<`, and `<=` in place of `=`. For
+///   example, `[request{latency_ms>250}]=debug` enables spans whose
+///   `latency_ms` field is recorded with a numeric value greater than 250.
+/// - Event field values may be matched against an *unanchored* regular
+///   expression using `=~` in place of `=`. For example,
+///   `my_crate[{message=~"timeout|refused"}]=trace` enables events in
+///   `my_crate` whose message contains "timeout" or "refused" anywhere in its
+///   output. Unlike other field value filters, `=~` directives are checked
+///   when the event is recorded, so they match the field values the event
+///   actually has, rather than enabling all events at the directive's level.
+///   Because `=~` matches are always regular expressions, they cannot be used
+///   when regular expressions are [disabled](Builder::with_regex).
 /// - `level` sets a maximum verbosity level accepted by this directive.
+/// - Field predicates inside the `{}` may be combined into boolean
+///   expressions with the `and`, `or`, and `not` keywords and parenthesized
+///   groups, such as `[span{(a=1 and b=2) or c=3}]=debug`. A comma between
+///   predicates is equivalent to `and`. `not` inverts a predicate or group,
+///   matching when it does *not* match the recorded values.
+/// - Prefixing a directive with `!` negates it: the spans and events it
+///   matches are *disabled*, even if another directive would enable them. For
+///   example, `info,!hyper[{tenant=acme}]=info` records everything at the
+///   `info` level except `hyper` events whose `tenant` field is `acme`.
+///   Negated directives may not match on span names.
 ///
 /// When a field value directive (`[{<FIELD NAME>=<FIELD_VALUE>}]=...`) matches a
 /// value's [`std::fmt::Debug`] output (i.e., the field value in the directive
@@ -197,15 +228,25 @@ use tracing_core::{
 pub struct EnvFilter {
     statics: directive::Statics,
     dynamics: directive::Dynamics,
+    event_directives: Vec<Directive>,
+    /// Negated directives without value predicates, which disable everything
+    /// they match.
+    exclusions: Vec<Directive>,
+    /// Negated directives with value predicates, which disable events whose
+    /// recorded field values match.
+    event_exclusions: Vec<Directive>,
     has_dynamics: bool,
     by_id: RwLock<HashMap<span::Id, directive::SpanMatcher>>,
     by_cs: RwLock<HashMap<callsite::Identifier, directive::CallsiteMatcher>>,
+    /// Cached verdicts of the static directive table, recorded when each
+    /// callsite is registered, so that re-checking a callsite (when its
+    /// cached interest is `sometimes`) is a single map lookup rather than a
+    /// scan of the directives.
+    static_verdicts: RwLock<HashMap<callsite::Identifier, bool>>,
     scope: ThreadLocal<RefCell<Vec<LevelFilter>>>,
     regex: bool,
 }
 
-type FieldMap<T> = HashMap<Field, T>;
-
 /// Indicates that an error occurred while parsing a `EnvFilter` from an
 /// environment variable.
 #[cfg_attr(docsrs, doc(cfg(all(feature = "env-filter", feature = "std"))))]
@@ -455,7 +496,15 @@ impl EnvFilter {
         if !self.regex {
             directive.deregexify();
         }
-        if let Some(stat) = directive.to_static() {
+        if directive.is_negated() {
+            if directive.has_value_fields() {
+                self.event_exclusions.push(directive);
+            } else {
+                self.exclusions.push(directive);
+            }
+        } else if directive.is_event_matcher() {
+            self.event_directives.push(directive);
+        } else if let Some(stat) = directive.to_static() {
             self.statics.add(stat)
         } else {
             self.has_dynamics = true;
@@ -473,8 +522,32 @@ impl EnvFilter {
     /// [`Filter::enabled`] methods on `EnvFilter`'s implementations of those
     /// traits, but it does not require the trait to be in scope.
     pub fn enabled<C>(&self, metadata: &Metadata<'_>, _: Context<'_, C>) -> bool {
+        if self.metadata_enabled(metadata) {
+            return true;
+        }
+
+        // An event-time directive may still enable this event once its field
+        // values are recorded.
+        metadata.is_event()
+            && self
+                .event_directives
+                .iter()
+                .any(|d| d.level >= *metadata.level() && d.cares_about(metadata))
+    }
+
+    fn metadata_enabled(&self, metadata: &Metadata<'_>) -> bool {
         let level = metadata.level();
 
+        // A negated directive disables everything it matches, regardless of
+        // what any other directive would enable.
+        if self
+            .exclusions
+            .iter()
+            .any(|directive| directive.level >= *level && directive.cares_about(metadata))
+        {
+            return false;
+        }
+
         // is it possible for a dynamic filter directive to enable this event?
         // if not, we can avoid the thread local access + iterating over the
         // spans in the current scope.
@@ -508,6 +581,16 @@ impl EnvFilter {
 
         // is it possible for a static filter directive to enable this event?
         if self.statics.max_level >= *level {
+            // In the steady state, the static verdict for this callsite was
+            // cached when it was registered.
+            if let Some(verdict) = self
+                .static_verdicts
+                .read()
+                .ok()
+                .and_then(|verdicts| verdicts.get(&metadata.callsite()).copied())
+            {
+                return verdict;
+            }
             // Otherwise, fall back to checking if the callsite is
             // statically enabled.
             return self.statics.enabled(metadata);
@@ -516,6 +599,61 @@ impl EnvFilter {
         false
     }
 
+    /// Returns `true` if the provided [`Event`] should be recorded, based on
+    /// the values of its fields.
+    ///
+    /// This is equivalent to calling the [`Subscribe::event_enabled`] or
+    /// [`Filter::event_enabled`] methods on `EnvFilter`'s implementations of
+    /// those traits, but it does not require the trait to be in scope.
+    ///
+    /// [`Filter::event_enabled`]: crate::subscribe::Filter::event_enabled
+    pub fn event_enabled<C>(&self, event: &Event<'_>, _: Context<'_, C>) -> bool {
+        // If no directives match on event field values, every event that
+        // passed `enabled` is recorded; skip visiting its fields entirely.
+        if self.event_directives.is_empty() && self.event_exclusions.is_empty() {
+            return true;
+        }
+
+        let metadata = event.metadata();
+        let level = metadata.level();
+
+        // A negated directive with value predicates disables this event if
+        // its recorded field values match, regardless of what any other
+        // directive would enable.
+        for directive in &self.event_exclusions {
+            if directive.level < *level || !directive.cares_about(metadata) {
+                continue;
+            }
+            if let Some(matcher) = directive.field_matcher(metadata) {
+                let matcher = matcher.to_span_match();
+                event.record(&mut matcher.visitor());
+                if matcher.is_matched() {
+                    return false;
+                }
+            }
+        }
+
+        if self.metadata_enabled(metadata) {
+            // The event was already enabled by a static or dynamic directive;
+            // event-time directives can only enable *additional* events.
+            return true;
+        }
+
+        self.event_directives.iter().any(|directive| {
+            if directive.level < *level || !directive.cares_about(metadata) {
+                return false;
+            }
+            match directive.field_matcher(metadata) {
+                Some(matcher) => {
+                    let matcher = matcher.to_span_match();
+                    event.record(&mut matcher.visitor());
+                    matcher.is_matched()
+                }
+                None => false,
+            }
+        })
+    }
+
     /// Returns an optional hint of the highest [verbosity level][level] that
     /// this `EnvFilter` will enable.
     ///
@@ -531,10 +669,14 @@ impl EnvFilter {
             // until recording.
             return Some(LevelFilter::TRACE);
         }
-        std::cmp::max(
+        let hint = std::cmp::max(
             self.statics.max_level.into(),
             self.dynamics.max_level.into(),
-        )
+        );
+        // Event-time directives are stored in neither table, so their levels
+        // must be considered as well.
+        let event_level = self.event_directives.iter().map(|d| d.level).max();
+        std::cmp::max(hint, event_level)
     }
 
     /// Informs the filter that a new span was created.
@@ -616,6 +758,27 @@ impl EnvFilter {
     }
 
     fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        // A negated directive disables everything it matches, so the callsite
+        // can never be enabled.
+        if self
+            .exclusions
+            .iter()
+            .any(|d| d.level >= *metadata.level() && d.cares_about(metadata))
+        {
+            return Interest::never();
+        }
+
+        // A negated directive with value predicates can only determine
+        // whether this event is disabled once its field values are recorded.
+        if metadata.is_event()
+            && self
+                .event_exclusions
+                .iter()
+                .any(|d| d.level >= *metadata.level() && d.cares_about(metadata))
+        {
+            return Interest::sometimes();
+        }
+
         if self.has_dynamics && metadata.is_span() {
             // If this metadata describes a span, first, check if there is a
             // dynamic filter that should be constructed for it. If so, it
@@ -627,9 +790,25 @@ impl EnvFilter {
             }
         }
 
-        // Otherwise, check if any of our static filters enable this metadata.
-        if self.statics.enabled(metadata) {
+        // Otherwise, check if any of our static filters enable this metadata,
+        // caching the verdict so that re-checking the callsite later (if its
+        // interest ends up cached as `sometimes`) doesn't re-scan the
+        // directives.
+        let statically_enabled = self.statics.enabled(metadata);
+        if let Ok(mut verdicts) = self.static_verdicts.write() {
+            verdicts.insert(metadata.callsite(), statically_enabled);
+        }
+        if statically_enabled {
             Interest::always()
+        } else if metadata.is_event()
+            && self
+                .event_directives
+                .iter()
+                .any(|d| d.cares_about(metadata))
+        {
+            // An event-time directive can only determine whether this event
+            // is enabled once its field values are recorded.
+            Interest::sometimes()
         } else {
             self.base_interest()
         }
@@ -652,6 +831,11 @@ impl<C: Collect> Subscribe<C> for EnvFilter {
         self.enabled(metadata, ctx)
     }
 
+    #[inline]
+    fn event_enabled(&self, event: &Event<'_>, ctx: Context<'_, C>) -> bool {
+        self.event_enabled(event, ctx)
+    }
+
     #[inline]
     fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
         self.on_new_span(attrs, id, ctx)
@@ -688,6 +872,11 @@ feature! {
             self.enabled(meta, ctx.clone())
         }
 
+        #[inline]
+        fn event_enabled(&self, event: &Event<'_>, ctx: &Context<'_, C>) -> bool {
+            self.event_enabled(event, ctx.clone())
+        }
+
         #[inline]
         fn callsite_enabled(&self, meta: &'static Metadata<'static>) -> Interest {
             self.register_callsite(meta)
@@ -762,7 +951,7 @@ impl fmt::Display for EnvFilter {
         };
 
         let mut dynamics = self.dynamics.iter();
-        if let Some(next) = dynamics.next() {
+        let wrote_dynamics = if let Some(next) = dynamics.next() {
             if wrote_statics {
                 f.write_str(",")?;
             }
@@ -770,6 +959,23 @@ impl fmt::Display for EnvFilter {
             for directive in dynamics {
                 write!(f, ",{}", directive)?;
             }
+            true
+        } else {
+            false
+        };
+
+        let mut wrote_any = wrote_statics || wrote_dynamics;
+        let others = self
+            .event_directives
+            .iter()
+            .chain(self.exclusions.iter())
+            .chain(self.event_exclusions.iter());
+        for directive in others {
+            if wrote_any {
+                f.write_str(",")?;
+            }
+            fmt::Display::fmt(directive, f)?;
+            wrote_any = true;
         }
         Ok(())
     }
@@ -941,6 +1147,28 @@ mod tests {
         assert!(interest.is_never());
     }
 
+    #[test]
+    fn callsite_enabled_by_location_glob() {
+        let filter = EnvFilter::new("file:src/net/**=debug").with_collector(NoCollector);
+        static META: &Metadata<'static> = &Metadata::new(
+            "mySpan",
+            "app",
+            Level::DEBUG,
+            Some("src/net/tcp.rs"),
+            Some(42),
+            Some("app::net::tcp"),
+            FieldSet::new(&[], identify_callsite!(&Cs)),
+            Kind::SPAN,
+        );
+
+        let interest = filter.register_callsite(META);
+        assert!(interest.is_always());
+
+        let filter = EnvFilter::new("module:app::db::*=debug").with_collector(NoCollector);
+        let interest = filter.register_callsite(META);
+        assert!(interest.is_never());
+    }
+
     #[test]
     fn roundtrip() {
         let f1: EnvFilter =
@@ -15,12 +15,36 @@ feature! {
     #![all(feature = "env-filter", feature = "std")]
     mod env;
     pub use self::env::*;
+
+    pub mod named;
+    pub use self::named::{registry, FilterRegistry};
 }
 
 feature! {
     #![all(feature = "registry", feature = "std")]
     mod subscriber_filters;
     pub use self::subscriber_filters::*;
+
+    mod boost;
+    pub use self::boost::{BoostFilter, BoostHandle};
+
+    mod dedup;
+    pub use self::dedup::DedupFilter;
+
+    mod rate_limit;
+    pub use self::rate_limit::RateLimitFilter;
+
+    mod sampling;
+    pub use self::sampling::SamplingFilter;
+
+    mod tail_sampling;
+    pub use self::tail_sampling::{TailPolicy, TailSampling};
+
+    mod span_scope;
+    pub use self::span_scope::{CaptureSpanFields, CapturedFields, CapturedValue, SpanScopeFilter};
+
+    mod stats;
+    pub use self::stats::{CallsiteStats, FilterStats, StatsHandle};
 }
 
 pub use self::filter_fn::*;
@@ -33,6 +57,12 @@ pub use self::level::{LevelFilter, ParseError as LevelParseError};
 #[allow(unused_imports)]
 pub(crate) use self::has_psf_stubs::*;
 
+feature! {
+    #![feature = "std"]
+    mod watch;
+    pub use self::watch::{watch_file, FileWatcher};
+}
+
 feature! {
     #![any(feature = "std", feature = "alloc")]
     pub mod targets;
@@ -0,0 +1,180 @@
+//! A process-wide registry of named, reloadable filters.
+//!
+//! When composing many [`Filtered`] subscribers — one per exporter, for
+//! example — there is ordinarily no way to address an individual
+//! subscriber's filter after the collector has been built. This module
+//! provides a global [`FilterRegistry`] where an [`EnvFilter`] can be
+//! [registered] under a string name when the collector is assembled, and
+//! later fetched or replaced at runtime by that name, enabling per-exporter
+//! verbosity control from admin tooling.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{filter::{self, EnvFilter}, prelude::*};
+//!
+//! let fmt = tracing_subscriber::fmt::subscriber()
+//!     .with_filter(filter::registry().register("console", EnvFilter::new("info")));
+//! tracing_subscriber::registry().with(fmt).init();
+//!
+//! // ... later, from a control endpoint:
+//! filter::registry()
+//!     .set("console", EnvFilter::new("debug"))
+//!     .expect("the `console` filter should be registered");
+//! ```
+//!
+//! [`Filtered`]: crate::filter::Filtered
+//! [registered]: FilterRegistry::register
+use crate::{filter::EnvFilter, reload};
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, error, fmt, sync::Mutex};
+
+/// Returns the global [`FilterRegistry`].
+pub fn registry() -> &'static FilterRegistry {
+    static REGISTRY: Lazy<FilterRegistry> = Lazy::new(|| FilterRegistry {
+        handles: Mutex::new(HashMap::new()),
+    });
+    &REGISTRY
+}
+
+/// A registry of named, reloadable [`EnvFilter`]s.
+///
+/// A `FilterRegistry` maps string names to [`reload::Handle`]s, so that a
+/// per-subscriber filter can be replaced at runtime by whoever knows its
+/// name. The global instance is returned by [`registry()`]; see the
+/// [module-level documentation][self] for details.
+#[derive(Debug)]
+pub struct FilterRegistry {
+    handles: Mutex<HashMap<String, reload::Handle<EnvFilter>>>,
+}
+
+/// Indicates that a registered filter could not be fetched or replaced.
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    /// No filter is registered under the given name.
+    NotFound(String),
+    /// The filter's collector was dropped, or its lock was poisoned.
+    Reload(reload::Error),
+}
+
+// === impl FilterRegistry ===
+
+impl FilterRegistry {
+    /// Registers `filter` under `name`, returning a reloadable filter to be
+    /// passed to [`with_filter`].
+    ///
+    /// The returned [`reload::Subscriber`] wraps `filter`; the registry keeps
+    /// a [`reload::Handle`] to it, so that the filter can later be replaced
+    /// with [`set`] or inspected with [`get`]. Registering a second filter
+    /// under the same name replaces the previous registration.
+    ///
+    /// [`with_filter`]: crate::subscribe::CollectExt
+    /// [`set`]: FilterRegistry::set
+    /// [`get`]: FilterRegistry::get
+    pub fn register(
+        &self,
+        name: impl Into<String>,
+        filter: EnvFilter,
+    ) -> reload::Subscriber<EnvFilter> {
+        let (filter, handle) = reload::Subscriber::new(filter);
+        self.lock().insert(name.into(), handle);
+        filter
+    }
+
+    /// Replaces the filter registered under `name` with `filter`.
+    ///
+    /// Returns an error if no filter is registered under `name`, or if the
+    /// registered filter's collector has been dropped.
+    pub fn set(&self, name: &str, filter: EnvFilter) -> Result<(), Error> {
+        self.handle(name)
+            .ok_or_else(|| Error::not_found(name))?
+            .reload(filter)
+            .map_err(Error::reload)
+    }
+
+    /// Returns the directives of the filter registered under `name`.
+    ///
+    /// Returns an error if no filter is registered under `name`, or if the
+    /// registered filter's collector has been dropped.
+    pub fn get(&self, name: &str) -> Result<String, Error> {
+        self.handle(name)
+            .ok_or_else(|| Error::not_found(name))?
+            .with_current(ToString::to_string)
+            .map_err(Error::reload)
+    }
+
+    /// Returns a [`reload::Handle`] to the filter registered under `name`,
+    /// if one is registered.
+    pub fn handle(&self, name: &str) -> Option<reload::Handle<EnvFilter>> {
+        self.lock().get(name).cloned()
+    }
+
+    /// Returns the names of all registered filters.
+    pub fn names(&self) -> Vec<String> {
+        self.lock().keys().cloned().collect()
+    }
+
+    /// Removes the registration under `name`, returning `true` if a filter
+    /// was registered.
+    ///
+    /// This does not affect the filter itself, only the registry's handle to
+    /// it.
+    pub fn deregister(&self, name: &str) -> bool {
+        self.lock().remove(name).is_some()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, reload::Handle<EnvFilter>>> {
+        // If a thread panicked while holding the lock, the map itself cannot
+        // have been left in an inconsistent state, so continue with it.
+        self.handles
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// === impl Error ===
+
+impl Error {
+    fn not_found(name: &str) -> Self {
+        Self {
+            kind: ErrorKind::NotFound(name.to_string()),
+        }
+    }
+
+    fn reload(error: reload::Error) -> Self {
+        Self {
+            kind: ErrorKind::Reload(error),
+        }
+    }
+
+    /// Returns `true` if this error was caused by no filter being registered
+    /// under the given name.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self.kind, ErrorKind::NotFound(_))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ErrorKind::NotFound(ref name) => {
+                write!(f, "no filter is registered under the name `{}`", name)
+            }
+            ErrorKind::Reload(ref err) => err.fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.kind {
+            ErrorKind::NotFound(_) => None,
+            ErrorKind::Reload(ref err) => Some(err),
+        }
+    }
+}
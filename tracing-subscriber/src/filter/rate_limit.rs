@@ -0,0 +1,330 @@
+//! A [`Filter`] that limits how many events are enabled per time window.
+//!
+//! See the [`RateLimitFilter`] documentation for details.
+//!
+//! [`Filter`]: crate::subscribe::Filter
+use crate::subscribe::{Context, Filter, Subscribe};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tracing_core::{
+    callsite::{self, Callsite},
+    field::{Field, FieldSet, Visit},
+    identify_callsite, Collect, Event, Interest, Kind, Level, Metadata,
+};
+
+/// A [`Filter`] that limits events to a maximum rate, suppressing the excess.
+///
+/// Bursts of identical errors — a downstream dependency failing, a retry
+/// loop spinning — can flood a log pipeline with events that add no new
+/// information. A `RateLimitFilter` applies a token bucket to each event
+/// *key*, enabling at most `max` events per key per `window` and suppressing
+/// the rest. By default, each callsite is limited independently; events may
+/// instead be keyed by their [target] or by the value of a named [field].
+///
+/// When a suppressed key's bucket refills and an event is enabled again, the
+/// filter emits a summary event at the `WARN` level, with the target
+/// `tracing_subscriber::filter::rate_limit` and a `suppressed` field
+/// recording how many events were discarded. Summary events are never rate
+/// limited.
+///
+/// Spans are not rate limited; only events consume tokens.
+///
+/// Like [`EnvFilter`], a `RateLimitFilter` may be used in two ways: as a
+/// [`Subscribe`] layered over an entire subscriber stack, limiting events
+/// globally, or as a per-subscriber [`Filter`] (with
+/// [`with_filter`][plf]), limiting events for a single subscriber. Summary
+/// events are dispatched through [`Context::event`], so they are recorded
+/// by the subscribers *below* the rate limiter in the stack. When rate
+/// limiting a single subscriber, prefer the [`Subscribe`] form layered
+/// directly above it, so that the subscriber sees the summaries for the
+/// events it lost.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tracing_subscriber::{filter::RateLimitFilter, prelude::*};
+///
+/// tracing_subscriber::registry()
+///     .with(tracing_subscriber::fmt::subscriber())
+///     // Allow each callsite to emit at most 10 events per second.
+///     .with(RateLimitFilter::new(10, Duration::from_secs(1)))
+///     .init();
+/// ```
+///
+/// # Notes
+///
+/// The filter keeps one bucket per key behind a [`Mutex`]. When keying by
+/// field value, the number of buckets grows with the number of distinct
+/// values observed, so the keying field should have bounded cardinality.
+///
+/// [`EnvFilter`]: crate::filter::EnvFilter
+/// [`Filter`]: crate::subscribe::Filter
+/// [`Subscribe`]: crate::subscribe::Subscribe
+/// [`Context::event`]: crate::subscribe::Context::event
+/// [plf]: crate::subscribe::Subscribe::with_filter
+/// [target]: RateLimitFilter::per_target
+/// [field]: RateLimitFilter::per_field
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug)]
+pub struct RateLimitFilter {
+    max: u64,
+    window: Duration,
+    key_by: KeyBy,
+    buckets: Mutex<HashMap<BucketKey, Bucket>>,
+}
+
+/// How events are grouped into buckets.
+#[derive(Debug)]
+enum KeyBy {
+    Callsite,
+    Target,
+    Field(String),
+}
+
+#[derive(Debug, Hash, PartialEq, Eq)]
+enum BucketKey {
+    Callsite(callsite::Identifier),
+    Target(String),
+    FieldValue(String),
+}
+
+/// A token bucket for a single key.
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    suppressed: u64,
+}
+
+static SUMMARY_FIELDS: &[&str] = &["message", "suppressed"];
+struct SummaryCallsite;
+static SUMMARY_CALLSITE: SummaryCallsite = SummaryCallsite;
+static SUMMARY_META: Metadata<'static> = Metadata::new(
+    "rate limit summary",
+    "tracing_subscriber::filter::rate_limit",
+    Level::WARN,
+    None,
+    None,
+    None,
+    FieldSet::new(SUMMARY_FIELDS, identify_callsite!(&SUMMARY_CALLSITE)),
+    Kind::EVENT,
+);
+
+impl Callsite for SummaryCallsite {
+    fn set_interest(&self, _: Interest) {}
+
+    fn metadata(&self) -> &'static Metadata<'static> {
+        &SUMMARY_META
+    }
+}
+
+// === impl RateLimitFilter ===
+
+impl RateLimitFilter {
+    /// Returns a new `RateLimitFilter` that enables at most `max` events per
+    /// `window`, per callsite.
+    ///
+    /// Events are limited with a token bucket: each key may burst up to `max`
+    /// events, and tokens are replenished continuously at a rate of `max` per
+    /// `window`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero or `window` is a zero-length duration, as the
+    /// resulting filter would either enable nothing or rate limit nothing.
+    pub fn new(max: u64, window: Duration) -> Self {
+        assert!(max > 0, "a rate limit of 0 events would disable all events");
+        assert!(
+            window > Duration::from_secs(0),
+            "a zero-length window would not rate limit anything"
+        );
+        Self {
+            max,
+            window,
+            key_by: KeyBy::Callsite,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Limits events by their target, rather than per callsite.
+    ///
+    /// All events sharing a target draw tokens from the same bucket.
+    pub fn per_target(self) -> Self {
+        Self {
+            key_by: KeyBy::Target,
+            ..self
+        }
+    }
+
+    /// Limits events by the value of the field named `field`, rather than
+    /// per callsite.
+    ///
+    /// All events recording the same value for `field` draw tokens from the
+    /// same bucket. Events that do not record the field are not rate
+    /// limited.
+    pub fn per_field(self, field: impl Into<String>) -> Self {
+        Self {
+            key_by: KeyBy::Field(field.into()),
+            ..self
+        }
+    }
+
+    /// Decides whether `event` is enabled, taking a token from its bucket
+    /// and emitting a summary through `cx` if the bucket has refilled after
+    /// running dry.
+    fn limit<C: Collect>(&self, event: &Event<'_>, cx: &Context<'_, C>) -> bool {
+        let meta = event.metadata();
+        // The filter's own summary events are exempt, so that a busy bucket
+        // cannot suppress its own summaries (or recurse).
+        if core::ptr::eq::<Metadata<'_>>(meta, &SUMMARY_META) {
+            return true;
+        }
+
+        let key = match self.key_by {
+            KeyBy::Callsite => BucketKey::Callsite(meta.callsite()),
+            KeyBy::Target => BucketKey::Target(meta.target().to_string()),
+            KeyBy::Field(ref field) => {
+                let mut visitor = FieldValueVisitor { field, value: None };
+                event.record(&mut visitor);
+                match visitor.value {
+                    Some(value) => BucketKey::FieldValue(value),
+                    // Events that don't record the field are not limited.
+                    None => return true,
+                }
+            }
+        };
+
+        let (enabled, suppressed) = self.take(key);
+        if suppressed > 0 {
+            Self::emit_summary(cx, suppressed);
+        }
+        enabled
+    }
+
+    /// Returns the interest for `meta`, shared by the `Subscribe` and
+    /// `Filter` implementations.
+    fn interest(meta: &'static Metadata<'static>) -> Interest {
+        if meta.is_event() {
+            // Whether an event is enabled depends on how full its bucket is
+            // at the time, so the decision can never be cached.
+            Interest::sometimes()
+        } else {
+            Interest::always()
+        }
+    }
+
+    /// Takes a token from the bucket for `key`, returning whether the event
+    /// should be enabled, along with the number of events that were
+    /// suppressed before this one if the bucket had run dry in the meantime.
+    fn take(&self, key: BucketKey) -> (bool, u64) {
+        let now = Instant::now();
+        let mut buckets = match self.buckets.lock() {
+            Ok(buckets) => buckets,
+            // A panic while the lock was held can't have left a bucket in an
+            // inconsistent state, so keep going with the map as it is.
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: self.max as f64,
+            last_refill: now,
+            suppressed: 0,
+        });
+
+        let rate = self.max as f64 / self.window.as_secs_f64();
+        let elapsed = now.saturating_duration_since(bucket.last_refill);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * rate).min(self.max as f64);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            (true, std::mem::take(&mut bucket.suppressed))
+        } else {
+            bucket.suppressed += 1;
+            (false, 0)
+        }
+    }
+
+    /// Emits the summary event recording that `suppressed` events were
+    /// discarded since the last enabled event with the same key.
+    ///
+    /// The summary is dispatched through the [`Context`] rather than the
+    /// default dispatcher: this is called while the triggering event is being
+    /// dispatched, and the dispatcher guards against re-entrancy by handing
+    /// out a no-op `Dispatch` for the duration.
+    fn emit_summary<C: Collect>(cx: &Context<'_, C>, suppressed: u64) {
+        if !cx.enabled(&SUMMARY_META) {
+            return;
+        }
+        let fields = SUMMARY_META.fields();
+        let message = fields.field("message").expect("message field must exist");
+        let count = fields
+            .field("suppressed")
+            .expect("suppressed field must exist");
+        cx.event(&Event::new(
+            &SUMMARY_META,
+            &fields.value_set(&[
+                (
+                    &message,
+                    Some(&format_args!("events were suppressed by the rate limit")
+                        as &dyn tracing_core::field::Value),
+                ),
+                (&count, Some(&suppressed as &dyn tracing_core::field::Value)),
+            ]),
+        ));
+    }
+}
+
+impl<C: Collect> Filter<C> for RateLimitFilter {
+    fn enabled(&self, _meta: &Metadata<'_>, _: &Context<'_, C>) -> bool {
+        // Only events consume tokens, and they are counted in
+        // `event_enabled`, once per event that would otherwise be recorded.
+        true
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, cx: &Context<'_, C>) -> bool {
+        self.limit(event, cx)
+    }
+
+    fn callsite_enabled(&self, meta: &'static Metadata<'static>) -> Interest {
+        Self::interest(meta)
+    }
+}
+
+impl<C: Collect> Subscribe<C> for RateLimitFilter {
+    #[inline]
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        Self::interest(metadata)
+    }
+
+    #[inline]
+    fn event_enabled(&self, event: &Event<'_>, cx: Context<'_, C>) -> bool {
+        self.limit(event, &cx)
+    }
+}
+
+// === impl FieldValueVisitor ===
+
+/// Records the value of a single named field, as a string.
+struct FieldValueVisitor<'a> {
+    field: &'a str,
+    value: Option<String>,
+}
+
+impl Visit for FieldValueVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == self.field {
+            self.value = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == self.field {
+            self.value = Some(format!("{:?}", value));
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! A [`Filter`] that samples whole traces by deciding at the root span.
+//!
+//! See the [`SamplingFilter`] documentation for details.
+//!
+//! [`Filter`]: crate::subscribe::Filter
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Filter},
+};
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tracing_core::{
+    field::{Field, Visit},
+    span, Collect, Event, Interest, Metadata,
+};
+
+/// A [`Filter`] that keeps or drops entire traces, deciding when the root
+/// span is created.
+///
+/// High-throughput services frequently cannot afford to record every trace.
+/// A `SamplingFilter` makes a single keep-or-drop decision per trace — at
+/// the root span — and applies that decision to every descendant span and
+/// to the events recorded inside them, so that sampled traces are always
+/// complete. Events recorded outside of any span are not part of a trace,
+/// and are always enabled.
+///
+/// By default, the decision is made randomly, keeping roughly `ratio` of
+/// all traces. Alternatively, [`keyed_by`] makes the decision by hashing
+/// the value of a trace-id-like field recorded on the root span, so that
+/// the same trace id is sampled identically on every service that sees it
+/// (and across restarts).
+///
+/// # Examples
+///
+/// ```
+/// use tracing_subscriber::{filter::SamplingFilter, prelude::*};
+///
+/// // Keep ten percent of all traces.
+/// let filter = SamplingFilter::new(0.1);
+///
+/// tracing_subscriber::registry()
+///     .with(tracing_subscriber::fmt::subscriber().with_filter(filter))
+///     .init();
+/// ```
+///
+/// # Notes
+///
+/// When sampling is keyed, the keying field's value is only available once
+/// the root span has been created, after the span has already been enabled.
+/// Keyed root spans are therefore always recorded, and the sampling
+/// decision applies to their descendants and events. Randomly sampled root
+/// spans are dropped outright.
+///
+/// [`Filter`]: crate::subscribe::Filter
+/// [`keyed_by`]: SamplingFilter::keyed_by
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug)]
+pub struct SamplingFilter {
+    ratio: f64,
+    key_field: Option<String>,
+    state: RandomState,
+    counter: AtomicU64,
+}
+
+/// The decision recorded on a keyed root span, consulted by its descendants.
+struct SampleDecision(bool);
+
+// === impl SamplingFilter ===
+
+impl SamplingFilter {
+    /// Returns a new `SamplingFilter` that randomly keeps roughly `ratio` of
+    /// all traces.
+    ///
+    /// A ratio of `1.0` keeps every trace, and a ratio of `0.0` drops every
+    /// trace.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` is not between `0.0` and `1.0`.
+    pub fn new(ratio: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&ratio),
+            "a sampling ratio must be between 0.0 and 1.0"
+        );
+        Self {
+            ratio,
+            key_field: None,
+            state: RandomState::new(),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Makes the sampling decision by hashing the value of the field named
+    /// `field` on the root span, rather than randomly.
+    ///
+    /// A given field value always produces the same decision, so services
+    /// that record the same trace id will sample the same traces. Root spans
+    /// that do not record the field are always kept.
+    pub fn keyed_by(self, field: impl Into<String>) -> Self {
+        Self {
+            key_field: Some(field.into()),
+            ..self
+        }
+    }
+
+    /// Randomly decides whether to keep a trace, honoring the configured
+    /// ratio.
+    fn draw(&self) -> bool {
+        let n = self.counter.fetch_add(1, Ordering::Relaxed);
+        let mut hasher = self.state.build_hasher();
+        hasher.write_u64(n);
+        self.keep(hasher.finish())
+    }
+
+    /// Returns whether a trace with the provided hash is kept.
+    fn keep(&self, hash: u64) -> bool {
+        self.ratio >= 1.0 || (hash as f64) < self.ratio * (u64::MAX as f64)
+    }
+
+    /// Returns the sampling decision recorded on `span`, if any.
+    ///
+    /// Spans that carry no recorded decision were kept: a randomly dropped
+    /// root is not visible to this filter at all, so any visible ancestor
+    /// either was sampled in, or defers to a keyed decision in its
+    /// extensions.
+    fn inherit<C>(span: &crate::registry::SpanRef<'_, C>) -> bool
+    where
+        C: for<'lookup> LookupSpan<'lookup>,
+    {
+        span.extensions()
+            .get::<SampleDecision>()
+            .map(|decision| decision.0)
+            .unwrap_or(true)
+    }
+
+    /// A stable 64-bit FNV-1a hash of a key, so that a given trace id
+    /// samples identically across processes and restarts.
+    fn hash_key(key: &str) -> u64 {
+        const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+        let mut hash = OFFSET;
+        for byte in key.as_bytes() {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+        hash
+    }
+}
+
+impl<C> Filter<C> for SamplingFilter
+where
+    C: Collect + for<'lookup> LookupSpan<'lookup>,
+{
+    fn enabled(&self, meta: &Metadata<'_>, cx: &Context<'_, C>) -> bool {
+        // `Context::current_span` reflects the collector's (unfiltered) view
+        // of the current span, while `Context::span` only resolves spans this
+        // filter enabled. A span that exists but cannot be resolved was
+        // dropped by this filter, and the rest of its trace follows it.
+        match cx.current_span().id() {
+            Some(id) => match cx.span(id) {
+                Some(parent) => Self::inherit(&parent),
+                None => false,
+            },
+            None if meta.is_span() => {
+                if self.key_field.is_some() {
+                    // The keying field's value is not available until the
+                    // span is created; the decision is made in `on_new_span`.
+                    true
+                } else {
+                    self.draw()
+                }
+            }
+            // An event outside of any span is not part of a trace.
+            None => true,
+        }
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, cx: &Context<'_, C>) -> bool {
+        if event.is_root() {
+            return true;
+        }
+        if let Some(span) = cx.event_span(event) {
+            return Self::inherit(&span);
+        }
+        // The event's parent span could not be resolved: either the parent
+        // was dropped by this filter, or the event is contextual and there
+        // is no current span at all.
+        event.is_contextual() && cx.current_span().id().is_none()
+    }
+
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, cx: Context<'_, C>) {
+        let field = match self.key_field.as_deref() {
+            Some(field) => field,
+            None => return,
+        };
+        let span = match cx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+        // Only root spans carry a decision; descendants follow it by lookup.
+        if span.parent().is_some() {
+            return;
+        }
+        let mut visitor = KeyFieldVisitor { field, value: None };
+        attrs.record(&mut visitor);
+        let keep = match visitor.value {
+            Some(value) => self.keep(Self::hash_key(&value)),
+            // Root spans that don't record the keying field are kept.
+            None => true,
+        };
+        span.extensions_mut().insert(SampleDecision(keep));
+    }
+
+    fn callsite_enabled(&self, _: &'static Metadata<'static>) -> Interest {
+        if self.ratio >= 1.0 && self.key_field.is_none() {
+            Interest::always()
+        } else {
+            // Each trace is sampled individually, so the decision for a
+            // callsite can never be cached.
+            Interest::sometimes()
+        }
+    }
+}
+
+// === impl KeyFieldVisitor ===
+
+/// Records the value of the keying field, as a string.
+struct KeyFieldVisitor<'a> {
+    field: &'a str,
+    value: Option<String>,
+}
+
+impl Visit for KeyFieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == self.field {
+            self.value = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == self.field {
+            self.value = Some(format!("{:?}", value));
+        }
+    }
+}
@@ -0,0 +1,393 @@
+//! A filter that enables spans and events based on field values recorded by
+//! their *ancestor* spans.
+//!
+//! See the [`SpanScopeFilter`] documentation for details.
+use crate::{
+    filter::LevelFilter,
+    registry::{LookupSpan, SpanRef},
+    subscribe::{Context, Filter, Subscribe},
+};
+use core::fmt;
+use tracing_core::{
+    field::{Field, Visit},
+    span, Collect, Event, Interest, Metadata,
+};
+
+/// A [`Filter`] that enables spans and events when an ancestor span in the
+/// current scope recorded a given field value.
+///
+/// Unlike [`EnvFilter`]'s field directives, which match values recorded by the
+/// span a directive names, this filter walks the entire span scope from the
+/// current span to the root, consulting values previously captured by a
+/// [`CaptureSpanFields`] subscriber. This allows enabling verbose events deep
+/// inside a request based on a value recorded once, at the top of the request
+/// — for example, enabling `DEBUG` events only for a particular tenant.
+///
+/// The filter enables:
+///
+/// - anything at or above the base level (see [`with_base_level`]; the
+///   default base level is [`OFF`]),
+/// - spans at or above the filter's level, so that the span scope exists when
+///   events inside it are filtered, and
+/// - events at or above the filter's level, *if* a span in the current scope
+///   recorded the expected field value.
+///
+/// A [`CaptureSpanFields`] subscriber capturing the filter's field must be
+/// added to the collector *before* any filtered subscribers, or no values
+/// will be found.
+///
+/// # Examples
+///
+/// ```
+/// use tracing_subscriber::{
+///     filter::{CaptureSpanFields, LevelFilter, SpanScopeFilter},
+///     prelude::*,
+/// };
+///
+/// // Enable `INFO` everywhere, and `DEBUG` events inside requests where an
+/// // ancestor span recorded `tenant_id = "acme"`.
+/// let filter = SpanScopeFilter::new("tenant_id", "acme", LevelFilter::DEBUG)
+///     .with_base_level(LevelFilter::INFO);
+///
+/// tracing_subscriber::registry()
+///     .with(CaptureSpanFields::new(["tenant_id"]))
+///     .with(tracing_subscriber::fmt::subscriber().with_filter(filter))
+///     .init();
+/// ```
+///
+/// [`EnvFilter`]: crate::filter::EnvFilter
+/// [`Filter`]: crate::subscribe::Filter
+/// [`with_base_level`]: SpanScopeFilter::with_base_level
+/// [`OFF`]: LevelFilter::OFF
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug, Clone)]
+pub struct SpanScopeFilter {
+    field: String,
+    value: CapturedValue,
+    level: LevelFilter,
+    base: LevelFilter,
+}
+
+/// A [`Subscribe`] implementation that captures the values of a set of span
+/// fields, so that they may be consulted by a [`SpanScopeFilter`].
+///
+/// Captured values are stored in the span's [extensions], as a
+/// [`CapturedFields`] instance; they may also be read by other subscribers.
+/// Only the named fields are captured, so the cost of capturing is
+/// proportional to the number of fields consulted by filters, not the number
+/// of fields spans record.
+///
+/// [extensions]: crate::registry::ExtensionsMut
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug, Clone)]
+pub struct CaptureSpanFields {
+    fields: Vec<String>,
+}
+
+/// Span field values captured by a [`CaptureSpanFields`] subscriber, stored
+/// in the span's [extensions].
+///
+/// [extensions]: crate::registry::Extensions
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug, Default)]
+pub struct CapturedFields {
+    values: Vec<(&'static str, CapturedValue)>,
+}
+
+/// A field value captured by a [`CaptureSpanFields`] subscriber.
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum CapturedValue {
+    /// A `bool` value.
+    Bool(bool),
+    /// An `i64` value.
+    I64(i64),
+    /// A `u64` value.
+    U64(u64),
+    /// An `f64` value.
+    F64(f64),
+    /// A string value, recorded either as a `&str` or as the value's
+    /// `fmt::Debug` output.
+    Str(String),
+}
+
+/// A visitor that records the values of a set of named fields into a
+/// [`CapturedFields`].
+struct CaptureVisitor<'a> {
+    names: &'a [String],
+    values: &'a mut CapturedFields,
+}
+
+// === impl SpanScopeFilter ===
+
+impl SpanScopeFilter {
+    /// Returns a new `SpanScopeFilter` that enables spans and events at or
+    /// above `level` when a span in the current scope recorded `value` for
+    /// the field named `field`.
+    ///
+    /// By default, nothing outside a matching scope is enabled; use
+    /// [`with_base_level`] to set a level that is enabled unconditionally.
+    ///
+    /// [`with_base_level`]: SpanScopeFilter::with_base_level
+    pub fn new(
+        field: impl Into<String>,
+        value: impl Into<CapturedValue>,
+        level: LevelFilter,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            value: value.into(),
+            level,
+            base: LevelFilter::OFF,
+        }
+    }
+
+    /// Sets a base level that is enabled unconditionally, regardless of the
+    /// current span scope.
+    ///
+    /// This allows using a single `SpanScopeFilter` to express "everything at
+    /// `INFO`, plus `DEBUG` inside matching scopes".
+    pub fn with_base_level(self, base: LevelFilter) -> Self {
+        Self { base, ..self }
+    }
+
+    fn scope_matches<'a, C>(&self, span: Option<SpanRef<'a, C>>) -> bool
+    where
+        C: for<'lookup> LookupSpan<'lookup>,
+    {
+        let span = match span {
+            Some(span) => span,
+            None => return false,
+        };
+        span.scope().any(|span| {
+            span.extensions()
+                .get::<CapturedFields>()
+                .and_then(|fields| fields.get(&self.field))
+                .map(|value| value.matches(&self.value))
+                .unwrap_or(false)
+        })
+    }
+}
+
+impl<C> Filter<C> for SpanScopeFilter
+where
+    C: Collect + for<'lookup> LookupSpan<'lookup>,
+{
+    fn enabled(&self, meta: &Metadata<'_>, cx: &Context<'_, C>) -> bool {
+        let level = meta.level();
+        if self.base >= *level {
+            return true;
+        }
+
+        if self.level < *level {
+            return false;
+        }
+
+        // Spans within the filter's level are always enabled, so that the
+        // span scope (and any captured field values) exists by the time the
+        // events inside it are filtered.
+        if meta.is_span() {
+            return true;
+        }
+
+        self.scope_matches(cx.lookup_current())
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, cx: &Context<'_, C>) -> bool {
+        let level = event.metadata().level();
+        if self.base >= *level {
+            return true;
+        }
+
+        // An event with an explicitly set parent may be outside the current
+        // scope, so its own span chain is checked rather than the scope that
+        // `enabled` saw.
+        self.scope_matches(cx.event_span(event))
+    }
+
+    fn callsite_enabled(&self, meta: &'static Metadata<'static>) -> Interest {
+        let level = meta.level();
+        if self.base >= *level || (meta.is_span() && self.level >= *level) {
+            Interest::always()
+        } else if self.level >= *level {
+            // Whether the event is enabled depends on the span scope it
+            // occurs in, so the decision cannot be cached.
+            Interest::sometimes()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(core::cmp::max(self.level, self.base))
+    }
+}
+
+// === impl CaptureSpanFields ===
+
+impl CaptureSpanFields {
+    /// Returns a new `CaptureSpanFields` subscriber that captures the values
+    /// of the fields with the given names.
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            fields: fields.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl<C> Subscribe<C> for CaptureSpanFields
+where
+    C: Collect + for<'lookup> LookupSpan<'lookup>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("span must already exist!");
+        let mut values = CapturedFields::default();
+        attrs.record(&mut CaptureVisitor {
+            names: &self.fields,
+            values: &mut values,
+        });
+        if !values.values.is_empty() {
+            span.extensions_mut().insert(values);
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, record: &span::Record<'_>, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("span must already exist!");
+        let mut extensions = span.extensions_mut();
+        if let Some(values) = extensions.get_mut::<CapturedFields>() {
+            record.record(&mut CaptureVisitor {
+                names: &self.fields,
+                values,
+            });
+            return;
+        }
+
+        let mut values = CapturedFields::default();
+        record.record(&mut CaptureVisitor {
+            names: &self.fields,
+            values: &mut values,
+        });
+        if !values.values.is_empty() {
+            extensions.insert(values);
+        }
+    }
+}
+
+// === impl CapturedFields ===
+
+impl CapturedFields {
+    /// Returns the captured value of the field with the given name, if one
+    /// was recorded.
+    pub fn get(&self, name: &str) -> Option<&CapturedValue> {
+        self.values
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| value)
+    }
+
+    fn insert(&mut self, name: &'static str, value: CapturedValue) {
+        if let Some((_, existing)) = self.values.iter_mut().find(|(field, _)| *field == name) {
+            *existing = value;
+        } else {
+            self.values.push((name, value));
+        }
+    }
+}
+
+// === impl CapturedValue ===
+
+impl CapturedValue {
+    fn matches(&self, other: &CapturedValue) -> bool {
+        use std::convert::TryFrom;
+        use CapturedValue::*;
+
+        match (self, other) {
+            // Integer values compare across signedness, since the recorded
+            // type depends on how the span's field was declared.
+            (I64(a), U64(b)) | (U64(b), I64(a)) => {
+                u64::try_from(*a).map(|a| a == *b).unwrap_or(false)
+            }
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl From<&str> for CapturedValue {
+    fn from(value: &str) -> Self {
+        CapturedValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for CapturedValue {
+    fn from(value: String) -> Self {
+        CapturedValue::Str(value)
+    }
+}
+
+impl From<bool> for CapturedValue {
+    fn from(value: bool) -> Self {
+        CapturedValue::Bool(value)
+    }
+}
+
+impl From<i64> for CapturedValue {
+    fn from(value: i64) -> Self {
+        CapturedValue::I64(value)
+    }
+}
+
+impl From<u64> for CapturedValue {
+    fn from(value: u64) -> Self {
+        CapturedValue::U64(value)
+    }
+}
+
+impl From<f64> for CapturedValue {
+    fn from(value: f64) -> Self {
+        CapturedValue::F64(value)
+    }
+}
+
+// === impl CaptureVisitor ===
+
+impl CaptureVisitor<'_> {
+    fn capture(&mut self, field: &Field, value: CapturedValue) {
+        if self.names.iter().any(|name| name == field.name()) {
+            self.values.insert(field.name(), value);
+        }
+    }
+}
+
+impl Visit for CaptureVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.capture(field, CapturedValue::F64(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.capture(field, CapturedValue::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.capture(field, CapturedValue::U64(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.capture(field, CapturedValue::Bool(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.capture(field, CapturedValue::Str(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        // Avoid formatting the value unless the field is actually captured.
+        if self.names.iter().any(|name| name == field.name()) {
+            self.values
+                .insert(field.name(), CapturedValue::Str(format!("{:?}", value)));
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! A [`Filter`] wrapper that counts how many spans and events its inner
+//! filter allowed and suppressed.
+//!
+//! See the [`FilterStats`] documentation for details.
+//!
+//! [`Filter`]: crate::subscribe::Filter
+use crate::subscribe::{Context, Filter, Subscribe};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tracing_core::{callsite, Collect, Interest, Level, Metadata};
+
+/// A [`Filter`] wrapper that counts the decisions its inner filter makes.
+///
+/// Filtering configuration tends to accrete: directives are added during
+/// incidents and never removed, and it is hard to tell from the output alone
+/// which directives are doing work and which noisy targets should be
+/// filtered next. A `FilterStats` wraps any filter — an [`EnvFilter`], a
+/// [`Targets`], or a custom one — and counts, per callsite, how many spans
+/// and events the inner filter allowed and how many it suppressed. The
+/// counts are exposed through a [`StatsHandle`] as a [snapshot
+/// API](StatsHandle::callsites).
+///
+/// # Examples
+///
+/// ```
+/// use tracing_subscriber::{filter::{EnvFilter, FilterStats}, prelude::*};
+///
+/// let filter = EnvFilter::new("info,my_crate=debug");
+/// let (filter, handle) = FilterStats::new(filter);
+///
+/// let collector = tracing_subscriber::registry()
+///     .with(tracing_subscriber::fmt::subscriber().with_filter(filter));
+/// # let _ = collector;
+///
+/// // ... some time later ...
+/// for callsite in handle.callsites() {
+///     println!(
+///         "{} {}: allowed={} suppressed={}",
+///         callsite.level(),
+///         callsite.target(),
+///         callsite.allowed(),
+///         callsite.suppressed(),
+///     );
+/// }
+/// ```
+///
+/// # Notes
+///
+/// So that every decision is observed, the wrapper reports
+/// [`Interest::sometimes`] for all callsites, disabling the per-callsite
+/// caching that would otherwise let always-enabled or never-enabled
+/// callsites bypass the filter entirely. Combined with the per-decision
+/// accounting (a mutex-guarded map lookup), this makes `FilterStats`
+/// noticeably more expensive than the bare inner filter; it is intended as
+/// an opt-in diagnostic, not a permanent fixture.
+///
+/// Spans and events excluded by the global [max level hint] are discarded
+/// before any filter runs, and are not counted.
+///
+/// [`Filter`]: crate::subscribe::Filter
+/// [`EnvFilter`]: crate::filter::EnvFilter
+/// [`Targets`]: crate::filter::Targets
+/// [max level hint]: crate::subscribe::Filter::max_level_hint
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug)]
+pub struct FilterStats<F> {
+    inner: F,
+    shared: Arc<Shared>,
+}
+
+/// Provides access to the decision counts recorded by a [`FilterStats`].
+///
+/// The handle may be cloned and read from any thread.
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug, Clone)]
+pub struct StatsHandle {
+    shared: Arc<Shared>,
+}
+
+/// A snapshot of the decisions recorded for a single callsite.
+///
+/// This is returned by [`StatsHandle::callsites`].
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug, Clone)]
+pub struct CallsiteStats {
+    name: &'static str,
+    target: String,
+    level: Level,
+    allowed: u64,
+    suppressed: u64,
+}
+
+#[derive(Debug)]
+struct Shared {
+    callsites: Mutex<HashMap<callsite::Identifier, CallsiteCounts>>,
+}
+
+#[derive(Debug)]
+struct CallsiteCounts {
+    name: &'static str,
+    target: String,
+    level: Level,
+    allowed: u64,
+    suppressed: u64,
+}
+
+// === impl FilterStats ===
+
+impl<F> FilterStats<F> {
+    /// Wraps `inner`, returning the new filter and a [`StatsHandle`] that
+    /// provides access to the decision counts it records.
+    pub fn new(inner: F) -> (Self, StatsHandle) {
+        let shared = Arc::new(Shared {
+            callsites: Mutex::new(HashMap::new()),
+        });
+        let handle = StatsHandle {
+            shared: shared.clone(),
+        };
+        (Self { inner, shared }, handle)
+    }
+
+    /// Records one decision for `metadata`.
+    fn record(&self, metadata: &Metadata<'_>, allowed: bool) {
+        let mut callsites = self.shared.callsites.lock().expect("filter stats poisoned");
+        let counts = callsites
+            .entry(metadata.callsite())
+            .or_insert_with(|| CallsiteCounts {
+                name: metadata.name(),
+                target: metadata.target().to_owned(),
+                level: *metadata.level(),
+                allowed: 0,
+                suppressed: 0,
+            });
+        if allowed {
+            counts.allowed += 1;
+        } else {
+            counts.suppressed += 1;
+        }
+    }
+}
+
+// === impl StatsHandle ===
+
+impl StatsHandle {
+    /// Returns a [`CallsiteStats`] for each callsite the filter has been
+    /// consulted about, in an unspecified order.
+    ///
+    /// Each snapshot reflects the decisions recorded so far; decisions
+    /// recorded after this method returns are not included.
+    pub fn callsites(&self) -> Vec<CallsiteStats> {
+        let callsites = self.shared.callsites.lock().expect("filter stats poisoned");
+        callsites
+            .values()
+            .map(|counts| CallsiteStats {
+                name: counts.name,
+                target: counts.target.clone(),
+                level: counts.level,
+                allowed: counts.allowed,
+                suppressed: counts.suppressed,
+            })
+            .collect()
+    }
+}
+
+// === impl CallsiteStats ===
+
+impl CallsiteStats {
+    /// Returns the name of the callsite's span or event.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the callsite's target.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the callsite's verbosity level.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Returns the number of spans and events from this callsite that the
+    /// inner filter allowed.
+    pub fn allowed(&self) -> u64 {
+        self.allowed
+    }
+
+    /// Returns the number of spans and events from this callsite that the
+    /// inner filter suppressed.
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed
+    }
+}
+
+impl<C, F> Filter<C> for FilterStats<F>
+where
+    C: Collect,
+    F: Filter<C>,
+{
+    fn enabled(&self, metadata: &Metadata<'_>, cx: &Context<'_, C>) -> bool {
+        let allowed = self.inner.enabled(metadata, cx);
+        self.record(metadata, allowed);
+        allowed
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        let _ = metadata;
+        // Report `sometimes` even when the inner filter's interest is
+        // `always` or `never`, so that every decision passes through
+        // `enabled` and is counted.
+        Interest::sometimes()
+    }
+
+    fn max_level_hint(&self) -> Option<crate::filter::LevelFilter> {
+        self.inner.max_level_hint()
+    }
+}
+
+impl<C, F> Subscribe<C> for FilterStats<F>
+where
+    C: Collect,
+    F: Subscribe<C>,
+{
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, C>) -> bool {
+        let allowed = self.inner.enabled(metadata, ctx);
+        self.record(metadata, allowed);
+        allowed
+    }
+
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        let _ = metadata;
+        // As in the `Filter` implementation, force per-decision checks.
+        Interest::sometimes()
+    }
+
+    fn max_level_hint(&self) -> Option<crate::filter::LevelFilter> {
+        self.inner.max_level_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::{filter_fn, LevelFilter};
+    use crate::prelude::*;
+
+    use tracing::collect::with_default;
+
+    #[test]
+    fn counts_allowed_and_suppressed() {
+        // `filter_fn` rather than `LevelFilter::INFO`, so that the global max
+        // level hint doesn't discard the debug event before it is counted.
+        let (filter, handle) = FilterStats::new(filter_fn(|meta| *meta.level() <= Level::INFO));
+        let collector =
+            crate::registry().with(crate::subscribe::Identity::new().with_filter(filter));
+
+        with_default(collector, || {
+            for _ in 0..2 {
+                tracing::info!("allowed");
+            }
+            tracing::debug!("suppressed");
+        });
+
+        let mut callsites = handle.callsites();
+        callsites.sort_by_key(|callsite| callsite.level());
+
+        assert_eq!(callsites.len(), 2);
+        assert_eq!(callsites[0].level(), Level::INFO);
+        assert_eq!(callsites[0].allowed(), 2);
+        assert_eq!(callsites[0].suppressed(), 0);
+        assert_eq!(callsites[1].level(), Level::DEBUG);
+        assert_eq!(callsites[1].allowed(), 0);
+        assert_eq!(callsites[1].suppressed(), 1);
+    }
+
+    #[test]
+    fn snapshots_are_independent() {
+        let (filter, handle) = FilterStats::new(LevelFilter::INFO);
+        let collector =
+            crate::registry().with(crate::subscribe::Identity::new().with_filter(filter));
+
+        fn emit() {
+            tracing::info!("an event");
+        }
+
+        with_default(collector, || {
+            emit();
+            let before = handle.callsites();
+            assert_eq!(before[0].allowed(), 1);
+
+            emit();
+            assert_eq!(before[0].allowed(), 1, "snapshot should not change");
+            assert_eq!(handle.callsites()[0].allowed(), 2);
+        });
+    }
+}
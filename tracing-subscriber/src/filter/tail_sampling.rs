@@ -0,0 +1,557 @@
+//! A subscriber that buffers whole traces and forwards only those that
+//! match a policy.
+//!
+//! See the [`TailSampling`] documentation for details.
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tracing_core::{
+    field::{display, DisplayValue, Field, Value, Visit},
+    span, Collect, Event, Interest, Level, Metadata,
+};
+
+/// A [`Subscribe`] implementation that samples traces by their outcome,
+/// rather than up front.
+///
+/// Head sampling (see [`SamplingFilter`]) must decide whether to keep a
+/// trace before anything has happened in it, so it cannot prefer the
+/// interesting ones. A `TailSampling` subscriber instead buffers every
+/// span and event of a trace in memory, keyed by the trace's root span,
+/// and only when the root span closes decides whether the trace is
+/// forwarded to the wrapped subscriber or discarded. The decision is made
+/// by a [`TailPolicy`]: a trace is kept if it contains an event at the
+/// `ERROR` level, if the root span was open for longer than a latency
+/// threshold, or if any span or event recorded a matching field value —
+/// whichever rules the policy enables.
+///
+/// Events recorded outside of any span are not part of a trace, and are
+/// forwarded to the wrapped subscriber immediately.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tracing_subscriber::{filter::{TailPolicy, TailSampling}, prelude::*};
+///
+/// // An export-style subscriber that consumes replayed traces.
+/// struct Exporter;
+/// impl<C: tracing_core::Collect> tracing_subscriber::Subscribe<C> for Exporter {
+///     fn on_event(
+///         &self,
+///         event: &tracing_core::Event<'_>,
+///         _ctx: tracing_subscriber::subscribe::Context<'_, C>,
+///     ) {
+///         // ... export the event ...
+///     }
+/// }
+///
+/// // Keep traces that contain an error, or that took longer than a second.
+/// let policy = TailPolicy::new()
+///     .with_error_events()
+///     .with_latency(Duration::from_secs(1));
+///
+/// tracing_subscriber::registry()
+///     .with(TailSampling::new(Exporter, policy))
+///     .init();
+/// ```
+///
+/// # Notes
+///
+/// A kept trace is replayed from owned storage: the wrapped subscriber
+/// receives the buffered span attributes, recorded values, and events, in
+/// the order they originally occurred. Enter, exit, and close
+/// notifications are *not* replayed, and spans other than the root may
+/// already have been freed from the registry by the time the trace is
+/// replayed — each span's registry entry is only kept alive until all of
+/// its children have closed, and buffering a trace cannot extend that
+/// without also preventing the root span from ever closing. The wrapped
+/// subscriber should therefore consume the replayed notifications
+/// directly, rather than looking the corresponding spans up via
+/// [`Context::span`]; subscribers that require those lookups to succeed
+/// (such as `fmt::Subscriber`) are not suitable targets.
+///
+/// A trace's buffer is freed when the root span closes. Traces whose root
+/// span never closes are buffered indefinitely.
+///
+/// [`Subscribe`]: crate::subscribe::Subscribe
+/// [`SamplingFilter`]: crate::filter::SamplingFilter
+/// [`Context::span`]: crate::subscribe::Context::span
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug)]
+pub struct TailSampling<S> {
+    inner: S,
+    policy: TailPolicy,
+    traces: Mutex<HashMap<span::Id, TraceBuffer>>,
+}
+
+/// The rules deciding which traces a [`TailSampling`] subscriber keeps.
+///
+/// A new policy matches no traces; each `with_*` method enables one rule,
+/// and a trace is kept if *any* enabled rule matches it.
+#[derive(Debug, Default)]
+pub struct TailPolicy {
+    error_events: bool,
+    latency: Option<Duration>,
+    fields: Vec<(String, String)>,
+}
+
+/// The trace's root span id, recorded on each span's extensions so that
+/// descendants can find their trace's buffer.
+struct TraceRoot(span::Id);
+
+/// The buffered notifications of a single in-flight trace.
+#[derive(Debug)]
+struct TraceBuffer {
+    records: Vec<Record>,
+    started: Instant,
+    matched: bool,
+}
+
+/// An owned representation of a single buffered notification.
+#[derive(Debug)]
+enum Record {
+    NewSpan {
+        metadata: &'static Metadata<'static>,
+        id: span::Id,
+        parent: Option<span::Id>,
+        values: Vec<(&'static str, OwnedValue)>,
+    },
+    Values {
+        id: span::Id,
+        metadata: &'static Metadata<'static>,
+        values: Vec<(&'static str, OwnedValue)>,
+    },
+    Event {
+        metadata: &'static Metadata<'static>,
+        parent: Option<span::Id>,
+        values: Vec<(&'static str, OwnedValue)>,
+    },
+}
+
+/// An owned copy of a recorded value.
+#[derive(Debug)]
+enum OwnedValue {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(String),
+    Debug(String),
+}
+
+/// A borrowed view of an [`OwnedValue`] that can be recorded in a
+/// [`ValueSet`](tracing_core::field::ValueSet).
+enum Rendered<'a> {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(&'a str),
+    Display(DisplayValue<&'a str>),
+}
+
+// === impl TailPolicy ===
+
+impl TailPolicy {
+    /// Returns a new policy that matches no traces.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps traces that contain an event at the `ERROR` level.
+    pub fn with_error_events(self) -> Self {
+        Self {
+            error_events: true,
+            ..self
+        }
+    }
+
+    /// Keeps traces whose root span was open for at least `threshold`.
+    pub fn with_latency(self, threshold: Duration) -> Self {
+        Self {
+            latency: Some(threshold),
+            ..self
+        }
+    }
+
+    /// Keeps traces in which any span or event records the field named
+    /// `name` with the given value.
+    ///
+    /// Values are compared by their recorded string representation.
+    pub fn with_field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.push((name.into(), value.into()));
+        self
+    }
+
+    /// Returns whether an event with the provided metadata matches on its
+    /// own.
+    fn matches_event(&self, metadata: &Metadata<'_>) -> bool {
+        self.error_events && *metadata.level() == Level::ERROR
+    }
+
+    /// Returns whether any of the captured values matches a field rule.
+    fn matches_fields(&self, values: &[(&'static str, OwnedValue)]) -> bool {
+        self.fields.iter().any(|(name, expected)| {
+            values
+                .iter()
+                .any(|(recorded, value)| recorded == name && value.matches(expected))
+        })
+    }
+
+    /// Returns whether a trace open for `elapsed` matches the latency rule.
+    fn matches_latency(&self, elapsed: Duration) -> bool {
+        matches!(self.latency, Some(threshold) if elapsed >= threshold)
+    }
+}
+
+// === impl TailSampling ===
+
+impl<S> TailSampling<S> {
+    /// Returns a new `TailSampling` subscriber wrapping `inner`, keeping
+    /// the traces matched by `policy`.
+    pub fn new(inner: S, policy: TailPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            traces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Runs `f` with the buffer map, recovering the lock if it was
+    /// poisoned.
+    fn with_traces<T>(&self, f: impl FnOnce(&mut HashMap<span::Id, TraceBuffer>) -> T) -> T {
+        let mut traces = match self.traces.lock() {
+            Ok(traces) => traces,
+            // A panic while the lock was held can't have left a buffer in an
+            // inconsistent state, so keep going with the map as it is.
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        f(&mut traces)
+    }
+
+    /// Appends `record` to the buffer of the trace rooted at `root`,
+    /// marking the trace as matched if `matched` is set.
+    fn buffer(&self, root: &span::Id, record: Record, matched: bool) {
+        self.with_traces(|traces| {
+            if let Some(buffer) = traces.get_mut(root) {
+                buffer.records.push(record);
+                buffer.matched |= matched;
+            }
+        });
+    }
+}
+
+impl<C, S> Subscribe<C> for TailSampling<S>
+where
+    C: Collect + for<'lookup> LookupSpan<'lookup>,
+    S: Subscribe<C>,
+{
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        self.inner.register_callsite(metadata)
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, C>) -> bool {
+        self.inner.enabled(metadata, ctx)
+    }
+
+    fn max_level_hint(&self) -> Option<crate::filter::LevelFilter> {
+        self.inner.max_level_hint()
+    }
+
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+        let values = capture(|visitor| attrs.record(visitor));
+        let matched = self.policy.matches_fields(&values);
+
+        let parent = span.parent().map(|parent| parent.id());
+        let root = parent
+            .as_ref()
+            .and_then(|parent| Some(ctx.span(parent)?.extensions().get::<TraceRoot>()?.0.clone()));
+        let record = Record::NewSpan {
+            metadata: span.metadata(),
+            id: id.clone(),
+            parent,
+            values,
+        };
+
+        match root {
+            Some(root) => {
+                span.extensions_mut().insert(TraceRoot(root.clone()));
+                self.buffer(&root, record, matched);
+            }
+            // This span starts a new trace.
+            None => {
+                span.extensions_mut().insert(TraceRoot(id.clone()));
+                self.with_traces(|traces| {
+                    traces.insert(
+                        id.clone(),
+                        TraceBuffer {
+                            records: vec![record],
+                            started: Instant::now(),
+                            matched,
+                        },
+                    );
+                });
+            }
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+        let root = match span.extensions().get::<TraceRoot>() {
+            Some(root) => root.0.clone(),
+            None => return,
+        };
+        let captured = capture(|visitor| values.record(visitor));
+        let matched = self.policy.matches_fields(&captured);
+        self.buffer(
+            &root,
+            Record::Values {
+                id: id.clone(),
+                metadata: span.metadata(),
+                values: captured,
+            },
+            matched,
+        );
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let (root, parent) = {
+            let span = ctx.event_span(event);
+            let root = span
+                .as_ref()
+                .and_then(|span| Some(span.extensions().get::<TraceRoot>()?.0.clone()));
+            (root, span.map(|span| span.id()))
+        };
+        let root = match root {
+            Some(root) => root,
+            // Events outside of any trace are forwarded immediately.
+            None => return self.inner.on_event(event, ctx),
+        };
+
+        let values = capture(|visitor| event.record(visitor));
+        let matched =
+            self.policy.matches_event(event.metadata()) || self.policy.matches_fields(&values);
+        self.buffer(
+            &root,
+            Record::Event {
+                metadata: event.metadata(),
+                parent,
+                values,
+            },
+            matched,
+        );
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+        let root = match trace_root(&id, &ctx) {
+            Some(root) => root,
+            None => return,
+        };
+        if root != id {
+            return;
+        }
+
+        // The root span closed; the trace is complete. Decide whether to
+        // forward it.
+        let buffer = match self.with_traces(|traces| traces.remove(&root)) {
+            Some(buffer) => buffer,
+            None => return,
+        };
+        if buffer.matched || self.policy.matches_latency(buffer.started.elapsed()) {
+            self.forward(&buffer.records, &ctx);
+        }
+    }
+}
+
+impl<S> TailSampling<S> {
+    /// Replays the buffered records of a kept trace to the wrapped
+    /// subscriber.
+    fn forward<C>(&self, records: &[Record], ctx: &Context<'_, C>)
+    where
+        C: Collect + for<'lookup> LookupSpan<'lookup>,
+        S: Subscribe<C>,
+    {
+        for record in records {
+            match record {
+                Record::NewSpan {
+                    metadata,
+                    id,
+                    parent,
+                    values,
+                } => with_value_set(metadata, values, |value_set| {
+                    let attrs = match parent {
+                        Some(parent) => span::Attributes::child_of(
+                            parent.clone(),
+                            metadata,
+                            value_set,
+                        ),
+                        None => span::Attributes::new_root(metadata, value_set),
+                    };
+                    self.inner.on_new_span(&attrs, id, ctx.clone());
+                }),
+                Record::Values {
+                    id,
+                    metadata,
+                    values,
+                } => with_value_set(metadata, values, |value_set| {
+                    let record = span::Record::new(value_set);
+                    self.inner.on_record(id, &record, ctx.clone());
+                }),
+                Record::Event {
+                    metadata,
+                    parent,
+                    values,
+                } => with_value_set(metadata, values, |value_set| {
+                    let event = Event::new_child_of(parent.clone(), metadata, value_set);
+                    self.inner.on_event(&event, ctx.clone());
+                }),
+            }
+        }
+    }
+}
+
+/// Returns the root of the trace that the span with the provided `id`
+/// belongs to.
+fn trace_root<C>(id: &span::Id, ctx: &Context<'_, C>) -> Option<span::Id>
+where
+    C: Collect + for<'lookup> LookupSpan<'lookup>,
+{
+    Some(ctx.span(id)?.extensions().get::<TraceRoot>()?.0.clone())
+}
+
+/// Captures the values recorded by `record` into owned storage.
+fn capture(record: impl FnOnce(&mut dyn Visit)) -> Vec<(&'static str, OwnedValue)> {
+    let mut visitor = CaptureVisitor(Vec::new());
+    record(&mut visitor);
+    visitor.0
+}
+
+/// Reconstructs a [`ValueSet`](tracing_core::field::ValueSet) for
+/// `metadata` from captured values, and runs `f` with it.
+fn with_value_set<R>(
+    metadata: &'static Metadata<'static>,
+    values: &[(&'static str, OwnedValue)],
+    f: impl FnOnce(&tracing_core::field::ValueSet<'_>) -> R,
+) -> R {
+    let fields = metadata.fields();
+    let mut resolved = Vec::with_capacity(values.len());
+    for (name, value) in values {
+        if let Some(field) = fields.field(name) {
+            resolved.push((field, value.rendered()));
+        }
+    }
+
+    // `FieldSet::value_set` only accepts fixed-size arrays, so dispatch on
+    // the number of recorded values. Callsites may declare at most 32
+    // fields, making larger arities unreachable.
+    macro_rules! dispatch {
+        ($($len:tt),*) => {
+            match resolved.len() {
+                $(
+                    $len => {
+                        let entries: [(&Field, Option<&dyn Value>); $len] =
+                            std::array::from_fn(|i| (&resolved[i].0, Some(resolved[i].1.as_value())));
+                        f(&fields.value_set(&entries))
+                    }
+                )*
+                _ => {
+                    let entries: [(&Field, Option<&dyn Value>); 0] = [];
+                    f(&fields.value_set(&entries))
+                }
+            }
+        };
+    }
+    dispatch!(
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+        25, 26, 27, 28, 29, 30, 31, 32
+    )
+}
+
+// === impl OwnedValue ===
+
+impl OwnedValue {
+    /// Returns whether the value's recorded string representation equals
+    /// `expected`.
+    fn matches(&self, expected: &str) -> bool {
+        match self {
+            OwnedValue::Bool(b) => expected == if *b { "true" } else { "false" },
+            OwnedValue::I64(i) => i.to_string() == expected,
+            OwnedValue::U64(u) => u.to_string() == expected,
+            OwnedValue::F64(f) => f.to_string() == expected,
+            OwnedValue::Str(s) | OwnedValue::Debug(s) => s == expected,
+        }
+    }
+
+    /// Borrows the value for recording into a replayed value set.
+    fn rendered(&self) -> Rendered<'_> {
+        match self {
+            OwnedValue::Bool(b) => Rendered::Bool(*b),
+            OwnedValue::I64(i) => Rendered::I64(*i),
+            OwnedValue::U64(u) => Rendered::U64(*u),
+            OwnedValue::F64(f) => Rendered::F64(*f),
+            OwnedValue::Str(s) => Rendered::Str(s),
+            // The value was captured via its `Debug` representation;
+            // replaying it with `display` preserves that formatting.
+            OwnedValue::Debug(s) => Rendered::Display(display(s.as_str())),
+        }
+    }
+}
+
+impl Rendered<'_> {
+    fn as_value(&self) -> &dyn Value {
+        match self {
+            Rendered::Bool(b) => b,
+            Rendered::I64(i) => i,
+            Rendered::U64(u) => u,
+            Rendered::F64(f) => f,
+            Rendered::Str(s) => s,
+            Rendered::Display(d) => d,
+        }
+    }
+}
+
+// === impl CaptureVisitor ===
+
+/// Copies every recorded value into owned storage.
+struct CaptureVisitor(Vec<(&'static str, OwnedValue)>);
+
+impl Visit for CaptureVisitor {
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.push((field.name(), OwnedValue::Bool(value)));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.push((field.name(), OwnedValue::I64(value)));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.push((field.name(), OwnedValue::U64(value)));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.push((field.name(), OwnedValue::F64(value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name(), OwnedValue::Str(value.to_string())));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .push((field.name(), OwnedValue::Debug(format!("{:?}", value))));
+    }
+}
@@ -111,6 +111,13 @@ use tracing_core::{Collect, Interest, Level, Metadata};
 /// This is particularly useful when the list of enabled targets is configurable
 /// by the user at runtime.
 ///
+/// When parsing, a directive may also match on a span or event's source
+/// location instead of its target, using a glob pattern prefixed with `file:`
+/// or `module:` (e.g. `file:src/net/**=trace` or `module:myapp::db::*=debug`).
+/// In these patterns, `*` matches any sequence of characters except the path
+/// separator, `**` also matches separators, and `?` matches a single
+/// character.
+///
 /// The `Targets` filter can be used as a [per-subscriber filter][plf] *and* as a
 /// [global filter][global]:
 ///
@@ -219,6 +226,7 @@ impl Targets {
     pub fn with_target(mut self, target: impl Into<String>, level: impl Into<LevelFilter>) -> Self {
         self.0.add(StaticDirective::new(
             Some(target.into()),
+            None,
             Default::default(),
             level.into(),
         ));
@@ -274,7 +282,7 @@ impl Targets {
     /// will be enabled if their level is at or below the provided level.
     pub fn with_default(mut self, level: impl Into<LevelFilter>) -> Self {
         self.0
-            .add(StaticDirective::new(None, Default::default(), level.into()));
+            .add(StaticDirective::new(None, None, Default::default(), level.into()));
         self
     }
 
@@ -408,7 +416,7 @@ where
 {
     fn extend<I: IntoIterator<Item = (T, L)>>(&mut self, iter: I) {
         let iter = iter.into_iter().map(|(target, level)| {
-            StaticDirective::new(Some(target.into()), Default::default(), level.into())
+            StaticDirective::new(Some(target.into()), None, Default::default(), level.into())
         });
         self.0.extend(iter);
     }
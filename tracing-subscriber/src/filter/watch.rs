@@ -0,0 +1,208 @@
+//! Reloading filter directives from a watched file.
+//!
+//! See the [`watch_file`] documentation for details.
+use crate::reload;
+use std::{
+    fmt,
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+use tracing_core::{
+    callsite::Callsite,
+    dispatch,
+    field::FieldSet,
+    identify_callsite,
+    metadata::{Kind, Level, Metadata},
+    Event, Interest,
+};
+
+/// Watches `path` for changes, reloading the filter behind `handle` with the
+/// file's contents whenever it is modified.
+///
+/// This drives a [`reload::Subscriber`]'s filter from a configuration file,
+/// so that verbosity can be changed at runtime by editing the file — for
+/// example, from a mounted Kubernetes ConfigMap. The file is expected to
+/// contain filter directives parseable by `T`, such as an [`EnvFilter`] or
+/// [`Targets`] directive string.
+///
+/// A background thread polls the file's modification time every `interval`,
+/// and reloads the filter when it changes. The file is also loaded once
+/// immediately, so its directives take effect without waiting for the first
+/// change. If the file cannot be read or its contents fail to parse, a
+/// diagnostic event is emitted at the `WARN` level (with the target
+/// `tracing_subscriber::filter::watch`) and the previous filter is left in
+/// place.
+///
+/// The watcher thread exits on its own when the collector that owns the
+/// reloaded filter is dropped. Dropping the returned [`FileWatcher`] also
+/// stops it, at the next poll.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tracing_subscriber::{filter::{self, Targets}, prelude::*, reload};
+///
+/// let targets = Targets::new().with_default(filter::LevelFilter::INFO);
+/// let (targets, handle) = reload::Subscriber::new(targets);
+///
+/// tracing_subscriber::registry()
+///     .with(tracing_subscriber::fmt::subscriber().with_filter(targets))
+///     .init();
+///
+/// let _watcher = filter::watch_file("/etc/myapp/filter", Duration::from_secs(5), handle);
+/// ```
+///
+/// [`reload::Subscriber`]: crate::reload::Subscriber
+/// [`EnvFilter`]: crate::filter::EnvFilter
+/// [`Targets`]: crate::filter::Targets
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn watch_file<T>(
+    path: impl Into<PathBuf>,
+    interval: Duration,
+    handle: reload::Handle<T>,
+) -> FileWatcher
+where
+    T: FromStr + Send + Sync + 'static,
+    T::Err: fmt::Display,
+{
+    let path = path.into();
+    let stop = Arc::new(AtomicBool::new(false));
+    let watcher = FileWatcher { stop: stop.clone() };
+    thread::Builder::new()
+        .name("tracing-filter-watch".into())
+        .spawn(move || {
+            let mut last_modified = None;
+            loop {
+                if stop.load(Ordering::Acquire) {
+                    return;
+                }
+                match modified(&path) {
+                    // Reload on the first poll, and again whenever the
+                    // modification time changes.
+                    Some(modified) if last_modified != Some(modified) => {
+                        last_modified = Some(modified);
+                        if load(&path, &handle).is_err() {
+                            // The collector owning the filter is gone; there
+                            // is nothing left to reload.
+                            return;
+                        }
+                    }
+                    Some(_) => {}
+                    None => emit_diagnostic(&path, "the filter file could not be read"),
+                }
+                thread::sleep(interval);
+            }
+        })
+        .expect("failed to spawn the filter file watcher thread");
+    watcher
+}
+
+/// Stops the background thread spawned by [`watch_file`] when dropped.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct FileWatcher {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        // The thread observes the flag at its next poll; there is no need to
+        // block on joining it here.
+        self.stop.store(true, Ordering::Release);
+    }
+}
+
+/// Returns the file's modification time, if it can be read.
+fn modified(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Reads and parses the file, reloading `handle` with the result.
+///
+/// Returns an error only if the collector owning the filter has been
+/// dropped; read and parse failures emit a diagnostic and leave the current
+/// filter in place.
+fn load<T>(path: &std::path::Path, handle: &reload::Handle<T>) -> Result<(), reload::Error>
+where
+    T: FromStr + 'static,
+    T::Err: fmt::Display,
+{
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) => {
+            emit_diagnostic(path, &format!("failed to read the filter file: {}", error));
+            return Ok(());
+        }
+    };
+    match contents.trim().parse::<T>() {
+        Ok(filter) => match handle.reload(filter) {
+            Ok(()) => Ok(()),
+            Err(error) if error.is_dropped() => Err(error),
+            Err(error) => {
+                emit_diagnostic(path, &format!("failed to reload the filter: {}", error));
+                Ok(())
+            }
+        },
+        Err(error) => {
+            emit_diagnostic(path, &format!("failed to parse the filter file: {}", error));
+            Ok(())
+        }
+    }
+}
+
+static DIAGNOSTIC_FIELDS: &[&str] = &["message", "path"];
+struct DiagnosticCallsite;
+static DIAGNOSTIC_CALLSITE: DiagnosticCallsite = DiagnosticCallsite;
+static DIAGNOSTIC_META: Metadata<'static> = Metadata::new(
+    "filter file diagnostic",
+    "tracing_subscriber::filter::watch",
+    Level::WARN,
+    None,
+    None,
+    None,
+    FieldSet::new(DIAGNOSTIC_FIELDS, identify_callsite!(&DIAGNOSTIC_CALLSITE)),
+    Kind::EVENT,
+);
+
+impl Callsite for DiagnosticCallsite {
+    fn set_interest(&self, _: Interest) {}
+
+    fn metadata(&self) -> &'static Metadata<'static> {
+        &DIAGNOSTIC_META
+    }
+}
+
+/// Emits the diagnostic event recording why the filter was not reloaded.
+fn emit_diagnostic(path: &std::path::Path, message: &str) {
+    dispatch::get_default(|dispatch| {
+        if !dispatch.enabled(&DIAGNOSTIC_META) {
+            return;
+        }
+        let fields = DIAGNOSTIC_META.fields();
+        let message_field = fields.field("message").expect("message field must exist");
+        let path_field = fields.field("path").expect("path field must exist");
+        let path = path.display().to_string();
+        dispatch.event(&Event::new(
+            &DIAGNOSTIC_META,
+            &fields.value_set(&[
+                (
+                    &message_field,
+                    Some(&message as &dyn tracing_core::field::Value),
+                ),
+                (
+                    &path_field,
+                    Some(&path.as_str() as &dyn tracing_core::field::Value),
+                ),
+            ]),
+        ));
+    });
+}
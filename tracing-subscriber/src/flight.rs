@@ -0,0 +1,552 @@
+//! An in-memory "flight recorder" for recent trace events.
+//!
+//! Verbose output is most valuable in the moments before a failure, but
+//! logging at `TRACE` all the time is too expensive for production. This
+//! module provides a [`Subscriber`] that records every event it sees into a
+//! fixed-size, per-thread ring buffer instead of writing it anywhere: old
+//! events are silently overwritten, so the buffers always hold the *most
+//! recent* events at a bounded memory cost. When something goes wrong, the
+//! buffered events are dumped to a writer, providing `TRACE`-level context
+//! for the failure without always-on verbose output.
+//!
+//! A dump can be triggered in three ways:
+//!
+//! - automatically, when an event at or above a severity threshold is
+//!   recorded (by default, [`ERROR`]; see [`Builder::dump_on`]),
+//! - explicitly, by calling [`Handle::dump`] — for example, from a thread
+//!   listening for a signal such as `SIGUSR1`, or
+//! - on panic, if [`Handle::install_panic_hook`] has been called.
+//!
+//! For programmatic access — such as feeding the buffered events to another
+//! subscriber or an error report — [`Handle::entries`] returns a snapshot of
+//! the buffers as [`Entry`] values instead of formatting them.
+//!
+//! Each thread records into its own buffer, so recording an event only locks
+//! a mutex that is never contended in steady state; the buffers are only
+//! locked across threads while a dump or snapshot is in progress.
+//!
+//! # Examples
+//!
+//! Recording everything while only printing `INFO` and above, using a
+//! [per-subscriber filter] to keep the ordinary output quiet:
+//!
+//! ```
+//! use tracing_subscriber::{filter::LevelFilter, flight, prelude::*};
+//!
+//! let (flight, handle) = flight::Subscriber::new();
+//! let collector = tracing_subscriber::registry()
+//!     .with(tracing_subscriber::fmt::subscriber().with_filter(LevelFilter::INFO))
+//!     .with(flight);
+//! # let _ = collector;
+//!
+//! // ... when an ERROR event occurs, the recent TRACE-level history is
+//! // dumped to stderr automatically. It can also be dumped by hand:
+//! handle.dump().expect("dumping the flight recorder failed");
+//! ```
+//!
+//! Note that the flight recorder can only record events that are enabled by
+//! the collector as a whole: a global `INFO` filter would discard `TRACE`
+//! events before they reach any subscriber. Filter the *other* subscribers
+//! in the stack (as above) rather than the whole collector.
+//!
+//! [`ERROR`]: tracing_core::Level::ERROR
+//! [per-subscriber filter]: crate::subscribe#filtering-with-subscribers
+use crate::{
+    filter::LevelFilter,
+    fmt::MakeWriter,
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    io::{self, Write as _},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use thread_local::ThreadLocal;
+use tracing_core::{field, Collect, Event, Level};
+
+/// The default number of events retained per thread.
+const DEFAULT_CAPACITY: usize = 128;
+
+/// A [`Subscribe`] implementation that records recent events into per-thread
+/// ring buffers, dumping them on demand.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber<W = fn() -> io::Stderr> {
+    shared: Arc<Shared<W>>,
+    dump_on: LevelFilter,
+}
+
+/// Triggers dumps of, and provides access to, the events recorded by a
+/// flight recorder [`Subscriber`].
+///
+/// This is returned by [`Subscriber::new`] and [`Builder::finish`], and may
+/// be cloned and sent to other threads freely.
+#[derive(Debug)]
+pub struct Handle<W = fn() -> io::Stderr> {
+    shared: Arc<Shared<W>>,
+}
+
+/// Configures a flight recorder [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    capacity: usize,
+    dump_on: LevelFilter,
+}
+
+/// A single recorded event, as returned by [`Handle::entries`].
+#[derive(Debug, Clone)]
+pub struct Entry {
+    at: Instant,
+    thread: Option<String>,
+    level: Level,
+    target: &'static str,
+    scope: Option<String>,
+    fields: String,
+}
+
+#[derive(Debug)]
+struct Shared<W> {
+    buffers: ThreadLocal<Mutex<ThreadBuffer>>,
+    capacity: usize,
+    make_writer: W,
+}
+
+/// The ring buffer of events recorded by a single thread.
+#[derive(Debug)]
+struct ThreadBuffer {
+    thread: Option<String>,
+    events: VecDeque<RecordedEvent>,
+}
+
+#[derive(Debug, Clone)]
+struct RecordedEvent {
+    at: Instant,
+    level: Level,
+    target: &'static str,
+    scope: Option<String>,
+    fields: String,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new flight recorder with the default configuration, and a
+    /// [`Handle`] for dumping it.
+    ///
+    /// The recorder retains the last 128 events per thread, and dumps them
+    /// to standard error when an [`ERROR`] event is recorded. Use
+    /// [`builder`](Self::builder) to change the capacity, threshold, or
+    /// writer.
+    ///
+    /// [`ERROR`]: tracing_core::Level::ERROR
+    pub fn new() -> (Self, Handle) {
+        Self::builder().finish(io::stderr as fn() -> io::Stderr)
+    }
+
+    /// Returns a [`Builder`] for configuring a flight recorder.
+    pub fn builder() -> Builder {
+        Builder {
+            capacity: DEFAULT_CAPACITY,
+            dump_on: LevelFilter::ERROR,
+        }
+    }
+}
+
+impl<W> Subscriber<W> {
+    /// Records `event`'s fields and scope into the current thread's buffer.
+    fn record(&self, level: Level, target: &'static str, scope: Option<String>, fields: String) {
+        let mut buffer = self
+            .shared
+            .buffers
+            .get_or(|| Mutex::new(ThreadBuffer::new()))
+            .lock()
+            .expect("flight recorder buffer poisoned");
+        if buffer.events.len() == self.shared.capacity {
+            buffer.events.pop_front();
+        }
+        buffer.events.push_back(RecordedEvent {
+            at: Instant::now(),
+            level,
+            target,
+            scope,
+            fields,
+        });
+    }
+}
+
+impl<C, W> Subscribe<C> for Subscriber<W>
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let metadata = event.metadata();
+        let scope = ctx.event_scope(event).map(|scope| {
+            let mut names = String::new();
+            for span in scope.from_root() {
+                if !names.is_empty() {
+                    names.push(':');
+                }
+                names.push_str(span.name());
+            }
+            names
+        });
+
+        let mut fields = String::new();
+        event.record(&mut FieldVisitor {
+            fields: &mut fields,
+        });
+        self.record(*metadata.level(), metadata.target(), scope, fields);
+
+        // The triggering event is recorded first, so that it appears at the
+        // end of its own dump.
+        if *metadata.level() <= self.dump_on {
+            let _ = self.shared.dump();
+        }
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the number of events retained per thread.
+    ///
+    /// When a thread's buffer is full, recording a new event drops that
+    /// thread's oldest buffered event. The default is 128 events per thread.
+    pub fn capacity(self, capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the severity threshold that triggers an automatic dump.
+    ///
+    /// Recording an event at or above this level dumps the buffers to the
+    /// writer. The default is [`ERROR`]; use [`LevelFilter::OFF`] to only
+    /// dump explicitly (via a [`Handle`]).
+    ///
+    /// [`ERROR`]: tracing_core::Level::ERROR
+    pub fn dump_on(self, level: LevelFilter) -> Self {
+        Self {
+            dump_on: level,
+            ..self
+        }
+    }
+
+    /// Returns a flight recorder [`Subscriber`] that dumps to `make_writer`,
+    /// and a [`Handle`] for dumping it.
+    pub fn finish<W>(self, make_writer: W) -> (Subscriber<W>, Handle<W>)
+    where
+        W: for<'a> MakeWriter<'a>,
+    {
+        let shared = Arc::new(Shared {
+            buffers: ThreadLocal::new(),
+            capacity: self.capacity,
+            make_writer,
+        });
+        let handle = Handle {
+            shared: shared.clone(),
+        };
+        let subscriber = Subscriber {
+            shared,
+            dump_on: self.dump_on,
+        };
+        (subscriber, handle)
+    }
+}
+
+// === impl Handle ===
+
+impl<W> Handle<W> {
+    /// Returns a snapshot of the buffered events from every thread, oldest
+    /// first.
+    ///
+    /// Events recorded after this method returns are not reflected in the
+    /// snapshot.
+    pub fn entries(&self) -> Vec<Entry> {
+        self.shared.entries()
+    }
+
+    /// Dumps the buffered events from every thread to the writer, oldest
+    /// first.
+    ///
+    /// The buffers are not cleared by a dump; subsequent dumps will repeat
+    /// events that have not yet been overwritten.
+    pub fn dump(&self) -> io::Result<()>
+    where
+        W: for<'a> MakeWriter<'a>,
+    {
+        self.shared.dump()
+    }
+
+    /// Installs a panic hook that dumps the buffered events before the
+    /// previously-installed hook runs.
+    ///
+    /// This wraps (rather than replaces) the current panic hook, so panic
+    /// messages and backtraces are still printed after the dump.
+    pub fn install_panic_hook(&self)
+    where
+        W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+    {
+        let handle = self.clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = handle.dump();
+            previous(info);
+        }));
+    }
+}
+
+impl<W> Clone for Handle<W> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+// === impl Shared ===
+
+impl<W> Shared<W> {
+    fn entries(&self) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        for buffer in self.buffers.iter() {
+            let buffer = buffer.lock().expect("flight recorder buffer poisoned");
+            entries.extend(buffer.events.iter().map(|event| Entry {
+                at: event.at,
+                thread: buffer.thread.clone(),
+                level: event.level,
+                target: event.target,
+                scope: event.scope.clone(),
+                fields: event.fields.clone(),
+            }));
+        }
+        entries.sort_by_key(|entry| entry.at);
+        entries
+    }
+
+    fn dump(&self) -> io::Result<()>
+    where
+        W: for<'a> MakeWriter<'a>,
+    {
+        let entries = self.entries();
+        let now = Instant::now();
+        let mut writer = self.make_writer.make_writer();
+        writeln!(writer, "flight recorder dump ({} events):", entries.len())?;
+        for entry in &entries {
+            let age = now.saturating_duration_since(entry.at);
+            write!(writer, "[{:>12.1?} ago] {:>5} ", age, entry.level)?;
+            if let Some(thread) = entry.thread() {
+                write!(writer, "{} ", thread)?;
+            }
+            if let Some(scope) = entry.scope() {
+                write!(writer, "{}: ", scope)?;
+            }
+            writeln!(writer, "{}: {}", entry.target, entry.fields)?;
+        }
+        writer.flush()
+    }
+}
+
+// === impl ThreadBuffer ===
+
+impl ThreadBuffer {
+    fn new() -> Self {
+        Self {
+            thread: std::thread::current().name().map(String::from),
+            events: VecDeque::new(),
+        }
+    }
+}
+
+// === impl Entry ===
+
+impl Entry {
+    /// Returns the time elapsed since this event was recorded.
+    pub fn age(&self) -> Duration {
+        Instant::now().saturating_duration_since(self.at)
+    }
+
+    /// Returns the name of the thread that recorded this event, if it had
+    /// one.
+    pub fn thread(&self) -> Option<&str> {
+        self.thread.as_deref()
+    }
+
+    /// Returns the event's level.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Returns the event's target.
+    pub fn target(&self) -> &'static str {
+        self.target
+    }
+
+    /// Returns the names of the spans the event occurred in, from the root
+    /// span inward, separated by `:`.
+    ///
+    /// Returns `None` for events recorded outside of any span.
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+
+    /// Returns the event's fields, rendered as text.
+    pub fn fields(&self) -> &str {
+        &self.fields
+    }
+}
+
+/// Renders an event's fields into a single line of text.
+struct FieldVisitor<'a> {
+    fields: &'a mut String,
+}
+
+impl field::Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+        if field.name() == "message" {
+            let _ = write!(self.fields, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, "{}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    /// A [`MakeWriter`] that appends to a shared buffer.
+    #[derive(Clone)]
+    struct MakeBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for MakeBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for MakeBuf {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn buffers_keep_the_most_recent_events() {
+        let (flight, handle) = Subscriber::builder()
+            .capacity(2)
+            .dump_on(LevelFilter::OFF)
+            .finish(io::sink as fn() -> io::Sink);
+        let collector = crate::registry().with(flight);
+
+        with_default(collector, || {
+            tracing::trace!("first");
+            tracing::trace!("second");
+            tracing::trace!("third");
+        });
+
+        let entries = handle.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].fields(), "second");
+        assert_eq!(entries[1].fields(), "third");
+        assert_eq!(entries[0].level(), Level::TRACE);
+        assert_eq!(entries[0].target(), module_path!());
+    }
+
+    #[test]
+    fn entries_record_the_span_scope() {
+        let (flight, handle) = Subscriber::builder()
+            .dump_on(LevelFilter::OFF)
+            .finish(io::sink as fn() -> io::Sink);
+        let collector = crate::registry().with(flight);
+
+        with_default(collector, || {
+            let outer = tracing::info_span!("outer");
+            let _outer = outer.enter();
+            let inner = tracing::info_span!("inner");
+            let _inner = inner.enter();
+            tracing::debug!(answer = 42, "in scope");
+            drop((_inner, _outer));
+            tracing::debug!("out of scope");
+        });
+
+        let entries = handle.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].scope(), Some("outer:inner"));
+        assert_eq!(entries[0].fields(), "in scope answer=42");
+        assert_eq!(entries[1].scope(), None);
+    }
+
+    #[test]
+    fn error_events_trigger_a_dump() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let (flight, _handle) = Subscriber::builder().finish(MakeBuf(buf.clone()));
+        let collector = crate::registry().with(flight);
+
+        with_default(collector, || {
+            tracing::trace!("some context");
+            assert!(buf.lock().unwrap().is_empty());
+            tracing::error!("it broke");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(
+            output.starts_with("flight recorder dump (2 events):"),
+            "unexpected output: {}",
+            output
+        );
+        assert!(output.contains("some context"), "{}", output);
+        assert!(output.contains("it broke"), "{}", output);
+    }
+
+    #[test]
+    fn dumps_merge_buffers_across_threads() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let (flight, handle) = Subscriber::builder()
+            .dump_on(LevelFilter::OFF)
+            .finish(MakeBuf(buf.clone()));
+        let collector = crate::registry().with(flight);
+
+        let dispatch = tracing::Dispatch::new(collector);
+        let worker = dispatch.clone();
+        tracing::dispatch::with_default(&dispatch, || {
+            tracing::trace!("on the main thread");
+            std::thread::Builder::new()
+                .name("worker".into())
+                .spawn(move || {
+                    tracing::dispatch::with_default(&worker, || {
+                        tracing::trace!("on the worker thread")
+                    })
+                })
+                .unwrap()
+                .join()
+                .unwrap();
+        });
+
+        handle.dump().unwrap();
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("on the main thread"), "{}", output);
+        assert!(output.contains("worker "), "{}", output);
+        assert!(output.contains("on the worker thread"), "{}", output);
+    }
+}
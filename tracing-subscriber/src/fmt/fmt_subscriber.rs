@@ -68,7 +68,10 @@ pub struct Subscriber<C, N = format::DefaultFields, E = format::Format, W = fn()
     fmt_event: E,
     fmt_span: format::FmtSpanConfig,
     is_ansi: bool,
+    #[cfg(feature = "ansi")]
+    theme: format::Theme,
     log_internal_errors: bool,
+    truncation: Option<format::TruncationRules>,
     _inner: PhantomData<fn(C)>,
 }
 
@@ -118,7 +121,10 @@ where
             fmt_span: self.fmt_span,
             make_writer: self.make_writer,
             is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             _inner: self._inner,
         }
     }
@@ -148,7 +154,105 @@ where
             fmt_span: self.fmt_span,
             make_writer: self.make_writer,
             is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
+            log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
+            _inner: self._inner,
+        }
+    }
+
+    /// Formats events from the given target (and its submodules) with a
+    /// different [event formatter][`FormatEvent`], keeping the current
+    /// formatter for all other events.
+    ///
+    /// Calls may be chained to override several targets. See
+    /// [`format::TargetFormat`] for details on how targets are matched.
+    ///
+    /// # Examples
+    ///
+    /// Rendering a noisy dependency's events compactly while other events
+    /// use the default format:
+    ///
+    /// ```rust
+    /// use tracing_subscriber::fmt::{self, format};
+    ///
+    /// let fmt_subscriber = fmt::subscriber()
+    ///     .with_format_for_target("sqlx", format().compact());
+    /// # // this is necessary for type inference.
+    /// # use tracing_subscriber::Subscribe as _;
+    /// # let _ = fmt_subscriber.with_collector(tracing_subscriber::registry::Registry::default());
+    /// ```
+    ///
+    /// [`FormatEvent`]: format::FormatEvent
+    pub fn with_format_for_target<E2>(
+        self,
+        target: impl Into<String>,
+        format: E2,
+    ) -> Subscriber<C, N, format::TargetFormat<E2, E>, W>
+    where
+        E2: FormatEvent<C, N> + 'static,
+    {
+        Subscriber {
+            fmt_fields: self.fmt_fields,
+            fmt_event: format::TargetFormat::new(target, format, self.fmt_event),
+            fmt_span: self.fmt_span,
+            make_writer: self.make_writer,
+            is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
+            log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
+            _inner: self._inner,
+        }
+    }
+
+    /// Additionally writes every event, formatted with the given
+    /// [event formatter][`FormatEvent`], to the given [`MakeWriter`].
+    ///
+    /// Unlike [`MakeWriterExt::and`], which duplicates identical bytes to
+    /// both writers, each configured (format, writer) pair formats the event
+    /// independently. Calls may be chained to configure several additional
+    /// outputs. See [`format::TeeFormat`] for details.
+    ///
+    /// # Examples
+    ///
+    /// Emitting human-readable output on stderr and newline-delimited JSON
+    /// to stdout:
+    ///
+    /// ```rust
+    /// use tracing_subscriber::fmt::{self, format};
+    ///
+    /// let fmt_subscriber = fmt::subscriber()
+    ///     .with_writer(std::io::stderr)
+    ///     .with_tee(format().json(), std::io::stdout);
+    /// # // this is necessary for type inference.
+    /// # use tracing_subscriber::Subscribe as _;
+    /// # let _ = fmt_subscriber.with_collector(tracing_subscriber::registry::Registry::default());
+    /// ```
+    ///
+    /// [`FormatEvent`]: format::FormatEvent
+    /// [`MakeWriter`]: super::writer::MakeWriter
+    /// [`MakeWriterExt::and`]: super::writer::MakeWriterExt::and
+    pub fn with_tee<E2, W2>(
+        self,
+        format: E2,
+        make_writer: W2,
+    ) -> Subscriber<C, N, format::TeeFormat<E2, W2, E>, W>
+    where
+        E2: FormatEvent<C, N> + 'static,
+        W2: for<'writer> MakeWriter<'writer> + 'static,
+    {
+        Subscriber {
+            fmt_fields: self.fmt_fields,
+            fmt_event: format::TeeFormat::new(format, make_writer, self.fmt_event),
+            fmt_span: self.fmt_span,
+            make_writer: self.make_writer,
+            is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             _inner: self._inner,
         }
     }
@@ -184,7 +288,10 @@ impl<C, N, E, W> Subscriber<C, N, E, W> {
             fmt_event: self.fmt_event,
             fmt_span: self.fmt_span,
             is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             make_writer,
             _inner: self._inner,
         }
@@ -231,13 +338,18 @@ impl<C, N, E, W> Subscriber<C, N, E, W> {
     /// Sets whether this subscriber should use ANSI terminal formatting
     /// escape codes (such as colors).
     ///
+    /// Like [`with_ansi`](Subscriber::with_ansi), this accepts either a
+    /// `bool` or a [`Theme`](format::Theme).
+    ///
     /// This method is primarily expected to be used with the
     /// [`reload::Handle::modify`](crate::reload::Handle::modify) method when changing
     /// the writer.
     #[cfg(feature = "ansi")]
     #[cfg_attr(docsrs, doc(cfg(feature = "ansi")))]
-    pub fn set_ansi(&mut self, ansi: bool) {
-        self.is_ansi = ansi;
+    pub fn set_ansi(&mut self, ansi: impl Into<format::Theme>) {
+        let theme = ansi.into();
+        self.is_ansi = theme.styled();
+        self.theme = theme;
     }
 
     /// Modifies how synthesized events are emitted at points in the [span
@@ -289,7 +401,10 @@ impl<C, N, E, W> Subscriber<C, N, E, W> {
             fmt_event: self.fmt_event,
             fmt_span: self.fmt_span,
             is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             make_writer: TestWriter::default(),
             _inner: self._inner,
         }
@@ -320,10 +435,31 @@ impl<C, N, E, W> Subscriber<C, N, E, W> {
     /// whether or not other crates in the dependency graph enable the "ansi"
     /// feature flag.
     ///
+    /// When the "ansi" crate feature flag is enabled, this method accepts
+    /// either a `bool` or a [`Theme`](format::Theme) describing the colors
+    /// and styles to use: `with_ansi(true)` is equivalent to passing
+    /// [`Theme::default`](format::Theme::default), and `with_ansi(false)` to
+    /// passing [`Theme::none`](format::Theme::none).
+    ///
     /// [`with_ansi`]: Subscriber::with_ansi
     /// [`set_ansi`]: Subscriber::set_ansi
+    #[cfg(feature = "ansi")]
+    pub fn with_ansi(self, ansi: impl Into<format::Theme>) -> Self {
+        let theme = ansi.into();
+        Subscriber {
+            is_ansi: theme.styled(),
+            theme,
+            ..self
+        }
+    }
+
+    /// Sets whether or not the formatter emits ANSI terminal escape codes
+    /// for colors and other text formatting.
+    ///
+    /// [`with_ansi`]: Subscriber::with_ansi
+    /// [`set_ansi`]: Subscriber::set_ansi
+    #[cfg(not(feature = "ansi"))]
     pub fn with_ansi(self, ansi: bool) -> Self {
-        #[cfg(not(feature = "ansi"))]
         if ansi {
             const ERROR: &str =
                 "tracing-subscriber: the `ansi` crate feature is required to enable ANSI terminal colors";
@@ -385,7 +521,10 @@ impl<C, N, E, W> Subscriber<C, N, E, W> {
             fmt_event: self.fmt_event,
             fmt_span: self.fmt_span,
             is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             make_writer: f(self.make_writer),
             _inner: self._inner,
         }
@@ -417,7 +556,10 @@ where
             fmt_span: self.fmt_span,
             make_writer: self.make_writer,
             is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             _inner: self._inner,
         }
     }
@@ -430,7 +572,10 @@ where
             fmt_span: self.fmt_span.without_time(),
             make_writer: self.make_writer,
             is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             _inner: self._inner,
         }
     }
@@ -515,6 +660,40 @@ where
         }
     }
 
+    /// Sets whether or not displayed source code locations are wrapped in
+    /// [OSC 8] terminal hyperlinks, if the terminal is detected to support
+    /// them.
+    ///
+    /// See [`format::Format::with_hyperlinks`] for details.
+    ///
+    /// [OSC 8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+    pub fn with_hyperlinks(
+        self,
+        display_hyperlinks: bool,
+    ) -> Subscriber<C, N, format::Format<L, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_hyperlinks(display_hyperlinks),
+            ..self
+        }
+    }
+
+    /// Wraps displayed source code locations in [OSC 8] terminal hyperlinks
+    /// pointing at the provided URL template, such as
+    /// `"vscode://file/{path}:{line}"`.
+    ///
+    /// See [`format::Format::with_hyperlink_template`] for details.
+    ///
+    /// [OSC 8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+    pub fn with_hyperlink_template(
+        self,
+        template: impl Into<String>,
+    ) -> Subscriber<C, N, format::Format<L, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_hyperlink_template(template),
+            ..self
+        }
+    }
+
     /// Sets whether or not an event's level is displayed.
     pub fn with_level(self, display_level: bool) -> Subscriber<C, N, format::Format<L, T>, W> {
         Subscriber {
@@ -551,6 +730,47 @@ where
         }
     }
 
+    /// Sets whether or not the current [process ID] is displayed when
+    /// formatting events.
+    ///
+    /// [process ID]: std::process::id
+    pub fn with_process_id(
+        self,
+        display_process_id: bool,
+    ) -> Subscriber<C, N, format::Format<L, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_process_id(display_process_id),
+            ..self
+        }
+    }
+
+    /// Sets a hostname that is included in every formatted event.
+    pub fn with_hostname(
+        self,
+        hostname: impl Into<String>,
+    ) -> Subscriber<C, N, format::Format<L, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_hostname(hostname),
+            ..self
+        }
+    }
+
+    /// Sets static resource attributes, such as a `service.name` or
+    /// `service.version`, that are included in every formatted event.
+    pub fn with_resource<K, V>(
+        self,
+        resource: impl IntoIterator<Item = (K, V)>,
+    ) -> Subscriber<C, N, format::Format<L, T>, W>
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Subscriber {
+            fmt_event: self.fmt_event.with_resource(resource),
+            ..self
+        }
+    }
+
     /// Sets the subscriber being built to use a [less verbose formatter](format::Compact).
     pub fn compact(self) -> Subscriber<C, N, format::Format<format::Compact, T>, W>
     where
@@ -562,7 +782,73 @@ where
             fmt_span: self.fmt_span,
             make_writer: self.make_writer,
             is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
+            log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
+            _inner: self._inner,
+        }
+    }
+
+    /// Sets the subscriber being built to use a
+    /// [column-aligned formatter](format::Columns).
+    ///
+    /// # Options
+    ///
+    /// - [`Subscriber::with_target_width`] and
+    ///   [`Subscriber::with_message_width`] set the column widths.
+    /// - [`Subscriber::with_truncate_message`] truncates messages that
+    ///   overflow their column.
+    pub fn columns(self) -> Subscriber<C, N, format::Format<format::Columns, T>, W>
+    where
+        N: for<'writer> FormatFields<'writer> + 'static,
+    {
+        Subscriber {
+            fmt_event: self.fmt_event.columns(),
+            fmt_fields: self.fmt_fields,
+            fmt_span: self.fmt_span,
+            make_writer: self.make_writer,
+            is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
+            log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
+            _inner: self._inner,
+        }
+    }
+
+    /// Sets the subscriber being built to use a [CSV formatter](format::Csv).
+    ///
+    /// # Example Output
+    ///
+    /// ```text
+    /// timestamp,level,target,message
+    /// fake time,INFO,mycrate,some message
+    /// ```
+    ///
+    /// # Options
+    ///
+    /// - [`Subscriber::with_columns`] declares the column set.
+    /// - [`Subscriber::with_delimiter`] replaces the `,` delimiter, e.g.
+    ///   with `'\t'` for TSV output.
+    /// - [`Subscriber::with_header`] controls the header row.
+    /// - [`Subscriber::with_extra_fields_column`] appends a trailing JSON
+    ///   column collecting unclaimed event fields.
+    pub fn csv(self) -> Subscriber<C, N, format::Format<format::Csv, T>, W>
+    where
+        N: for<'writer> FormatFields<'writer> + 'static,
+    {
+        Subscriber {
+            fmt_event: self.fmt_event.csv(),
+            fmt_fields: self.fmt_fields,
+            fmt_span: self.fmt_span,
+            make_writer: self.make_writer,
+            // always disable ANSI escapes in CSV mode!
+            is_ansi: false,
+            #[cfg(feature = "ansi")]
+            theme: format::Theme::none(),
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             _inner: self._inner,
         }
     }
@@ -577,7 +863,10 @@ where
             fmt_span: self.fmt_span,
             make_writer: self.make_writer,
             is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             _inner: self._inner,
         }
     }
@@ -607,10 +896,297 @@ where
             make_writer: self.make_writer,
             // always disable ANSI escapes in JSON mode!
             is_ansi: false,
+            #[cfg(feature = "ansi")]
+            theme: format::Theme::none(),
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             _inner: self._inner,
         }
     }
+
+    /// Sets the subscriber being built to use a [GELF formatter](format::Gelf),
+    /// for shipping logs to a Graylog input.
+    ///
+    /// # Options
+    ///
+    /// - [`Subscriber::with_host`] can be used to set the payload's `host`
+    ///   key.
+    ///
+    /// See [`format::Gelf`] for details, and [`format::GelfUdpWriter`] and
+    /// [`format::GelfTcpWriter`] for the matching transports.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn gelf(self) -> Subscriber<C, format::JsonFields, format::Format<format::Gelf, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.gelf(),
+            fmt_fields: format::JsonFields::new(),
+            fmt_span: self.fmt_span,
+            make_writer: self.make_writer,
+            // always disable ANSI escapes in GELF mode!
+            is_ansi: false,
+            #[cfg(feature = "ansi")]
+            theme: format::Theme::none(),
+            log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
+            _inner: self._inner,
+        }
+    }
+
+    /// Sets the subscriber being built to use a
+    /// [syslog formatter](format::Syslog), for shipping logs to a syslog
+    /// collector.
+    ///
+    /// # Options
+    ///
+    /// - [`Subscriber::with_facility`] sets the facility used to compute
+    ///   each message's `PRI`.
+    /// - [`Subscriber::with_app_name`] sets the `APP-NAME` part.
+    /// - [`Subscriber::with_sd_id`] sets the `SD-ID` of the structured-data
+    ///   element carrying the event's fields.
+    /// - [`Subscriber::rfc_3164`] switches to the legacy RFC 3164 format.
+    ///
+    /// See [`format::Syslog`] for details, and [`format::SyslogUnixWriter`],
+    /// [`format::SyslogUdpWriter`], and [`format::SyslogTcpWriter`] for the
+    /// matching transports.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn syslog(self) -> Subscriber<C, format::JsonFields, format::Format<format::Syslog, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.syslog(),
+            fmt_fields: format::JsonFields::new(),
+            fmt_span: self.fmt_span,
+            make_writer: self.make_writer,
+            // always disable ANSI escapes in syslog mode!
+            is_ansi: false,
+            #[cfg(feature = "ansi")]
+            theme: format::Theme::none(),
+            log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
+            _inner: self._inner,
+        }
+    }
+
+    /// Sets the subscriber being built to use a [logfmt formatter](format::Logfmt).
+    ///
+    /// Each event is formatted as a single line of space-separated
+    /// `key=value` pairs, with fields from the event's spans flattened onto
+    /// the line.
+    ///
+    /// # Example Output
+    ///
+    /// ```text
+    /// ts="fake time" level=info target=mycrate message="some message" key=value
+    /// ```
+    ///
+    /// # Options
+    ///
+    /// - [`Subscriber::with_span_field_prefix`] can be used to override how
+    ///   flattened span field keys are prefixed.
+    pub fn logfmt(
+        self,
+    ) -> Subscriber<C, format::LogfmtFields, format::Format<format::Logfmt, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.logfmt(),
+            fmt_fields: format::LogfmtFields::new(),
+            fmt_span: self.fmt_span,
+            make_writer: self.make_writer,
+            // always disable ANSI escapes in logfmt mode!
+            is_ansi: false,
+            #[cfg(feature = "ansi")]
+            theme: format::Theme::none(),
+            log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
+            _inner: self._inner,
+        }
+    }
+}
+
+impl<C, N, T, W> Subscriber<C, N, format::Format<format::Columns, T>, W> {
+    /// Sets the width of the target column, or disables target alignment
+    /// when `None`.
+    ///
+    /// See [`format::Columns`]
+    pub fn with_target_width(
+        self,
+        width: Option<usize>,
+    ) -> Subscriber<C, N, format::Format<format::Columns, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_target_width(width),
+            ..self
+        }
+    }
+
+    /// Sets the width of the message column, or disables message alignment
+    /// when `None`.
+    ///
+    /// See [`format::Columns`]
+    pub fn with_message_width(
+        self,
+        width: Option<usize>,
+    ) -> Subscriber<C, N, format::Format<format::Columns, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_message_width(width),
+            ..self
+        }
+    }
+
+    /// Sets whether messages longer than the message column are truncated,
+    /// rather than overflowing it.
+    ///
+    /// See [`format::Columns`]
+    pub fn with_truncate_message(
+        self,
+        truncate_message: bool,
+    ) -> Subscriber<C, N, format::Format<format::Columns, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_truncate_message(truncate_message),
+            ..self
+        }
+    }
+}
+
+impl<C, N, T, W> Subscriber<C, N, format::Format<format::Csv, T>, W> {
+    /// Declares the set of columns written for each event, replacing the
+    /// default column set.
+    ///
+    /// See [`format::Csv`]
+    pub fn with_columns(
+        self,
+        columns: impl IntoIterator<Item = format::CsvColumn>,
+    ) -> Subscriber<C, N, format::Format<format::Csv, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_columns(columns),
+            ..self
+        }
+    }
+
+    /// Sets the delimiter written between cells.
+    ///
+    /// See [`format::Csv`]
+    pub fn with_delimiter(
+        self,
+        delimiter: char,
+    ) -> Subscriber<C, N, format::Format<format::Csv, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_delimiter(delimiter),
+            ..self
+        }
+    }
+
+    /// Sets whether a header row naming the columns is written before the
+    /// first record.
+    ///
+    /// See [`format::Csv`]
+    pub fn with_header(self, header: bool) -> Subscriber<C, N, format::Format<format::Csv, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_header(header),
+            ..self
+        }
+    }
+
+    /// Appends a trailing column collecting unclaimed event fields as a JSON
+    /// object.
+    ///
+    /// See [`format::Csv`]
+    pub fn with_extra_fields_column(
+        self,
+        name: impl Into<String>,
+    ) -> Subscriber<C, N, format::Format<format::Csv, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_extra_fields_column(name),
+            ..self
+        }
+    }
+}
+
+impl<C, T, W> Subscriber<C, format::LogfmtFields, format::Format<format::Logfmt, T>, W> {
+    /// Sets a fixed prefix for flattened span field keys, replacing the
+    /// default span-name prefix.
+    ///
+    /// See [`format::Logfmt`]
+    pub fn with_span_field_prefix(
+        self,
+        prefix: impl Into<String>,
+    ) -> Subscriber<C, format::LogfmtFields, format::Format<format::Logfmt, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_span_field_prefix(prefix),
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+impl<C, T, W> Subscriber<C, format::JsonFields, format::Format<format::Gelf, T>, W> {
+    /// Sets the value of the `host` key included in every GELF payload.
+    ///
+    /// See [`format::Gelf`]
+    pub fn with_host(
+        self,
+        host: impl Into<String>,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Gelf, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_host(host),
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+impl<C, T, W> Subscriber<C, format::JsonFields, format::Format<format::Syslog, T>, W> {
+    /// Sets the facility used to compute each syslog message's `PRI`.
+    ///
+    /// See [`format::Syslog`]
+    pub fn with_facility(
+        self,
+        facility: format::Facility,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Syslog, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_facility(facility),
+            ..self
+        }
+    }
+
+    /// Sets the value of the `APP-NAME` part included in every syslog
+    /// message.
+    ///
+    /// See [`format::Syslog`]
+    pub fn with_app_name(
+        self,
+        app_name: impl Into<String>,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Syslog, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_app_name(app_name),
+            ..self
+        }
+    }
+
+    /// Sets the `SD-ID` of the structured-data element carrying each event's
+    /// fields.
+    ///
+    /// See [`format::Syslog`]
+    pub fn with_sd_id(
+        self,
+        sd_id: impl Into<String>,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Syslog, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_sd_id(sd_id),
+            ..self
+        }
+    }
+
+    /// Switches to the legacy RFC 3164 ("BSD syslog") message format.
+    ///
+    /// See [`format::Syslog`]
+    pub fn rfc_3164(
+        self,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Syslog, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.rfc_3164(),
+            ..self
+        }
+    }
 }
 
 #[cfg(feature = "json")]
@@ -659,6 +1235,212 @@ impl<C, T, W> Subscriber<C, format::JsonFields, format::Format<format::Json, T>,
             ..self
         }
     }
+
+    /// If set to `true`, the timestamp is emitted as fractional seconds
+    /// since the Unix epoch, as a JSON number, instead of the string
+    /// rendered by the configured timer.
+    ///
+    /// See [`format::Json`]
+    pub fn with_unix_timestamp(
+        self,
+        unix_timestamp: bool,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Json, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_unix_timestamp(unix_timestamp),
+            fmt_fields: format::JsonFields::new(),
+            ..self
+        }
+    }
+
+    /// Sets the key under which the timestamp is emitted.
+    ///
+    /// See [`format::Json`]
+    pub fn with_timestamp_key(
+        self,
+        timestamp_key: &'static str,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Json, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_timestamp_key(timestamp_key),
+            fmt_fields: format::JsonFields::new(),
+            ..self
+        }
+    }
+
+    /// Sets the key under which the event's level is emitted.
+    ///
+    /// See [`format::Json`]
+    pub fn with_level_key(
+        self,
+        level_key: &'static str,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Json, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_level_key(level_key),
+            fmt_fields: format::JsonFields::new(),
+            ..self
+        }
+    }
+
+    /// Sets the key under which the event's target is emitted.
+    ///
+    /// See [`format::Json`]
+    pub fn with_target_key(
+        self,
+        target_key: &'static str,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Json, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_target_key(target_key),
+            fmt_fields: format::JsonFields::new(),
+            ..self
+        }
+    }
+
+    /// Adds a constant field emitted in every record, such as a service
+    /// name, version, or host.
+    ///
+    /// See [`format::Json`]
+    pub fn with_constant_field(
+        self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Json, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_constant_field(key, value),
+            fmt_fields: format::JsonFields::new(),
+            ..self
+        }
+    }
+
+    /// Sets whether the span list is emitted as a single object keyed by
+    /// span name, with typed field values nested under each name, rather
+    /// than as an array of objects.
+    ///
+    /// See [`format::Json`]
+    pub fn with_nested_spans(
+        self,
+        nested_spans: bool,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Json, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_nested_spans(nested_spans),
+            fmt_fields: format::JsonFields::new(),
+            ..self
+        }
+    }
+
+    /// Sets the key under which the event's fields are emitted.
+    ///
+    /// See [`format::Json`]
+    pub fn with_fields_key(
+        self,
+        fields_key: &'static str,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Json, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_fields_key(fields_key),
+            fmt_fields: format::JsonFields::new(),
+            ..self
+        }
+    }
+
+    /// Sets the key under which the current span is emitted.
+    ///
+    /// See [`format::Json`]
+    pub fn with_current_span_key(
+        self,
+        current_span_key: &'static str,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Json, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_current_span_key(current_span_key),
+            fmt_fields: format::JsonFields::new(),
+            ..self
+        }
+    }
+
+    /// Sets the key under which the span list is emitted.
+    ///
+    /// See [`format::Json`]
+    pub fn with_spans_key(
+        self,
+        spans_key: &'static str,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Json, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_spans_key(spans_key),
+            fmt_fields: format::JsonFields::new(),
+            ..self
+        }
+    }
+
+    /// Masks fields matching the provided [`RedactionRules`] before they are
+    /// serialized.
+    ///
+    /// See [`format::RedactionRules`] for details on configuring which
+    /// fields and values are masked.
+    ///
+    /// [`RedactionRules`]: format::RedactionRules
+    pub fn with_field_redaction(
+        self,
+        rules: format::RedactionRules,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Json, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_field_redaction(rules.clone()),
+            fmt_fields: self.fmt_fields.with_field_redaction(rules),
+            ..self
+        }
+    }
+
+    /// Truncates field values and lines according to the provided
+    /// [`TruncationRules`].
+    ///
+    /// Note that whole-line limits applied to JSON output produce lines that
+    /// are no longer valid JSON; see [`format::TruncationRules`] for details.
+    ///
+    /// [`TruncationRules`]: format::TruncationRules
+    pub fn with_truncation(
+        self,
+        rules: format::TruncationRules,
+    ) -> Subscriber<C, format::JsonFields, format::Format<format::Json, T>, W> {
+        Subscriber {
+            fmt_event: self.fmt_event.with_truncation(rules.clone()),
+            fmt_fields: self.fmt_fields.with_truncation(rules.clone()),
+            truncation: Some(rules),
+            ..self
+        }
+    }
+}
+
+impl<C, E, W> Subscriber<C, format::DefaultFields, E, W> {
+    /// Masks fields matching the provided [`RedactionRules`] before they are
+    /// written.
+    ///
+    /// See [`format::RedactionRules`] for details on configuring which
+    /// fields and values are masked.
+    ///
+    /// [`RedactionRules`]: format::RedactionRules
+    pub fn with_field_redaction(
+        self,
+        rules: format::RedactionRules,
+    ) -> Subscriber<C, format::DefaultFields, E, W> {
+        Subscriber {
+            fmt_fields: self.fmt_fields.with_field_redaction(rules),
+            ..self
+        }
+    }
+
+    /// Truncates field values and lines according to the provided
+    /// [`TruncationRules`].
+    ///
+    /// See [`format::TruncationRules`] for details on configuring value and
+    /// line limits.
+    ///
+    /// [`TruncationRules`]: format::TruncationRules
+    pub fn with_truncation(
+        self,
+        rules: format::TruncationRules,
+    ) -> Subscriber<C, format::DefaultFields, E, W> {
+        Subscriber {
+            fmt_fields: self.fmt_fields.with_truncation(rules.clone()),
+            truncation: Some(rules),
+            ..self
+        }
+    }
 }
 
 impl<C, N, E, W> Subscriber<C, N, E, W> {
@@ -674,7 +1456,10 @@ impl<C, N, E, W> Subscriber<C, N, E, W> {
             fmt_span: self.fmt_span,
             make_writer: self.make_writer,
             is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             _inner: self._inner,
         }
     }
@@ -705,7 +1490,10 @@ impl<C, N, E, W> Subscriber<C, N, E, W> {
             fmt_span: self.fmt_span,
             make_writer: self.make_writer,
             is_ansi: self.is_ansi,
+            #[cfg(feature = "ansi")]
+            theme: self.theme,
             log_internal_errors: self.log_internal_errors,
+            truncation: self.truncation,
             _inner: self._inner,
         }
     }
@@ -723,7 +1511,10 @@ impl<C> Default for Subscriber<C> {
             fmt_span: format::FmtSpanConfig::default(),
             make_writer: io::stdout,
             is_ansi: ansi,
+            #[cfg(feature = "ansi")]
+            theme: format::Theme::default(),
             log_internal_errors: false,
+            truncation: None,
             _inner: PhantomData,
         }
     }
@@ -744,6 +1535,14 @@ where
             event,
         }
     }
+
+    #[inline]
+    fn make_fields_writer<'a>(&self, fields: &'a mut FormattedFields<N>) -> format::Writer<'a> {
+        let writer = fields.as_writer().with_ansi(self.is_ansi);
+        #[cfg(feature = "ansi")]
+        let writer = writer.with_theme(self.theme);
+        writer
+    }
 }
 
 /// A formatted representation of a span's fields stored in its [extensions].
@@ -838,7 +1637,7 @@ where
             let mut fields = FormattedFields::<N>::new(String::new());
             if self
                 .fmt_fields
-                .format_fields(fields.as_writer().with_ansi(self.is_ansi), attrs)
+                .format_fields(self.make_fields_writer(&mut fields), attrs)
                 .is_ok()
             {
                 fields.was_ansi = self.is_ansi;
@@ -878,7 +1677,7 @@ where
         let mut fields = FormattedFields::<N>::new(String::new());
         if self
             .fmt_fields
-            .format_fields(fields.as_writer().with_ansi(self.is_ansi), values)
+            .format_fields(self.make_fields_writer(&mut fields), values)
             .is_ok()
         {
             fields.was_ansi = self.is_ansi;
@@ -988,12 +1787,20 @@ where
                 .fmt_event
                 .format_event(
                     &ctx,
-                    format::Writer::new(&mut buf).with_ansi(self.is_ansi),
+                    {
+                        let writer = format::Writer::new(&mut buf).with_ansi(self.is_ansi);
+                        #[cfg(feature = "ansi")]
+                        let writer = writer.with_theme(self.theme);
+                        writer
+                    },
                     event,
                 )
                 .is_ok()
             {
-                let mut writer = self.make_writer.make_writer_for(event.metadata());
+                if let Some(rules) = &self.truncation {
+                    rules.apply_to_line(buf);
+                }
+                let mut writer = self.make_writer.make_writer_for_event(event);
                 let res = io::Write::write_all(&mut writer, buf.as_bytes());
                 if self.log_internal_errors {
                     if let Err(e) = res {
@@ -1003,7 +1810,7 @@ where
             } else if self.log_internal_errors {
                 let err_msg = format!("Unable to format the following event. Name: {}; Fields: {:?}\n",
                     event.metadata().name(), event.fields());
-                let mut writer = self.make_writer.make_writer_for(event.metadata());
+                let mut writer = self.make_writer.make_writer_for_event(event);
                 let res = io::Write::write_all(&mut writer, err_msg.as_bytes());
                 if let Err(e) = res {
                     eprintln!("[tracing-subscriber] Unable to write an \"event formatting error\" to the Writer for this Subscriber! Error: {}\n", e);
@@ -0,0 +1,265 @@
+use super::{FmtThreadName, Format, FormatEvent, FormatFields, FormatTime, Writer};
+use crate::{
+    fmt::fmt_subscriber::{FmtContext, FormattedFields},
+    registry::{LookupSpan, Scope},
+};
+use std::fmt;
+use tracing_core::{Collect, Event};
+
+#[cfg(feature = "tracing-log")]
+use tracing_log::NormalizeEvent;
+
+/// Marker for [`Format`] that indicates that a column-aligned log format
+/// should be used.
+///
+/// The level, target, and message are each written into a fixed-width
+/// column, so that interactive terminal output lines up and can be scanned
+/// vertically. Fields from the event's spans are appended after the message
+/// column, as in the [`Compact`](super::Compact) format.
+///
+/// Targets longer than their column are truncated from the left (keeping the
+/// most specific trailing segments) and prefixed with `…`. Messages longer
+/// than their column overflow it by default, shifting the rest of the line
+/// right; [`with_truncate_message`] can be used to truncate them instead.
+/// Setting a width to `None` disables alignment for that column, and the
+/// level and target columns can be removed entirely with
+/// [`Format::with_level`] and [`Format::with_target`].
+///
+/// Note that column widths are measured in characters, so they do not
+/// account for ANSI escape codes; alignment is most accurate with ANSI
+/// colors disabled.
+///
+/// # Example Output
+///
+/// ```text
+/// 2022-02-15T18:40:14.289898Z  INFO app::db                   connection established       port=5432
+/// 2022-02-15T18:40:14.289974Z  WARN app::db::pool             retrying                     attempt=2
+/// 2022-02-15T18:40:14.290011Z DEBUG app::server               listening
+/// ```
+///
+/// [`with_truncate_message`]: Format::with_truncate_message
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Columns {
+    pub(crate) target_width: Option<usize>,
+    pub(crate) message_width: Option<usize>,
+    pub(crate) truncate_message: bool,
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Self {
+            target_width: Some(24),
+            message_width: None,
+            truncate_message: false,
+        }
+    }
+}
+
+impl Columns {
+    /// See [`Format::with_target_width`].
+    pub(crate) fn with_target_width(&mut self, width: Option<usize>) {
+        self.target_width = width;
+    }
+
+    /// See [`Format::with_message_width`].
+    pub(crate) fn with_message_width(&mut self, width: Option<usize>) {
+        self.message_width = width;
+    }
+
+    /// See [`Format::with_truncate_message`].
+    pub(crate) fn with_truncate_message(&mut self, truncate_message: bool) {
+        self.truncate_message = truncate_message;
+    }
+}
+
+/// Truncates `text` from the left to `width` characters, marking the elision
+/// with a leading `…`.
+fn truncate_start(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return text.to_string();
+    }
+
+    let skip = len - width.saturating_sub(1);
+    let tail: String = text.chars().skip(skip).collect();
+    format!("…{}", tail)
+}
+
+impl<C, N, T> FormatEvent<C, N> for Format<Columns, T>
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    T: FormatTime,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, C, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        #[cfg(feature = "tracing-log")]
+        let normalized_meta = event.normalized_metadata();
+        #[cfg(feature = "tracing-log")]
+        let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
+        #[cfg(not(feature = "tracing-log"))]
+        let meta = event.metadata();
+
+        self.format_timestamp(&mut writer)?;
+        self.format_level(*meta.level(), &mut writer)?;
+
+        if self.display_thread_name {
+            let current_thread = std::thread::current();
+            match current_thread.name() {
+                Some(name) => {
+                    write!(writer, "{} ", FmtThreadName::new(name))?;
+                }
+                // fall-back to thread id when name is absent and ids are not enabled
+                None if !self.display_thread_id => {
+                    write!(writer, "{:0>2?} ", current_thread.id())?;
+                }
+                _ => {}
+            }
+        }
+
+        if self.display_thread_id {
+            write!(writer, "{:0>2?} ", std::thread::current().id())?;
+        }
+
+        self.format_process_info(&mut writer)?;
+
+        if self.display_target {
+            let target_style = writer.target_style();
+            match self.format.target_width {
+                Some(width) => {
+                    let column = format!("{:<width$}", truncate_start(meta.target(), width));
+                    write!(writer, "{} ", target_style.paint(column))?;
+                }
+                None => write!(writer, "{} ", target_style.paint(meta.target()))?,
+            }
+        }
+
+        let mut message = String::new();
+        {
+            let message_writer = Writer::new(&mut message).with_ansi(writer.has_ansi_escapes());
+            #[cfg(feature = "ansi")]
+            let message_writer = message_writer.with_theme(writer.theme);
+            ctx.format_fields(message_writer, event)?;
+        }
+        match self.format.message_width {
+            Some(width) if self.format.truncate_message && message.chars().count() > width => {
+                let truncated: String = message.chars().take(width.saturating_sub(1)).collect();
+                write!(writer, "{}…", truncated)?;
+            }
+            Some(width) => write!(writer, "{:<width$}", message, width = width)?,
+            None => writer.write_str(&message)?,
+        }
+
+        let dimmed = writer.dimmed();
+        for span in ctx.event_scope().into_iter().flat_map(Scope::from_root) {
+            let exts = span.extensions();
+            if let Some(fields) = exts.get::<FormattedFields<N>>() {
+                if !fields.is_empty() {
+                    write!(writer, " {}", dimmed.paint(&fields.fields))?;
+                }
+            }
+        }
+
+        self.format_resource(&mut writer)?;
+        writeln!(writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fmt::{
+        format::DefaultFields, test::MockMakeWriter, time::FormatTime, CollectorBuilder,
+    };
+
+    use tracing::{self, collect::with_default};
+
+    struct MockTime;
+    impl FormatTime for MockTime {
+        fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+            write!(w, "fake time")
+        }
+    }
+
+    fn collector() -> CollectorBuilder<DefaultFields, Format<Columns>> {
+        CollectorBuilder::default().columns()
+    }
+
+    fn test_columns<T>(
+        expected: &str,
+        builder: CollectorBuilder<DefaultFields, Format<Columns>>,
+        f: T,
+    ) where
+        T: Fn(),
+    {
+        let make_writer = MockMakeWriter::default();
+        let collector = builder
+            .with_writer(make_writer.clone())
+            .with_ansi(false)
+            .with_timer(MockTime)
+            .finish();
+
+        with_default(collector, f);
+
+        let actual = make_writer.get_string();
+        assert_eq!(expected, actual.as_str());
+    }
+
+    #[test]
+    fn columns_pad_and_align() {
+        let expected = "fake time  INFO app::db    ready to serve port=5432      pool=3\n";
+        let collector = collector()
+            .with_target_width(Some(10))
+            .with_message_width(Some(29));
+        test_columns(expected, collector, || {
+            let span = tracing::info_span!("pool", pool = 3);
+            let _guard = span.enter();
+            tracing::info!(target: "app::db", port = 5432, "ready to serve");
+        });
+    }
+
+    #[test]
+    fn columns_truncate_long_target() {
+        let expected = "fake time  WARN \u{2026}g::target hello\n";
+        let collector = collector().with_target_width(Some(10));
+        test_columns(expected, collector, || {
+            tracing::warn!(target: "very::long::target", "hello");
+        });
+    }
+
+    #[test]
+    fn columns_message_overflows_by_default() {
+        let expected = "fake time  INFO app a message longer than its column extra=true\n";
+        let collector = collector()
+            .with_target_width(Some(3))
+            .with_message_width(Some(10));
+        test_columns(expected, collector, || {
+            tracing::info!(target: "app", extra = true, "a message longer than its column");
+        });
+    }
+
+    #[test]
+    fn columns_truncate_message() {
+        let expected = "fake time  INFO app a very l\u{2026}\n";
+        let collector = collector()
+            .with_target_width(Some(3))
+            .with_message_width(Some(9))
+            .with_truncate_message(true);
+        test_columns(expected, collector, || {
+            tracing::info!(target: "app", "a very long message");
+        });
+    }
+
+    #[test]
+    fn columns_unaligned_when_widths_are_disabled() {
+        let expected = "fake time  INFO app::db hello\n";
+        let collector = collector().with_target_width(None);
+        test_columns(expected, collector, || {
+            tracing::info!(target: "app::db", "hello");
+        });
+    }
+}
@@ -0,0 +1,479 @@
+use super::{Format, FormatEvent, FormatFields, FormatTime, Writer};
+use crate::{fmt::fmt_subscriber::FmtContext, registry::LookupSpan};
+use std::{
+    fmt::{self, Write as _},
+    sync::atomic::{AtomicBool, Ordering},
+};
+use tracing_core::{
+    field::{self, Field},
+    Collect, Event,
+};
+
+#[cfg(feature = "tracing-log")]
+use tracing_log::NormalizeEvent;
+
+/// Marker for [`Format`] that indicates that the CSV log format should be
+/// used.
+///
+/// Each event is formatted as one delimiter-separated record, with a column
+/// set declared up front, so the output can be loaded directly into a
+/// spreadsheet or a `pandas` data frame. Cells containing the delimiter,
+/// double quotes, or line breaks are quoted and escaped as described by
+/// [RFC 4180]; everything else is written bare.
+///
+/// # Example Output
+///
+/// ```text
+/// timestamp,level,target,message
+/// fake time,INFO,mycrate,some message
+/// ```
+///
+/// # Options
+///
+/// - [`Csv::with_columns`] declares the column set. The built-in
+///   [`CsvColumn::Timestamp`], [`CsvColumn::Level`], and
+///   [`CsvColumn::Target`] columns may be combined with any number of
+///   [`CsvColumn::Field`] columns naming event fields; an event that does
+///   not record a named field leaves that cell empty. The default column
+///   set is the timestamp, level, target, and `message` columns.
+/// - [`Csv::with_delimiter`] replaces the `,` delimiter, e.g. with `'\t'`
+///   for TSV output.
+/// - [`Csv::with_header`] controls whether a header row naming the columns
+///   is written before the first record (enabled by default).
+/// - [`Csv::with_extra_fields_column`] appends a trailing column collecting
+///   any event fields not already claimed by a [`CsvColumn::Field`] column,
+///   encoded as a JSON object.
+///
+/// Only the fields of the event itself are considered for
+/// [`CsvColumn::Field`] and extra-fields columns; span fields are not
+/// included in CSV output.
+///
+/// The header row is written by the formatter the first time it formats an
+/// event, so it precedes the records in the output without requiring any
+/// explicit setup step.
+///
+/// [RFC 4180]: https://www.rfc-editor.org/rfc/rfc4180
+#[derive(Debug)]
+pub struct Csv {
+    pub(crate) columns: Vec<CsvColumn>,
+    pub(crate) delimiter: char,
+    pub(crate) header: bool,
+    pub(crate) extra_fields_column: Option<String>,
+    header_written: AtomicBool,
+}
+
+/// A column of the [`Csv`] log format.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CsvColumn {
+    /// The event's timestamp, as rendered by the [`Format`]'s timer.
+    ///
+    /// If timestamps are disabled with [`without_time`], this column is left
+    /// empty.
+    ///
+    /// [`without_time`]: Format::without_time
+    Timestamp,
+    /// The event's verbosity level, e.g. `INFO`.
+    Level,
+    /// The event's target.
+    Target,
+    /// The value of the event field with the given name.
+    ///
+    /// The event's message is available as the `message` field.
+    Field(String),
+}
+
+impl Csv {
+    /// Declares the set of columns written for each event, replacing the
+    /// default column set.
+    ///
+    /// See [`CsvColumn`] for the available columns.
+    pub fn with_columns(&mut self, columns: impl IntoIterator<Item = CsvColumn>) {
+        self.columns = columns.into_iter().collect();
+    }
+
+    /// Sets the delimiter written between cells.
+    ///
+    /// The default delimiter is `,`; pass `'\t'` for TSV output.
+    pub fn with_delimiter(&mut self, delimiter: char) {
+        self.delimiter = delimiter;
+    }
+
+    /// Sets whether a header row naming the columns is written before the
+    /// first record.
+    ///
+    /// Enabled by default.
+    pub fn with_header(&mut self, header: bool) {
+        self.header = header;
+    }
+
+    /// Appends a trailing column with the given name, collecting any event
+    /// fields not claimed by a [`CsvColumn::Field`] column as a JSON object.
+    pub fn with_extra_fields_column(&mut self, name: impl Into<String>) {
+        self.extra_fields_column = Some(name.into());
+    }
+}
+
+impl Default for Csv {
+    fn default() -> Self {
+        Self {
+            columns: vec![
+                CsvColumn::Timestamp,
+                CsvColumn::Level,
+                CsvColumn::Target,
+                CsvColumn::Field(String::from("message")),
+            ],
+            delimiter: ',',
+            header: true,
+            extra_fields_column: None,
+            header_written: AtomicBool::new(false),
+        }
+    }
+}
+
+impl Clone for Csv {
+    fn clone(&self) -> Self {
+        Self {
+            columns: self.columns.clone(),
+            delimiter: self.delimiter,
+            header: self.header,
+            extra_fields_column: self.extra_fields_column.clone(),
+            // A cloned formatter writes its own header row.
+            header_written: AtomicBool::new(false),
+        }
+    }
+}
+
+impl CsvColumn {
+    /// Returns the name written for this column in the header row.
+    fn name(&self) -> &str {
+        match self {
+            CsvColumn::Timestamp => "timestamp",
+            CsvColumn::Level => "level",
+            CsvColumn::Target => "target",
+            CsvColumn::Field(name) => name,
+        }
+    }
+}
+
+impl<C, N, T> FormatEvent<C, N> for Format<Csv, T>
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    T: FormatTime,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, C, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        #[cfg(feature = "tracing-log")]
+        let normalized_meta = event.normalized_metadata();
+        #[cfg(feature = "tracing-log")]
+        let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
+        #[cfg(not(feature = "tracing-log"))]
+        let meta = event.metadata();
+
+        if self.format.header && !self.format.header_written.swap(true, Ordering::Relaxed) {
+            for (i, column) in self.format.columns.iter().enumerate() {
+                if i > 0 {
+                    writer.write_char(self.format.delimiter)?;
+                }
+                write_cell(&mut writer, column.name(), self.format.delimiter)?;
+            }
+            if let Some(name) = &self.format.extra_fields_column {
+                if !self.format.columns.is_empty() {
+                    writer.write_char(self.format.delimiter)?;
+                }
+                write_cell(&mut writer, name, self.format.delimiter)?;
+            }
+            writeln!(writer)?;
+        }
+
+        let mut visitor = CsvVisitor::default();
+        event.record(&mut visitor);
+
+        for (i, column) in self.format.columns.iter().enumerate() {
+            if i > 0 {
+                writer.write_char(self.format.delimiter)?;
+            }
+            match column {
+                CsvColumn::Timestamp => {
+                    if self.display_timestamp {
+                        let mut timestamp = String::new();
+                        // If getting the timestamp failed, don't bail ---
+                        // only bail on formatting errors.
+                        if self
+                            .timer
+                            .format_time(&mut Writer::new(&mut timestamp))
+                            .is_err()
+                        {
+                            timestamp = String::from("<unknown time>");
+                        }
+                        write_cell(&mut writer, &timestamp, self.format.delimiter)?;
+                    }
+                }
+                CsvColumn::Level => write!(writer, "{}", meta.level())?,
+                CsvColumn::Target => write_cell(&mut writer, meta.target(), self.format.delimiter)?,
+                CsvColumn::Field(name) => {
+                    if let Some((_, value)) =
+                        visitor.values.iter().rev().find(|(field, _)| field == name)
+                    {
+                        write_cell(&mut writer, value.cell(), self.format.delimiter)?;
+                    }
+                }
+            }
+        }
+
+        if let Some(_name) = &self.format.extra_fields_column {
+            if !self.format.columns.is_empty() {
+                writer.write_char(self.format.delimiter)?;
+            }
+            let mut json = String::from("{");
+            let mut wrote_any = false;
+            for (field, value) in &visitor.values {
+                let claimed = self
+                    .format
+                    .columns
+                    .iter()
+                    .any(|column| matches!(column, CsvColumn::Field(name) if name == field));
+                if claimed {
+                    continue;
+                }
+                if wrote_any {
+                    json.push(',');
+                }
+                write_json_str(&mut json, field);
+                json.push(':');
+                match value {
+                    CsvValue::Str(value) => write_json_str(&mut json, value),
+                    CsvValue::Raw(value) => json.push_str(value),
+                }
+                wrote_any = true;
+            }
+            json.push('}');
+            write_cell(&mut writer, &json, self.format.delimiter)?;
+        }
+
+        writeln!(writer)
+    }
+}
+
+/// Writes `value` as one cell, quoting and escaping it as described by RFC
+/// 4180 if it contains the delimiter, a double quote, or a line break.
+fn write_cell(writer: &mut Writer<'_>, value: &str, delimiter: char) -> fmt::Result {
+    let needs_quoting = value
+        .chars()
+        .any(|c| c == delimiter || c == '"' || c == '\n' || c == '\r');
+    if !needs_quoting {
+        return writer.write_str(value);
+    }
+    writer.write_char('"')?;
+    for c in value.chars() {
+        if c == '"' {
+            writer.write_str("\"\"")?;
+        } else {
+            writer.write_char(c)?;
+        }
+    }
+    writer.write_char('"')
+}
+
+/// Writes `value` as a JSON string, escaping quotes, backslashes, and
+/// control characters.
+fn write_json_str(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// The value of an event field, as captured for a CSV record.
+///
+/// `Raw` values (numbers and booleans) are written into the extra-fields
+/// JSON object without quoting; `Str` values are quoted and escaped.
+#[derive(Debug)]
+enum CsvValue {
+    Str(String),
+    Raw(String),
+}
+
+impl CsvValue {
+    /// Returns the value as written into an ordinary cell.
+    fn cell(&self) -> &str {
+        match self {
+            CsvValue::Str(value) | CsvValue::Raw(value) => value,
+        }
+    }
+}
+
+/// Captures the fields of an event for CSV formatting.
+#[derive(Debug, Default)]
+struct CsvVisitor {
+    values: Vec<(&'static str, CsvValue)>,
+}
+
+impl CsvVisitor {
+    fn record(&mut self, field: &Field, value: CsvValue) {
+        let name = field.name();
+        // Skip fields that are actually log metadata that have already been handled
+        #[cfg(feature = "tracing-log")]
+        if name.starts_with("log.") {
+            return;
+        }
+        let name = name.strip_prefix("r#").unwrap_or(name);
+        self.values.push((name, value));
+    }
+}
+
+impl field::Visit for CsvVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record(field, CsvValue::Raw(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record(field, CsvValue::Raw(value.to_string()));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record(field, CsvValue::Raw(value.to_string()));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record(field, CsvValue::Raw(value.to_string()));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, CsvValue::Str(value.to_owned()));
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.record(field, CsvValue::Str(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, CsvValue::Str(format!("{:?}", value)));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fmt::format::DefaultFields;
+    use crate::fmt::{test::MockMakeWriter, time::FormatTime, CollectorBuilder};
+
+    use tracing::{self, collect::with_default};
+
+    struct MockTime;
+    impl FormatTime for MockTime {
+        fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+            write!(w, "fake time")
+        }
+    }
+
+    fn collector() -> CollectorBuilder<DefaultFields, Format<Csv>> {
+        CollectorBuilder::default().csv()
+    }
+
+    fn test_csv<T>(expected: &str, builder: CollectorBuilder<DefaultFields, Format<Csv>>, f: T)
+    where
+        T: Fn(),
+    {
+        let make_writer = MockMakeWriter::default();
+        let collector = builder
+            .with_writer(make_writer.clone())
+            .with_timer(MockTime)
+            .finish();
+
+        with_default(collector, f);
+
+        let actual = make_writer.get_string();
+        assert_eq!(expected, actual.as_str());
+    }
+
+    #[test]
+    fn default_columns_and_header() {
+        let expected = "timestamp,level,target,message\n\
+                        fake time,INFO,tracing_subscriber::fmt::format::csv::test,first\n\
+                        fake time,WARN,tracing_subscriber::fmt::format::csv::test,second\n";
+        test_csv(expected, collector(), || {
+            tracing::info!("first");
+            tracing::warn!("second");
+        });
+    }
+
+    #[test]
+    fn missing_fields_are_empty_cells() {
+        let expected = "level,user,code\n\
+                        INFO,alice,\n\
+                        INFO,,7\n";
+        test_csv(
+            expected,
+            collector().with_columns(vec![
+                CsvColumn::Level,
+                CsvColumn::Field(String::from("user")),
+                CsvColumn::Field(String::from("code")),
+            ]),
+            || {
+                tracing::info!(user = "alice", "ignored");
+                tracing::info!(code = 7, "ignored");
+            },
+        );
+    }
+
+    #[test]
+    fn cells_are_quoted() {
+        let expected = "fake time,INFO,tracing_subscriber::fmt::format::csv::test,\
+                        \"hello, \"\"world\"\"\"\n";
+        test_csv(expected, collector().with_header(false), || {
+            tracing::info!("hello, \"world\"");
+        });
+    }
+
+    #[test]
+    fn tab_delimiter() {
+        let expected = "level\tmessage\n\
+                        INFO\ta, b\n";
+        test_csv(
+            expected,
+            collector()
+                .with_columns(vec![
+                    CsvColumn::Level,
+                    CsvColumn::Field(String::from("message")),
+                ])
+                .with_delimiter('\t'),
+            || {
+                tracing::info!("a, b");
+            },
+        );
+    }
+
+    #[test]
+    fn extra_fields_as_json() {
+        let expected = "level,message,extra\n\
+                        INFO,hi,\"{\"\"user\"\":\"\"alice\"\",\"\"code\"\":7,\"\"flag\"\":true}\"\n";
+        test_csv(
+            expected,
+            collector()
+                .with_columns(vec![
+                    CsvColumn::Level,
+                    CsvColumn::Field(String::from("message")),
+                ])
+                .with_extra_fields_column("extra"),
+            || {
+                tracing::info!(user = "alice", code = 7, flag = true, "hi");
+            },
+        );
+    }
+}
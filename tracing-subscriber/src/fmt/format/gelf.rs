@@ -0,0 +1,655 @@
+use super::{Format, FormatEvent, FormatFields, Writer};
+use crate::{
+    fmt::{
+        fmt_subscriber::{FmtContext, FormattedFields},
+        writer::MakeWriter,
+    },
+    registry::LookupSpan,
+};
+use serde_json::{Map, Value};
+use std::{
+    fmt,
+    io::{self, Write as _},
+    net::{TcpStream, ToSocketAddrs, UdpSocket},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing_core::{
+    field::{self, Field},
+    Collect, Event, Level,
+};
+
+#[cfg(feature = "tracing-log")]
+use tracing_log::NormalizeEvent;
+
+/// Marker for [`Format`] that indicates that the GELF log format should be
+/// used.
+///
+/// Each event is formatted as a newline-delimited [GELF] payload, so that
+/// services can ship logs directly to a Graylog input without a conversion
+/// sidecar. The payload contains the standard `version`, `host`,
+/// `short_message`, `timestamp`, and `level` keys, with the event's level
+/// mapped to the equivalent syslog severity. All other data — the event's
+/// remaining fields, the fields of its spans, its target, and (if enabled on
+/// the [`Format`]) its source location — is emitted as `_`-prefixed
+/// additional fields, as the spec requires.
+///
+/// # Example Output
+///
+/// ```ignore,json
+/// {"_answer":42,"_target":"mycrate","host":"myhost","level":6,"short_message":"some message","timestamp":1692700000.123,"version":"1.1"}
+/// ```
+///
+/// The event's `message` field becomes `short_message`; events without a
+/// message use the event's metadata name. Span fields are flattened into
+/// additional fields from root to leaf, so an inner span's field wins over
+/// an outer span's field with the same name.
+///
+/// # Options
+///
+/// - [`Gelf::with_host`] sets the `host` key. It defaults to the `HOSTNAME`
+///   environment variable, falling back to `localhost`.
+///
+/// Delivery to Graylog is handled by the writer: pair this formatter with
+/// [`GelfUdpWriter`] for chunked UDP or [`GelfTcpWriter`] for
+/// null-delimited TCP framing.
+///
+/// [GELF]: https://go2docs.graylog.org/current/getting_in_log_data/gelf.html
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Gelf {
+    pub(crate) host: String,
+}
+
+impl Default for Gelf {
+    fn default() -> Self {
+        Self {
+            host: std::env::var("HOSTNAME").unwrap_or_else(|_| String::from("localhost")),
+        }
+    }
+}
+
+impl Gelf {
+    /// Sets the value of the `host` key included in every payload.
+    pub fn with_host(&mut self, host: impl Into<String>) {
+        self.host = host.into();
+    }
+}
+
+impl<C, N, T> FormatEvent<C, N> for Format<Gelf, T>
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, C, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        #[cfg(feature = "tracing-log")]
+        let normalized_meta = event.normalized_metadata();
+        #[cfg(feature = "tracing-log")]
+        let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
+        #[cfg(not(feature = "tracing-log"))]
+        let meta = event.metadata();
+
+        let mut payload = Map::new();
+        payload.insert("version".into(), Value::from("1.1"));
+        payload.insert("host".into(), Value::from(&self.format.host[..]));
+        payload.insert("level".into(), Value::from(syslog_level(meta.level())));
+
+        if self.display_timestamp {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs_f64())
+                .unwrap_or(0.0);
+            payload.insert("timestamp".into(), Value::from(timestamp));
+        }
+
+        if self.display_target {
+            payload.insert("_target".into(), Value::from(meta.target()));
+        }
+
+        if self.display_filename {
+            if let Some(filename) = meta.file() {
+                payload.insert("_file".into(), Value::from(filename));
+            }
+        }
+
+        if self.display_line_number {
+            if let Some(line_number) = meta.line() {
+                payload.insert("_line".into(), Value::from(line_number));
+            }
+        }
+
+        // Span fields from root to leaf, so that inner spans override outer
+        // ones on key collisions.
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let ext = span.extensions();
+                if let Some(fields) = ext.get::<FormattedFields<N>>() {
+                    if fields.is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<Map<String, Value>>(fields.fields.as_str()) {
+                        Ok(span_fields) => {
+                            for (key, value) in span_fields {
+                                payload.insert(format!("_{}", key), value);
+                            }
+                        }
+                        // The span's fields were formatted by something
+                        // other than `JsonFields`; include them verbatim
+                        // under the span's name.
+                        Err(_) => {
+                            payload.insert(
+                                format!("_{}", span.name()),
+                                Value::from(&fields.fields[..]),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut visitor = GelfVisitor {
+            payload: &mut payload,
+            message: None,
+        };
+        event.record(&mut visitor);
+        let message = visitor.message.unwrap_or_else(|| meta.name().to_string());
+        payload.insert("short_message".into(), Value::from(message));
+
+        let payload = serde_json::to_string(&payload).map_err(|_| fmt::Error)?;
+        writer.write_str(&payload)?;
+        writeln!(writer)
+    }
+}
+
+/// Returns the syslog severity corresponding to `level`, as used by the GELF
+/// `level` key.
+fn syslog_level(level: &Level) -> u64 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        Level::DEBUG | Level::TRACE => 7,
+    }
+}
+
+/// Records an event's fields into a GELF payload, keeping the `message`
+/// field aside for `short_message`.
+struct GelfVisitor<'a> {
+    payload: &'a mut Map<String, Value>,
+    message: Option<String>,
+}
+
+impl GelfVisitor<'_> {
+    fn record_value(&mut self, field: &Field, value: Value) {
+        let name = field.name();
+        // Skip fields that are actually log metadata that have already been handled
+        #[cfg(feature = "tracing-log")]
+        if name.starts_with("log.") {
+            return;
+        }
+        let name = name.strip_prefix("r#").unwrap_or(name);
+        self.payload.insert(format!("_{}", name), value);
+    }
+}
+
+impl field::Visit for GelfVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record_value(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_value(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record_value(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record_value(field, Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_string());
+        } else {
+            self.record_value(field, Value::from(value));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{:?}", value));
+        } else {
+            self.record_value(field, Value::from(format!("{:?}", value)));
+        }
+    }
+}
+
+/// The GELF chunked-message header length: two magic bytes, an 8-byte
+/// message ID, a sequence number, and a sequence count.
+const CHUNK_HEADER_LEN: usize = 12;
+
+/// The maximum number of chunks a GELF message may be split into.
+const MAX_CHUNKS: usize = 128;
+
+/// A [`MakeWriter`] that ships each formatted event to a Graylog UDP input.
+///
+/// Each event becomes one datagram; messages larger than the configured
+/// datagram size are split using GELF's chunking scheme, up to the
+/// spec's limit of 128 chunks. Oversized messages and network errors are
+/// reported to the [`fmt` subscriber], which drops the event.
+///
+/// This writer is intended for use with the [`Gelf`] formatter:
+///
+/// ```no_run
+/// use tracing_subscriber::fmt::format::GelfUdpWriter;
+///
+/// let writer = GelfUdpWriter::connect("graylog.example.com:12201")
+///     .expect("failed to connect to the Graylog input");
+/// tracing_subscriber::fmt()
+///     .gelf()
+///     .with_writer(writer)
+///     .init();
+/// ```
+///
+/// [`fmt` subscriber]: crate::fmt::Subscriber
+#[derive(Debug)]
+pub struct GelfUdpWriter {
+    socket: UdpSocket,
+    max_datagram_size: usize,
+}
+
+impl GelfUdpWriter {
+    /// Connects a writer to the Graylog UDP input at `addr`.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(addr)?;
+        Ok(Self {
+            socket,
+            max_datagram_size: 8192,
+        })
+    }
+
+    /// Sets the largest datagram the writer will send, in bytes.
+    ///
+    /// Messages larger than this are chunked. The default of 8192 bytes is
+    /// safe for loopback and most LANs; lower it to fit the path MTU when
+    /// shipping across networks that fragment poorly.
+    pub fn with_max_datagram_size(mut self, max_datagram_size: usize) -> Self {
+        assert!(
+            max_datagram_size > CHUNK_HEADER_LEN,
+            "the maximum datagram size must exceed the {}-byte chunk header",
+            CHUNK_HEADER_LEN
+        );
+        self.max_datagram_size = max_datagram_size;
+        self
+    }
+
+    fn send(&self, message: &[u8]) -> io::Result<()> {
+        // The trailing newline added by the formatter is line framing, not
+        // part of the payload.
+        let message = strip_newline(message);
+        if message.is_empty() {
+            return Ok(());
+        }
+        if message.len() <= self.max_datagram_size {
+            self.socket.send(message)?;
+            return Ok(());
+        }
+
+        let chunk_len = self.max_datagram_size - CHUNK_HEADER_LEN;
+        let chunks = message.chunks(chunk_len);
+        let count = chunks.len();
+        if count > MAX_CHUNKS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the GELF message does not fit in 128 chunks",
+            ));
+        }
+
+        let id = message_id();
+        let mut datagram = Vec::with_capacity(self.max_datagram_size);
+        for (seq, chunk) in chunks.enumerate() {
+            datagram.clear();
+            datagram.extend_from_slice(&[0x1e, 0x0f]);
+            datagram.extend_from_slice(&id);
+            datagram.push(seq as u8);
+            datagram.push(count as u8);
+            datagram.extend_from_slice(chunk);
+            self.socket.send(&datagram)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for GelfUdpWriter {
+    type Writer = GelfUdpIo<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        GelfUdpIo {
+            writer: self,
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// An [`io::Write`] implementation returned by [`GelfUdpWriter`], buffering
+/// one formatted event and sending it as a datagram when flushed or dropped.
+#[derive(Debug)]
+pub struct GelfUdpIo<'a> {
+    writer: &'a GelfUdpWriter,
+    buf: Vec<u8>,
+}
+
+impl io::Write for GelfUdpIo<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.writer.send(&self.buf);
+        self.buf.clear();
+        result
+    }
+}
+
+impl Drop for GelfUdpIo<'_> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Returns a message ID for GELF chunking, unique enough that interleaved
+/// messages from one process are not reassembled into each other.
+fn message_id() -> [u8; 8] {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    (nanos
+        .wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        .wrapping_add(count ^ std::process::id() as u64))
+    .to_be_bytes()
+}
+
+/// A [`MakeWriter`] that ships each formatted event to a Graylog TCP input.
+///
+/// GELF over TCP delimits messages with null bytes rather than newlines, so
+/// this writer replaces the formatter's trailing newline with a `\0` frame
+/// terminator. Network errors are reported to the [`fmt` subscriber], which
+/// drops the event; the connection is not re-established automatically.
+///
+/// This writer is intended for use with the [`Gelf`] formatter:
+///
+/// ```no_run
+/// use tracing_subscriber::fmt::format::GelfTcpWriter;
+///
+/// let writer = GelfTcpWriter::connect("graylog.example.com:12201")
+///     .expect("failed to connect to the Graylog input");
+/// tracing_subscriber::fmt()
+///     .gelf()
+///     .with_writer(writer)
+///     .init();
+/// ```
+///
+/// [`fmt` subscriber]: crate::fmt::Subscriber
+#[derive(Debug)]
+pub struct GelfTcpWriter {
+    stream: Mutex<TcpStream>,
+}
+
+impl GelfTcpWriter {
+    /// Connects a writer to the Graylog TCP input at `addr`.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        Ok(Self {
+            stream: Mutex::new(stream),
+        })
+    }
+
+    fn send(&self, message: &[u8]) -> io::Result<()> {
+        let message = strip_newline(message);
+        if message.is_empty() {
+            return Ok(());
+        }
+        let mut stream = match self.stream.lock() {
+            Ok(stream) => stream,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        stream.write_all(message)?;
+        stream.write_all(&[0])?;
+        stream.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for GelfTcpWriter {
+    type Writer = GelfTcpIo<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        GelfTcpIo {
+            writer: self,
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// An [`io::Write`] implementation returned by [`GelfTcpWriter`], buffering
+/// one formatted event and sending it as a null-delimited frame when flushed
+/// or dropped.
+#[derive(Debug)]
+pub struct GelfTcpIo<'a> {
+    writer: &'a GelfTcpWriter,
+    buf: Vec<u8>,
+}
+
+impl io::Write for GelfTcpIo<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.writer.send(&self.buf);
+        self.buf.clear();
+        result
+    }
+}
+
+impl Drop for GelfTcpIo<'_> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+pub(super) fn strip_newline(message: &[u8]) -> &[u8] {
+    match message {
+        [rest @ .., b'\n'] => rest,
+        message => message,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fmt::{test::MockMakeWriter, CollectorBuilder};
+
+    use tracing::{self, collect::with_default};
+
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    fn collector() -> CollectorBuilder<super::super::JsonFields, Format<Gelf>> {
+        CollectorBuilder::default().gelf().with_host("testhost")
+    }
+
+    fn last_payload(buffer: &MockMakeWriter) -> serde_json::Value {
+        let buf = buffer.get_string();
+        let line = buf
+            .lines()
+            .last()
+            .expect("expected at least one line to be written!");
+        serde_json::from_str(line).expect("the GELF payload must be valid JSON")
+    }
+
+    #[test]
+    fn gelf() {
+        let make_writer = MockMakeWriter::default();
+        let collector = collector().with_writer(make_writer.clone()).finish();
+
+        with_default(collector, || {
+            let span = tracing::info_span!("gelf_span", answer = 42);
+            let _guard = span.enter();
+            tracing::info!(key = "value", "some gelf test");
+        });
+
+        let payload = last_payload(&make_writer);
+        assert_eq!(payload["version"], "1.1");
+        assert_eq!(payload["host"], "testhost");
+        assert_eq!(payload["level"], 6);
+        assert_eq!(payload["short_message"], "some gelf test");
+        assert_eq!(payload["_answer"], 42);
+        assert_eq!(payload["_key"], "value");
+        assert_eq!(
+            payload["_target"],
+            "tracing_subscriber::fmt::format::gelf::test"
+        );
+        assert!(payload["timestamp"].is_f64());
+    }
+
+    #[test]
+    fn level_mapping_and_missing_message() {
+        let make_writer = MockMakeWriter::default();
+        let collector = collector().with_writer(make_writer.clone()).finish();
+
+        with_default(collector, || {
+            tracing::error!(cause = "bad yak");
+        });
+
+        let payload = last_payload(&make_writer);
+        assert_eq!(payload["level"], 3);
+        assert_eq!(payload["_cause"], "bad yak");
+        // Events without a message fall back to the metadata name.
+        assert!(payload["short_message"].is_string());
+    }
+
+    #[test]
+    fn inner_span_fields_win() {
+        let make_writer = MockMakeWriter::default();
+        let collector = collector().with_writer(make_writer.clone()).finish();
+
+        with_default(collector, || {
+            let outer = tracing::info_span!("outer", shared = "outer");
+            let _outer = outer.enter();
+            let inner = tracing::info_span!("inner", shared = "inner");
+            let _inner = inner.enter();
+            tracing::info!("collision");
+        });
+
+        let payload = last_payload(&make_writer);
+        assert_eq!(payload["_shared"], "inner");
+    }
+
+    #[test]
+    fn udp_writer_sends_a_datagram() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let writer = GelfUdpWriter::connect(receiver.local_addr().unwrap()).unwrap();
+        let collector = collector().with_writer(writer).finish();
+
+        with_default(collector, || {
+            tracing::info!("over the wire");
+        });
+
+        let mut buf = [0u8; 9000];
+        let len = receiver.recv(&mut buf).unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&buf[..len]).unwrap();
+        assert_eq!(payload["short_message"], "over the wire");
+    }
+
+    #[test]
+    fn udp_writer_chunks_large_messages() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let writer = GelfUdpWriter::connect(receiver.local_addr().unwrap())
+            .unwrap()
+            .with_max_datagram_size(256);
+        let collector = collector().with_writer(writer).finish();
+
+        let big = "x".repeat(600);
+        with_default(collector, || {
+            tracing::info!(big = big.as_str(), "chunked");
+        });
+
+        let mut chunks = Vec::new();
+        let mut buf = [0u8; 512];
+        loop {
+            let len = receiver.recv(&mut buf).unwrap();
+            let datagram = &buf[..len];
+            assert!(len <= 256, "datagrams must respect the configured size");
+            assert_eq!(
+                &datagram[..2],
+                &[0x1e, 0x0f],
+                "chunks begin with the magic bytes"
+            );
+            let seq = datagram[10] as usize;
+            let count = datagram[11] as usize;
+            chunks.push((seq, datagram[CHUNK_HEADER_LEN..].to_vec()));
+            if chunks.len() == count {
+                break;
+            }
+        }
+
+        chunks.sort_by_key(|(seq, _)| *seq);
+        let message: Vec<u8> = chunks.into_iter().flat_map(|(_, chunk)| chunk).collect();
+        let payload: serde_json::Value = serde_json::from_slice(&message).unwrap();
+        assert_eq!(payload["short_message"], "chunked");
+        assert_eq!(payload["_big"], big.as_str());
+    }
+
+    #[test]
+    fn tcp_writer_null_delimits_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let writer = GelfTcpWriter::connect(listener.local_addr().unwrap()).unwrap();
+        let collector = collector().with_writer(writer).finish();
+
+        with_default(collector, || {
+            tracing::info!("framed");
+        });
+
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut frame = Vec::new();
+        let mut byte = [0u8; 1];
+        loop {
+            stream.read_exact(&mut byte).unwrap();
+            if byte[0] == 0 {
+                break;
+            }
+            frame.push(byte[0]);
+        }
+        let payload: serde_json::Value = serde_json::from_slice(&frame).unwrap();
+        assert_eq!(payload["short_message"], "framed");
+        assert!(
+            !frame.contains(&b'\n'),
+            "the formatter's newline must not be sent over TCP"
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use super::{Format, FormatEvent, FormatFields, FormatTime, Writer};
+use super::{
+    Format, FormatEvent, FormatFields, FormatTime, RedactionRules, TruncationRules, Writer,
+};
 use crate::{
     field::{RecordFields, VisitOutput},
     fmt::{
@@ -12,6 +14,7 @@ use serde_json::Serializer;
 use std::{
     collections::BTreeMap,
     fmt::{self, Write},
+    sync::Arc,
 };
 use tracing_core::{
     field::{self, Field},
@@ -69,11 +72,22 @@ use tracing_log::NormalizeEvent;
 /// By default, event fields are not flattened, and both current span and span
 /// list are logged.
 ///
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Json {
     pub(crate) flatten_event: bool,
     pub(crate) display_current_span: bool,
     pub(crate) display_span_list: bool,
+    pub(crate) nested_spans: bool,
+    pub(crate) unix_timestamp: bool,
+    pub(crate) timestamp_key: &'static str,
+    pub(crate) level_key: &'static str,
+    pub(crate) target_key: &'static str,
+    pub(crate) fields_key: &'static str,
+    pub(crate) current_span_key: &'static str,
+    pub(crate) spans_key: &'static str,
+    pub(crate) constant_fields: Vec<(String, serde_json::Value)>,
+    pub(crate) redaction: Option<Arc<RedactionRules>>,
+    pub(crate) truncation: Option<TruncationRules>,
 }
 
 impl Json {
@@ -92,6 +106,80 @@ impl Json {
     pub fn with_span_list(&mut self, display_span_list: bool) {
         self.display_span_list = display_span_list;
     }
+
+    /// If set to `true`, the span list is emitted as a single object keyed by
+    /// span name, with each span's typed field values nested under its name,
+    /// rather than as an array of objects.
+    ///
+    /// Spans are emitted from root to leaf, so when nested spans share a
+    /// name, the repeated key's last occurrence — which most JSON consumers
+    /// retain — is the innermost span.
+    pub fn with_nested_spans(&mut self, nested_spans: bool) {
+        self.nested_spans = nested_spans;
+    }
+
+    /// If set to `true`, the timestamp is emitted as fractional seconds
+    /// since the Unix epoch, as a JSON number, instead of the string
+    /// rendered by the configured [timer].
+    ///
+    /// [timer]: crate::fmt::time::FormatTime
+    pub fn with_unix_timestamp(&mut self, unix_timestamp: bool) {
+        self.unix_timestamp = unix_timestamp;
+    }
+
+    /// Sets the key under which the timestamp is emitted.
+    pub fn with_timestamp_key(&mut self, timestamp_key: &'static str) {
+        self.timestamp_key = timestamp_key;
+    }
+
+    /// Sets the key under which the event's level is emitted.
+    pub fn with_level_key(&mut self, level_key: &'static str) {
+        self.level_key = level_key;
+    }
+
+    /// Sets the key under which the event's target is emitted.
+    pub fn with_target_key(&mut self, target_key: &'static str) {
+        self.target_key = target_key;
+    }
+
+    /// Adds a constant field emitted in every record, such as a service
+    /// name, version, or host.
+    pub fn with_constant_field(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) {
+        self.constant_fields.push((key.into(), value.into()));
+    }
+
+    /// Sets the key under which the event's fields are emitted.
+    ///
+    /// Has no effect when events are flattened with [`Json::flatten_event`].
+    pub fn with_fields_key(&mut self, fields_key: &'static str) {
+        self.fields_key = fields_key;
+    }
+
+    /// Sets the key under which the current span is emitted.
+    pub fn with_current_span_key(&mut self, current_span_key: &'static str) {
+        self.current_span_key = current_span_key;
+    }
+
+    /// Sets the key under which the span list is emitted.
+    pub fn with_spans_key(&mut self, spans_key: &'static str) {
+        self.spans_key = spans_key;
+    }
+
+    /// Masks event fields matching the provided [`RedactionRules`] before
+    /// they are serialized.
+    pub fn with_field_redaction(&mut self, rules: RedactionRules) {
+        self.redaction = Some(Arc::new(rules));
+    }
+
+    /// Truncates event field values according to the provided
+    /// [`TruncationRules`] before they are serialized.
+    pub fn with_truncation(&mut self, rules: TruncationRules) {
+        self.truncation = Some(rules);
+    }
 }
 
 struct SerializableContext<'a, 'b, Span, N>(
@@ -193,6 +281,234 @@ where
     }
 }
 
+struct SerializableSpanMap<'a, 'b, Span, N>(
+    &'b crate::subscribe::Context<'a, Span>,
+    std::marker::PhantomData<N>,
+)
+where
+    Span: Collect + for<'lookup> crate::registry::LookupSpan<'lookup>,
+    N: for<'writer> FormatFields<'writer> + 'static;
+
+impl<Span, N> serde::ser::Serialize for SerializableSpanMap<'_, '_, Span, N>
+where
+    Span: Collect + for<'lookup> crate::registry::LookupSpan<'lookup>,
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    where
+        Ser: serde::ser::Serializer,
+    {
+        let mut serializer = serializer.serialize_map(None)?;
+
+        if let Some(leaf_span) = self.0.lookup_current() {
+            for span in leaf_span.scope().from_root() {
+                serializer.serialize_entry(
+                    span.metadata().name(),
+                    &SerializableSpanFields(&span, self.1),
+                )?;
+            }
+        }
+
+        serializer.end()
+    }
+}
+
+/// A span's fields as a typed JSON object, without the `name` entry added by
+/// [`SerializableSpan`].
+struct SerializableSpanFields<'a, 'b, Span, N>(
+    &'b crate::registry::SpanRef<'a, Span>,
+    std::marker::PhantomData<N>,
+)
+where
+    Span: for<'lookup> crate::registry::LookupSpan<'lookup>,
+    N: for<'writer> FormatFields<'writer> + 'static;
+
+impl<Span, N> serde::ser::Serialize for SerializableSpanFields<'_, '_, Span, N>
+where
+    Span: for<'lookup> crate::registry::LookupSpan<'lookup>,
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    where
+        Ser: serde::ser::Serializer,
+    {
+        let mut serializer = serializer.serialize_map(None)?;
+
+        let ext = self.0.extensions();
+        let data = ext
+            .get::<FormattedFields<N>>()
+            .expect("Unable to find FormattedFields in extensions; this is a bug");
+
+        match serde_json::from_str::<serde_json::Value>(data) {
+            Ok(serde_json::Value::Object(fields)) => {
+                for field in fields {
+                    serializer.serialize_entry(&field.0, &field.1)?;
+                }
+            }
+            // As in `SerializableSpan`, fields that are not a valid JSON
+            // object are a bug, but should not take the program down outside
+            // of debug builds.
+            Ok(value) if cfg!(debug_assertions) => panic!(
+                "span '{}' had malformed fields! this is a bug.\n  error: invalid JSON object\n  fields: {:?}",
+                self.0.metadata().name(),
+                value
+            ),
+            Ok(value) => {
+                serializer.serialize_entry("field", &value)?;
+                serializer.serialize_entry("field_error", "field was no a valid object")?
+            }
+            Err(e) if cfg!(debug_assertions) => panic!(
+                "span '{}' had malformed fields! this is a bug.\n  error: {}\n  fields: {:?}",
+                self.0.metadata().name(),
+                e,
+                data
+            ),
+            Err(e) => serializer.serialize_entry("field_error", &format!("{}", e))?,
+        };
+        serializer.end()
+    }
+}
+
+struct SerializableEventFields<'a, 'event>(
+    &'a Event<'event>,
+    &'a Option<Arc<RedactionRules>>,
+    &'a Option<TruncationRules>,
+);
+
+impl serde::ser::Serialize for SerializableEventFields<'_, '_> {
+    fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    where
+        Ser: serde::ser::Serializer,
+    {
+        let serializer = serializer.serialize_map(None)?;
+        let mut visitor = RedactingMapVisitor::new(serializer, self.1.clone(), self.2.clone());
+        self.0.record(&mut visitor);
+        visitor.take_serializer()?.end()
+    }
+}
+
+/// A visitor that serializes each field as a map entry, masking fields that
+/// match the configured [`RedactionRules`].
+struct RedactingMapVisitor<S: SerializeMap> {
+    serializer: S,
+    state: Result<(), S::Error>,
+    redaction: Option<Arc<RedactionRules>>,
+    truncation: Option<TruncationRules>,
+}
+
+impl<S: SerializeMap> RedactingMapVisitor<S> {
+    fn new(
+        serializer: S,
+        redaction: Option<Arc<RedactionRules>>,
+        truncation: Option<TruncationRules>,
+    ) -> Self {
+        Self {
+            serializer,
+            state: Ok(()),
+            redaction,
+            truncation,
+        }
+    }
+
+    fn take_serializer(self) -> Result<S, S::Error> {
+        self.state?;
+        Ok(self.serializer)
+    }
+
+    /// Returns `true` if the field was masked, serializing the replacement
+    /// placeholder in its place.
+    fn redact(&mut self, field: &Field, value: Option<&str>) -> bool {
+        let rules = match &self.redaction {
+            Some(rules) => rules,
+            None => return false,
+        };
+        if !rules.field_matches(field.name())
+            && !value.map_or(false, |value| rules.value_matches(value))
+        {
+            return false;
+        }
+        self.state = self
+            .serializer
+            .serialize_entry(field.name(), rules.replacement());
+        true
+    }
+
+    /// Like [`redact`](Self::redact), but formats the value for matching only
+    /// when value predicates are present.
+    fn redact_debug(&mut self, field: &Field, value: &dyn fmt::Debug) -> bool {
+        let has_value_rules = self
+            .redaction
+            .as_ref()
+            .map_or(false, |rules| rules.has_value_rules());
+        if has_value_rules {
+            self.redact(field, Some(&format!("{:?}", value)))
+        } else {
+            self.redact(field, None)
+        }
+    }
+}
+
+impl<S: SerializeMap> field::Visit for RedactingMapVisitor<S> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if self.state.is_ok() && !self.redact(field, None) {
+            self.state = self.serializer.serialize_entry(field.name(), &value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if self.state.is_ok() && !self.redact(field, None) {
+            self.state = self.serializer.serialize_entry(field.name(), &value);
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if self.state.is_ok() && !self.redact(field, None) {
+            self.state = self.serializer.serialize_entry(field.name(), &value);
+        }
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        if self.state.is_ok() && !self.redact(field, None) {
+            self.state = self.serializer.serialize_entry(field.name(), &value);
+        }
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        if self.state.is_ok() && !self.redact(field, None) {
+            self.state = self.serializer.serialize_entry(field.name(), &value);
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if self.state.is_ok() && !self.redact(field, None) {
+            self.state = self.serializer.serialize_entry(field.name(), &value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if self.state.is_ok() && !self.redact(field, Some(value)) {
+            let truncated = self
+                .truncation
+                .as_ref()
+                .and_then(|rules| rules.truncate_value(value));
+            let value = truncated.as_deref().unwrap_or(value);
+            self.state = self.serializer.serialize_entry(field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.state.is_ok() && !self.redact_debug(field, value) {
+            let value = format!("{:?}", value);
+            let value = self
+                .truncation
+                .as_ref()
+                .and_then(|rules| rules.truncate_value(&value))
+                .unwrap_or(value);
+            self.state = self.serializer.serialize_entry(field.name(), &value);
+        }
+    }
+}
+
 impl<C, N, T> FormatEvent<C, N> for Format<Json, T>
 where
     C: Collect + for<'lookup> LookupSpan<'lookup>,
@@ -209,7 +525,9 @@ where
         C: Collect + for<'a> LookupSpan<'a>,
     {
         let mut timestamp = String::new();
-        self.timer.format_time(&mut Writer::new(&mut timestamp))?;
+        if self.display_timestamp && !self.format.unix_timestamp {
+            self.timer.format_time(&mut Writer::new(&mut timestamp))?;
+        }
 
         #[cfg(feature = "tracing-log")]
         let normalized_meta = event.normalized_metadata();
@@ -224,11 +542,19 @@ where
             let mut serializer = serializer.serialize_map(None)?;
 
             if self.display_timestamp {
-                serializer.serialize_entry("timestamp", &timestamp)?;
+                if self.format.unix_timestamp {
+                    let timestamp = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|elapsed| elapsed.as_secs_f64())
+                        .unwrap_or(0.0);
+                    serializer.serialize_entry(self.format.timestamp_key, &timestamp)?;
+                } else {
+                    serializer.serialize_entry(self.format.timestamp_key, &timestamp)?;
+                }
             }
 
             if self.display_level {
-                serializer.serialize_entry("level", &meta.level().as_serde())?;
+                serializer.serialize_entry(self.format.level_key, &meta.level().as_serde())?;
             }
 
             let format_field_marker: std::marker::PhantomData<N> = std::marker::PhantomData;
@@ -244,17 +570,27 @@ where
             };
 
             if self.format.flatten_event {
-                let mut visitor = tracing_serde::SerdeMapVisitor::new(serializer);
+                let mut visitor = RedactingMapVisitor::new(
+                    serializer,
+                    self.format.redaction.clone(),
+                    self.format.truncation.clone(),
+                );
                 event.record(&mut visitor);
 
                 serializer = visitor.take_serializer()?;
             } else {
-                use tracing_serde::fields::AsMap;
-                serializer.serialize_entry("fields", &event.field_map())?;
+                serializer.serialize_entry(
+                    self.format.fields_key,
+                    &SerializableEventFields(
+                        event,
+                        &self.format.redaction,
+                        &self.format.truncation,
+                    ),
+                )?;
             };
 
             if self.display_target {
-                serializer.serialize_entry("target", meta.target())?;
+                serializer.serialize_entry(self.format.target_key, meta.target())?;
             }
 
             if self.display_filename {
@@ -272,16 +608,26 @@ where
             if self.format.display_current_span {
                 if let Some(ref span) = current_span {
                     serializer
-                        .serialize_entry("span", &SerializableSpan(span, format_field_marker))
+                        .serialize_entry(
+                            self.format.current_span_key,
+                            &SerializableSpan(span, format_field_marker),
+                        )
                         .unwrap_or(());
                 }
             }
 
             if self.format.display_span_list && current_span.is_some() {
-                serializer.serialize_entry(
-                    "spans",
-                    &SerializableContext(&ctx.ctx, format_field_marker),
-                )?;
+                if self.format.nested_spans {
+                    serializer.serialize_entry(
+                        self.format.spans_key,
+                        &SerializableSpanMap(&ctx.ctx, format_field_marker),
+                    )?;
+                } else {
+                    serializer.serialize_entry(
+                        self.format.spans_key,
+                        &SerializableContext(&ctx.ctx, format_field_marker),
+                    )?;
+                }
             }
 
             if self.display_thread_name {
@@ -304,6 +650,22 @@ where
                     .serialize_entry("threadId", &format!("{:?}", std::thread::current().id()))?;
             }
 
+            if let Some(hostname) = &self.hostname {
+                serializer.serialize_entry("hostname", hostname)?;
+            }
+
+            if self.display_process_id {
+                serializer.serialize_entry("pid", &std::process::id())?;
+            }
+
+            for (key, value) in &self.resource {
+                serializer.serialize_entry(key, value)?;
+            }
+
+            for (key, value) in &self.format.constant_fields {
+                serializer.serialize_entry(key, value)?;
+            }
+
             serializer.end()
         };
 
@@ -318,6 +680,17 @@ impl Default for Json {
             flatten_event: false,
             display_current_span: true,
             display_span_list: true,
+            nested_spans: false,
+            unix_timestamp: false,
+            timestamp_key: "timestamp",
+            level_key: "level",
+            target_key: "target",
+            fields_key: "fields",
+            current_span_key: "span",
+            spans_key: "spans",
+            constant_fields: Vec::new(),
+            redaction: None,
+            truncation: None,
         }
     }
 }
@@ -326,16 +699,36 @@ impl Default for Json {
 ///
 #[derive(Debug)]
 pub struct JsonFields {
-    // reserve the ability to add fields to this without causing a breaking
-    // change in the future.
-    _private: (),
+    redaction: Option<Arc<RedactionRules>>,
+    truncation: Option<TruncationRules>,
 }
 
 impl JsonFields {
     /// Returns a new JSON [`FormatFields`] implementation.
     ///
     pub fn new() -> Self {
-        Self { _private: () }
+        Self {
+            redaction: None,
+            truncation: None,
+        }
+    }
+
+    /// Masks fields matching the provided [`RedactionRules`] before they are
+    /// serialized.
+    pub fn with_field_redaction(self, rules: RedactionRules) -> Self {
+        Self {
+            redaction: Some(Arc::new(rules)),
+            ..self
+        }
+    }
+
+    /// Truncates field values according to the provided [`TruncationRules`]
+    /// before they are serialized.
+    pub fn with_truncation(self, rules: TruncationRules) -> Self {
+        Self {
+            truncation: Some(rules),
+            ..self
+        }
     }
 }
 
@@ -349,6 +742,8 @@ impl<'a> FormatFields<'a> for JsonFields {
     /// Format the provided `fields` to the provided `writer`, returning a result.
     fn format_fields<R: RecordFields>(&self, mut writer: Writer<'_>, fields: R) -> fmt::Result {
         let mut v = JsonVisitor::new(&mut writer);
+        v.redaction = self.redaction.clone();
+        v.truncation = self.truncation.clone();
         fields.record(&mut v);
         v.finish()
     }
@@ -368,6 +763,8 @@ impl<'a> FormatFields<'a> for JsonFields {
             // existing string.
             let mut writer = current.as_writer();
             let mut v = JsonVisitor::new(&mut writer);
+            v.redaction = self.redaction.clone();
+            v.truncation = self.truncation.clone();
             fields.record(&mut v);
             v.finish()?;
             return Ok(());
@@ -392,6 +789,8 @@ impl<'a> FormatFields<'a> for JsonFields {
             serde_json::from_str(current).map_err(|_| fmt::Error)?;
         let mut v = JsonVisitor::new(&mut new);
         v.values = map;
+        v.redaction = self.redaction.clone();
+        v.truncation = self.truncation.clone();
         fields.record(&mut v);
         v.finish()?;
         current.fields = new;
@@ -407,6 +806,8 @@ impl<'a> FormatFields<'a> for JsonFields {
 pub struct JsonVisitor<'a> {
     values: BTreeMap<&'a str, serde_json::Value>,
     writer: &'a mut dyn Write,
+    redaction: Option<Arc<RedactionRules>>,
+    truncation: Option<TruncationRules>,
 }
 
 impl fmt::Debug for JsonVisitor<'_> {
@@ -426,6 +827,39 @@ impl<'a> JsonVisitor<'a> {
         Self {
             values: BTreeMap::new(),
             writer,
+            redaction: None,
+            truncation: None,
+        }
+    }
+
+    /// Returns `true` if the field was masked by the visitor's redaction
+    /// rules, recording the replacement placeholder in its place.
+    fn redact(&mut self, field: &Field, value: Option<&str>) -> bool {
+        let rules = match &self.redaction {
+            Some(rules) => rules,
+            None => return false,
+        };
+        let name = field.name();
+        if !rules.field_matches(name) && !value.map_or(false, |value| rules.value_matches(value)) {
+            return false;
+        }
+        let name = name.strip_prefix("r#").unwrap_or(name);
+        self.values
+            .insert(name, serde_json::Value::from(rules.replacement()));
+        true
+    }
+
+    /// Like [`redact`](Self::redact), but formats the value for matching only
+    /// when value predicates are present.
+    fn redact_debug(&mut self, field: &Field, value: &dyn fmt::Debug) -> bool {
+        let has_value_rules = self
+            .redaction
+            .as_ref()
+            .map_or(false, |rules| rules.has_value_rules());
+        if has_value_rules {
+            self.redact(field, Some(&format!("{:?}", value)))
+        } else {
+            self.redact(field, None)
         }
     }
 }
@@ -460,53 +894,87 @@ impl crate::field::VisitOutput<fmt::Result> for JsonVisitor<'_> {
 impl field::Visit for JsonVisitor<'_> {
     /// Visit a double precision floating point value.
     fn record_f64(&mut self, field: &Field, value: f64) {
+        if self.redact(field, None) {
+            return;
+        }
         self.values
             .insert(field.name(), serde_json::Value::from(value));
     }
 
     /// Visit a signed 64-bit integer value.
     fn record_i64(&mut self, field: &Field, value: i64) {
+        if self.redact(field, None) {
+            return;
+        }
         self.values
             .insert(field.name(), serde_json::Value::from(value));
     }
 
     /// Visit an unsigned 64-bit integer value.
     fn record_u64(&mut self, field: &Field, value: u64) {
+        if self.redact(field, None) {
+            return;
+        }
         self.values
             .insert(field.name(), serde_json::Value::from(value));
     }
 
     /// Visit a boolean value.
     fn record_bool(&mut self, field: &Field, value: bool) {
+        if self.redact(field, None) {
+            return;
+        }
         self.values
             .insert(field.name(), serde_json::Value::from(value));
     }
 
     /// Visit a string value.
     fn record_str(&mut self, field: &Field, value: &str) {
+        if self.redact(field, Some(value)) {
+            return;
+        }
+        if let Some(truncated) = self
+            .truncation
+            .as_ref()
+            .and_then(|rules| rules.truncate_value(value))
+        {
+            self.values
+                .insert(field.name(), serde_json::Value::from(truncated));
+            return;
+        }
         self.values
             .insert(field.name(), serde_json::Value::from(value));
     }
 
     fn record_bytes(&mut self, field: &Field, value: &[u8]) {
+        if self.redact(field, None) {
+            return;
+        }
         self.values
             .insert(field.name(), serde_json::Value::from(value));
     }
 
     fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
-        match field.name() {
-            // Skip fields that are actually log metadata that have already been handled
-            #[cfg(feature = "tracing-log")]
-            name if name.starts_with("log.") => (),
-            name if name.starts_with("r#") => {
-                self.values
-                    .insert(&name[2..], serde_json::Value::from(format!("{:?}", value)));
-            }
-            name => {
-                self.values
-                    .insert(name, serde_json::Value::from(format!("{:?}", value)));
-            }
-        };
+        let name = field.name();
+
+        // Skip fields that are actually log metadata that have already been handled
+        #[cfg(feature = "tracing-log")]
+        if name.starts_with("log.") {
+            return;
+        }
+
+        if self.redact_debug(field, value) {
+            return;
+        }
+
+        let name = name.strip_prefix("r#").unwrap_or(name);
+        let value = format!("{:?}", value);
+        let value = self
+            .truncation
+            .as_ref()
+            .and_then(|rules| rules.truncate_value(&value))
+            .unwrap_or(value);
+        self.values.insert(name, serde_json::Value::from(value));
     }
 }
 #[cfg(test)]
@@ -551,6 +1019,92 @@ mod test {
         });
     }
 
+    #[test]
+    fn json_nested_spans() {
+        let expected =
+        "{\"timestamp\":\"fake time\",\"level\":\"INFO\",\"fields\":{\"message\":\"some json test\"},\"target\":\"tracing_subscriber::fmt::format::json::test\",\"spans\":{\"outer_span\":{\"answer\":42},\"inner_span\":{\"ratio\":0.5,\"shaved\":true}}}\n";
+        let collector = collector()
+            .flatten_event(false)
+            .with_current_span(false)
+            .with_span_list(true)
+            .with_nested_spans(true);
+        test_json(expected, collector, || {
+            let outer = tracing::span!(tracing::Level::INFO, "outer_span", answer = 42);
+            let _outer = outer.enter();
+            let inner = tracing::span!(
+                tracing::Level::INFO,
+                "inner_span",
+                ratio = 0.5,
+                shaved = true
+            );
+            let _inner = inner.enter();
+            tracing::info!("some json test");
+        });
+    }
+
+    #[test]
+    fn json_renamed_keys() {
+        let expected =
+        "{\"timestamp\":\"fake time\",\"level\":\"INFO\",\"payload\":{\"message\":\"some json test\"},\"target\":\"tracing_subscriber::fmt::format::json::test\",\"context\":{\"answer\":42,\"name\":\"json_span\"},\"resource\":{\"json_span\":{\"answer\":42}}}\n";
+        let collector = collector()
+            .flatten_event(false)
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_nested_spans(true)
+            .with_fields_key("payload")
+            .with_current_span_key("context")
+            .with_spans_key("resource");
+        test_json(expected, collector, || {
+            let span = tracing::span!(tracing::Level::INFO, "json_span", answer = 42);
+            let _guard = span.enter();
+            tracing::info!("some json test");
+        });
+    }
+
+    #[test]
+    fn json_schema_keys_and_constant_fields() {
+        let expected =
+        "{\"@timestamp\":\"fake time\",\"severity\":\"INFO\",\"log.logger\":\"tracing_subscriber::fmt::format::json::test\",\"fields\":{\"message\":\"some json test\"},\"service.name\":\"billing\",\"service.version\":3}\n";
+        let collector = collector()
+            .flatten_event(false)
+            .with_current_span(false)
+            .with_span_list(false)
+            .with_timestamp_key("@timestamp")
+            .with_level_key("severity")
+            .with_target_key("log.logger")
+            .with_constant_field("service.name", "billing")
+            .with_constant_field("service.version", 3);
+        test_json(expected, collector, || {
+            tracing::info!("some json test");
+        });
+    }
+
+    #[test]
+    fn json_unix_timestamp() {
+        let buffer = MockMakeWriter::default();
+        let collector = collector()
+            .flatten_event(false)
+            .with_current_span(false)
+            .with_span_list(false)
+            .with_unix_timestamp(true)
+            .with_writer(buffer.clone())
+            .finish();
+
+        with_default(collector, || {
+            tracing::info!("some json test");
+        });
+
+        let event = parse_as_json(&buffer);
+        let timestamp = event["timestamp"]
+            .as_f64()
+            .expect("the timestamp must be a JSON number");
+        assert!(
+            timestamp > 0.0,
+            "the timestamp must be seconds since the epoch: {}",
+            timestamp,
+        );
+    }
+
     #[test]
     fn json_filename() {
         let current_path = Path::new("tracing-subscriber")
@@ -789,6 +1343,34 @@ mod test {
         });
     }
 
+    #[test]
+    fn json_hostname_pid_and_resource() {
+        let buffer = MockMakeWriter::default();
+        let collector = collector()
+            .flatten_event(false)
+            .with_current_span(false)
+            .with_span_list(false)
+            .with_hostname("web-1")
+            .with_process_id(true)
+            .with_resource([("service.name", "api")])
+            .with_writer(buffer.clone())
+            .finish();
+
+        with_default(collector, || {
+            tracing::info!("some json test");
+        });
+
+        let event = parse_as_json(&buffer);
+        assert_eq!(event["hostname"], "web-1");
+        assert_eq!(
+            event["pid"]
+                .as_u64()
+                .expect("the pid must be a JSON number"),
+            u64::from(std::process::id())
+        );
+        assert_eq!(event["service.name"], "api");
+    }
+
     fn parse_as_json(buffer: &MockMakeWriter) -> serde_json::Value {
         let buf = String::from_utf8(buffer.buf().to_vec()).unwrap();
         let json = buf
@@ -0,0 +1,519 @@
+use super::{Format, FormatEvent, FormatFields, FormatTime, Writer};
+use crate::{
+    field::MakeVisitor,
+    fmt::fmt_subscriber::{FmtContext, FormattedFields},
+    registry::LookupSpan,
+};
+use std::fmt;
+use tracing_core::{
+    field::{self, Field},
+    Collect, Event, Level,
+};
+
+#[cfg(feature = "tracing-log")]
+use tracing_log::NormalizeEvent;
+
+/// Marker for [`Format`] that indicates that the logfmt log format should be
+/// used.
+///
+/// Each event is formatted as a single line of space-separated `key=value`
+/// pairs, as popularized by Heroku and consumed natively by ingestion
+/// pipelines such as Grafana Loki. Values containing spaces, quotes, equals
+/// signs, or control characters are double-quoted and backslash-escaped;
+/// everything else is written bare.
+///
+/// # Example Output
+///
+/// ```text
+/// ts="fake time" level=info target=mycrate shaving.yaks=3 message="some message" key=value
+/// ```
+///
+/// The line begins with the timestamp (`ts`), level, and target, followed by
+/// any of the optional thread and source-location keys enabled on the
+/// [`Format`], then the fields of the event's spans from root to leaf, and
+/// finally the event's own fields. The event's message is emitted under the
+/// `message` key.
+///
+/// # Options
+///
+/// Span fields are flattened onto the line, prefixed by default with the
+/// span's name followed by a `.` (so a `yaks` field on a span named
+/// `shaving` becomes `shaving.yaks`). [`Logfmt::with_span_field_prefix`] can
+/// be used to replace the span name with a fixed prefix, including the empty
+/// string to flatten span fields with their bare names.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Logfmt {
+    pub(crate) span_prefix: Option<String>,
+}
+
+impl Logfmt {
+    /// Sets a fixed prefix for flattened span field keys, replacing the
+    /// default span-name prefix.
+    ///
+    /// The prefix is prepended to span field names as-is; pass `"span."` for
+    /// keys like `span.yaks`, or `""` to flatten span fields under their
+    /// bare names.
+    pub fn with_span_field_prefix(&mut self, prefix: impl Into<String>) {
+        self.span_prefix = Some(prefix.into());
+    }
+}
+
+impl<C, N, T> FormatEvent<C, N> for Format<Logfmt, T>
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    T: FormatTime,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, C, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        #[cfg(feature = "tracing-log")]
+        let normalized_meta = event.normalized_metadata();
+        #[cfg(feature = "tracing-log")]
+        let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
+        #[cfg(not(feature = "tracing-log"))]
+        let meta = event.metadata();
+
+        let mut wrote_any = false;
+
+        if self.display_timestamp {
+            let mut timestamp = String::new();
+            // If getting the timestamp failed, don't bail --- only bail on
+            // formatting errors.
+            if self
+                .timer
+                .format_time(&mut Writer::new(&mut timestamp))
+                .is_err()
+            {
+                timestamp = String::from("<unknown time>");
+            }
+            write_pair(&mut writer, "ts", &timestamp)?;
+            wrote_any = true;
+        }
+
+        if self.display_level {
+            if wrote_any {
+                writer.write_char(' ')?;
+            }
+            write!(writer, "level={}", level_str(meta.level()))?;
+            wrote_any = true;
+        }
+
+        if self.display_target {
+            if wrote_any {
+                writer.write_char(' ')?;
+            }
+            write_pair(&mut writer, "target", meta.target())?;
+            wrote_any = true;
+        }
+
+        if self.display_thread_name {
+            if let Some(name) = std::thread::current().name() {
+                if wrote_any {
+                    writer.write_char(' ')?;
+                }
+                write_pair(&mut writer, "thread_name", name)?;
+                wrote_any = true;
+            }
+        }
+
+        if self.display_thread_id {
+            if wrote_any {
+                writer.write_char(' ')?;
+            }
+            write!(writer, "thread_id={:?}", std::thread::current().id())?;
+            wrote_any = true;
+        }
+
+        if let Some(hostname) = &self.hostname {
+            if wrote_any {
+                writer.write_char(' ')?;
+            }
+            write_pair(&mut writer, "hostname", hostname)?;
+            wrote_any = true;
+        }
+
+        if self.display_process_id {
+            if wrote_any {
+                writer.write_char(' ')?;
+            }
+            write!(writer, "pid={}", std::process::id())?;
+            wrote_any = true;
+        }
+
+        if self.display_filename {
+            if let Some(filename) = meta.file() {
+                if wrote_any {
+                    writer.write_char(' ')?;
+                }
+                write_pair(&mut writer, "file", filename)?;
+                wrote_any = true;
+            }
+        }
+
+        if self.display_line_number {
+            if let Some(line_number) = meta.line() {
+                if wrote_any {
+                    writer.write_char(' ')?;
+                }
+                write!(writer, "line={}", line_number)?;
+                wrote_any = true;
+            }
+        }
+
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let ext = span.extensions();
+                if let Some(fields) = ext.get::<FormattedFields<N>>() {
+                    if fields.is_empty() {
+                        continue;
+                    }
+                    let (prefix, sep) = match &self.format.span_prefix {
+                        Some(prefix) => (&prefix[..], ""),
+                        None => (span.metadata().name(), "."),
+                    };
+                    wrote_any =
+                        write_prefixed(&mut writer, prefix, sep, &fields.fields, wrote_any)?;
+                }
+            }
+        }
+
+        if wrote_any {
+            writer.write_char(' ')?;
+        }
+        ctx.format_fields(writer.by_ref(), event)?;
+
+        for (key, value) in &self.resource {
+            writer.write_char(' ')?;
+            write_pair(&mut writer, key, value)?;
+        }
+
+        writeln!(writer)
+    }
+}
+
+/// Returns the lowercased logfmt rendering of `level`.
+fn level_str(level: &Level) -> &'static str {
+    match *level {
+        Level::TRACE => "trace",
+        Level::DEBUG => "debug",
+        Level::INFO => "info",
+        Level::WARN => "warn",
+        Level::ERROR => "error",
+    }
+}
+
+/// Writes `key=value`, quoting and escaping the value if required.
+fn write_pair(writer: &mut Writer<'_>, key: &str, value: &str) -> fmt::Result {
+    writer.write_str(key)?;
+    writer.write_char('=')?;
+    write_value(writer, value)
+}
+
+/// Writes `value`, double-quoting and backslash-escaping it if it is empty
+/// or contains a character with logfmt syntax meaning.
+fn write_value(writer: &mut Writer<'_>, value: &str) -> fmt::Result {
+    let needs_quoting = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '"' || c == '=' || c == '\\' || c.is_control());
+    if !needs_quoting {
+        return writer.write_str(value);
+    }
+    writer.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => writer.write_str("\\\"")?,
+            '\\' => writer.write_str("\\\\")?,
+            '\n' => writer.write_str("\\n")?,
+            '\r' => writer.write_str("\\r")?,
+            '\t' => writer.write_str("\\t")?,
+            c => writer.write_char(c)?,
+        }
+    }
+    writer.write_char('"')
+}
+
+/// Re-emits logfmt-formatted span `fields`, prepending `prefix` and `sep` to
+/// each key.
+///
+/// `fields` is expected to be the output of [`LogfmtFields`]; pairs are
+/// recovered by splitting on spaces outside of quoted values. If the span's
+/// fields were recorded with a different field formatter, each
+/// space-separated token is prefixed as-is.
+fn write_prefixed(
+    writer: &mut Writer<'_>,
+    prefix: &str,
+    sep: &str,
+    fields: &str,
+    mut wrote_any: bool,
+) -> Result<bool, fmt::Error> {
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in fields.char_indices() {
+        match c {
+            '\\' if in_quotes && !escaped => {
+                escaped = true;
+                continue;
+            }
+            '"' if !escaped => in_quotes = !in_quotes,
+            ' ' if !in_quotes => {
+                if i > start {
+                    if wrote_any {
+                        writer.write_char(' ')?;
+                    }
+                    write!(writer, "{}{}{}", prefix, sep, &fields[start..i])?;
+                    wrote_any = true;
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+        escaped = false;
+    }
+    if fields.len() > start {
+        if wrote_any {
+            writer.write_char(' ')?;
+        }
+        write!(writer, "{}{}{}", prefix, sep, &fields[start..])?;
+        wrote_any = true;
+    }
+    Ok(wrote_any)
+}
+
+/// The logfmt [`FormatFields`] implementation.
+///
+/// Fields are formatted as space-separated `key=value` pairs, with string
+/// values quoted and escaped as described on [`Logfmt`].
+#[derive(Debug, Default)]
+pub struct LogfmtFields {
+    // reserve the ability to add fields to this without causing a breaking
+    // change in the future.
+    _private: (),
+}
+
+impl LogfmtFields {
+    /// Returns a new logfmt [`FormatFields`] implementation.
+    pub fn new() -> Self {
+        Self { _private: () }
+    }
+}
+
+impl<'a> MakeVisitor<Writer<'a>> for LogfmtFields {
+    type Visitor = LogfmtVisitor<'a>;
+
+    #[inline]
+    fn make_visitor(&self, target: Writer<'a>) -> Self::Visitor {
+        LogfmtVisitor::new(target, true)
+    }
+}
+
+/// The [visitor] produced by [`LogfmtFields`].
+///
+/// [visitor]: crate::field::Visit
+pub struct LogfmtVisitor<'a> {
+    writer: Writer<'a>,
+    is_empty: bool,
+    result: fmt::Result,
+}
+
+impl<'a> LogfmtVisitor<'a> {
+    /// Returns a new visitor that writes to the provided `writer`.
+    ///
+    /// If `is_empty` is `true`, the visitor will not write a leading space
+    /// before the first field.
+    pub fn new(writer: Writer<'a>, is_empty: bool) -> Self {
+        Self {
+            writer,
+            is_empty,
+            result: Ok(()),
+        }
+    }
+
+    fn maybe_pad(&mut self) {
+        if self.is_empty {
+            self.is_empty = false;
+        } else {
+            self.result = write!(self.writer, " ");
+        }
+    }
+
+    fn record_key(&mut self, field: &Field) -> bool {
+        if self.result.is_err() {
+            return false;
+        }
+        let name = field.name();
+        // Skip fields that are actually log metadata that have already been handled
+        #[cfg(feature = "tracing-log")]
+        if name.starts_with("log.") {
+            return false;
+        }
+        self.maybe_pad();
+        if self.result.is_err() {
+            return false;
+        }
+        let name = name.strip_prefix("r#").unwrap_or(name);
+        self.result = write!(self.writer, "{}=", name);
+        self.result.is_ok()
+    }
+}
+
+impl field::Visit for LogfmtVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if self.record_key(field) {
+            self.result = write!(self.writer, "{}", value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if self.record_key(field) {
+            self.result = write!(self.writer, "{}", value);
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if self.record_key(field) {
+            self.result = write!(self.writer, "{}", value);
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if self.record_key(field) {
+            self.result = write!(self.writer, "{}", value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if self.record_key(field) {
+            self.result = write_value(&mut self.writer, value);
+        }
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        if self.record_key(field) {
+            self.result = write_value(&mut self.writer, &value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.record_key(field) {
+            self.result = write_value(&mut self.writer, &format!("{:?}", value));
+        }
+    }
+}
+
+impl crate::field::VisitOutput<fmt::Result> for LogfmtVisitor<'_> {
+    fn finish(self) -> fmt::Result {
+        self.result
+    }
+}
+
+impl crate::field::VisitFmt for LogfmtVisitor<'_> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        &mut self.writer
+    }
+}
+
+impl fmt::Debug for LogfmtVisitor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogfmtVisitor")
+            .field("writer", &format_args!("<dyn fmt::Write>"))
+            .field("is_empty", &self.is_empty)
+            .field("result", &self.result)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fmt::{test::MockMakeWriter, time::FormatTime, CollectorBuilder};
+
+    use tracing::{self, collect::with_default};
+
+    use std::fmt;
+
+    struct MockTime;
+    impl FormatTime for MockTime {
+        fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+            write!(w, "fake time")
+        }
+    }
+
+    fn collector() -> CollectorBuilder<LogfmtFields, Format<Logfmt>> {
+        CollectorBuilder::default().logfmt()
+    }
+
+    fn test_logfmt<T>(expected: &str, builder: CollectorBuilder<LogfmtFields, Format<Logfmt>>, f: T)
+    where
+        T: Fn(),
+    {
+        let make_writer = MockMakeWriter::default();
+        let collector = builder
+            .with_writer(make_writer.clone())
+            .with_timer(MockTime)
+            .finish();
+
+        with_default(collector, f);
+
+        let actual = make_writer.get_string();
+        assert_eq!(expected, actual.as_str());
+    }
+
+    #[test]
+    fn logfmt() {
+        let expected = "ts=\"fake time\" level=info \
+                        target=tracing_subscriber::fmt::format::logfmt::test \
+                        logfmt_span.answer=42 logfmt_span.greeting=\"hello logfmt\" \
+                        message=\"some logfmt test\" key=value\n";
+        test_logfmt(expected, collector(), || {
+            let span = tracing::info_span!("logfmt_span", answer = 42, greeting = "hello logfmt");
+            let _guard = span.enter();
+            tracing::info!(key = "value", "some logfmt test");
+        });
+    }
+
+    #[test]
+    fn escaping() {
+        let expected = "ts=\"fake time\" level=info \
+                        target=tracing_subscriber::fmt::format::logfmt::test \
+                        message=\"quotes \\\" and = signs\" empty=\"\" newline=\"a\\nb\"\n";
+        test_logfmt(expected, collector(), || {
+            tracing::info!(empty = "", newline = "a\nb", "quotes \" and = signs");
+        });
+    }
+
+    #[test]
+    fn fixed_span_prefix() {
+        let expected = "ts=\"fake time\" level=info \
+                        target=tracing_subscriber::fmt::format::logfmt::test \
+                        span.answer=42 message=prefixed\n";
+        test_logfmt(
+            expected,
+            collector().with_span_field_prefix("span."),
+            || {
+                let span = tracing::info_span!("logfmt_span", answer = 42);
+                let _guard = span.enter();
+                tracing::info!("prefixed");
+            },
+        );
+    }
+
+    #[test]
+    fn empty_span_prefix_and_nesting() {
+        let expected = "ts=\"fake time\" level=info \
+                        target=tracing_subscriber::fmt::format::logfmt::test \
+                        outer=1 inner=\"two words\" message=nested\n";
+        test_logfmt(expected, collector().with_span_field_prefix(""), || {
+            let outer = tracing::info_span!("outer_span", outer = 1);
+            let _outer = outer.enter();
+            let inner = tracing::info_span!("inner_span", inner = "two words");
+            let _inner = inner.enter();
+            tracing::info!("nested");
+        });
+    }
+}
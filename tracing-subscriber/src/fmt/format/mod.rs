@@ -28,25 +28,47 @@
 //!   for production use with systems where structured logs are consumed as JSON
 //!   by analysis and viewing tools. The JSON output is not optimized for human
 //!   readability. See [here](Json#example-output) for sample output.
+//!
+//! * [`Logfmt`]: Outputs logs as single lines of space-separated `key=value`
+//!   pairs. This is intended for production use with ingestion pipelines
+//!   that prefer logfmt over JSON, such as Grafana Loki or Heroku. See
+//!   [here](Logfmt#example-output) for sample output.
+//!
+//! * [`Gelf`]: Outputs newline-delimited [GELF] payloads for shipping
+//!   directly to a Graylog input, paired with the [`GelfUdpWriter`] or
+//!   [`GelfTcpWriter`]. See [here](Gelf#example-output) for sample output.
+//!
+//! * [`Syslog`]: Outputs [RFC 5424] (or legacy RFC 3164) syslog messages,
+//!   with structured data derived from fields, paired with the
+//!   [`SyslogUnixWriter`], [`SyslogUdpWriter`], or [`SyslogTcpWriter`]. See
+//!   [here](Syslog#example-output) for sample output.
+//!
+//! * [`Csv`]: Outputs one delimiter-separated record per event, with a
+//!   user-declared column set, for quick analysis in spreadsheets or
+//!   `pandas`. See [here](Csv#example-output) for sample output.
+//!
+//! [GELF]: https://go2docs.graylog.org/current/getting_in_log_data/gelf.html
+//! [RFC 5424]: https://www.rfc-editor.org/rfc/rfc5424
 use super::time::{FormatTime, SystemTime};
 use crate::{
     field::{MakeOutput, MakeVisitor, RecordFields, VisitFmt, VisitOutput},
     fmt::fmt_subscriber::{FmtContext, FormattedFields},
+    fmt::writer::MakeWriter,
     registry::LookupSpan,
     registry::Scope,
 };
 
-use std::{fmt, marker::PhantomData};
+use std::{env, fmt, io, marker::PhantomData, sync::Arc};
 use tracing_core::{
     field::{self, Field, Visit},
-    span, Collect, Event, Level,
+    span, Collect, Event, Level, Metadata,
 };
 
 #[cfg(feature = "tracing-log")]
 use tracing_log::NormalizeEvent;
 
 #[cfg(feature = "ansi")]
-use nu_ansi_term::{Color, Style};
+use nu_ansi_term::Style;
 
 #[cfg(feature = "json")]
 mod json;
@@ -54,12 +76,45 @@ mod json;
 #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 pub use json::*;
 
+#[cfg(feature = "json")]
+mod gelf;
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use gelf::*;
+
+#[cfg(feature = "json")]
+mod syslog;
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use syslog::*;
+
+mod logfmt;
+pub use logfmt::*;
+
+mod columns;
+pub use columns::*;
+
+mod csv;
+pub use csv::*;
+
+mod redact;
+pub use redact::RedactionRules;
+
+mod truncate;
+pub use truncate::TruncationRules;
+
 #[cfg(feature = "ansi")]
 mod pretty;
 #[cfg(feature = "ansi")]
 #[cfg_attr(docsrs, doc(cfg(feature = "ansi")))]
 pub use pretty::*;
 
+#[cfg(feature = "ansi")]
+mod theme;
+#[cfg(feature = "ansi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ansi")))]
+pub use theme::Theme;
+
 use fmt::{Debug, Display};
 
 /// A type that can format a tracing [`Event`] to a [`Writer`].
@@ -223,6 +278,144 @@ where
         (*self)(ctx, writer, event)
     }
 }
+
+/// A [`FormatEvent`] combinator that formats events from a particular target
+/// with a different event formatter.
+///
+/// Events whose target is `prefix`, or begins with `prefix` followed by
+/// `::`, are formatted with the override formatter; all other events are
+/// formatted with the wrapped formatter. This allows, for example, rendering
+/// a noisy dependency's events on a single compact line while the
+/// application's own events use the [pretty](Pretty) format, without
+/// composing multiple subscribers and filters.
+///
+/// This is returned by [`Subscriber::with_format_for_target`] and
+/// [`CollectorBuilder::with_format_for_target`], and calls may be chained to
+/// override several targets. Note that the field formatter is shared by all
+/// event formatters; overrides only affect how the event itself is rendered.
+///
+/// [`Subscriber::with_format_for_target`]: crate::fmt::Subscriber::with_format_for_target
+/// [`CollectorBuilder::with_format_for_target`]: crate::fmt::CollectorBuilder::with_format_for_target
+#[derive(Debug, Clone)]
+pub struct TargetFormat<A, B> {
+    prefix: String,
+    format: A,
+    inner: B,
+}
+
+impl<A, B> TargetFormat<A, B> {
+    pub(crate) fn new(prefix: impl Into<String>, format: A, inner: B) -> Self {
+        Self {
+            prefix: prefix.into(),
+            format,
+            inner,
+        }
+    }
+
+    /// Returns whether `target` is the configured prefix, or a module path
+    /// beneath it.
+    fn matches(&self, target: &str) -> bool {
+        target
+            .strip_prefix(&self.prefix)
+            .map_or(false, |rest| rest.is_empty() || rest.starts_with("::"))
+    }
+}
+
+impl<C, N, A, B> FormatEvent<C, N> for TargetFormat<A, B>
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    A: FormatEvent<C, N>,
+    B: FormatEvent<C, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, C, N>,
+        writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        #[cfg(feature = "tracing-log")]
+        let normalized_meta = event.normalized_metadata();
+        #[cfg(feature = "tracing-log")]
+        let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
+        #[cfg(not(feature = "tracing-log"))]
+        let meta = event.metadata();
+
+        if self.matches(meta.target()) {
+            self.format.format_event(ctx, writer, event)
+        } else {
+            self.inner.format_event(ctx, writer, event)
+        }
+    }
+}
+
+/// A [`FormatEvent`] combinator that formats each event a second time with
+/// its own event formatter, writing the result to its own [`MakeWriter`].
+///
+/// This allows a single subscriber to emit differently-formatted copies of
+/// the same events to multiple destinations — for example, human-readable
+/// output on stderr and newline-delimited JSON in a file — while sharing one
+/// set of span and filter configuration, rather than composing several fully
+/// separate `fmt` subscribers. Unlike [`MakeWriterExt::and`], which duplicates
+/// identical bytes to both writers, each configured (format, writer) pair
+/// formats the event independently.
+///
+/// This is returned by [`Subscriber::with_tee`] and
+/// [`CollectorBuilder::with_tee`], and calls may be chained to configure
+/// several additional outputs. Note that the field formatter is shared by all
+/// event formatters, and the secondary output is always written without ANSI
+/// escape codes.
+///
+/// [`MakeWriter`]: crate::fmt::writer::MakeWriter
+/// [`MakeWriterExt::and`]: crate::fmt::writer::MakeWriterExt::and
+/// [`Subscriber::with_tee`]: crate::fmt::Subscriber::with_tee
+/// [`CollectorBuilder::with_tee`]: crate::fmt::CollectorBuilder::with_tee
+#[derive(Debug, Clone)]
+pub struct TeeFormat<A, W, B> {
+    format: A,
+    make_writer: W,
+    inner: B,
+}
+
+impl<A, W, B> TeeFormat<A, W, B> {
+    pub(crate) fn new(format: A, make_writer: W, inner: B) -> Self {
+        Self {
+            format,
+            make_writer,
+            inner,
+        }
+    }
+}
+
+impl<C, N, A, W, B> FormatEvent<C, N> for TeeFormat<A, W, B>
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    A: FormatEvent<C, N>,
+    W: for<'a> MakeWriter<'a>,
+    B: FormatEvent<C, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, C, N>,
+        writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut buf = String::new();
+        if self
+            .format
+            .format_event(ctx, Writer::new(&mut buf), event)
+            .is_ok()
+        {
+            let mut writer = self.make_writer.make_writer_for_event(event);
+            // If the secondary output cannot be written, the primary output
+            // should still be produced, so the error is discarded here.
+            let _ = io::Write::write_all(&mut writer, buf.as_bytes());
+        }
+        self.inner.format_event(ctx, writer, event)
+    }
+}
+
 /// A type that can format a [set of fields] to a [`Writer`].
 ///
 /// `FormatFields` is primarily used in the context of [`fmt::Subscriber`]. Each
@@ -283,6 +476,45 @@ pub fn json() -> Format<Json> {
     format().json()
 }
 
+/// Returns the default configuration for a logfmt [event formatter].
+///
+/// [event formatter]: FormatEvent
+pub fn logfmt() -> Format<Logfmt> {
+    format().logfmt()
+}
+
+/// Returns the default configuration for a column-aligned [event formatter].
+///
+/// [event formatter]: FormatEvent
+pub fn columns() -> Format<Columns> {
+    format().columns()
+}
+
+/// Returns the default configuration for a CSV [event formatter].
+///
+/// [event formatter]: FormatEvent
+pub fn csv() -> Format<Csv> {
+    format().csv()
+}
+
+/// Returns the default configuration for a GELF [event formatter].
+///
+/// [event formatter]: FormatEvent
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub fn gelf() -> Format<Gelf> {
+    format().gelf()
+}
+
+/// Returns the default configuration for a syslog [event formatter].
+///
+/// [event formatter]: FormatEvent
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub fn syslog() -> Format<Syslog> {
+    format().syslog()
+}
+
 /// Returns a [`FormatFields`] implementation that formats fields using the
 /// provided function or closure.
 ///
@@ -312,6 +544,8 @@ pub struct Writer<'writer> {
     writer: &'writer mut dyn fmt::Write,
     // TODO(eliza): add ANSI support
     is_ansi: bool,
+    #[cfg(feature = "ansi")]
+    theme: Theme,
 }
 
 /// A [`FormatFields`] implementation that formats fields by calling a function
@@ -413,6 +647,10 @@ pub struct Format<F = Full, T = SystemTime> {
     pub(crate) display_thread_name: bool,
     pub(crate) display_filename: bool,
     pub(crate) display_line_number: bool,
+    pub(crate) display_process_id: bool,
+    pub(crate) hostname: Option<String>,
+    pub(crate) resource: Vec<(String, String)>,
+    pub(crate) hyperlinks: Option<String>,
 }
 
 // === impl Writer ===
@@ -438,6 +676,8 @@ impl<'writer> Writer<'writer> {
         Self {
             writer: writer as &mut dyn fmt::Write,
             is_ansi: false,
+            #[cfg(feature = "ansi")]
+            theme: Theme::default(),
         }
     }
 
@@ -446,6 +686,11 @@ impl<'writer> Writer<'writer> {
         Self { is_ansi, ..self }
     }
 
+    #[cfg(feature = "ansi")]
+    pub(crate) fn with_theme(self, theme: Theme) -> Self {
+        Self { theme, ..self }
+    }
+
     /// Return a new `Writer` that mutably borrows `self`.
     ///
     /// This can be used to temporarily borrow a `Writer` to pass a new `Writer`
@@ -453,9 +698,13 @@ impl<'writer> Writer<'writer> {
     /// to still be used once that function returns.
     pub fn by_ref(&mut self) -> Writer<'_> {
         let is_ansi = self.is_ansi;
+        #[cfg(feature = "ansi")]
+        let theme = self.theme;
         Writer {
             writer: self as &mut dyn fmt::Write,
             is_ansi,
+            #[cfg(feature = "ansi")]
+            theme,
         }
     }
 
@@ -528,33 +777,64 @@ impl<'writer> Writer<'writer> {
         self.is_ansi
     }
 
-    pub(in crate::fmt::format) fn bold(&self) -> Style {
+    pub(in crate::fmt::format) fn dimmed(&self) -> Style {
         #[cfg(feature = "ansi")]
         {
             if self.is_ansi {
-                return Style::new().bold();
+                return Style::new().dimmed();
             }
         }
 
         Style::new()
     }
 
-    pub(in crate::fmt::format) fn dimmed(&self) -> Style {
+    #[cfg(feature = "ansi")]
+    pub(in crate::fmt::format) fn level_style(&self, level: Level) -> Style {
+        if self.is_ansi {
+            return self.theme.level(level);
+        }
+
+        Style::new()
+    }
+
+    pub(in crate::fmt::format) fn target_style(&self) -> Style {
         #[cfg(feature = "ansi")]
         {
             if self.is_ansi {
-                return Style::new().dimmed();
+                return self.theme.target();
             }
         }
 
         Style::new()
     }
 
-    pub(in crate::fmt::format) fn italic(&self) -> Style {
+    pub(in crate::fmt::format) fn field_name_style(&self) -> Style {
         #[cfg(feature = "ansi")]
         {
             if self.is_ansi {
-                return Style::new().italic();
+                return self.theme.field_name();
+            }
+        }
+
+        Style::new()
+    }
+
+    pub(in crate::fmt::format) fn field_value_style(&self) -> Style {
+        #[cfg(feature = "ansi")]
+        {
+            if self.is_ansi {
+                return self.theme.field_value();
+            }
+        }
+
+        Style::new()
+    }
+
+    pub(in crate::fmt::format) fn span_name_style(&self) -> Style {
+        #[cfg(feature = "ansi")]
+        {
+            if self.is_ansi {
+                return self.theme.span_name();
             }
         }
 
@@ -603,6 +883,10 @@ impl Default for Format<Full, SystemTime> {
             display_thread_name: false,
             display_filename: false,
             display_line_number: false,
+            hyperlinks: None,
+            display_process_id: false,
+            hostname: None,
+            resource: Vec::new(),
         }
     }
 }
@@ -623,6 +907,39 @@ impl<F, T> Format<F, T> {
             display_thread_name: self.display_thread_name,
             display_filename: self.display_filename,
             display_line_number: self.display_line_number,
+            hyperlinks: self.hyperlinks,
+            display_process_id: self.display_process_id,
+            hostname: self.hostname,
+            resource: self.resource,
+        }
+    }
+
+    /// Use a column-aligned, scannable output format.
+    ///
+    /// # Options
+    ///
+    /// - [`Format::with_target_width`] and [`Format::with_message_width`]
+    ///   set the column widths.
+    /// - [`Format::with_truncate_message`] truncates messages that overflow
+    ///   their column.
+    ///
+    /// See [`Columns`].
+    pub fn columns(self) -> Format<Columns, T> {
+        Format {
+            format: Columns::default(),
+            timer: self.timer,
+            ansi: self.ansi,
+            display_target: self.display_target,
+            display_timestamp: self.display_timestamp,
+            display_level: self.display_level,
+            display_thread_id: self.display_thread_id,
+            display_thread_name: self.display_thread_name,
+            display_filename: self.display_filename,
+            display_line_number: self.display_line_number,
+            hyperlinks: self.hyperlinks,
+            display_process_id: self.display_process_id,
+            hostname: self.hostname,
+            resource: self.resource,
         }
     }
 
@@ -662,6 +979,10 @@ impl<F, T> Format<F, T> {
             display_thread_name: self.display_thread_name,
             display_filename: true,
             display_line_number: true,
+            hyperlinks: self.hyperlinks,
+            display_process_id: self.display_process_id,
+            hostname: self.hostname,
+            resource: self.resource,
         }
     }
 
@@ -693,6 +1014,149 @@ impl<F, T> Format<F, T> {
             display_thread_name: self.display_thread_name,
             display_filename: self.display_filename,
             display_line_number: self.display_line_number,
+            hyperlinks: self.hyperlinks,
+            display_process_id: self.display_process_id,
+            hostname: self.hostname,
+            resource: self.resource,
+        }
+    }
+
+    /// Use the GELF format, for shipping logs to a Graylog input.
+    ///
+    /// # Example Output
+    ///
+    /// ```ignore,json
+    /// {"_key":"value","_target":"mycrate","host":"myhost","level":6,"short_message":"some message","timestamp":1692700000.123,"version":"1.1"}
+    /// ```
+    ///
+    /// # Options
+    ///
+    /// - [`Format::with_host`] can be used to set the payload's `host` key.
+    ///
+    /// See [`Gelf`].
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn gelf(self) -> Format<Gelf, T> {
+        Format {
+            format: Gelf::default(),
+            timer: self.timer,
+            ansi: self.ansi,
+            display_target: self.display_target,
+            display_timestamp: self.display_timestamp,
+            display_level: self.display_level,
+            display_thread_id: self.display_thread_id,
+            display_thread_name: self.display_thread_name,
+            display_filename: self.display_filename,
+            display_line_number: self.display_line_number,
+            hyperlinks: self.hyperlinks,
+            display_process_id: self.display_process_id,
+            hostname: self.hostname,
+            resource: self.resource,
+        }
+    }
+
+    /// Use the syslog message format, for shipping logs to a syslog
+    /// collector.
+    ///
+    /// # Example Output
+    ///
+    /// ```ignore
+    /// <14>1 2023-08-22T14:00:00.123456Z myhost myapp 1234 - [tracing@0 target="mycrate" answer="42"] some message
+    /// ```
+    ///
+    /// # Options
+    ///
+    /// - [`Format::with_facility`] sets the facility used to compute each
+    ///   message's `PRI`.
+    /// - [`Format::with_app_name`] sets the `APP-NAME` part.
+    /// - [`Format::with_sd_id`] sets the `SD-ID` of the structured-data
+    ///   element carrying the event's fields.
+    /// - [`Format::rfc_3164`] switches to the legacy RFC 3164 format.
+    /// - [`Format::with_hostname`] sets the `HOSTNAME` part.
+    ///
+    /// See [`Syslog`].
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn syslog(self) -> Format<Syslog, T> {
+        Format {
+            format: Syslog::default(),
+            timer: self.timer,
+            ansi: self.ansi,
+            display_target: self.display_target,
+            display_timestamp: self.display_timestamp,
+            display_level: self.display_level,
+            display_thread_id: self.display_thread_id,
+            display_thread_name: self.display_thread_name,
+            display_filename: self.display_filename,
+            display_line_number: self.display_line_number,
+            hyperlinks: self.hyperlinks,
+            display_process_id: self.display_process_id,
+            hostname: self.hostname,
+            resource: self.resource,
+        }
+    }
+
+    /// Use the logfmt format.
+    ///
+    /// Each event is formatted as a single line of space-separated
+    /// `key=value` pairs, with fields from the event's spans flattened onto
+    /// the line.
+    ///
+    /// # Example Output
+    ///
+    /// ```text
+    /// ts="fake time" level=info target=mycrate message="some message" key=value
+    /// ```
+    ///
+    /// # Options
+    ///
+    /// - [`Format::with_span_field_prefix`] can be used to override how
+    ///   flattened span field keys are prefixed.
+    ///
+    /// Use the CSV format.
+    ///
+    /// The column set, delimiter, header row, and extra-fields column can be
+    /// configured on the returned `Format` with [`Format::with_columns`],
+    /// [`Format::with_delimiter`], [`Format::with_header`], and
+    /// [`Format::with_extra_fields_column`].
+    ///
+    /// See [`Csv`].
+    pub fn csv(self) -> Format<Csv, T> {
+        Format {
+            format: Csv::default(),
+            timer: self.timer,
+            ansi: self.ansi,
+            display_target: self.display_target,
+            display_timestamp: self.display_timestamp,
+            display_level: self.display_level,
+            display_thread_id: self.display_thread_id,
+            display_thread_name: self.display_thread_name,
+            display_filename: self.display_filename,
+            display_line_number: self.display_line_number,
+            hyperlinks: self.hyperlinks,
+            display_process_id: self.display_process_id,
+            hostname: self.hostname,
+            resource: self.resource,
+        }
+    }
+
+    /// See [`Logfmt`].
+    pub fn logfmt(self) -> Format<Logfmt, T> {
+        Format {
+            format: Logfmt::default(),
+            timer: self.timer,
+            ansi: self.ansi,
+            display_target: self.display_target,
+            display_timestamp: self.display_timestamp,
+            display_level: self.display_level,
+            display_thread_id: self.display_thread_id,
+            display_thread_name: self.display_thread_name,
+            display_filename: self.display_filename,
+            display_line_number: self.display_line_number,
+            hyperlinks: self.hyperlinks,
+            display_process_id: self.display_process_id,
+            hostname: self.hostname,
+            resource: self.resource,
         }
     }
 
@@ -722,6 +1186,10 @@ impl<F, T> Format<F, T> {
             display_thread_name: self.display_thread_name,
             display_filename: self.display_filename,
             display_line_number: self.display_line_number,
+            hyperlinks: self.hyperlinks,
+            display_process_id: self.display_process_id,
+            hostname: self.hostname,
+            resource: self.resource,
         }
     }
 
@@ -738,6 +1206,10 @@ impl<F, T> Format<F, T> {
             display_thread_name: self.display_thread_name,
             display_filename: self.display_filename,
             display_line_number: self.display_line_number,
+            hyperlinks: self.hyperlinks,
+            display_process_id: self.display_process_id,
+            hostname: self.hostname,
+            resource: self.resource,
         }
     }
 
@@ -787,6 +1259,43 @@ impl<F, T> Format<F, T> {
         }
     }
 
+    /// Sets whether or not the current [process ID] is displayed when
+    /// formatting events.
+    ///
+    /// [process ID]: std::process::id
+    pub fn with_process_id(self, display_process_id: bool) -> Format<F, T> {
+        Format {
+            display_process_id,
+            ..self
+        }
+    }
+
+    /// Sets a hostname that is included in every formatted event.
+    pub fn with_hostname(self, hostname: impl Into<String>) -> Format<F, T> {
+        Format {
+            hostname: Some(hostname.into()),
+            ..self
+        }
+    }
+
+    /// Sets static resource attributes, such as a `service.name` or
+    /// `service.version`, that are included in every formatted event.
+    ///
+    /// This replaces any previously configured resource attributes.
+    pub fn with_resource<K, V>(self, resource: impl IntoIterator<Item = (K, V)>) -> Format<F, T>
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Format {
+            resource: resource
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+            ..self
+        }
+    }
+
     /// Sets whether or not an event's [source code file path][file] is
     /// displayed.
     ///
@@ -809,6 +1318,57 @@ impl<F, T> Format<F, T> {
         }
     }
 
+    /// Sets whether or not displayed source code locations are wrapped in
+    /// [OSC 8] terminal hyperlink escape sequences, making them clickable in
+    /// supporting terminal emulators.
+    ///
+    /// Hyperlinks are only emitted when the terminal is detected to support
+    /// them (via the `TERM_PROGRAM`, `VTE_VERSION`, and similar environment
+    /// variables) and when ANSI escape sequences are enabled; in other
+    /// environments this setting has no effect. Use
+    /// [`Format::with_hyperlink_template`] to emit hyperlinks unconditionally
+    /// or to customize the link target.
+    ///
+    /// Hyperlinks point at `file://{path}` URLs by default, which most
+    /// terminals open in an editor. Note that source file paths recorded in
+    /// event metadata are relative to the workspace root, so the links are
+    /// most useful when the process runs from the directory it was compiled
+    /// in.
+    ///
+    /// [OSC 8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+    pub fn with_hyperlinks(self, display_hyperlinks: bool) -> Format<F, T> {
+        Format {
+            hyperlinks: if display_hyperlinks && supports_hyperlinks() {
+                Some(DEFAULT_HYPERLINK_TEMPLATE.to_string())
+            } else {
+                None
+            },
+            ..self
+        }
+    }
+
+    /// Wraps displayed source code locations in [OSC 8] terminal hyperlinks
+    /// pointing at the provided URL template.
+    ///
+    /// The template may contain `{path}` and `{line}` placeholders, which are
+    /// replaced with the event's source file path and line number. For
+    /// example, `"vscode://file/{path}:{line}"` opens the location in VS
+    /// Code, and a GitHub blob URL template such as
+    /// `"https://github.com/me/repo/blob/main/{path}#L{line}"` links to the
+    /// source on GitHub.
+    ///
+    /// Unlike [`Format::with_hyperlinks`], this does not attempt to detect
+    /// whether the terminal supports hyperlinks; links are emitted whenever
+    /// ANSI escape sequences are enabled.
+    ///
+    /// [OSC 8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+    pub fn with_hyperlink_template(self, template: impl Into<String>) -> Format<F, T> {
+        Format {
+            hyperlinks: Some(template.into()),
+            ..self
+        }
+    }
+
     /// Sets whether or not the source code location from which an event
     /// originated is displayed.
     ///
@@ -827,7 +1387,7 @@ impl<F, T> Format<F, T> {
             let fmt_level = {
                 #[cfg(feature = "ansi")]
                 {
-                    F::format_level(level, writer.has_ansi_escapes())
+                    F::format_level(level, writer.has_ansi_escapes(), writer.level_style(level))
                 }
                 #[cfg(not(feature = "ansi"))]
                 {
@@ -869,53 +1429,409 @@ impl<F, T> Format<F, T> {
             }
         }
 
-        // Otherwise, just format the timestamp without ANSI formatting.
-        // If getting the timestamp failed, don't bail --- only bail on
-        // formatting errors.
-        if self.timer.format_time(writer).is_err() {
-            writer.write_str("<unknown time>")?;
-        }
-        writer.write_char(' ')
+        // Otherwise, just format the timestamp without ANSI formatting.
+        // If getting the timestamp failed, don't bail --- only bail on
+        // formatting errors.
+        if self.timer.format_time(writer).is_err() {
+            writer.write_str("<unknown time>")?;
+        }
+        writer.write_char(' ')
+    }
+
+    /// Writes the configured hostname and the current process ID, if enabled.
+    fn format_process_info(&self, writer: &mut Writer<'_>) -> fmt::Result {
+        if let Some(hostname) = &self.hostname {
+            write!(writer, "{} ", hostname)?;
+        }
+
+        if self.display_process_id {
+            write!(writer, "{} ", std::process::id())?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the hyperlink URL for the event's source location, if
+    /// hyperlinks are enabled, ANSI escapes are supported, and the event has
+    /// a source file.
+    fn source_link(&self, writer: &Writer<'_>, meta: &Metadata<'_>) -> Option<String> {
+        if !writer.has_ansi_escapes() {
+            return None;
+        }
+        let template = self.hyperlinks.as_deref()?;
+        let path = meta.file()?;
+        let line = meta.line().map(|line| line.to_string()).unwrap_or_default();
+        Some(template.replace("{path}", path).replace("{line}", &line))
+    }
+
+    /// Appends the configured resource attributes as trailing `key=value`
+    /// pairs.
+    fn format_resource(&self, writer: &mut Writer<'_>) -> fmt::Result {
+        for (key, value) in &self.resource {
+            write!(
+                writer,
+                " {}{}{}",
+                writer.field_name_style().paint(key),
+                writer.dimmed().paint("="),
+                value
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The default [OSC 8] hyperlink target for source locations.
+///
+/// [OSC 8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+const DEFAULT_HYPERLINK_TEMPLATE: &str = "file://{path}";
+
+/// Writes the opening [OSC 8] escape sequence for a hyperlink to `url`.
+///
+/// [OSC 8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+pub(in crate::fmt::format) fn write_hyperlink_start(
+    writer: &mut Writer<'_>,
+    url: &str,
+) -> fmt::Result {
+    write!(writer, "\x1b]8;;{}\x1b\\", url)
+}
+
+/// Writes the closing [OSC 8] escape sequence, ending a hyperlink.
+///
+/// [OSC 8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+pub(in crate::fmt::format) fn write_hyperlink_end(writer: &mut Writer<'_>) -> fmt::Result {
+    writer.write_str("\x1b]8;;\x1b\\")
+}
+
+/// Returns whether the current terminal emulator advertises support for
+/// OSC 8 hyperlinks.
+///
+/// There is no terminfo capability for hyperlinks, so this checks the
+/// environment variables set by the major terminal emulators known to
+/// support them.
+fn supports_hyperlinks() -> bool {
+    if let Ok(program) = env::var("TERM_PROGRAM") {
+        if matches!(
+            program.as_str(),
+            "iTerm.app" | "WezTerm" | "Hyper" | "vscode" | "ghostty"
+        ) {
+            return true;
+        }
+    }
+
+    // VTE-based terminals (GNOME Terminal, Tilix, ...) support hyperlinks
+    // since 0.50.
+    if let Ok(version) = env::var("VTE_VERSION") {
+        if version
+            .parse::<u32>()
+            .map_or(false, |version| version >= 5000)
+        {
+            return true;
+        }
+    }
+
+    // Windows Terminal, Konsole, kitty, and WezTerm each set their own
+    // variable.
+    if env::var_os("WT_SESSION").is_some()
+        || env::var_os("KONSOLE_VERSION").is_some()
+        || env::var_os("KITTY_WINDOW_ID").is_some()
+        || env::var_os("WEZTERM_PANE").is_some()
+    {
+        return true;
+    }
+
+    matches!(
+        env::var("TERM").as_deref(),
+        Ok("xterm-kitty") | Ok("foot") | Ok("foot-extra")
+    )
+}
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+impl<T> Format<Json, T> {
+    /// Use the full JSON format with the event's event fields flattened.
+    ///
+    /// # Example Output
+    ///
+    /// ```ignore,json
+    /// {"timestamp":"Feb 20 11:28:15.096","level":"INFO","target":"mycrate", "message":"some message", "key": "value"}
+    /// ```
+    /// See [`Json`]
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn flatten_event(mut self, flatten_event: bool) -> Format<Json, T> {
+        self.format.flatten_event(flatten_event);
+        self
+    }
+
+    /// Sets whether or not the formatter will include the current span in
+    /// formatted events.
+    ///
+    /// See [`Json`]
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn with_current_span(mut self, display_current_span: bool) -> Format<Json, T> {
+        self.format.with_current_span(display_current_span);
+        self
+    }
+
+    /// Sets whether or not the formatter will include a list (from root to
+    /// leaf) of all currently entered spans in formatted events.
+    ///
+    /// See [`Json`]
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn with_span_list(mut self, display_span_list: bool) -> Format<Json, T> {
+        self.format.with_span_list(display_span_list);
+        self
+    }
+
+    /// If set to `true`, the timestamp is emitted as fractional seconds
+    /// since the Unix epoch, as a JSON number, instead of the string
+    /// rendered by the configured [timer].
+    ///
+    /// See [`Json`]
+    ///
+    /// [timer]: crate::fmt::time::FormatTime
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn with_unix_timestamp(mut self, unix_timestamp: bool) -> Format<Json, T> {
+        self.format.with_unix_timestamp(unix_timestamp);
+        self
+    }
+
+    /// Sets the key under which the timestamp is emitted.
+    ///
+    /// See [`Json`]
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn with_timestamp_key(mut self, timestamp_key: &'static str) -> Format<Json, T> {
+        self.format.with_timestamp_key(timestamp_key);
+        self
+    }
+
+    /// Sets the key under which the event's level is emitted.
+    ///
+    /// See [`Json`]
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn with_level_key(mut self, level_key: &'static str) -> Format<Json, T> {
+        self.format.with_level_key(level_key);
+        self
+    }
+
+    /// Sets the key under which the event's target is emitted.
+    ///
+    /// See [`Json`]
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn with_target_key(mut self, target_key: &'static str) -> Format<Json, T> {
+        self.format.with_target_key(target_key);
+        self
+    }
+
+    /// Adds a constant field emitted in every record, such as a service
+    /// name, version, or host.
+    ///
+    /// See [`Json`]
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn with_constant_field(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Format<Json, T> {
+        self.format.with_constant_field(key, value);
+        self
+    }
+
+    /// Sets whether the span list is emitted as a single object keyed by
+    /// span name, with typed field values nested under each name, rather
+    /// than as an array of objects.
+    ///
+    /// See [`Json`]
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn with_nested_spans(mut self, nested_spans: bool) -> Format<Json, T> {
+        self.format.with_nested_spans(nested_spans);
+        self
+    }
+
+    /// Sets the key under which the event's fields are emitted.
+    ///
+    /// See [`Json`]
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn with_fields_key(mut self, fields_key: &'static str) -> Format<Json, T> {
+        self.format.with_fields_key(fields_key);
+        self
     }
-}
 
-#[cfg(feature = "json")]
-#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
-impl<T> Format<Json, T> {
-    /// Use the full JSON format with the event's event fields flattened.
+    /// Sets the key under which the current span is emitted.
     ///
-    /// # Example Output
+    /// See [`Json`]
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn with_current_span_key(mut self, current_span_key: &'static str) -> Format<Json, T> {
+        self.format.with_current_span_key(current_span_key);
+        self
+    }
+
+    /// Sets the key under which the span list is emitted.
     ///
-    /// ```ignore,json
-    /// {"timestamp":"Feb 20 11:28:15.096","level":"INFO","target":"mycrate", "message":"some message", "key": "value"}
-    /// ```
     /// See [`Json`]
     #[cfg(feature = "json")]
     #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
-    pub fn flatten_event(mut self, flatten_event: bool) -> Format<Json, T> {
-        self.format.flatten_event(flatten_event);
+    pub fn with_spans_key(mut self, spans_key: &'static str) -> Format<Json, T> {
+        self.format.with_spans_key(spans_key);
         self
     }
 
-    /// Sets whether or not the formatter will include the current span in
-    /// formatted events.
+    /// Masks event fields matching the provided [`RedactionRules`] before
+    /// they are serialized.
     ///
     /// See [`Json`]
     #[cfg(feature = "json")]
     #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
-    pub fn with_current_span(mut self, display_current_span: bool) -> Format<Json, T> {
-        self.format.with_current_span(display_current_span);
+    pub fn with_field_redaction(mut self, rules: RedactionRules) -> Format<Json, T> {
+        self.format.with_field_redaction(rules);
         self
     }
 
-    /// Sets whether or not the formatter will include a list (from root to
-    /// leaf) of all currently entered spans in formatted events.
+    /// Truncates event field values according to the provided
+    /// [`TruncationRules`] before they are serialized.
     ///
     /// See [`Json`]
     #[cfg(feature = "json")]
     #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
-    pub fn with_span_list(mut self, display_span_list: bool) -> Format<Json, T> {
-        self.format.with_span_list(display_span_list);
+    pub fn with_truncation(mut self, rules: TruncationRules) -> Format<Json, T> {
+        self.format.with_truncation(rules);
+        self
+    }
+}
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+impl<T> Format<Gelf, T> {
+    /// Sets the value of the `host` key included in every payload.
+    ///
+    /// See [`Gelf`]
+    pub fn with_host(mut self, host: impl Into<String>) -> Format<Gelf, T> {
+        self.format.with_host(host);
+        self
+    }
+}
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+impl<T> Format<Syslog, T> {
+    /// Sets the facility used to compute each message's `PRI`.
+    ///
+    /// See [`Syslog`]
+    pub fn with_facility(mut self, facility: Facility) -> Format<Syslog, T> {
+        self.format.with_facility(facility);
+        self
+    }
+
+    /// Sets the value of the `APP-NAME` part included in every message.
+    ///
+    /// See [`Syslog`]
+    pub fn with_app_name(mut self, app_name: impl Into<String>) -> Format<Syslog, T> {
+        self.format.with_app_name(app_name);
+        self
+    }
+
+    /// Sets the `SD-ID` of the structured-data element carrying the event's
+    /// fields.
+    ///
+    /// See [`Syslog`]
+    pub fn with_sd_id(mut self, sd_id: impl Into<String>) -> Format<Syslog, T> {
+        self.format.with_sd_id(sd_id);
+        self
+    }
+
+    /// Switches to the legacy RFC 3164 ("BSD syslog") message format.
+    ///
+    /// See [`Syslog`]
+    pub fn rfc_3164(mut self) -> Format<Syslog, T> {
+        self.format.rfc_3164();
+        self
+    }
+}
+
+impl<T> Format<Columns, T> {
+    /// Sets the width of the target column, or disables target alignment
+    /// when `None`.
+    ///
+    /// See [`Columns`]
+    pub fn with_target_width(mut self, width: Option<usize>) -> Format<Columns, T> {
+        self.format.with_target_width(width);
+        self
+    }
+
+    /// Sets the width of the message column, or disables message alignment
+    /// when `None`.
+    ///
+    /// See [`Columns`]
+    pub fn with_message_width(mut self, width: Option<usize>) -> Format<Columns, T> {
+        self.format.with_message_width(width);
+        self
+    }
+
+    /// Sets whether messages longer than the message column are truncated,
+    /// rather than overflowing it.
+    ///
+    /// See [`Columns`]
+    pub fn with_truncate_message(mut self, truncate_message: bool) -> Format<Columns, T> {
+        self.format.with_truncate_message(truncate_message);
+        self
+    }
+}
+
+impl<T> Format<Csv, T> {
+    /// Declares the set of columns written for each event, replacing the
+    /// default column set.
+    ///
+    /// See [`Csv`]
+    pub fn with_columns(mut self, columns: impl IntoIterator<Item = CsvColumn>) -> Format<Csv, T> {
+        self.format.with_columns(columns);
+        self
+    }
+
+    /// Sets the delimiter written between cells.
+    ///
+    /// See [`Csv`]
+    pub fn with_delimiter(mut self, delimiter: char) -> Format<Csv, T> {
+        self.format.with_delimiter(delimiter);
+        self
+    }
+
+    /// Sets whether a header row naming the columns is written before the
+    /// first record.
+    ///
+    /// See [`Csv`]
+    pub fn with_header(mut self, header: bool) -> Format<Csv, T> {
+        self.format.with_header(header);
+        self
+    }
+
+    /// Appends a trailing column collecting unclaimed event fields as a JSON
+    /// object.
+    ///
+    /// See [`Csv`]
+    pub fn with_extra_fields_column(mut self, name: impl Into<String>) -> Format<Csv, T> {
+        self.format.with_extra_fields_column(name);
+        self
+    }
+}
+
+impl<T> Format<Logfmt, T> {
+    /// Sets a fixed prefix for flattened span field keys, replacing the
+    /// default span-name prefix.
+    ///
+    /// See [`Logfmt`]
+    pub fn with_span_field_prefix(mut self, prefix: impl Into<String>) -> Format<Logfmt, T> {
+        self.format.with_span_field_prefix(prefix);
         self
     }
 }
@@ -968,21 +1884,29 @@ where
             write!(writer, "{:0>2?} ", std::thread::current().id())?;
         }
 
+        self.format_process_info(&mut writer)?;
+
         let dimmed = writer.dimmed();
 
         if let Some(scope) = ctx.event_scope() {
-            let bold = writer.bold();
+            let span_name = writer.span_name_style();
 
             let mut seen = false;
 
             for span in scope.from_root() {
-                write!(writer, "{}", bold.paint(span.metadata().name()))?;
+                write!(writer, "{}", span_name.paint(span.metadata().name()))?;
                 seen = true;
 
                 let ext = span.extensions();
                 if let Some(fields) = &ext.get::<FormattedFields<N>>() {
                     if !fields.is_empty() {
-                        write!(writer, "{}{}{}", bold.paint("{"), fields, bold.paint("}"))?;
+                        write!(
+                            writer,
+                            "{}{}{}",
+                            span_name.paint("{"),
+                            fields,
+                            span_name.paint("}")
+                        )?;
                     }
                 }
                 write!(writer, "{}", dimmed.paint(":"))?;
@@ -994,11 +1918,12 @@ where
         }
 
         if self.display_target {
+            let target = writer.target_style();
             write!(
                 writer,
                 "{}{} ",
-                dimmed.paint(meta.target()),
-                dimmed.paint(":")
+                target.paint(meta.target()),
+                target.paint(":")
             )?;
         }
 
@@ -1008,6 +1933,15 @@ where
             None
         };
 
+        let source_link = if self.display_filename {
+            self.source_link(&writer, meta)
+        } else {
+            None
+        };
+        if let Some(url) = &source_link {
+            write_hyperlink_start(&mut writer, url)?;
+        }
+
         if self.display_filename {
             if let Some(filename) = meta.file() {
                 write!(
@@ -1030,7 +1964,12 @@ where
             )?;
         }
 
+        if source_link.is_some() {
+            write_hyperlink_end(&mut writer)?;
+        }
+
         ctx.format_fields(writer.by_ref(), event)?;
+        self.format_resource(&mut writer)?;
         writeln!(writer)
     }
 }
@@ -1075,16 +2014,28 @@ where
             write!(writer, "{:0>2?} ", std::thread::current().id())?;
         }
 
+        self.format_process_info(&mut writer)?;
+
         let dimmed = writer.dimmed();
         if self.display_target {
+            let target = writer.target_style();
             write!(
                 writer,
                 "{}{}",
-                dimmed.paint(meta.target()),
-                dimmed.paint(":")
+                target.paint(meta.target()),
+                target.paint(":")
             )?;
         }
 
+        let source_link = if self.display_filename {
+            self.source_link(&writer, meta)
+        } else {
+            None
+        };
+        if let Some(url) = &source_link {
+            write_hyperlink_start(&mut writer, url)?;
+        }
+
         if self.display_filename {
             if let Some(filename) = meta.file() {
                 write!(writer, "{}{}", dimmed.paint(filename), dimmed.paint(":"))?;
@@ -1104,6 +2055,10 @@ where
             }
         }
 
+        if source_link.is_some() {
+            write_hyperlink_end(&mut writer)?;
+        }
+
         ctx.format_fields(writer.by_ref(), event)?;
 
         for span in ctx.event_scope().into_iter().flat_map(Scope::from_root) {
@@ -1115,6 +2070,7 @@ where
             }
         }
 
+        self.format_resource(&mut writer)?;
         writeln!(writer)
     }
 }
@@ -1136,9 +2092,8 @@ where
 ///
 #[derive(Debug)]
 pub struct DefaultFields {
-    // reserve the ability to add fields to this without causing a breaking
-    // change in the future.
-    _private: (),
+    redaction: Option<Arc<RedactionRules>>,
+    truncation: Option<TruncationRules>,
 }
 
 /// The [visitor] produced by [`DefaultFields`]'s [`MakeVisitor`] implementation.
@@ -1150,13 +2105,36 @@ pub struct DefaultVisitor<'a> {
     writer: Writer<'a>,
     is_empty: bool,
     result: fmt::Result,
+    redaction: Option<Arc<RedactionRules>>,
+    truncation: Option<TruncationRules>,
 }
 
 impl DefaultFields {
     /// Returns a new default [`FormatFields`] implementation.
     ///
     pub fn new() -> Self {
-        Self { _private: () }
+        Self {
+            redaction: None,
+            truncation: None,
+        }
+    }
+
+    /// Masks fields matching the provided [`RedactionRules`] before they are
+    /// formatted.
+    pub fn with_field_redaction(self, rules: RedactionRules) -> Self {
+        Self {
+            redaction: Some(Arc::new(rules)),
+            ..self
+        }
+    }
+
+    /// Truncates field values according to the provided [`TruncationRules`]
+    /// before they are formatted.
+    pub fn with_truncation(self, rules: TruncationRules) -> Self {
+        Self {
+            truncation: Some(rules),
+            ..self
+        }
     }
 }
 
@@ -1172,6 +2150,8 @@ impl<'a> MakeVisitor<Writer<'a>> for DefaultFields {
     #[inline]
     fn make_visitor(&self, target: Writer<'a>) -> Self::Visitor {
         DefaultVisitor::new(target, true)
+            .with_redaction(self.redaction.clone())
+            .with_truncation(self.truncation.clone())
     }
 }
 
@@ -1189,9 +2169,19 @@ impl<'a> DefaultVisitor<'a> {
             writer,
             is_empty,
             result: Ok(()),
+            redaction: None,
+            truncation: None,
         }
     }
 
+    pub(crate) fn with_redaction(self, redaction: Option<Arc<RedactionRules>>) -> Self {
+        Self { redaction, ..self }
+    }
+
+    pub(crate) fn with_truncation(self, truncation: Option<TruncationRules>) -> Self {
+        Self { truncation, ..self }
+    }
+
     fn maybe_pad(&mut self) {
         if self.is_empty {
             self.is_empty = false;
@@ -1216,7 +2206,7 @@ impl field::Visit for DefaultVisitor<'_> {
 
     fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
         if let Some(source) = value.source() {
-            let italic = self.writer.italic();
+            let italic = self.writer.field_name_style();
             self.record_debug(
                 field,
                 &format_args!(
@@ -1247,24 +2237,71 @@ impl field::Visit for DefaultVisitor<'_> {
             return;
         }
 
+        if let Some(rules) = self.redaction.clone() {
+            if rules.should_redact(name, value) {
+                self.maybe_pad();
+                let name = name.strip_prefix("r#").unwrap_or(name);
+                self.result = match name {
+                    "message" => write!(self.writer, "{}", rules.replacement()),
+                    name => write!(
+                        self.writer,
+                        "{}{}{}",
+                        self.writer.field_name_style().paint(name),
+                        self.writer.dimmed().paint("="),
+                        rules.replacement()
+                    ),
+                };
+                return;
+            }
+        }
+
         // emit separating spaces if needed
         self.maybe_pad();
 
+        let value_style = self.writer.field_value_style();
+
+        if let Some(rules) = self.truncation.clone() {
+            if rules.limits_values() {
+                let value = format!("{:?}", value);
+                let value = rules.truncate_value(&value).unwrap_or(value);
+                self.result = match name {
+                    "message" => write!(self.writer, "{}", value),
+                    name => {
+                        let name = name.strip_prefix("r#").unwrap_or(name);
+                        write!(
+                            self.writer,
+                            "{}{}{}{}{}",
+                            self.writer.field_name_style().paint(name),
+                            self.writer.dimmed().paint("="),
+                            value_style.prefix(),
+                            value,
+                            value_style.suffix()
+                        )
+                    }
+                };
+                return;
+            }
+        }
+
         self.result = match name {
             "message" => write!(self.writer, "{:?}", value),
             name if name.starts_with("r#") => write!(
                 self.writer,
-                "{}{}{:?}",
-                self.writer.italic().paint(&name[2..]),
+                "{}{}{}{:?}{}",
+                self.writer.field_name_style().paint(&name[2..]),
                 self.writer.dimmed().paint("="),
-                value
+                value_style.prefix(),
+                value,
+                value_style.suffix()
             ),
             name => write!(
                 self.writer,
-                "{}{}{:?}",
-                self.writer.italic().paint(name),
+                "{}{}{}{:?}{}",
+                self.writer.field_name_style().paint(name),
                 self.writer.dimmed().paint("="),
-                value
+                value_style.prefix(),
+                value,
+                value_style.suffix()
             ),
         };
     }
@@ -1370,10 +2407,11 @@ trait LevelNames {
     const ERROR_STR: &'static str;
 
     #[cfg(feature = "ansi")]
-    fn format_level(level: Level, ansi: bool) -> FmtLevel<Self> {
+    fn format_level(level: Level, ansi: bool, style: Style) -> FmtLevel<Self> {
         FmtLevel {
             level,
             ansi,
+            style,
             _f: PhantomData,
         }
     }
@@ -1403,6 +2441,13 @@ impl LevelNames for Full {
     const WARN_STR: &'static str = " WARN";
     const ERROR_STR: &'static str = "ERROR";
 }
+impl LevelNames for Columns {
+    const TRACE_STR: &'static str = "TRACE";
+    const DEBUG_STR: &'static str = "DEBUG";
+    const INFO_STR: &'static str = " INFO";
+    const WARN_STR: &'static str = " WARN";
+    const ERROR_STR: &'static str = "ERROR";
+}
 impl LevelNames for Compact {
     const TRACE_STR: &'static str = ".";
     const DEBUG_STR: &'static str = ":";
@@ -1415,6 +2460,8 @@ struct FmtLevel<F: ?Sized> {
     level: Level,
     #[cfg(feature = "ansi")]
     ansi: bool,
+    #[cfg(feature = "ansi")]
+    style: Style,
     _f: PhantomData<fn(F)>,
 }
 
@@ -1424,11 +2471,11 @@ impl<F: LevelNames> fmt::Display for FmtLevel<F> {
         {
             if self.ansi {
                 return match self.level {
-                    Level::TRACE => write!(f, "{}", Color::Purple.paint(F::TRACE_STR)),
-                    Level::DEBUG => write!(f, "{}", Color::Blue.paint(F::DEBUG_STR)),
-                    Level::INFO => write!(f, "{}", Color::Green.paint(F::INFO_STR)),
-                    Level::WARN => write!(f, "{}", Color::Yellow.paint(F::WARN_STR)),
-                    Level::ERROR => write!(f, "{}", Color::Red.paint(F::ERROR_STR)),
+                    Level::TRACE => write!(f, "{}", self.style.paint(F::TRACE_STR)),
+                    Level::DEBUG => write!(f, "{}", self.style.paint(F::DEBUG_STR)),
+                    Level::INFO => write!(f, "{}", self.style.paint(F::INFO_STR)),
+                    Level::WARN => write!(f, "{}", self.style.paint(F::WARN_STR)),
+                    Level::ERROR => write!(f, "{}", self.style.paint(F::ERROR_STR)),
                 };
             }
         }
@@ -1706,6 +2753,177 @@ pub(super) mod test {
         assert_info_hello_ansi(false, expected);
     }
 
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn with_ansi_theme() {
+        use nu_ansi_term::Color;
+        let theme = super::Theme::default()
+            .with_info(Color::Fixed(42))
+            .with_target(Color::Rgb(128, 128, 128));
+        let expected = "\u{1b}[2mfake time\u{1b}[0m \u{1b}[38;5;42m INFO\u{1b}[0m \u{1b}[38;2;128;128;128mtracing_subscriber::fmt::format::test\u{1b}[0m\u{1b}[38;2;128;128;128m:\u{1b}[0m hello\n";
+        let make_writer = MockMakeWriter::default();
+        let subscriber = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_ansi(theme)
+            .with_timer(MockTime);
+        assert_info_hello(subscriber, make_writer, expected)
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn with_ansi_theme_none() {
+        let expected = "fake time  INFO tracing_subscriber::fmt::format::test: hello\n";
+        let make_writer = MockMakeWriter::default();
+        let subscriber = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_ansi(super::Theme::none())
+            .with_timer(MockTime);
+        assert_info_hello(subscriber, make_writer, expected)
+    }
+
+    #[test]
+    fn with_hostname_and_process_id() {
+        let make_writer = MockMakeWriter::default();
+        let subscriber = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_hostname("web-1")
+            .with_process_id(true)
+            .with_timer(MockTime);
+        #[cfg(feature = "ansi")]
+        let subscriber = subscriber.with_ansi(false);
+        let expected = format!(
+            "fake time  INFO web-1 {} tracing_subscriber::fmt::format::test: hello\n",
+            std::process::id()
+        );
+        assert_info_hello(subscriber, make_writer, &expected)
+    }
+
+    #[test]
+    fn with_resource() {
+        let make_writer = MockMakeWriter::default();
+        let subscriber = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_resource([("service.name", "api"), ("service.version", "1.2.3")])
+            .with_timer(MockTime);
+        #[cfg(feature = "ansi")]
+        let subscriber = subscriber.with_ansi(false);
+        let expected = "fake time  INFO tracing_subscriber::fmt::format::test: hello service.name=api service.version=1.2.3\n";
+        assert_info_hello(subscriber, make_writer, expected)
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn hyperlinked_source_location() {
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_ansi(true)
+            .with_file(true)
+            .with_line_number(true)
+            .with_hyperlink_template("vscode://file/{path}:{line}")
+            .with_timer(MockTime)
+            .finish();
+
+        with_default(collector, || {
+            tracing::info!("hello");
+        });
+
+        let actual = make_writer.get_string();
+        assert!(
+            actual.contains(&format!("\u{1b}]8;;vscode://file/{}:", file!())),
+            "expected a hyperlink to the source location, got: {:?}",
+            actual
+        );
+        assert!(
+            actual.contains("\u{1b}]8;;\u{1b}\\"),
+            "expected the hyperlink to be closed, got: {:?}",
+            actual
+        );
+    }
+
+    #[test]
+    fn no_hyperlinks_without_ansi() {
+        let make_writer = MockMakeWriter::default();
+        let subscriber = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_file(true)
+            .with_line_number(true)
+            .with_hyperlink_template("vscode://file/{path}:{line}")
+            .with_timer(MockTime);
+        #[cfg(feature = "ansi")]
+        let subscriber = subscriber.with_ansi(false);
+        let collector = subscriber.finish();
+
+        with_default(collector, || {
+            tracing::info!("hello");
+        });
+
+        let actual = make_writer.get_string();
+        assert!(
+            !actual.contains("]8;;"),
+            "expected no hyperlinks without ANSI escapes, got: {:?}",
+            actual
+        );
+    }
+
+    #[test]
+    fn format_for_target_overrides_matching_targets() {
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_ansi(false)
+            .with_timer(MockTime)
+            .with_format_for_target(
+                "noisy",
+                crate::fmt::format().without_time().with_level(false),
+            )
+            .finish();
+
+        with_default(collector, || {
+            tracing::info!(target: "noisy", "loud");
+            tracing::info!(target: "noisy::db", "also loud");
+            tracing::info!(target: "noisy_neighbor", "calm");
+            tracing::info!(target: "quiet", "calm");
+        });
+
+        let actual = make_writer.get_string();
+        assert_eq!(
+            actual,
+            "noisy: loud\n\
+             noisy::db: also loud\n\
+             fake time  INFO noisy_neighbor: calm\n\
+             fake time  INFO quiet: calm\n"
+        );
+    }
+
+    #[test]
+    fn tee_formats_each_output_independently() {
+        let primary = MockMakeWriter::default();
+        let secondary = MockMakeWriter::default();
+        let collector = crate::fmt::Collector::builder()
+            .with_writer(primary.clone())
+            .with_ansi(false)
+            .with_timer(MockTime)
+            .with_tee(
+                crate::fmt::format()
+                    .without_time()
+                    .with_level(false)
+                    .with_target(false),
+                secondary.clone(),
+            )
+            .finish();
+
+        with_default(collector, || {
+            tracing::info!("hello");
+        });
+
+        assert_eq!(
+            primary.get_string(),
+            "fake time  INFO tracing_subscriber::fmt::format::test: hello\n"
+        );
+        assert_eq!(secondary.get_string(), "hello\n");
+    }
+
     #[cfg(feature = "ansi")]
     fn assert_info_hello_ansi(is_ansi: bool, expected: &str) {
         let make_writer = MockMakeWriter::default();
@@ -8,13 +8,13 @@ use crate::{
 use std::fmt;
 use tracing_core::{
     field::{self, Field},
-    Collect, Event, Level,
+    Collect, Event,
 };
 
 #[cfg(feature = "tracing-log")]
 use tracing_log::NormalizeEvent;
 
-use nu_ansi_term::{Color, Style};
+use nu_ansi_term::Style;
 
 /// An excessively pretty, human-readable event formatter.
 ///
@@ -141,16 +141,6 @@ impl Default for Pretty {
 }
 
 impl Pretty {
-    fn style_for(level: &Level) -> Style {
-        match *level {
-            Level::TRACE => Style::new().fg(Color::Purple),
-            Level::DEBUG => Style::new().fg(Color::Blue),
-            Level::INFO => Style::new().fg(Color::Green),
-            Level::WARN => Style::new().fg(Color::Yellow),
-            Level::ERROR => Style::new().fg(Color::Red),
-        }
-    }
-
     /// Sets whether the event's source code location is displayed.
     ///
     /// This defaults to `true`.
@@ -189,7 +179,7 @@ where
         self.format_timestamp(&mut writer)?;
 
         let style = if self.display_level && writer.has_ansi_escapes() {
-            Pretty::style_for(meta.level())
+            writer.level_style(*meta.level())
         } else {
             Style::new()
         };
@@ -254,11 +244,19 @@ where
             self.format.display_location,
             self.display_filename,
         ) {
-            write!(writer, "    {} {}", dimmed.paint("at"), file,)?;
+            let source_link = self.source_link(&writer, meta);
+            write!(writer, "    {} ", dimmed.paint("at"))?;
+            if let Some(url) = &source_link {
+                write_hyperlink_start(&mut writer, url)?;
+            }
+            write!(writer, "{}", file)?;
 
             if let Some(line) = line_number {
                 write!(writer, ":{}", line)?;
             }
+            if source_link.is_some() {
+                write_hyperlink_end(&mut writer)?;
+            }
             writer.write_char(if thread { ' ' } else { '\n' })?;
         } else if thread {
             write!(writer, "    ")?;
@@ -281,7 +279,21 @@ where
             writer.write_char('\n')?;
         }
 
-        let bold = writer.bold();
+        if self.hostname.is_some() || self.display_process_id {
+            write!(writer, "    {} ", dimmed.paint("from"))?;
+            if let Some(hostname) = &self.hostname {
+                write!(writer, "{}", hostname)?;
+                if self.display_process_id {
+                    writer.write_char(' ')?;
+                }
+            }
+            if self.display_process_id {
+                write!(writer, "{}", std::process::id())?;
+            }
+            writer.write_char('\n')?;
+        }
+
+        let span_name = writer.span_name_style();
         let span = event
             .parent()
             .and_then(|id| ctx.span(id))
@@ -297,14 +309,14 @@ where
                     "    {} {}::{}",
                     dimmed.paint("in"),
                     meta.target(),
-                    bold.paint(meta.name()),
+                    span_name.paint(meta.name()),
                 )?;
             } else {
                 write!(
                     writer,
                     "    {} {}",
                     dimmed.paint("in"),
-                    bold.paint(meta.name()),
+                    span_name.paint(meta.name()),
                 )?;
             }
 
@@ -318,6 +330,21 @@ where
             writer.write_char('\n')?;
         }
 
+        if !self.resource.is_empty() {
+            let italic = writer.field_name_style();
+            write!(writer, "    {}", dimmed.paint("with"))?;
+            for (key, value) in &self.resource {
+                write!(
+                    writer,
+                    " {}{}{}",
+                    italic.paint(key),
+                    dimmed.paint("="),
+                    value
+                )?;
+            }
+            writer.write_char('\n')?;
+        }
+
         writer.write_char('\n')
     }
 }
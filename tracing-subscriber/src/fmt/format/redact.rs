@@ -0,0 +1,311 @@
+use std::fmt;
+use std::sync::Arc;
+
+/// Rules for masking sensitive fields before they are written.
+///
+/// `RedactionRules` describe which fields should have their values replaced
+/// with a placeholder when events and spans are formatted, so that
+/// compliance scrubbing is centralized in the subscriber rather than
+/// hand-rolled in every exporter. Fields can be matched by name, either
+/// exactly or with `*` glob patterns, and values can be matched with
+/// arbitrary predicates (for example, a regex matching email addresses or
+/// credit card numbers).
+///
+/// Rules are installed with [`Subscriber::with_field_redaction`] or
+/// [`CollectorBuilder::with_field_redaction`], and apply to the
+/// [`DefaultFields`] and [`JsonFields`] field formatters.
+///
+/// # Examples
+///
+/// ```
+/// use tracing_subscriber::fmt::format::RedactionRules;
+///
+/// let rules = RedactionRules::new()
+///     // mask `password` fields anywhere...
+///     .redact_field("password")
+///     // ...and any field ending in `.card_number`...
+///     .redact_field("*.card_number")
+///     // ...as well as any value that looks like an email address.
+///     .redact_values(|value| value.contains('@'));
+///
+/// let collector = tracing_subscriber::fmt()
+///     .with_field_redaction(rules)
+///     .finish();
+/// ```
+///
+/// [`Subscriber::with_field_redaction`]: crate::fmt::Subscriber::with_field_redaction
+/// [`CollectorBuilder::with_field_redaction`]: crate::fmt::CollectorBuilder::with_field_redaction
+/// [`DefaultFields`]: super::DefaultFields
+/// [`JsonFields`]: super::JsonFields
+#[derive(Clone, Default)]
+pub struct RedactionRules {
+    fields: Vec<String>,
+    values: Vec<Arc<dyn Fn(&str) -> bool + Send + Sync>>,
+    replacement: Option<String>,
+}
+
+impl RedactionRules {
+    /// Returns a new, empty set of redaction rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Masks fields whose name matches `pattern`.
+    ///
+    /// A `*` in the pattern matches any (possibly empty) sequence of
+    /// characters, so `"*.token"` masks `auth.token` and
+    /// `session.refresh.token`; patterns without a `*` must match the field
+    /// name exactly. Raw-identifier fields are matched without their `r#`
+    /// prefix.
+    pub fn redact_field(mut self, pattern: impl Into<String>) -> Self {
+        self.fields.push(pattern.into());
+        self
+    }
+
+    /// Masks any field whose value matches the provided predicate.
+    ///
+    /// The predicate is given the value as recorded: string values are
+    /// passed as-is, and other values are passed in their `fmt::Debug`
+    /// representation.
+    pub fn redact_values(mut self, matches: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        self.values.push(Arc::new(matches));
+        self
+    }
+
+    /// Sets the placeholder written in place of masked values.
+    ///
+    /// Defaults to `[REDACTED]`.
+    pub fn with_replacement(mut self, replacement: impl Into<String>) -> Self {
+        self.replacement = Some(replacement.into());
+        self
+    }
+
+    /// Returns the placeholder written in place of masked values.
+    pub(crate) fn replacement(&self) -> &str {
+        self.replacement.as_deref().unwrap_or("[REDACTED]")
+    }
+
+    /// Returns whether a field named `name` should be masked regardless of
+    /// its value.
+    pub(crate) fn field_matches(&self, name: &str) -> bool {
+        let name = name.strip_prefix("r#").unwrap_or(name);
+        self.fields
+            .iter()
+            .any(|pattern| glob_matches(pattern, name))
+    }
+
+    /// Returns whether a value should be masked.
+    pub(crate) fn value_matches(&self, value: &str) -> bool {
+        self.values.iter().any(|matches| matches(value))
+    }
+
+    /// Returns whether any value predicates have been configured.
+    pub(crate) fn has_value_rules(&self) -> bool {
+        !self.values.is_empty()
+    }
+
+    /// Returns whether the field `name` with the provided value should be
+    /// masked, formatting the value only if value predicates are present.
+    pub(crate) fn should_redact(&self, name: &str, value: &dyn fmt::Debug) -> bool {
+        if self.field_matches(name) {
+            return true;
+        }
+        if !self.has_value_rules() {
+            return false;
+        }
+        self.value_matches(&format!("{:?}", value))
+    }
+}
+
+impl PartialEq for RedactionRules {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields == other.fields
+            && self.replacement == other.replacement
+            && self.values.len() == other.values.len()
+            && self
+                .values
+                .iter()
+                .zip(&other.values)
+                // value predicates are opaque closures, so they can only be
+                // compared by identity.
+                .all(|(a, b)| Arc::as_ptr(a) as *const () == Arc::as_ptr(b) as *const ())
+    }
+}
+
+impl Eq for RedactionRules {}
+
+impl fmt::Debug for RedactionRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedactionRules")
+            .field("fields", &self.fields)
+            .field(
+                "values",
+                &format_args!("[{} predicates]", self.values.len()),
+            )
+            .field("replacement", &self.replacement())
+            .finish()
+    }
+}
+
+/// Returns whether `name` matches `pattern`, where `*` in the pattern
+/// matches any (possibly empty) sequence of characters.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().expect("split always yields at least one part");
+    if !name.starts_with(first) {
+        return false;
+    }
+    let mut rest = &name[first.len()..];
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            // the last part must match the end of the name.
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    // the pattern contained no `*`: it must have matched exactly.
+    rest.is_empty()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fmt::test::MockMakeWriter;
+
+    use tracing::collect::with_default;
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_matches("password", "password"));
+        assert!(!glob_matches("password", "password_hash"));
+        assert!(glob_matches("*.token", "auth.token"));
+        assert!(glob_matches("*.token", "session.refresh.token"));
+        assert!(!glob_matches("*.token", "token"));
+        assert!(glob_matches("card*", "card_number"));
+        assert!(glob_matches("*secret*", "my_secret_key"));
+        assert!(glob_matches("*", "anything"));
+    }
+
+    #[test]
+    fn redacts_fields_by_name() {
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_field_redaction(RedactionRules::new().redact_field("password"))
+            .with_ansi(false)
+            .without_time()
+            .finish();
+
+        with_default(collector, || {
+            tracing::info!(user = "alice", password = "hunter2", "logged in");
+        });
+
+        let actual = make_writer.get_string();
+        assert!(
+            actual.contains("password=[REDACTED]"),
+            "the password must be masked: {:?}",
+            actual,
+        );
+        assert!(
+            actual.contains("user=\"alice\""),
+            "other fields must be unchanged: {:?}",
+            actual,
+        );
+    }
+
+    #[test]
+    fn redacts_values_by_predicate() {
+        let rules = RedactionRules::new()
+            .redact_values(|value| value.contains('@'))
+            .with_replacement("<scrubbed>");
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_field_redaction(rules)
+            .with_ansi(false)
+            .without_time()
+            .finish();
+
+        with_default(collector, || {
+            tracing::info!(email = "alice@example.com", plan = "free", "signed up");
+        });
+
+        let actual = make_writer.get_string();
+        assert!(
+            actual.contains("email=<scrubbed>"),
+            "the email must be masked: {:?}",
+            actual,
+        );
+        assert!(
+            actual.contains("plan=\"free\""),
+            "other fields must be unchanged: {:?}",
+            actual,
+        );
+    }
+
+    #[test]
+    fn redacts_span_fields() {
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_field_redaction(RedactionRules::new().redact_field("*.secret"))
+            .with_ansi(false)
+            .without_time()
+            .finish();
+
+        with_default(collector, || {
+            let span = tracing::info_span!("request", api.secret = "s3cr3t");
+            let _guard = span.enter();
+            tracing::info!("handling");
+        });
+
+        let actual = make_writer.get_string();
+        assert!(
+            actual.contains("api.secret=[REDACTED]"),
+            "the span field must be masked: {:?}",
+            actual,
+        );
+        assert!(
+            !actual.contains("s3cr3t"),
+            "the raw value must not appear: {:?}",
+            actual,
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn redacts_json_fields() {
+        let rules = RedactionRules::new()
+            .redact_field("password")
+            .redact_values(|value| value.contains('@'));
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::fmt::Collector::builder()
+            .json()
+            .with_writer(make_writer.clone())
+            .with_field_redaction(rules)
+            .finish();
+
+        with_default(collector, || {
+            let span = tracing::info_span!("login", password = "hunter2");
+            span.record("password", "changed");
+            let _guard = span.enter();
+            tracing::info!(email = "alice@example.com", user = "alice", "logged in");
+        });
+
+        let buf = make_writer.buf();
+        let actual = std::str::from_utf8(&buf[..]).unwrap();
+        let json: serde_json::Value = serde_json::from_str(actual.lines().next().unwrap()).unwrap();
+        assert_eq!(json["fields"]["email"], "[REDACTED]");
+        assert_eq!(json["fields"]["user"], "alice");
+        assert_eq!(json["span"]["password"], "[REDACTED]");
+        assert!(
+            !actual.contains("hunter2") && !actual.contains("changed"),
+            "raw values must not appear: {:?}",
+            actual,
+        );
+    }
+}
@@ -0,0 +1,902 @@
+use super::{gelf::strip_newline, Format, FormatEvent, FormatFields, Writer};
+use crate::{
+    fmt::{
+        fmt_subscriber::{FmtContext, FormattedFields},
+        time::datetime::DateTime,
+        writer::MakeWriter,
+    },
+    registry::LookupSpan,
+};
+use serde_json::{Map, Value};
+use std::{
+    fmt,
+    io::{self, Write as _},
+    net::{TcpStream, ToSocketAddrs, UdpSocket},
+    sync::Mutex,
+    time::SystemTime,
+};
+use tracing_core::{
+    field::{self, Field},
+    Collect, Event, Level,
+};
+
+#[cfg(feature = "tracing-log")]
+use tracing_log::NormalizeEvent;
+
+/// Marker for [`Format`] that indicates that the syslog message format
+/// should be used.
+///
+/// Each event is formatted as a newline-delimited [RFC 5424] syslog message,
+/// so that services can ship logs to enterprise syslog collectors without a
+/// conversion sidecar. The message's `PRI` is computed from the configured
+/// [`Facility`] and the event's level, mapped to the equivalent syslog
+/// severity; the event's remaining fields, the fields of its spans, and (if
+/// enabled on the [`Format`]) its target and source location are emitted as
+/// parameters of a single structured-data element.
+///
+/// # Example Output
+///
+/// ```ignore
+/// <14>1 2023-08-22T14:00:00.123456Z myhost myapp 1234 - [tracing@0 target="mycrate" answer="42"] some message
+/// ```
+///
+/// The event's `message` field becomes the free-form `MSG` part; events
+/// without a message use the event's metadata name. Span fields are emitted
+/// from root to leaf, followed by the event's own fields.
+///
+/// # Options
+///
+/// - [`Syslog::with_facility`] sets the facility used to compute `PRI`. It
+///   defaults to [`Facility::User`].
+/// - [`Syslog::with_app_name`] sets the `APP-NAME` part. It defaults to the
+///   name of the current executable.
+/// - [`Syslog::with_sd_id`] sets the `SD-ID` of the structured-data element.
+///   It defaults to `tracing@0`.
+/// - [`Syslog::rfc_3164`] switches to the legacy [RFC 3164] ("BSD syslog")
+///   format, which has no structured data; fields are appended to the
+///   message as `key=value` pairs instead.
+/// - [`Format::with_hostname`] sets the `HOSTNAME` part. It defaults to the
+///   `HOSTNAME` environment variable, falling back to `localhost`.
+///
+/// Delivery is handled by the writer: pair this formatter with
+/// [`SyslogUnixWriter`] for the system logger's `/dev/log` socket,
+/// [`SyslogUdpWriter`] for UDP, or [`SyslogTcpWriter`] for octet-counted TCP
+/// framing (including TLS sessions established by the caller).
+///
+/// [RFC 5424]: https://www.rfc-editor.org/rfc/rfc5424
+/// [RFC 3164]: https://www.rfc-editor.org/rfc/rfc3164
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Syslog {
+    pub(crate) facility: Facility,
+    pub(crate) app_name: String,
+    pub(crate) sd_id: String,
+    pub(crate) rfc_3164: bool,
+}
+
+impl Default for Syslog {
+    fn default() -> Self {
+        Self {
+            facility: Facility::User,
+            app_name: default_app_name(),
+            sd_id: String::from("tracing@0"),
+            rfc_3164: false,
+        }
+    }
+}
+
+impl Syslog {
+    /// Sets the facility used to compute each message's `PRI`.
+    pub fn with_facility(&mut self, facility: Facility) {
+        self.facility = facility;
+    }
+
+    /// Sets the value of the `APP-NAME` part included in every message.
+    pub fn with_app_name(&mut self, app_name: impl Into<String>) {
+        self.app_name = app_name.into();
+    }
+
+    /// Sets the `SD-ID` of the structured-data element carrying the event's
+    /// fields.
+    pub fn with_sd_id(&mut self, sd_id: impl Into<String>) {
+        self.sd_id = sd_id.into();
+    }
+
+    /// Switches to the legacy [RFC 3164] ("BSD syslog") message format.
+    ///
+    /// [RFC 3164]: https://www.rfc-editor.org/rfc/rfc3164
+    pub fn rfc_3164(&mut self) {
+        self.rfc_3164 = true;
+    }
+}
+
+/// A syslog facility code, as defined by [RFC 5424 §6.2.1].
+///
+/// [RFC 5424 §6.2.1]: https://www.rfc-editor.org/rfc/rfc5424#section-6.2.1
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Facility {
+    /// Kernel messages.
+    Kern = 0,
+    /// User-level messages. This is the default.
+    User = 1,
+    /// Mail system.
+    Mail = 2,
+    /// System daemons.
+    Daemon = 3,
+    /// Security/authorization messages.
+    Auth = 4,
+    /// Messages generated internally by syslogd.
+    Syslog = 5,
+    /// Line printer subsystem.
+    Lpr = 6,
+    /// Network news subsystem.
+    News = 7,
+    /// UUCP subsystem.
+    Uucp = 8,
+    /// Clock daemon.
+    Cron = 9,
+    /// Security/authorization messages.
+    AuthPriv = 10,
+    /// FTP daemon.
+    Ftp = 11,
+    /// Locally used facility 0.
+    Local0 = 16,
+    /// Locally used facility 1.
+    Local1 = 17,
+    /// Locally used facility 2.
+    Local2 = 18,
+    /// Locally used facility 3.
+    Local3 = 19,
+    /// Locally used facility 4.
+    Local4 = 20,
+    /// Locally used facility 5.
+    Local5 = 21,
+    /// Locally used facility 6.
+    Local6 = 22,
+    /// Locally used facility 7.
+    Local7 = 23,
+}
+
+impl<C, N, T> FormatEvent<C, N> for Format<Syslog, T>
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, C, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        #[cfg(feature = "tracing-log")]
+        let normalized_meta = event.normalized_metadata();
+        #[cfg(feature = "tracing-log")]
+        let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
+        #[cfg(not(feature = "tracing-log"))]
+        let meta = event.metadata();
+
+        let priority = (self.format.facility as u8) * 8 + syslog_severity(meta.level());
+
+        let mut params: Vec<(String, String)> = Vec::new();
+        if self.display_target {
+            params.push((String::from("target"), meta.target().to_string()));
+        }
+        if self.display_filename {
+            if let Some(filename) = meta.file() {
+                params.push((String::from("file"), filename.to_string()));
+            }
+        }
+        if self.display_line_number {
+            if let Some(line_number) = meta.line() {
+                params.push((String::from("line"), line_number.to_string()));
+            }
+        }
+
+        // Span fields from root to leaf, followed by the event's own fields.
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let ext = span.extensions();
+                if let Some(fields) = ext.get::<FormattedFields<N>>() {
+                    if fields.is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<Map<String, Value>>(fields.fields.as_str()) {
+                        Ok(span_fields) => {
+                            for (key, value) in span_fields {
+                                params.push((key, param_value(value)));
+                            }
+                        }
+                        // The span's fields were formatted by something
+                        // other than `JsonFields`; include them verbatim
+                        // under the span's name.
+                        Err(_) => {
+                            params.push((span.name().to_string(), fields.fields.clone()));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut visitor = SyslogVisitor {
+            params: &mut params,
+            message: None,
+        };
+        event.record(&mut visitor);
+        let message = visitor.message.unwrap_or_else(|| meta.name().to_string());
+
+        let hostname = self
+            .hostname
+            .clone()
+            .or_else(|| std::env::var("HOSTNAME").ok())
+            .unwrap_or_else(|| String::from("localhost"));
+
+        if self.format.rfc_3164 {
+            let now = DateTime::from(SystemTime::now());
+            write!(
+                writer,
+                "<{}>{} {:>2} {:02}:{:02}:{:02} {} {}[{}]: {}",
+                priority,
+                MONTHS[usize::from(now.month()) - 1],
+                now.day(),
+                now.hour(),
+                now.minute(),
+                now.second(),
+                hostname,
+                self.format.app_name,
+                std::process::id(),
+                message,
+            )?;
+            for (name, value) in &params {
+                if value.contains(|c: char| c.is_whitespace() || c == '"' || c == '=') {
+                    write!(writer, " {}={:?}", name, value)?;
+                } else {
+                    write!(writer, " {}={}", name, value)?;
+                }
+            }
+            return writeln!(writer);
+        }
+
+        write!(writer, "<{}>1 ", priority)?;
+        if self.display_timestamp {
+            write!(writer, "{} ", DateTime::from(SystemTime::now()))?;
+        } else {
+            writer.write_str("- ")?;
+        }
+        write!(
+            writer,
+            "{} {} {} - ",
+            hostname,
+            self.format.app_name,
+            std::process::id(),
+        )?;
+
+        if params.is_empty() {
+            writer.write_str("-")?;
+        } else {
+            write!(writer, "[{}", self.format.sd_id)?;
+            for (name, value) in &params {
+                write!(writer, " {}=\"", name)?;
+                write_sd_value(&mut writer, value)?;
+                writer.write_char('"')?;
+            }
+            writer.write_char(']')?;
+        }
+
+        writeln!(writer, " {}", message)
+    }
+}
+
+static MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Returns the syslog severity corresponding to `level`, as used to compute
+/// a message's `PRI`.
+fn syslog_severity(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        Level::DEBUG | Level::TRACE => 7,
+    }
+}
+
+fn default_app_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| String::from("-"))
+}
+
+/// Renders a span field's JSON value as a structured-data parameter value.
+fn param_value(value: Value) -> String {
+    match value {
+        Value::String(value) => value,
+        value => value.to_string(),
+    }
+}
+
+/// Writes `value` with the `"`, `\`, and `]` characters escaped, as
+/// [RFC 5424 §6.3.3] requires for `PARAM-VALUE`s.
+///
+/// [RFC 5424 §6.3.3]: https://www.rfc-editor.org/rfc/rfc5424#section-6.3.3
+fn write_sd_value(writer: &mut Writer<'_>, value: &str) -> fmt::Result {
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            writer.write_char('\\')?;
+        }
+        writer.write_char(c)?;
+    }
+    Ok(())
+}
+
+/// Records an event's fields as structured-data parameters, keeping the
+/// `message` field aside for the `MSG` part.
+struct SyslogVisitor<'a> {
+    params: &'a mut Vec<(String, String)>,
+    message: Option<String>,
+}
+
+impl SyslogVisitor<'_> {
+    fn record_value(&mut self, field: &Field, value: String) {
+        let name = field.name();
+        // Skip fields that are actually log metadata that have already been handled
+        #[cfg(feature = "tracing-log")]
+        if name.starts_with("log.") {
+            return;
+        }
+        let name = name.strip_prefix("r#").unwrap_or(name);
+        self.params.push((name.to_string(), value));
+    }
+}
+
+impl field::Visit for SyslogVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record_value(field, value.to_string());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_value(field, value.to_string());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record_value(field, value.to_string());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record_value(field, value.to_string());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_string());
+        } else {
+            self.record_value(field, value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{:?}", value));
+        } else {
+            self.record_value(field, format!("{:?}", value));
+        }
+    }
+}
+
+/// A [`MakeWriter`] that ships each formatted event to the system logger's
+/// Unix datagram socket.
+///
+/// Each event becomes one datagram. Network errors are reported to the
+/// [`fmt` subscriber], which drops the event.
+///
+/// This writer is intended for use with the [`Syslog`] formatter:
+///
+/// ```no_run
+/// use tracing_subscriber::fmt::format::SyslogUnixWriter;
+///
+/// let writer = SyslogUnixWriter::local()
+///     .expect("failed to connect to the system logger");
+/// tracing_subscriber::fmt()
+///     .syslog()
+///     .with_writer(writer)
+///     .init();
+/// ```
+///
+/// [`fmt` subscriber]: crate::fmt::Subscriber
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct SyslogUnixWriter {
+    socket: std::os::unix::net::UnixDatagram,
+}
+
+#[cfg(unix)]
+impl SyslogUnixWriter {
+    /// Connects a writer to the system logger's socket at `/dev/log`,
+    /// falling back to `/var/run/syslog` (used on macOS).
+    pub fn local() -> io::Result<Self> {
+        Self::connect("/dev/log").or_else(|_| Self::connect("/var/run/syslog"))
+    }
+
+    /// Connects a writer to the Unix datagram socket at `path`.
+    pub fn connect(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        let socket = std::os::unix::net::UnixDatagram::unbound()?;
+        socket.connect(path)?;
+        Ok(Self { socket })
+    }
+
+    fn send(&self, message: &[u8]) -> io::Result<()> {
+        // The trailing newline added by the formatter is line framing, not
+        // part of the message.
+        let message = strip_newline(message);
+        if message.is_empty() {
+            return Ok(());
+        }
+        self.socket.send(message).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+impl<'a> MakeWriter<'a> for SyslogUnixWriter {
+    type Writer = SyslogUnixIo<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        SyslogUnixIo {
+            writer: self,
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// An [`io::Write`] implementation returned by [`SyslogUnixWriter`],
+/// buffering one formatted event and sending it as a datagram when flushed
+/// or dropped.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct SyslogUnixIo<'a> {
+    writer: &'a SyslogUnixWriter,
+    buf: Vec<u8>,
+}
+
+#[cfg(unix)]
+impl io::Write for SyslogUnixIo<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.writer.send(&self.buf);
+        self.buf.clear();
+        result
+    }
+}
+
+#[cfg(unix)]
+impl Drop for SyslogUnixIo<'_> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// A [`MakeWriter`] that ships each formatted event to a syslog UDP
+/// collector.
+///
+/// Each event becomes one datagram. Network errors are reported to the
+/// [`fmt` subscriber], which drops the event.
+///
+/// This writer is intended for use with the [`Syslog`] formatter:
+///
+/// ```no_run
+/// use tracing_subscriber::fmt::format::SyslogUdpWriter;
+///
+/// let writer = SyslogUdpWriter::connect("syslog.example.com:514")
+///     .expect("failed to connect to the syslog collector");
+/// tracing_subscriber::fmt()
+///     .syslog()
+///     .with_writer(writer)
+///     .init();
+/// ```
+///
+/// [`fmt` subscriber]: crate::fmt::Subscriber
+#[derive(Debug)]
+pub struct SyslogUdpWriter {
+    socket: UdpSocket,
+}
+
+impl SyslogUdpWriter {
+    /// Connects a writer to the syslog UDP collector at `addr`.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(addr)?;
+        Ok(Self { socket })
+    }
+
+    fn send(&self, message: &[u8]) -> io::Result<()> {
+        let message = strip_newline(message);
+        if message.is_empty() {
+            return Ok(());
+        }
+        self.socket.send(message).map(|_| ())
+    }
+}
+
+impl<'a> MakeWriter<'a> for SyslogUdpWriter {
+    type Writer = SyslogUdpIo<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        SyslogUdpIo {
+            writer: self,
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// An [`io::Write`] implementation returned by [`SyslogUdpWriter`], buffering
+/// one formatted event and sending it as a datagram when flushed or dropped.
+#[derive(Debug)]
+pub struct SyslogUdpIo<'a> {
+    writer: &'a SyslogUdpWriter,
+    buf: Vec<u8>,
+}
+
+impl io::Write for SyslogUdpIo<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.writer.send(&self.buf);
+        self.buf.clear();
+        result
+    }
+}
+
+impl Drop for SyslogUdpIo<'_> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// A [`MakeWriter`] that ships each formatted event to a syslog TCP
+/// collector.
+///
+/// Messages are framed with the octet-counting scheme from [RFC 6587]: the
+/// message's length in bytes, a space, then the message itself. Network
+/// errors are reported to the [`fmt` subscriber], which drops the event; the
+/// connection is not re-established automatically.
+///
+/// For syslog over TLS ([RFC 5425]), establish the TLS session with a crate
+/// such as `native-tls` or `rustls` and pass the connected stream to
+/// [`SyslogTcpWriter::from_stream`]; the framing is identical.
+///
+/// This writer is intended for use with the [`Syslog`] formatter:
+///
+/// ```no_run
+/// use tracing_subscriber::fmt::format::SyslogTcpWriter;
+///
+/// let writer = SyslogTcpWriter::connect("syslog.example.com:6514")
+///     .expect("failed to connect to the syslog collector");
+/// tracing_subscriber::fmt()
+///     .syslog()
+///     .with_writer(writer)
+///     .init();
+/// ```
+///
+/// [RFC 6587]: https://www.rfc-editor.org/rfc/rfc6587
+/// [RFC 5425]: https://www.rfc-editor.org/rfc/rfc5425
+/// [`fmt` subscriber]: crate::fmt::Subscriber
+pub struct SyslogTcpWriter {
+    stream: Mutex<Box<dyn io::Write + Send>>,
+}
+
+impl SyslogTcpWriter {
+    /// Connects a writer to the syslog TCP collector at `addr`.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::from_stream(TcpStream::connect(addr)?))
+    }
+
+    /// Wraps an already-connected stream, such as a TLS session established
+    /// by the caller.
+    pub fn from_stream(stream: impl io::Write + Send + 'static) -> Self {
+        Self {
+            stream: Mutex::new(Box::new(stream)),
+        }
+    }
+
+    fn send(&self, message: &[u8]) -> io::Result<()> {
+        let message = strip_newline(message);
+        if message.is_empty() {
+            return Ok(());
+        }
+        let mut stream = match self.stream.lock() {
+            Ok(stream) => stream,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        write!(stream, "{} ", message.len())?;
+        stream.write_all(message)?;
+        stream.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for SyslogTcpWriter {
+    type Writer = SyslogTcpIo<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        SyslogTcpIo {
+            writer: self,
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Debug for SyslogTcpWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("SyslogTcpWriter { .. }")
+    }
+}
+
+/// An [`io::Write`] implementation returned by [`SyslogTcpWriter`],
+/// buffering one formatted event and sending it as an octet-counted frame
+/// when flushed or dropped.
+#[derive(Debug)]
+pub struct SyslogTcpIo<'a> {
+    writer: &'a SyslogTcpWriter,
+    buf: Vec<u8>,
+}
+
+impl io::Write for SyslogTcpIo<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.writer.send(&self.buf);
+        self.buf.clear();
+        result
+    }
+}
+
+impl Drop for SyslogTcpIo<'_> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fmt::{test::MockMakeWriter, CollectorBuilder};
+
+    use tracing::{self, collect::with_default};
+
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    fn collector() -> CollectorBuilder<super::super::JsonFields, Format<Syslog>> {
+        CollectorBuilder::default()
+            .syslog()
+            .with_hostname("testhost")
+            .with_app_name("testapp")
+    }
+
+    fn last_line(buffer: &MockMakeWriter) -> String {
+        let buf = buffer.get_string();
+        buf.lines()
+            .last()
+            .expect("expected at least one line to be written!")
+            .to_string()
+    }
+
+    #[test]
+    fn rfc_5424() {
+        let make_writer = MockMakeWriter::default();
+        let collector = collector()
+            .without_time()
+            .with_writer(make_writer.clone())
+            .finish();
+
+        with_default(collector, || {
+            let span = tracing::info_span!("syslog_span", answer = 42);
+            let _guard = span.enter();
+            tracing::info!(key = "value", "some syslog test");
+        });
+
+        let line = last_line(&make_writer);
+        let expected = format!(
+            "<14>1 - testhost testapp {} - [tracing@0 \
+             target=\"tracing_subscriber::fmt::format::syslog::test\" \
+             answer=\"42\" key=\"value\"] some syslog test",
+            std::process::id(),
+        );
+        assert_eq!(line, expected);
+    }
+
+    #[test]
+    fn priority_reflects_facility_and_severity() {
+        let make_writer = MockMakeWriter::default();
+        let collector = collector()
+            .with_facility(Facility::Local3)
+            .without_time()
+            .with_writer(make_writer.clone())
+            .finish();
+
+        with_default(collector, || {
+            tracing::error!("bad yak");
+        });
+
+        // local3 (19) * 8 + error severity (3)
+        assert!(last_line(&make_writer).starts_with("<155>1 "));
+    }
+
+    #[test]
+    fn timestamp_is_rfc_3339() {
+        let make_writer = MockMakeWriter::default();
+        let collector = collector().with_writer(make_writer.clone()).finish();
+
+        with_default(collector, || {
+            tracing::info!("timestamped");
+        });
+
+        let line = last_line(&make_writer);
+        let timestamp = line
+            .split(' ')
+            .nth(1)
+            .expect("the message must have a timestamp part");
+        assert!(
+            timestamp.contains('T') && timestamp.ends_with('Z'),
+            "expected an RFC 3339 timestamp, got: {:?}",
+            timestamp
+        );
+    }
+
+    #[test]
+    fn empty_structured_data_is_nil() {
+        let make_writer = MockMakeWriter::default();
+        let collector = collector()
+            .with_target(false)
+            .without_time()
+            .with_writer(make_writer.clone())
+            .finish();
+
+        with_default(collector, || {
+            tracing::info!("bare");
+        });
+
+        let line = last_line(&make_writer);
+        assert!(
+            line.ends_with(" - bare"),
+            "expected a nil structured-data part, got: {:?}",
+            line
+        );
+    }
+
+    #[test]
+    fn sd_values_are_escaped() {
+        let make_writer = MockMakeWriter::default();
+        let collector = collector()
+            .with_target(false)
+            .without_time()
+            .with_writer(make_writer.clone())
+            .finish();
+
+        with_default(collector, || {
+            tracing::info!(tricky = r#"a "quoted] \ value"#, "escaped");
+        });
+
+        let line = last_line(&make_writer);
+        assert!(
+            line.contains(r#"tricky="a \"quoted\] \\ value""#),
+            "expected escaped structured-data value, got: {:?}",
+            line
+        );
+    }
+
+    #[test]
+    fn rfc_3164() {
+        let make_writer = MockMakeWriter::default();
+        let collector = collector()
+            .with_target(false)
+            .rfc_3164()
+            .with_writer(make_writer.clone())
+            .finish();
+
+        with_default(collector, || {
+            tracing::warn!(key = "value", "old school");
+        });
+
+        let line = last_line(&make_writer);
+        let pattern = regex::Regex::new(
+            "^<12>[A-Z][a-z]{2} [ 1-9][0-9]? [0-9]{2}:[0-9]{2}:[0-9]{2} \
+             testhost testapp\\[[0-9]+\\]: old school key=value$",
+        )
+        .unwrap();
+        assert!(pattern.is_match(&line), "unexpected message: {:?}", line);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_writer_sends_a_datagram() {
+        let path =
+            std::env::temp_dir().join(format!("tracing-syslog-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let receiver = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let writer = SyslogUnixWriter::connect(&path).unwrap();
+        let collector = collector().without_time().with_writer(writer).finish();
+
+        with_default(collector, || {
+            tracing::info!("to the system logger");
+        });
+
+        let mut buf = [0u8; 2048];
+        let len = receiver.recv(&mut buf).unwrap();
+        let message = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(message.starts_with("<14>1 "));
+        assert!(message.ends_with("to the system logger"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn udp_writer_sends_a_datagram() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let writer = SyslogUdpWriter::connect(receiver.local_addr().unwrap()).unwrap();
+        let collector = collector().without_time().with_writer(writer).finish();
+
+        with_default(collector, || {
+            tracing::info!("over the wire");
+        });
+
+        let mut buf = [0u8; 2048];
+        let len = receiver.recv(&mut buf).unwrap();
+        let message = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(message.starts_with("<14>1 "));
+        assert!(message.ends_with("over the wire"));
+    }
+
+    #[test]
+    fn tcp_writer_octet_counts_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let writer = SyslogTcpWriter::connect(listener.local_addr().unwrap()).unwrap();
+        let collector = collector().without_time().with_writer(writer).finish();
+
+        with_default(collector, || {
+            tracing::info!("framed");
+        });
+
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut length = Vec::new();
+        let mut byte = [0u8; 1];
+        loop {
+            stream.read_exact(&mut byte).unwrap();
+            if byte[0] == b' ' {
+                break;
+            }
+            length.push(byte[0]);
+        }
+        let length: usize = std::str::from_utf8(&length).unwrap().parse().unwrap();
+        let mut frame = vec![0u8; length];
+        stream.read_exact(&mut frame).unwrap();
+        let message = std::str::from_utf8(&frame).unwrap();
+        assert!(message.starts_with("<14>1 "));
+        assert!(message.ends_with("framed"));
+        assert!(
+            !frame.contains(&b'\n'),
+            "the formatter's newline must not be framed"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use nu_ansi_term::{Color, Style};
+use tracing_core::Level;
+
+/// A color theme for [`fmt`] output.
+///
+/// A `Theme` determines the [`Style`] used for each part of a formatted
+/// event: the event's [`Level`], its target, field names, field values, and
+/// the names of the spans in its context. Styles are [`nu_ansi_term`]
+/// [`Style`]s, so 256-color palettes ([`Color::Fixed`]) and 24-bit truecolor
+/// ([`Color::Rgb`]) are supported, as well as attributes such as bold and
+/// italic.
+///
+/// A theme is installed by passing it to [`Subscriber::with_ansi`] or
+/// [`CollectorBuilder::with_ansi`], which continue to accept `bool`s:
+/// `with_ansi(true)` is equivalent to `with_ansi(Theme::default())`, and
+/// `with_ansi(false)` to `with_ansi(Theme::none())`.
+///
+/// # Examples
+///
+/// Overriding individual styles of the default theme:
+///
+/// ```
+/// use nu_ansi_term::{Color, Style};
+/// use tracing_subscriber::fmt::format::Theme;
+///
+/// let theme = Theme::default()
+///     .with_info(Color::Fixed(42))
+///     .with_error(Style::new().fg(Color::Rgb(255, 85, 85)).bold());
+///
+/// let collector = tracing_subscriber::fmt()
+///     .with_ansi(theme)
+///     .finish();
+/// ```
+///
+/// [`fmt`]: mod@crate::fmt
+/// [`Subscriber::with_ansi`]: crate::fmt::Subscriber::with_ansi
+/// [`CollectorBuilder::with_ansi`]: crate::fmt::CollectorBuilder::with_ansi
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    styled: bool,
+    trace: Style,
+    debug: Style,
+    info: Style,
+    warn: Style,
+    error: Style,
+    target: Style,
+    field_name: Style,
+    field_value: Style,
+    span_name: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            styled: true,
+            trace: Style::new().fg(Color::Purple),
+            debug: Style::new().fg(Color::Blue),
+            info: Style::new().fg(Color::Green),
+            warn: Style::new().fg(Color::Yellow),
+            error: Style::new().fg(Color::Red),
+            target: Style::new().dimmed(),
+            field_name: Style::new().italic(),
+            field_value: Style::new(),
+            span_name: Style::new().bold(),
+        }
+    }
+}
+
+impl Theme {
+    /// Returns the default theme, with the colors `fmt` output has always
+    /// used.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a theme that applies no styling at all, equivalent to
+    /// `with_ansi(false)`.
+    pub fn none() -> Self {
+        Self {
+            styled: false,
+            trace: Style::new(),
+            debug: Style::new(),
+            info: Style::new(),
+            warn: Style::new(),
+            error: Style::new(),
+            target: Style::new(),
+            field_name: Style::new(),
+            field_value: Style::new(),
+            span_name: Style::new(),
+        }
+    }
+
+    /// Returns a theme with brighter 256-color level colors, for dark
+    /// terminal backgrounds.
+    pub fn dark() -> Self {
+        Self {
+            trace: Style::new().fg(Color::Fixed(177)),
+            debug: Style::new().fg(Color::Fixed(75)),
+            info: Style::new().fg(Color::Fixed(114)),
+            warn: Style::new().fg(Color::Fixed(221)),
+            error: Style::new().fg(Color::Fixed(203)),
+            ..Self::default()
+        }
+    }
+
+    /// Returns a theme with darker 256-color level colors, for light
+    /// terminal backgrounds.
+    pub fn light() -> Self {
+        Self {
+            trace: Style::new().fg(Color::Fixed(90)),
+            debug: Style::new().fg(Color::Fixed(25)),
+            info: Style::new().fg(Color::Fixed(28)),
+            warn: Style::new().fg(Color::Fixed(130)),
+            error: Style::new().fg(Color::Fixed(124)),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the style used for the [`TRACE`](Level::TRACE) level.
+    pub fn with_trace(self, style: impl Into<Style>) -> Self {
+        Self {
+            trace: style.into(),
+            ..self
+        }
+    }
+
+    /// Sets the style used for the [`DEBUG`](Level::DEBUG) level.
+    pub fn with_debug(self, style: impl Into<Style>) -> Self {
+        Self {
+            debug: style.into(),
+            ..self
+        }
+    }
+
+    /// Sets the style used for the [`INFO`](Level::INFO) level.
+    pub fn with_info(self, style: impl Into<Style>) -> Self {
+        Self {
+            info: style.into(),
+            ..self
+        }
+    }
+
+    /// Sets the style used for the [`WARN`](Level::WARN) level.
+    pub fn with_warn(self, style: impl Into<Style>) -> Self {
+        Self {
+            warn: style.into(),
+            ..self
+        }
+    }
+
+    /// Sets the style used for the [`ERROR`](Level::ERROR) level.
+    pub fn with_error(self, style: impl Into<Style>) -> Self {
+        Self {
+            error: style.into(),
+            ..self
+        }
+    }
+
+    /// Sets the style used for event targets.
+    pub fn with_target(self, style: impl Into<Style>) -> Self {
+        Self {
+            target: style.into(),
+            ..self
+        }
+    }
+
+    /// Sets the style used for field names.
+    pub fn with_field_name(self, style: impl Into<Style>) -> Self {
+        Self {
+            field_name: style.into(),
+            ..self
+        }
+    }
+
+    /// Sets the style used for field values.
+    pub fn with_field_value(self, style: impl Into<Style>) -> Self {
+        Self {
+            field_value: style.into(),
+            ..self
+        }
+    }
+
+    /// Sets the style used for span names.
+    pub fn with_span_name(self, style: impl Into<Style>) -> Self {
+        Self {
+            span_name: style.into(),
+            ..self
+        }
+    }
+
+    /// Returns whether this theme applies any styling at all.
+    pub(in crate::fmt) fn styled(&self) -> bool {
+        self.styled
+    }
+
+    /// Returns the style used for the given level.
+    pub(in crate::fmt) fn level(&self, level: Level) -> Style {
+        match level {
+            Level::TRACE => self.trace,
+            Level::DEBUG => self.debug,
+            Level::INFO => self.info,
+            Level::WARN => self.warn,
+            Level::ERROR => self.error,
+        }
+    }
+
+    pub(in crate::fmt) fn target(&self) -> Style {
+        self.target
+    }
+
+    pub(in crate::fmt) fn field_name(&self) -> Style {
+        self.field_name
+    }
+
+    pub(in crate::fmt) fn field_value(&self) -> Style {
+        self.field_value
+    }
+
+    pub(in crate::fmt) fn span_name(&self) -> Style {
+        self.span_name
+    }
+}
+
+impl From<bool> for Theme {
+    fn from(ansi: bool) -> Self {
+        if ansi {
+            Self::default()
+        } else {
+            Self::none()
+        }
+    }
+}
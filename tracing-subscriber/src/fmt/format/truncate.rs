@@ -0,0 +1,326 @@
+/// Rules for limiting the length of formatted output.
+///
+/// A single oversized field — a multi-megabyte `Debug` representation, say —
+/// can render terminal output unusable and overwhelm log pipelines built
+/// around line-sized records. `TruncationRules` bound the damage by limiting
+/// the length of individual field values, the length of whole formatted
+/// lines, or both.
+///
+/// Truncated values and lines end with an `… (+N bytes)` marker recording how
+/// many bytes were dropped. Alternatively, [`fold_lines`] replaces line
+/// truncation with folding: overlong lines are split into indented
+/// continuation lines instead of losing data.
+///
+/// Rules are installed with [`Subscriber::with_truncation`] or
+/// [`CollectorBuilder::with_truncation`]. Value limits apply to the
+/// [`DefaultFields`] and [`JsonFields`] field formatters; line limits apply
+/// to each formatted event as a whole, regardless of the event formatter.
+///
+/// All lengths are measured in bytes of formatted output, including any ANSI
+/// escape sequences, so line limits are best combined with
+/// [`Subscriber::with_ansi(false)`]. Note also that folding or truncating
+/// whole lines of JSON output produces lines that are no longer valid JSON;
+/// for JSON output, prefer limiting field values only.
+///
+/// # Examples
+///
+/// ```
+/// use tracing_subscriber::fmt::format::TruncationRules;
+///
+/// let rules = TruncationRules::new()
+///     // keep at most 256 bytes of any single field value...
+///     .max_value_length(256)
+///     // ...and fold lines longer than the terminal instead of truncating.
+///     .max_line_length(120)
+///     .fold_lines();
+///
+/// let collector = tracing_subscriber::fmt()
+///     .with_truncation(rules)
+///     .finish();
+/// ```
+///
+/// [`fold_lines`]: TruncationRules::fold_lines
+/// [`Subscriber::with_truncation`]: crate::fmt::Subscriber::with_truncation
+/// [`CollectorBuilder::with_truncation`]: crate::fmt::CollectorBuilder::with_truncation
+/// [`Subscriber::with_ansi(false)`]: crate::fmt::Subscriber::with_ansi
+/// [`DefaultFields`]: super::DefaultFields
+/// [`JsonFields`]: super::JsonFields
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TruncationRules {
+    max_value_length: Option<usize>,
+    max_line_length: Option<usize>,
+    fold: bool,
+}
+
+/// The indentation prepended to continuation lines when folding.
+const FOLD_INDENT: &str = "    ";
+
+impl TruncationRules {
+    /// Returns a new, empty set of truncation rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits each formatted field value to at most `max` bytes.
+    ///
+    /// Values longer than `max` are cut at a character boundary and suffixed
+    /// with `… (+N bytes)`, where `N` is the number of bytes removed.
+    pub fn max_value_length(mut self, max: usize) -> Self {
+        self.max_value_length = Some(max);
+        self
+    }
+
+    /// Limits each formatted event to at most `max` bytes per line.
+    ///
+    /// By default, longer lines are cut at a character boundary and suffixed
+    /// with `… (+N bytes)`; [`fold_lines`](TruncationRules::fold_lines) wraps
+    /// them onto continuation lines instead.
+    pub fn max_line_length(mut self, max: usize) -> Self {
+        self.max_line_length = Some(max);
+        self
+    }
+
+    /// Folds lines longer than the [maximum line length] onto indented
+    /// continuation lines, instead of truncating them.
+    ///
+    /// [maximum line length]: TruncationRules::max_line_length
+    pub fn fold_lines(mut self) -> Self {
+        self.fold = true;
+        self
+    }
+
+    /// Returns whether a maximum field value length has been configured.
+    pub(crate) fn limits_values(&self) -> bool {
+        self.max_value_length.is_some()
+    }
+
+    /// Returns the truncated form of `value`, or `None` if it is within the
+    /// maximum value length (or no maximum is configured).
+    pub(crate) fn truncate_value(&self, value: &str) -> Option<String> {
+        let max = self.max_value_length?;
+        if value.len() <= max {
+            return None;
+        }
+        let keep = floor_char_boundary(value, max);
+        Some(format!(
+            "{}… (+{} bytes)",
+            &value[..keep],
+            value.len() - keep
+        ))
+    }
+
+    /// Truncates or folds `line` in place, if it exceeds the maximum line
+    /// length.
+    ///
+    /// The line's trailing newline (if any) is preserved and not counted
+    /// against the limit.
+    pub(crate) fn apply_to_line(&self, line: &mut String) {
+        let max = match self.max_line_length {
+            Some(max) if max > 0 => max,
+            _ => return,
+        };
+        let content_len = line.strip_suffix('\n').map(str::len).unwrap_or(line.len());
+        if content_len <= max {
+            return;
+        }
+
+        let had_newline = content_len < line.len();
+        let mut content = std::mem::take(line);
+        content.truncate(content_len);
+
+        if self.fold {
+            let mut rest = content.as_str();
+            loop {
+                let mut keep = floor_char_boundary(rest, max);
+                if keep == 0 {
+                    // the limit is smaller than the next character; keep the
+                    // whole character anyway so that every iteration makes
+                    // progress, rather than folding forever.
+                    keep = rest.chars().next().map(char::len_utf8).unwrap_or(0);
+                }
+                line.push_str(&rest[..keep]);
+                rest = &rest[keep..];
+                if rest.is_empty() {
+                    break;
+                }
+                line.push('\n');
+                line.push_str(FOLD_INDENT);
+            }
+        } else {
+            let keep = floor_char_boundary(&content, max);
+            line.push_str(&content[..keep]);
+            line.push_str(&format!("… (+{} bytes)", content.len() - keep));
+        }
+
+        if had_newline {
+            line.push('\n');
+        }
+    }
+}
+
+/// Returns the largest index no greater than `index` that lies on a `char`
+/// boundary of `s`.
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    let mut index = index;
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fmt::{format::FmtSpan, test::MockMakeWriter};
+    use tracing::collect::with_default;
+
+    #[test]
+    fn truncates_long_values() {
+        let rules = TruncationRules::new().max_value_length(8);
+        assert_eq!(rules.truncate_value("short"), None);
+        assert_eq!(
+            rules.truncate_value("this value is too long").as_deref(),
+            Some("this val… (+14 bytes)")
+        );
+    }
+
+    #[test]
+    fn truncation_respects_char_boundaries() {
+        let rules = TruncationRules::new().max_value_length(5);
+        // `ä` is two bytes, so cutting at 5 bytes would split it.
+        assert_eq!(
+            rules.truncate_value("aaaaää").as_deref(),
+            Some("aaaa… (+4 bytes)")
+        );
+    }
+
+    #[test]
+    fn truncates_long_lines() {
+        let rules = TruncationRules::new().max_line_length(10);
+        let mut line = String::from("a line that is far too long\n");
+        rules.apply_to_line(&mut line);
+        assert_eq!(line, "a line tha… (+17 bytes)\n");
+
+        let mut short = String::from("short\n");
+        rules.apply_to_line(&mut short);
+        assert_eq!(short, "short\n");
+    }
+
+    #[test]
+    fn folds_long_lines() {
+        let rules = TruncationRules::new().max_line_length(10).fold_lines();
+        let mut line = String::from("a line that is far too long\n");
+        rules.apply_to_line(&mut line);
+        assert_eq!(line, "a line tha\n    t is far t\n    oo long\n");
+    }
+
+    #[test]
+    fn folds_lines_narrower_than_one_char() {
+        // `ä` is two bytes, wider than the one-byte limit; folding must still
+        // terminate, keeping one character per line.
+        let rules = TruncationRules::new().max_line_length(1).fold_lines();
+        let mut line = String::from("äb\n");
+        rules.apply_to_line(&mut line);
+        assert_eq!(line, "ä\n    b\n");
+    }
+
+    #[test]
+    fn truncates_event_field_values() {
+        let rules = TruncationRules::new().max_value_length(8);
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_level(false)
+            .with_ansi(false)
+            .without_time()
+            .with_truncation(rules)
+            .finish();
+
+        with_default(collector, || {
+            tracing::info!(value = "a value that is rather too long", "short");
+        });
+
+        let actual = make_writer.get_string();
+        assert_eq!(
+            actual.as_str(),
+            "tracing_subscriber::fmt::format::truncate::test: short value=\"a value… (+25 bytes)\n"
+        );
+    }
+
+    #[test]
+    fn truncates_span_field_values() {
+        let rules = TruncationRules::new().max_value_length(8);
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_level(false)
+            .with_ansi(false)
+            .without_time()
+            .with_span_events(FmtSpan::NEW)
+            .with_truncation(rules)
+            .finish();
+
+        with_default(collector, || {
+            tracing::info_span!("span", value = "a value that is rather too long");
+        });
+
+        let actual = make_writer.get_string();
+        assert!(
+            actual.contains("value=\"a value… (+25 bytes)"),
+            "{:?}",
+            actual
+        );
+    }
+
+    #[test]
+    fn truncates_whole_lines() {
+        let rules = TruncationRules::new().max_line_length(32);
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::fmt::Collector::builder()
+            .with_writer(make_writer.clone())
+            .with_level(false)
+            .with_ansi(false)
+            .without_time()
+            .with_truncation(rules)
+            .finish();
+
+        with_default(collector, || {
+            tracing::info!("a message that overflows the configured line length");
+        });
+
+        let actual = make_writer.get_string();
+        assert_eq!(
+            actual.as_str(),
+            "tracing_subscriber::fmt::format:… (+68 bytes)\n"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn truncates_json_field_values() {
+        let rules = TruncationRules::new().max_value_length(8);
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::fmt::Collector::builder()
+            .json()
+            .with_writer(make_writer.clone())
+            .without_time()
+            .with_truncation(rules)
+            .finish();
+
+        with_default(collector, || {
+            let span = tracing::info_span!("span", span_value = "a span value that is too long");
+            let _entered = span.enter();
+            tracing::info!(value = "a value that is rather too long", "short");
+        });
+
+        let buf = make_writer.get_string();
+        let json: serde_json::Value =
+            serde_json::from_str(buf.lines().next().expect("a line should be written"))
+                .expect("output should be valid JSON");
+        assert_eq!(json["fields"]["value"], "a value … (+23 bytes)");
+        assert_eq!(json["span"]["span_value"], "a span v… (+21 bytes)");
+    }
+}
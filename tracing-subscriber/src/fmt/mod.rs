@@ -610,6 +610,24 @@ where
         }
     }
 
+    /// Sets whether or not the formatter emits ANSI terminal escape codes
+    /// for colors and other text formatting.
+    ///
+    /// This method accepts either a `bool` or a [`Theme`](format::Theme)
+    /// describing the colors and styles to use: `with_ansi(true)` is
+    /// equivalent to passing [`Theme::default`](format::Theme::default), and
+    /// `with_ansi(false)` to passing [`Theme::none`](format::Theme::none).
+    #[cfg(feature = "ansi")]
+    pub fn with_ansi(
+        self,
+        ansi: impl Into<format::Theme>,
+    ) -> CollectorBuilder<N, format::Format<L, T>, F, W> {
+        CollectorBuilder {
+            inner: self.inner.with_ansi(ansi),
+            ..self
+        }
+    }
+
     /// Sets whether or not the formatter emits ANSI terminal escape codes
     /// for colors and other text formatting.
     ///
@@ -625,6 +643,7 @@ where
     /// ANSI escape codes can ensure that they are not used, regardless of
     /// whether or not other crates in the dependency graph enable the "ansi"
     /// feature flag.
+    #[cfg(not(feature = "ansi"))]
     pub fn with_ansi(self, ansi: bool) -> CollectorBuilder<N, format::Format<L, T>, F, W> {
         CollectorBuilder {
             inner: self.inner.with_ansi(ansi),
@@ -692,6 +711,40 @@ where
         }
     }
 
+    /// Sets whether or not displayed source code locations are wrapped in
+    /// [OSC 8] terminal hyperlinks, if the terminal is detected to support
+    /// them.
+    ///
+    /// See [`format::Format::with_hyperlinks`] for details.
+    ///
+    /// [OSC 8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+    pub fn with_hyperlinks(
+        self,
+        display_hyperlinks: bool,
+    ) -> CollectorBuilder<N, format::Format<L, T>, F, W> {
+        CollectorBuilder {
+            inner: self.inner.with_hyperlinks(display_hyperlinks),
+            ..self
+        }
+    }
+
+    /// Wraps displayed source code locations in [OSC 8] terminal hyperlinks
+    /// pointing at the provided URL template, such as
+    /// `"vscode://file/{path}:{line}"`.
+    ///
+    /// See [`format::Format::with_hyperlink_template`] for details.
+    ///
+    /// [OSC 8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+    pub fn with_hyperlink_template(
+        self,
+        template: impl Into<String>,
+    ) -> CollectorBuilder<N, format::Format<L, T>, F, W> {
+        CollectorBuilder {
+            inner: self.inner.with_hyperlink_template(template),
+            ..self
+        }
+    }
+
     /// Sets whether or not an event's level is displayed.
     pub fn with_level(
         self,
@@ -731,6 +784,47 @@ where
         }
     }
 
+    /// Sets whether or not the current [process ID] is displayed when
+    /// formatting events.
+    ///
+    /// [process ID]: std::process::id
+    pub fn with_process_id(
+        self,
+        display_process_id: bool,
+    ) -> CollectorBuilder<N, format::Format<L, T>, F, W> {
+        CollectorBuilder {
+            inner: self.inner.with_process_id(display_process_id),
+            ..self
+        }
+    }
+
+    /// Sets a hostname that is included in every formatted event.
+    pub fn with_hostname(
+        self,
+        hostname: impl Into<String>,
+    ) -> CollectorBuilder<N, format::Format<L, T>, F, W> {
+        CollectorBuilder {
+            inner: self.inner.with_hostname(hostname),
+            ..self
+        }
+    }
+
+    /// Sets static resource attributes, such as a `service.name` or
+    /// `service.version`, that are included in every formatted event.
+    pub fn with_resource<K, V>(
+        self,
+        resource: impl IntoIterator<Item = (K, V)>,
+    ) -> CollectorBuilder<N, format::Format<L, T>, F, W>
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        CollectorBuilder {
+            inner: self.inner.with_resource(resource),
+            ..self
+        }
+    }
+
     /// Sets the collector being built to use a less verbose formatter.
     ///
     /// See [`format::Compact`] for details.
@@ -744,6 +838,19 @@ where
         }
     }
 
+    /// Sets the collector being built to use a column-aligned formatter.
+    ///
+    /// See [`format::Columns`] for details.
+    pub fn columns(self) -> CollectorBuilder<N, format::Format<format::Columns, T>, F, W>
+    where
+        N: for<'writer> FormatFields<'writer> + 'static,
+    {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.columns(),
+        }
+    }
+
     /// Sets the collector being built to use an [excessively pretty, human-readable formatter](crate::fmt::format::Pretty).
     #[cfg(feature = "ansi")]
     #[cfg_attr(docsrs, doc(cfg(feature = "ansi")))]
@@ -770,6 +877,259 @@ where
             inner: self.inner.json(),
         }
     }
+
+    /// Sets the collector being built to use a GELF formatter, for shipping
+    /// logs to a Graylog input.
+    ///
+    /// See [`format::Gelf`] for details.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn gelf(self) -> CollectorBuilder<format::JsonFields, format::Format<format::Gelf, T>, F, W>
+    where
+        N: for<'writer> FormatFields<'writer> + 'static,
+    {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.gelf(),
+        }
+    }
+
+    /// Sets the collector being built to use a syslog formatter, for
+    /// shipping logs to a syslog collector.
+    ///
+    /// See [`format::Syslog`] for details.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn syslog(
+        self,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Syslog, T>, F, W>
+    where
+        N: for<'writer> FormatFields<'writer> + 'static,
+    {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.syslog(),
+        }
+    }
+
+    /// Sets the collector being built to use a CSV formatter.
+    ///
+    /// See [`format::Csv`] for details.
+    pub fn csv(self) -> CollectorBuilder<N, format::Format<format::Csv, T>, F, W>
+    where
+        N: for<'writer> FormatFields<'writer> + 'static,
+    {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.csv(),
+        }
+    }
+
+    /// Sets the collector being built to use a logfmt formatter.
+    ///
+    /// See [`format::Logfmt`] for details.
+    pub fn logfmt(
+        self,
+    ) -> CollectorBuilder<format::LogfmtFields, format::Format<format::Logfmt, T>, F, W>
+    where
+        N: for<'writer> FormatFields<'writer> + 'static,
+    {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.logfmt(),
+        }
+    }
+}
+
+impl<N, T, F, W> CollectorBuilder<N, format::Format<format::Columns, T>, F, W> {
+    /// Sets the width of the target column, or disables target alignment
+    /// when `None`.
+    ///
+    /// See [`format::Columns`] for details.
+    pub fn with_target_width(
+        self,
+        width: Option<usize>,
+    ) -> CollectorBuilder<N, format::Format<format::Columns, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_target_width(width),
+        }
+    }
+
+    /// Sets the width of the message column, or disables message alignment
+    /// when `None`.
+    ///
+    /// See [`format::Columns`] for details.
+    pub fn with_message_width(
+        self,
+        width: Option<usize>,
+    ) -> CollectorBuilder<N, format::Format<format::Columns, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_message_width(width),
+        }
+    }
+
+    /// Sets whether messages longer than the message column are truncated,
+    /// rather than overflowing it.
+    ///
+    /// See [`format::Columns`] for details.
+    pub fn with_truncate_message(
+        self,
+        truncate_message: bool,
+    ) -> CollectorBuilder<N, format::Format<format::Columns, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_truncate_message(truncate_message),
+        }
+    }
+}
+
+impl<N, T, F, W> CollectorBuilder<N, format::Format<format::Csv, T>, F, W> {
+    /// Declares the set of columns written for each event, replacing the
+    /// default column set.
+    ///
+    /// See [`format::Csv`] for details.
+    pub fn with_columns(
+        self,
+        columns: impl IntoIterator<Item = format::CsvColumn>,
+    ) -> CollectorBuilder<N, format::Format<format::Csv, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_columns(columns),
+        }
+    }
+
+    /// Sets the delimiter written between cells.
+    ///
+    /// See [`format::Csv`] for details.
+    pub fn with_delimiter(
+        self,
+        delimiter: char,
+    ) -> CollectorBuilder<N, format::Format<format::Csv, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_delimiter(delimiter),
+        }
+    }
+
+    /// Sets whether a header row naming the columns is written before the
+    /// first record.
+    ///
+    /// See [`format::Csv`] for details.
+    pub fn with_header(
+        self,
+        header: bool,
+    ) -> CollectorBuilder<N, format::Format<format::Csv, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_header(header),
+        }
+    }
+
+    /// Appends a trailing column collecting unclaimed event fields as a JSON
+    /// object.
+    ///
+    /// See [`format::Csv`] for details.
+    pub fn with_extra_fields_column(
+        self,
+        name: impl Into<String>,
+    ) -> CollectorBuilder<N, format::Format<format::Csv, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_extra_fields_column(name),
+        }
+    }
+}
+
+impl<T, F, W> CollectorBuilder<format::LogfmtFields, format::Format<format::Logfmt, T>, F, W> {
+    /// Sets a fixed prefix for flattened span field keys, replacing the
+    /// default span-name prefix.
+    ///
+    /// See [`format::Logfmt`] for details.
+    pub fn with_span_field_prefix(
+        self,
+        prefix: impl Into<String>,
+    ) -> CollectorBuilder<format::LogfmtFields, format::Format<format::Logfmt, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_span_field_prefix(prefix),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+impl<T, F, W> CollectorBuilder<format::JsonFields, format::Format<format::Gelf, T>, F, W> {
+    /// Sets the value of the `host` key included in every GELF payload.
+    ///
+    /// See [`format::Gelf`] for details.
+    pub fn with_host(
+        self,
+        host: impl Into<String>,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Gelf, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_host(host),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+impl<T, F, W> CollectorBuilder<format::JsonFields, format::Format<format::Syslog, T>, F, W> {
+    /// Sets the facility used to compute each syslog message's `PRI`.
+    ///
+    /// See [`format::Syslog`] for details.
+    pub fn with_facility(
+        self,
+        facility: format::Facility,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Syslog, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_facility(facility),
+        }
+    }
+
+    /// Sets the value of the `APP-NAME` part included in every syslog
+    /// message.
+    ///
+    /// See [`format::Syslog`] for details.
+    pub fn with_app_name(
+        self,
+        app_name: impl Into<String>,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Syslog, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_app_name(app_name),
+        }
+    }
+
+    /// Sets the `SD-ID` of the structured-data element carrying each event's
+    /// fields.
+    ///
+    /// See [`format::Syslog`] for details.
+    pub fn with_sd_id(
+        self,
+        sd_id: impl Into<String>,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Syslog, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_sd_id(sd_id),
+        }
+    }
+
+    /// Switches to the legacy RFC 3164 ("BSD syslog") message format.
+    ///
+    /// See [`format::Syslog`] for details.
+    pub fn rfc_3164(
+        self,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Syslog, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.rfc_3164(),
+        }
+    }
 }
 
 #[cfg(feature = "json")]
@@ -815,6 +1175,199 @@ impl<T, F, W> CollectorBuilder<format::JsonFields, format::Format<format::Json,
             inner: self.inner.with_span_list(display_span_list),
         }
     }
+
+    /// If set to `true`, the timestamp is emitted as fractional seconds
+    /// since the Unix epoch, as a JSON number, instead of the string
+    /// rendered by the configured timer.
+    ///
+    /// See [`format::Json`] for details.
+    pub fn with_unix_timestamp(
+        self,
+        unix_timestamp: bool,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Json, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_unix_timestamp(unix_timestamp),
+        }
+    }
+
+    /// Sets the key under which the timestamp is emitted.
+    ///
+    /// See [`format::Json`] for details.
+    pub fn with_timestamp_key(
+        self,
+        timestamp_key: &'static str,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Json, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_timestamp_key(timestamp_key),
+        }
+    }
+
+    /// Sets the key under which the event's level is emitted.
+    ///
+    /// See [`format::Json`] for details.
+    pub fn with_level_key(
+        self,
+        level_key: &'static str,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Json, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_level_key(level_key),
+        }
+    }
+
+    /// Sets the key under which the event's target is emitted.
+    ///
+    /// See [`format::Json`] for details.
+    pub fn with_target_key(
+        self,
+        target_key: &'static str,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Json, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_target_key(target_key),
+        }
+    }
+
+    /// Adds a constant field emitted in every record, such as a service
+    /// name, version, or host.
+    ///
+    /// See [`format::Json`] for details.
+    pub fn with_constant_field(
+        self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Json, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_constant_field(key, value),
+        }
+    }
+
+    /// Sets whether the span list is emitted as a single object keyed by span
+    /// name, with typed field values nested under each name, rather than as
+    /// an array of objects.
+    ///
+    /// See [`format::Json`] for details.
+    pub fn with_nested_spans(
+        self,
+        nested_spans: bool,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Json, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_nested_spans(nested_spans),
+        }
+    }
+
+    /// Sets the key under which the event's fields are emitted.
+    ///
+    /// See [`format::Json`] for details.
+    pub fn with_fields_key(
+        self,
+        fields_key: &'static str,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Json, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_fields_key(fields_key),
+        }
+    }
+
+    /// Sets the key under which the current span is emitted.
+    ///
+    /// See [`format::Json`] for details.
+    pub fn with_current_span_key(
+        self,
+        current_span_key: &'static str,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Json, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_current_span_key(current_span_key),
+        }
+    }
+
+    /// Sets the key under which the span list is emitted.
+    ///
+    /// See [`format::Json`] for details.
+    pub fn with_spans_key(
+        self,
+        spans_key: &'static str,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Json, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_spans_key(spans_key),
+        }
+    }
+
+    /// Masks fields matching the provided [`RedactionRules`] before they are
+    /// serialized.
+    ///
+    /// See [`format::RedactionRules`] for details on configuring which
+    /// fields and values are masked.
+    ///
+    /// [`RedactionRules`]: format::RedactionRules
+    pub fn with_field_redaction(
+        self,
+        rules: format::RedactionRules,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Json, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_field_redaction(rules),
+        }
+    }
+
+    /// Truncates field values and lines according to the provided
+    /// [`TruncationRules`].
+    ///
+    /// Note that whole-line limits applied to JSON output produce lines that
+    /// are no longer valid JSON; see [`format::TruncationRules`] for details.
+    ///
+    /// [`TruncationRules`]: format::TruncationRules
+    pub fn with_truncation(
+        self,
+        rules: format::TruncationRules,
+    ) -> CollectorBuilder<format::JsonFields, format::Format<format::Json, T>, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_truncation(rules),
+        }
+    }
+}
+
+impl<E, F, W> CollectorBuilder<format::DefaultFields, E, F, W> {
+    /// Masks fields matching the provided [`RedactionRules`] before they are
+    /// written.
+    ///
+    /// See [`format::RedactionRules`] for details on configuring which
+    /// fields and values are masked.
+    ///
+    /// [`RedactionRules`]: format::RedactionRules
+    pub fn with_field_redaction(
+        self,
+        rules: format::RedactionRules,
+    ) -> CollectorBuilder<format::DefaultFields, E, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_field_redaction(rules),
+        }
+    }
+
+    /// Truncates field values and lines according to the provided
+    /// [`TruncationRules`].
+    ///
+    /// See [`format::TruncationRules`] for details on configuring value and
+    /// line limits.
+    ///
+    /// [`TruncationRules`]: format::TruncationRules
+    pub fn with_truncation(
+        self,
+        rules: format::TruncationRules,
+    ) -> CollectorBuilder<format::DefaultFields, E, F, W> {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_truncation(rules),
+        }
+    }
 }
 
 impl<N, E, F, W> CollectorBuilder<N, E, reload::Subscriber<F>, W>
@@ -1120,6 +1673,85 @@ impl<N, E, F, W> CollectorBuilder<N, E, F, W> {
         }
     }
 
+    /// Formats events from the given target (and its submodules) with a
+    /// different [event formatter][`FormatEvent`], keeping the current
+    /// formatter for all other events.
+    ///
+    /// Calls may be chained to override several targets. See
+    /// [`format::TargetFormat`] for details on how targets are matched.
+    ///
+    /// # Examples
+    ///
+    /// Rendering a noisy dependency's events compactly while other events
+    /// use the default format:
+    ///
+    /// ```rust
+    /// use tracing_subscriber::fmt::format;
+    ///
+    /// let subscriber = tracing_subscriber::fmt()
+    ///     .with_format_for_target("sqlx", format().compact())
+    ///     .finish();
+    /// ```
+    ///
+    /// [`FormatEvent`]: format::FormatEvent
+    pub fn with_format_for_target<E2>(
+        self,
+        target: impl Into<String>,
+        format: E2,
+    ) -> CollectorBuilder<N, format::TargetFormat<E2, E>, F, W>
+    where
+        E2: FormatEvent<Registry, N> + 'static,
+        N: for<'writer> FormatFields<'writer> + 'static,
+        W: for<'writer> MakeWriter<'writer> + 'static,
+    {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_format_for_target(target, format),
+        }
+    }
+
+    /// Additionally writes every event, formatted with the given
+    /// [event formatter][`FormatEvent`], to the given [`MakeWriter`].
+    ///
+    /// Unlike [`MakeWriterExt::and`], which duplicates identical bytes to
+    /// both writers, each configured (format, writer) pair formats the event
+    /// independently. Calls may be chained to configure several additional
+    /// outputs. See [`format::TeeFormat`] for details.
+    ///
+    /// # Examples
+    ///
+    /// Emitting human-readable output on stderr and newline-delimited JSON
+    /// to stdout:
+    ///
+    /// ```rust
+    /// use tracing_subscriber::fmt::format;
+    ///
+    /// let collector = tracing_subscriber::fmt()
+    ///     .with_writer(std::io::stderr)
+    ///     .with_tee(format().json(), std::io::stdout)
+    ///     .finish();
+    /// ```
+    ///
+    /// [`FormatEvent`]: format::FormatEvent
+    /// [`MakeWriter`]: writer::MakeWriter
+    /// [`MakeWriterExt::and`]: writer::MakeWriterExt::and
+    pub fn with_tee<E2, W2>(
+        self,
+        format: E2,
+        make_writer: W2,
+    ) -> CollectorBuilder<N, format::TeeFormat<E2, W2, E>, F, W>
+    where
+        E2: FormatEvent<Registry, N> + 'static,
+        W2: for<'writer> MakeWriter<'writer> + 'static,
+        N: for<'writer> FormatFields<'writer> + 'static,
+        W: for<'writer> MakeWriter<'writer> + 'static,
+    {
+        CollectorBuilder {
+            filter: self.filter,
+            inner: self.inner.with_tee(format, make_writer),
+        }
+    }
+
     /// Updates the field formatter by applying a function to the existing field formatter.
     ///
     /// This sets the field formatter that the subscriber being built will use to record fields.
@@ -1230,7 +1862,7 @@ pub fn init() {
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     use crate::{
         filter::LevelFilter,
         fmt::{
@@ -3,13 +3,6 @@ use crate::fmt::time::FormatTime;
 
 use std::sync::Arc;
 
-/// Formats [local time]s and [UTC time]s with `FormatTime` implementations
-/// that use the [`chrono` crate].
-///
-/// [local time]: [`chrono::offset::Local`]
-/// [UTC time]: [`chrono::offset::Utc`]
-/// [`chrono` crate]: [`chrono`]
-
 /// Formats the current [local time] using a [formatter] from the [`chrono`] crate.
 ///
 /// [local time]: chrono::Local::now()
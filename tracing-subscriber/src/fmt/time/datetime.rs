@@ -219,6 +219,29 @@ pub(crate) struct DateTime {
     nanos: u32,
 }
 
+#[cfg(feature = "json")]
+impl DateTime {
+    pub(in crate::fmt) fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub(in crate::fmt) fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub(in crate::fmt) fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub(in crate::fmt) fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    pub(in crate::fmt) fn second(&self) -> u8 {
+        self.second
+    }
+}
+
 impl fmt::Display for DateTime {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.year > 9999 {
@@ -3,7 +3,7 @@ use crate::fmt::format::Writer;
 use std::fmt;
 use std::time::Instant;
 
-mod datetime;
+pub(in crate::fmt) mod datetime;
 
 #[cfg(feature = "time")]
 mod time_crate;
@@ -16,6 +16,10 @@ pub use time_crate::UtcTime;
 #[cfg_attr(docsrs, doc(cfg(all(unsound_local_offset, feature = "local-time"))))]
 pub use time_crate::LocalTime;
 
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+pub use time_crate::OffsetTime;
+
 /// [`chrono`]-based implementation for [`FormatTime`].
 #[cfg(feature = "chrono")]
 mod chrono_crate;
@@ -1,6 +1,10 @@
 use crate::fmt::{format::Writer, time::FormatTime, writer::WriteAdaptor};
 use std::fmt;
-use time::{format_description::well_known, formatting::Formattable, OffsetDateTime};
+use time::{
+    format_description::{modifier, well_known, Component, FormatItem},
+    formatting::Formattable,
+    OffsetDateTime, UtcOffset,
+};
 
 /// Formats the current [local time] using a [formatter] from the [`time` crate].
 ///
@@ -45,6 +49,25 @@ pub struct UtcTime<F> {
     format: F,
 }
 
+/// Formats the current time at a fixed [UTC offset], using a [formatter] from
+/// the [`time` crate].
+///
+/// Unlike [`LocalTime`], the offset is provided explicitly — or captured once
+/// at startup by [`OffsetTime::local_rfc_3339`] — so formatting a timestamp
+/// never needs to determine the local offset. This makes `OffsetTime` sound
+/// to use in multi-threaded programs, where determining the local offset on
+/// every event is not.
+///
+/// [UTC offset]: https://docs.rs/time/0.3/time/struct.UtcOffset.html
+/// [formatter]: https://docs.rs/time/0.3/time/formatting/trait.Formattable.html
+/// [`time` crate]: https://docs.rs/time/0.3/time/
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+#[derive(Clone, Debug)]
+pub struct OffsetTime<F> {
+    offset: UtcOffset,
+    format: F,
+}
+
 // === impl LocalTime ===
 
 #[cfg(feature = "local-time")]
@@ -291,6 +314,204 @@ where
     }
 }
 
+// === impl OffsetTime ===
+
+impl OffsetTime<well_known::Rfc3339> {
+    /// Returns a formatter that formats the current time in the [RFC 3339]
+    /// format (a subset of the [ISO 8601] timestamp format), using the local
+    /// UTC offset as determined when this method is called.
+    ///
+    /// Because the offset is captured once and reused, this is sound to use
+    /// even after other threads have been spawned, unlike [`LocalTime`],
+    /// which determines the local offset on every call.
+    ///
+    /// Returns an error if the local offset could not be determined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tracing_subscriber::fmt::{self, time::OffsetTime};
+    ///
+    /// let timer = OffsetTime::local_rfc_3339().expect("could not get local offset!");
+    /// let collector = tracing_subscriber::fmt()
+    ///     .with_timer(timer);
+    /// # drop(collector);
+    /// ```
+    ///
+    /// [RFC 3339]: https://datatracker.ietf.org/doc/html/rfc3339
+    /// [ISO 8601]: https://en.wikipedia.org/wiki/ISO_8601
+    #[cfg(feature = "local-time")]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "time", feature = "local-time"))))]
+    pub fn local_rfc_3339() -> Result<Self, time::error::IndeterminateOffset> {
+        Ok(Self::new(
+            UtcOffset::current_local_offset()?,
+            well_known::Rfc3339,
+        ))
+    }
+}
+
+impl OffsetTime<Vec<FormatItem<'static>>> {
+    /// Returns a formatter that formats the current time at the provided [UTC
+    /// offset] in an [RFC 3339]-style format, with the provided number of
+    /// fractional-second digits.
+    ///
+    /// A `subsecond_digits` of `0` omits the fractional seconds entirely.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `subsecond_digits` is greater than 9, as timestamps have at
+    /// most nanosecond precision.
+    ///
+    /// # Examples
+    ///
+    /// Formatting timestamps with millisecond precision at UTC+2:
+    ///
+    /// ```
+    /// use time::macros::offset;
+    /// use tracing_subscriber::fmt::{self, time::OffsetTime};
+    ///
+    /// let timer = OffsetTime::rfc_3339_with_subsecond_digits(offset!(+2), 3);
+    /// let collector = tracing_subscriber::fmt()
+    ///     .with_timer(timer);
+    /// # drop(collector);
+    /// ```
+    ///
+    /// [UTC offset]: https://docs.rs/time/0.3/time/struct.UtcOffset.html
+    /// [RFC 3339]: https://datatracker.ietf.org/doc/html/rfc3339
+    pub fn rfc_3339_with_subsecond_digits(offset: UtcOffset, subsecond_digits: u8) -> Self {
+        Self::new(offset, rfc_3339_format_items(subsecond_digits))
+    }
+}
+
+impl<F: Formattable> OffsetTime<F> {
+    /// Returns a formatter that formats the current time at the provided [UTC
+    /// offset], using the [`time` crate] with the provided format. The format
+    /// may be any type that implements the [`Formattable`] trait.
+    ///
+    /// Typically, the format will be a format description string, or one of
+    /// the `time` crate's [well-known formats].
+    ///
+    /// If the format description is statically known, then the
+    /// [`format_description!`] macro should be used. This is identical to the
+    /// [`time::format_description::parse`] method, but runs at compile-time,
+    /// throwing an error if the format description is invalid. If the desired
+    /// format is not known statically (e.g., a user is providing a format
+    /// string), then the [`time::format_description::parse`] method should be
+    /// used. Note that this method is fallible.
+    ///
+    /// See the [`time` book] for details on the format description syntax.
+    ///
+    /// # Examples
+    ///
+    /// Using the [`format_description!`] macro:
+    ///
+    /// ```
+    /// use time::macros::{format_description, offset};
+    /// use tracing_subscriber::fmt::{self, time::OffsetTime};
+    ///
+    /// let timer = OffsetTime::new(offset!(+2), format_description!("[hour]:[minute]:[second]"));
+    /// let collector = tracing_subscriber::fmt()
+    ///     .with_timer(timer);
+    /// # drop(collector);
+    /// ```
+    ///
+    /// Using [`time::format_description::parse`]:
+    ///
+    /// ```
+    /// use time::macros::offset;
+    /// use tracing_subscriber::fmt::{self, time::OffsetTime};
+    ///
+    /// let time_format = time::format_description::parse("[hour]:[minute]:[second]")
+    ///     .expect("format string should be valid!");
+    /// let timer = OffsetTime::new(offset!(+2), time_format);
+    /// let collector = tracing_subscriber::fmt()
+    ///     .with_timer(timer);
+    /// # drop(collector);
+    /// ```
+    ///
+    /// Using the [`format_description!`] macro requires enabling the `time`
+    /// crate's "macros" feature flag.
+    ///
+    /// [UTC offset]: https://docs.rs/time/0.3/time/struct.UtcOffset.html
+    /// [`time` crate]: https://docs.rs/time/0.3/time/
+    /// [`Formattable`]: https://docs.rs/time/0.3/time/formatting/trait.Formattable.html
+    /// [well-known formats]: https://docs.rs/time/0.3/time/format_description/well_known/index.html
+    /// [`format_description!`]: https://docs.rs/time/0.3/time/macros/macro.format_description.html
+    /// [`time::format_description::parse`]: https://docs.rs/time/0.3/time/format_description/fn.parse.html
+    /// [`time` book]: https://time-rs.github.io/book/api/format-description.html
+    pub fn new(offset: UtcOffset, format: F) -> Self {
+        Self { offset, format }
+    }
+}
+
+impl<F> FormatTime for OffsetTime<F>
+where
+    F: Formattable,
+{
+    fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+        let now = OffsetDateTime::now_utc().to_offset(self.offset);
+        format_datetime(now, w, &self.format)
+    }
+}
+
+/// Builds an RFC 3339-style format description with the provided number of
+/// fractional-second digits.
+//
+// The `time` crate's modifier types are `#[non_exhaustive]`, so they must be
+// constructed with `Default::default()` and then mutated. Newer `time`
+// releases deprecate several of these components in favor of replacements
+// that don't exist in the minimum supported `time` version, so those
+// deprecations are allowed here.
+#[allow(clippy::field_reassign_with_default, deprecated)]
+fn rfc_3339_format_items(subsecond_digits: u8) -> Vec<FormatItem<'static>> {
+    assert!(
+        subsecond_digits <= 9,
+        "timestamps have at most 9 fractional-second digits (got {})",
+        subsecond_digits
+    );
+
+    let mut items = vec![
+        FormatItem::Component(Component::Year(modifier::Year::default())),
+        FormatItem::Literal(b"-"),
+        FormatItem::Component(Component::Month(modifier::Month::default())),
+        FormatItem::Literal(b"-"),
+        FormatItem::Component(Component::Day(modifier::Day::default())),
+        FormatItem::Literal(b"T"),
+        FormatItem::Component(Component::Hour(modifier::Hour::default())),
+        FormatItem::Literal(b":"),
+        FormatItem::Component(Component::Minute(modifier::Minute::default())),
+        FormatItem::Literal(b":"),
+        FormatItem::Component(Component::Second(modifier::Second::default())),
+    ];
+
+    if subsecond_digits > 0 {
+        let mut subsecond = modifier::Subsecond::default();
+        subsecond.digits = match subsecond_digits {
+            1 => modifier::SubsecondDigits::One,
+            2 => modifier::SubsecondDigits::Two,
+            3 => modifier::SubsecondDigits::Three,
+            4 => modifier::SubsecondDigits::Four,
+            5 => modifier::SubsecondDigits::Five,
+            6 => modifier::SubsecondDigits::Six,
+            7 => modifier::SubsecondDigits::Seven,
+            8 => modifier::SubsecondDigits::Eight,
+            _ => modifier::SubsecondDigits::Nine,
+        };
+        items.push(FormatItem::Literal(b"."));
+        items.push(FormatItem::Component(Component::Subsecond(subsecond)));
+    }
+
+    let mut offset_hour = modifier::OffsetHour::default();
+    offset_hour.sign_is_mandatory = true;
+    items.push(FormatItem::Component(Component::OffsetHour(offset_hour)));
+    items.push(FormatItem::Literal(b":"));
+    items.push(FormatItem::Component(Component::OffsetMinute(
+        modifier::OffsetMinute::default(),
+    )));
+
+    items
+}
+
 fn format_datetime(
     now: OffsetDateTime,
     into: &mut Writer<'_>,
@@ -301,3 +522,60 @@ fn format_datetime(
         .map_err(|_| fmt::Error)
         .map(|_| ())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn format_now(timer: &impl FormatTime) -> String {
+        let mut buf = String::new();
+        timer
+            .format_time(&mut Writer::new(&mut buf))
+            .expect("timestamp should format successfully");
+        buf
+    }
+
+    #[test]
+    fn offset_time_rfc_3339_with_subsecond_digits() {
+        let offset = UtcOffset::from_hms(2, 0, 0).unwrap();
+        let timer = OffsetTime::rfc_3339_with_subsecond_digits(offset, 3);
+        let formatted = format_now(&timer);
+        assert!(
+            formatted.ends_with("+02:00"),
+            "timestamp {:?} should use the provided offset",
+            formatted,
+        );
+        let (_, subsec) = formatted
+            .split_once('.')
+            .expect("timestamp should include fractional seconds");
+        assert_eq!(
+            subsec.len() - "+02:00".len(),
+            3,
+            "timestamp {:?} should have 3 fractional-second digits",
+            formatted,
+        );
+    }
+
+    #[test]
+    fn offset_time_without_subsecond_digits() {
+        let offset = UtcOffset::from_hms(-5, 0, 0).unwrap();
+        let timer = OffsetTime::rfc_3339_with_subsecond_digits(offset, 0);
+        let formatted = format_now(&timer);
+        assert!(
+            formatted.ends_with("-05:00"),
+            "timestamp {:?} should use the provided offset",
+            formatted,
+        );
+        assert!(
+            !formatted.contains('.'),
+            "timestamp {:?} should not include fractional seconds",
+            formatted,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 9 fractional-second digits")]
+    fn offset_time_rejects_too_many_subsecond_digits() {
+        let _ = OffsetTime::rfc_3339_with_subsecond_digits(UtcOffset::UTC, 10);
+    }
+}
@@ -3,11 +3,16 @@
 //! [`io::Write`]: std::io::Write
 
 use std::{
+    collections::HashMap,
     fmt,
     io::{self, Write},
-    sync::{Mutex, MutexGuard},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
 };
-use tracing_core::Metadata;
+use tracing_core::{field, Event, Metadata};
 
 /// A type that can create [`io::Write`] instances.
 ///
@@ -220,6 +225,34 @@ pub trait MakeWriter<'a> {
         let _ = meta;
         self.make_writer()
     }
+
+    /// Returns a [`Writer`] for writing data from the provided [`Event`].
+    ///
+    /// By default, this calls [`self.make_writer_for(event.metadata())`][for],
+    /// ignoring everything about the event except its [`Metadata`].
+    /// Implementations can override this to select a writer based on the
+    /// event's *field values* in addition to its metadata — something
+    /// [`make_writer_for`][for] cannot do, as field values are not part of a
+    /// span or event's metadata. For example, events recorded with an
+    /// `audit = true` field might be written to a separate audit log.
+    ///
+    /// [`fmt::Subscriber`] and [`fmt::Collector`] call this method when
+    /// writing an event; [`make_writer_for`][for] is still used for output
+    /// that is not associated with an event, such as synthesized span
+    /// lifecycle output.
+    ///
+    /// See [`FieldRouter`] for a ready-made implementation that routes events
+    /// to cached writers keyed by the value of a single field.
+    ///
+    /// [`Writer`]: MakeWriter::Writer
+    /// [`Event`]: tracing_core::Event
+    /// [`Metadata`]: tracing_core::Metadata
+    /// [for]: MakeWriter::make_writer_for
+    /// [`fmt::Subscriber`]: super::super::fmt::Subscriber
+    /// [`fmt::Collector`]: super::super::fmt::Collector
+    fn make_writer_for_event(&'a self, event: &Event<'_>) -> Self::Writer {
+        self.make_writer_for(event.metadata())
+    }
 }
 
 /// Extension trait adding combinators for working with types implementing
@@ -474,6 +507,48 @@ pub trait MakeWriterExt<'a>: MakeWriter<'a> {
     {
         OrElse::new(self, other)
     }
+
+    /// Combines `self` with a fallback [`MakeWriter`], returning a new
+    /// [`MakeWriter`] that fails over to the fallback when writing to `self`'s
+    /// [writer] fails.
+    ///
+    /// When a write to the primary writer returns an error — for example,
+    /// because a log file could not be written or a disk filled up — the
+    /// failing output is written to the fallback instead, along with a
+    /// one-time diagnostic line describing the failure, and subsequent output
+    /// goes to the fallback as well. The primary writer is retried
+    /// periodically; the retry interval can be configured with
+    /// [`Fallback::retry_after`].
+    ///
+    /// Unlike [`or_else`], which falls back when the primary `MakeWriter` is
+    /// *disabled* by a filtering combinator, `with_fallback` falls back when
+    /// writing to the primary output *fails*.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tracing_subscriber::fmt::writer::MakeWriterExt;
+    /// use std::{fs::File, sync::Mutex};
+    ///
+    /// # fn docs() -> Result<(), std::io::Error> {
+    /// // Write to a log file, but fall back to stderr if the file becomes
+    /// // unwritable (for example, because the disk is full).
+    /// let file = Mutex::new(File::create("my_app.log")?);
+    /// let mk_writer = file.with_fallback(std::io::stderr);
+    ///
+    /// tracing_subscriber::fmt().with_writer(mk_writer).init();
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// [writer]: std::io::Write
+    /// [`or_else`]: MakeWriterExt::or_else
+    fn with_fallback<B>(self, fallback: B) -> Fallback<Self, B>
+    where
+        Self: Sized,
+        B: MakeWriter<'a> + Sized,
+    {
+        Fallback::new(self, fallback)
+    }
 }
 
 /// A type implementing [`io::Write`] for a [`MutexGuard`] where the type
@@ -632,6 +707,88 @@ pub struct Tee<A, B> {
     b: B,
 }
 
+/// Combines two types implementing [`MakeWriter`] so that output goes to the
+/// first [`MakeWriter`] until writing to it fails, and to the second from then
+/// on, with the first retried periodically.
+///
+/// This is returned by the [`MakeWriterExt::with_fallback`] method. See the
+/// method documentation for details.
+#[derive(Debug)]
+pub struct Fallback<A, B> {
+    primary: A,
+    fallback: B,
+    retry_interval: Duration,
+    last_failure: Mutex<Option<Instant>>,
+    reported: AtomicBool,
+}
+
+/// A [writer] returned by a [`Fallback`], writing to either the primary or
+/// the fallback output, and failing over mid-write if the primary output
+/// returns an error.
+///
+/// [writer]: std::io::Write
+pub struct FallbackWriter<'a, A: MakeWriter<'a>, B: MakeWriter<'a>> {
+    make: &'a Fallback<A, B>,
+    inner: EitherWriter<A::Writer, B::Writer>,
+    retrying: bool,
+}
+
+/// A [`MakeWriter`] that routes events to different [writers] based on the
+/// value of one of their fields.
+///
+/// A `FieldRouter` is constructed from the name of the field to route on and a
+/// function that builds a writer for each distinct value of that field. The
+/// function is called at most once per value; the resulting writer is cached
+/// and reused for all subsequent events carrying the same value. Events that
+/// do not record the field at all — as well as output that is not associated
+/// with an event, such as synthesized span lifecycle output — are passed to
+/// the function with `None`.
+///
+/// String field values are passed to the function as recorded; values of other
+/// types are formatted with their [`fmt::Debug`] implementations (so a
+/// `tenant = 42` field routes on `"42"`, and `audit = true` on `"true"`).
+///
+/// # Examples
+///
+/// Writing each tenant's events to its own log file:
+///
+/// ```
+/// use std::fs::File;
+/// use tracing_subscriber::fmt::writer::FieldRouter;
+///
+/// let writer = FieldRouter::new("tenant", |tenant| {
+///     let name = match tenant {
+///         Some(tenant) => format!("logs/{}.log", tenant),
+///         None => String::from("logs/other.log"),
+///     };
+///     File::create(name).expect("failed to create log file")
+/// });
+///
+/// let collector = tracing_subscriber::fmt()
+///     .with_writer(writer)
+///     .finish();
+/// # drop(collector);
+/// ```
+///
+/// [writers]: std::io::Write
+pub struct FieldRouter<F, W> {
+    field: String,
+    make: F,
+    writers: Mutex<HashMap<Option<String>, W>>,
+}
+
+/// A [writer] returned by a [`FieldRouter`].
+///
+/// This holds a lock on the router's cache of writers for the duration of a
+/// write, so writes through a single [`FieldRouter`] are serialized.
+///
+/// [writer]: std::io::Write
+#[derive(Debug)]
+pub struct RoutedWriter<'a, W> {
+    writers: MutexGuard<'a, HashMap<Option<String>, W>>,
+    key: Option<String>,
+}
+
 /// A bridge between `fmt::Write` and `io::Write`.
 ///
 /// This is used by the timestamp formatting implementation for the `time`
@@ -726,6 +883,10 @@ impl<'a> MakeWriter<'a> for BoxMakeWriter {
     fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
         self.inner.make_writer_for(meta)
     }
+
+    fn make_writer_for_event(&'a self, event: &Event<'_>) -> Self::Writer {
+        self.inner.make_writer_for_event(event)
+    }
 }
 
 struct Boxed<M>(M);
@@ -745,6 +906,11 @@ where
         let w = self.0.make_writer_for(meta);
         Box::new(w)
     }
+
+    fn make_writer_for_event(&'a self, event: &Event<'_>) -> Self::Writer {
+        let w = self.0.make_writer_for_event(event);
+        Box::new(w)
+    }
 }
 
 // === impl Mutex/MutexGuardWriter ===
@@ -901,6 +1067,14 @@ impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for WithMaxLevel<M> {
         }
         OptionalWriter::none()
     }
+
+    #[inline]
+    fn make_writer_for_event(&'a self, event: &Event<'_>) -> Self::Writer {
+        if event.metadata().level() <= &self.level {
+            return OptionalWriter::some(self.make.make_writer_for_event(event));
+        }
+        OptionalWriter::none()
+    }
 }
 
 // === impl WithMinLevel ===
@@ -934,6 +1108,14 @@ impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for WithMinLevel<M> {
         }
         OptionalWriter::none()
     }
+
+    #[inline]
+    fn make_writer_for_event(&'a self, event: &Event<'_>) -> Self::Writer {
+        if event.metadata().level() >= &self.level {
+            return OptionalWriter::some(self.make.make_writer_for_event(event));
+        }
+        OptionalWriter::none()
+    }
 }
 
 // ==== impl WithFilter ===
@@ -975,6 +1157,15 @@ where
             OptionalWriter::none()
         }
     }
+
+    #[inline]
+    fn make_writer_for_event(&'a self, event: &Event<'_>) -> Self::Writer {
+        if (self.filter)(event.metadata()) {
+            OptionalWriter::some(self.make.make_writer_for_event(event))
+        } else {
+            OptionalWriter::none()
+        }
+    }
 }
 
 // === impl Tee ===
@@ -1008,6 +1199,14 @@ where
     fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
         Tee::new(self.a.make_writer_for(meta), self.b.make_writer_for(meta))
     }
+
+    #[inline]
+    fn make_writer_for_event(&'a self, event: &Event<'_>) -> Self::Writer {
+        Tee::new(
+            self.a.make_writer_for_event(event),
+            self.b.make_writer_for_event(event),
+        )
+    }
 }
 
 macro_rules! impl_tee {
@@ -1093,6 +1292,316 @@ where
             EitherWriter::B(_) => EitherWriter::B(self.or_else.make_writer_for(meta)),
         }
     }
+
+    #[inline]
+    fn make_writer_for_event(&'a self, event: &Event<'_>) -> Self::Writer {
+        match self.inner.make_writer_for_event(event) {
+            EitherWriter::A(writer) => EitherWriter::A(writer),
+            EitherWriter::B(_) => EitherWriter::B(self.or_else.make_writer_for_event(event)),
+        }
+    }
+}
+
+// === impl Fallback ===
+
+impl<A, B> Fallback<A, B> {
+    /// Combines a primary and a fallback [`MakeWriter`], returning a new
+    /// [`MakeWriter`] that fails over to the fallback when writing to the
+    /// primary fails.
+    ///
+    /// See the documentation for [`MakeWriterExt::with_fallback`] for details.
+    pub fn new<'a>(primary: A, fallback: B) -> Self
+    where
+        A: MakeWriter<'a>,
+        B: MakeWriter<'a>,
+    {
+        Self {
+            primary,
+            fallback,
+            retry_interval: Duration::from_secs(5),
+            last_failure: Mutex::new(None),
+            reported: AtomicBool::new(false),
+        }
+    }
+
+    /// Sets how long output is written to the fallback before the primary
+    /// writer is retried.
+    ///
+    /// If the retried primary writer fails again, output returns to the
+    /// fallback and the interval starts over. The default interval is five
+    /// seconds.
+    pub fn retry_after(self, interval: Duration) -> Self {
+        Self {
+            retry_interval: interval,
+            ..self
+        }
+    }
+
+    /// Returns whether the next writer should target the primary output, and
+    /// whether doing so is a retry after an earlier failure.
+    fn use_primary(&self) -> (bool, bool) {
+        match *self.last_failure.lock().expect("lock poisoned") {
+            None => (true, false),
+            Some(failed_at) if failed_at.elapsed() >= self.retry_interval => (true, true),
+            Some(_) => (false, false),
+        }
+    }
+
+    /// Records a failed write to the primary output, emitting a one-time
+    /// diagnostic line to the fallback.
+    fn note_failure(&self, error: &io::Error, fallback: &mut dyn io::Write) {
+        *self.last_failure.lock().expect("lock poisoned") = Some(Instant::now());
+        if !self.reported.swap(true, Ordering::AcqRel) {
+            let _ = writeln!(
+                fallback,
+                "tracing-subscriber: writing to the primary writer failed, \
+                 falling back: {}",
+                error
+            );
+        }
+    }
+
+    /// Records a successful retry of the primary output, re-arming the
+    /// one-time diagnostic for the next failure.
+    fn note_recovery(&self) {
+        *self.last_failure.lock().expect("lock poisoned") = None;
+        self.reported.store(false, Ordering::Release);
+    }
+}
+
+impl<'a, A, B> MakeWriter<'a> for Fallback<A, B>
+where
+    A: MakeWriter<'a> + 'a,
+    B: MakeWriter<'a> + 'a,
+{
+    type Writer = FallbackWriter<'a, A, B>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        let (use_primary, retrying) = self.use_primary();
+        let inner = if use_primary {
+            EitherWriter::A(self.primary.make_writer())
+        } else {
+            EitherWriter::B(self.fallback.make_writer())
+        };
+        FallbackWriter {
+            make: self,
+            inner,
+            retrying,
+        }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        let (use_primary, retrying) = self.use_primary();
+        let inner = if use_primary {
+            EitherWriter::A(self.primary.make_writer_for(meta))
+        } else {
+            EitherWriter::B(self.fallback.make_writer_for(meta))
+        };
+        FallbackWriter {
+            make: self,
+            inner,
+            retrying,
+        }
+    }
+
+    fn make_writer_for_event(&'a self, event: &Event<'_>) -> Self::Writer {
+        let (use_primary, retrying) = self.use_primary();
+        let inner = if use_primary {
+            EitherWriter::A(self.primary.make_writer_for_event(event))
+        } else {
+            EitherWriter::B(self.fallback.make_writer_for_event(event))
+        };
+        FallbackWriter {
+            make: self,
+            inner,
+            retrying,
+        }
+    }
+}
+
+impl<'a, A, B> io::Write for FallbackWriter<'a, A, B>
+where
+    A: MakeWriter<'a>,
+    B: MakeWriter<'a>,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            EitherWriter::A(primary) => match primary.write(buf) {
+                Ok(n) => {
+                    if self.retrying {
+                        self.make.note_recovery();
+                        self.retrying = false;
+                    }
+                    Ok(n)
+                }
+                Err(error) => {
+                    let mut fallback = self.make.fallback.make_writer();
+                    self.make.note_failure(&error, &mut fallback);
+                    // Write the failed output to the fallback, so that the
+                    // event triggering the failover is not lost.
+                    let result = fallback.write(buf);
+                    self.inner = EitherWriter::B(fallback);
+                    result
+                }
+            },
+            EitherWriter::B(fallback) => fallback.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.inner {
+            EitherWriter::A(primary) => match primary.flush() {
+                Ok(()) => {
+                    if self.retrying {
+                        self.make.note_recovery();
+                        self.retrying = false;
+                    }
+                    Ok(())
+                }
+                Err(error) => {
+                    let mut fallback = self.make.fallback.make_writer();
+                    self.make.note_failure(&error, &mut fallback);
+                    self.inner = EitherWriter::B(fallback);
+                    Err(error)
+                }
+            },
+            EitherWriter::B(fallback) => fallback.flush(),
+        }
+    }
+}
+
+impl<'a, A, B> fmt::Debug for FallbackWriter<'a, A, B>
+where
+    A: MakeWriter<'a>,
+    B: MakeWriter<'a>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FallbackWriter")
+            .field(
+                "inner",
+                &format_args!(
+                    "{}",
+                    match self.inner {
+                        EitherWriter::A(_) => "primary",
+                        EitherWriter::B(_) => "fallback",
+                    }
+                ),
+            )
+            .field("retrying", &self.retrying)
+            .finish()
+    }
+}
+
+// === impl FieldRouter/RoutedWriter ===
+
+impl<F, W> FieldRouter<F, W>
+where
+    F: Fn(Option<&str>) -> W,
+    W: io::Write,
+{
+    /// Returns a new `FieldRouter` that routes events on the value of the
+    /// field named `field`, using `make` to build a [writer] for each distinct
+    /// value.
+    ///
+    /// `make` is called with `Some(value)` the first time each value of the
+    /// field is seen, and with `None` for events that do not record the field;
+    /// the returned writers are cached and reused.
+    ///
+    /// [writer]: std::io::Write
+    pub fn new(field: impl Into<String>, make: F) -> Self {
+        Self {
+            field: field.into(),
+            make,
+            writers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn writer_for(&self, key: Option<String>) -> RoutedWriter<'_, W> {
+        let mut writers = self.writers.lock().expect("lock poisoned");
+        if !writers.contains_key(&key) {
+            writers.insert(key.clone(), (self.make)(key.as_deref()));
+        }
+        RoutedWriter { writers, key }
+    }
+}
+
+impl<'a, F, W> MakeWriter<'a> for FieldRouter<F, W>
+where
+    F: Fn(Option<&str>) -> W,
+    W: io::Write + 'a,
+{
+    type Writer = RoutedWriter<'a, W>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.writer_for(None)
+    }
+
+    fn make_writer_for_event(&'a self, event: &Event<'_>) -> Self::Writer {
+        let mut visitor = RoutingKeyVisitor {
+            field: self.field.as_str(),
+            value: None,
+        };
+        event.record(&mut visitor);
+        self.writer_for(visitor.value)
+    }
+}
+
+impl<F, W> fmt::Debug for FieldRouter<F, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FieldRouter")
+            .field("field", &self.field)
+            .field("make", &format_args!("..."))
+            .field("writers", &format_args!("..."))
+            .finish()
+    }
+}
+
+/// Extracts the value of the field a [`FieldRouter`] routes on.
+struct RoutingKeyVisitor<'a> {
+    field: &'a str,
+    value: Option<String>,
+}
+
+impl field::Visit for RoutingKeyVisitor<'_> {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == self.field {
+            self.value = Some(value.to_owned());
+        }
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn fmt::Debug) {
+        if field.name() == self.field {
+            self.value = Some(format!("{:?}", value));
+        }
+    }
+}
+
+impl<W> io::Write for RoutedWriter<'_, W>
+where
+    W: io::Write,
+{
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner().write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner().flush()
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.inner().write_all(buf)
+    }
+}
+
+impl<W> RoutedWriter<'_, W> {
+    fn inner(&mut self) -> &mut W {
+        self.writers
+            .get_mut(&self.key)
+            .expect("writer is inserted before the `RoutedWriter` is returned")
+    }
 }
 
 // === impl WriteAdaptor ===
@@ -1127,6 +1636,611 @@ impl fmt::Debug for WriteAdaptor<'_> {
         f.pad("WriteAdaptor { .. }")
     }
 }
+// === impl EventLogWriter ===
+
+/// A [`MakeWriter`] that reports events to the Windows Event Log via
+/// `ReportEventW`.
+///
+/// Event levels are mapped to event types: [`ERROR`] events are reported as
+/// `EVENTLOG_ERROR_TYPE`, [`WARN`] events as `EVENTLOG_WARNING_TYPE`, and all
+/// other events as `EVENTLOG_INFORMATION_TYPE`. Each formatted event —
+/// including its fields, as rendered by the configured formatter — becomes
+/// the report's insertion string. Reporting errors are returned to the
+/// [`fmt` subscriber], which drops the event.
+///
+/// The event source named when [registering](EventLogWriter::register) the
+/// writer should be registered with the Event Log service (typically by the
+/// service's installer); unregistered sources still work, but the Event
+/// Viewer displays their messages with a generic description.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tracing_subscriber::fmt::writer::EventLogWriter;
+///
+/// # fn docs() -> std::io::Result<()> {
+/// let writer = EventLogWriter::register("MyService")?;
+/// tracing_subscriber::fmt()
+///     .with_writer(writer)
+///     .with_ansi(false)
+///     .init();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`ERROR`]: tracing_core::Level::ERROR
+/// [`WARN`]: tracing_core::Level::WARN
+/// [`fmt` subscriber]: super::Subscriber
+#[cfg(windows)]
+#[cfg_attr(docsrs, doc(cfg(windows)))]
+#[derive(Debug)]
+pub struct EventLogWriter {
+    handle: event_log::Handle,
+    event_id: u32,
+}
+
+// The event log handle may be used concurrently from any thread;
+// `ReportEventW` performs its own synchronization.
+#[cfg(windows)]
+unsafe impl Send for EventLogWriter {}
+#[cfg(windows)]
+unsafe impl Sync for EventLogWriter {}
+
+#[cfg(windows)]
+impl EventLogWriter {
+    /// Registers an event source with the given name on the local machine,
+    /// returning a writer that reports events under it.
+    pub fn register(source: impl AsRef<str>) -> io::Result<Self> {
+        let source = event_log::wide(source.as_ref());
+        // A null server name reports to the local machine's event log.
+        let handle = unsafe { event_log::RegisterEventSourceW(std::ptr::null(), source.as_ptr()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            handle,
+            event_id: 0,
+        })
+    }
+
+    /// Sets the event ID included in every report.
+    ///
+    /// Event IDs identify message definitions in the source's registered
+    /// message file. The default of 0 is fine for sources without one.
+    pub fn with_event_id(self, event_id: u32) -> Self {
+        Self { event_id, ..self }
+    }
+
+    fn report(&self, event_type: u16, message: &[u8]) -> io::Result<()> {
+        let message = String::from_utf8_lossy(message);
+        // The trailing newline added by the formatter is line framing, not
+        // part of the message.
+        let message = message.trim_end_matches('\n');
+        if message.is_empty() {
+            return Ok(());
+        }
+        let message = event_log::wide(message);
+        let strings = [message.as_ptr()];
+        let reported = unsafe {
+            event_log::ReportEventW(
+                self.handle,
+                event_type,
+                0,
+                self.event_id,
+                std::ptr::null_mut(),
+                1,
+                0,
+                strings.as_ptr(),
+                std::ptr::null_mut(),
+            )
+        };
+        if reported == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+impl Drop for EventLogWriter {
+    fn drop(&mut self) {
+        unsafe {
+            event_log::DeregisterEventSource(self.handle);
+        }
+    }
+}
+
+#[cfg(windows)]
+impl<'a> MakeWriter<'a> for EventLogWriter {
+    type Writer = EventLogIo<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        EventLogIo {
+            writer: self,
+            event_type: event_log::EVENTLOG_INFORMATION_TYPE,
+            buf: Vec::new(),
+        }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        let event_type = match *meta.level() {
+            tracing_core::Level::ERROR => event_log::EVENTLOG_ERROR_TYPE,
+            tracing_core::Level::WARN => event_log::EVENTLOG_WARNING_TYPE,
+            _ => event_log::EVENTLOG_INFORMATION_TYPE,
+        };
+        EventLogIo {
+            writer: self,
+            event_type,
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// An [`io::Write`] implementation returned by [`EventLogWriter`], buffering
+/// one formatted event and reporting it when flushed or dropped.
+#[cfg(windows)]
+#[cfg_attr(docsrs, doc(cfg(windows)))]
+#[derive(Debug)]
+pub struct EventLogIo<'a> {
+    writer: &'a EventLogWriter,
+    event_type: u16,
+    buf: Vec<u8>,
+}
+
+#[cfg(windows)]
+impl io::Write for EventLogIo<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.writer.report(self.event_type, &self.buf);
+        self.buf.clear();
+        result
+    }
+}
+
+#[cfg(windows)]
+impl Drop for EventLogIo<'_> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Raw bindings to the event logging functions in `advapi32`, used instead
+/// of a Windows bindings crate to avoid a platform-specific dependency.
+#[cfg(windows)]
+mod event_log {
+    use std::ffi::c_void;
+
+    pub(super) type Handle = *mut c_void;
+
+    pub(super) const EVENTLOG_ERROR_TYPE: u16 = 0x0001;
+    pub(super) const EVENTLOG_WARNING_TYPE: u16 = 0x0002;
+    pub(super) const EVENTLOG_INFORMATION_TYPE: u16 = 0x0004;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        pub(super) fn RegisterEventSourceW(server: *const u16, source: *const u16) -> Handle;
+        pub(super) fn DeregisterEventSource(handle: Handle) -> i32;
+        pub(super) fn ReportEventW(
+            handle: Handle,
+            event_type: u16,
+            category: u16,
+            event_id: u32,
+            user_sid: *mut c_void,
+            num_strings: u16,
+            data_size: u32,
+            strings: *const *const u16,
+            raw_data: *mut c_void,
+        ) -> i32;
+    }
+
+    /// Returns `s` as a null-terminated UTF-16 string.
+    pub(super) fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(Some(0)).collect()
+    }
+}
+
+// === impl LogcatWriter ===
+
+/// A [`MakeWriter`] that routes events to Android's logcat via
+/// `__android_log_write`.
+///
+/// Event levels are mapped to logcat priorities ([`ERROR`] → `ERROR`,
+/// [`WARN`] → `WARN`, [`INFO`] → `INFO`, [`DEBUG`] → `DEBUG`, [`TRACE`] →
+/// `VERBOSE`), and the event's target is used as the logcat tag, truncated
+/// to the 23 bytes that releases before API level 26 allow. A fixed tag can
+/// be set with [`with_tag`](LogcatWriter::with_tag) instead.
+///
+/// Each line of the formatted event becomes one logcat entry, so multi-line
+/// formatters such as [`Pretty`] remain readable. Since logcat records its
+/// own timestamps, [`without_time`] is recommended:
+///
+/// ```no_run
+/// use tracing_subscriber::fmt::writer::LogcatWriter;
+///
+/// tracing_subscriber::fmt()
+///     .with_writer(LogcatWriter::new())
+///     .with_ansi(false)
+///     .without_time()
+///     .init();
+/// ```
+///
+/// [`ERROR`]: tracing_core::Level::ERROR
+/// [`WARN`]: tracing_core::Level::WARN
+/// [`INFO`]: tracing_core::Level::INFO
+/// [`DEBUG`]: tracing_core::Level::DEBUG
+/// [`TRACE`]: tracing_core::Level::TRACE
+/// [`Pretty`]: super::format::Pretty
+/// [`without_time`]: super::CollectorBuilder::without_time
+#[cfg(target_os = "android")]
+#[cfg_attr(docsrs, doc(cfg(target_os = "android")))]
+#[derive(Debug, Default)]
+pub struct LogcatWriter {
+    tag: Option<String>,
+}
+
+#[cfg(target_os = "android")]
+impl LogcatWriter {
+    /// Returns a new writer that tags entries with each event's target.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a fixed logcat tag used for every entry, instead of the event's
+    /// target.
+    pub fn with_tag(self, tag: impl Into<String>) -> Self {
+        Self {
+            tag: Some(tag.into()),
+        }
+    }
+
+    fn tag_for(&self, target: &str) -> String {
+        match &self.tag {
+            Some(tag) => tag.clone(),
+            None => logcat::truncate_tag(target).to_string(),
+        }
+    }
+}
+
+#[cfg(target_os = "android")]
+impl<'a> MakeWriter<'a> for LogcatWriter {
+    type Writer = LogcatIo;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogcatIo {
+            priority: logcat::ANDROID_LOG_INFO,
+            tag: self.tag_for("tracing"),
+            buf: Vec::new(),
+        }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        let priority = match *meta.level() {
+            tracing_core::Level::ERROR => logcat::ANDROID_LOG_ERROR,
+            tracing_core::Level::WARN => logcat::ANDROID_LOG_WARN,
+            tracing_core::Level::INFO => logcat::ANDROID_LOG_INFO,
+            tracing_core::Level::DEBUG => logcat::ANDROID_LOG_DEBUG,
+            tracing_core::Level::TRACE => logcat::ANDROID_LOG_VERBOSE,
+        };
+        LogcatIo {
+            priority,
+            tag: self.tag_for(meta.target()),
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// An [`io::Write`] implementation returned by [`LogcatWriter`], buffering
+/// one formatted event and writing each of its lines to logcat when flushed
+/// or dropped.
+#[cfg(target_os = "android")]
+#[cfg_attr(docsrs, doc(cfg(target_os = "android")))]
+#[derive(Debug)]
+pub struct LogcatIo {
+    priority: std::os::raw::c_int,
+    tag: String,
+    buf: Vec<u8>,
+}
+
+#[cfg(target_os = "android")]
+impl io::Write for LogcatIo {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+        let tag = std::ffi::CString::new(self.tag.as_str()).map_err(invalid)?;
+        let text = String::from_utf8_lossy(&self.buf);
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            let line = std::ffi::CString::new(line).map_err(invalid)?;
+            let written =
+                unsafe { logcat::__android_log_write(self.priority, tag.as_ptr(), line.as_ptr()) };
+            if written < 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "__android_log_write failed",
+                ));
+            }
+        }
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "android")]
+impl Drop for LogcatIo {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Raw bindings to Android's `liblog`, used instead of a bindings crate to
+/// avoid a platform-specific dependency.
+#[cfg(target_os = "android")]
+mod logcat {
+    use std::os::raw::{c_char, c_int};
+
+    pub(super) const ANDROID_LOG_VERBOSE: c_int = 2;
+    pub(super) const ANDROID_LOG_DEBUG: c_int = 3;
+    pub(super) const ANDROID_LOG_INFO: c_int = 4;
+    pub(super) const ANDROID_LOG_WARN: c_int = 5;
+    pub(super) const ANDROID_LOG_ERROR: c_int = 6;
+
+    /// The largest tag accepted before API level 26.
+    const MAX_TAG_LEN: usize = 23;
+
+    #[link(name = "log")]
+    extern "C" {
+        pub(super) fn __android_log_write(
+            prio: c_int,
+            tag: *const c_char,
+            text: *const c_char,
+        ) -> c_int;
+    }
+
+    /// Truncates `tag` to the longest prefix that fits in [`MAX_TAG_LEN`]
+    /// bytes without splitting a character.
+    pub(super) fn truncate_tag(tag: &str) -> &str {
+        if tag.len() <= MAX_TAG_LEN {
+            return tag;
+        }
+        let mut len = MAX_TAG_LEN;
+        while !tag.is_char_boundary(len) {
+            len -= 1;
+        }
+        &tag[..len]
+    }
+}
+
+// === impl OsLogWriter ===
+
+/// A [`MakeWriter`] that forwards events to Apple's unified logging system,
+/// so they appear natively in Console.app and `log stream`.
+///
+/// Events are logged under the configured subsystem, with the event's target
+/// as the category. Levels are mapped to [`OSLogType`]s ([`ERROR`] →
+/// `error`, [`WARN`] → `default`, [`INFO`] → `info`, [`DEBUG`] and
+/// [`TRACE`] → `debug`).
+///
+/// By default, messages are logged with `%{public}s` visibility. If an
+/// event's field set contains a field named `redacted` (or one using the
+/// `redacted.` prefix), the message is logged with `%{private}s` visibility
+/// instead, so the unified logging system hides its contents unless private
+/// data logging is enabled.
+///
+/// Since the unified logging system records its own timestamps and levels,
+/// [`without_time`] is recommended:
+///
+/// ```no_run
+/// use tracing_subscriber::fmt::writer::OsLogWriter;
+///
+/// tracing_subscriber::fmt()
+///     .with_writer(OsLogWriter::new("com.example.my-app"))
+///     .with_ansi(false)
+///     .without_time()
+///     .init();
+/// ```
+///
+/// [`OSLogType`]: https://developer.apple.com/documentation/os/oslogtype
+/// [`ERROR`]: tracing_core::Level::ERROR
+/// [`WARN`]: tracing_core::Level::WARN
+/// [`INFO`]: tracing_core::Level::INFO
+/// [`DEBUG`]: tracing_core::Level::DEBUG
+/// [`TRACE`]: tracing_core::Level::TRACE
+/// [`without_time`]: super::CollectorBuilder::without_time
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[cfg_attr(docsrs, doc(cfg(any(target_os = "macos", target_os = "ios"))))]
+#[derive(Debug)]
+pub struct OsLogWriter {
+    subsystem: String,
+    logs: Mutex<HashMap<String, os_log::OsLog>>,
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl OsLogWriter {
+    /// Returns a new writer logging under the given subsystem, which should
+    /// be a reverse-DNS identifier such as `com.example.my-app`.
+    pub fn new(subsystem: impl Into<String>) -> Self {
+        Self {
+            subsystem: subsystem.into(),
+            logs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached `os_log_t` object for `category`, creating it on
+    /// first use.
+    fn log_for(&self, category: &str) -> os_log::OsLog {
+        let mut logs = self.logs.lock().expect("lock poisoned");
+        if let Some(log) = logs.get(category) {
+            return *log;
+        }
+        let log = os_log::OsLog::create(&self.subsystem, category);
+        logs.insert(category.to_string(), log);
+        log
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl<'a> MakeWriter<'a> for OsLogWriter {
+    type Writer = OsLogIo;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        OsLogIo {
+            log: self.log_for("tracing"),
+            log_type: os_log::OS_LOG_TYPE_DEFAULT,
+            private: false,
+            buf: Vec::new(),
+        }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        let log_type = match *meta.level() {
+            tracing_core::Level::ERROR => os_log::OS_LOG_TYPE_ERROR,
+            tracing_core::Level::WARN => os_log::OS_LOG_TYPE_DEFAULT,
+            tracing_core::Level::INFO => os_log::OS_LOG_TYPE_INFO,
+            tracing_core::Level::DEBUG | tracing_core::Level::TRACE => os_log::OS_LOG_TYPE_DEBUG,
+        };
+        let private = meta
+            .fields()
+            .iter()
+            .any(|field| field.name() == "redacted" || field.name().starts_with("redacted."));
+        OsLogIo {
+            log: self.log_for(meta.target()),
+            log_type,
+            private,
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// An [`io::Write`] implementation returned by [`OsLogWriter`], buffering
+/// one formatted event and writing each of its lines to the unified logging
+/// system when flushed or dropped.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[cfg_attr(docsrs, doc(cfg(any(target_os = "macos", target_os = "ios"))))]
+#[derive(Debug)]
+pub struct OsLogIo {
+    log: os_log::OsLog,
+    log_type: u8,
+    private: bool,
+    buf: Vec<u8>,
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl io::Write for OsLogIo {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let text = String::from_utf8_lossy(&self.buf);
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            self.log.write(self.log_type, self.private, line)?;
+        }
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl Drop for OsLogIo {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Raw bindings to the unified logging functions in `libSystem` (which is
+/// always linked on Apple targets), used instead of a bindings crate to
+/// avoid a platform-specific dependency.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod os_log {
+    use std::{
+        ffi::{c_void, CString},
+        io,
+        os::raw::c_char,
+    };
+
+    pub(super) const OS_LOG_TYPE_DEFAULT: u8 = 0x00;
+    pub(super) const OS_LOG_TYPE_INFO: u8 = 0x01;
+    pub(super) const OS_LOG_TYPE_DEBUG: u8 = 0x02;
+    pub(super) const OS_LOG_TYPE_ERROR: u8 = 0x10;
+
+    extern "C" {
+        static __dso_handle: c_void;
+
+        fn os_log_create(subsystem: *const c_char, category: *const c_char) -> *mut c_void;
+
+        fn _os_log_impl(
+            dso: *const c_void,
+            log: *mut c_void,
+            log_type: u8,
+            format: *const c_char,
+            buf: *const u8,
+            size: u32,
+        );
+    }
+
+    /// A shared `os_log_t` object.
+    ///
+    /// `os_log_create` returns a reference to an object that lives for the
+    /// remainder of the process and may be used concurrently from any
+    /// thread, so this is a plain copyable pointer.
+    #[derive(Debug, Clone, Copy)]
+    pub(super) struct OsLog(*mut c_void);
+
+    unsafe impl Send for OsLog {}
+    unsafe impl Sync for OsLog {}
+
+    impl OsLog {
+        pub(super) fn create(subsystem: &str, category: &str) -> Self {
+            let subsystem = CString::new(subsystem.replace('\0', "")).expect("NULs are removed");
+            let category = CString::new(category.replace('\0', "")).expect("NULs are removed");
+            Self(unsafe { os_log_create(subsystem.as_ptr(), category.as_ptr()) })
+        }
+
+        /// Logs `line` as the sole argument of a `%{public}s` (or
+        /// `%{private}s`) format string, using the serialized argument
+        /// buffer layout expected by `_os_log_impl`.
+        pub(super) fn write(&self, log_type: u8, private: bool, line: &str) -> io::Result<()> {
+            let line =
+                CString::new(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let (format, header, descriptor): (&[u8], u8, u8) = if private {
+                // `0x01` marks the buffer as containing private items, and
+                // `0x01` in the descriptor's low nibble marks the argument
+                // itself as private.
+                (b"%{private}s\0", 0x03, 0x21)
+            } else {
+                // `0x02` marks the buffer as containing non-scalar items,
+                // and `0x22` describes a public string argument.
+                (b"%{public}s\0", 0x02, 0x22)
+            };
+            let mut buf = [0u8; 12];
+            buf[0] = header;
+            buf[1] = 1; // one argument
+            buf[2] = descriptor;
+            buf[3] = std::mem::size_of::<usize>() as u8;
+            buf[4..].copy_from_slice(&(line.as_ptr() as usize as u64).to_ne_bytes());
+            unsafe {
+                _os_log_impl(
+                    &__dso_handle,
+                    self.0,
+                    log_type,
+                    format.as_ptr().cast(),
+                    buf.as_ptr(),
+                    buf.len() as u32,
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
 // === blanket impls ===
 
 impl<'a, M> MakeWriterExt<'a> for M where M: MakeWriter<'a> {}
@@ -1391,4 +2505,197 @@ mod test {
         has_lines(&a_buf, &lines[..]);
         has_lines(&b_buf, &lines[..]);
     }
+
+    #[test]
+    fn field_router_routes_by_field_value() {
+        let a_buf = Arc::new(Mutex::new(Vec::new()));
+        let b_buf = Arc::new(Mutex::new(Vec::new()));
+        let other_buf = Arc::new(Mutex::new(Vec::new()));
+
+        let make_writer = {
+            let (a_buf, b_buf, other_buf) = (a_buf.clone(), b_buf.clone(), other_buf.clone());
+            FieldRouter::new("tenant", move |tenant| match tenant {
+                Some("a") => MockWriter::new(a_buf.clone()),
+                Some(_) => MockWriter::new(b_buf.clone()),
+                None => MockWriter::new(other_buf.clone()),
+            })
+        };
+
+        let c = {
+            #[cfg(feature = "ansi")]
+            let f = Format::default().without_time().with_ansi(false);
+            #[cfg(not(feature = "ansi"))]
+            let f = Format::default().without_time();
+            Collector::builder()
+                .event_format(f)
+                .with_writer(make_writer)
+                .with_max_level(Level::TRACE)
+                .finish()
+        };
+
+        let _s = tracing::collect::set_default(c);
+        info!(tenant = "a", "for tenant a");
+        info!(tenant = 42, "for the answer");
+        info!("unrouted");
+
+        has_lines(&a_buf, &[(Level::INFO, "for tenant a tenant=\"a\"")]);
+        has_lines(&b_buf, &[(Level::INFO, "for the answer tenant=42")]);
+        has_lines(&other_buf, &[(Level::INFO, "unrouted")]);
+    }
+
+    #[test]
+    fn field_router_caches_writers_per_key() {
+        use std::sync::atomic::AtomicUsize;
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let make_writer = {
+            let (buf, calls) = (buf.clone(), calls.clone());
+            FieldRouter::new("tenant", move |_| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                MockWriter::new(buf.clone())
+            })
+        };
+
+        let c = {
+            #[cfg(feature = "ansi")]
+            let f = Format::default().without_time().with_ansi(false);
+            #[cfg(not(feature = "ansi"))]
+            let f = Format::default().without_time();
+            Collector::builder()
+                .event_format(f)
+                .with_writer(make_writer)
+                .with_max_level(Level::TRACE)
+                .finish()
+        };
+
+        let _s = tracing::collect::set_default(c);
+        info!(tenant = "a", "one");
+        info!(tenant = "a", "two");
+        info!(tenant = "b", "three");
+
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    /// A writer that fails every write while its `healthy` flag is unset.
+    struct FlakyWriter {
+        healthy: Arc<AtomicBool>,
+        buf: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl io::Write for FlakyWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.healthy.load(Ordering::Relaxed) {
+                self.buf.lock().unwrap().write(buf)
+            } else {
+                Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+            }
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn flaky_writer(
+        healthy: &Arc<AtomicBool>,
+        buf: &Arc<Mutex<Vec<u8>>>,
+    ) -> impl Fn() -> FlakyWriter {
+        let (healthy, buf) = (healthy.clone(), buf.clone());
+        move || FlakyWriter {
+            healthy: healthy.clone(),
+            buf: buf.clone(),
+        }
+    }
+
+    #[test]
+    fn fallback_fails_over_and_recovers() {
+        let healthy = Arc::new(AtomicBool::new(true));
+        let primary_buf = Arc::new(Mutex::new(Vec::new()));
+        let fallback_buf = Arc::new(Mutex::new(Vec::new()));
+
+        let make_writer = flaky_writer(&healthy, &primary_buf)
+            .with_fallback(MockMakeWriter::new(fallback_buf.clone()))
+            // Retry the primary writer on every event.
+            .retry_after(Duration::from_millis(0));
+
+        let c = {
+            #[cfg(feature = "ansi")]
+            let f = Format::default().without_time().with_ansi(false);
+            #[cfg(not(feature = "ansi"))]
+            let f = Format::default().without_time();
+            Collector::builder()
+                .event_format(f)
+                .with_writer(make_writer)
+                .finish()
+        };
+
+        let _s = tracing::collect::set_default(c);
+        info!("healthy");
+        healthy.store(false, Ordering::Relaxed);
+        info!("failing");
+        info!("still failing");
+        healthy.store(true, Ordering::Relaxed);
+        info!("recovered");
+
+        let primary = String::from_utf8(primary_buf.try_lock().unwrap().to_vec()).unwrap();
+        assert!(primary.contains("healthy"), "primary was: {:?}", primary);
+        assert!(primary.contains("recovered"), "primary was: {:?}", primary);
+
+        let fallback = String::from_utf8(fallback_buf.try_lock().unwrap().to_vec()).unwrap();
+        assert!(fallback.contains("failing"), "fallback was: {:?}", fallback);
+        assert!(
+            fallback.contains("still failing"),
+            "fallback was: {:?}",
+            fallback
+        );
+        // The diagnostic is only emitted for the first of the two failures.
+        assert_eq!(
+            fallback.matches("falling back").count(),
+            1,
+            "fallback was: {:?}",
+            fallback
+        );
+    }
+
+    #[test]
+    fn fallback_waits_before_retrying_primary() {
+        let healthy = Arc::new(AtomicBool::new(false));
+        let primary_buf = Arc::new(Mutex::new(Vec::new()));
+        let fallback_buf = Arc::new(Mutex::new(Vec::new()));
+
+        let make_writer = flaky_writer(&healthy, &primary_buf)
+            .with_fallback(MockMakeWriter::new(fallback_buf.clone()))
+            .retry_after(Duration::from_secs(60));
+
+        let c = {
+            #[cfg(feature = "ansi")]
+            let f = Format::default().without_time().with_ansi(false);
+            #[cfg(not(feature = "ansi"))]
+            let f = Format::default().without_time();
+            Collector::builder()
+                .event_format(f)
+                .with_writer(make_writer)
+                .finish()
+        };
+
+        let _s = tracing::collect::set_default(c);
+        info!("failing");
+        // Even though the primary writer is healthy again, it is not retried
+        // until the retry interval has elapsed.
+        healthy.store(true, Ordering::Relaxed);
+        info!("too soon");
+
+        let primary = String::from_utf8(primary_buf.try_lock().unwrap().to_vec()).unwrap();
+        assert!(primary.is_empty(), "primary was: {:?}", primary);
+
+        let fallback = String::from_utf8(fallback_buf.try_lock().unwrap().to_vec()).unwrap();
+        assert!(fallback.contains("failing"), "fallback was: {:?}", fallback);
+        assert!(
+            fallback.contains("too soon"),
+            "fallback was: {:?}",
+            fallback
+        );
+    }
 }
@@ -0,0 +1,720 @@
+//! Writes structured entries to [Google Cloud Logging].
+//!
+//! Cloud Logging is GCP's hosted log store: structured entries are written
+//! to a *log* within a project with the `entries:write` API, tagged with a
+//! *monitored resource* descriptor that tells GCP what produced them. On
+//! GKE and GCE this is usually delegated to a sidecar or node agent that
+//! scrapes stdout; this module instead provides a [`Subscriber`] that
+//! writes entries directly, using only the standard library.
+//!
+//! Each event becomes one entry: its level is mapped to a Cloud Logging
+//! severity, its message and fields become the `jsonPayload`, and the
+//! `trace` and `spanId` correlation fields are derived from the span
+//! hierarchy so that the Logs Explorer can group entries by request.
+//! Entries are buffered and written in batches from a background thread.
+//!
+//! # Limitations
+//!
+//! - Connections are plaintext HTTP without TLS, so entries must go
+//!   through a local proxy or emulator rather than directly to
+//!   `logging.googleapis.com`.
+//! - Authentication is a static bearer token supplied at build time;
+//!   tokens are not refreshed from the metadata server.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{gcp_logging, prelude::*};
+//!
+//! let writer = gcp_logging::Subscriber::builder()
+//!     .with_endpoint("127.0.0.1:8080")
+//!     .with_project("my-project")
+//!     .with_log_id("my-service")
+//!     .with_resource("gce_instance")
+//!     .with_resource_label("instance_id", "12345")
+//!     .with_access_token("ya29.token")
+//!     .finish()
+//!     .expect("failed to start the Cloud Logging writer");
+//! let collector = tracing_subscriber::registry().with(writer);
+//! # let _ = collector;
+//! ```
+//!
+//! [Google Cloud Logging]: https://cloud.google.com/logging/docs
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, SystemTime},
+};
+use tracing_core::{field, span, Collect, Event, Level};
+
+/// A [`Subscribe`] implementation that writes entries to Cloud Logging.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    // The standard library's channel sender is not `Sync` on our minimum
+    // supported Rust version, so sends are serialized through a mutex.
+    sender: Mutex<mpsc::Sender<String>>,
+    project: String,
+}
+
+/// Configures a Cloud Logging [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    endpoint: String,
+    project: String,
+    log_id: String,
+    resource_type: String,
+    resource_labels: Vec<(String, String)>,
+    access_token: Option<String>,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+/// The trace identity of an open span, stored in its extensions for entry
+/// correlation.
+struct GcpTrace {
+    /// 32 lowercase hex characters, shared by every span in the trace.
+    trace_id: String,
+    /// 16 lowercase hex characters, unique to this span.
+    span_id: String,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a [`Builder`] for configuring a Cloud Logging writer.
+    pub fn builder() -> Builder {
+        Builder {
+            endpoint: "127.0.0.1:8080".to_owned(),
+            project: String::new(),
+            log_id: "tracing".to_owned(),
+            resource_type: "global".to_owned(),
+            resource_labels: Vec::new(),
+            access_token: None,
+            batch_size: 512,
+            batch_timeout: Duration::from_secs(5),
+            max_retries: 3,
+        }
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+
+        let parent = if attrs.is_root() {
+            None
+        } else if attrs.is_contextual() {
+            ctx.lookup_current()
+        } else {
+            attrs.parent().and_then(|id| ctx.span(id))
+        };
+        let trace_id = match parent {
+            Some(parent) => match parent.extensions().get::<GcpTrace>() {
+                Some(parent) => parent.trace_id.clone(),
+                None => hex_id::<16>(),
+            },
+            None => hex_id::<16>(),
+        };
+        span.extensions_mut().insert(GcpTrace {
+            trace_id,
+            span_id: hex_id::<8>(),
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let metadata = event.metadata();
+        let mut entry = String::from("{\"severity\":\"");
+        entry.push_str(severity(metadata.level()));
+        entry.push_str("\",\"timestamp\":\"");
+        let _ = write!(entry, "{}", format_timestamp(unix_millis()));
+        entry.push_str("\",\"jsonPayload\":{\"target\":\"");
+        escape_into(&mut entry, metadata.target());
+        entry.push('"');
+        event.record(&mut PayloadVisitor { json: &mut entry });
+        entry.push('}');
+        if let Some(span) = ctx.event_span(event) {
+            let extensions = span.extensions();
+            if let Some(trace) = extensions.get::<GcpTrace>() {
+                let _ = write!(
+                    entry,
+                    ",\"trace\":\"projects/{}/traces/{}\",\"spanId\":\"{}\"",
+                    self.project, trace.trace_id, trace.span_id,
+                );
+            }
+        }
+        entry.push('}');
+
+        // The only send error is a disconnected worker; entries are dropped
+        // in that case, as there is nowhere to write them to.
+        let _ = self
+            .sender
+            .lock()
+            .expect("gcp_logging sender poisoned")
+            .send(entry);
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the `host:port` of the Cloud Logging endpoint or proxy.
+    ///
+    /// The default is `127.0.0.1:8080`. Any `http://` prefix and trailing
+    /// slash are ignored.
+    pub fn with_endpoint(self, endpoint: impl Into<String>) -> Self {
+        let mut endpoint = endpoint.into();
+        if let Some(stripped) = endpoint.strip_prefix("http://") {
+            endpoint = stripped.to_owned();
+        }
+        if let Some(stripped) = endpoint.strip_suffix('/') {
+            endpoint = stripped.to_owned();
+        }
+        Self { endpoint, ..self }
+    }
+
+    /// Sets the GCP project entries are written to.
+    ///
+    /// This is required, and [`finish`](Self::finish) fails without it.
+    pub fn with_project(self, project: impl Into<String>) -> Self {
+        Self {
+            project: project.into(),
+            ..self
+        }
+    }
+
+    /// Sets the log ID within the project.
+    ///
+    /// The default is `tracing`, giving the log name
+    /// `projects/<project>/logs/tracing`.
+    pub fn with_log_id(self, log_id: impl Into<String>) -> Self {
+        Self {
+            log_id: log_id.into(),
+            ..self
+        }
+    }
+
+    /// Sets the monitored resource type entries are tagged with, such as
+    /// `gce_instance` or `k8s_container`.
+    ///
+    /// The default is `global`.
+    pub fn with_resource(self, resource_type: impl Into<String>) -> Self {
+        Self {
+            resource_type: resource_type.into(),
+            ..self
+        }
+    }
+
+    /// Adds a label to the monitored resource descriptor, such as
+    /// `instance_id` for `gce_instance` resources.
+    pub fn with_resource_label(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.resource_labels.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the OAuth bearer token sent with every request.
+    ///
+    /// Without one, requests carry no `Authorization` header, which only a
+    /// local emulator will accept.
+    pub fn with_access_token(self, access_token: impl Into<String>) -> Self {
+        Self {
+            access_token: Some(access_token.into()),
+            ..self
+        }
+    }
+
+    /// Sets when a batch is written: once `batch_size` entries have
+    /// gathered, or `batch_timeout` after the first one arrives.
+    ///
+    /// The defaults are 512 entries and 5 seconds.
+    pub fn batching(self, batch_size: usize, batch_timeout: Duration) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            batch_timeout,
+            ..self
+        }
+    }
+
+    /// Sets how many times a failed batch is retried before being dropped.
+    ///
+    /// Retries back off exponentially, starting at 100 milliseconds. The
+    /// default is 3 retries.
+    pub fn with_max_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Returns the configured writer [`Subscriber`], spawning its write
+    /// thread.
+    ///
+    /// Fails if no project was configured. The thread runs until the
+    /// `Subscriber` is dropped; any entries still buffered at that point
+    /// are written before it exits.
+    pub fn finish(self) -> io::Result<Subscriber> {
+        if self.project.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a GCP project is required",
+            ));
+        }
+
+        let mut prefix = String::from("{\"logName\":\"projects/");
+        escape_into(&mut prefix, &self.project);
+        prefix.push_str("/logs/");
+        escape_into(&mut prefix, &self.log_id);
+        prefix.push_str("\",\"resource\":{\"type\":\"");
+        escape_into(&mut prefix, &self.resource_type);
+        prefix.push('"');
+        if !self.resource_labels.is_empty() {
+            prefix.push_str(",\"labels\":{");
+            for (i, (name, value)) in self.resource_labels.iter().enumerate() {
+                if i != 0 {
+                    prefix.push(',');
+                }
+                prefix.push('"');
+                escape_into(&mut prefix, name);
+                prefix.push_str("\":\"");
+                escape_into(&mut prefix, value);
+                prefix.push('"');
+            }
+            prefix.push('}');
+        }
+        prefix.push_str("},\"entries\":[");
+
+        let (sender, receiver) = mpsc::channel();
+        let worker = Worker {
+            endpoint: self.endpoint,
+            prefix,
+            access_token: self.access_token,
+            batch_size: self.batch_size,
+            batch_timeout: self.batch_timeout,
+            max_retries: self.max_retries,
+        };
+        thread::Builder::new()
+            .name("tracing-gcp-logging".into())
+            .spawn(move || worker.run(receiver))?;
+        Ok(Subscriber {
+            sender: Mutex::new(sender),
+            project: self.project,
+        })
+    }
+}
+
+// === impl Worker ===
+
+/// The write thread: gathers entries into batches and posts them.
+struct Worker {
+    endpoint: String,
+    /// The request body up to and including the opening of the `entries`
+    /// array, rendered once from the log name and resource descriptor.
+    prefix: String,
+    access_token: Option<String>,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+impl Worker {
+    fn run(&self, receiver: mpsc::Receiver<String>) {
+        let mut batch = Vec::new();
+        loop {
+            match receiver.recv_timeout(self.batch_timeout) {
+                Ok(entry) => {
+                    batch.push(entry);
+                    if batch.len() >= self.batch_size {
+                        self.write(&mut batch);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => self.write(&mut batch),
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    self.write(&mut batch);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Writes the batch, retrying with exponential backoff; the batch is
+    /// cleared either way.
+    fn write(&self, batch: &mut Vec<String>) {
+        if batch.is_empty() {
+            return;
+        }
+        let mut body = self.prefix.clone();
+        for (i, entry) in batch.iter().enumerate() {
+            if i != 0 {
+                body.push(',');
+            }
+            body.push_str(entry);
+        }
+        body.push_str("]}");
+        batch.clear();
+
+        let mut backoff = Duration::from_millis(100);
+        for attempt in 0..=self.max_retries {
+            if self.post(&body).is_ok() {
+                return;
+            }
+            if attempt < self.max_retries {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+    }
+
+    /// Posts one batch, returning an error unless the server responds with
+    /// a success status.
+    fn post(&self, body: &str) -> io::Result<()> {
+        let mut stream = TcpStream::connect(&self.endpoint)?;
+        let mut request = format!(
+            "POST /v2/entries:write HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Content-Type: application/json\r\n",
+            self.endpoint,
+        );
+        if let Some(token) = &self.access_token {
+            let _ = write!(request, "Authorization: Bearer {}\r\n", token);
+        }
+        let _ = write!(
+            request,
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len(),
+        );
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body.as_bytes())?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        // "HTTP/1.1 200 OK" => "200"
+        let code = status.split_whitespace().nth(1).unwrap_or("");
+        if code.starts_with('2') {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Cloud Logging responded with status {:?}", status.trim()),
+            ))
+        }
+    }
+}
+
+/// Maps a `tracing` level to a Cloud Logging severity.
+fn severity(level: &Level) -> &'static str {
+    match *level {
+        Level::ERROR => "ERROR",
+        Level::WARN => "WARNING",
+        Level::INFO => "INFO",
+        _ => "DEBUG",
+    }
+}
+
+/// Returns the current wall-clock time in milliseconds since the Unix
+/// epoch.
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Formats a Unix millisecond timestamp as RFC 3339, such as
+/// `2026-08-29T13:14:15.678Z`.
+fn format_timestamp(millis: u64) -> String {
+    let (year, month, day) = civil_date(millis / 86_400_000);
+    let rest = millis % 86_400_000;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rest / 3_600_000,
+        rest / 60_000 % 60,
+        rest / 1000 % 60,
+        rest % 1000,
+    )
+}
+
+/// Converts days since the Unix epoch to a `(year, month, day)` civil
+/// date.
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let (year, month) = if month < 10 {
+        (year_of_era + era * 400, month + 3)
+    } else {
+        (year_of_era + era * 400 + 1, month - 9)
+    };
+    (year, month, day)
+}
+
+/// Returns `N` pseudo-random bytes as a lowercase hex string.
+///
+/// This uses the standard library's randomly-seeded hasher rather than a
+/// cryptographic RNG; IDs are unique in practice but not unpredictable.
+fn hex_id<const N: usize>() -> String {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+        sync::atomic::{AtomicU64, Ordering},
+    };
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut id = String::with_capacity(N * 2);
+    let mut remaining = N;
+    while remaining > 0 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        for byte in &hasher.finish().to_le_bytes()[..remaining.min(8)] {
+            let _ = write!(id, "{:02x}", byte);
+        }
+        remaining -= remaining.min(8);
+    }
+    id
+}
+
+/// Writes `value` into `out` with JSON string escaping.
+fn escape_into(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+/// Appends event fields to the `jsonPayload` object being built.
+struct PayloadVisitor<'a> {
+    json: &'a mut String,
+}
+
+impl PayloadVisitor<'_> {
+    fn member(&mut self, field: &field::Field) {
+        self.json.push_str(",\"");
+        escape_into(self.json, field.name());
+        self.json.push_str("\":");
+    }
+}
+
+impl field::Visit for PayloadVisitor<'_> {
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        if !value.is_finite() {
+            self.record_debug(field, &value);
+            return;
+        }
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.member(field);
+        self.json.push('"');
+        escape_into(self.json, value);
+        self.json.push('"');
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.member(field);
+        self.json.push('"');
+        escape_into(self.json, &format!("{:?}", value));
+        self.json.push('"');
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::net::TcpListener;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn severities_map_from_levels() {
+        assert_eq!(severity(&Level::ERROR), "ERROR");
+        assert_eq!(severity(&Level::WARN), "WARNING");
+        assert_eq!(severity(&Level::INFO), "INFO");
+        assert_eq!(severity(&Level::DEBUG), "DEBUG");
+        assert_eq!(severity(&Level::TRACE), "DEBUG");
+    }
+
+    #[test]
+    fn missing_projects_are_rejected() {
+        let error = Subscriber::builder()
+            .finish()
+            .expect_err("finish should require a project");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    /// Accepts one write and returns its request line, headers, and body.
+    fn accept_write(listener: &TcpListener) -> (String, String, String) {
+        let (stream, _) = listener.accept().expect("no write received");
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader
+            .read_line(&mut request_line)
+            .expect("failed to read request line");
+        let mut headers = String::new();
+        let mut content_length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).expect("failed to read header");
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            headers.push_str(header);
+            headers.push('\n');
+            if let Some(length) = header
+                .to_ascii_lowercase()
+                .strip_prefix("content-length:")
+                .map(str::trim)
+            {
+                content_length = length.parse().expect("invalid content-length");
+            }
+        }
+        let mut body = vec![0; content_length];
+        io::Read::read_exact(&mut reader, &mut body).expect("failed to read body");
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+            .expect("failed to respond");
+        (
+            request_line.trim().to_owned(),
+            headers,
+            String::from_utf8(body).expect("body was not UTF-8"),
+        )
+    }
+
+    /// Returns the value of the first `"key":"..."` member in `json`.
+    fn json_str(json: &str, key: &str) -> String {
+        let marker = format!("\"{}\":\"", key);
+        let start = json.find(&marker).expect("key not found") + marker.len();
+        json[start..]
+            .split('"')
+            .next()
+            .expect("unterminated value")
+            .to_owned()
+    }
+
+    #[test]
+    fn entries_write_with_trace_correlation() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let writer = Subscriber::builder()
+            .with_endpoint(format!("http://{}/", addr))
+            .with_project("test-project")
+            .with_log_id("write-test")
+            .with_resource("gce_instance")
+            .with_resource_label("instance_id", "12345")
+            .with_access_token("test-token")
+            .batching(2, Duration::from_secs(30))
+            .finish()
+            .expect("failed to start writer");
+        let collector = crate::registry().with(writer);
+
+        with_default(collector, || {
+            let span = tracing::info_span!("handle_request");
+            let _entered = span.enter();
+            tracing::info!(user = "ferris", attempts = 2, "logged in");
+            tracing::warn!("token expires soon");
+        });
+
+        let (request_line, headers, body) = accept_write(&listener);
+        assert_eq!(request_line, "POST /v2/entries:write HTTP/1.1");
+        assert!(
+            headers.contains("Authorization: Bearer test-token"),
+            "missing bearer token: {}",
+            headers,
+        );
+        assert!(body.contains("\"logName\":\"projects/test-project/logs/write-test\""));
+        assert!(body.contains(
+            "\"resource\":{\"type\":\"gce_instance\",\"labels\":{\"instance_id\":\"12345\"}}"
+        ));
+        assert!(body.contains("\"severity\":\"INFO\""));
+        assert!(body.contains("\"severity\":\"WARNING\""));
+        assert!(body.contains("\"message\":\"logged in\""));
+        assert!(body.contains("\"user\":\"ferris\""));
+        assert!(body.contains("\"attempts\":2"));
+
+        let trace = json_str(&body, "trace");
+        let prefix = "projects/test-project/traces/";
+        assert!(trace.starts_with(prefix), "unexpected trace: {}", trace);
+        assert_eq!(trace.len(), prefix.len() + 32);
+        assert_eq!(json_str(&body, "spanId").len(), 16);
+        // Both entries came from the same span, and so share a trace.
+        assert_eq!(body.matches(&trace).count(), 2);
+    }
+
+    #[test]
+    fn events_outside_spans_have_no_trace() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let writer = Subscriber::builder()
+            .with_endpoint(addr.to_string())
+            .with_project("test-project")
+            .batching(1, Duration::from_secs(30))
+            .finish()
+            .expect("failed to start writer");
+        let collector = crate::registry().with(writer);
+
+        with_default(collector, || {
+            tracing::info!("standalone");
+        });
+
+        let (_, headers, body) = accept_write(&listener);
+        assert!(!headers.contains("Authorization"), "unexpected auth header");
+        assert!(body.contains("\"message\":\"standalone\""));
+        assert!(!body.contains("\"trace\":"), "unexpected trace: {}", body);
+    }
+}
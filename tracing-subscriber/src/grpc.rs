@@ -0,0 +1,1303 @@
+//! Streams events to gRPC clients over a server-streaming RPC.
+//!
+//! This module provides a [`Subscriber`] that serves the following gRPC
+//! service, so remote debugging tools and sidecars can subscribe to a
+//! process' telemetry without the process embedding a gRPC stack:
+//!
+//! ```text
+//! package tracing;
+//!
+//! service TraceStream {
+//!   // Streams events matching the request's filter until the client
+//!   // disconnects.
+//!   rpc Watch(WatchRequest) returns (stream Event);
+//! }
+//!
+//! message WatchRequest {
+//!   // Comma-separated `target=level` directives, as accepted by the
+//!   // `Targets` filter; empty subscribes to everything.
+//!   string filter = 1;
+//! }
+//!
+//! message Event {
+//!   uint64 timestamp_millis = 1;
+//!   uint32 level = 2; // 1 = ERROR .. 5 = TRACE
+//!   string target = 3;
+//!   string message = 4;
+//!   repeated Field fields = 5;
+//! }
+//!
+//! message Field {
+//!   string name = 1;
+//!   string value = 2;
+//! }
+//! ```
+//!
+//! For example, using [`grpcurl`]:
+//!
+//! ```text
+//! $ grpcurl -plaintext -d '{"filter": "info,my_crate=debug"}' \
+//!       localhost:6671 tracing.TraceStream/Watch
+//! ```
+//!
+//! The filter is applied per connection, so each client chooses what it
+//! receives. Backpressure is handled with a bounded queue per client:
+//! when a client falls behind, events are dropped *for that client*
+//! rather than buffered without bound or allowed to stall the traced
+//! program.
+//!
+//! Like the [`otlp`] module, this module speaks the wire protocol
+//! directly rather than pulling in a gRPC stack: it implements the
+//! subset of HTTP/2 and HPACK that gRPC clients need. It serves
+//! plaintext (`http`) connections only.
+//!
+//! # Security
+//!
+//! The endpoint performs no authentication; anyone who can connect to it
+//! can read the process' tracing output. Bind it to a loopback address,
+//! never to a publicly reachable one.
+//!
+//! [`grpcurl`]: https://github.com/fullstorydev/grpcurl
+//! [`otlp`]: mod@crate::otlp
+use crate::{
+    filter::Targets,
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{sync_channel, SyncSender, TrySendError},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::SystemTime,
+};
+use tracing_core::{field, Collect, Event, Level};
+
+/// The RPC this module serves.
+const WATCH_PATH: &str = "/tracing.TraceStream/Watch";
+
+/// How many events may be queued per client before events are dropped
+/// for that client.
+const QUEUE_CAPACITY: usize = 64;
+
+/// The client connection preface (RFC 9113, section 3.4).
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// The default HTTP/2 frame size limit, also used as our write chunk
+/// size; peers may not lower it, so chunks of this size are always legal.
+const MAX_FRAME_LEN: usize = 16_384;
+
+/// The largest frame payload accepted from a peer.
+const MAX_ACCEPTED_FRAME_LEN: usize = 1024 * 1024;
+
+const FRAME_DATA: u8 = 0x0;
+const FRAME_HEADERS: u8 = 0x1;
+const FRAME_RST_STREAM: u8 = 0x3;
+const FRAME_SETTINGS: u8 = 0x4;
+const FRAME_PING: u8 = 0x6;
+const FRAME_GOAWAY: u8 = 0x7;
+const FRAME_WINDOW_UPDATE: u8 = 0x8;
+const FRAME_CONTINUATION: u8 = 0x9;
+
+const FLAG_END_STREAM: u8 = 0x1;
+const FLAG_ACK: u8 = 0x1;
+const FLAG_END_HEADERS: u8 = 0x4;
+const FLAG_PADDED: u8 = 0x8;
+const FLAG_PRIORITY: u8 = 0x20;
+
+/// A [`Subscribe`] implementation that streams events to subscribed gRPC
+/// clients.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    shared: Arc<Shared>,
+}
+
+/// A running gRPC endpoint.
+///
+/// This is returned by [`serve`]. The endpoint is served on background
+/// threads, which run until the process exits; dropping the `Server`
+/// does not shut it down.
+#[derive(Debug)]
+pub struct Server {
+    addr: Option<SocketAddr>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    clients: Mutex<Vec<Client>>,
+    next_client: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Client {
+    id: u64,
+    filter: Option<Targets>,
+    queue: SyncSender<Arc<Outgoing>>,
+}
+
+/// One encoded event, shared between the queues of all subscribed
+/// clients.
+#[derive(Debug)]
+struct Outgoing {
+    level: Level,
+    target: String,
+    encoded: Vec<u8>,
+}
+
+/// Starts serving the gRPC endpoint on a TCP socket bound to `addr`,
+/// returning the subscriber that feeds it.
+///
+/// The returned [`Server`]'s [`local_addr`] method returns the address
+/// the listener was actually bound to, which is useful when binding to
+/// port 0.
+///
+/// [`local_addr`]: Server::local_addr
+pub fn serve(addr: impl ToSocketAddrs) -> io::Result<(Subscriber, Server)> {
+    let listener = TcpListener::bind(addr)?;
+    let addr = listener.local_addr().ok();
+    let shared = Arc::new(Shared::default());
+    let accept_shared = shared.clone();
+    thread::Builder::new()
+        .name("tracing-grpc".into())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let shared = accept_shared.clone();
+                let _ = thread::Builder::new()
+                    .name("tracing-grpc-conn".into())
+                    .spawn(move || {
+                        let _ = handle_connection(&shared, stream);
+                    });
+            }
+        })?;
+    Ok((Subscriber { shared }, Server { addr }))
+}
+
+// === impl Server ===
+
+impl Server {
+    /// Returns the local address of the TCP listener.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+}
+
+// === impl Subscriber ===
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        let mut clients = self.shared.clients.lock().expect("grpc lock poisoned");
+        if clients.is_empty() {
+            return;
+        }
+        let metadata = event.metadata();
+        let outgoing = Arc::new(Outgoing {
+            level: *metadata.level(),
+            target: metadata.target().to_owned(),
+            encoded: encode_event(event),
+        });
+        clients.retain(|client| {
+            let enabled = client
+                .filter
+                .as_ref()
+                .map(|filter| filter.would_enable(&outgoing.target, &outgoing.level))
+                .unwrap_or(true);
+            if !enabled {
+                return true;
+            }
+            match client.queue.try_send(outgoing.clone()) {
+                // A full queue means the client is falling behind; the
+                // event is dropped for that client only.
+                Ok(()) | Err(TrySendError::Full(_)) => true,
+                Err(TrySendError::Disconnected(_)) => false,
+            }
+        });
+    }
+}
+
+// === impl Shared ===
+
+impl Shared {
+    fn remove_client(&self, id: u64) {
+        self.clients
+            .lock()
+            .expect("grpc lock poisoned")
+            .retain(|client| client.id != id);
+    }
+}
+
+/// The write half of one HTTP/2 connection, shared between the reader
+/// thread and the per-stream writer threads.
+#[derive(Debug)]
+struct Connection {
+    writer: Mutex<TcpStream>,
+    flow: Mutex<Flow>,
+    flow_changed: Condvar,
+}
+
+/// Send-side flow-control windows: the connection-level window, and one per
+/// live stream. A stream missing from the map has been closed.
+#[derive(Debug)]
+struct Flow {
+    connection: i64,
+    streams: HashMap<u32, i64>,
+    initial: i64,
+}
+
+// === impl Connection ===
+
+impl Connection {
+    fn new(stream: TcpStream) -> Self {
+        Self {
+            writer: Mutex::new(stream),
+            flow: Mutex::new(Flow {
+                connection: 65_535,
+                streams: HashMap::new(),
+                initial: 65_535,
+            }),
+            flow_changed: Condvar::new(),
+        }
+    }
+
+    /// Writes one frame.
+    fn write_frame(
+        &self,
+        frame_type: u8,
+        flags: u8,
+        stream_id: u32,
+        payload: &[u8],
+    ) -> io::Result<()> {
+        let mut frame = Vec::with_capacity(9 + payload.len());
+        frame.extend_from_slice(&(payload.len() as u32).to_be_bytes()[1..]);
+        frame.push(frame_type);
+        frame.push(flags);
+        frame.extend_from_slice(&stream_id.to_be_bytes());
+        frame.extend_from_slice(payload);
+        self.writer
+            .lock()
+            .expect("grpc lock poisoned")
+            .write_all(&frame)
+    }
+
+    /// Writes `data` on a stream as DATA frames, blocking until the
+    /// peer's flow-control windows allow it. Fails if the stream closes.
+    fn send_data(&self, stream_id: u32, data: &[u8]) -> io::Result<()> {
+        for chunk in data.chunks(MAX_FRAME_LEN) {
+            self.reserve_window(stream_id, chunk.len() as i64)?;
+            self.write_frame(FRAME_DATA, 0, stream_id, chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Blocks until both the connection and stream windows cover `len`,
+    /// then consumes that much.
+    fn reserve_window(&self, stream_id: u32, len: i64) -> io::Result<()> {
+        let mut flow = self.flow.lock().expect("grpc lock poisoned");
+        loop {
+            let stream = match flow.streams.get(&stream_id) {
+                Some(window) => *window,
+                None => return Err(io::Error::new(io::ErrorKind::BrokenPipe, "stream closed")),
+            };
+            if flow.connection >= len && stream >= len {
+                flow.connection -= len;
+                *flow.streams.get_mut(&stream_id).expect("checked above") -= len;
+                return Ok(());
+            }
+            flow = self.flow_changed.wait(flow).expect("grpc lock poisoned");
+        }
+    }
+
+    /// Closes a stream, waking any writer blocked on its window.
+    fn close_stream(&self, stream_id: u32) {
+        self.flow
+            .lock()
+            .expect("grpc lock poisoned")
+            .streams
+            .remove(&stream_id);
+        self.flow_changed.notify_all();
+    }
+}
+
+/// Per-stream request state accumulated by the connection reader.
+#[derive(Debug, Default)]
+struct StreamBuf {
+    header_block: Vec<u8>,
+    path: Option<String>,
+    headers_done: bool,
+    body: Vec<u8>,
+    ended: bool,
+    dispatched: bool,
+}
+
+/// Runs the server side of one connection: the preface and settings
+/// exchange, then the frame loop.
+fn handle_connection(shared: &Arc<Shared>, stream: TcpStream) -> io::Result<()> {
+    let mut reader = stream.try_clone()?;
+    let conn = Arc::new(Connection::new(stream));
+    let result = serve_connection(shared, &conn, &mut reader);
+
+    // Wake writers and drop this connection's clients, so their queue
+    // senders disconnect and the per-stream threads exit.
+    let ids: Vec<u32> = conn
+        .flow
+        .lock()
+        .expect("grpc lock poisoned")
+        .streams
+        .keys()
+        .copied()
+        .collect();
+    for id in ids {
+        conn.close_stream(id);
+    }
+    result
+}
+
+fn serve_connection(
+    shared: &Arc<Shared>,
+    conn: &Arc<Connection>,
+    reader: &mut TcpStream,
+) -> io::Result<()> {
+    let mut preface = [0u8; 24];
+    reader.read_exact(&mut preface)?;
+    if preface != *PREFACE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an HTTP/2 connection preface",
+        ));
+    }
+    conn.write_frame(FRAME_SETTINGS, 0, 0, &[])?;
+
+    let mut decoder = HeaderDecoder::default();
+    let mut streams: HashMap<u32, StreamBuf> = HashMap::new();
+    loop {
+        let mut header = [0u8; 9];
+        reader.read_exact(&mut header)?;
+        let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+        let frame_type = header[3];
+        let flags = header[4];
+        let stream_id =
+            u32::from_be_bytes([header[5], header[6], header[7], header[8]]) & 0x7fff_ffff;
+        if len > MAX_ACCEPTED_FRAME_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame too large",
+            ));
+        }
+        let mut payload = vec![0u8; len];
+        reader.read_exact(&mut payload)?;
+
+        match frame_type {
+            FRAME_SETTINGS if flags & FLAG_ACK == 0 => {
+                apply_settings(conn, &payload);
+                conn.write_frame(FRAME_SETTINGS, FLAG_ACK, 0, &[])?;
+            }
+            FRAME_PING if flags & FLAG_ACK == 0 => {
+                conn.write_frame(FRAME_PING, FLAG_ACK, 0, &payload)?;
+            }
+            FRAME_WINDOW_UPDATE if payload.len() == 4 => {
+                let increment =
+                    (u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]])
+                        & 0x7fff_ffff) as i64;
+                let mut flow = conn.flow.lock().expect("grpc lock poisoned");
+                if stream_id == 0 {
+                    flow.connection += increment;
+                } else if let Some(window) = flow.streams.get_mut(&stream_id) {
+                    *window += increment;
+                }
+                drop(flow);
+                conn.flow_changed.notify_all();
+            }
+            FRAME_HEADERS => {
+                let buf = streams.entry(stream_id).or_default();
+                let fragment = strip_padding_and_priority(&payload, flags)?;
+                buf.header_block.extend_from_slice(fragment);
+                if flags & FLAG_END_STREAM != 0 {
+                    buf.ended = true;
+                }
+                if flags & FLAG_END_HEADERS != 0 {
+                    finish_headers(&mut decoder, buf)?;
+                }
+            }
+            FRAME_CONTINUATION => {
+                let buf = streams.entry(stream_id).or_default();
+                buf.header_block.extend_from_slice(&payload);
+                if flags & FLAG_END_HEADERS != 0 {
+                    finish_headers(&mut decoder, buf)?;
+                }
+            }
+            FRAME_DATA => {
+                let buf = streams.entry(stream_id).or_default();
+                let data = strip_padding_and_priority(&payload, flags & FLAG_PADDED)?;
+                buf.body.extend_from_slice(data);
+                if flags & FLAG_END_STREAM != 0 {
+                    buf.ended = true;
+                }
+            }
+            FRAME_RST_STREAM => {
+                streams.remove(&stream_id);
+                conn.close_stream(stream_id);
+            }
+            FRAME_GOAWAY => return Ok(()),
+            _ => {}
+        }
+
+        if let Some(buf) = streams.get_mut(&stream_id) {
+            if buf.ended && buf.headers_done && !buf.dispatched {
+                buf.dispatched = true;
+                dispatch(shared, conn, stream_id, buf)?;
+            }
+        }
+    }
+}
+
+/// Applies a peer SETTINGS payload; only `INITIAL_WINDOW_SIZE` affects
+/// this subset.
+fn apply_settings(conn: &Connection, payload: &[u8]) {
+    for setting in payload.chunks_exact(6) {
+        let id = u16::from_be_bytes([setting[0], setting[1]]);
+        let value = u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]]);
+        if id == 0x4 {
+            let mut flow = conn.flow.lock().expect("grpc lock poisoned");
+            let delta = value as i64 - flow.initial;
+            flow.initial = value as i64;
+            for window in flow.streams.values_mut() {
+                *window += delta;
+            }
+            drop(flow);
+            conn.flow_changed.notify_all();
+        }
+    }
+}
+
+/// Removes the padding and priority prologue from a HEADERS or DATA
+/// payload.
+fn strip_padding_and_priority(payload: &[u8], flags: u8) -> io::Result<&[u8]> {
+    let mut payload = payload;
+    let mut pad = 0;
+    if flags & FLAG_PADDED != 0 {
+        let (&first, rest) = payload
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "truncated frame"))?;
+        pad = first as usize;
+        payload = rest;
+    }
+    if flags & FLAG_PRIORITY != 0 {
+        if payload.len() < 5 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "truncated frame",
+            ));
+        }
+        payload = &payload[5..];
+    }
+    if pad > payload.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "bad padding"));
+    }
+    Ok(&payload[..payload.len() - pad])
+}
+
+/// Decodes a stream's accumulated header block and records its `:path`.
+fn finish_headers(decoder: &mut HeaderDecoder, buf: &mut StreamBuf) -> io::Result<()> {
+    let headers = decoder.decode(&buf.header_block)?;
+    buf.header_block = Vec::new();
+    buf.headers_done = true;
+    buf.path = headers
+        .into_iter()
+        .find(|(name, _)| name == ":path")
+        .map(|(_, value)| value);
+    Ok(())
+}
+
+/// Answers one complete request: either rejects it with a trailers-only
+/// response, or subscribes the stream and spawns its writer thread.
+fn dispatch(
+    shared: &Arc<Shared>,
+    conn: &Arc<Connection>,
+    stream_id: u32,
+    buf: &StreamBuf,
+) -> io::Result<()> {
+    if buf.path.as_deref() != Some(WATCH_PATH) {
+        // gRPC status 12: UNIMPLEMENTED.
+        return reject(conn, stream_id, "12", "unknown service or method");
+    }
+    let filter = match parse_watch_request(&buf.body) {
+        Some(directives) if directives.is_empty() => None,
+        Some(directives) => match directives.parse::<Targets>() {
+            Ok(targets) => Some(targets),
+            // gRPC status 3: INVALID_ARGUMENT.
+            Err(error) => return reject(conn, stream_id, "3", &error.to_string()),
+        },
+        None => return reject(conn, stream_id, "3", "malformed WatchRequest"),
+    };
+
+    conn.write_frame(
+        FRAME_HEADERS,
+        FLAG_END_HEADERS,
+        stream_id,
+        &encode_headers(&[(":status", "200"), ("content-type", "application/grpc")]),
+    )?;
+
+    {
+        let mut flow = conn.flow.lock().expect("grpc lock poisoned");
+        let initial = flow.initial;
+        flow.streams.insert(stream_id, initial);
+    }
+
+    let (sender, receiver) = sync_channel::<Arc<Outgoing>>(QUEUE_CAPACITY);
+    let id = shared.next_client.fetch_add(1, Ordering::Relaxed);
+    shared
+        .clients
+        .lock()
+        .expect("grpc lock poisoned")
+        .push(Client {
+            id,
+            filter,
+            queue: sender,
+        });
+
+    let shared = shared.clone();
+    let conn = conn.clone();
+    thread::Builder::new()
+        .name("tracing-grpc-stream".into())
+        .spawn(move || {
+            while let Ok(event) = receiver.recv() {
+                // Length-prefixed gRPC message framing: a compression
+                // flag byte, then the message length.
+                let mut message = Vec::with_capacity(5 + event.encoded.len());
+                message.push(0);
+                message.extend_from_slice(&(event.encoded.len() as u32).to_be_bytes());
+                message.extend_from_slice(&event.encoded);
+                if conn.send_data(stream_id, &message).is_err() {
+                    break;
+                }
+            }
+            shared.remove_client(id);
+        })?;
+    Ok(())
+}
+
+/// Sends a trailers-only error response.
+fn reject(conn: &Connection, stream_id: u32, status: &str, message: &str) -> io::Result<()> {
+    conn.write_frame(
+        FRAME_HEADERS,
+        FLAG_END_HEADERS | FLAG_END_STREAM,
+        stream_id,
+        &encode_headers(&[
+            (":status", "200"),
+            ("content-type", "application/grpc"),
+            ("grpc-status", status),
+            ("grpc-message", message),
+        ]),
+    )
+}
+
+/// Extracts the `filter` field from a length-prefixed `WatchRequest`.
+fn parse_watch_request(body: &[u8]) -> Option<String> {
+    if body.len() < 5 {
+        return None;
+    }
+    let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+    let mut message = body.get(5..5 + len)?;
+    let mut filter = String::new();
+    while !message.is_empty() {
+        let (key, rest) = decode_varint(message)?;
+        message = rest;
+        match (key >> 3, key & 0x7) {
+            (1, 2) => {
+                let (len, rest) = decode_varint(message)?;
+                let bytes = rest.get(..len as usize)?;
+                filter = String::from_utf8(bytes.to_vec()).ok()?;
+                message = &rest[len as usize..];
+            }
+            // Skip unknown fields by wire type.
+            (_, 0) => message = decode_varint(message)?.1,
+            (_, 2) => {
+                let (len, rest) = decode_varint(message)?;
+                message = rest.get(len as usize..)?;
+            }
+            (_, 5) => message = message.get(4..)?,
+            (_, 1) => message = message.get(8..)?,
+            _ => return None,
+        }
+    }
+    Some(filter)
+}
+
+fn decode_varint(buf: &[u8]) -> Option<(u64, &[u8])> {
+    let mut value = 0u64;
+    for (i, &byte) in buf.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, &buf[i + 1..]));
+        }
+    }
+    None
+}
+
+/// Encodes one event as a `tracing.Event` protobuf message.
+fn encode_event(event: &Event<'_>) -> Vec<u8> {
+    let mut visitor = EventVisitor::default();
+    event.record(&mut visitor);
+    let metadata = event.metadata();
+
+    let mut buf = Vec::new();
+    encode_varint_field(&mut buf, 1, unix_millis());
+    encode_varint_field(&mut buf, 2, level_value(metadata.level()));
+    encode_string(&mut buf, 3, metadata.target());
+    encode_string(&mut buf, 4, &visitor.message);
+    for (name, value) in &visitor.fields {
+        let mut field = Vec::new();
+        encode_string(&mut field, 1, name);
+        encode_string(&mut field, 2, value);
+        encode_message(&mut buf, 5, &field);
+    }
+    buf
+}
+
+fn level_value(level: &Level) -> u64 {
+    match *level {
+        Level::ERROR => 1,
+        Level::WARN => 2,
+        Level::INFO => 3,
+        Level::DEBUG => 4,
+        Level::TRACE => 5,
+    }
+}
+
+/// Returns the current time as milliseconds since the Unix epoch.
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Records an event's `message` and stringifies its other fields.
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl EventVisitor {
+    fn record(&mut self, field: &field::Field, value: String) {
+        if field.name() == "message" {
+            self.message = value;
+        } else {
+            self.fields.push((field.name().to_owned(), value));
+        }
+    }
+}
+
+impl field::Visit for EventVisitor {
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.record(field, value.to_owned());
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.record(field, format!("{:?}", value));
+    }
+}
+
+// Protobuf encoding primitives.
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+fn encode_key(buf: &mut Vec<u8>, field_number: u64, wire_type: u64) {
+    encode_varint(buf, (field_number << 3) | wire_type);
+}
+
+fn encode_varint_field(buf: &mut Vec<u8>, field_number: u64, value: u64) {
+    encode_key(buf, field_number, 0);
+    encode_varint(buf, value);
+}
+
+fn encode_string(buf: &mut Vec<u8>, field_number: u64, value: &str) {
+    encode_key(buf, field_number, 2);
+    encode_varint(buf, value.len() as u64);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+fn encode_message(buf: &mut Vec<u8>, field_number: u64, message: &[u8]) {
+    encode_key(buf, field_number, 2);
+    encode_varint(buf, message.len() as u64);
+    buf.extend_from_slice(message);
+}
+
+// HPACK (RFC 7541).
+
+/// Encodes response headers as literal fields without indexing, which
+/// needs no decoder state on the peer.
+fn encode_headers(headers: &[(&str, &str)]) -> Vec<u8> {
+    let mut block = Vec::new();
+    for (name, value) in headers {
+        block.push(0x00);
+        encode_hpack_string(&mut block, name);
+        encode_hpack_string(&mut block, value);
+    }
+    block
+}
+
+/// Encodes a string literal without Huffman coding.
+fn encode_hpack_string(block: &mut Vec<u8>, value: &str) {
+    encode_hpack_int(block, 0x00, 7, value.len() as u64);
+    block.extend_from_slice(value.as_bytes());
+}
+
+/// Encodes an integer with an N-bit prefix (RFC 7541, section 5.1).
+fn encode_hpack_int(block: &mut Vec<u8>, first: u8, prefix_bits: u8, mut value: u64) {
+    let max = (1u64 << prefix_bits) - 1;
+    if value < max {
+        block.push(first | value as u8);
+        return;
+    }
+    block.push(first | max as u8);
+    value -= max;
+    while value >= 0x80 {
+        block.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    block.push(value as u8);
+}
+
+/// The HPACK static table (RFC 7541, appendix A).
+const STATIC_TABLE: [(&str, &str); 61] = [
+    (":authority", ""),
+    (":method", "GET"),
+    (":method", "POST"),
+    (":path", "/"),
+    (":path", "/index.html"),
+    (":scheme", "http"),
+    (":scheme", "https"),
+    (":status", "200"),
+    (":status", "204"),
+    (":status", "206"),
+    (":status", "304"),
+    (":status", "400"),
+    (":status", "404"),
+    (":status", "500"),
+    ("accept-charset", ""),
+    ("accept-encoding", "gzip, deflate"),
+    ("accept-language", ""),
+    ("accept-ranges", ""),
+    ("accept", ""),
+    ("access-control-allow-origin", ""),
+    ("age", ""),
+    ("allow", ""),
+    ("authorization", ""),
+    ("cache-control", ""),
+    ("content-disposition", ""),
+    ("content-encoding", ""),
+    ("content-language", ""),
+    ("content-length", ""),
+    ("content-location", ""),
+    ("content-range", ""),
+    ("content-type", ""),
+    ("cookie", ""),
+    ("date", ""),
+    ("etag", ""),
+    ("expect", ""),
+    ("expires", ""),
+    ("from", ""),
+    ("host", ""),
+    ("if-match", ""),
+    ("if-modified-since", ""),
+    ("if-none-match", ""),
+    ("if-range", ""),
+    ("if-unmodified-since", ""),
+    ("last-modified", ""),
+    ("link", ""),
+    ("location", ""),
+    ("max-forwards", ""),
+    ("proxy-authenticate", ""),
+    ("proxy-authorization", ""),
+    ("range", ""),
+    ("referer", ""),
+    ("refresh", ""),
+    ("retry-after", ""),
+    ("server", ""),
+    ("set-cookie", ""),
+    ("strict-transport-security", ""),
+    ("transfer-encoding", ""),
+    ("user-agent", ""),
+    ("vary", ""),
+    ("via", ""),
+    ("www-authenticate", ""),
+];
+
+/// A stateful HPACK decoder for one connection's request headers.
+#[derive(Debug)]
+struct HeaderDecoder {
+    dynamic: VecDeque<(String, String)>,
+    size: usize,
+    max_size: usize,
+}
+
+impl Default for HeaderDecoder {
+    fn default() -> Self {
+        Self {
+            dynamic: VecDeque::new(),
+            size: 0,
+            max_size: 4096,
+        }
+    }
+}
+
+// === impl HeaderDecoder ===
+
+impl HeaderDecoder {
+    fn decode(&mut self, mut block: &[u8]) -> io::Result<Vec<(String, String)>> {
+        let mut headers = Vec::new();
+        while let Some(&first) = block.first() {
+            if first & 0x80 != 0 {
+                // Indexed header field.
+                let (index, rest) = decode_hpack_int(block, 7)?;
+                block = rest;
+                headers.push(self.lookup(index)?);
+            } else if first & 0xc0 == 0x40 {
+                // Literal with incremental indexing.
+                let (header, rest) = self.decode_literal(block, 6)?;
+                block = rest;
+                self.insert(header.clone());
+                headers.push(header);
+            } else if first & 0xe0 == 0x20 {
+                // Dynamic table size update.
+                let (size, rest) = decode_hpack_int(block, 5)?;
+                block = rest;
+                self.max_size = size as usize;
+                self.evict();
+            } else {
+                // Literal without indexing / never indexed.
+                let (header, rest) = self.decode_literal(block, 4)?;
+                block = rest;
+                headers.push(header);
+            }
+        }
+        Ok(headers)
+    }
+
+    fn decode_literal<'a>(
+        &self,
+        block: &'a [u8],
+        prefix_bits: u8,
+    ) -> io::Result<((String, String), &'a [u8])> {
+        let (index, block) = decode_hpack_int(block, prefix_bits)?;
+        let (name, block) = if index == 0 {
+            decode_hpack_string(block)?
+        } else {
+            (self.lookup(index)?.0, block)
+        };
+        let (value, block) = decode_hpack_string(block)?;
+        Ok(((name, value), block))
+    }
+
+    fn lookup(&self, index: u64) -> io::Result<(String, String)> {
+        let index = index as usize;
+        if (1..=STATIC_TABLE.len()).contains(&index) {
+            let (name, value) = STATIC_TABLE[index - 1];
+            return Ok((name.to_owned(), value.to_owned()));
+        }
+        self.dynamic
+            .get(index - STATIC_TABLE.len() - 1)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "bad HPACK index"))
+    }
+
+    fn insert(&mut self, header: (String, String)) {
+        // Each entry costs its lengths plus 32 bytes of overhead
+        // (RFC 7541, section 4.1).
+        self.size += header.0.len() + header.1.len() + 32;
+        self.dynamic.push_front(header);
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.size > self.max_size {
+            match self.dynamic.pop_back() {
+                Some((name, value)) => self.size -= name.len() + value.len() + 32,
+                None => {
+                    self.size = 0;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Decodes an integer with an N-bit prefix (RFC 7541, section 5.1).
+fn decode_hpack_int(block: &[u8], prefix_bits: u8) -> io::Result<(u64, &[u8])> {
+    let truncated = || io::Error::new(io::ErrorKind::InvalidData, "truncated header block");
+    let (&first, mut block) = block.split_first().ok_or_else(truncated)?;
+    let max = (1u64 << prefix_bits) - 1;
+    let mut value = u64::from(first) & max;
+    if value < max {
+        return Ok((value, block));
+    }
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = block.split_first().ok_or_else(truncated)?;
+        block = rest;
+        value += u64::from(byte & 0x7f) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Ok((value, block));
+        }
+        if shift > 56 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "integer too large",
+            ));
+        }
+    }
+}
+
+/// Decodes a length-prefixed, possibly Huffman-coded string literal.
+fn decode_hpack_string(block: &[u8]) -> io::Result<(String, &[u8])> {
+    let huffman = block
+        .first()
+        .map(|&first| first & 0x80 != 0)
+        .unwrap_or(false);
+    let (len, block) = decode_hpack_int(block, 7)?;
+    let bytes = block
+        .get(..len as usize)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "truncated header block"))?;
+    let value = if huffman {
+        huffman_decode(bytes)?
+    } else {
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "non-UTF-8 header"))?
+    };
+    Ok((value, &block[len as usize..]))
+}
+
+/// The Huffman code table (RFC 7541, appendix B), as `(code, bits)` per
+/// symbol; the final entry is the EOS symbol.
+#[rustfmt::skip]
+const HUFFMAN_TABLE: [(u32, u8); 257] = [
+    (8184, 13), (8388568, 23), (268435426, 28), (268435427, 28),
+    (268435428, 28), (268435429, 28), (268435430, 28), (268435431, 28),
+    (268435432, 28), (16777194, 24), (1073741820, 30), (268435433, 28),
+    (268435434, 28), (1073741821, 30), (268435435, 28), (268435436, 28),
+    (268435437, 28), (268435438, 28), (268435439, 28), (268435440, 28),
+    (268435441, 28), (268435442, 28), (1073741822, 30), (268435443, 28),
+    (268435444, 28), (268435445, 28), (268435446, 28), (268435447, 28),
+    (268435448, 28), (268435449, 28), (268435450, 28), (268435451, 28),
+    (20, 6), (1016, 10), (1017, 10), (4090, 12),
+    (8185, 13), (21, 6), (248, 8), (2042, 11),
+    (1018, 10), (1019, 10), (249, 8), (2043, 11),
+    (250, 8), (22, 6), (23, 6), (24, 6),
+    (0, 5), (1, 5), (2, 5), (25, 6),
+    (26, 6), (27, 6), (28, 6), (29, 6),
+    (30, 6), (31, 6), (92, 7), (251, 8),
+    (32764, 15), (32, 6), (4091, 12), (1020, 10),
+    (8186, 13), (33, 6), (93, 7), (94, 7),
+    (95, 7), (96, 7), (97, 7), (98, 7),
+    (99, 7), (100, 7), (101, 7), (102, 7),
+    (103, 7), (104, 7), (105, 7), (106, 7),
+    (107, 7), (108, 7), (109, 7), (110, 7),
+    (111, 7), (112, 7), (113, 7), (114, 7),
+    (252, 8), (115, 7), (253, 8), (8187, 13),
+    (524272, 19), (8188, 13), (16380, 14), (34, 6),
+    (32765, 15), (3, 5), (35, 6), (4, 5),
+    (36, 6), (5, 5), (37, 6), (38, 6),
+    (39, 6), (6, 5), (116, 7), (117, 7),
+    (40, 6), (41, 6), (42, 6), (7, 5),
+    (43, 6), (118, 7), (44, 6), (8, 5),
+    (9, 5), (45, 6), (119, 7), (120, 7),
+    (121, 7), (122, 7), (123, 7), (32766, 15),
+    (2044, 11), (16381, 14), (8189, 13), (268435452, 28),
+    (1048550, 20), (4194258, 22), (1048551, 20), (1048552, 20),
+    (4194259, 22), (4194260, 22), (4194261, 22), (8388569, 23),
+    (4194262, 22), (8388570, 23), (8388571, 23), (8388572, 23),
+    (8388573, 23), (8388574, 23), (16777195, 24), (8388575, 23),
+    (16777196, 24), (16777197, 24), (4194263, 22), (8388576, 23),
+    (16777198, 24), (8388577, 23), (8388578, 23), (8388579, 23),
+    (8388580, 23), (2097116, 21), (4194264, 22), (8388581, 23),
+    (4194265, 22), (8388582, 23), (8388583, 23), (16777199, 24),
+    (4194266, 22), (2097117, 21), (1048553, 20), (4194267, 22),
+    (4194268, 22), (8388584, 23), (8388585, 23), (2097118, 21),
+    (8388586, 23), (4194269, 22), (4194270, 22), (16777200, 24),
+    (2097119, 21), (4194271, 22), (8388587, 23), (8388588, 23),
+    (2097120, 21), (2097121, 21), (4194272, 22), (2097122, 21),
+    (8388589, 23), (4194273, 22), (8388590, 23), (8388591, 23),
+    (1048554, 20), (4194274, 22), (4194275, 22), (4194276, 22),
+    (8388592, 23), (4194277, 22), (4194278, 22), (8388593, 23),
+    (67108832, 26), (67108833, 26), (1048555, 20), (524273, 19),
+    (4194279, 22), (8388594, 23), (4194280, 22), (33554412, 25),
+    (67108834, 26), (67108835, 26), (67108836, 26), (134217694, 27),
+    (134217695, 27), (67108837, 26), (16777201, 24), (33554413, 25),
+    (524274, 19), (2097123, 21), (67108838, 26), (134217696, 27),
+    (134217697, 27), (67108839, 26), (134217698, 27), (16777202, 24),
+    (2097124, 21), (2097125, 21), (67108840, 26), (67108841, 26),
+    (268435453, 28), (134217699, 27), (134217700, 27), (134217701, 27),
+    (1048556, 20), (16777203, 24), (1048557, 20), (2097126, 21),
+    (4194281, 22), (2097127, 21), (2097128, 21), (8388595, 23),
+    (4194282, 22), (4194283, 22), (33554414, 25), (33554415, 25),
+    (16777204, 24), (16777205, 24), (67108842, 26), (8388596, 23),
+    (67108843, 26), (134217702, 27), (67108844, 26), (67108845, 26),
+    (134217703, 27), (134217704, 27), (134217705, 27), (134217706, 27),
+    (134217707, 27), (268435454, 28), (134217708, 27), (134217709, 27),
+    (134217710, 27), (134217711, 27), (134217712, 27), (67108846, 26),
+    (1073741823, 30),
+];
+
+/// Decodes a Huffman-coded string literal by walking a code tree built
+/// from [`HUFFMAN_TABLE`].
+fn huffman_decode(bytes: &[u8]) -> io::Result<String> {
+    // Nodes are pairs of child links; positive values are node indices,
+    // and `-1 - symbol` marks a leaf.
+    let mut nodes: Vec<[i32; 2]> = vec![[0; 2]];
+    for (symbol, &(code, bits)) in HUFFMAN_TABLE.iter().enumerate().take(256) {
+        let mut node = 0usize;
+        for i in (0..bits).rev() {
+            let bit = ((code >> i) & 1) as usize;
+            if i == 0 {
+                nodes[node][bit] = -1 - symbol as i32;
+            } else {
+                if nodes[node][bit] <= 0 {
+                    nodes.push([0; 2]);
+                    let next = (nodes.len() - 1) as i32;
+                    nodes[node][bit] = next;
+                }
+                node = nodes[node][bit] as usize;
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut node = 0usize;
+    for &byte in bytes {
+        for i in (0..8).rev() {
+            let bit = ((byte >> i) & 1) as usize;
+            match nodes[node][bit] {
+                leaf if leaf < 0 => {
+                    out.push((-1 - leaf) as u8);
+                    node = 0;
+                }
+                0 => {
+                    // An unused branch; only reachable within the EOS
+                    // code, which is padding at the end of the string.
+                    node = 0;
+                }
+                next => node = next as usize,
+            }
+        }
+    }
+    String::from_utf8(out)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "non-UTF-8 header"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::time::Duration;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn huffman_decodes_the_reference_vector() {
+        // From RFC 7541, appendix C.4.1.
+        let encoded = [
+            0xf1, 0xe3, 0xc2, 0xe5, 0xf2, 0x3a, 0x6b, 0xa0, 0xab, 0x90, 0xf4, 0xff,
+        ];
+        assert_eq!(huffman_decode(&encoded).unwrap(), "www.example.com");
+    }
+
+    #[test]
+    fn hpack_integers_round_trip() {
+        // The 1337-with-5-bit-prefix example from RFC 7541, appendix C.1.
+        let (value, rest) = decode_hpack_int(&[0x1f, 0x9a, 0x0a], 5).unwrap();
+        assert_eq!(value, 1337);
+        assert!(rest.is_empty());
+
+        let mut block = Vec::new();
+        encode_hpack_int(&mut block, 0x00, 5, 1337);
+        assert_eq!(block, [0x1f, 0x9a, 0x0a]);
+    }
+
+    #[test]
+    fn watch_requests_parse_and_skip_unknown_fields() {
+        let mut proto = Vec::new();
+        encode_varint_field(&mut proto, 9, 42);
+        encode_string(&mut proto, 1, "info,my_crate=debug");
+        let mut body = vec![0];
+        body.extend_from_slice(&(proto.len() as u32).to_be_bytes());
+        body.extend_from_slice(&proto);
+        assert_eq!(
+            parse_watch_request(&body).as_deref(),
+            Some("info,my_crate=debug")
+        );
+        assert_eq!(parse_watch_request(&[0]), None);
+    }
+
+    /// Reads frames until one of the given type arrives on `stream_id`,
+    /// skipping connection-level frames.
+    fn read_frame_of_type(reader: &mut TcpStream, frame_type: u8, stream_id: u32) -> (u8, Vec<u8>) {
+        loop {
+            let mut header = [0u8; 9];
+            reader.read_exact(&mut header).expect("no frame");
+            let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+            let mut payload = vec![0u8; len];
+            reader.read_exact(&mut payload).expect("truncated frame");
+            let stream = u32::from_be_bytes([header[5], header[6], header[7], header[8]]);
+            if header[3] == frame_type && stream == stream_id {
+                return (header[4], payload);
+            }
+        }
+    }
+
+    #[test]
+    fn watch_streams_matching_events() {
+        let (subscriber, server) = serve("127.0.0.1:0").expect("failed to bind");
+        let addr = server.local_addr().expect("no local addr");
+        let collector = crate::registry().with(subscriber);
+
+        with_default(collector, || {
+            let mut stream = TcpStream::connect(addr).expect("failed to connect");
+            stream.write_all(PREFACE).expect("failed to send preface");
+            stream
+                .write_all(&[0, 0, 0, FRAME_SETTINGS, 0, 0, 0, 0, 0])
+                .expect("failed to send settings");
+
+            // HEADERS: `:method: POST` (indexed) and a literal `:path`.
+            let mut block = vec![0x83];
+            block.push(0x00);
+            encode_hpack_string(&mut block, ":path");
+            encode_hpack_string(&mut block, WATCH_PATH);
+            let mut frame = (block.len() as u32).to_be_bytes()[1..].to_vec();
+            frame.extend_from_slice(&[FRAME_HEADERS, FLAG_END_HEADERS, 0, 0, 0, 1]);
+            frame.extend_from_slice(&block);
+            stream.write_all(&frame).expect("failed to send headers");
+
+            // DATA: a WatchRequest subscribing at `info`.
+            let mut proto = Vec::new();
+            encode_string(&mut proto, 1, "info");
+            let mut body = vec![0];
+            body.extend_from_slice(&(proto.len() as u32).to_be_bytes());
+            body.extend_from_slice(&proto);
+            let mut frame = (body.len() as u32).to_be_bytes()[1..].to_vec();
+            frame.extend_from_slice(&[FRAME_DATA, FLAG_END_STREAM, 0, 0, 0, 1]);
+            frame.extend_from_slice(&body);
+            stream.write_all(&frame).expect("failed to send request");
+
+            let (flags, _) = read_frame_of_type(&mut stream, FRAME_HEADERS, 1);
+            assert_eq!(flags & FLAG_END_STREAM, 0, "stream ended early");
+
+            // Events dispatched after the subscription arrive on the
+            // stream; those below the filter do not.
+            stream
+                .set_read_timeout(Some(Duration::from_millis(100)))
+                .unwrap();
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            let data = loop {
+                tracing::debug!(target: "app", "too verbose");
+                tracing::info!(target: "app", rows = 3, "queried");
+                let mut header = [0u8; 9];
+                match stream.read_exact(&mut header) {
+                    Ok(()) => {
+                        let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+                        let mut payload = vec![0u8; len];
+                        stream.read_exact(&mut payload).expect("truncated frame");
+                        if header[3] == FRAME_DATA {
+                            break payload;
+                        }
+                    }
+                    Err(_) if std::time::Instant::now() < deadline => continue,
+                    Err(e) => panic!("no event frame: {}", e),
+                }
+            };
+
+            // The gRPC message prefix, then the Event protobuf.
+            assert_eq!(data[0], 0);
+            let len = u32::from_be_bytes([data[1], data[2], data[3], data[4]]) as usize;
+            let message = &data[5..5 + len];
+            let expect = |needle: &[u8]| {
+                assert!(
+                    message.windows(needle.len()).any(|w| w == needle),
+                    "{:?} not found in {:?}",
+                    needle,
+                    message
+                );
+            };
+            expect(b"app");
+            expect(b"queried");
+            expect(b"rows");
+            assert!(
+                !message.windows(11).any(|w| w == b"too verbose"),
+                "filtered event leaked"
+            );
+        });
+    }
+
+    #[test]
+    fn unknown_methods_get_a_grpc_error() {
+        let (subscriber, server) = serve("127.0.0.1:0").expect("failed to bind");
+        let addr = server.local_addr().expect("no local addr");
+        let collector = crate::registry().with(subscriber);
+
+        with_default(collector, || {
+            let mut stream = TcpStream::connect(addr).expect("failed to connect");
+            stream.write_all(PREFACE).expect("failed to send preface");
+
+            let mut block = vec![0x83];
+            block.push(0x00);
+            encode_hpack_string(&mut block, ":path");
+            encode_hpack_string(&mut block, "/tracing.TraceStream/Nope");
+            let mut frame = (block.len() as u32).to_be_bytes()[1..].to_vec();
+            frame.extend_from_slice(&[
+                FRAME_HEADERS,
+                FLAG_END_HEADERS | FLAG_END_STREAM,
+                0,
+                0,
+                0,
+                1,
+            ]);
+            frame.extend_from_slice(&block);
+            stream.write_all(&frame).expect("failed to send headers");
+
+            let (flags, payload) = read_frame_of_type(&mut stream, FRAME_HEADERS, 1);
+            assert_ne!(flags & FLAG_END_STREAM, 0, "expected trailers-only");
+            let headers = HeaderDecoder::default().decode(&payload).unwrap();
+            assert!(
+                headers.contains(&("grpc-status".to_owned(), "12".to_owned())),
+                "{:?}",
+                headers
+            );
+        });
+    }
+}
@@ -0,0 +1,740 @@
+//! Sends completed spans to [Honeycomb] as events.
+//!
+//! Honeycomb is an observability service built around wide, high-cardinality
+//! events: each completed span becomes one event in a *dataset*, carrying its
+//! duration, its fields, and `trace.*` identifiers derived from the span
+//! hierarchy so that Honeycomb can reassemble traces. This module provides a
+//! [`Subscriber`] that ships spans to the Honeycomb batch API over HTTP using
+//! only the standard library.
+//!
+//! Events are buffered and sent in batches, either when a batch fills up or
+//! when the batch timeout elapses. High-volume services can thin the stream
+//! with [`Builder::with_sampler`], a hook that inspects each span's name and
+//! fields and returns its sample rate; sampled-out spans are dropped locally
+//! and kept spans are annotated with the rate so Honeycomb can rescale
+//! counts.
+//!
+//! # Limitations
+//!
+//! - Connections are plaintext HTTP without TLS, so events must be routed
+//!   through a local proxy such as [Refinery] rather than directly to
+//!   `api.honeycomb.io`.
+//! - Only completed spans are exported; free-standing events are ignored.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{honeycomb, prelude::*};
+//!
+//! let exporter = honeycomb::Subscriber::builder()
+//!     .with_endpoint("127.0.0.1:8081")
+//!     .with_api_key("my-team-key")
+//!     .with_dataset("my-service")
+//!     // Keep every error, and one in ten of everything else.
+//!     .with_sampler(|_name, fields| {
+//!         let errored = fields.iter().any(|(name, _)| *name == "error");
+//!         if errored {
+//!             1
+//!         } else {
+//!             10
+//!         }
+//!     })
+//!     .finish()
+//!     .expect("failed to start the Honeycomb exporter");
+//! let collector = tracing_subscriber::registry().with(exporter);
+//! # let _ = collector;
+//! ```
+//!
+//! [Honeycomb]: https://www.honeycomb.io/
+//! [Refinery]: https://docs.honeycomb.io/manage-data-volume/refinery/
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    fmt,
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+use tracing_core::{field, span, Collect};
+
+/// Decides the sample rate for a completed span from its name and fields.
+///
+/// A return value of `1` keeps every such span, `n` keeps one in `n`, and
+/// `0` drops them all.
+type Sampler = dyn Fn(&str, &[(&'static str, String)]) -> u32 + Send + Sync;
+
+/// A [`Subscribe`] implementation that sends completed spans to Honeycomb.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Subscriber {
+    // The standard library's channel sender is not `Sync` on our minimum
+    // supported Rust version, so sends are serialized through a mutex.
+    sender: Mutex<mpsc::Sender<String>>,
+    sampler: Option<Box<Sampler>>,
+}
+
+/// Configures a Honeycomb exporter [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+pub struct Builder {
+    endpoint: String,
+    api_key: String,
+    dataset: String,
+    sampler: Option<Box<Sampler>>,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+/// The in-progress data for a span, stored in its extensions.
+struct HoneycombSpan {
+    trace_id: String,
+    span_id: String,
+    parent_id: Option<String>,
+    start: Instant,
+    start_millis: u64,
+    fields: Vec<(&'static str, String)>,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a [`Builder`] for configuring a Honeycomb exporter.
+    pub fn builder() -> Builder {
+        Builder {
+            endpoint: "127.0.0.1:8081".to_owned(),
+            api_key: String::new(),
+            dataset: "tracing".to_owned(),
+            sampler: None,
+            batch_size: 512,
+            batch_timeout: Duration::from_secs(5),
+            max_retries: 3,
+        }
+    }
+}
+
+impl fmt::Debug for Subscriber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscriber")
+            .field("sampler", &self.sampler.as_ref().map(|_| "..."))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+
+        let parent = if attrs.is_root() {
+            None
+        } else if attrs.is_contextual() {
+            ctx.lookup_current()
+        } else {
+            attrs.parent().and_then(|id| ctx.span(id))
+        };
+        let (trace_id, parent_id) = match parent {
+            Some(parent) => match parent.extensions().get::<HoneycombSpan>() {
+                Some(parent) => (parent.trace_id.clone(), Some(parent.span_id.clone())),
+                None => (hex_id::<16>(), None),
+            },
+            None => (hex_id::<16>(), None),
+        };
+
+        let mut fields = Vec::new();
+        attrs.record(&mut FieldVisitor {
+            fields: &mut fields,
+        });
+        span.extensions_mut().insert(HoneycombSpan {
+            trace_id,
+            span_id: hex_id::<8>(),
+            parent_id,
+            start: Instant::now(),
+            start_millis: unix_millis(),
+            fields,
+        });
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(data) = extensions.get_mut::<HoneycombSpan>() {
+            values.record(&mut FieldVisitor {
+                fields: &mut data.fields,
+            });
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(&id).expect("Span not found, this is a bug");
+        let data = match span.extensions_mut().remove::<HoneycombSpan>() {
+            Some(data) => data,
+            None => return,
+        };
+
+        let rate = match &self.sampler {
+            Some(sampler) => sampler(span.name(), &data.fields),
+            None => 1,
+        };
+        if !sample(rate) {
+            return;
+        }
+
+        let event = encode_event(span.name(), span.metadata().target(), rate, &data);
+        // The only send error is a disconnected worker; spans are dropped in
+        // that case, as there is nowhere to export them to.
+        let _ = self
+            .sender
+            .lock()
+            .expect("honeycomb sender poisoned")
+            .send(event);
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the `host:port` of the Honeycomb API or proxy to send events
+    /// to.
+    ///
+    /// The default is `127.0.0.1:8081`. Any `http://` prefix and trailing
+    /// slash are ignored.
+    pub fn with_endpoint(self, endpoint: impl Into<String>) -> Self {
+        let mut endpoint = endpoint.into();
+        if let Some(stripped) = endpoint.strip_prefix("http://") {
+            endpoint = stripped.to_owned();
+        }
+        if let Some(stripped) = endpoint.strip_suffix('/') {
+            endpoint = stripped.to_owned();
+        }
+        Self { endpoint, ..self }
+    }
+
+    /// Sets the API key sent with every batch.
+    ///
+    /// Honeycomb calls this the *team key*; it is required, and
+    /// [`finish`](Self::finish) fails without one.
+    pub fn with_api_key(self, api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            ..self
+        }
+    }
+
+    /// Sets the dataset that events are written to.
+    ///
+    /// The default is `tracing`.
+    pub fn with_dataset(self, dataset: impl Into<String>) -> Self {
+        Self {
+            dataset: dataset.into(),
+            ..self
+        }
+    }
+
+    /// Sets a dynamic sampling hook, called with each completed span's name
+    /// and fields.
+    ///
+    /// The hook returns the span's sample rate: `1` keeps every such span,
+    /// `n` keeps one in `n` (chosen at random), and `0` drops them all.
+    /// Kept events carry the rate so Honeycomb can rescale counts. By
+    /// default every span is kept.
+    pub fn with_sampler(
+        self,
+        sampler: impl Fn(&str, &[(&'static str, String)]) -> u32 + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            sampler: Some(Box::new(sampler)),
+            ..self
+        }
+    }
+
+    /// Sets when a batch is sent: once `batch_size` events have gathered,
+    /// or `batch_timeout` after the first one arrives.
+    ///
+    /// The defaults are 512 events and 5 seconds.
+    pub fn batching(self, batch_size: usize, batch_timeout: Duration) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            batch_timeout,
+            ..self
+        }
+    }
+
+    /// Sets how many times a failed batch is retried before being dropped.
+    ///
+    /// Retries back off exponentially, starting at 100 milliseconds. The
+    /// default is 3 retries.
+    pub fn with_max_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Returns the configured exporter [`Subscriber`], spawning its export
+    /// thread.
+    ///
+    /// Fails if no API key was configured. The thread runs until the
+    /// `Subscriber` is dropped; any events still buffered at that point are
+    /// sent before it exits.
+    pub fn finish(self) -> io::Result<Subscriber> {
+        if self.api_key.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a Honeycomb API key is required",
+            ));
+        }
+        let (sender, receiver) = mpsc::channel();
+        let worker = Worker {
+            endpoint: self.endpoint,
+            api_key: self.api_key,
+            dataset: self.dataset,
+            batch_size: self.batch_size,
+            batch_timeout: self.batch_timeout,
+            max_retries: self.max_retries,
+        };
+        thread::Builder::new()
+            .name("tracing-honeycomb".into())
+            .spawn(move || worker.run(receiver))?;
+        Ok(Subscriber {
+            sender: Mutex::new(sender),
+            sampler: self.sampler,
+        })
+    }
+}
+
+impl fmt::Debug for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("endpoint", &self.endpoint)
+            .field("dataset", &self.dataset)
+            .field("batch_size", &self.batch_size)
+            .field("batch_timeout", &self.batch_timeout)
+            .field("max_retries", &self.max_retries)
+            .finish_non_exhaustive()
+    }
+}
+
+// === impl Worker ===
+
+/// The export thread: gathers events into batches and posts them.
+struct Worker {
+    endpoint: String,
+    api_key: String,
+    dataset: String,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+impl Worker {
+    fn run(&self, receiver: mpsc::Receiver<String>) {
+        let mut batch = Vec::new();
+        loop {
+            match receiver.recv_timeout(self.batch_timeout) {
+                Ok(event) => {
+                    batch.push(event);
+                    if batch.len() >= self.batch_size {
+                        self.export(&mut batch);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => self.export(&mut batch),
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    self.export(&mut batch);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Sends the batch, retrying with exponential backoff; the batch is
+    /// cleared either way.
+    fn export(&self, batch: &mut Vec<String>) {
+        if batch.is_empty() {
+            return;
+        }
+        let mut body = String::from("[");
+        for (i, event) in batch.iter().enumerate() {
+            if i != 0 {
+                body.push(',');
+            }
+            body.push_str(event);
+        }
+        body.push(']');
+        batch.clear();
+
+        let mut backoff = Duration::from_millis(100);
+        for attempt in 0..=self.max_retries {
+            if self.post(&body).is_ok() {
+                return;
+            }
+            if attempt < self.max_retries {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+    }
+
+    /// Posts one batch, returning an error unless the server responds with
+    /// a success status.
+    fn post(&self, body: &str) -> io::Result<()> {
+        let mut stream = TcpStream::connect(&self.endpoint)?;
+        let request = format!(
+            "POST /1/batch/{} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Content-Type: application/json\r\n\
+             X-Honeycomb-Team: {}\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n",
+            self.dataset,
+            self.endpoint,
+            self.api_key,
+            body.len(),
+        );
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body.as_bytes())?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        // "HTTP/1.1 200 OK" => "200"
+        let code = status.split_whitespace().nth(1).unwrap_or("");
+        if code.starts_with('2') {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Honeycomb responded with status {:?}", status.trim()),
+            ))
+        }
+    }
+}
+
+/// Encodes one completed span as a Honeycomb batch event.
+fn encode_event(name: &str, target: &str, rate: u32, data: &HoneycombSpan) -> String {
+    let mut json = String::from("{\"time\":\"");
+    let _ = write!(json, "{}", format_timestamp(data.start_millis));
+    let _ = write!(json, "\",\"samplerate\":{}", rate);
+    json.push_str(",\"data\":{\"name\":\"");
+    escape_into(&mut json, name);
+    json.push_str("\",\"target\":\"");
+    escape_into(&mut json, target);
+    let _ = write!(
+        json,
+        "\",\"duration_ms\":{:.3}",
+        data.start.elapsed().as_secs_f64() * 1000.0,
+    );
+    json.push_str(",\"trace.trace_id\":\"");
+    json.push_str(&data.trace_id);
+    json.push_str("\",\"trace.span_id\":\"");
+    json.push_str(&data.span_id);
+    json.push('"');
+    if let Some(parent_id) = &data.parent_id {
+        json.push_str(",\"trace.parent_id\":\"");
+        json.push_str(parent_id);
+        json.push('"');
+    }
+    for (field, value) in &data.fields {
+        json.push_str(",\"");
+        escape_into(&mut json, field);
+        json.push_str("\":\"");
+        escape_into(&mut json, value);
+        json.push('"');
+    }
+    json.push_str("}}");
+    json
+}
+
+/// Decides whether to keep a span sampled at one in `rate`.
+fn sample(rate: u32) -> bool {
+    match rate {
+        0 => false,
+        1 => true,
+        rate => {
+            let roll = u64::from_le_bytes(random_bytes::<8>());
+            roll % u64::from(rate) == 0
+        }
+    }
+}
+
+/// Returns the current wall-clock time in milliseconds since the Unix
+/// epoch.
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Formats a Unix millisecond timestamp as RFC 3339, such as
+/// `2026-08-29T13:14:15.678Z`.
+fn format_timestamp(millis: u64) -> String {
+    let (year, month, day) = civil_date(millis / 86_400_000);
+    let rest = millis % 86_400_000;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rest / 3_600_000,
+        rest / 60_000 % 60,
+        rest / 1000 % 60,
+        rest % 1000,
+    )
+}
+
+/// Converts days since the Unix epoch to a `(year, month, day)` civil
+/// date.
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let (year, month) = if month < 10 {
+        (year_of_era + era * 400, month + 3)
+    } else {
+        (year_of_era + era * 400 + 1, month - 9)
+    };
+    (year, month, day)
+}
+
+/// Returns `N` pseudo-random bytes, suitable for span IDs and sampling.
+///
+/// This uses the standard library's randomly-seeded hasher rather than a
+/// cryptographic RNG; IDs are unique in practice but not unpredictable.
+fn random_bytes<const N: usize>() -> [u8; N] {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+        sync::atomic::{AtomicU64, Ordering},
+    };
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut bytes = [0; N];
+    for chunk in bytes.chunks_mut(8) {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        let word = hasher.finish().to_le_bytes();
+        chunk.copy_from_slice(&word[..chunk.len()]);
+    }
+    bytes
+}
+
+/// Returns `N` pseudo-random bytes as a lowercase hex string.
+fn hex_id<const N: usize>() -> String {
+    let mut id = String::with_capacity(N * 2);
+    for byte in random_bytes::<N>() {
+        let _ = write!(id, "{:02x}", byte);
+    }
+    id
+}
+
+/// Writes `value` into `out` with JSON string escaping.
+fn escape_into(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+/// Records span fields as strings.
+struct FieldVisitor<'a> {
+    fields: &'a mut Vec<(&'static str, String)>,
+}
+
+impl FieldVisitor<'_> {
+    fn record(&mut self, field: &field::Field, value: String) {
+        match self
+            .fields
+            .iter_mut()
+            .find(|(name, _)| *name == field.name())
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.fields.push((field.name(), value)),
+        }
+    }
+}
+
+impl field::Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.record(field, value.to_owned());
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.record(field, format!("{:?}", value));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::net::TcpListener;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn sample_decisions_are_deterministic_at_the_edges() {
+        assert!(!sample(0));
+        assert!(sample(1));
+    }
+
+    #[test]
+    fn missing_api_keys_are_rejected() {
+        let error = Subscriber::builder()
+            .finish()
+            .expect_err("finish should require an API key");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    /// Accepts one batch and returns its request line, headers, and body.
+    fn accept_batch(listener: &TcpListener) -> (String, String, String) {
+        let (stream, _) = listener.accept().expect("no batch received");
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader
+            .read_line(&mut request_line)
+            .expect("failed to read request line");
+        let mut headers = String::new();
+        let mut content_length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).expect("failed to read header");
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            headers.push_str(&header.to_ascii_lowercase());
+            headers.push('\n');
+            if let Some(length) = header
+                .to_ascii_lowercase()
+                .strip_prefix("content-length:")
+                .map(str::trim)
+            {
+                content_length = length.parse().expect("invalid content-length");
+            }
+        }
+        let mut body = vec![0; content_length];
+        io::Read::read_exact(&mut reader, &mut body).expect("failed to read body");
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .expect("failed to respond");
+        (
+            request_line.trim().to_owned(),
+            headers,
+            String::from_utf8(body).expect("body was not UTF-8"),
+        )
+    }
+
+    /// Returns the value of the first `"key":"..."` member in `json`.
+    fn json_str(json: &str, key: &str) -> String {
+        let marker = format!("\"{}\":\"", key);
+        let start = json.find(&marker).expect("key not found") + marker.len();
+        json[start..]
+            .split('"')
+            .next()
+            .expect("unterminated value")
+            .to_owned()
+    }
+
+    #[test]
+    fn spans_export_with_derived_trace_ids() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let exporter = Subscriber::builder()
+            .with_endpoint(format!("http://{}/", addr))
+            .with_api_key("test-key")
+            .with_dataset("batch-test")
+            .batching(2, Duration::from_secs(30))
+            .finish()
+            .expect("failed to start exporter");
+        let collector = crate::registry().with(exporter);
+
+        with_default(collector, || {
+            let parent = tracing::info_span!("handle_request", method = "GET");
+            let _entered = parent.enter();
+            tracing::info_span!("query_db", rows = 7).in_scope(|| {});
+        });
+
+        let (request_line, headers, body) = accept_batch(&listener);
+        assert_eq!(request_line, "POST /1/batch/batch-test HTTP/1.1");
+        assert!(
+            headers.contains("x-honeycomb-team: test-key"),
+            "missing team header: {}",
+            headers,
+        );
+
+        // The child span closes first, so it leads the batch.
+        let (child, parent) = body.split_once("},{").expect("expected two events");
+        assert_eq!(json_str(child, "name"), "query_db");
+        assert_eq!(json_str(child, "rows"), "7");
+        assert_eq!(json_str(parent, "name"), "handle_request");
+        assert_eq!(json_str(parent, "method"), "GET");
+        assert_eq!(
+            json_str(child, "trace.trace_id"),
+            json_str(parent, "trace.trace_id"),
+        );
+        assert_eq!(
+            json_str(child, "trace.parent_id"),
+            json_str(parent, "trace.span_id"),
+        );
+        assert!(body.contains("\"duration_ms\":"));
+        assert!(body.contains("\"samplerate\":1"));
+    }
+
+    #[test]
+    fn samplers_drop_spans_by_field() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let exporter = Subscriber::builder()
+            .with_endpoint(addr.to_string())
+            .with_api_key("test-key")
+            .with_sampler(|_, fields| {
+                let noisy = fields
+                    .iter()
+                    .any(|(name, value)| *name == "noisy" && value == "true");
+                if noisy {
+                    0
+                } else {
+                    1
+                }
+            })
+            .batching(1, Duration::from_secs(30))
+            .finish()
+            .expect("failed to start exporter");
+        let collector = crate::registry().with(exporter);
+
+        with_default(collector, || {
+            tracing::info_span!("health_check", noisy = true).in_scope(|| {});
+            tracing::info_span!("checkout", order = 9).in_scope(|| {});
+        });
+
+        let (_, _, body) = accept_batch(&listener);
+        assert_eq!(json_str(&body, "name"), "checkout");
+        assert!(!body.contains("health_check"), "sampled-out span exported");
+    }
+}
@@ -0,0 +1,464 @@
+//! Live-span introspection for diagnosing stuck requests and leaked spans.
+//!
+//! When a production process wedges, the question is rarely "what was
+//! logged" but "what is it doing *right now*". This module provides a
+//! [`Subscriber`] that tracks every span currently alive — name, target,
+//! level, recorded fields, parent link, and how long it has been open —
+//! and a [`Handle`] that serializes that state as JSON on demand. A span
+//! that shows up in the dump with an age of twenty minutes is either a
+//! stuck request or a leak; either way, it has a name and fields attached.
+//!
+//! The dump can also carry application-provided attributes, set with
+//! [`Handle::annotate`] — typically the active filter directives and the
+//! list of configured subscribers, which are not discoverable from inside
+//! a single subscriber.
+//!
+//! For convenience, [`serve_tcp`] serves the dump from a tiny embedded
+//! HTTP listener, so `curl` is the only tooling needed.
+//!
+//! # Examples
+//!
+//! ```
+//! use tracing_subscriber::{introspect, prelude::*};
+//!
+//! let (introspect, handle) = introspect::Subscriber::new();
+//! let collector = tracing_subscriber::registry().with(introspect);
+//! # let _ = collector;
+//! // ... later, perhaps from a debug endpoint ...
+//! let json = handle.dump_json();
+//! # drop(json);
+//! ```
+//!
+//! Serving the dump over HTTP:
+//!
+//! ```no_run
+//! # use tracing_subscriber::{introspect, prelude::*};
+//! # let (introspect, handle) = introspect::Subscriber::new();
+//! # let _ = tracing_subscriber::registry().with(introspect);
+//! introspect::serve_tcp(handle, "127.0.0.1:6670")
+//!     .expect("failed to bind the introspection endpoint");
+//! ```
+//!
+//! # Security
+//!
+//! The embedded listener performs no authentication, and span fields may
+//! contain sensitive data. Bind it to a loopback address only.
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+    time::Instant,
+};
+use tracing_core::{field, span, Collect, Event, Level};
+
+/// A [`Subscribe`] implementation that tracks the set of currently-live
+/// spans.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    shared: Arc<Shared>,
+}
+
+/// Provides access to the live-span state tracked by an introspection
+/// [`Subscriber`].
+///
+/// This is returned by [`Subscriber::new`], and may be cloned and sent to
+/// other threads freely.
+#[derive(Debug, Clone)]
+pub struct Handle {
+    shared: Arc<Shared>,
+}
+
+/// A running introspection endpoint.
+///
+/// This is returned by [`serve_tcp`]. The endpoint is served on a
+/// background thread, which runs until the process exits; dropping the
+/// `Server` does not shut it down.
+#[derive(Debug)]
+pub struct Server {
+    addr: Option<SocketAddr>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    start: Instant,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    spans: HashMap<u64, SpanInfo>,
+    attributes: Vec<(String, String)>,
+}
+
+/// Everything the dump reports about one live span.
+#[derive(Debug)]
+struct SpanInfo {
+    name: &'static str,
+    target: &'static str,
+    level: Level,
+    parent: Option<u64>,
+    opened_us: u64,
+    /// Field names paired with their values, pre-rendered as JSON.
+    fields: Vec<(String, String)>,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new introspection subscriber, and a [`Handle`] that dumps
+    /// the live-span state it tracks.
+    pub fn new() -> (Self, Handle) {
+        let shared = Arc::new(Shared {
+            start: Instant::now(),
+            state: Mutex::new(State::default()),
+        });
+        let handle = Handle {
+            shared: shared.clone(),
+        };
+        (Self { shared }, handle)
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut fields = Vec::new();
+        attrs.record(&mut FieldVisitor::new(&mut fields));
+        let info = SpanInfo {
+            name: span.metadata().name(),
+            target: span.metadata().target(),
+            level: *span.metadata().level(),
+            parent: span.parent().map(|parent| parent.id().into_u64()),
+            opened_us: self.shared.elapsed_us(),
+            fields,
+        };
+        let mut state = self.shared.state.lock().expect("introspect state poisoned");
+        state.spans.insert(id.into_u64(), info);
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, _ctx: Context<'_, C>) {
+        let mut state = self.shared.state.lock().expect("introspect state poisoned");
+        if let Some(info) = state.spans.get_mut(&id.into_u64()) {
+            let mut fields = Vec::new();
+            values.record(&mut FieldVisitor::new(&mut fields));
+            for (name, value) in fields {
+                info.fields.retain(|(existing, _)| existing != &name);
+                info.fields.push((name, value));
+            }
+        }
+    }
+
+    fn on_event(&self, _event: &Event<'_>, _ctx: Context<'_, C>) {}
+
+    fn on_close(&self, id: span::Id, _ctx: Context<'_, C>) {
+        let mut state = self.shared.state.lock().expect("introspect state poisoned");
+        state.spans.remove(&id.into_u64());
+    }
+}
+
+// === impl Handle ===
+
+impl Handle {
+    /// Attaches an application-provided attribute to the dump, replacing
+    /// any previous value for `key`.
+    ///
+    /// Use this for state the subscriber cannot see itself, such as the
+    /// active filter directives or the list of configured subscribers.
+    pub fn annotate(&self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        let mut state = self.shared.state.lock().expect("introspect state poisoned");
+        state.attributes.retain(|(existing, _)| existing != &key);
+        state.attributes.push((key, value.into()));
+    }
+
+    /// Serializes the current live-span state as a JSON document.
+    ///
+    /// The dump is a snapshot: it lists every span that was open at the
+    /// moment of the call, ordered by span ID (roughly creation order),
+    /// along with the process uptime and any attributes set with
+    /// [`annotate`](Handle::annotate).
+    pub fn dump_json(&self) -> String {
+        let uptime_us = self.shared.elapsed_us();
+        let state = self.shared.state.lock().expect("introspect state poisoned");
+
+        let mut out = String::new();
+        let _ = write!(out, "{{\"uptime_us\":{},\"attributes\":{{", uptime_us);
+        for (i, (key, value)) in state.attributes.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "\"{}\":\"{}\"", escape(key), escape(value));
+        }
+        out.push_str("},\"spans\":[");
+
+        let mut ids: Vec<&u64> = state.spans.keys().collect();
+        ids.sort_unstable();
+        for (i, id) in ids.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let info = &state.spans[id];
+            let _ = write!(
+                out,
+                "{{\"id\":{},\"name\":\"{}\",\"target\":\"{}\",\"level\":\"{}\"",
+                id,
+                escape(info.name),
+                escape(info.target),
+                info.level,
+            );
+            match info.parent {
+                Some(parent) => {
+                    let _ = write!(out, ",\"parent\":{}", parent);
+                }
+                None => out.push_str(",\"parent\":null"),
+            }
+            let _ = write!(
+                out,
+                ",\"age_us\":{},\"fields\":{{",
+                uptime_us.saturating_sub(info.opened_us)
+            );
+            for (j, (name, value)) in info.fields.iter().enumerate() {
+                if j > 0 {
+                    out.push(',');
+                }
+                let _ = write!(out, "\"{}\":{}", escape(name), value);
+            }
+            out.push_str("}}");
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+// === impl Shared ===
+
+impl Shared {
+    /// Returns the microseconds elapsed since the subscriber was created.
+    fn elapsed_us(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+}
+
+// === impl Server ===
+
+impl Server {
+    /// Returns the local address the listener was bound to.
+    ///
+    /// This is useful when binding to port 0.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+}
+
+/// Starts serving `handle`'s dump over HTTP on a TCP socket bound to
+/// `addr`.
+///
+/// Every request receives a `200 OK` response with a fresh dump,
+/// regardless of its method or path. The returned [`Server`]'s
+/// [`local_addr`] method returns the address the listener was actually
+/// bound to, which is useful when binding to port 0.
+///
+/// [`local_addr`]: Server::local_addr
+pub fn serve_tcp(handle: Handle, addr: impl ToSocketAddrs) -> io::Result<Server> {
+    let listener = TcpListener::bind(addr)?;
+    let addr = listener.local_addr().ok();
+    thread::Builder::new()
+        .name("tracing-introspect".into())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = handle_connection(&handle, stream);
+            }
+        })?;
+    Ok(Server { addr })
+}
+
+/// Reads one HTTP request from `stream` and responds with a dump.
+fn handle_connection(handle: &Handle, stream: impl io::Read + Write) -> io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    // Consume the request head; the response is the same for any request.
+    let mut line = String::new();
+    while stream.read_line(&mut line)? != 0 {
+        if line == "\r\n" || line == "\n" {
+            break;
+        }
+        line.clear();
+    }
+    let body = handle.dump_json();
+    let response = format!(
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\
+         \r\n\
+         {}",
+        body.len(),
+        body
+    );
+    stream.get_mut().write_all(response.as_bytes())
+}
+
+/// Escapes a string for inclusion in a JSON string literal.
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Records span fields as `(name, JSON value)` pairs.
+struct FieldVisitor<'a> {
+    fields: &'a mut Vec<(String, String)>,
+}
+
+impl<'a> FieldVisitor<'a> {
+    fn new(fields: &'a mut Vec<(String, String)>) -> Self {
+        Self { fields }
+    }
+
+    fn push(&mut self, field: &field::Field, value: String) {
+        self.fields.push((field.name().to_string(), value));
+    }
+}
+
+impl field::Visit for FieldVisitor<'_> {
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.push(field, value.to_string());
+    }
+
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.push(field, value.to_string());
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.push(field, value.to_string());
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        if value.is_finite() {
+            self.push(field, value.to_string());
+        } else {
+            self.push(field, format!("\"{}\"", value));
+        }
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.push(field, format!("\"{}\"", escape(value)));
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.push(field, format!("\"{}\"", escape(&format!("{:?}", value))));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn live_spans_appear_and_closed_spans_disappear() {
+        let (introspect, handle) = Subscriber::new();
+        let collector = crate::registry().with(introspect);
+        with_default(collector, || {
+            let parent = tracing::info_span!("request", method = "GET");
+            let _parent = parent.enter();
+            let child = tracing::debug_span!("query", table = "users");
+            let _child = child.enter();
+
+            let dump = handle.dump_json();
+            assert!(dump.contains("\"name\":\"request\""), "{}", dump);
+            assert!(dump.contains("\"method\":\"GET\""), "{}", dump);
+            assert!(dump.contains("\"name\":\"query\""), "{}", dump);
+            assert!(dump.contains("\"level\":\"DEBUG\""), "{}", dump);
+            // The child's parent link points at the request span's ID.
+            let parent_id = parent.id().expect("span was disabled").into_u64();
+            assert!(
+                dump.contains(&format!("\"parent\":{}", parent_id)),
+                "{}",
+                dump
+            );
+
+            drop(_child);
+            drop(child);
+            let dump = handle.dump_json();
+            assert!(!dump.contains("\"name\":\"query\""), "{}", dump);
+            assert!(dump.contains("\"name\":\"request\""), "{}", dump);
+        });
+        assert!(!handle.dump_json().contains("\"name\":\"request\""));
+    }
+
+    #[test]
+    fn recorded_fields_replace_earlier_values() {
+        let (introspect, handle) = Subscriber::new();
+        let collector = crate::registry().with(introspect);
+        with_default(collector, || {
+            let span = tracing::info_span!("job", state = "queued");
+            span.record("state", "running");
+            let dump = handle.dump_json();
+            assert!(dump.contains("\"state\":\"running\""), "{}", dump);
+            assert!(!dump.contains("queued"), "{}", dump);
+        });
+    }
+
+    #[test]
+    fn annotations_are_included() {
+        let (_introspect, handle) = Subscriber::new();
+        handle.annotate("filter", "info,mycrate=debug");
+        handle.annotate("filter", "debug");
+        let dump = handle.dump_json();
+        assert!(dump.contains("\"filter\":\"debug\""), "{}", dump);
+        assert!(!dump.contains("mycrate"), "{}", dump);
+    }
+
+    #[test]
+    fn dumps_are_served_over_http() {
+        use std::net::TcpStream;
+
+        let (_introspect, handle) = Subscriber::new();
+        handle.annotate("layers", "introspect");
+        let server = serve_tcp(handle, "127.0.0.1:0").expect("failed to bind");
+        let addr = server.local_addr().expect("TCP listener has an address");
+
+        let mut stream = TcpStream::connect(addr).expect("failed to connect");
+        stream
+            .write_all(b"GET /debug/spans HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .expect("failed to write request");
+        let mut response = String::new();
+        io::Read::read_to_string(&mut stream, &mut response).expect("failed to read response");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(
+            response.contains("Content-Type: application/json"),
+            "{}",
+            response
+        );
+        assert!(
+            response.contains("\"layers\":\"introspect\""),
+            "{}",
+            response
+        );
+    }
+}
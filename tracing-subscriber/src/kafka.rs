@@ -0,0 +1,1068 @@
+//! Publishes serialized events to a [Kafka] topic.
+//!
+//! Kafka is a common backbone for log pipelines: events published to a topic
+//! can fan out to archival, alerting, and search consumers without the
+//! producing service knowing about any of them. The official Rust client
+//! wraps `librdkafka`, a large C dependency; for programs that just want
+//! their events on a topic, this module provides a [`Subscriber`] that
+//! speaks the Kafka produce protocol directly over TCP, with no dependencies
+//! beyond the standard library.
+//!
+//! Each event is serialized as a single message — JSON by default, or
+//! MessagePack via [`Builder::with_format`] — and handed to a background
+//! thread that batches messages into produce requests. A *key field* may be
+//! configured with [`Builder::with_key_field`]: the value of that event
+//! field (say, `tenant_id`) becomes the message key, and messages are
+//! partitioned by the same murmur2 hash the official Java client uses, so
+//! all events for one tenant land on one partition, in order. Events
+//! without the key field are spread round-robin.
+//!
+//! Delivery failures are not silent: after a produce request exhausts its
+//! retries, a [`DeliveryError`] accounting for the lost events is sent on
+//! the error channel returned by [`Builder::finish`], where the application
+//! can log or export it.
+//!
+//! # Limitations
+//!
+//! - The producer connects to a single configured broker and does not fetch
+//!   cluster metadata, so that broker must lead every partition it is asked
+//!   to write to. Point it at a single-broker cluster, or at a proxy that
+//!   routes produce requests.
+//! - There is no TLS and no SASL authentication.
+//! - The partition count is configuration ([`Builder::with_partitions`]),
+//!   not discovered; it must match the topic for keyed partitioning to
+//!   agree with other producers.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{kafka, prelude::*};
+//!
+//! let (producer, errors) = kafka::Subscriber::builder()
+//!     .with_broker("127.0.0.1:9092")
+//!     .with_topic("service-logs")
+//!     .with_key_field("tenant_id")
+//!     .with_partitions(12)
+//!     .finish()
+//!     .expect("failed to start the Kafka producer");
+//! let collector = tracing_subscriber::registry().with(producer);
+//! # let _ = collector;
+//!
+//! std::thread::spawn(move || {
+//!     while let Some(error) = errors.next() {
+//!         eprintln!("failed to publish events: {}", error);
+//!     }
+//! });
+//! ```
+//!
+//! [Kafka]: https://kafka.apache.org/
+use crate::subscribe::{Context, Subscribe};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Read, Write},
+    net::TcpStream,
+    sync::mpsc,
+    thread,
+    time::{Duration, SystemTime},
+};
+use tracing_core::{field, Collect, Event};
+
+/// A [`Subscribe`] implementation that publishes events to a Kafka topic.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    sender: mpsc::SyncSender<Message>,
+    key_field: Option<String>,
+    format: Format,
+}
+
+/// Configures a Kafka producer [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    broker: String,
+    topic: String,
+    key_field: Option<String>,
+    partitions: i32,
+    format: Format,
+    queue_capacity: usize,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+/// The serialization format for published events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+    /// One JSON object per message (the default).
+    Json,
+    /// One MessagePack map per message.
+    MessagePack,
+}
+
+/// Receives accounting for events that could not be delivered.
+///
+/// This is returned by [`Builder::finish`]. Each [`DeliveryError`] describes
+/// one produce request that was dropped after exhausting its retries. If
+/// nobody is draining the channel, further errors are discarded rather than
+/// accumulating without bound.
+#[derive(Debug)]
+pub struct Errors {
+    receiver: mpsc::Receiver<DeliveryError>,
+}
+
+/// One dropped produce request: the error and how many events it took down.
+#[derive(Debug)]
+pub struct DeliveryError {
+    events: usize,
+    error: io::Error,
+}
+
+/// The default broker address, on the conventional Kafka port.
+const DEFAULT_BROKER: &str = "127.0.0.1:9092";
+
+/// How many undrained delivery errors are buffered before being discarded.
+const ERROR_CAPACITY: usize = 16;
+
+/// A serialized event, as handed to the producer thread.
+struct Message {
+    millis: i64,
+    key: Option<String>,
+    payload: Vec<u8>,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a [`Builder`] for configuring a Kafka producer.
+    pub fn builder() -> Builder {
+        Builder {
+            broker: DEFAULT_BROKER.to_owned(),
+            topic: "tracing".to_owned(),
+            key_field: None,
+            partitions: 1,
+            format: Format::Json,
+            queue_capacity: 4096,
+            batch_size: 512,
+            batch_timeout: Duration::from_secs(1),
+            max_retries: 3,
+        }
+    }
+}
+
+impl<C: Collect> Subscribe<C> for Subscriber {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        let millis = unix_millis();
+        let mut visitor = FieldVisitor {
+            message: None,
+            fields: Vec::new(),
+        };
+        event.record(&mut visitor);
+
+        let key = self.key_field.as_deref().and_then(|key_field| {
+            visitor
+                .fields
+                .iter()
+                .find(|(name, _)| *name == key_field)
+                .map(|(_, value)| value.to_string())
+        });
+        let metadata = event.metadata();
+        let payload = match self.format {
+            Format::Json => encode_json(millis, metadata, &visitor),
+            Format::MessagePack => encode_msgpack(millis, metadata, &visitor),
+        };
+
+        // A full queue means the producer is behind; drop the event rather
+        // than blocking the recording thread. A disconnected queue means
+        // the producer thread has exited.
+        let _ = self.sender.try_send(Message {
+            millis,
+            key,
+            payload,
+        });
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the `host:port` of the broker to publish to.
+    ///
+    /// The default is `127.0.0.1:9092`, the conventional Kafka port.
+    pub fn with_broker(self, broker: impl Into<String>) -> Self {
+        Self {
+            broker: broker.into(),
+            ..self
+        }
+    }
+
+    /// Sets the topic events are published to.
+    ///
+    /// The default is `tracing`.
+    pub fn with_topic(self, topic: impl Into<String>) -> Self {
+        Self {
+            topic: topic.into(),
+            ..self
+        }
+    }
+
+    /// Sets the event field whose value becomes the message key.
+    ///
+    /// Keyed messages are partitioned by the murmur2 hash of the key, as
+    /// the official Java client does, so all events sharing a key preserve
+    /// their order on one partition. Events without the field are spread
+    /// round-robin. By default no key is set and all events are unkeyed.
+    pub fn with_key_field(self, key_field: impl Into<String>) -> Self {
+        Self {
+            key_field: Some(key_field.into()),
+            ..self
+        }
+    }
+
+    /// Sets the number of partitions in the topic.
+    ///
+    /// The partition count is not discovered from the broker; it must match
+    /// the topic for keyed partitioning to agree with other producers. The
+    /// default is 1.
+    pub fn with_partitions(self, partitions: i32) -> Self {
+        Self {
+            partitions: partitions.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the serialization format for published events.
+    ///
+    /// The default is [`Format::Json`].
+    pub fn with_format(self, format: Format) -> Self {
+        Self { format, ..self }
+    }
+
+    /// Sets how many events may be queued for publishing before new events
+    /// are dropped.
+    ///
+    /// The default is 4096 events.
+    pub fn with_queue_capacity(self, queue_capacity: usize) -> Self {
+        Self {
+            queue_capacity: queue_capacity.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the number of events that triggers a produce request.
+    ///
+    /// A batch is published when it reaches this size, or when
+    /// [`batch_timeout`](Self::with_batch_timeout) elapses, whichever comes
+    /// first. The default is 512 events.
+    pub fn with_batch_size(self, batch_size: usize) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            ..self
+        }
+    }
+
+    /// Sets how long events may be buffered before being published.
+    ///
+    /// The default is 1 second.
+    pub fn with_batch_timeout(self, batch_timeout: Duration) -> Self {
+        Self {
+            batch_timeout,
+            ..self
+        }
+    }
+
+    /// Sets how many times a failed produce request is retried before the
+    /// batch is dropped and reported on the error channel.
+    ///
+    /// Retries back off exponentially, starting at 100 milliseconds. The
+    /// default is 3 retries.
+    pub fn with_max_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Returns the configured producer [`Subscriber`] and its delivery
+    /// [`Errors`] channel, spawning the producer thread.
+    ///
+    /// The thread runs until the `Subscriber` is dropped; any events still
+    /// buffered at that point are flushed before it exits.
+    pub fn finish(self) -> io::Result<(Subscriber, Errors)> {
+        let (sender, receiver) = mpsc::sync_channel(self.queue_capacity);
+        let (error_sender, error_receiver) = mpsc::sync_channel(ERROR_CAPACITY);
+        let worker = Worker {
+            broker: self.broker,
+            topic: self.topic,
+            partitions: self.partitions,
+            batch_size: self.batch_size,
+            batch_timeout: self.batch_timeout,
+            max_retries: self.max_retries,
+            errors: error_sender,
+            round_robin: 0,
+        };
+        thread::Builder::new()
+            .name("tracing-kafka".into())
+            .spawn(move || worker.run(receiver))?;
+        Ok((
+            Subscriber {
+                sender,
+                key_field: self.key_field,
+                format: self.format,
+            },
+            Errors {
+                receiver: error_receiver,
+            },
+        ))
+    }
+}
+
+// === impl Errors ===
+
+impl Errors {
+    /// Blocks until the next delivery error, returning `None` once the
+    /// producer has shut down.
+    pub fn next(&self) -> Option<DeliveryError> {
+        self.receiver.recv().ok()
+    }
+
+    /// Returns the next delivery error without blocking, or `None` if no
+    /// error is pending.
+    pub fn try_next(&self) -> Option<DeliveryError> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+// === impl DeliveryError ===
+
+impl DeliveryError {
+    /// Returns the number of events dropped with this produce request.
+    pub fn events(&self) -> usize {
+        self.events
+    }
+
+    /// Returns the error from the final delivery attempt.
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dropped {} event(s): {}", self.events, self.error)
+    }
+}
+
+// === impl Worker ===
+
+/// The producer thread: batches messages, partitions them, and sends
+/// produce requests to the broker.
+struct Worker {
+    broker: String,
+    topic: String,
+    partitions: i32,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+    errors: mpsc::SyncSender<DeliveryError>,
+    round_robin: u64,
+}
+
+impl Worker {
+    fn run(mut self, receiver: mpsc::Receiver<Message>) {
+        let mut batch = Vec::new();
+        loop {
+            match receiver.recv_timeout(self.batch_timeout) {
+                Ok(message) => {
+                    batch.push(message);
+                    if batch.len() >= self.batch_size {
+                        self.publish(&mut batch);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    if !batch.is_empty() {
+                        self.publish(&mut batch);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    if !batch.is_empty() {
+                        self.publish(&mut batch);
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Publishes `batch` as one produce request, retrying with exponential
+    /// backoff; if all attempts fail, the loss is reported on the error
+    /// channel and the batch is dropped.
+    fn publish(&mut self, batch: &mut Vec<Message>) {
+        let events = batch.len();
+        let request = self.encode_produce_request(batch);
+        batch.clear();
+
+        let mut backoff = Duration::from_millis(100);
+        let mut last_error = None;
+        for attempt in 0..=self.max_retries {
+            match self.send(&request) {
+                Ok(()) => return,
+                Err(error) => last_error = Some(error),
+            }
+            if attempt < self.max_retries {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+        // If nobody is draining the channel, the error is discarded; the
+        // alternative is unbounded buffering inside the producer.
+        let _ = self.errors.try_send(DeliveryError {
+            events,
+            error: last_error.expect("failed publish must produce an error"),
+        });
+    }
+
+    /// Assigns `message` to a partition: keyed messages by murmur2, as the
+    /// official Java client partitions, unkeyed messages round-robin.
+    fn partition(&mut self, message: &Message) -> i32 {
+        match &message.key {
+            Some(key) => ((murmur2(key.as_bytes()) & 0x7fff_ffff) % self.partitions as u32) as i32,
+            None => {
+                let partition = (self.round_robin % self.partitions as u64) as i32;
+                self.round_robin += 1;
+                partition
+            }
+        }
+    }
+
+    /// Encodes `batch` as a complete, length-prefixed produce request.
+    fn encode_produce_request(&mut self, batch: &[Message]) -> Vec<u8> {
+        /// Produce (api_key 0), version 3: the first version to carry
+        /// record batches, and the last before flexible encoding.
+        const API_KEY_PRODUCE: i16 = 0;
+        const API_VERSION: i16 = 3;
+
+        let mut partitioned: BTreeMap<i32, Vec<&Message>> = BTreeMap::new();
+        for message in batch {
+            partitioned
+                .entry(self.partition(message))
+                .or_default()
+                .push(message);
+        }
+
+        let mut body = Vec::new();
+        put_i16(&mut body, API_KEY_PRODUCE);
+        put_i16(&mut body, API_VERSION);
+        put_i32(&mut body, 0); // correlation_id
+        put_string(&mut body, "tracing-subscriber"); // client_id
+        put_i16(&mut body, -1); // transactional_id: null
+        put_i16(&mut body, 1); // acks: leader only
+        put_i32(&mut body, 30_000); // timeout_ms
+        put_i32(&mut body, 1); // one topic
+        put_string(&mut body, &self.topic);
+        put_i32(&mut body, partitioned.len() as i32);
+        for (partition, messages) in &partitioned {
+            put_i32(&mut body, *partition);
+            let records = encode_record_batch(messages);
+            put_i32(&mut body, records.len() as i32);
+            body.extend_from_slice(&records);
+        }
+
+        let mut request = Vec::with_capacity(body.len() + 4);
+        put_i32(&mut request, body.len() as i32);
+        request.extend_from_slice(&body);
+        request
+    }
+
+    /// Sends one produce request, returning an error unless the broker
+    /// acknowledges every partition.
+    fn send(&self, request: &[u8]) -> io::Result<()> {
+        let mut stream = TcpStream::connect(&self.broker)?;
+        stream.write_all(request)?;
+
+        let mut size = [0; 4];
+        stream.read_exact(&mut size)?;
+        let mut response = vec![0; i32::from_be_bytes(size).max(0) as usize];
+        stream.read_exact(&mut response)?;
+        check_produce_response(&response)
+    }
+}
+
+/// Checks every partition error code in a produce response.
+fn check_produce_response(response: &[u8]) -> io::Result<()> {
+    let mut cursor = Cursor { bytes: response };
+    cursor.i32()?; // correlation_id
+    let topics = cursor.i32()?;
+    for _ in 0..topics {
+        let name_len = cursor.i16()?.max(0) as usize;
+        cursor.skip(name_len)?;
+        let partitions = cursor.i32()?;
+        for _ in 0..partitions {
+            let partition = cursor.i32()?;
+            let error_code = cursor.i16()?;
+            cursor.skip(16)?; // base_offset, log_append_time
+            if error_code != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!(
+                        "broker rejected partition {} with error code {}",
+                        partition, error_code,
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A checked big-endian reader over a response body.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+}
+
+impl Cursor<'_> {
+    fn i16(&mut self) -> io::Result<i16> {
+        let (head, rest) = split(self.bytes, 2)?;
+        self.bytes = rest;
+        Ok(i16::from_be_bytes([head[0], head[1]]))
+    }
+
+    fn i32(&mut self) -> io::Result<i32> {
+        let (head, rest) = split(self.bytes, 4)?;
+        self.bytes = rest;
+        Ok(i32::from_be_bytes([head[0], head[1], head[2], head[3]]))
+    }
+
+    fn skip(&mut self, n: usize) -> io::Result<()> {
+        let (_, rest) = split(self.bytes, n)?;
+        self.bytes = rest;
+        Ok(())
+    }
+}
+
+fn split(bytes: &[u8], n: usize) -> io::Result<(&[u8], &[u8])> {
+    if bytes.len() < n {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "truncated produce response",
+        ));
+    }
+    Ok(bytes.split_at(n))
+}
+
+// === record batch encoding ===
+//
+// The "v2" record batch is Kafka's on-the-wire (and on-disk) message
+// format since 0.11. Offsets below refer to the batch header layout in the
+// Kafka protocol guide.
+
+/// Encodes `messages` as one record batch.
+fn encode_record_batch(messages: &[&Message]) -> Vec<u8> {
+    let first_timestamp = messages.iter().map(|m| m.millis).min().unwrap_or(0);
+    let max_timestamp = messages.iter().map(|m| m.millis).max().unwrap_or(0);
+
+    // Everything the CRC covers: attributes through the records.
+    let mut crc_body = Vec::new();
+    put_i16(&mut crc_body, 0); // attributes: no compression
+    put_i32(&mut crc_body, messages.len() as i32 - 1); // last_offset_delta
+    put_i64(&mut crc_body, first_timestamp);
+    put_i64(&mut crc_body, max_timestamp);
+    put_i64(&mut crc_body, -1); // producer_id: not idempotent
+    put_i16(&mut crc_body, -1); // producer_epoch
+    put_i32(&mut crc_body, -1); // base_sequence
+    put_i32(&mut crc_body, messages.len() as i32);
+    for (offset, message) in messages.iter().enumerate() {
+        encode_record(&mut crc_body, offset as i64, message, first_timestamp);
+    }
+
+    let mut batch = Vec::with_capacity(crc_body.len() + 21);
+    put_i64(&mut batch, 0); // base_offset: assigned by the broker
+    put_i32(&mut batch, crc_body.len() as i32 + 9); // batch_length
+    put_i32(&mut batch, -1); // partition_leader_epoch
+    batch.push(2); // magic
+    put_i32(&mut batch, crc32c(&crc_body) as i32);
+    batch.extend_from_slice(&crc_body);
+    batch
+}
+
+/// Encodes one record within a batch.
+fn encode_record(buf: &mut Vec<u8>, offset_delta: i64, message: &Message, first_timestamp: i64) {
+    let mut record = Vec::new();
+    record.push(0); // attributes
+    put_varint(&mut record, message.millis - first_timestamp);
+    put_varint(&mut record, offset_delta);
+    match &message.key {
+        Some(key) => {
+            put_varint(&mut record, key.len() as i64);
+            record.extend_from_slice(key.as_bytes());
+        }
+        None => put_varint(&mut record, -1),
+    }
+    put_varint(&mut record, message.payload.len() as i64);
+    record.extend_from_slice(&message.payload);
+    put_varint(&mut record, 0); // headers
+
+    put_varint(buf, record.len() as i64);
+    buf.extend_from_slice(&record);
+}
+
+fn put_i16(buf: &mut Vec<u8>, value: i16) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_i32(buf: &mut Vec<u8>, value: i32) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_i64(buf: &mut Vec<u8>, value: i64) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_string(buf: &mut Vec<u8>, value: &str) {
+    put_i16(buf, value.len() as i16);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+/// Writes a zigzag-encoded variable-length integer, as records use.
+fn put_varint(buf: &mut Vec<u8>, value: i64) {
+    let mut value = ((value << 1) ^ (value >> 63)) as u64;
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// Computes the CRC-32C (Castagnoli) checksum record batches carry.
+fn crc32c(data: &[u8]) -> u32 {
+    const POLY: u32 = 0x82f6_3b78;
+    let mut crc = u32::MAX;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (if crc & 1 != 0 { POLY } else { 0 });
+        }
+    }
+    !crc
+}
+
+/// Hashes `key` with the murmur2 variant the official Java client
+/// partitions by.
+fn murmur2(key: &[u8]) -> u32 {
+    const SEED: u32 = 0x9747_b28c;
+    const M: u32 = 0x5bd1_e995;
+
+    let mut hash = SEED ^ key.len() as u32;
+    let mut chunks = key.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        word = word.wrapping_mul(M);
+        word ^= word >> 24;
+        word = word.wrapping_mul(M);
+        hash = hash.wrapping_mul(M);
+        hash ^= word;
+    }
+    let tail = chunks.remainder();
+    if tail.len() >= 3 {
+        hash ^= u32::from(tail[2]) << 16;
+    }
+    if tail.len() >= 2 {
+        hash ^= u32::from(tail[1]) << 8;
+    }
+    if !tail.is_empty() {
+        hash ^= u32::from(tail[0]);
+        hash = hash.wrapping_mul(M);
+    }
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(M);
+    hash ^= hash >> 15;
+    hash
+}
+
+/// Returns the current wall-clock time in milliseconds since the Unix
+/// epoch.
+fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+// === serialization ===
+
+/// A captured field value, serialized once the format is known.
+enum Value {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(value) => value.fmt(f),
+            Value::I64(value) => value.fmt(f),
+            Value::U64(value) => value.fmt(f),
+            Value::F64(value) => value.fmt(f),
+            Value::Str(value) => f.write_str(value),
+        }
+    }
+}
+
+/// Records event fields, extracting the `message` field.
+struct FieldVisitor {
+    message: Option<String>,
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl field::Visit for FieldVisitor {
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.fields.push((field.name(), Value::Bool(value)));
+    }
+
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.fields.push((field.name(), Value::I64(value)));
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.fields.push((field.name(), Value::U64(value)));
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.fields.push((field.name(), Value::F64(value)));
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_owned());
+            return;
+        }
+        self.fields
+            .push((field.name(), Value::Str(value.to_owned())));
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn fmt::Debug) {
+        let rendered = format!("{:?}", value);
+        if field.name() == "message" {
+            self.message = Some(rendered);
+            return;
+        }
+        self.fields.push((field.name(), Value::Str(rendered)));
+    }
+}
+
+/// Serializes one event as a JSON object.
+fn encode_json(
+    millis: i64,
+    metadata: &tracing_core::Metadata<'_>,
+    visitor: &FieldVisitor,
+) -> Vec<u8> {
+    use std::fmt::Write as _;
+
+    let mut json = String::from("{\"timestamp\":");
+    let _ = write!(json, "{}", millis);
+    json.push_str(",\"level\":\"");
+    json.push_str(metadata.level().as_str());
+    json.push_str("\",\"target\":\"");
+    escape_into(&mut json, metadata.target());
+    json.push('"');
+    if let Some(message) = &visitor.message {
+        json.push_str(",\"message\":\"");
+        escape_into(&mut json, message);
+        json.push('"');
+    }
+    if !visitor.fields.is_empty() {
+        json.push_str(",\"fields\":{");
+        for (i, (name, value)) in visitor.fields.iter().enumerate() {
+            if i != 0 {
+                json.push(',');
+            }
+            json.push('"');
+            escape_into(&mut json, name);
+            json.push_str("\":");
+            match value {
+                Value::Str(value) => {
+                    json.push('"');
+                    escape_into(&mut json, value);
+                    json.push('"');
+                }
+                Value::F64(value) if !value.is_finite() => {
+                    let _ = write!(json, "\"{}\"", value);
+                }
+                value => {
+                    let _ = write!(json, "{}", value);
+                }
+            }
+        }
+        json.push('}');
+    }
+    json.push('}');
+    json.into_bytes()
+}
+
+/// Writes `value` into `out` with JSON string escaping.
+fn escape_into(out: &mut String, value: &str) {
+    use std::fmt::Write as _;
+
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+/// Serializes one event as a MessagePack map with the same layout as the
+/// JSON format.
+fn encode_msgpack(
+    millis: i64,
+    metadata: &tracing_core::Metadata<'_>,
+    visitor: &FieldVisitor,
+) -> Vec<u8> {
+    let mut buf = Vec::new();
+    let mut members = 3;
+    if visitor.message.is_some() {
+        members += 1;
+    }
+    if !visitor.fields.is_empty() {
+        members += 1;
+    }
+    mp_map_len(&mut buf, members);
+    mp_str(&mut buf, "timestamp");
+    mp_i64(&mut buf, millis);
+    mp_str(&mut buf, "level");
+    mp_str(&mut buf, metadata.level().as_str());
+    mp_str(&mut buf, "target");
+    mp_str(&mut buf, metadata.target());
+    if let Some(message) = &visitor.message {
+        mp_str(&mut buf, "message");
+        mp_str(&mut buf, message);
+    }
+    if !visitor.fields.is_empty() {
+        mp_str(&mut buf, "fields");
+        mp_map_len(&mut buf, visitor.fields.len());
+        for (name, value) in &visitor.fields {
+            mp_str(&mut buf, name);
+            match value {
+                Value::Bool(value) => buf.push(if *value { 0xc3 } else { 0xc2 }),
+                Value::I64(value) => mp_i64(&mut buf, *value),
+                Value::U64(value) if *value <= i64::MAX as u64 => mp_i64(&mut buf, *value as i64),
+                Value::U64(value) => {
+                    buf.push(0xcf);
+                    buf.extend_from_slice(&value.to_be_bytes());
+                }
+                Value::F64(value) => {
+                    buf.push(0xcb);
+                    buf.extend_from_slice(&value.to_be_bytes());
+                }
+                Value::Str(value) => mp_str(&mut buf, value),
+            }
+        }
+    }
+    buf
+}
+
+fn mp_map_len(buf: &mut Vec<u8>, len: usize) {
+    if len <= 0xf {
+        buf.push(0x80 | len as u8);
+    } else {
+        buf.push(0xde);
+        buf.extend_from_slice(&(len as u16).to_be_bytes());
+    }
+}
+
+fn mp_str(buf: &mut Vec<u8>, value: &str) {
+    let bytes = value.as_bytes();
+    if bytes.len() <= 0x1f {
+        buf.push(0xa0 | bytes.len() as u8);
+    } else if bytes.len() <= 0xff {
+        buf.push(0xd9);
+        buf.push(bytes.len() as u8);
+    } else {
+        buf.push(0xda);
+        buf.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    }
+    buf.extend_from_slice(bytes);
+}
+
+fn mp_i64(buf: &mut Vec<u8>, value: i64) {
+    match value {
+        0..=0x7f => buf.push(value as u8),
+        -32..=-1 => buf.push(value as u8),
+        _ => {
+            buf.push(0xd3);
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::net::TcpListener;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn crc32c_matches_the_reference_vector() {
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+    }
+
+    #[test]
+    fn murmur2_matches_the_java_client() {
+        // Vectors from the Java client's UtilsTest.
+        assert_eq!(murmur2(b"21") as i32, -973_932_308);
+        assert_eq!(murmur2(b"foobar") as i32, -790_332_482);
+        assert_eq!(murmur2(b"a-little-bit-long-string") as i32, -985_981_536);
+    }
+
+    #[test]
+    fn varints_zigzag_encode() {
+        let mut buf = Vec::new();
+        put_varint(&mut buf, 0);
+        assert_eq!(buf, [0x00]);
+        buf.clear();
+        put_varint(&mut buf, -1);
+        assert_eq!(buf, [0x01]);
+        buf.clear();
+        put_varint(&mut buf, 300);
+        assert_eq!(buf, [0xd8, 0x04]);
+    }
+
+    #[test]
+    fn record_batches_are_well_formed() {
+        let messages = [
+            Message {
+                millis: 1000,
+                key: Some("tenant-42".to_owned()),
+                payload: b"{}".to_vec(),
+            },
+            Message {
+                millis: 1005,
+                key: None,
+                payload: b"{}".to_vec(),
+            },
+        ];
+        let refs: Vec<&Message> = messages.iter().collect();
+        let batch = encode_record_batch(&refs);
+
+        // batch_length covers everything after itself.
+        let batch_length = i32::from_be_bytes([batch[8], batch[9], batch[10], batch[11]]);
+        assert_eq!(batch_length as usize, batch.len() - 12);
+        // magic is v2.
+        assert_eq!(batch[16], 2);
+        // The CRC covers everything after itself.
+        let crc = i32::from_be_bytes([batch[17], batch[18], batch[19], batch[20]]);
+        assert_eq!(crc, crc32c(&batch[21..]) as i32);
+        // record_count sits at the end of the batch header.
+        let count = i32::from_be_bytes([batch[57], batch[58], batch[59], batch[60]]);
+        assert_eq!(count, 2);
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    /// Accepts one produce request, responds with `error_code` for a single
+    /// partition, and returns the raw request.
+    fn accept_produce(listener: &TcpListener, error_code: i16) -> Vec<u8> {
+        let (mut stream, _) = listener.accept().expect("no produce request received");
+        let mut size = [0; 4];
+        stream.read_exact(&mut size).expect("failed to read size");
+        let mut request = vec![0; i32::from_be_bytes(size) as usize];
+        stream
+            .read_exact(&mut request)
+            .expect("failed to read request");
+
+        let mut body = Vec::new();
+        put_i32(&mut body, 0); // correlation_id
+        put_i32(&mut body, 1); // one topic
+        put_string(&mut body, "logs");
+        put_i32(&mut body, 1); // one partition
+        put_i32(&mut body, 0); // partition index
+        put_i16(&mut body, error_code);
+        put_i64(&mut body, 0); // base_offset
+        put_i64(&mut body, -1); // log_append_time
+        put_i32(&mut body, 0); // throttle_time_ms
+        let mut response = Vec::new();
+        put_i32(&mut response, body.len() as i32);
+        response.extend_from_slice(&body);
+        stream.write_all(&response).expect("failed to respond");
+        request
+    }
+
+    #[test]
+    fn publishes_keyed_events() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let (producer, errors) = Subscriber::builder()
+            .with_broker(addr.to_string())
+            .with_topic("logs")
+            .with_key_field("tenant_id")
+            .finish()
+            .expect("failed to start producer");
+        let collector = crate::registry().with(producer);
+
+        with_default(collector, || {
+            tracing::info!(tenant_id = "tenant-42", "logged in");
+        });
+        // Dropping the collector disconnects the queue, flushing the batch.
+
+        let request = accept_produce(&listener, 0);
+        assert!(contains(&request, b"logs"), "missing topic");
+        assert!(contains(&request, b"tenant-42"), "missing message key");
+        assert!(contains(&request, b"logged in"), "missing message body");
+        assert!(
+            contains(&request, b"\"tenant_id\":\"tenant-42\""),
+            "missing key field in payload",
+        );
+        assert!(errors.try_next().is_none(), "unexpected delivery error");
+    }
+
+    #[test]
+    fn delivery_failures_reach_the_error_channel() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let (producer, errors) = Subscriber::builder()
+            .with_broker(addr.to_string())
+            .with_topic("logs")
+            .with_max_retries(1)
+            .finish()
+            .expect("failed to start producer");
+        let collector = crate::registry().with(producer);
+
+        with_default(collector, || {
+            tracing::info!("going nowhere");
+            tracing::info!("also going nowhere");
+        });
+
+        // NOT_LEADER_OR_FOLLOWER, on every attempt.
+        accept_produce(&listener, 6);
+        accept_produce(&listener, 6);
+
+        let error = errors.next().expect("no delivery error reported");
+        assert_eq!(error.events(), 2);
+        assert!(
+            error.error().to_string().contains("error code 6"),
+            "unexpected error: {}",
+            error,
+        );
+    }
+}
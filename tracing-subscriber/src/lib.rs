@@ -52,6 +52,109 @@
 //! - `alloc`: Depend on [`liballoc`] (enabled by "std").
 //! - `env-filter`: Enables the [`EnvFilter`] type, which implements filtering
 //!   similar to the [`env_logger` crate]. **Requires "std"**.
+//! - `admin`: Enables the [`admin`] module, which serves a control endpoint
+//!   for changing an [`EnvFilter`] at runtime. **Requires "env-filter" and
+//!   "registry"**.
+//! - `timing`: Enables the [`timing`] module, which aggregates span busy and
+//!   idle times into per-callsite latency histograms. **Requires
+//!   "registry"**.
+//! - `report`: Enables the [`report`] module, which records the span tree
+//!   and renders it as a standalone HTML trace report. **Requires
+//!   "registry"**.
+//! - `msgpack`: Enables the [`msgpack`] module, which writes events as
+//!   length-prefixed MessagePack frames. **Requires "fmt"**.
+//! - `span-children`: Maintains child span links in the [`Registry`],
+//!   enabling the [`SpanRef::children`] and [`SpanRef::descendants`]
+//!   queries. **Requires "registry"**.
+//! - `flight`: Enables the [`flight`] module, which records recent events
+//!   into per-thread ring buffers for on-demand dumping. **Requires
+//!   "fmt"**.
+//! - `panic-hook`: Enables the [`panic`] module, which installs a panic
+//!   hook reporting panics as structured error events.
+//! - `metrics`: Enables the [`metrics`] module, which aggregates
+//!   conventionally-named event fields into in-process metrics. **Requires
+//!   "timing"**.
+//! - `prometheus`: Enables the [`prometheus`] module, which renders metrics
+//!   in the Prometheus text exposition format and serves them over HTTP.
+//!   **Requires "metrics"**.
+//! - `otlp`: Enables the [`otlp`] module, which exports spans over
+//!   OTLP/HTTP without the OpenTelemetry SDK. **Requires "registry"**.
+//! - `chrome-trace`: Enables the [`chrome`] module, which writes spans and
+//!   events in the Chrome Trace Event JSON format for `chrome://tracing`
+//!   and Perfetto. **Requires "registry"**.
+//! - `perfetto`: Enables the [`perfetto`] module, which writes spans and
+//!   events as Perfetto TrackEvent protos. **Requires "registry"**.
+//! - `user-events`: Enables the [`user_events`] module, which emits spans
+//!   and events as Linux `user_events` tracepoints. **Requires "registry";
+//!   Linux only**.
+//! - `etw`: Enables the [`etw`] module, which emits spans and events as
+//!   ETW TraceLogging records. **Requires "registry"; the subscriber is
+//!   Windows only**.
+//! - `usdt`: Enables the [`usdt`] module, which fires USDT/SDT probes for
+//!   span lifecycle transitions and event dispatch. **Requires
+//!   "registry"**.
+//! - `tui`: Enables the [`tui`] module, which shows live events and the
+//!   tree of currently-open spans in an interactive terminal view.
+//!   **Requires "registry"**.
+//! - `websocket`: Enables the [`websocket`] module, which streams
+//!   JSON-formatted events to WebSocket clients with per-connection
+//!   filters. **Requires "registry"**.
+//! - `grpc`: Enables the [`grpc`] module, which streams events to gRPC
+//!   clients over a server-streaming RPC with per-client filters.
+//!   **Requires "registry"**.
+//! - `test-util`: Enables the [`test`] module, which records events and
+//!   span lifecycles into an inspectable store for test assertions.
+//!   **Requires "registry"**.
+//! - `sqlite`: Enables the [`sqlite`] module, which writes events and
+//!   completed spans into a SQLite database. **Requires "registry" and
+//!   the [`rusqlite` crate]**.
+//! - `elasticsearch`: Enables the [`elasticsearch`] module, which ships
+//!   JSON-formatted events to an Elasticsearch or OpenSearch cluster in
+//!   bulk requests. **Requires "registry"**.
+//! - `kafka`: Enables the [`kafka`] module, which publishes serialized
+//!   events to a Kafka topic with key-based partitioning. **Requires
+//!   "registry"**.
+//! - `loki`: Enables the [`loki`] module, which pushes events to a Grafana
+//!   Loki server, grouped into streams by a configurable label set.
+//!   **Requires "registry"**.
+//! - `sentry`: Enables the [`sentry`] module, which reports error events to
+//!   Sentry with lower-severity events attached as breadcrumbs. **Requires
+//!   "registry"**.
+//! - `honeycomb`: Enables the [`honeycomb`] module, which sends completed
+//!   spans to Honeycomb as events with trace identifiers and dynamic
+//!   sampling. **Requires "registry"**.
+//! - `cloudwatch`: Enables the [`cloudwatch`] module, which ships formatted
+//!   events to AWS CloudWatch Logs with signed requests and batching under
+//!   the API limits. **Requires "registry"**.
+//! - `gcp-logging`: Enables the [`gcp_logging`] module, which writes
+//!   structured entries to Google Cloud Logging with severity mapping and
+//!   trace correlation. **Requires "registry"**.
+//! - `datadog`: Enables the [`datadog`] module, which exports completed
+//!   span trees to the Datadog agent in its native trace format.
+//!   **Requires "registry"**.
+//! - `trace-context`: Enables the [`trace_context`] module, which
+//!   propagates W3C `traceparent`/`tracestate` headers across process
+//!   boundaries. **Requires "registry"**.
+//! - `span-ids`: Enables the [`span_ids`] module, which gives application
+//!   code layer-agnostic access to the wire trace and span IDs assigned to
+//!   a span. **Requires "registry"**.
+//! - `baggage`: Enables the [`baggage`] module, which propagates key-value
+//!   context to descendant spans and events and across process boundaries.
+//!   **Requires "registry"**.
+//! - `signal`: Enables the [`signal`] module, which changes the active
+//!   filter in response to `SIGUSR1`/`SIGUSR2` (Unix only). **Requires
+//!   "env-filter"**.
+//! - `introspect`: Enables the [`introspect`] module, which dumps the set
+//!   of currently-live spans as JSON. **Requires "registry"**.
+//! - `deferred`: Enables the [`deferred`] module, which buffers each root
+//!   span's events and writes them out only if the span failed.
+//!   **Requires "fmt"**.
+//! - `budget`: Enables the [`budget`] module, which drops events for
+//!   tenants exceeding a per-tenant volume budget. **Requires
+//!   "registry"**.
+//! - `rollup`: Enables the [`rollup`] module, which aggregates repetitive
+//!   events into periodic per-callsite summaries. **Requires
+//!   "registry"**.
 //! - `fmt`: Enables the [`fmt`] module, which provides a subscriber
 //!   implementation for printing formatted representations of trace events.
 //!   Enabled by default. **Requires "registry" and "std"**.
@@ -119,6 +222,41 @@
 //!
 //! [`fmt`]: mod@fmt
 //! [`registry`]: mod@registry
+//! [`flight`]: mod@flight
+//! [`panic`]: mod@crate::panic
+//! [`metrics`]: mod@metrics
+//! [`prometheus`]: mod@prometheus
+//! [`otlp`]: mod@otlp
+//! [`chrome`]: mod@chrome
+//! [`perfetto`]: mod@perfetto
+//! [`user_events`]: mod@user_events
+//! [`etw`]: mod@etw
+//! [`usdt`]: mod@usdt
+//! [`tui`]: mod@tui
+//! [`websocket`]: mod@websocket
+//! [`grpc`]: mod@grpc
+//! [`test`]: mod@test
+//! [`sqlite`]: mod@sqlite
+//! [`rusqlite` crate]: https://crates.io/crates/rusqlite
+//! [`elasticsearch`]: mod@elasticsearch
+//! [`kafka`]: mod@kafka
+//! [`loki`]: mod@loki
+//! [`sentry`]: mod@sentry
+//! [`honeycomb`]: mod@honeycomb
+//! [`cloudwatch`]: mod@cloudwatch
+//! [`gcp_logging`]: mod@gcp_logging
+//! [`datadog`]: mod@datadog
+//! [`trace_context`]: mod@trace_context
+//! [`span_ids`]: mod@span_ids
+//! [`baggage`]: mod@baggage
+//! [`signal`]: mod@signal
+//! [`introspect`]: mod@introspect
+//! [`deferred`]: mod@deferred
+//! [`budget`]: mod@budget
+//! [`rollup`]: mod@rollup
+//! [`Registry`]: registry::Registry
+//! [`SpanRef::children`]: registry::SpanRef::children
+//! [`SpanRef::descendants`]: registry::SpanRef::descendants
 //! [`Collect`]: tracing_core::collect::Collect
 //! [collector]: tracing_core::collect::Collect
 //! [`EnvFilter`]: filter::EnvFilter
@@ -205,6 +343,195 @@ feature! {
     pub use filter::EnvFilter;
 }
 
+feature! {
+    #![all(feature = "admin", feature = "std")]
+    pub mod admin;
+}
+
+feature! {
+    #![all(feature = "config", feature = "std")]
+    pub mod config;
+}
+
+feature! {
+    #![all(feature = "timing", feature = "std")]
+    pub mod timing;
+}
+
+feature! {
+    #![all(feature = "report", feature = "std")]
+    pub mod report;
+}
+
+feature! {
+    #![all(feature = "msgpack", feature = "std")]
+    pub mod msgpack;
+}
+
+feature! {
+    #![all(feature = "flight", feature = "std")]
+    pub mod flight;
+}
+
+feature! {
+    #![all(feature = "panic-hook", feature = "std")]
+    pub mod panic;
+}
+
+feature! {
+    #![all(feature = "metrics", feature = "std")]
+    pub mod metrics;
+}
+
+feature! {
+    #![all(feature = "prometheus", feature = "std")]
+    pub mod prometheus;
+}
+
+feature! {
+    #![all(feature = "otlp", feature = "std")]
+    pub mod otlp;
+}
+
+feature! {
+    #![all(feature = "chrome-trace", feature = "std")]
+    pub mod chrome;
+}
+
+feature! {
+    #![all(feature = "perfetto", feature = "std")]
+    pub mod perfetto;
+}
+
+feature! {
+    #![all(feature = "user-events", feature = "std", target_os = "linux")]
+    pub mod user_events;
+}
+
+feature! {
+    #![all(feature = "etw", feature = "std")]
+    pub mod etw;
+}
+
+feature! {
+    #![all(feature = "usdt", feature = "std")]
+    pub mod usdt;
+}
+
+feature! {
+    #![all(feature = "tui", feature = "std")]
+    pub mod tui;
+}
+
+feature! {
+    #![all(feature = "websocket", feature = "std")]
+    pub mod websocket;
+}
+
+feature! {
+    #![all(feature = "grpc", feature = "std")]
+    pub mod grpc;
+}
+
+feature! {
+    #![all(feature = "test-util", feature = "std")]
+    pub mod test;
+}
+
+feature! {
+    #![all(feature = "sqlite", feature = "std")]
+    pub mod sqlite;
+}
+
+feature! {
+    #![all(feature = "elasticsearch", feature = "std")]
+    pub mod elasticsearch;
+}
+
+feature! {
+    #![all(feature = "kafka", feature = "std")]
+    pub mod kafka;
+}
+
+feature! {
+    #![all(feature = "loki", feature = "std")]
+    pub mod loki;
+}
+
+feature! {
+    #![all(feature = "sentry", feature = "std")]
+    pub mod sentry;
+}
+
+feature! {
+    #![all(feature = "honeycomb", feature = "std")]
+    pub mod honeycomb;
+}
+
+feature! {
+    #![all(feature = "cloudwatch", feature = "std")]
+    pub mod cloudwatch;
+}
+
+feature! {
+    #![all(feature = "gcp-logging", feature = "std")]
+    pub mod gcp_logging;
+}
+
+feature! {
+    #![all(feature = "datadog", feature = "std")]
+    pub mod datadog;
+}
+
+feature! {
+    #![all(feature = "trace-context", feature = "std")]
+    pub mod trace_context;
+}
+
+feature! {
+    #![all(feature = "span-ids", feature = "std")]
+    pub mod span_ids;
+}
+
+feature! {
+    #![all(feature = "baggage", feature = "std")]
+    pub mod baggage;
+}
+
+feature! {
+    #![all(feature = "signal", feature = "std", unix)]
+    pub mod signal;
+}
+
+feature! {
+    #![all(feature = "introspect", feature = "std")]
+    pub mod introspect;
+}
+
+feature! {
+    #![all(feature = "deferred", feature = "std")]
+    pub mod deferred;
+}
+
+feature! {
+    #![all(feature = "budget", feature = "std")]
+    pub mod budget;
+}
+
+feature! {
+    #![all(feature = "rollup", feature = "std")]
+    pub mod rollup;
+}
+
+#[cfg(any(
+    all(feature = "etw", feature = "std"),
+    all(feature = "websocket", feature = "std")
+))]
+mod sha1;
+
+#[cfg(all(feature = "cloudwatch", feature = "std"))]
+mod sha256;
+
 pub use subscribe::Subscribe;
 
 feature! {
@@ -0,0 +1,809 @@
+//! Pushes events to a [Grafana Loki] log aggregation server.
+//!
+//! Loki stores log lines indexed by a small set of *labels*; queries select
+//! streams by label and then grep the lines. Its push API is simple enough
+//! that many services hand-roll a client for it. This module provides that
+//! client as a [`Subscriber`]: events are formatted as [logfmt] lines,
+//! grouped into streams by a configurable label set, batched in a
+//! background thread, and `POST`ed to `/loki/api/v1/push` as
+//! snappy-compressed protobuf — the same wire format the official agents
+//! use — with no dependencies beyond the standard library.
+//!
+//! Labels determine how Loki partitions and indexes the streams, so they
+//! should stay low-cardinality: static labels such as `job` are configured
+//! with [`Builder::with_label`], the event's level is included by default,
+//! and selected event fields (say, `region`) can be promoted to labels with
+//! [`Builder::with_field_label`]. Everything else stays in the log line.
+//! Failed pushes are retried with exponential backoff before the batch is
+//! dropped.
+//!
+//! # Limitations
+//!
+//! - Only plaintext `http://` endpoints are supported; there is no TLS and
+//!   no tenant authentication, so multi-tenant Loki deployments need a
+//!   gateway in front.
+//! - The JSON push format and gzip compression are not implemented; the
+//!   protobuf format is what the official agents send.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{loki, prelude::*};
+//!
+//! let pusher = loki::Subscriber::builder()
+//!     .with_endpoint("127.0.0.1:3100")
+//!     .with_label("job", "my-service")
+//!     .with_field_label("region")
+//!     .finish()
+//!     .expect("failed to start the Loki pusher");
+//! let collector = tracing_subscriber::registry().with(pusher);
+//! # let _ = collector;
+//! ```
+//!
+//! [Grafana Loki]: https://grafana.com/oss/loki/
+//! [logfmt]: https://brandur.org/logfmt
+use crate::subscribe::{Context, Subscribe};
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, SystemTime},
+};
+use tracing_core::{field, Collect, Event};
+
+/// A [`Subscribe`] implementation that pushes events to a Loki server.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    // The standard library's channel sender is not `Sync` on our minimum
+    // supported Rust version, so sends are serialized through a mutex.
+    sender: Mutex<mpsc::Sender<Entry>>,
+    labels: Vec<(String, String)>,
+    level_label: bool,
+    target_label: bool,
+    field_labels: Vec<String>,
+}
+
+/// Configures a Loki pusher [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    endpoint: String,
+    labels: Vec<(String, String)>,
+    level_label: bool,
+    target_label: bool,
+    field_labels: Vec<String>,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+/// The default endpoint, on the conventional Loki port.
+const DEFAULT_ENDPOINT: &str = "127.0.0.1:3100";
+
+/// The path the push API is served on.
+const PUSH_PATH: &str = "/loki/api/v1/push";
+
+/// A formatted event, as handed to the push thread.
+struct Entry {
+    /// The event time in nanoseconds since the Unix epoch.
+    nanos: u64,
+    /// The rendered label set selecting the stream, e.g.
+    /// `{job="api",level="info"}`.
+    labels: String,
+    /// The logfmt-rendered log line.
+    line: String,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a [`Builder`] for configuring a Loki pusher.
+    pub fn builder() -> Builder {
+        Builder {
+            endpoint: DEFAULT_ENDPOINT.to_owned(),
+            labels: Vec::new(),
+            level_label: true,
+            target_label: false,
+            field_labels: Vec::new(),
+            batch_size: 512,
+            batch_timeout: Duration::from_secs(5),
+            max_retries: 3,
+        }
+    }
+}
+
+impl<C: Collect> Subscribe<C> for Subscriber {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        let mut visitor = FieldVisitor {
+            message: None,
+            fields: Vec::new(),
+        };
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let mut labels = String::from("{");
+        let mut first = true;
+        for (key, value) in &self.labels {
+            push_label(&mut labels, &mut first, key, value);
+        }
+        if self.level_label {
+            let level = metadata.level().as_str().to_ascii_lowercase();
+            push_label(&mut labels, &mut first, "level", &level);
+        }
+        if self.target_label {
+            push_label(&mut labels, &mut first, "target", metadata.target());
+        }
+        for name in &self.field_labels {
+            if let Some((_, value)) = visitor.fields.iter().find(|(field, _)| field == name) {
+                push_label(&mut labels, &mut first, name, value);
+            }
+        }
+        labels.push('}');
+
+        let mut line = visitor.message.unwrap_or_default();
+        for (name, value) in &visitor.fields {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(name);
+            line.push('=');
+            push_logfmt_value(&mut line, value);
+        }
+
+        // The only send error is a disconnected worker; events are dropped
+        // in that case, as there is nowhere to push them to.
+        let _ = self
+            .sender
+            .lock()
+            .expect("loki sender poisoned")
+            .send(Entry {
+                nanos: unix_nanos(),
+                labels,
+                line,
+            });
+    }
+}
+
+/// Appends one `key="value"` pair to a rendered label set.
+fn push_label(labels: &mut String, first: &mut bool, key: &str, value: &str) {
+    if !*first {
+        labels.push(',');
+    }
+    *first = false;
+    labels.push_str(key);
+    labels.push_str("=\"");
+    for c in value.chars() {
+        match c {
+            '"' => labels.push_str("\\\""),
+            '\\' => labels.push_str("\\\\"),
+            '\n' => labels.push_str("\\n"),
+            c => labels.push(c),
+        }
+    }
+    labels.push('"');
+}
+
+/// Appends a logfmt value, quoting it if it contains spaces, quotes, or
+/// equals signs.
+fn push_logfmt_value(line: &mut String, value: &str) {
+    let needs_quotes = value.is_empty() || value.contains(&[' ', '"', '=', '\n'][..]);
+    if !needs_quotes {
+        line.push_str(value);
+        return;
+    }
+    line.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            c => line.push(c),
+        }
+    }
+    line.push('"');
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the `host:port` of the Loki server to push to.
+    ///
+    /// An `http://` scheme prefix is accepted and ignored. The push path
+    /// (`/loki/api/v1/push`) is appended automatically. The default is
+    /// `127.0.0.1:3100`, the conventional Loki port.
+    pub fn with_endpoint(self, endpoint: impl Into<String>) -> Self {
+        let endpoint = endpoint.into();
+        let endpoint = endpoint
+            .strip_prefix("http://")
+            .map(String::from)
+            .unwrap_or(endpoint);
+        let endpoint = endpoint.trim_end_matches('/').to_owned();
+        Self { endpoint, ..self }
+    }
+
+    /// Adds a static label attached to every stream, such as `job` or
+    /// `environment`.
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets whether the event's level is attached as a `level` label.
+    ///
+    /// Enabled by default; Loki's level-aware log volume views rely on it.
+    pub fn with_level_label(self, level_label: bool) -> Self {
+        Self {
+            level_label,
+            ..self
+        }
+    }
+
+    /// Sets whether the event's target is attached as a `target` label.
+    ///
+    /// Disabled by default: targets are usually module paths, which can be
+    /// numerous enough to strain Loki's label index.
+    pub fn with_target_label(self, target_label: bool) -> Self {
+        Self {
+            target_label,
+            ..self
+        }
+    }
+
+    /// Promotes an event field to a stream label.
+    ///
+    /// Events carrying the field are grouped into per-value streams; the
+    /// field also remains in the log line. Only promote low-cardinality
+    /// fields — a label per user or per request defeats Loki's index.
+    pub fn with_field_label(mut self, name: impl Into<String>) -> Self {
+        self.field_labels.push(name.into());
+        self
+    }
+
+    /// Sets the number of events that triggers a push.
+    ///
+    /// A batch is pushed when it reaches this size, or when
+    /// [`batch_timeout`](Self::with_batch_timeout) elapses, whichever comes
+    /// first. The default is 512 events.
+    pub fn with_batch_size(self, batch_size: usize) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            ..self
+        }
+    }
+
+    /// Sets how long events may be buffered before being pushed.
+    ///
+    /// The default is 5 seconds.
+    pub fn with_batch_timeout(self, batch_timeout: Duration) -> Self {
+        Self {
+            batch_timeout,
+            ..self
+        }
+    }
+
+    /// Sets how many times a failed push is retried before the batch is
+    /// dropped.
+    ///
+    /// Retries back off exponentially, starting at 100 milliseconds. The
+    /// default is 3 retries.
+    pub fn with_max_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Returns the configured pusher [`Subscriber`], spawning its push
+    /// thread.
+    ///
+    /// The thread runs until the `Subscriber` is dropped; any events still
+    /// buffered at that point are flushed before it exits.
+    pub fn finish(self) -> io::Result<Subscriber> {
+        let (sender, receiver) = mpsc::channel();
+        let worker = Worker {
+            endpoint: self.endpoint,
+            batch_size: self.batch_size,
+            batch_timeout: self.batch_timeout,
+            max_retries: self.max_retries,
+        };
+        thread::Builder::new()
+            .name("tracing-loki".into())
+            .spawn(move || worker.run(receiver))?;
+        Ok(Subscriber {
+            sender: Mutex::new(sender),
+            labels: self.labels,
+            level_label: self.level_label,
+            target_label: self.target_label,
+            field_labels: self.field_labels,
+        })
+    }
+}
+
+// === impl Worker ===
+
+/// The push thread: batches entries, groups them into streams, and posts
+/// them to the push API.
+struct Worker {
+    endpoint: String,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+impl Worker {
+    fn run(&self, receiver: mpsc::Receiver<Entry>) {
+        let mut batch = Vec::new();
+        loop {
+            match receiver.recv_timeout(self.batch_timeout) {
+                Ok(entry) => {
+                    batch.push(entry);
+                    if batch.len() >= self.batch_size {
+                        self.push(&mut batch);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    if !batch.is_empty() {
+                        self.push(&mut batch);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    if !batch.is_empty() {
+                        self.push(&mut batch);
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Pushes `batch`, retrying with exponential backoff; the batch is
+    /// dropped either way.
+    fn push(&self, batch: &mut Vec<Entry>) {
+        let body = snappy_compress(&encode_push_request(batch));
+        batch.clear();
+        let mut backoff = Duration::from_millis(100);
+        for attempt in 0..=self.max_retries {
+            if self.post(&body).is_ok() {
+                return;
+            }
+            if attempt < self.max_retries {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+    }
+
+    /// Posts one compressed push request, returning an error unless the
+    /// server responds with a success status.
+    fn post(&self, body: &[u8]) -> io::Result<()> {
+        let mut stream = TcpStream::connect(&self.endpoint)?;
+        let request = format!(
+            "POST {} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Content-Type: application/x-protobuf\r\n\
+             Content-Encoding: snappy\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n",
+            PUSH_PATH,
+            self.endpoint,
+            body.len(),
+        );
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body)?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        // "HTTP/1.1 204 No Content" => "204"
+        let code = status.split_whitespace().nth(1).unwrap_or("");
+        if code.starts_with('2') {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Loki responded with status {:?}", status.trim()),
+            ))
+        }
+    }
+}
+
+// === protobuf encoding ===
+//
+// Loki's `PushRequest` is a three-message protobuf schema; encoding it by
+// hand avoids a code-generation dependency. Field numbers below refer to
+// the logproto definitions.
+
+/// Encodes a `PushRequest`, grouping `batch` into one stream per label
+/// set.
+fn encode_push_request(batch: &[Entry]) -> Vec<u8> {
+    let mut streams: BTreeMap<&str, Vec<&Entry>> = BTreeMap::new();
+    for entry in batch {
+        streams.entry(&entry.labels).or_default().push(entry);
+    }
+
+    let mut request = Vec::new();
+    for (labels, entries) in &streams {
+        // StreamAdapter { labels (1), entries (2) }.
+        let mut stream = Vec::new();
+        encode_string(&mut stream, 1, labels);
+        for entry in entries {
+            // EntryAdapter { timestamp (1): Timestamp { seconds (1),
+            // nanos (2) }, line (2) }.
+            let mut timestamp = Vec::new();
+            encode_key(&mut timestamp, 1, WIRE_VARINT);
+            encode_varint(&mut timestamp, entry.nanos / 1_000_000_000);
+            encode_key(&mut timestamp, 2, WIRE_VARINT);
+            encode_varint(&mut timestamp, entry.nanos % 1_000_000_000);
+            let mut entry_msg = Vec::new();
+            encode_message(&mut entry_msg, 1, &timestamp);
+            encode_string(&mut entry_msg, 2, &entry.line);
+            encode_message(&mut stream, 2, &entry_msg);
+        }
+        // PushRequest.streams (field 1).
+        encode_message(&mut request, 1, &stream);
+    }
+    request
+}
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_LEN: u64 = 2;
+
+fn encode_key(buf: &mut Vec<u8>, field: u64, wire_type: u64) {
+    encode_varint(buf, (field << 3) | wire_type);
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn encode_string(buf: &mut Vec<u8>, field: u64, value: &str) {
+    encode_key(buf, field, WIRE_LEN);
+    encode_varint(buf, value.len() as u64);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+fn encode_message(buf: &mut Vec<u8>, field: u64, message: &[u8]) {
+    encode_key(buf, field, WIRE_LEN);
+    encode_varint(buf, message.len() as u64);
+    buf.extend_from_slice(message);
+}
+
+// === snappy compression ===
+//
+// Loki's protobuf push bodies are compressed with snappy's block format.
+// This is a greedy encoder in the style of the reference implementation's
+// fast path: a hash table of four-byte sequences finds back-references,
+// which are emitted as two-byte-offset copies between literal runs.
+
+/// Compresses `input` into the snappy block format.
+fn snappy_compress(input: &[u8]) -> Vec<u8> {
+    const HASH_BITS: u32 = 14;
+
+    let mut out = Vec::with_capacity(input.len() / 2 + 8);
+    encode_varint(&mut out, input.len() as u64);
+
+    // Candidate positions, offset by one so zero means "empty".
+    let mut table = vec![0usize; 1 << HASH_BITS];
+    let hash = |word: u32| (word.wrapping_mul(0x1e35_a7bd) >> (32 - HASH_BITS)) as usize;
+
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i + 4 <= input.len() {
+        let word = u32::from_le_bytes([input[i], input[i + 1], input[i + 2], input[i + 3]]);
+        let slot = &mut table[hash(word)];
+        let prev = *slot;
+        *slot = i + 1;
+        if prev == 0 || i + 1 - prev > 0xffff {
+            i += 1;
+            continue;
+        }
+        let candidate = prev - 1;
+        if input[candidate..candidate + 4] != input[i..i + 4] {
+            i += 1;
+            continue;
+        }
+        let mut len = 4;
+        while i + len < input.len() && input[candidate + len] == input[i + len] {
+            len += 1;
+        }
+        emit_literal(&mut out, &input[literal_start..i]);
+        emit_copy(&mut out, i - candidate, len);
+        i += len;
+        literal_start = i;
+    }
+    emit_literal(&mut out, &input[literal_start..]);
+    out
+}
+
+/// Emits a literal run, splitting it into maximum-length elements.
+fn emit_literal(out: &mut Vec<u8>, mut literal: &[u8]) {
+    while !literal.is_empty() {
+        let len = literal.len().min(65536);
+        match len {
+            1..=60 => out.push((len as u8 - 1) << 2),
+            61..=256 => {
+                out.push(60 << 2);
+                out.push(len as u8 - 1);
+            }
+            _ => {
+                out.push(61 << 2);
+                out.extend_from_slice(&((len - 1) as u16).to_le_bytes());
+            }
+        }
+        out.extend_from_slice(&literal[..len]);
+        literal = &literal[len..];
+    }
+}
+
+/// Emits a back-reference as two-byte-offset copy elements.
+fn emit_copy(out: &mut Vec<u8>, offset: usize, mut len: usize) {
+    while len > 0 {
+        let this = len.min(64);
+        out.push(((this as u8 - 1) << 2) | 0b10);
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        len -= this;
+    }
+}
+
+/// Returns the current wall-clock time in nanoseconds since the Unix
+/// epoch.
+fn unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Records event fields as logfmt pairs, extracting the `message` field.
+struct FieldVisitor {
+    message: Option<String>,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl field::Visit for FieldVisitor {
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.fields.push((field.name(), value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.fields.push((field.name(), value.to_string()));
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.fields.push((field.name(), value.to_string()));
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.fields.push((field.name(), value.to_string()));
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_owned());
+            return;
+        }
+        self.fields.push((field.name(), value.to_owned()));
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        let rendered = format!("{:?}", value);
+        if field.name() == "message" {
+            self.message = Some(rendered);
+            return;
+        }
+        self.fields.push((field.name(), rendered));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::net::TcpListener;
+    use tracing::collect::with_default;
+
+    /// Decompresses a snappy block, for checking what the encoder emitted.
+    fn snappy_decompress(input: &[u8]) -> Vec<u8> {
+        let mut input = input;
+        let mut expected = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = input[0];
+            input = &input[1..];
+            expected |= u64::from(byte & 0x7f) << shift;
+            if byte < 0x80 {
+                break;
+            }
+            shift += 7;
+        }
+
+        let mut out = Vec::with_capacity(expected as usize);
+        while !input.is_empty() {
+            let tag = input[0];
+            input = &input[1..];
+            match tag & 0b11 {
+                0b00 => {
+                    let mut len = (tag >> 2) as usize + 1;
+                    if len > 60 {
+                        let extra = len - 60;
+                        len = 1 + input[..extra]
+                            .iter()
+                            .rev()
+                            .fold(0, |acc, &b| (acc << 8) | usize::from(b));
+                        input = &input[extra..];
+                    }
+                    out.extend_from_slice(&input[..len]);
+                    input = &input[len..];
+                }
+                0b01 => {
+                    let len = ((tag >> 2) & 0b111) as usize + 4;
+                    let offset = usize::from(tag >> 5) << 8 | usize::from(input[0]);
+                    input = &input[1..];
+                    copy_within(&mut out, offset, len);
+                }
+                0b10 => {
+                    let len = (tag >> 2) as usize + 1;
+                    let offset = usize::from(u16::from_le_bytes([input[0], input[1]]));
+                    input = &input[2..];
+                    copy_within(&mut out, offset, len);
+                }
+                _ => panic!("unexpected four-byte-offset copy"),
+            }
+        }
+        assert_eq!(out.len() as u64, expected, "length preamble disagrees");
+        out
+    }
+
+    /// Copies `len` bytes from `offset` back, byte by byte, as copies may
+    /// overlap their own output.
+    fn copy_within(out: &mut Vec<u8>, offset: usize, len: usize) {
+        let start = out.len() - offset;
+        for i in 0..len {
+            let byte = out[start + i];
+            out.push(byte);
+        }
+    }
+
+    #[test]
+    fn short_literals_encode_directly() {
+        assert_eq!(snappy_compress(b"abc"), [3, 0x08, b'a', b'b', b'c']);
+    }
+
+    #[test]
+    fn compression_round_trips_and_shrinks() {
+        let mut input = Vec::new();
+        for i in 0..200 {
+            let _ = writeln!(
+                &mut input,
+                "level=info target=app::server message=\"request {} handled\"",
+                i % 10,
+            );
+        }
+        let compressed = snappy_compress(&input);
+        assert!(
+            compressed.len() < input.len() / 2,
+            "repetitive input did not compress: {} -> {}",
+            input.len(),
+            compressed.len(),
+        );
+        assert_eq!(snappy_decompress(&compressed), input);
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        let mut labels = String::from("{");
+        let mut first = true;
+        push_label(&mut labels, &mut first, "job", "api");
+        push_label(&mut labels, &mut first, "note", "say \"hi\"");
+        labels.push('}');
+        assert_eq!(labels, "{job=\"api\",note=\"say \\\"hi\\\"\"}");
+    }
+
+    #[test]
+    fn logfmt_values_quote_when_needed() {
+        let mut line = String::new();
+        push_logfmt_value(&mut line, "simple");
+        assert_eq!(line, "simple");
+        line.clear();
+        push_logfmt_value(&mut line, "two words");
+        assert_eq!(line, "\"two words\"");
+    }
+
+    /// Accepts one push request and returns its request line, headers, and
+    /// decompressed body.
+    fn accept_push(listener: &TcpListener) -> (String, String, Vec<u8>) {
+        let (stream, _) = listener.accept().expect("no push received");
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader
+            .read_line(&mut request_line)
+            .expect("failed to read request line");
+        let mut headers = String::new();
+        let mut content_length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).expect("failed to read header");
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            headers.push_str(&header.to_ascii_lowercase());
+            headers.push('\n');
+            if let Some(length) = header
+                .to_ascii_lowercase()
+                .strip_prefix("content-length:")
+                .map(str::trim)
+            {
+                content_length = length.parse().expect("invalid content-length");
+            }
+        }
+        let mut body = vec![0; content_length];
+        io::Read::read_exact(&mut reader, &mut body).expect("failed to read body");
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+            .expect("failed to respond");
+        (
+            request_line.trim().to_owned(),
+            headers,
+            snappy_decompress(&body),
+        )
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    #[test]
+    fn pushes_streams_grouped_by_labels() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let pusher = Subscriber::builder()
+            .with_endpoint(format!("http://{}", addr))
+            .with_label("job", "push-test")
+            .with_field_label("region")
+            .finish()
+            .expect("failed to start pusher");
+        let collector = crate::registry().with(pusher);
+
+        with_default(collector, || {
+            tracing::info!(region = "eu-west", code = 200, "request handled");
+            tracing::warn!("no region here");
+        });
+        // Dropping the collector disconnects the channel, flushing the
+        // batch.
+
+        let (request_line, headers, body) = accept_push(&listener);
+        assert_eq!(request_line, "POST /loki/api/v1/push HTTP/1.1");
+        assert!(headers.contains("content-type: application/x-protobuf"));
+        assert!(
+            contains(
+                &body,
+                b"{job=\"push-test\",level=\"info\",region=\"eu-west\"}",
+            ),
+            "missing keyed stream",
+        );
+        assert!(
+            contains(&body, b"{job=\"push-test\",level=\"warn\"}"),
+            "missing unkeyed stream",
+        );
+        assert!(
+            contains(&body, b"request handled region=eu-west code=200"),
+            "missing log line",
+        );
+    }
+}
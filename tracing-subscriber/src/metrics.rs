@@ -0,0 +1,545 @@
+//! In-process metric aggregation driven by event field conventions.
+//!
+//! Instrumented code frequently wants to count things ("requests served"),
+//! track a level ("connections open"), or measure a distribution ("payload
+//! size") alongside its trace output, without pulling in a separate metrics
+//! pipeline. This module provides a [`Subscriber`] that interprets
+//! conventionally-named event fields as metric updates and maintains the
+//! aggregates in-process:
+//!
+//! - `counter.<name>`: adds the field's value to the counter `<name>`,
+//! - `gauge.<name>`: sets the gauge `<name>` to the field's value, and
+//! - `histogram.<name>`: records the field's value into the distribution
+//!   `<name>`.
+//!
+//! Fields without one of these prefixes are ignored, so metric updates can
+//! be attached to ordinary events:
+//!
+//! ```
+//! tracing::info!(counter.requests = 1, histogram.payload_bytes = 512, "handled request");
+//! ```
+//!
+//! Aggregates are read by polling the [`Handle`] returned from
+//! [`Subscriber::new`]: [`Handle::counters`], [`Handle::gauges`], and
+//! [`Handle::histograms`] return snapshots that an exporter can convert to
+//! its wire format on its own schedule.
+//!
+//! # Labels
+//!
+//! Metrics are often split by a dimension that is already recorded on an
+//! enclosing span — the request method, the peer address, and so on.
+//! [`Subscriber::with_span_label`] names span fields to extract as labels:
+//! when a metric event occurs, the subscriber looks up the named fields in
+//! the event's span scope (innermost value wins) and aggregates each
+//! distinct combination of label values as a separate series.
+//!
+//! ```
+//! use tracing_subscriber::{metrics, prelude::*};
+//!
+//! let (metrics, handle) = metrics::Subscriber::new();
+//! let collector = tracing_subscriber::registry()
+//!     .with(metrics.with_span_label("method"));
+//! # let _ = collector;
+//!
+//! // counted separately for method="GET" and method="POST" spans:
+//! // tracing::info!(counter.requests = 1);
+//! # let _ = &handle;
+//! ```
+//!
+//! # Histograms
+//!
+//! Distributions are aggregated into logarithmic-bucket histograms (shared
+//! with the [`timing`](crate::timing) module), so recording is
+//! constant-time and reported percentiles are approximate, with a relative
+//! error of at most a few percent.
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+    timing::Histogram,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tracing_core::{field, span, Collect, Event};
+
+/// A [`Subscribe`] implementation that aggregates conventionally-named event
+/// fields into in-process metrics.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    shared: Arc<Shared>,
+    labels: Vec<String>,
+}
+
+/// Provides access to the metrics aggregated by a metrics [`Subscriber`].
+///
+/// This is returned by [`Subscriber::new`], and may be cloned and sent to
+/// other threads freely.
+#[derive(Debug, Clone)]
+pub struct Handle {
+    shared: Arc<Shared>,
+}
+
+/// A snapshot of a single counter series, as returned by
+/// [`Handle::counters`].
+#[derive(Debug, Clone)]
+pub struct Counter {
+    name: &'static str,
+    labels: Labels,
+    value: u64,
+}
+
+/// A snapshot of a single gauge series, as returned by [`Handle::gauges`].
+#[derive(Debug, Clone)]
+pub struct Gauge {
+    name: &'static str,
+    labels: Labels,
+    value: f64,
+}
+
+/// A snapshot of a single histogram series, as returned by
+/// [`Handle::histograms`].
+#[derive(Debug, Clone)]
+pub struct Distribution {
+    name: &'static str,
+    labels: Labels,
+    histogram: Histogram,
+}
+
+/// The label values identifying one series, sorted by label name.
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Debug, Default)]
+struct Shared {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    counters: HashMap<(&'static str, Labels), u64>,
+    gauges: HashMap<(&'static str, Labels), f64>,
+    histograms: HashMap<(&'static str, Labels), Histogram>,
+}
+
+/// The label values extracted from one span's fields, stored in that span's
+/// extensions.
+#[derive(Debug, Default)]
+struct SpanLabels(Vec<(&'static str, String)>);
+
+/// A single metric update parsed from an event field.
+enum Update {
+    Counter(&'static str, u64),
+    Gauge(&'static str, f64),
+    Histogram(&'static str, u64),
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new metrics subscriber, and a [`Handle`] that provides
+    /// access to the metrics it aggregates.
+    pub fn new() -> (Self, Handle) {
+        let shared = Arc::new(Shared::default());
+        let handle = Handle {
+            shared: shared.clone(),
+        };
+        let this = Self {
+            shared,
+            labels: Vec::new(),
+        };
+        (this, handle)
+    }
+
+    /// Extracts the span field named `field` as a metric label.
+    ///
+    /// Metric updates occurring inside a span with this field are aggregated
+    /// separately per value of the field; if several spans in scope record
+    /// it, the innermost span's value wins. This method may be called
+    /// multiple times to extract several labels.
+    ///
+    /// By default, no labels are extracted.
+    pub fn with_span_label(mut self, field: impl Into<String>) -> Self {
+        self.labels.push(field.into());
+        self
+    }
+
+    /// Records the label fields of a span's attributes or `record` call into
+    /// its [`SpanLabels`].
+    fn record_labels(&self, record: &mut SpanLabels, fields: &mut dyn FnMut(&mut LabelVisitor<'_>)) {
+        let mut visitor = LabelVisitor {
+            names: &self.labels,
+            labels: &mut record.0,
+        };
+        fields(&mut visitor);
+    }
+
+    /// Collects the labels in scope for `event`, innermost values winning.
+    fn scope_labels<C>(&self, event: &Event<'_>, ctx: &Context<'_, C>) -> Labels
+    where
+        C: Collect + for<'a> LookupSpan<'a>,
+    {
+        let mut labels: Labels = Vec::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(span_labels) = span.extensions().get::<SpanLabels>() {
+                    for (name, value) in &span_labels.0 {
+                        match labels.iter_mut().find(|(existing, _)| existing == name) {
+                            Some((_, existing)) => *existing = value.clone(),
+                            None => labels.push((name, value.clone())),
+                        }
+                    }
+                }
+            }
+        }
+        labels.sort_by_key(|(name, _)| *name);
+        labels
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        if self.labels.is_empty() {
+            return;
+        }
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut labels = SpanLabels::default();
+        self.record_labels(&mut labels, &mut |visitor| attrs.record(visitor));
+        if !labels.0.is_empty() {
+            span.extensions_mut().insert(labels);
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        if self.labels.is_empty() {
+            return;
+        }
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        match extensions.get_mut::<SpanLabels>() {
+            Some(labels) => self.record_labels(labels, &mut |visitor| values.record(visitor)),
+            None => {
+                let mut labels = SpanLabels::default();
+                self.record_labels(&mut labels, &mut |visitor| values.record(visitor));
+                if !labels.0.is_empty() {
+                    extensions.insert(labels);
+                }
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let mut visitor = UpdateVisitor {
+            updates: Vec::new(),
+        };
+        event.record(&mut visitor);
+        if visitor.updates.is_empty() {
+            return;
+        }
+
+        let labels = self.scope_labels(event, &ctx);
+        let mut inner = self.shared.inner.lock().expect("metrics poisoned");
+        for update in visitor.updates {
+            match update {
+                Update::Counter(name, value) => {
+                    *inner.counters.entry((name, labels.clone())).or_default() += value;
+                }
+                Update::Gauge(name, value) => {
+                    inner.gauges.insert((name, labels.clone()), value);
+                }
+                Update::Histogram(name, value) => {
+                    inner
+                        .histograms
+                        .entry((name, labels.clone()))
+                        .or_default()
+                        .record(value);
+                }
+            }
+        }
+    }
+}
+
+// === impl Handle ===
+
+impl Handle {
+    /// Returns a snapshot of every counter series, in an unspecified order.
+    pub fn counters(&self) -> Vec<Counter> {
+        let inner = self.shared.inner.lock().expect("metrics poisoned");
+        inner
+            .counters
+            .iter()
+            .map(|((name, labels), &value)| Counter {
+                name,
+                labels: labels.clone(),
+                value,
+            })
+            .collect()
+    }
+
+    /// Returns a snapshot of every gauge series, in an unspecified order.
+    pub fn gauges(&self) -> Vec<Gauge> {
+        let inner = self.shared.inner.lock().expect("metrics poisoned");
+        inner
+            .gauges
+            .iter()
+            .map(|((name, labels), &value)| Gauge {
+                name,
+                labels: labels.clone(),
+                value,
+            })
+            .collect()
+    }
+
+    /// Returns a snapshot of every histogram series, in an unspecified
+    /// order.
+    pub fn histograms(&self) -> Vec<Distribution> {
+        let inner = self.shared.inner.lock().expect("metrics poisoned");
+        inner
+            .histograms
+            .iter()
+            .map(|((name, labels), histogram)| Distribution {
+                name,
+                labels: labels.clone(),
+                histogram: histogram.clone(),
+            })
+            .collect()
+    }
+}
+
+// === impl Counter ===
+
+impl Counter {
+    /// Returns the counter's name, without the `counter.` prefix.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the label values identifying this series, sorted by label
+    /// name.
+    pub fn labels(&self) -> &[(&'static str, String)] {
+        &self.labels
+    }
+
+    /// Returns the counter's current value.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+// === impl Gauge ===
+
+impl Gauge {
+    /// Returns the gauge's name, without the `gauge.` prefix.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the label values identifying this series, sorted by label
+    /// name.
+    pub fn labels(&self) -> &[(&'static str, String)] {
+        &self.labels
+    }
+
+    /// Returns the gauge's most recently recorded value.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+}
+
+// === impl Distribution ===
+
+impl Distribution {
+    /// Returns the histogram's name, without the `histogram.` prefix.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the label values identifying this series, sorted by label
+    /// name.
+    pub fn labels(&self) -> &[(&'static str, String)] {
+        &self.labels
+    }
+
+    /// Returns the number of recorded values.
+    pub fn count(&self) -> u64 {
+        self.histogram.count
+    }
+
+    /// Returns the sum of the recorded values.
+    pub fn sum(&self) -> u64 {
+        self.histogram.sum
+    }
+
+    /// Returns the approximate maximum recorded value.
+    pub fn max(&self) -> u64 {
+        self.histogram.max
+    }
+
+    /// Returns the approximate value at the given percentile, where
+    /// `percentile` is in the range `0.0..=100.0`.
+    ///
+    /// Returns `0` if no values have been recorded.
+    pub fn percentile(&self, percentile: f64) -> u64 {
+        self.histogram.percentile(percentile)
+    }
+}
+
+/// Parses `counter.`/`gauge.`/`histogram.`-prefixed fields into [`Update`]s.
+struct UpdateVisitor {
+    updates: Vec<Update>,
+}
+
+impl UpdateVisitor {
+    fn update(&mut self, name: &'static str, value: f64) {
+        if let Some(name) = name.strip_prefix("counter.") {
+            self.updates
+                .push(Update::Counter(name, value.max(0.0) as u64));
+        } else if let Some(name) = name.strip_prefix("gauge.") {
+            self.updates.push(Update::Gauge(name, value));
+        } else if let Some(name) = name.strip_prefix("histogram.") {
+            self.updates
+                .push(Update::Histogram(name, value.max(0.0) as u64));
+        }
+    }
+}
+
+impl field::Visit for UpdateVisitor {
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.update(field.name(), value as f64);
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.update(field.name(), value as f64);
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.update(field.name(), value);
+    }
+
+    fn record_debug(&mut self, _: &field::Field, _: &dyn std::fmt::Debug) {}
+}
+
+/// Records span fields with configured label names as label values.
+struct LabelVisitor<'a> {
+    names: &'a [String],
+    labels: &'a mut Vec<(&'static str, String)>,
+}
+
+impl LabelVisitor<'_> {
+    fn record(&mut self, field: &field::Field, value: String) {
+        if !self.names.iter().any(|name| name == field.name()) {
+            return;
+        }
+        match self
+            .labels
+            .iter_mut()
+            .find(|(name, _)| *name == field.name())
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.labels.push((field.name(), value)),
+        }
+    }
+}
+
+impl field::Visit for LabelVisitor<'_> {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.record(field, value.to_owned());
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.record(field, format!("{:?}", value));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn counters_accumulate() {
+        let (metrics, handle) = Subscriber::new();
+        let collector = crate::registry().with(metrics);
+
+        with_default(collector, || {
+            tracing::info!(counter.requests = 1, "one");
+            tracing::info!(counter.requests = 2u64, not_a_metric = 3, "two");
+        });
+
+        let counters = handle.counters();
+        assert_eq!(counters.len(), 1);
+        assert_eq!(counters[0].name(), "requests");
+        assert_eq!(counters[0].labels(), &[]);
+        assert_eq!(counters[0].value(), 3);
+    }
+
+    #[test]
+    fn gauges_keep_the_latest_value() {
+        let (metrics, handle) = Subscriber::new();
+        let collector = crate::registry().with(metrics);
+
+        with_default(collector, || {
+            tracing::info!(gauge.connections = 4, "up");
+            tracing::info!(gauge.connections = 2.5, "down");
+        });
+
+        let gauges = handle.gauges();
+        assert_eq!(gauges.len(), 1);
+        assert_eq!(gauges[0].name(), "connections");
+        assert_eq!(gauges[0].value(), 2.5);
+    }
+
+    #[test]
+    fn histograms_aggregate_distributions() {
+        let (metrics, handle) = Subscriber::new();
+        let collector = crate::registry().with(metrics);
+
+        with_default(collector, || {
+            for size in [100u64, 200, 300] {
+                tracing::info!(histogram.payload_bytes = size, "sent");
+            }
+        });
+
+        let histograms = handle.histograms();
+        assert_eq!(histograms.len(), 1);
+        assert_eq!(histograms[0].name(), "payload_bytes");
+        assert_eq!(histograms[0].count(), 3);
+        assert_eq!(histograms[0].sum(), 600);
+        // Bucketing is approximate; the max has a relative error of at most
+        // 1/16th.
+        assert!((282..=320).contains(&histograms[0].max()));
+    }
+
+    #[test]
+    fn labels_come_from_the_span_scope() {
+        let (metrics, handle) = Subscriber::new();
+        let collector = crate::registry().with(metrics.with_span_label("method"));
+
+        with_default(collector, || {
+            for method in ["GET", "GET", "POST"] {
+                let span = tracing::info_span!("request", method);
+                let _entered = span.enter();
+                tracing::info!(counter.requests = 1, "handled");
+            }
+            // An inner span's label value overrides the outer one.
+            let outer = tracing::info_span!("request", method = "GET");
+            let _outer = outer.enter();
+            let inner = tracing::info_span!("retry", method = "PUT");
+            let _inner = inner.enter();
+            tracing::info!(counter.requests = 1, "handled");
+        });
+
+        let mut counters = handle.counters();
+        counters.sort_by(|a, b| a.labels().cmp(b.labels()));
+        let series: Vec<_> = counters
+            .iter()
+            .map(|counter| (counter.labels()[0].1.as_str(), counter.value()))
+            .collect();
+        assert_eq!(series, [("GET", 2), ("POST", 1), ("PUT", 1)]);
+    }
+}
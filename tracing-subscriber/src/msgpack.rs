@@ -0,0 +1,614 @@
+//! Length-prefixed MessagePack event streams.
+//!
+//! Text formats like JSON-per-line spend most of their encoding budget on
+//! escaping and number-to-decimal conversion, which becomes measurable at
+//! high event rates. This module provides a [`Subscriber`] that writes each
+//! event as one [MessagePack]-encoded frame instead, preserving the types of
+//! recorded field values (integers stay integers, booleans stay booleans),
+//! so events can be shipped to a collector with far lower CPU cost than a
+//! text format.
+//!
+//! Each frame is a big-endian `u32` byte length followed by that many bytes
+//! of MessagePack: a map with the event's `timestamp` (milliseconds since
+//! the Unix epoch), `level`, `target`, the names of the `spans` enclosing
+//! the event (root first), and a `fields` map of the event's recorded
+//! values. The length prefix lets a consumer skip or buffer whole frames
+//! without parsing them.
+//!
+//! The stream is not self-describing text, so this module also provides
+//! [`FrameReader`], which iterates over the frames of a stream and renders
+//! each one back to a JSON object for inspection:
+//!
+//! ```
+//! use tracing_subscriber::{msgpack, prelude::*};
+//! # use std::sync::{Arc, Mutex};
+//!
+//! # let buf = Arc::new(Mutex::new(Vec::<u8>::new()));
+//! # let writer = buf.clone();
+//! # let make_writer = move || MockWriter(writer.clone());
+//! # struct MockWriter(Arc<Mutex<Vec<u8>>>);
+//! # impl std::io::Write for MockWriter {
+//! #     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+//! #         self.0.lock().unwrap().extend_from_slice(buf);
+//! #         Ok(buf.len())
+//! #     }
+//! #     fn flush(&mut self) -> std::io::Result<()> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! let collector = tracing_subscriber::registry().with(msgpack::Subscriber::new(make_writer));
+//!
+//! tracing::collect::with_default(collector, || {
+//!     tracing::info!(port = 8080u64, "listening");
+//! });
+//!
+//! # let bytes = buf.lock().unwrap().clone();
+//! for frame in msgpack::FrameReader::new(&bytes[..]) {
+//!     println!("{}", frame.unwrap());
+//! }
+//! ```
+//!
+//! [MessagePack]: https://msgpack.org/
+use crate::{
+    fmt::MakeWriter,
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    convert::TryInto,
+    fmt::{self, Write as _},
+    io::{self, Read, Write as _},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing_core::{
+    field::{self, Field},
+    Collect, Event,
+};
+
+/// A [`Subscribe`] implementation that writes each event as one
+/// length-prefixed MessagePack frame.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber<W> {
+    make_writer: W,
+}
+
+impl<W> Subscriber<W>
+where
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    /// Returns a new `Subscriber` writing MessagePack frames to the given
+    /// [`MakeWriter`].
+    pub fn new(make_writer: W) -> Self {
+        Self { make_writer }
+    }
+}
+
+impl<C, W> Subscribe<C> for Subscriber<W>
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let metadata = event.metadata();
+
+        let mut values = FieldVisitor::default();
+        event.record(&mut values);
+
+        let mut spans = Vec::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                spans.push(span.name());
+            }
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+
+        let mut frame = Vec::new();
+        encode_map_len(&mut frame, 5);
+        encode_str(&mut frame, "timestamp");
+        encode_u64(&mut frame, timestamp);
+        encode_str(&mut frame, "level");
+        encode_str(&mut frame, metadata.level().as_str());
+        encode_str(&mut frame, "target");
+        encode_str(&mut frame, metadata.target());
+        encode_str(&mut frame, "spans");
+        encode_array_len(&mut frame, spans.len());
+        for name in spans {
+            encode_str(&mut frame, name);
+        }
+        encode_str(&mut frame, "fields");
+        encode_map_len(&mut frame, values.fields.len());
+        for (name, value) in &values.fields {
+            encode_str(&mut frame, name);
+            match value {
+                Value::I64(value) => encode_i64(&mut frame, *value),
+                Value::U64(value) => encode_u64(&mut frame, *value),
+                Value::F64(value) => encode_f64(&mut frame, *value),
+                Value::Bool(value) => encode_bool(&mut frame, *value),
+                Value::Str(value) => encode_str(&mut frame, value),
+            }
+        }
+
+        let mut writer = self.make_writer.make_writer_for_event(event);
+        // Dropping the event is the only reasonable response to a failed
+        // write; a subscriber must not panic, and writing an error report to
+        // the same stream would corrupt the framing.
+        let _ = writer.write_all(&(frame.len() as u32).to_be_bytes());
+        let _ = writer.write_all(&frame);
+    }
+}
+
+/// The value of a recorded field, preserving the type it was recorded with.
+#[derive(Debug)]
+enum Value {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+    Str(String),
+}
+
+/// Captures the fields of an event as typed [`Value`]s.
+#[derive(Debug, Default)]
+struct FieldVisitor {
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl FieldVisitor {
+    fn record(&mut self, field: &Field, value: Value) {
+        let name = field.name();
+        let name = name.strip_prefix("r#").unwrap_or(name);
+        self.fields.push((name, value));
+    }
+}
+
+impl field::Visit for FieldVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record(field, Value::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record(field, Value::U64(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record(field, Value::F64(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record(field, Value::Bool(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, Value::Str(value.to_owned()));
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.record(field, Value::Str(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, Value::Str(format!("{:?}", value)));
+    }
+}
+
+// === encoding ===
+//
+// Hand-rolled encoders for the subset of MessagePack the subscriber emits:
+// maps, arrays, strings, integers, `f64`s, and booleans. The subset is small
+// enough that depending on a MessagePack crate is not worth the compile-time
+// cost.
+
+fn encode_map_len(buf: &mut Vec<u8>, len: usize) {
+    match len {
+        0..=15 => buf.push(0x80 | len as u8),
+        16..=0xffff => {
+            buf.push(0xde);
+            buf.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            buf.push(0xdf);
+            buf.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+}
+
+fn encode_array_len(buf: &mut Vec<u8>, len: usize) {
+    match len {
+        0..=15 => buf.push(0x90 | len as u8),
+        16..=0xffff => {
+            buf.push(0xdc);
+            buf.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            buf.push(0xdd);
+            buf.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+}
+
+fn encode_str(buf: &mut Vec<u8>, value: &str) {
+    match value.len() {
+        0..=31 => buf.push(0xa0 | value.len() as u8),
+        32..=0xff => {
+            buf.push(0xd9);
+            buf.push(value.len() as u8);
+        }
+        0x100..=0xffff => {
+            buf.push(0xda);
+            buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        }
+        _ => {
+            buf.push(0xdb);
+            buf.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        }
+    }
+    buf.extend_from_slice(value.as_bytes());
+}
+
+fn encode_u64(buf: &mut Vec<u8>, value: u64) {
+    match value {
+        0..=0x7f => buf.push(value as u8),
+        0x80..=0xff => {
+            buf.push(0xcc);
+            buf.push(value as u8);
+        }
+        0x100..=0xffff => {
+            buf.push(0xcd);
+            buf.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            buf.push(0xce);
+            buf.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            buf.push(0xcf);
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+fn encode_i64(buf: &mut Vec<u8>, value: i64) {
+    if value >= 0 {
+        return encode_u64(buf, value as u64);
+    }
+    match value {
+        -32..=-1 => buf.push(value as u8),
+        -0x80..=-33 => {
+            buf.push(0xd0);
+            buf.push(value as u8);
+        }
+        -0x8000..=-0x81 => {
+            buf.push(0xd1);
+            buf.extend_from_slice(&(value as i16).to_be_bytes());
+        }
+        -0x8000_0000..=-0x8001 => {
+            buf.push(0xd2);
+            buf.extend_from_slice(&(value as i32).to_be_bytes());
+        }
+        _ => {
+            buf.push(0xd3);
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+fn encode_f64(buf: &mut Vec<u8>, value: f64) {
+    buf.push(0xcb);
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn encode_bool(buf: &mut Vec<u8>, value: bool) {
+    buf.push(if value { 0xc3 } else { 0xc2 });
+}
+
+// === decoding ===
+
+/// Iterates over the frames of a MessagePack event stream, rendering each
+/// frame as a JSON object.
+///
+/// This is the inspection counterpart of [`Subscriber`]: it reads the
+/// length-prefixed frames the subscriber writes and converts each one back
+/// to a line of JSON, so a captured binary stream can be examined with
+/// ordinary text tools. The iterator ends when the stream is exhausted, and
+/// yields an error for a truncated or malformed frame.
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    reader: R,
+}
+
+impl<R: Read> FrameReader<R> {
+    /// Returns a new `FrameReader` reading frames from the given stream.
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+}
+
+impl<R: Read> Iterator for FrameReader<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut len = [0u8; 4];
+        match self.reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return None,
+            Err(error) => return Some(Err(error)),
+        }
+        let mut frame = vec![0u8; u32::from_be_bytes(len) as usize];
+        if let Err(error) = self.reader.read_exact(&mut frame) {
+            return Some(Err(error));
+        }
+
+        let mut json = String::new();
+        let mut bytes = &frame[..];
+        if let Err(error) = decode_value(&mut bytes, &mut json) {
+            return Some(Err(error));
+        }
+        if !bytes.is_empty() {
+            return Some(Err(malformed("trailing bytes after frame value")));
+        }
+        Some(Ok(json))
+    }
+}
+
+fn malformed(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_owned())
+}
+
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> io::Result<&'a [u8]> {
+    if bytes.len() < n {
+        return Err(malformed("truncated frame"));
+    }
+    let (taken, rest) = bytes.split_at(n);
+    *bytes = rest;
+    Ok(taken)
+}
+
+/// Decodes one MessagePack value from `bytes`, appending its JSON rendering
+/// to `json`.
+fn decode_value(bytes: &mut &[u8], json: &mut String) -> io::Result<()> {
+    let tag = take(bytes, 1)?[0];
+    match tag {
+        // positive fixint
+        0x00..=0x7f => write_json_u64(json, tag as u64),
+        // fixmap
+        0x80..=0x8f => decode_map(bytes, json, (tag & 0x0f) as usize)?,
+        // fixarray
+        0x90..=0x9f => decode_array(bytes, json, (tag & 0x0f) as usize)?,
+        // fixstr
+        0xa0..=0xbf => decode_str(bytes, json, (tag & 0x1f) as usize)?,
+        0xc2 => json.push_str("false"),
+        0xc3 => json.push_str("true"),
+        // uint 8/16/32/64
+        0xcc => write_json_u64(json, take(bytes, 1)?[0] as u64),
+        0xcd => write_json_u64(
+            json,
+            u16::from_be_bytes(take(bytes, 2)?.try_into().unwrap()) as u64,
+        ),
+        0xce => write_json_u64(
+            json,
+            u32::from_be_bytes(take(bytes, 4)?.try_into().unwrap()) as u64,
+        ),
+        0xcf => write_json_u64(
+            json,
+            u64::from_be_bytes(take(bytes, 8)?.try_into().unwrap()),
+        ),
+        // int 8/16/32/64
+        0xd0 => write_json_i64(json, take(bytes, 1)?[0] as i8 as i64),
+        0xd1 => write_json_i64(
+            json,
+            i16::from_be_bytes(take(bytes, 2)?.try_into().unwrap()) as i64,
+        ),
+        0xd2 => write_json_i64(
+            json,
+            i32::from_be_bytes(take(bytes, 4)?.try_into().unwrap()) as i64,
+        ),
+        0xd3 => write_json_i64(
+            json,
+            i64::from_be_bytes(take(bytes, 8)?.try_into().unwrap()),
+        ),
+        // float 64
+        0xcb => {
+            let value = f64::from_be_bytes(take(bytes, 8)?.try_into().unwrap());
+            if value.is_finite() {
+                let _ = write!(json, "{}", value);
+            } else {
+                // JSON has no rendering for non-finite numbers.
+                json.push_str("null");
+            }
+        }
+        // str 8/16/32
+        0xd9 => {
+            let len = take(bytes, 1)?[0] as usize;
+            decode_str(bytes, json, len)?;
+        }
+        0xda => {
+            let len = u16::from_be_bytes(take(bytes, 2)?.try_into().unwrap()) as usize;
+            decode_str(bytes, json, len)?;
+        }
+        0xdb => {
+            let len = u32::from_be_bytes(take(bytes, 4)?.try_into().unwrap()) as usize;
+            decode_str(bytes, json, len)?;
+        }
+        // array 16/32
+        0xdc => {
+            let len = u16::from_be_bytes(take(bytes, 2)?.try_into().unwrap()) as usize;
+            decode_array(bytes, json, len)?;
+        }
+        0xdd => {
+            let len = u32::from_be_bytes(take(bytes, 4)?.try_into().unwrap()) as usize;
+            decode_array(bytes, json, len)?;
+        }
+        // map 16/32
+        0xde => {
+            let len = u16::from_be_bytes(take(bytes, 2)?.try_into().unwrap()) as usize;
+            decode_map(bytes, json, len)?;
+        }
+        0xdf => {
+            let len = u32::from_be_bytes(take(bytes, 4)?.try_into().unwrap()) as usize;
+            decode_map(bytes, json, len)?;
+        }
+        // negative fixint
+        0xe0..=0xff => write_json_i64(json, tag as i8 as i64),
+        _ => return Err(malformed("unsupported MessagePack type")),
+    }
+    Ok(())
+}
+
+fn decode_map(bytes: &mut &[u8], json: &mut String, len: usize) -> io::Result<()> {
+    json.push('{');
+    for i in 0..len {
+        if i > 0 {
+            json.push(',');
+        }
+        decode_value(bytes, json)?;
+        json.push(':');
+        decode_value(bytes, json)?;
+    }
+    json.push('}');
+    Ok(())
+}
+
+fn decode_array(bytes: &mut &[u8], json: &mut String, len: usize) -> io::Result<()> {
+    json.push('[');
+    for i in 0..len {
+        if i > 0 {
+            json.push(',');
+        }
+        decode_value(bytes, json)?;
+    }
+    json.push(']');
+    Ok(())
+}
+
+fn decode_str(bytes: &mut &[u8], json: &mut String, len: usize) -> io::Result<()> {
+    let value = std::str::from_utf8(take(bytes, len)?)
+        .map_err(|_| malformed("string is not valid UTF-8"))?;
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    Ok(())
+}
+
+fn write_json_u64(json: &mut String, value: u64) {
+    let _ = write!(json, "{}", value);
+}
+
+fn write_json_i64(json: &mut String, value: i64) {
+    let _ = write!(json, "{}", value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fmt::test::MockMakeWriter;
+    use crate::prelude::*;
+
+    use tracing::collect::with_default;
+
+    fn frames(make_writer: &MockMakeWriter) -> Vec<String> {
+        let bytes = make_writer.buf().clone();
+        FrameReader::new(&bytes[..])
+            .collect::<io::Result<Vec<_>>>()
+            .expect("stream should decode")
+    }
+
+    #[test]
+    fn frames_are_length_prefixed() {
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::registry().with(Subscriber::new(make_writer.clone()));
+
+        with_default(collector, || {
+            tracing::info!("first");
+            tracing::info!("second");
+        });
+
+        let bytes = make_writer.buf().clone();
+        let mut rest = &bytes[..];
+        for _ in 0..2 {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            rest = &rest[4 + len..];
+        }
+        assert!(rest.is_empty(), "{} trailing bytes", rest.len());
+    }
+
+    #[test]
+    fn field_types_are_preserved() {
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::registry().with(Subscriber::new(make_writer.clone()));
+
+        with_default(collector, || {
+            tracing::info!(port = 8080u64, delta = -3, ratio = 0.5, ok = true, "hi");
+        });
+
+        let frames = frames(&make_writer);
+        assert_eq!(frames.len(), 1);
+        let json = &frames[0];
+        assert!(
+            json.contains(
+                r#""fields":{"message":"hi","port":8080,"delta":-3,"ratio":0.5,"ok":true}"#
+            ),
+            "unexpected frame: {}",
+            json,
+        );
+        assert!(
+            json.contains(r#""level":"INFO""#),
+            "unexpected frame: {}",
+            json
+        );
+    }
+
+    #[test]
+    fn enclosing_spans_are_recorded_root_first() {
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::registry().with(Subscriber::new(make_writer.clone()));
+
+        with_default(collector, || {
+            let outer = tracing::info_span!("outer");
+            let _outer = outer.enter();
+            let inner = tracing::info_span!("inner");
+            let _inner = inner.enter();
+            tracing::info!("hi");
+        });
+
+        let frames = frames(&make_writer);
+        assert_eq!(frames.len(), 1);
+        assert!(
+            frames[0].contains(r#""spans":["outer","inner"]"#),
+            "unexpected frame: {}",
+            frames[0],
+        );
+    }
+
+    #[test]
+    fn truncated_stream_is_an_error() {
+        let make_writer = MockMakeWriter::default();
+        let collector = crate::registry().with(Subscriber::new(make_writer.clone()));
+
+        with_default(collector, || {
+            tracing::info!("hi");
+        });
+
+        let bytes = make_writer.buf().clone();
+        let truncated = &bytes[..bytes.len() - 1];
+        let result = FrameReader::new(truncated).collect::<io::Result<Vec<_>>>();
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,722 @@
+//! A self-contained OTLP span exporter.
+//!
+//! [OTLP] (the OpenTelemetry protocol) is the de-facto wire format for
+//! shipping traces to collectors and observability backends. The official
+//! Rust bindings bring in the full OpenTelemetry SDK, its protobuf code
+//! generation, and an async runtime; for programs that just want their
+//! `tracing` spans to show up in an OTLP-speaking backend, that is a lot of
+//! machinery. This module provides a [`Subscriber`] that encodes spans into
+//! OTLP's trace-service protobuf by hand and ships them over OTLP/HTTP, with
+//! no dependencies beyond the standard library.
+//!
+//! Finished spans are handed to a background thread, batched, and sent as
+//! `POST` requests in binary protobuf to the endpoint's `/v1/traces` path
+//! (OTLP/HTTP is defined over both HTTP/1.1 and HTTP/2; this exporter uses
+//! HTTP/1.1). Failed requests are retried with exponential backoff before
+//! the batch is dropped.
+//!
+//! Span and trace IDs are generated in-process, so traces produced by this
+//! exporter are self-consistent, but they do not join traces propagated from
+//! other services. The IDs are pseudo-random, not cryptographically random.
+//!
+//! # Limitations
+//!
+//! - Only plaintext `http://` endpoints are supported; there is no TLS and
+//!   no gRPC transport.
+//! - Spans buffered when the process exits are only flushed if the
+//!   [`Subscriber`] (and the collector holding it) is dropped.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{otlp, prelude::*};
+//!
+//! let exporter = otlp::Subscriber::builder()
+//!     .with_endpoint("127.0.0.1:4318")
+//!     .with_service_name("my-service")
+//!     .with_resource_attribute("deployment.environment", "production")
+//!     .finish()
+//!     .expect("failed to start the OTLP exporter");
+//! let collector = tracing_subscriber::registry().with(exporter);
+//! # let _ = collector;
+//! ```
+//!
+//! [OTLP]: https://opentelemetry.io/docs/specs/otlp/
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, SystemTime},
+};
+use tracing_core::{field, span, Collect, Event};
+
+/// A [`Subscribe`] implementation that exports finished spans over
+/// OTLP/HTTP.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    // The standard library's channel sender is not `Sync` on our minimum
+    // supported Rust version, so sends are serialized through a mutex.
+    sender: Mutex<mpsc::Sender<FinishedSpan>>,
+}
+
+/// Configures an OTLP exporter [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    endpoint: String,
+    resource: Vec<(String, AttrValue)>,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+/// The default OTLP/HTTP endpoint, on the conventional port.
+const DEFAULT_ENDPOINT: &str = "127.0.0.1:4318";
+
+/// The path the trace service is served on, per the OTLP/HTTP spec.
+const TRACES_PATH: &str = "/v1/traces";
+
+/// A protobuf-compatible attribute value.
+#[derive(Debug, Clone)]
+enum AttrValue {
+    Str(String),
+    Int(i64),
+    Double(f64),
+    Bool(bool),
+}
+
+/// The in-progress data for a span, stored in its extensions.
+struct OtlpSpan {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    parent_span_id: Option<[u8; 8]>,
+    start: u64,
+    attributes: Vec<(&'static str, AttrValue)>,
+    events: Vec<OtlpEvent>,
+}
+
+/// An event recorded inside a span.
+struct OtlpEvent {
+    time: u64,
+    name: &'static str,
+    attributes: Vec<(&'static str, AttrValue)>,
+}
+
+/// A closed span, as handed to the export thread.
+struct FinishedSpan {
+    name: &'static str,
+    end: u64,
+    data: OtlpSpan,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a [`Builder`] for configuring an OTLP exporter.
+    pub fn builder() -> Builder {
+        Builder {
+            endpoint: DEFAULT_ENDPOINT.to_owned(),
+            resource: Vec::new(),
+            batch_size: 512,
+            batch_timeout: Duration::from_secs(5),
+            max_retries: 3,
+        }
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+
+        let parent = if attrs.is_root() {
+            None
+        } else if attrs.is_contextual() {
+            ctx.lookup_current()
+        } else {
+            attrs.parent().and_then(|id| ctx.span(id))
+        };
+        let (trace_id, parent_span_id) = match parent {
+            Some(parent) => match parent.extensions().get::<OtlpSpan>() {
+                Some(parent) => (parent.trace_id, Some(parent.span_id)),
+                None => (random_bytes(), None),
+            },
+            None => (random_bytes(), None),
+        };
+
+        let mut attributes = Vec::new();
+        attrs.record(&mut AttrVisitor {
+            attributes: &mut attributes,
+        });
+        span.extensions_mut().insert(OtlpSpan {
+            trace_id,
+            span_id: random_bytes(),
+            parent_span_id,
+            start: unix_nanos(),
+            attributes,
+            events: Vec::new(),
+        });
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(data) = extensions.get_mut::<OtlpSpan>() {
+            values.record(&mut AttrVisitor {
+                attributes: &mut data.attributes,
+            });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let span = match ctx.event_span(event) {
+            Some(span) => span,
+            None => return,
+        };
+        let mut attributes = Vec::new();
+        event.record(&mut AttrVisitor {
+            attributes: &mut attributes,
+        });
+        let mut extensions = span.extensions_mut();
+        if let Some(data) = extensions.get_mut::<OtlpSpan>() {
+            data.events.push(OtlpEvent {
+                time: unix_nanos(),
+                name: event.metadata().name(),
+                attributes,
+            });
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(&id).expect("Span not found, this is a bug");
+        let data = match span.extensions_mut().remove::<OtlpSpan>() {
+            Some(data) => data,
+            None => return,
+        };
+        let finished = FinishedSpan {
+            name: span.name(),
+            end: unix_nanos(),
+            data,
+        };
+        // The only send error is a disconnected worker; spans are dropped in
+        // that case, as there is nowhere to export them to.
+        let _ = self
+            .sender
+            .lock()
+            .expect("otlp sender poisoned")
+            .send(finished);
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the `host:port` of the OTLP/HTTP endpoint to export to.
+    ///
+    /// An `http://` scheme prefix is accepted and ignored. The trace service
+    /// path (`/v1/traces`) is appended automatically. The default is
+    /// `127.0.0.1:4318`, the conventional OTLP/HTTP port.
+    pub fn with_endpoint(self, endpoint: impl Into<String>) -> Self {
+        let endpoint = endpoint.into();
+        let endpoint = endpoint
+            .strip_prefix("http://")
+            .map(String::from)
+            .unwrap_or(endpoint);
+        let endpoint = endpoint.trim_end_matches('/').to_owned();
+        Self { endpoint, ..self }
+    }
+
+    /// Sets the `service.name` resource attribute.
+    ///
+    /// Most backends use this as the primary grouping for traces. This is
+    /// shorthand for
+    /// [`with_resource_attribute("service.name", ...)`](Self::with_resource_attribute).
+    pub fn with_service_name(self, name: impl Into<String>) -> Self {
+        self.with_resource_attribute("service.name", name.into())
+    }
+
+    /// Adds a resource attribute describing the exporting process, such as
+    /// `deployment.environment` or `host.name`.
+    ///
+    /// Resource attributes are attached once per export request, not to
+    /// every span.
+    pub fn with_resource_attribute(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.resource
+            .push((key.into(), AttrValue::Str(value.into())));
+        self
+    }
+
+    /// Sets the number of spans that triggers an export.
+    ///
+    /// A batch is exported when it reaches this size, or when
+    /// [`batch_timeout`](Self::with_batch_timeout) elapses, whichever comes
+    /// first. The default is 512 spans.
+    pub fn with_batch_size(self, batch_size: usize) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            ..self
+        }
+    }
+
+    /// Sets how long spans may be buffered before being exported.
+    ///
+    /// The default is 5 seconds.
+    pub fn with_batch_timeout(self, batch_timeout: Duration) -> Self {
+        Self {
+            batch_timeout,
+            ..self
+        }
+    }
+
+    /// Sets how many times a failed export is retried before the batch is
+    /// dropped.
+    ///
+    /// Retries back off exponentially, starting at 100 milliseconds. The
+    /// default is 3 retries.
+    pub fn with_max_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Returns the configured exporter [`Subscriber`], spawning its export
+    /// thread.
+    ///
+    /// The export thread runs until the `Subscriber` is dropped; any spans
+    /// still buffered at that point are flushed before it exits.
+    pub fn finish(self) -> io::Result<Subscriber> {
+        let (sender, receiver) = mpsc::channel();
+        let worker = Worker {
+            endpoint: self.endpoint,
+            resource: self.resource,
+            batch_size: self.batch_size,
+            batch_timeout: self.batch_timeout,
+            max_retries: self.max_retries,
+        };
+        thread::Builder::new()
+            .name("tracing-otlp".into())
+            .spawn(move || worker.run(receiver))?;
+        Ok(Subscriber {
+            sender: Mutex::new(sender),
+        })
+    }
+}
+
+// === impl Worker ===
+
+/// The export thread: batches finished spans and posts them to the endpoint.
+struct Worker {
+    endpoint: String,
+    resource: Vec<(String, AttrValue)>,
+    batch_size: usize,
+    batch_timeout: Duration,
+    max_retries: u32,
+}
+
+impl Worker {
+    fn run(&self, receiver: mpsc::Receiver<FinishedSpan>) {
+        let mut batch = Vec::new();
+        loop {
+            match receiver.recv_timeout(self.batch_timeout) {
+                Ok(span) => {
+                    batch.push(span);
+                    if batch.len() >= self.batch_size {
+                        self.export(&mut batch);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    if !batch.is_empty() {
+                        self.export(&mut batch);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    if !batch.is_empty() {
+                        self.export(&mut batch);
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Exports `batch`, retrying with exponential backoff; the batch is
+    /// dropped either way.
+    fn export(&self, batch: &mut Vec<FinishedSpan>) {
+        let body = encode_request(&self.resource, batch);
+        batch.clear();
+        let mut backoff = Duration::from_millis(100);
+        for attempt in 0..=self.max_retries {
+            if self.post(&body).is_ok() {
+                return;
+            }
+            if attempt < self.max_retries {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+    }
+
+    /// Posts one encoded export request, returning an error unless the
+    /// endpoint responds with a success status.
+    fn post(&self, body: &[u8]) -> io::Result<()> {
+        let mut stream = TcpStream::connect(&self.endpoint)?;
+        let request = format!(
+            "POST {} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Content-Type: application/x-protobuf\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n",
+            TRACES_PATH,
+            self.endpoint,
+            body.len(),
+        );
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body)?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        // "HTTP/1.1 200 OK" => "200"
+        let code = status.split_whitespace().nth(1).unwrap_or("");
+        if code.starts_with('2') {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("OTLP endpoint responded with status {:?}", status.trim()),
+            ))
+        }
+    }
+}
+
+// === protobuf encoding ===
+//
+// OTLP's `ExportTraceServiceRequest` is a small, stable protobuf schema;
+// encoding it by hand avoids a code-generation dependency. Field numbers
+// below refer to the opentelemetry-proto trace service definitions.
+
+/// Encodes an `ExportTraceServiceRequest` containing `spans`.
+fn encode_request(resource: &[(String, AttrValue)], spans: &[FinishedSpan]) -> Vec<u8> {
+    let mut resource_spans = Vec::new();
+    // ResourceSpans.resource (field 1): Resource.attributes (field 1).
+    let mut resource_msg = Vec::new();
+    for (key, value) in resource {
+        encode_key_value(&mut resource_msg, 1, key, value);
+    }
+    encode_message(&mut resource_spans, 1, &resource_msg);
+
+    // ResourceSpans.scope_spans (field 2).
+    let mut scope_spans = Vec::new();
+    // ScopeSpans.scope (field 1): InstrumentationScope.name (field 1).
+    let mut scope = Vec::new();
+    encode_string(&mut scope, 1, "tracing-subscriber");
+    encode_message(&mut scope_spans, 1, &scope);
+    for span in spans {
+        // ScopeSpans.spans (field 2).
+        encode_message(&mut scope_spans, 2, &encode_span(span));
+    }
+    encode_message(&mut resource_spans, 2, &scope_spans);
+
+    // ExportTraceServiceRequest.resource_spans (field 1).
+    let mut request = Vec::new();
+    encode_message(&mut request, 1, &resource_spans);
+    request
+}
+
+/// Encodes one `Span` message.
+fn encode_span(span: &FinishedSpan) -> Vec<u8> {
+    /// Span.kind (field 6): SPAN_KIND_INTERNAL.
+    const SPAN_KIND_INTERNAL: u64 = 1;
+
+    let mut buf = Vec::new();
+    encode_bytes(&mut buf, 1, &span.data.trace_id);
+    encode_bytes(&mut buf, 2, &span.data.span_id);
+    if let Some(parent) = &span.data.parent_span_id {
+        encode_bytes(&mut buf, 4, parent);
+    }
+    encode_string(&mut buf, 5, span.name);
+    encode_key(&mut buf, 6, WIRE_VARINT);
+    encode_varint(&mut buf, SPAN_KIND_INTERNAL);
+    encode_fixed64(&mut buf, 7, span.data.start);
+    encode_fixed64(&mut buf, 8, span.end);
+    for (key, value) in &span.data.attributes {
+        // Span.attributes (field 9).
+        encode_key_value(&mut buf, 9, key, value);
+    }
+    for event in &span.data.events {
+        // Span.events (field 11): Event { time (1), name (2),
+        // attributes (3) }.
+        let mut event_msg = Vec::new();
+        encode_fixed64(&mut event_msg, 1, event.time);
+        encode_string(&mut event_msg, 2, event.name);
+        for (key, value) in &event.attributes {
+            encode_key_value(&mut event_msg, 3, key, value);
+        }
+        encode_message(&mut buf, 11, &event_msg);
+    }
+    buf
+}
+
+/// Encodes a `KeyValue { key (1), value (2): AnyValue }` message as
+/// `field`.
+fn encode_key_value(buf: &mut Vec<u8>, field: u64, key: &str, value: &AttrValue) {
+    let mut any_value = Vec::new();
+    match value {
+        // AnyValue's oneof: string (1), bool (2), int (3), double (4).
+        AttrValue::Str(value) => encode_string(&mut any_value, 1, value),
+        AttrValue::Bool(value) => {
+            encode_key(&mut any_value, 2, WIRE_VARINT);
+            encode_varint(&mut any_value, u64::from(*value));
+        }
+        AttrValue::Int(value) => {
+            encode_key(&mut any_value, 3, WIRE_VARINT);
+            encode_varint(&mut any_value, *value as u64);
+        }
+        AttrValue::Double(value) => encode_fixed64(&mut any_value, 4, value.to_bits()),
+    }
+
+    let mut key_value = Vec::new();
+    encode_string(&mut key_value, 1, key);
+    encode_message(&mut key_value, 2, &any_value);
+    encode_message(buf, field, &key_value);
+}
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_FIXED64: u64 = 1;
+const WIRE_LEN: u64 = 2;
+
+fn encode_key(buf: &mut Vec<u8>, field: u64, wire_type: u64) {
+    encode_varint(buf, (field << 3) | wire_type);
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn encode_fixed64(buf: &mut Vec<u8>, field: u64, value: u64) {
+    encode_key(buf, field, WIRE_FIXED64);
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn encode_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    encode_key(buf, field, WIRE_LEN);
+    encode_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn encode_string(buf: &mut Vec<u8>, field: u64, value: &str) {
+    encode_bytes(buf, field, value.as_bytes());
+}
+
+fn encode_message(buf: &mut Vec<u8>, field: u64, message: &[u8]) {
+    encode_bytes(buf, field, message);
+}
+
+/// Returns the current wall-clock time in nanoseconds since the Unix epoch.
+fn unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Returns `N` pseudo-random bytes, suitable for span and trace IDs.
+///
+/// This uses the standard library's randomly-seeded hasher rather than a
+/// cryptographic RNG; IDs are unique in practice but not unpredictable.
+fn random_bytes<const N: usize>() -> [u8; N] {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+        sync::atomic::{AtomicU64, Ordering},
+    };
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut bytes = [0; N];
+    for chunk in bytes.chunks_mut(8) {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        let word = hasher.finish().to_le_bytes();
+        chunk.copy_from_slice(&word[..chunk.len()]);
+    }
+    bytes
+}
+
+/// Records event and span fields as OTLP attributes.
+struct AttrVisitor<'a> {
+    attributes: &'a mut Vec<(&'static str, AttrValue)>,
+}
+
+impl AttrVisitor<'_> {
+    fn record(&mut self, field: &field::Field, value: AttrValue) {
+        match self
+            .attributes
+            .iter_mut()
+            .find(|(name, _)| *name == field.name())
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.attributes.push((field.name(), value)),
+        }
+    }
+}
+
+impl field::Visit for AttrVisitor<'_> {
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.record(field, AttrValue::Int(value));
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.record(field, AttrValue::Int(value as i64));
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.record(field, AttrValue::Double(value));
+    }
+
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.record(field, AttrValue::Bool(value));
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.record(field, AttrValue::Str(value.to_owned()));
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.record(field, AttrValue::Str(format!("{:?}", value)));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::net::TcpListener;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn varints_encode_correctly() {
+        let mut buf = Vec::new();
+        encode_varint(&mut buf, 1);
+        assert_eq!(buf, [0x01]);
+        buf.clear();
+        encode_varint(&mut buf, 300);
+        assert_eq!(buf, [0xac, 0x02]);
+        buf.clear();
+        encode_varint(&mut buf, u64::MAX);
+        assert_eq!(
+            buf,
+            [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]
+        );
+    }
+
+    #[test]
+    fn key_values_encode_correctly() {
+        let mut buf = Vec::new();
+        encode_key_value(&mut buf, 9, "x", &AttrValue::Int(7));
+        // field 9, length-delimited: KeyValue { key: "x",
+        // value: AnyValue { int_value: 7 } }.
+        assert_eq!(
+            buf,
+            [
+                0x4a, 0x07, // Span.attributes, 7 bytes
+                0x0a, 0x01, b'x', // key (1): "x"
+                0x12, 0x02, // value (2): AnyValue, 2 bytes
+                0x18, 0x07, // int_value (3): 7
+            ]
+        );
+    }
+
+    #[test]
+    fn ids_are_distinct() {
+        let a: [u8; 16] = random_bytes();
+        let b: [u8; 16] = random_bytes();
+        assert_ne!(a, [0; 16]);
+        assert_ne!(a, b);
+    }
+
+    /// Accepts one OTLP/HTTP request and returns its body.
+    fn accept_export(listener: &TcpListener) -> Vec<u8> {
+        let (stream, _) = listener.accept().expect("no export request received");
+        let mut reader = BufReader::new(stream);
+        let mut content_length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).expect("failed to read header");
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(length) = header
+                .to_ascii_lowercase()
+                .strip_prefix("content-length:")
+                .map(str::trim)
+            {
+                content_length = length.parse().expect("invalid content-length");
+            }
+        }
+        let mut body = vec![0; content_length];
+        io::Read::read_exact(&mut reader, &mut body).expect("failed to read body");
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .expect("failed to respond");
+        body
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    #[test]
+    fn exports_spans_over_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let exporter = Subscriber::builder()
+            .with_endpoint(format!("http://{}", addr))
+            .with_service_name("test-service")
+            .finish()
+            .expect("failed to start exporter");
+        let collector = crate::registry().with(exporter);
+
+        with_default(collector, || {
+            let parent = tracing::info_span!("parent_span", answer = 42);
+            let _entered = parent.enter();
+            let child = tracing::info_span!("child_span");
+            child.in_scope(|| {
+                tracing::info!(detail = "inner", "something happened");
+            });
+        });
+        // Dropping the collector disconnects the channel, flushing the
+        // batch.
+
+        let body = accept_export(&listener);
+        assert!(contains(&body, b"test-service"), "missing resource");
+        assert!(contains(&body, b"parent_span"), "missing parent span");
+        assert!(contains(&body, b"child_span"), "missing child span");
+        assert!(contains(&body, b"detail"), "missing event attribute");
+    }
+}
@@ -0,0 +1,264 @@
+//! A panic hook that records panics as structured `tracing` events.
+//!
+//! By default, panics are reported by printing directly to standard error,
+//! bypassing `tracing` entirely: the panic message does not reach any
+//! subscriber, is not attributed to the span the thread was in, and is
+//! missing from log files and other non-terminal outputs. This module
+//! provides a [`PanicHook`] that reports panics as [`ERROR`] events instead,
+//! carrying the panic message, the source location, and the panicking
+//! thread's name as structured fields.
+//!
+//! The hook emits the event from the panicking thread, *before* unwinding
+//! begins, so the event is recorded inside the thread's current span scope:
+//! formatters that print span contexts (such as [`fmt`]) show the names and
+//! recorded fields of every span the thread was in when it panicked.
+//!
+//! The hook wraps (rather than replaces) the previously-installed panic
+//! hook, so the standard message and backtrace are still printed afterwards.
+//!
+//! # Examples
+//!
+//! ```
+//! tracing_subscriber::panic::PanicHook::new().install();
+//! ```
+//!
+//! Flushing a buffered writer before the process unwinds or aborts:
+//!
+//! ```
+//! use std::{
+//!     io::{self, BufWriter, Write},
+//!     sync::{Arc, Mutex},
+//! };
+//! use tracing_subscriber::panic::PanicHook;
+//!
+//! let writer = Arc::new(Mutex::new(BufWriter::new(io::stderr())));
+//! let flush = writer.clone();
+//! PanicHook::new()
+//!     .with_flush(move || {
+//!         let _ = flush.lock().unwrap().flush();
+//!     })
+//!     .install();
+//! ```
+//!
+//! [`ERROR`]: tracing_core::Level::ERROR
+//! [`fmt`]: mod@crate::fmt
+use std::{fmt, panic::Location, sync::Arc};
+
+/// A panic hook that emits an [`ERROR`] event describing the panic.
+///
+/// See the [module-level documentation](self) for details.
+///
+/// [`ERROR`]: tracing_core::Level::ERROR
+#[derive(Default)]
+pub struct PanicHook {
+    backtrace: Option<Box<dyn Fn() -> String + Send + Sync>>,
+    flushes: Vec<Box<dyn Fn() + Send + Sync>>,
+}
+
+// === impl PanicHook ===
+
+impl PanicHook {
+    /// Returns a new `PanicHook` with the default configuration.
+    ///
+    /// By default, no backtrace is captured and no writers are flushed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Captures a backtrace with the provided closure, recording it as the
+    /// `backtrace` field of the panic event.
+    ///
+    /// The closure runs on the panicking thread, before unwinding begins.
+    /// For example, using the standard library's backtrace support:
+    ///
+    /// ```
+    /// use tracing_subscriber::panic::PanicHook;
+    ///
+    /// PanicHook::new()
+    ///     .with_backtrace(|| std::backtrace::Backtrace::force_capture().to_string())
+    ///     .install();
+    /// ```
+    ///
+    /// By default, no backtrace is captured.
+    pub fn with_backtrace<F>(self, capture: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        Self {
+            backtrace: Some(Box::new(capture)),
+            ..self
+        }
+    }
+
+    /// Runs the provided closure after the panic event has been emitted,
+    /// before the previous panic hook runs.
+    ///
+    /// This is intended for flushing buffered writers — such as the
+    /// [`tracing-appender`] crate's non-blocking writer — so that the panic
+    /// event (and any events preceding it) reach their destination even if
+    /// the process aborts. This method may be called multiple times; the
+    /// closures run in the order they were added.
+    ///
+    /// [`tracing-appender`]: https://crates.io/crates/tracing-appender
+    pub fn with_flush<F>(mut self, flush: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.flushes.push(Box::new(flush));
+        self
+    }
+
+    /// Installs this hook as the process-wide panic hook.
+    ///
+    /// The previously-installed hook is wrapped, not replaced: it runs after
+    /// the panic event has been emitted and the flush closures have run, so
+    /// the standard panic message and backtrace are still printed.
+    pub fn install(self) {
+        let hook = Arc::new(self);
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let payload = info.payload();
+            let message = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("Box<dyn Any>");
+            // A panic inside the hook itself would abort the process; make
+            // sure one subscriber bug doesn't turn every panic into an
+            // abort.
+            let hook = hook.clone();
+            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+                hook.emit(message, info.location());
+                for flush in &hook.flushes {
+                    flush();
+                }
+            }));
+            previous(info);
+        }));
+    }
+
+    /// Emits the panic event on the panicking thread.
+    fn emit(&self, message: &str, location: Option<&Location<'_>>) {
+        let thread = std::thread::current();
+        let thread = thread.name().unwrap_or("<unnamed>");
+        let file = location.map(Location::file).unwrap_or("<unknown>");
+        let line = location.map(|location| location.line()).unwrap_or(0);
+        let column = location.map(|location| location.column()).unwrap_or(0);
+        match self.backtrace.as_ref().map(|capture| capture()) {
+            Some(backtrace) => tracing::error!(
+                target: "tracing_subscriber::panic",
+                thread,
+                location.file = file,
+                location.line = line,
+                location.column = column,
+                backtrace = backtrace.as_str(),
+                "{message}"
+            ),
+            None => tracing::error!(
+                target: "tracing_subscriber::panic",
+                thread,
+                location.file = file,
+                location.line = line,
+                location.column = column,
+                "{message}"
+            ),
+        }
+    }
+}
+
+impl fmt::Debug for PanicHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PanicHook")
+            .field("backtrace", &self.backtrace.is_some())
+            .field("flushes", &self.flushes.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        prelude::*,
+        registry::LookupSpan,
+        subscribe::{Context, Subscribe},
+    };
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    };
+    use tracing_core::{field, Collect, Event};
+
+    /// Records each event's message, `location.file` field, and span scope.
+    #[derive(Default)]
+    struct Probe {
+        events: Arc<Mutex<Vec<(String, Option<String>, Vec<&'static str>)>>>,
+    }
+
+    impl<C> Subscribe<C> for Probe
+    where
+        C: Collect + for<'a> LookupSpan<'a>,
+    {
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+            #[derive(Default)]
+            struct Visitor {
+                message: String,
+                file: Option<String>,
+            }
+            impl field::Visit for Visitor {
+                fn record_debug(&mut self, field: &field::Field, value: &dyn fmt::Debug) {
+                    match field.name() {
+                        "message" => self.message = format!("{:?}", value),
+                        "location.file" => self.file = Some(format!("{:?}", value)),
+                        _ => {}
+                    }
+                }
+            }
+
+            let mut visitor = Visitor::default();
+            event.record(&mut visitor);
+            let scope = ctx
+                .event_scope(event)
+                .map(|scope| scope.from_root().map(|span| span.name()).collect())
+                .unwrap_or_default();
+            self.events
+                .lock()
+                .unwrap()
+                .push((visitor.message, visitor.file, scope));
+        }
+    }
+
+    #[test]
+    fn panics_are_reported_as_events() {
+        let probe = Probe::default();
+        let events = probe.events.clone();
+        let collector = crate::registry().with(probe);
+
+        // Silence the default hook's output for the intentional panic, and
+        // restore it afterwards so other tests report panics normally.
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| {}));
+        let flushed = Arc::new(AtomicBool::new(false));
+        let flushed2 = flushed.clone();
+        PanicHook::new()
+            .with_backtrace(|| String::from("fake backtrace"))
+            .with_flush(move || flushed2.store(true, Ordering::SeqCst))
+            .install();
+
+        tracing::collect::with_default(collector, || {
+            let span = tracing::info_span!("doomed");
+            let _entered = span.enter();
+            let _ = std::panic::catch_unwind(|| panic!("boom"));
+        });
+        let _ = std::panic::take_hook();
+        std::panic::set_hook(default_hook);
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        let (message, file, scope) = &events[0];
+        assert_eq!(message, "boom");
+        assert_eq!(file.as_deref(), Some(format!("{:?}", file!()).as_str()));
+        assert_eq!(scope, &["doomed"]);
+        assert!(flushed.load(Ordering::SeqCst), "flush closure did not run");
+    }
+}
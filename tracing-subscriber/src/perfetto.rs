@@ -0,0 +1,639 @@
+//! Perfetto [TrackEvent] output for spans and events.
+//!
+//! Like the [`chrome`] module, this module records spans and events on
+//! per-thread timelines for the [Perfetto UI] (`ui.perfetto.dev`), but in
+//! Perfetto's native protobuf trace format rather than Chrome's JSON one.
+//! The protobuf format is considerably more compact — names, categories,
+//! and field names are [interned] once per thread rather than repeated in
+//! every record — so it scales to much larger traces. The protobuf
+//! encoding is written by hand here, as in the [`otlp`] module, so no
+//! protobuf dependency (or the Perfetto SDK) is required.
+//!
+//! Entering a span emits a `TYPE_SLICE_BEGIN` track event and exiting it a
+//! `TYPE_SLICE_END`, so a span entered several times appears as several
+//! slices; events are emitted as `TYPE_INSTANT`. Span and event fields are
+//! recorded as typed [debug annotations], and each record's target as its
+//! category. Each OS thread writes its own packet sequence with its own
+//! interning state and a thread descriptor carrying the thread's name, so
+//! the UI groups slices by thread under the process.
+//!
+//! The trace is written as a raw stream of `TracePacket`s, which the
+//! Perfetto UI reads directly — there is no footer, but the [`Guard`]
+//! returned by the constructors should be dropped at the end of the trace
+//! to flush buffered packets. By convention the file extension is
+//! `.perfetto-trace`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{perfetto, prelude::*};
+//!
+//! let (perfetto, guard) = perfetto::Subscriber::with_file("trace.perfetto-trace")
+//!     .expect("failed to create the trace file");
+//! let collector = tracing_subscriber::registry().with(perfetto);
+//! tracing::collect::set_global_default(collector)
+//!     .expect("failed to set global default collector");
+//!
+//! // ... run the program ...
+//!
+//! // Flushes any buffered trace packets.
+//! drop(guard);
+//! ```
+//!
+//! [TrackEvent]: https://perfetto.dev/docs/instrumentation/track-events
+//! [Perfetto UI]: https://ui.perfetto.dev
+//! [interned]: https://perfetto.dev/docs/design-docs/protozero#interned-data
+//! [debug annotations]: https://perfetto.dev/docs/instrumentation/track-events#debug-annotations
+//! [`chrome`]: mod@crate::chrome
+//! [`otlp`]: mod@crate::otlp
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+use thread_local::ThreadLocal;
+use tracing_core::{field, span, Collect, Event};
+
+/// The track UUID of the process track; thread tracks are its children.
+const PROCESS_TRACK_UUID: u64 = 1;
+
+/// `SEQ_INCREMENTAL_STATE_CLEARED`: this sequence's interning state starts
+/// fresh at this packet.
+const SEQ_INCREMENTAL_STATE_CLEARED: u64 = 1;
+/// `SEQ_NEEDS_INCREMENTAL_STATE`: this packet refers to interned state.
+const SEQ_NEEDS_INCREMENTAL_STATE: u64 = 2;
+
+/// `TrackEvent.Type` values.
+const TYPE_SLICE_BEGIN: u64 = 1;
+const TYPE_SLICE_END: u64 = 2;
+const TYPE_INSTANT: u64 = 3;
+
+/// A [`Subscribe`] implementation that writes spans and events as Perfetto
+/// `TrackEvent` protos.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    shared: Arc<Shared>,
+}
+
+/// Flushes the trace when dropped.
+///
+/// The trace format needs no footer, but the writer may buffer packets;
+/// drop the guard at the end of the trace to flush them. Spans and events
+/// recorded after the guard is dropped are silently discarded.
+#[derive(Debug)]
+#[must_use = "dropping the guard flushes the trace file"]
+pub struct Guard {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    writer: Mutex<State>,
+    start: Instant,
+    pid: u32,
+    sequences: ThreadLocal<RefCell<Sequence>>,
+    next_sequence: AtomicU64,
+    process_described: AtomicBool,
+}
+
+struct State {
+    writer: Box<dyn Write + Send>,
+    /// Set once the [`Guard`] has flushed the trace.
+    closed: bool,
+}
+
+/// Per-thread packet sequence state.
+///
+/// Interned string IDs are scoped to a `trusted_packet_sequence_id`, and a
+/// sequence may only be written from one thread, so each thread gets its
+/// own sequence with its own interning tables.
+struct Sequence {
+    id: u64,
+    track_uuid: u64,
+    /// Whether this sequence's track descriptor has been written.
+    started: bool,
+    event_names: HashMap<String, u64>,
+    categories: HashMap<String, u64>,
+    annotation_names: HashMap<String, u64>,
+}
+
+/// A span's fields as typed debug annotation values, stored in its
+/// extensions at creation and emitted with each `TYPE_SLICE_BEGIN`.
+struct SpanAnnotations(Vec<(String, AnnotationValue)>);
+
+/// The typed value of one debug annotation.
+enum AnnotationValue {
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    Double(f64),
+    Str(String),
+}
+
+/// Newly interned strings to be carried in a packet's `InternedData`.
+#[derive(Default)]
+struct NewInterns {
+    event_names: Vec<(u64, String)>,
+    categories: Vec<(u64, String)>,
+    annotation_names: Vec<(u64, String)>,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new `Subscriber` writing the trace to `writer`, and the
+    /// [`Guard`] that flushes it.
+    pub fn new(writer: impl Write + Send + 'static) -> (Self, Guard) {
+        let shared = Arc::new(Shared {
+            writer: Mutex::new(State {
+                writer: Box::new(writer),
+                closed: false,
+            }),
+            start: Instant::now(),
+            pid: std::process::id(),
+            sequences: ThreadLocal::new(),
+            next_sequence: AtomicU64::new(1),
+            process_described: AtomicBool::new(false),
+        });
+        let guard = Guard {
+            shared: shared.clone(),
+        };
+        (Self { shared }, guard)
+    }
+
+    /// Returns a new `Subscriber` writing the trace to a file created at
+    /// `path`, and the [`Guard`] that flushes it.
+    ///
+    /// If a file already exists at `path`, it is truncated.
+    pub fn with_file(path: impl AsRef<Path>) -> io::Result<(Self, Guard)> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+
+    /// Writes one track event packet on the current thread's sequence.
+    fn write_track_event(
+        &self,
+        event_type: u64,
+        name: &str,
+        category: &str,
+        annotations: &[(String, AnnotationValue)],
+    ) {
+        let timestamp = self.shared.start.elapsed().as_nanos() as u64;
+        let sequence = self.shared.sequences.get_or(|| {
+            let id = self.shared.next_sequence.fetch_add(1, Ordering::Relaxed);
+            RefCell::new(Sequence {
+                id,
+                track_uuid: PROCESS_TRACK_UUID + id,
+                started: false,
+                event_names: HashMap::new(),
+                categories: HashMap::new(),
+                annotation_names: HashMap::new(),
+            })
+        });
+        let mut sequence = sequence.borrow_mut();
+        let mut flags = SEQ_NEEDS_INCREMENTAL_STATE;
+        if !sequence.started {
+            sequence.started = true;
+            flags |= SEQ_INCREMENTAL_STATE_CLEARED;
+            self.write_descriptors(&sequence);
+        }
+
+        let mut interns = NewInterns::default();
+        let name_iid = intern(&mut sequence.event_names, &mut interns.event_names, name);
+        let category_iid = intern(&mut sequence.categories, &mut interns.categories, category);
+
+        // TrackEvent: category_iids = 3, debug_annotations = 4, type = 9,
+        // name_iid = 10, track_uuid = 11.
+        let mut track_event = Vec::new();
+        encode_varint_field(&mut track_event, 3, category_iid);
+        for (name, value) in annotations {
+            let iid = intern(
+                &mut sequence.annotation_names,
+                &mut interns.annotation_names,
+                name,
+            );
+            // DebugAnnotation: name_iid = 1, bool_value = 2, uint_value = 3,
+            // int_value = 4, double_value = 5, string_value = 6.
+            let mut annotation = Vec::new();
+            encode_varint_field(&mut annotation, 1, iid);
+            match value {
+                AnnotationValue::Bool(value) => {
+                    encode_varint_field(&mut annotation, 2, *value as u64)
+                }
+                AnnotationValue::Uint(value) => encode_varint_field(&mut annotation, 3, *value),
+                AnnotationValue::Int(value) => {
+                    encode_varint_field(&mut annotation, 4, *value as u64)
+                }
+                AnnotationValue::Double(value) => {
+                    encode_key(&mut annotation, 5, 1);
+                    annotation.extend_from_slice(&value.to_bits().to_le_bytes());
+                }
+                AnnotationValue::Str(value) => encode_string(&mut annotation, 6, value),
+            }
+            encode_message(&mut track_event, 4, &annotation);
+        }
+        encode_varint_field(&mut track_event, 9, event_type);
+        encode_varint_field(&mut track_event, 10, name_iid);
+        encode_varint_field(&mut track_event, 11, sequence.track_uuid);
+
+        // TracePacket: timestamp = 8, trusted_packet_sequence_id = 10,
+        // track_event = 11, interned_data = 12, sequence_flags = 13.
+        let mut packet = Vec::new();
+        encode_varint_field(&mut packet, 8, timestamp);
+        encode_varint_field(&mut packet, 10, sequence.id);
+        encode_message(&mut packet, 11, &track_event);
+        if let Some(interned_data) = interns.encode() {
+            encode_message(&mut packet, 12, &interned_data);
+        }
+        encode_varint_field(&mut packet, 13, flags);
+        self.shared.write_packet(&packet);
+    }
+
+    /// Writes the track descriptors for a sequence's first packet: the
+    /// process track (once per trace) and the current thread's track.
+    fn write_descriptors(&self, sequence: &Sequence) {
+        if !self.shared.process_described.swap(true, Ordering::Relaxed) {
+            // ProcessDescriptor: pid = 1.
+            let mut process = Vec::new();
+            encode_varint_field(&mut process, 1, u64::from(self.shared.pid));
+            // TrackDescriptor: uuid = 1, process = 3.
+            let mut descriptor = Vec::new();
+            encode_varint_field(&mut descriptor, 1, PROCESS_TRACK_UUID);
+            encode_message(&mut descriptor, 3, &process);
+            // TracePacket: track_descriptor = 60.
+            let mut packet = Vec::new();
+            encode_message(&mut packet, 60, &descriptor);
+            self.shared.write_packet(&packet);
+        }
+
+        let thread = std::thread::current();
+        // ThreadDescriptor: pid = 1, tid = 2, thread_name = 5.
+        let mut thread_descriptor = Vec::new();
+        encode_varint_field(&mut thread_descriptor, 1, u64::from(self.shared.pid));
+        encode_varint_field(&mut thread_descriptor, 2, sequence.id);
+        encode_string(
+            &mut thread_descriptor,
+            5,
+            thread.name().unwrap_or("<unnamed>"),
+        );
+        // TrackDescriptor: uuid = 1, thread = 4, parent_uuid = 5.
+        let mut descriptor = Vec::new();
+        encode_varint_field(&mut descriptor, 1, sequence.track_uuid);
+        encode_message(&mut descriptor, 4, &thread_descriptor);
+        encode_varint_field(&mut descriptor, 5, PROCESS_TRACK_UUID);
+        let mut packet = Vec::new();
+        encode_message(&mut packet, 60, &descriptor);
+        self.shared.write_packet(&packet);
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut annotations = Vec::new();
+        attrs.record(&mut AnnotationVisitor(&mut annotations));
+        span.extensions_mut().insert(SpanAnnotations(annotations));
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(SpanAnnotations(annotations)) = extensions.get_mut::<SpanAnnotations>() {
+            values.record(&mut AnnotationVisitor(annotations));
+        }
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let extensions = span.extensions();
+        let annotations = extensions
+            .get::<SpanAnnotations>()
+            .map(|SpanAnnotations(annotations)| &annotations[..])
+            .unwrap_or(&[]);
+        self.write_track_event(
+            TYPE_SLICE_BEGIN,
+            span.name(),
+            span.metadata().target(),
+            annotations,
+        );
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        self.write_track_event(TYPE_SLICE_END, span.name(), span.metadata().target(), &[]);
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        let mut annotations = Vec::new();
+        event.record(&mut AnnotationVisitor(&mut annotations));
+        self.write_track_event(
+            TYPE_INSTANT,
+            event.metadata().name(),
+            event.metadata().target(),
+            &annotations,
+        );
+    }
+}
+
+// === impl Shared ===
+
+impl Shared {
+    /// Appends one `TracePacket` to the trace, unless it has been closed.
+    ///
+    /// The trace format is a stream of `Trace.packet` fields (field 1).
+    fn write_packet(&self, packet: &[u8]) {
+        let mut framed = Vec::with_capacity(packet.len() + 4);
+        encode_message(&mut framed, 1, packet);
+        let mut state = self.writer.lock().expect("perfetto trace writer poisoned");
+        if state.closed {
+            return;
+        }
+        let _ = state.writer.write_all(&framed);
+    }
+}
+
+impl std::fmt::Debug for Shared {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Shared")
+            .field("pid", &self.pid)
+            .field("start", &self.start)
+            .finish()
+    }
+}
+
+// === impl Guard ===
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let mut state = self
+            .shared
+            .writer
+            .lock()
+            .expect("perfetto trace writer poisoned");
+        if !state.closed {
+            state.closed = true;
+            let _ = state.writer.flush();
+        }
+    }
+}
+
+// === impl NewInterns ===
+
+impl NewInterns {
+    /// Encodes an `InternedData` message carrying the newly interned
+    /// strings, or `None` if there are none.
+    ///
+    /// InternedData: event_categories = 1, event_names = 2,
+    /// debug_annotation_names = 3; each entry is `{ iid = 1, name = 2 }`.
+    fn encode(&self) -> Option<Vec<u8>> {
+        if self.event_names.is_empty()
+            && self.categories.is_empty()
+            && self.annotation_names.is_empty()
+        {
+            return None;
+        }
+        let mut interned = Vec::new();
+        for (field, entries) in [
+            (1, &self.categories),
+            (2, &self.event_names),
+            (3, &self.annotation_names),
+        ] {
+            for (iid, name) in entries {
+                let mut entry = Vec::new();
+                encode_varint_field(&mut entry, 1, *iid);
+                encode_string(&mut entry, 2, name);
+                encode_message(&mut interned, field, &entry);
+            }
+        }
+        Some(interned)
+    }
+}
+
+/// Looks up `name`'s interned ID, assigning one (and recording it in
+/// `new`, for this packet's `InternedData`) on first use.
+fn intern(table: &mut HashMap<String, u64>, new: &mut Vec<(u64, String)>, name: &str) -> u64 {
+    if let Some(iid) = table.get(name) {
+        return *iid;
+    }
+    // Interned IDs must be non-zero.
+    let iid = table.len() as u64 + 1;
+    table.insert(name.to_owned(), iid);
+    new.push((iid, name.to_owned()));
+    iid
+}
+
+/// Records fields as typed [`AnnotationValue`]s.
+struct AnnotationVisitor<'a>(&'a mut Vec<(String, AnnotationValue)>);
+
+impl field::Visit for AnnotationVisitor<'_> {
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.0
+            .push((field.name().to_owned(), AnnotationValue::Int(value)));
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.0
+            .push((field.name().to_owned(), AnnotationValue::Uint(value)));
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.0
+            .push((field.name().to_owned(), AnnotationValue::Double(value)));
+    }
+
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.0
+            .push((field.name().to_owned(), AnnotationValue::Bool(value)));
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.0.push((
+            field.name().to_owned(),
+            AnnotationValue::Str(value.to_owned()),
+        ));
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.0.push((
+            field.name().to_owned(),
+            AnnotationValue::Str(format!("{:?}", value)),
+        ));
+    }
+}
+
+// === protobuf encoding ===
+//
+// As in the `otlp` module, the handful of protobuf constructs needed here
+// are encoded by hand rather than pulling in a protobuf dependency.
+
+/// Encodes `value` as a base-128 varint.
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+/// Encodes a field key (field number and wire type).
+fn encode_key(buf: &mut Vec<u8>, field_number: u64, wire_type: u64) {
+    encode_varint(buf, (field_number << 3) | wire_type);
+}
+
+/// Encodes a varint-typed field.
+fn encode_varint_field(buf: &mut Vec<u8>, field_number: u64, value: u64) {
+    encode_key(buf, field_number, 0);
+    encode_varint(buf, value);
+}
+
+/// Encodes a string field.
+fn encode_string(buf: &mut Vec<u8>, field_number: u64, value: &str) {
+    encode_key(buf, field_number, 2);
+    encode_varint(buf, value.len() as u64);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+/// Encodes an embedded message field.
+fn encode_message(buf: &mut Vec<u8>, field_number: u64, message: &[u8]) {
+    encode_key(buf, field_number, 2);
+    encode_varint(buf, message.len() as u64);
+    buf.extend_from_slice(message);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    /// A writer appending to a shared buffer.
+    #[derive(Clone)]
+    struct Buf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn trace(f: impl FnOnce()) -> Vec<u8> {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let (perfetto, guard) = Subscriber::new(Buf(buf.clone()));
+        let collector = crate::registry().with(perfetto);
+        with_default(collector, f);
+        drop(guard);
+        let output = buf.lock().unwrap().clone();
+        output
+    }
+
+    fn count_occurrences(haystack: &[u8], needle: &[u8]) -> usize {
+        haystack
+            .windows(needle.len())
+            .filter(|window| *window == needle)
+            .count()
+    }
+
+    #[test]
+    fn varints_are_encoded_correctly() {
+        let mut buf = Vec::new();
+        encode_varint(&mut buf, 0);
+        assert_eq!(buf, [0x00]);
+        buf.clear();
+        encode_varint(&mut buf, 127);
+        assert_eq!(buf, [0x7f]);
+        buf.clear();
+        encode_varint(&mut buf, 300);
+        assert_eq!(buf, [0xac, 0x02]);
+    }
+
+    #[test]
+    fn traces_contain_names_and_annotations() {
+        let output = trace(|| {
+            let span = tracing::info_span!("perfetto_span", answer = 42);
+            let _entered = span.enter();
+            tracing::info!(detail = "inner_value", "something happened");
+        });
+
+        // The current thread's name, the span name, and annotation names
+        // and string values are embedded as raw string bytes.
+        let thread = std::thread::current();
+        assert!(count_occurrences(&output, thread.name().unwrap().as_bytes()) > 0);
+        assert!(count_occurrences(&output, b"perfetto_span") > 0);
+        assert!(count_occurrences(&output, b"answer") > 0);
+        assert!(count_occurrences(&output, b"detail") > 0);
+        assert!(count_occurrences(&output, b"inner_value") > 0);
+    }
+
+    #[test]
+    fn names_are_interned_once_per_thread() {
+        let output = trace(|| {
+            let span = tracing::info_span!("interned_span");
+            for _ in 0..10 {
+                let _entered = span.enter();
+            }
+        });
+
+        // The span is entered ten times, but its name is only written once,
+        // in the first packet's InternedData; later packets refer to it by
+        // its interned ID.
+        assert_eq!(count_occurrences(&output, b"interned_span"), 1);
+    }
+
+    #[test]
+    fn packet_stream_is_well_formed() {
+        let output = trace(|| {
+            let span = tracing::info_span!("span");
+            let _entered = span.enter();
+            tracing::info!("event");
+        });
+
+        // The trace must be a sequence of length-delimited `packet` fields
+        // (field 1, wire type 2) that exactly covers the output.
+        let mut offset = 0;
+        let mut packets = 0;
+        while offset < output.len() {
+            assert_eq!(output[offset], (1 << 3) | 2, "bad key at {}", offset);
+            offset += 1;
+            let mut len = 0u64;
+            let mut shift = 0;
+            loop {
+                let byte = output[offset];
+                offset += 1;
+                len |= u64::from(byte & 0x7f) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            offset += len as usize;
+            packets += 1;
+        }
+        assert_eq!(offset, output.len());
+        // Process descriptor, thread descriptor, begin, instant, end.
+        assert_eq!(packets, 5);
+    }
+}
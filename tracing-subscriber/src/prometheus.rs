@@ -0,0 +1,545 @@
+//! Prometheus text exposition for tracing-derived metrics.
+//!
+//! This module renders in-process aggregates in the [Prometheus text
+//! exposition format], so that a scraper can collect them. Two sources are
+//! supported:
+//!
+//! - the [`metrics`](crate::metrics) module's aggregates, via its
+//!   [`Handle`](crate::metrics::Handle), and
+//! - a [`Stats`] subscriber provided here, which counts events and spans by
+//!   level and target, and records span durations per callsite, with no
+//!   instrumentation conventions required.
+//!
+//! An [`Exporter`] combines any number of these sources. [`Exporter::render`]
+//! returns the exposition text as a `String`, for applications that already
+//! have an HTTP server to mount it on; [`serve_tcp`] serves it from a tiny
+//! embedded HTTP listener for applications that do not.
+//!
+//! Counters and gauges are rendered as the corresponding Prometheus types.
+//! Distributions (span durations and `histogram.*` aggregates) are rendered
+//! as [summaries] with `0.5`, `0.9`, and `0.99` quantiles, since the
+//! underlying histograms bucket values logarithmically rather than at fixed
+//! boundaries.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{metrics, prometheus, prelude::*};
+//!
+//! let (metrics, metrics_handle) = metrics::Subscriber::new();
+//! let (stats, stats_handle) = prometheus::Stats::new();
+//! let collector = tracing_subscriber::registry().with(metrics).with(stats);
+//! tracing::collect::set_global_default(collector)
+//!     .expect("failed to set global default collector");
+//!
+//! let exporter = prometheus::Exporter::new()
+//!     .with_metrics(metrics_handle)
+//!     .with_stats(stats_handle);
+//! prometheus::serve_tcp(exporter, "127.0.0.1:9464")
+//!     .expect("failed to bind the metrics endpoint");
+//! ```
+//!
+//! # Security
+//!
+//! The embedded listener performs no authentication and serves every request
+//! it receives. Bind it to a loopback or otherwise protected address, never
+//! to a publicly reachable one.
+//!
+//! [Prometheus text exposition format]:
+//!     https://prometheus.io/docs/instrumenting/exposition_formats/
+//! [summaries]: https://prometheus.io/docs/concepts/metric_types/#summary
+use crate::{
+    metrics,
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+    timing::Histogram,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+    time::Instant,
+};
+use tracing_core::{span, Collect, Event, Level, Metadata};
+
+/// Renders tracing-derived aggregates in Prometheus text exposition format.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Default)]
+pub struct Exporter {
+    metrics: Option<metrics::Handle>,
+    stats: Option<StatsHandle>,
+}
+
+/// A [`Subscribe`] implementation that counts events and spans by level and
+/// target, and records span durations per callsite.
+///
+/// The recorded aggregates are rendered by adding the [`StatsHandle`] to an
+/// [`Exporter`] with [`Exporter::with_stats`]:
+///
+/// - `tracing_events_total{level, target}`: the number of events recorded,
+/// - `tracing_spans_total{level, target}`: the number of spans created, and
+/// - `tracing_span_duration_seconds{name, target}`: the time from each
+///   span's creation to its close.
+#[derive(Debug)]
+pub struct Stats {
+    shared: Arc<Mutex<StatsInner>>,
+}
+
+/// Provides access to the aggregates recorded by a [`Stats`] subscriber.
+///
+/// This is returned by [`Stats::new`], and may be cloned and sent to other
+/// threads freely.
+#[derive(Debug, Clone)]
+pub struct StatsHandle {
+    shared: Arc<Mutex<StatsInner>>,
+}
+
+/// A running metrics endpoint.
+///
+/// This is returned by [`serve_tcp`]. The endpoint is served on a background
+/// thread, which runs until the process exits; dropping the `Server` does
+/// not shut it down.
+#[derive(Debug)]
+pub struct Server {
+    addr: Option<SocketAddr>,
+}
+
+#[derive(Debug, Default)]
+struct StatsInner {
+    events: BTreeMap<(&'static str, &'static str), u64>,
+    spans: BTreeMap<(&'static str, &'static str), u64>,
+    durations: BTreeMap<(&'static str, &'static str), Histogram>,
+}
+
+/// The creation time of a span, stored in its extensions by [`Stats`].
+struct SpanStarted(Instant);
+
+// === impl Exporter ===
+
+impl Exporter {
+    /// Returns a new `Exporter` with no sources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the aggregates behind a [`metrics::Handle`] to the exposition.
+    pub fn with_metrics(self, handle: metrics::Handle) -> Self {
+        Self {
+            metrics: Some(handle),
+            ..self
+        }
+    }
+
+    /// Adds the aggregates behind a [`StatsHandle`] to the exposition.
+    pub fn with_stats(self, handle: StatsHandle) -> Self {
+        Self {
+            stats: Some(handle),
+            ..self
+        }
+    }
+
+    /// Renders the current aggregates in Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        if let Some(stats) = &self.stats {
+            stats.render(&mut out);
+        }
+        if let Some(metrics) = &self.metrics {
+            render_metrics(metrics, &mut out);
+        }
+        out
+    }
+}
+
+// === impl Stats ===
+
+impl Stats {
+    /// Returns a new stats subscriber, and a [`StatsHandle`] that provides
+    /// access to the aggregates it records.
+    pub fn new() -> (Self, StatsHandle) {
+        let shared = Arc::new(Mutex::new(StatsInner::default()));
+        let handle = StatsHandle {
+            shared: shared.clone(),
+        };
+        (Self { shared }, handle)
+    }
+
+    fn count(
+        &self,
+        counts: fn(&mut StatsInner) -> &mut BTreeMap<(&'static str, &'static str), u64>,
+        metadata: &Metadata<'static>,
+    ) {
+        let mut inner = self.shared.lock().expect("prometheus stats poisoned");
+        *counts(&mut inner)
+            .entry((level_label(metadata.level()), metadata.target()))
+            .or_default() += 1;
+    }
+}
+
+impl<C> Subscribe<C> for Stats
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        self.count(|inner| &mut inner.spans, span.metadata());
+        span.extensions_mut().insert(SpanStarted(Instant::now()));
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        self.count(|inner| &mut inner.events, event.metadata());
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(&id).expect("Span not found, this is a bug");
+        let started = match span.extensions().get::<SpanStarted>() {
+            Some(started) => started.0,
+            None => return,
+        };
+        let nanos = started.elapsed().as_nanos() as u64;
+        let metadata = span.metadata();
+        let mut inner = self.shared.lock().expect("prometheus stats poisoned");
+        inner
+            .durations
+            .entry((metadata.name(), metadata.target()))
+            .or_default()
+            .record(nanos);
+    }
+}
+
+// === impl StatsHandle ===
+
+impl StatsHandle {
+    /// Renders the recorded aggregates into `out`.
+    fn render(&self, out: &mut String) {
+        let inner = self.shared.lock().expect("prometheus stats poisoned");
+        if !inner.events.is_empty() {
+            out.push_str("# TYPE tracing_events_total counter\n");
+            for ((level, target), count) in &inner.events {
+                let _ = writeln!(
+                    out,
+                    "tracing_events_total{{level=\"{}\",target=\"{}\"}} {}",
+                    level,
+                    escape_label(target),
+                    count
+                );
+            }
+        }
+        if !inner.spans.is_empty() {
+            out.push_str("# TYPE tracing_spans_total counter\n");
+            for ((level, target), count) in &inner.spans {
+                let _ = writeln!(
+                    out,
+                    "tracing_spans_total{{level=\"{}\",target=\"{}\"}} {}",
+                    level,
+                    escape_label(target),
+                    count
+                );
+            }
+        }
+        if !inner.durations.is_empty() {
+            out.push_str("# TYPE tracing_span_duration_seconds summary\n");
+            for ((name, target), histogram) in &inner.durations {
+                let labels = format!(
+                    "name=\"{}\",target=\"{}\"",
+                    escape_label(name),
+                    escape_label(target)
+                );
+                render_summary(
+                    out,
+                    "tracing_span_duration_seconds",
+                    &labels,
+                    histogram,
+                    1.0e-9,
+                );
+            }
+        }
+    }
+}
+
+// === impl Server ===
+
+impl Server {
+    /// Returns the local address the listener was bound to.
+    ///
+    /// This is useful when binding to port 0.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+}
+
+/// Starts serving `exporter`'s exposition over HTTP on a TCP socket bound to
+/// `addr`.
+///
+/// Every request receives a `200 OK` response with the current exposition,
+/// regardless of its method or path. The returned [`Server`]'s
+/// [`local_addr`] method returns the address the listener was actually bound
+/// to, which is useful when binding to port 0.
+///
+/// [`local_addr`]: Server::local_addr
+pub fn serve_tcp(exporter: Exporter, addr: impl ToSocketAddrs) -> io::Result<Server> {
+    let listener = TcpListener::bind(addr)?;
+    let addr = listener.local_addr().ok();
+    thread::Builder::new()
+        .name("tracing-prometheus".into())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = handle_connection(&exporter, stream);
+            }
+        })?;
+    Ok(Server { addr })
+}
+
+/// Reads one HTTP request from `stream` and responds with the exposition.
+fn handle_connection(exporter: &Exporter, stream: impl io::Read + Write) -> io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    // Consume the request head; the response is the same for any request.
+    let mut line = String::new();
+    while stream.read_line(&mut line)? != 0 {
+        if line == "\r\n" || line == "\n" {
+            break;
+        }
+        line.clear();
+    }
+    let body = exporter.render();
+    let response = format!(
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: text/plain; version=0.0.4; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\
+         \r\n\
+         {}",
+        body.len(),
+        body
+    );
+    stream.get_mut().write_all(response.as_bytes())
+}
+
+/// Renders the aggregates behind a [`metrics::Handle`] into `out`.
+fn render_metrics(handle: &metrics::Handle, out: &mut String) {
+    let mut counters = handle.counters();
+    counters.sort_by(|a, b| (a.name(), a.labels()).cmp(&(b.name(), b.labels())));
+    for counter in counters {
+        let name = sanitize_name(counter.name());
+        let _ = writeln!(out, "# TYPE {} counter", name);
+        let _ = writeln!(
+            out,
+            "{}{} {}",
+            name,
+            render_labels(counter.labels()),
+            counter.value()
+        );
+    }
+
+    let mut gauges = handle.gauges();
+    gauges.sort_by(|a, b| (a.name(), a.labels()).cmp(&(b.name(), b.labels())));
+    for gauge in gauges {
+        let name = sanitize_name(gauge.name());
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        let _ = writeln!(
+            out,
+            "{}{} {}",
+            name,
+            render_labels(gauge.labels()),
+            gauge.value()
+        );
+    }
+
+    let mut histograms = handle.histograms();
+    histograms.sort_by(|a, b| (a.name(), a.labels()).cmp(&(b.name(), b.labels())));
+    for histogram in histograms {
+        let name = sanitize_name(histogram.name());
+        let _ = writeln!(out, "# TYPE {} summary", name);
+        let labels = histogram
+            .labels()
+            .iter()
+            .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+            .collect::<Vec<_>>()
+            .join(",");
+        let quantile = |q: f64| histogram.percentile(q * 100.0) as f64;
+        render_quantiles(
+            out,
+            &name,
+            &labels,
+            quantile,
+            histogram.count(),
+            histogram.sum() as f64,
+        );
+    }
+}
+
+/// Renders one summary series scaled by `scale` (e.g. nanoseconds to
+/// seconds).
+fn render_summary(out: &mut String, name: &str, labels: &str, histogram: &Histogram, scale: f64) {
+    let quantile = |q: f64| histogram.percentile(q * 100.0) as f64 * scale;
+    render_quantiles(
+        out,
+        name,
+        labels,
+        quantile,
+        histogram.count,
+        histogram.sum as f64 * scale,
+    );
+}
+
+fn render_quantiles(
+    out: &mut String,
+    name: &str,
+    labels: &str,
+    quantile: impl Fn(f64) -> f64,
+    count: u64,
+    sum: f64,
+) {
+    let separator = if labels.is_empty() { "" } else { "," };
+    for q in [0.5, 0.9, 0.99] {
+        let _ = writeln!(
+            out,
+            "{}{{{}{}quantile=\"{}\"}} {}",
+            name,
+            labels,
+            separator,
+            q,
+            quantile(q)
+        );
+    }
+    let labels = if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels)
+    };
+    let _ = writeln!(out, "{}_sum{} {}", name, labels, sum);
+    let _ = writeln!(out, "{}_count{} {}", name, labels, count);
+}
+
+/// Renders a `{key="value",...}` label set, or nothing if there are no
+/// labels.
+fn render_labels(labels: &[(&'static str, String)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels = labels
+        .iter()
+        .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", labels)
+}
+
+/// Replaces characters that are invalid in Prometheus metric names.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Escapes a label value per the exposition format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Returns the lowercase label for a level.
+fn level_label(level: &Level) -> &'static str {
+    match *level {
+        Level::TRACE => "trace",
+        Level::DEBUG => "debug",
+        Level::INFO => "info",
+        Level::WARN => "warn",
+        Level::ERROR => "error",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn stats_render_counts_and_durations() {
+        let (stats, handle) = Stats::new();
+        let collector = crate::registry().with(stats);
+
+        with_default(collector, || {
+            tracing::info!("one");
+            tracing::info!("two");
+            tracing::warn!("three");
+            let span = tracing::info_span!("my_span");
+            let _entered = span.enter();
+        });
+
+        let rendered = Exporter::new().with_stats(handle).render();
+        let expected_events = format!(
+            "tracing_events_total{{level=\"info\",target=\"{}\"}} 2",
+            module_path!()
+        );
+        assert!(rendered.contains(&expected_events), "{}", rendered);
+        assert!(rendered.contains("level=\"warn\""), "{}", rendered);
+        let expected_spans = format!(
+            "tracing_spans_total{{level=\"info\",target=\"{}\"}} 1",
+            module_path!()
+        );
+        assert!(rendered.contains(&expected_spans), "{}", rendered);
+        let expected_duration = format!(
+            "tracing_span_duration_seconds_count{{name=\"my_span\",target=\"{}\"}} 1",
+            module_path!()
+        );
+        assert!(rendered.contains(&expected_duration), "{}", rendered);
+    }
+
+    #[test]
+    fn metrics_render_as_exposition() {
+        let (metrics, handle) = metrics::Subscriber::new();
+        let collector = crate::registry().with(metrics.with_span_label("method"));
+
+        with_default(collector, || {
+            let span = tracing::info_span!("request", method = "GET");
+            let _entered = span.enter();
+            tracing::info!(counter.requests = 3, gauge.depth = 1.5, "handled");
+        });
+
+        let rendered = Exporter::new().with_metrics(handle).render();
+        assert!(
+            rendered.contains("# TYPE requests counter\nrequests{method=\"GET\"} 3\n"),
+            "{}",
+            rendered
+        );
+        assert!(
+            rendered.contains("# TYPE depth gauge\ndepth{method=\"GET\"} 1.5\n"),
+            "{}",
+            rendered
+        );
+    }
+
+    #[test]
+    fn serves_the_exposition_over_http() {
+        let (stats, handle) = Stats::new();
+        let collector = crate::registry().with(stats);
+        with_default(collector, || tracing::info!("scraped"));
+
+        let exporter = Exporter::new().with_stats(handle);
+        let server = serve_tcp(exporter, "127.0.0.1:0").expect("failed to bind");
+        let addr = server.local_addr().expect("TCP listener has an address");
+
+        let mut stream = std::net::TcpStream::connect(addr).expect("failed to connect");
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .expect("failed to write request");
+        let mut response = String::new();
+        io::Read::read_to_string(&mut stream, &mut response).expect("failed to read response");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains("tracing_events_total"), "{}", response);
+    }
+}
@@ -6,6 +6,8 @@ use std::{
     collections::HashMap,
     fmt,
     hash::{BuildHasherDefault, Hasher},
+    mem::{self, MaybeUninit},
+    ptr,
 };
 
 #[allow(warnings)]
@@ -110,13 +112,121 @@ impl<'a> ExtensionsMut<'a> {
     }
 }
 
+/// The number of inline extension slots in each span's extensions.
+///
+/// Two slots cover the common case of a `fmt` subscriber's formatted fields
+/// plus one user-defined extension; additional small extensions spill to the
+/// heap-allocated map.
+const INLINE_SLOTS: usize = 2;
+
+/// The backing storage of an inline extension slot.
+///
+/// Sized to hold a `fmt` subscriber's `FormattedFields` (a `String` plus a
+/// flag), which is stored in the extensions of every span that subscriber
+/// records.
+type InlineBuf = [usize; 4];
+
+/// Returns `true` if a value of type `T` fits in an inline extension slot.
+///
+/// This depends only on `T`'s layout, so a given type is *always* stored
+/// inline or *always* boxed, regardless of insertion order.
+fn inline_eligible<T>() -> bool {
+    mem::size_of::<T>() <= mem::size_of::<InlineBuf>()
+        && mem::align_of::<T>() <= mem::align_of::<InlineBuf>()
+}
+
+/// An extension value stored inline, without a heap allocation.
+///
+/// # Invariants
+///
+/// `data` always holds a validly initialized value of the type identified by
+/// `type_id`, and `drop_fn` is that type's drop glue. The value is only
+/// written through [`InlineSlot::new`], which checks [`inline_eligible`], so
+/// it is always in bounds and sufficiently aligned.
+struct InlineSlot {
+    type_id: TypeId,
+    drop_fn: unsafe fn(*mut InlineBuf),
+    data: MaybeUninit<InlineBuf>,
+}
+
+impl InlineSlot {
+    fn new<T: Send + Sync + 'static>(val: T) -> Self {
+        debug_assert!(inline_eligible::<T>());
+        let mut data = MaybeUninit::<InlineBuf>::uninit();
+        // Safety: `inline_eligible` guarantees that `T` fits in (and is no
+        // more aligned than) `InlineBuf`.
+        unsafe { ptr::write(data.as_mut_ptr().cast::<T>(), val) };
+        Self {
+            type_id: TypeId::of::<T>(),
+            drop_fn: drop_inline::<T>,
+            data,
+        }
+    }
+
+    /// Returns a reference to the stored value, if it is a `T`.
+    fn get<T: 'static>(&self) -> Option<&T> {
+        if self.type_id != TypeId::of::<T>() {
+            return None;
+        }
+        // Safety: the slot invariant guarantees that `data` holds a valid
+        // `T` when the `TypeId`s match.
+        Some(unsafe { &*self.data.as_ptr().cast::<T>() })
+    }
+
+    /// Returns a mutable reference to the stored value, if it is a `T`.
+    fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        if self.type_id != TypeId::of::<T>() {
+            return None;
+        }
+        // Safety: as in `get`.
+        Some(unsafe { &mut *self.data.as_mut_ptr().cast::<T>() })
+    }
+
+    /// Consumes the slot, returning the stored `T`.
+    ///
+    /// The caller must have checked that the slot's `type_id` is `T`'s.
+    fn into_inner<T: 'static>(self) -> T {
+        debug_assert_eq!(self.type_id, TypeId::of::<T>());
+        // Safety: the slot invariant guarantees that `data` holds a valid
+        // `T`; forgetting `self` ensures its drop glue does not run again.
+        let val = unsafe { ptr::read(self.data.as_ptr().cast::<T>()) };
+        mem::forget(self);
+        val
+    }
+}
+
+impl Drop for InlineSlot {
+    fn drop(&mut self) {
+        // Safety: the slot invariant guarantees that `data` holds a valid
+        // value whose drop glue is `drop_fn`.
+        unsafe { (self.drop_fn)(self.data.as_mut_ptr()) }
+    }
+}
+
+unsafe fn drop_inline<T>(data: *mut InlineBuf) {
+    ptr::drop_in_place(data.cast::<T>());
+}
+
+impl fmt::Debug for InlineSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InlineSlot")
+            .field("type_id", &self.type_id)
+            .finish()
+    }
+}
+
 /// A type map of span extensions.
 ///
 /// [ExtensionsInner] is used by [Data] to store and
 /// span-specific data. A given [Subscriber] can read and write
 /// data that it is interested in recording and emitting.
+///
+/// Values small enough for an [`InlineSlot`] are stored inline, avoiding a
+/// heap allocation per extension on the hot path; larger values, or small
+/// values inserted once all inline slots are occupied, are boxed in the map.
 #[derive(Default)]
 pub(crate) struct ExtensionsInner {
+    inline: [Option<InlineSlot>; INLINE_SLOTS],
     map: AnyMap,
 }
 
@@ -126,6 +236,7 @@ impl ExtensionsInner {
     #[inline]
     pub(crate) fn new() -> ExtensionsInner {
         ExtensionsInner {
+            inline: Default::default(),
             map: AnyMap::default(),
         }
     }
@@ -135,6 +246,32 @@ impl ExtensionsInner {
     /// If a extension of this type already existed, it will
     /// be returned.
     pub(crate) fn insert<T: Send + Sync + 'static>(&mut self, val: T) -> Option<T> {
+        if !inline_eligible::<T>() {
+            return self.insert_boxed(val);
+        }
+        let type_id = TypeId::of::<T>();
+        // If an inline slot already holds a `T`, replace it in place.
+        for slot in self.inline.iter_mut().flatten() {
+            if slot.type_id == type_id {
+                let prev = mem::replace(slot, InlineSlot::new(val));
+                return Some(prev.into_inner());
+            }
+        }
+        // A previous `T` may have spilled to the map if the inline slots
+        // were full when it was inserted.
+        let prev = self.remove_boxed::<T>();
+        for slot in self.inline.iter_mut() {
+            if slot.is_none() {
+                *slot = Some(InlineSlot::new(val));
+                return prev;
+            }
+        }
+        // All inline slots are occupied by other types; spill to the heap.
+        self.insert_boxed(val);
+        prev
+    }
+
+    fn insert_boxed<T: Send + Sync + 'static>(&mut self, val: T) -> Option<T> {
         self.map
             .insert(TypeId::of::<T>(), Box::new(val))
             .and_then(|boxed| {
@@ -150,6 +287,13 @@ impl ExtensionsInner {
 
     /// Get a reference to a type previously inserted on this `Extensions`.
     pub(crate) fn get<T: 'static>(&self) -> Option<&T> {
+        if inline_eligible::<T>() {
+            for slot in self.inline.iter().flatten() {
+                if let Some(val) = slot.get::<T>() {
+                    return Some(val);
+                }
+            }
+        }
         self.map
             .get(&TypeId::of::<T>())
             .and_then(|boxed| (&**boxed as &(dyn Any + 'static)).downcast_ref())
@@ -157,6 +301,13 @@ impl ExtensionsInner {
 
     /// Get a mutable reference to a type previously inserted on this `Extensions`.
     pub(crate) fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        if inline_eligible::<T>() {
+            for slot in self.inline.iter_mut().flatten() {
+                if slot.type_id == TypeId::of::<T>() {
+                    return slot.get_mut::<T>();
+                }
+            }
+        }
         self.map
             .get_mut(&TypeId::of::<T>())
             .and_then(|boxed| (&mut **boxed as &mut (dyn Any + 'static)).downcast_mut())
@@ -166,6 +317,18 @@ impl ExtensionsInner {
     ///
     /// If a extension of this type existed, it will be returned.
     pub(crate) fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        if inline_eligible::<T>() {
+            let type_id = TypeId::of::<T>();
+            for slot in self.inline.iter_mut() {
+                if matches!(slot, Some(slot) if slot.type_id == type_id) {
+                    return slot.take().map(InlineSlot::into_inner);
+                }
+            }
+        }
+        self.remove_boxed()
+    }
+
+    fn remove_boxed<T: Send + Sync + 'static>(&mut self) -> Option<T> {
         self.map.remove(&TypeId::of::<T>()).and_then(|boxed| {
             #[allow(warnings)]
             {
@@ -184,6 +347,9 @@ impl ExtensionsInner {
     /// that future spans will not need to allocate new hashmaps.
     #[cfg(any(test, feature = "registry"))]
     pub(crate) fn clear(&mut self) {
+        for slot in self.inline.iter_mut() {
+            *slot = None;
+        }
         self.map.clear();
     }
 }
@@ -191,6 +357,10 @@ impl ExtensionsInner {
 impl fmt::Debug for ExtensionsInner {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Extensions")
+            .field(
+                "inline",
+                &self.inline.iter().filter(|slot| slot.is_some()).count(),
+            )
             .field("len", &self.map.len())
             .field("capacity", &self.map.capacity())
             .finish()
@@ -221,12 +391,21 @@ mod tests {
         assert_eq!(extensions.get(), Some(&MyType(10)));
     }
 
+    #[derive(Debug, PartialEq)]
+    struct BigA([usize; 8]);
+
+    #[derive(Debug, PartialEq)]
+    struct BigB([usize; 8]);
+
+    #[derive(Debug, PartialEq)]
+    struct BigC([usize; 8]);
+
     #[test]
     fn clear_retains_capacity() {
         let mut extensions = ExtensionsInner::new();
-        extensions.insert(5i32);
-        extensions.insert(MyType(10));
-        extensions.insert(true);
+        extensions.insert(BigA([5; 8]));
+        extensions.insert(BigB([10; 8]));
+        extensions.insert(BigC([0; 8]));
 
         assert_eq!(extensions.map.len(), 3);
         let prev_capacity = extensions.map.capacity();
@@ -244,15 +423,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn small_values_are_stored_inline() {
+        let mut extensions = ExtensionsInner::new();
+
+        extensions.insert(5i32);
+        extensions.insert(MyType(10));
+
+        assert_eq!(extensions.map.len(), 0, "small values should not be boxed");
+        assert_eq!(extensions.get(), Some(&5i32));
+        assert_eq!(extensions.get_mut(), Some(&mut MyType(10)));
+        assert_eq!(extensions.insert(7i32), Some(5i32));
+        assert_eq!(extensions.remove::<i32>(), Some(7i32));
+        assert!(extensions.get::<i32>().is_none());
+        assert_eq!(extensions.get(), Some(&MyType(10)));
+    }
+
+    #[test]
+    fn inline_slots_spill_to_the_heap() {
+        let mut extensions = ExtensionsInner::new();
+
+        extensions.insert(5i32);
+        extensions.insert(MyType(10));
+        // All inline slots are now occupied, so this spills to the map.
+        extensions.insert(true);
+
+        assert_eq!(extensions.map.len(), 1);
+        assert_eq!(extensions.get(), Some(&true));
+        assert_eq!(extensions.insert(false), Some(true));
+
+        // Removing an inline value frees its slot for the next insertion.
+        assert_eq!(extensions.remove::<i32>(), Some(5i32));
+        assert_eq!(extensions.insert(false), Some(false));
+        assert_eq!(extensions.map.len(), 0, "freed slot should be reused");
+        assert_eq!(extensions.get(), Some(&false));
+    }
+
     #[test]
     fn clear_drops_elements() {
         use std::sync::Arc;
         struct DropMePlease(Arc<()>);
-        struct DropMeTooPlease(Arc<()>);
+        // The padding pushes this type past the inline slot size, so that
+        // clearing both inline and boxed values is covered.
+        struct DropMeTooPlease(Arc<()>, #[allow(dead_code)] [usize; 8]);
 
         let mut extensions = ExtensionsInner::new();
         let val1 = DropMePlease(Arc::new(()));
-        let val2 = DropMeTooPlease(Arc::new(()));
+        let val2 = DropMeTooPlease(Arc::new(()), [0; 8]);
 
         let val1_dropped = Arc::downgrade(&val1.0);
         let val2_dropped = Arc::downgrade(&val2.0);
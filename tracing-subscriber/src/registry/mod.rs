@@ -76,6 +76,7 @@ feature! {
     mod sharded;
     mod stack;
 
+    pub use sharded::CapPolicy;
     pub use sharded::Data;
     pub use sharded::Registry;
 
@@ -201,6 +202,21 @@ pub trait SpanData<'a> {
         let _ = filter;
         true
     }
+
+    /// Returns the IDs of this span's direct children, in the order they
+    /// were created.
+    ///
+    /// ## Default Implementation
+    ///
+    /// By default, this method assumes that the [`LookupSpan`] implementation
+    /// does not track child spans, and always returns an empty `Vec`. The
+    /// [`Registry`] tracks child spans when the "span-children" feature flag
+    /// is enabled.
+    #[cfg(all(feature = "span-children", feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "span-children")))]
+    fn children(&self) -> Vec<Id> {
+        Vec::new()
+    }
 }
 
 /// A reference to [span data] and the associated [registry].
@@ -405,6 +421,90 @@ where
     }
 }
 
+feature! {
+    #![all(feature = "span-children", feature = "std")]
+
+    use std::collections::VecDeque;
+
+    /// An iterator over a span's direct children.
+    ///
+    /// This is returned by the [`SpanRef::children`] method.
+    #[derive(Debug)]
+    pub struct Children<'a, R> {
+        registry: &'a R,
+        ids: VecDeque<Id>,
+        filter: FilterId,
+    }
+
+    /// An iterator over all of a span's descendants, in depth-first order.
+    ///
+    /// This is returned by the [`SpanRef::descendants`] method.
+    #[derive(Debug)]
+    pub struct Descendants<'a, R> {
+        registry: &'a R,
+        stack: Vec<Id>,
+        filter: FilterId,
+    }
+
+    impl<'a, R> Iterator for Children<'a, R>
+    where
+        R: LookupSpan<'a>,
+    {
+        type Item = SpanRef<'a, R>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                let id = self.ids.pop_front()?;
+                let span = match self.registry.span(&id) {
+                    Some(span) => span,
+                    None => continue,
+                };
+
+                // If the child span is disabled for this subscriber's filter,
+                // it is invisible to the subscriber, and the disabled span's
+                // own children appear as direct children in its place.
+                if !span.is_enabled_for(self.filter) {
+                    self.ids.extend(span.data.children());
+                    continue;
+                }
+
+                return Some(span.with_filter(self.filter));
+            }
+        }
+    }
+
+    impl<'a, R> Iterator for Descendants<'a, R>
+    where
+        R: LookupSpan<'a>,
+    {
+        type Item = SpanRef<'a, R>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                let id = self.stack.pop()?;
+                let span = match self.registry.span(&id) {
+                    Some(span) => span,
+                    None => continue,
+                };
+
+                // Visit this span's children next, in creation order.
+                let mut children = span.data.children();
+                children.reverse();
+                self.stack.extend(children);
+
+                // As with `Children`, spans disabled for this subscriber's
+                // filter are skipped, but their descendants are still
+                // visited.
+                if !span.is_enabled_for(self.filter) {
+                    continue;
+                }
+
+                return Some(span.with_filter(self.filter));
+            }
+        }
+    }
+}
+
 impl<'a, R> SpanRef<'a, R>
 where
     R: LookupSpan<'a>,
@@ -540,6 +640,20 @@ where
     /// If the opposite order (from the root to this span) is desired, calling [`Scope::from_root`] on
     /// the returned iterator reverses the order.
     ///
+    /// # Per-subscriber filtering
+    ///
+    /// When a subscriber is wrapped in a [per-subscriber filter], the
+    /// `SpanRef`s it obtains through its [`Context`] carry that subscriber's
+    /// filter, and the scopes they return are filtered accordingly: spans
+    /// that the subscriber's filter disabled are skipped, so the subscriber
+    /// never observes ancestors it was not told about. Different subscribers
+    /// looking up the scope of the same span may therefore see different
+    /// scopes, each consistent with the spans that subscriber was notified
+    /// of.
+    ///
+    /// [per-subscriber filter]: crate::subscribe#per-subscriber-filtering
+    /// [`Context`]: crate::subscribe::Context
+    ///
     /// ```rust
     /// # use tracing::{span, Collect};
     /// # use tracing_subscriber::{
@@ -598,6 +712,67 @@ where
         self.data.extensions_mut()
     }
 
+    /// Returns the [`Timings`] recorded for this span, or `None` if no
+    /// [`SpanTimer`] is present in the subscriber stack.
+    ///
+    /// The returned value is a snapshot; timings recorded after this method
+    /// returns are not reflected in it.
+    ///
+    /// [`Timings`]: crate::timing::Timings
+    /// [`SpanTimer`]: crate::timing::SpanTimer
+    #[cfg(all(feature = "timing", feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "timing")))]
+    pub fn timings(&self) -> Option<crate::timing::Timings> {
+        self.extensions().get::<crate::timing::Timings>().cloned()
+    }
+
+    /// Returns an iterator over this span's direct children, in the order
+    /// they were created.
+    ///
+    /// Only children that are currently open (or still referenced by an open
+    /// descendant) are returned; the registry unlinks a child from its parent
+    /// when the child is closed and removed.
+    ///
+    /// # Per-subscriber filtering
+    ///
+    /// As with [`scope`], spans disabled by this subscriber's
+    /// [per-subscriber filter] are skipped; the enabled children of a
+    /// disabled span appear as direct children in its place.
+    ///
+    /// [`scope`]: Self::scope
+    /// [per-subscriber filter]: crate::subscribe#per-subscriber-filtering
+    #[cfg(all(feature = "span-children", feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "span-children")))]
+    pub fn children(&self) -> Children<'a, R> {
+        Children {
+            registry: self.registry,
+            ids: self.data.children().into(),
+            filter: self.filter,
+        }
+    }
+
+    /// Returns an iterator over all of this span's descendants, in
+    /// depth-first order, not including this span itself.
+    ///
+    /// This allows a subscriber to dump everything under a given span — for
+    /// example, all the work performed beneath a request span — without
+    /// maintaining its own child index. Spans disabled by this subscriber's
+    /// [per-subscriber filter] are skipped, but their descendants are still
+    /// visited.
+    ///
+    /// [per-subscriber filter]: crate::subscribe#per-subscriber-filtering
+    #[cfg(all(feature = "span-children", feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "span-children")))]
+    pub fn descendants(&self) -> Descendants<'a, R> {
+        let mut stack = self.data.children();
+        stack.reverse();
+        Descendants {
+            registry: self.registry,
+            stack,
+            filter: self.filter,
+        }
+    }
+
     #[cfg(all(feature = "registry", feature = "std"))]
     pub(crate) fn try_with_filter(self, filter: FilterId) -> Option<Self> {
         if self.is_enabled_for(filter) {
@@ -12,7 +12,9 @@ use crate::{
 };
 use std::{
     cell::{self, Cell, RefCell},
-    sync::atomic::{fence, AtomicUsize, Ordering},
+    collections::VecDeque,
+    sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering},
+    sync::Mutex,
 };
 use tracing_core::{
     dispatch::{self, Dispatch},
@@ -37,6 +39,38 @@ use tracing_core::{
 /// This registry is implemented using a [lock-free sharded slab][slab], and is
 /// highly optimized for concurrent access.
 ///
+/// # Concurrency
+///
+/// Span storage is sharded: each thread inserts new spans into its own shard
+/// of the slab, so creating spans on different threads never contends on a
+/// shared lock or allocator. Slots for closed spans are not freed eagerly;
+/// once every reference to a span has been dropped, its slot is cleared in
+/// place and lazily reused by a future span, so closing spans does not
+/// require synchronizing with concurrent readers (readers holding a
+/// [`SpanRef`] keep the slot alive via its reference count). The stack of
+/// spans currently entered is tracked per-thread, with no shared state at
+/// all.
+///
+/// The only locks in the registry are the per-span [extensions] maps. Each
+/// lock is scoped to a single span, so it is only ever contended when
+/// multiple threads access the *same* span's extensions concurrently;
+/// reading and writing extensions of different spans proceeds in parallel.
+/// The `parking_lot` feature flag replaces the standard library's `RwLock`
+/// with the [`parking_lot`] crate's implementation, which may further reduce
+/// the cost of these uncontended locks.
+///
+/// [`SpanRef`]: crate::registry::SpanRef
+/// [`parking_lot`]: https://crates.io/crates/parking_lot
+///
+/// # Bounded Registries
+///
+/// By default, the registry stores as many live spans as the program
+/// creates, so a span leak (such as an entered guard that is never dropped)
+/// grows its memory use without bound. A registry constructed with
+/// [`Registry::bounded`] enforces a maximum number of live spans, applying a
+/// [`CapPolicy`] when the limit is reached; the current number of live spans
+/// can be read with [`Registry::live_spans`].
+///
 /// # Span ID Generation
 ///
 /// Span IDs are not globally unique, but the registry ensures that
@@ -93,6 +127,66 @@ pub struct Registry {
     spans: Pool<DataInner>,
     current_spans: ThreadLocal<RefCell<SpanStack>>,
     next_filter_id: u8,
+    /// The number of spans that are currently live (created but not yet
+    /// closed or evicted). This is only maintained when the registry is
+    /// bounded, so that unbounded registries pay no per-span accounting cost.
+    live_spans: AtomicUsize,
+    /// The maximum number of live spans, if this registry is bounded.
+    max_spans: Option<usize>,
+    /// What to do when a new span would exceed `max_spans`.
+    cap_policy: CapPolicy,
+    /// The IDs of live spans in creation order, used to find the oldest span
+    /// to evict. Each entry records the span's [generation], so that entries
+    /// whose span has since closed (and whose slot may have been reused) can
+    /// be recognized as stale. This is only maintained when the registry's
+    /// policy is [`CapPolicy::EvictOldest`].
+    ///
+    /// [generation]: DataInner::generation
+    eviction_queue: Mutex<VecDeque<(Id, u64)>>,
+    /// The approximate number of stale entries in `eviction_queue`.
+    ///
+    /// Closing a span leaves its queue entry in place as a tombstone rather
+    /// than scanning the queue for it; once enough tombstones accumulate, the
+    /// queue is compacted in a single pass.
+    queue_tombstones: AtomicUsize,
+    /// A handle to the dispatcher this registry is part of, used to emit
+    /// eviction diagnostics.
+    ///
+    /// Span creation happens inside a `dispatch::get_default` closure, where
+    /// nested `get_default` calls see `Dispatch::none` rather than the
+    /// current dispatcher; the dispatcher is instead captured when a span is
+    /// entered, which dispatches to the registry directly.
+    eviction_dispatch: Mutex<Option<dispatch::WeakDispatch>>,
+    /// Whether `eviction_dispatch` has been captured yet.
+    dispatch_captured: AtomicBool,
+}
+
+/// A policy determining how a [bounded] [`Registry`] behaves when creating a
+/// new span would exceed its live-span limit.
+///
+/// [bounded]: Registry::bounded
+#[cfg(feature = "registry")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "registry", feature = "std"))))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CapPolicy {
+    /// New spans are refused while the registry is at its limit.
+    ///
+    /// The registry reports span callsites as disabled, so refused spans are
+    /// never created: no subscriber is notified of them, and events inside
+    /// them are attributed to the nearest enclosing live span.
+    RefuseNew,
+
+    /// The oldest live span is evicted to make room for a new one.
+    ///
+    /// Evicting a span clears its [extensions] and removes it from the
+    /// live-span count, and emits a `WARN`-level diagnostic event
+    /// (`registry_span_evicted`). The span's metadata remains accessible
+    /// until all handles to it are dropped, so subscribers observing the
+    /// span's close will still find it in the registry.
+    ///
+    /// [extensions]: super::Extensions
+    EvictOldest,
 }
 
 /// Span data stored in a [`Registry`].
@@ -128,6 +222,19 @@ struct DataInner {
     // The span's `Extensions` typemap. Allocations for the `HashMap` backing
     // this are pooled and reused in place.
     pub(crate) extensions: RwLock<ExtensionsInner>,
+    // The IDs of this span's direct children, in creation order. Like the
+    // extensions map, the `Vec`'s allocation is pooled and reused in place.
+    #[cfg(feature = "span-children")]
+    children: RwLock<Vec<Id>>,
+    // Whether a bounded registry has evicted this span. This ensures the
+    // span is only removed from the live-span count once, whether it is
+    // evicted, closed, or both.
+    evicted: AtomicBool,
+    // The number of spans that have occupied this slot, incremented each time
+    // the slot is checked out for a new span. Eviction-queue entries record
+    // the generation they were pushed with, so an entry whose slot has been
+    // cleared and reused can be recognized as stale.
+    generation: u64,
 }
 
 // === impl Registry ===
@@ -138,6 +245,13 @@ impl Default for Registry {
             spans: Pool::new(),
             current_spans: ThreadLocal::new(),
             next_filter_id: 0,
+            live_spans: AtomicUsize::new(0),
+            max_spans: None,
+            cap_policy: CapPolicy::RefuseNew,
+            eviction_queue: Mutex::new(VecDeque::new()),
+            queue_tombstones: AtomicUsize::new(0),
+            eviction_dispatch: Mutex::new(None),
+            dispatch_captured: AtomicBool::new(false),
         }
     }
 }
@@ -180,10 +294,176 @@ pub(crate) struct CloseGuard<'a> {
 }
 
 impl Registry {
+    /// Returns a new registry that stores at most `max_spans` live spans,
+    /// refusing new spans while it is at the limit.
+    ///
+    /// A span leak — such as an [`Entered`] guard that is never dropped —
+    /// grows an unbounded registry without limit, eventually taking the
+    /// process down with it. A bounded registry trades completeness for
+    /// predictable memory use: once `max_spans` spans are live, it applies a
+    /// [`CapPolicy`] (by default, [refusing new spans]) until existing spans
+    /// close. The current number of live spans can be read with
+    /// [`live_spans`].
+    ///
+    /// Note that the limit is enforced on a best-effort basis: spans created
+    /// concurrently on multiple threads may briefly overshoot it.
+    ///
+    /// Bounded registries check the limit for every created span, so span
+    /// callsites are no longer cached as always-enabled; this adds a small
+    /// per-span cost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tracing_subscriber::{prelude::*, registry::Registry};
+    ///
+    /// let collector = Registry::bounded(10_000)
+    ///     .with(tracing_subscriber::fmt::subscriber());
+    /// # let _ = collector;
+    /// ```
+    ///
+    /// [`Entered`]: https://docs.rs/tracing/latest/tracing/span/struct.Entered.html
+    /// [refusing new spans]: CapPolicy::RefuseNew
+    /// [`live_spans`]: Registry::live_spans
+    pub fn bounded(max_spans: usize) -> Self {
+        Self::bounded_with_policy(max_spans, CapPolicy::RefuseNew)
+    }
+
+    /// Returns a new registry that stores at most `max_spans` live spans,
+    /// applying the provided [`CapPolicy`] when a new span would exceed the
+    /// limit.
+    ///
+    /// See [`bounded`](Registry::bounded) for details.
+    pub fn bounded_with_policy(max_spans: usize, cap_policy: CapPolicy) -> Self {
+        Self {
+            max_spans: Some(max_spans),
+            cap_policy,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the number of spans that are currently live in this registry:
+    /// created, but not yet closed (or evicted, if this registry is
+    /// [bounded]).
+    ///
+    /// The count is only tracked by bounded registries; an unbounded registry
+    /// does no per-span accounting, and this method always returns 0 for one.
+    ///
+    /// [bounded]: Registry::bounded
+    pub fn live_spans(&self) -> usize {
+        self.live_spans.load(Ordering::Relaxed)
+    }
+
     fn get(&self, id: &Id) -> Option<Ref<'_, DataInner>> {
         self.spans.get(id_to_idx(id))
     }
 
+    /// Returns `true` if this registry is bounded and at its live-span
+    /// limit.
+    fn at_cap(&self) -> bool {
+        match self.max_spans {
+            Some(max) => self.live_spans.load(Ordering::Relaxed) >= max,
+            None => false,
+        }
+    }
+
+    /// Returns `true` if this registry evicts spans, and therefore maintains
+    /// the eviction queue.
+    fn evicts(&self) -> bool {
+        self.max_spans.is_some() && self.cap_policy == CapPolicy::EvictOldest
+    }
+
+    /// Evicts the oldest live span, clearing its extensions and removing it
+    /// from the live-span count.
+    ///
+    /// The span's slot is not cleared until all handles to it are dropped,
+    /// so its metadata remains accessible to subscribers in the meantime.
+    fn evict_oldest(&self) {
+        loop {
+            let (oldest, generation) = match self
+                .eviction_queue
+                .lock()
+                .expect("eviction queue poisoned")
+                .pop_front()
+            {
+                Some(oldest) => oldest,
+                None => return,
+            };
+
+            if let Some(data) = self.get(&oldest) {
+                // A stale entry for a span that has closed may point at a new
+                // span reusing its slot; the generation distinguishes them.
+                if data.generation != generation {
+                    continue;
+                }
+                if !data.evicted.swap(true, Ordering::AcqRel) {
+                    data.extensions.write().expect("Mutex poisoned").clear();
+                    self.live_spans.fetch_sub(1, Ordering::Relaxed);
+                    let dispatch = self
+                        .eviction_dispatch
+                        .lock()
+                        .expect("eviction dispatch poisoned")
+                        .as_ref()
+                        .and_then(dispatch::WeakDispatch::upgrade);
+                    match dispatch {
+                        Some(dispatch) => dispatch_eviction_event(&dispatch, data.metadata.name()),
+                        // The dispatcher has not been captured yet; this
+                        // works if a global default is set, and is otherwise
+                        // a no-op.
+                        None => dispatch::get_default(|dispatch| {
+                            dispatch_eviction_event(dispatch, data.metadata.name())
+                        }),
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Removes stale entries — spans that have closed or been evicted — from
+    /// the eviction queue in a single pass.
+    ///
+    /// This is called from the close path once enough tombstones have
+    /// accumulated, so the cost of the scan is amortized over the closes that
+    /// produced them.
+    fn compact_eviction_queue(&self) {
+        // This can be called while panicking, so ignore lock poisoning.
+        let mut queue = self
+            .eviction_queue
+            .lock()
+            .unwrap_or_else(|l| l.into_inner());
+        queue.retain(|(id, generation)| {
+            self.get(id)
+                .map(|data| {
+                    data.generation == *generation && !data.evicted.load(Ordering::Acquire)
+                })
+                .unwrap_or(false)
+        });
+        // Closes racing with the compaction may be under- or over-counted
+        // here; that only shifts when the next compaction is triggered.
+        self.queue_tombstones.store(0, Ordering::Relaxed);
+    }
+
+    /// Captures a weak handle to the current dispatcher, so that eviction
+    /// diagnostics can be emitted through the whole subscriber stack.
+    fn capture_dispatch(&self) {
+        dispatch::get_default(|dispatch| {
+            // If no dispatcher is actually set, don't capture the no-op
+            // dispatcher; a later call may see a real one.
+            if dispatch
+                .downcast_ref::<tracing_core::collect::NoCollector>()
+                .is_some()
+            {
+                return;
+            }
+            *self
+                .eviction_dispatch
+                .lock()
+                .expect("eviction dispatch poisoned") = Some(dispatch.downgrade());
+            self.dispatch_captured.store(true, Ordering::Release);
+        });
+    }
+
     /// Returns a guard which tracks how many `Subscriber`s have
     /// processed an `on_close` notification via the `CLOSE_COUNT` thread-local.
     /// For additional details, see [`CloseGuard`].
@@ -207,6 +487,86 @@ impl Registry {
     pub(crate) fn span_stack(&self) -> cell::Ref<'_, SpanStack> {
         self.current_spans.get_or_default().borrow()
     }
+
+    /// Records `child` as a child of the span with the ID `parent`.
+    #[cfg(feature = "span-children")]
+    fn add_child(&self, parent: &Id, child: &Id) {
+        if let Some(parent) = self.get(parent) {
+            parent
+                .children
+                .write()
+                .expect("Mutex poisoned")
+                .push(child.clone());
+        }
+    }
+
+    /// Removes the span with the ID `id` from its parent's child list, if it
+    /// has a parent.
+    ///
+    /// This is called when a span is removed from the registry, so that its
+    /// ID is not reused by a later span while still linked as a child of the
+    /// old span's parent.
+    #[cfg(feature = "span-children")]
+    fn remove_child(&self, id: &Id) {
+        let parent = match self.get(id).and_then(|data| data.parent.clone()) {
+            Some(parent) => parent,
+            None => return,
+        };
+        if let Some(parent) = self.get(&parent) {
+            parent
+                .children
+                .write()
+                // This function can be called while panicking, so ignore lock
+                // poisoning.
+                .unwrap_or_else(|l| l.into_inner())
+                .retain(|child| child != id);
+        }
+    }
+}
+
+/// Emits the `WARN`-level diagnostic event recorded when a bounded
+/// [`Registry`] evicts a span.
+fn dispatch_eviction_event(dispatch: &Dispatch, span_name: &str) {
+    use tracing_core::field::{self, Field};
+
+    struct EvictionCallsite;
+    impl tracing_core::callsite::Callsite for EvictionCallsite {
+        fn set_interest(&self, _: Interest) {}
+
+        fn metadata(&self) -> &Metadata<'_> {
+            &EVICTION_METADATA
+        }
+    }
+
+    static EVICTION_CALLSITE: EvictionCallsite = EvictionCallsite;
+    static EVICTION_METADATA: Metadata<'static> = tracing_core::metadata! {
+        name: "registry_span_evicted",
+        target: "tracing_subscriber::registry",
+        level: tracing_core::Level::WARN,
+        fields: &["message", "span.name"],
+        callsite: &EVICTION_CALLSITE,
+        kind: tracing_core::metadata::Kind::EVENT,
+    };
+
+    if !dispatch.enabled(&EVICTION_METADATA) {
+        return;
+    }
+
+    let fields = EVICTION_METADATA.fields();
+    let message = fields
+        .field("message")
+        .expect("eviction metadata has a message field");
+    let name_field = fields
+        .field("span.name")
+        .expect("eviction metadata has a span.name field");
+    let values: [(&Field, Option<&dyn field::Value>); 2] = [
+        (
+            &message,
+            Some(&"live span limit reached, evicting the oldest span"),
+        ),
+        (&name_field, Some(&span_name)),
+    ];
+    dispatch.event(&Event::new(&EVICTION_METADATA, &fields.value_set(&values)));
 }
 
 thread_local! {
@@ -218,7 +578,14 @@ thread_local! {
 }
 
 impl Collect for Registry {
-    fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if self.max_spans.is_some() && self.cap_policy == CapPolicy::RefuseNew && metadata.is_span()
+        {
+            // The live-span limit must be re-checked for every new span, so
+            // span callsites cannot be cached as always-enabled.
+            return Interest::sometimes();
+        }
+
         if self.has_per_subscriber_filters() {
             return FilterState::take_interest().unwrap_or_else(Interest::always);
         }
@@ -226,7 +593,11 @@ impl Collect for Registry {
         Interest::always()
     }
 
-    fn enabled(&self, _: &Metadata<'_>) -> bool {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        if metadata.is_span() && self.cap_policy == CapPolicy::RefuseNew && self.at_cap() {
+            return false;
+        }
+
         if self.has_per_subscriber_filters() {
             return FilterState::event_enabled();
         }
@@ -243,6 +614,14 @@ impl Collect for Registry {
             attrs.parent().map(|id| self.clone_span(id))
         };
 
+        #[cfg(feature = "span-children")]
+        let parent_id = parent.clone();
+
+        if self.at_cap() && self.cap_policy == CapPolicy::EvictOldest {
+            self.evict_oldest();
+        }
+
+        let mut generation = 0;
         let id = self
             .spans
             // Check out a `DataInner` entry from the pool for the new span. If
@@ -260,12 +639,34 @@ impl Collect for Registry {
                     }
                 }
 
+                data.generation = data.generation.wrapping_add(1);
+                generation = data.generation;
+
                 let refs = data.ref_count.get_mut();
                 debug_assert_eq!(*refs, 0);
                 *refs = 1;
             })
             .expect("Unable to allocate another span");
-        idx_to_id(id)
+        let id = idx_to_id(id);
+
+        // Live-span accounting is only needed to enforce a limit; leave
+        // unbounded registries free of it.
+        if self.max_spans.is_some() {
+            self.live_spans.fetch_add(1, Ordering::Relaxed);
+        }
+        if self.evicts() {
+            self.eviction_queue
+                .lock()
+                .expect("eviction queue poisoned")
+                .push_back((id.clone(), generation));
+        }
+
+        #[cfg(feature = "span-children")]
+        if let Some(parent) = parent_id {
+            self.add_child(&parent, &id);
+        }
+
+        id
     }
 
     /// This is intentionally not implemented, as recording fields
@@ -287,6 +688,10 @@ impl Collect for Registry {
     fn event(&self, _: &Event<'_>) {}
 
     fn enter(&self, id: &span::Id) {
+        if self.evicts() && !self.dispatch_captured.load(Ordering::Acquire) {
+            self.capture_dispatch();
+        }
+
         if self
             .current_spans
             .get_or_default()
@@ -404,6 +809,36 @@ impl Drop for CloseGuard<'_> {
             // `on_close` call. If the span is closing, it's okay to remove the
             // span.
             if c == 1 && self.is_closing {
+                // Live-span accounting is only maintained for bounded
+                // registries, keeping it off the close path otherwise.
+                if let Some(max_spans) = self.registry.max_spans {
+                    if let Some(data) = self.registry.get(&self.id) {
+                        // If a bounded registry already evicted this span, it
+                        // has also already been removed from the live-span
+                        // count.
+                        if !data.evicted.swap(true, Ordering::AcqRel) {
+                            self.registry.live_spans.fetch_sub(1, Ordering::Relaxed);
+                        }
+                    }
+                    if self.registry.evicts() {
+                        // The span's eviction-queue entry is left behind as a
+                        // tombstone — removing it here would scan the whole
+                        // queue on every close. Instead, compact the queue
+                        // once tombstones could make up half its entries.
+                        let tombstones = self
+                            .registry
+                            .queue_tombstones
+                            .fetch_add(1, Ordering::Relaxed)
+                            + 1;
+                        if tombstones >= max_spans {
+                            self.registry.compact_eviction_queue();
+                        }
+                    }
+                }
+                // Unlink the span from its parent's child list before its
+                // slot (and thus its ID) can be reused by a new span.
+                #[cfg(feature = "span-children")]
+                self.registry.remove_child(&self.id);
                 self.registry.spans.clear(id_to_idx(&self.id));
             }
         });
@@ -437,6 +872,11 @@ impl<'a> SpanData<'a> for Data<'a> {
     fn is_enabled_for(&self, filter: FilterId) -> bool {
         self.inner.filter_map.is_enabled(filter)
     }
+
+    #[cfg(feature = "span-children")]
+    fn children(&self) -> Vec<Id> {
+        self.inner.children.read().expect("Mutex poisoned").clone()
+    }
 }
 
 // === impl DataInner ===
@@ -482,6 +922,10 @@ impl Default for DataInner {
             parent: None,
             ref_count: AtomicUsize::new(0),
             extensions: RwLock::new(ExtensionsInner::new()),
+            #[cfg(feature = "span-children")]
+            children: RwLock::new(Vec::new()),
+            evicted: AtomicBool::new(false),
+            generation: 0,
         }
     }
 }
@@ -522,7 +966,17 @@ impl Clear for DataInner {
             })
             .clear();
 
+        // Clear (but do not deallocate) the pooled child list, as well.
+        #[cfg(feature = "span-children")]
+        self.children
+            .get_mut()
+            .unwrap_or_else(|l| l.into_inner())
+            .clear();
+
         self.filter_map = FilterMap::new();
+        *self.evicted.get_mut() = false;
+        // `self.generation` is deliberately *not* reset: it must survive the
+        // slot's reuse so that stale eviction-queue entries can be detected.
     }
 }
 
@@ -898,4 +1352,87 @@ mod tests {
             state.assert_closed_in_order(["child", "parent", "grandparent"]);
         });
     }
+
+    fn live_spans() -> usize {
+        dispatch::get_default(|dispatch| {
+            dispatch
+                .downcast_ref::<Registry>()
+                .expect("collector should be registry-backed")
+                .live_spans()
+        })
+    }
+
+    #[test]
+    fn bounded_registry_refuses_new_spans_at_cap() {
+        let subscriber = crate::subscribe::Identity::new().with_collector(Registry::bounded(2));
+
+        with_default(subscriber, || {
+            let span1 = tracing::info_span!("span1");
+            let _span2 = tracing::info_span!("span2");
+            assert_eq!(live_spans(), 2);
+
+            // The registry is at its limit; new spans are disabled.
+            let span3 = tracing::info_span!("span3");
+            assert!(span3.is_disabled());
+            assert_eq!(live_spans(), 2);
+
+            // Closing a span makes room for new ones again.
+            drop(span1);
+            assert_eq!(live_spans(), 1);
+            let span4 = tracing::info_span!("span4");
+            assert!(!span4.is_disabled());
+            assert_eq!(live_spans(), 2);
+        });
+    }
+
+    #[test]
+    fn bounded_registry_evicts_oldest_span_at_cap() {
+        use crate::subscribe::Subscribe;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Default)]
+        struct EvictionProbe {
+            evictions: Arc<AtomicUsize>,
+        }
+
+        impl<C> Subscribe<C> for EvictionProbe
+        where
+            C: Collect + for<'a> LookupSpan<'a>,
+        {
+            fn on_event(&self, event: &tracing_core::Event<'_>, _: Context<'_, C>) {
+                if event.metadata().name() == "registry_span_evicted" {
+                    self.evictions.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+
+        let evictions = Arc::new(AtomicUsize::new(0));
+        let probe = EvictionProbe {
+            evictions: evictions.clone(),
+        };
+        let subscriber = probe.with_collector(Registry::bounded_with_policy(
+            1,
+            super::CapPolicy::EvictOldest,
+        ));
+
+        with_default(subscriber, || {
+            let span1 = tracing::info_span!("span1");
+            // Entering a span lets the registry capture the dispatcher used
+            // to emit eviction diagnostics.
+            span1.in_scope(|| {});
+            assert_eq!(live_spans(), 1);
+
+            // Creating a second span evicts the first, emitting a diagnostic
+            // event; the live-span count stays at the limit.
+            let _span2 = tracing::info_span!("span2");
+            assert!(!_span2.is_disabled());
+            assert_eq!(evictions.load(Ordering::SeqCst), 1);
+            assert_eq!(live_spans(), 1);
+
+            // The evicted span's metadata remains accessible until its
+            // handle is dropped, and dropping it does not double-count.
+            drop(span1);
+            assert_eq!(live_spans(), 1);
+        });
+    }
 }
@@ -10,6 +10,11 @@
 //! change at runtime. Note that this subscriber introduces a (relatively small)
 //! amount of overhead, and should thus only be used as needed.
 //!
+//! Because a `reload::Subscriber` can only swap in a new value of the *same*
+//! type, this module also provides [`Stack`], a reloadable *boxed* subscriber
+//! tree that allows an entire composed pipeline to be replaced at runtime by
+//! a differently-shaped one.
+//!
 //! ## Note
 //!
 //! //! The [`Subscribe`] implementation is unable to implement downcasting functionality,
@@ -53,6 +58,42 @@ pub struct Handle<S> {
     inner: Weak<RwLock<S>>,
 }
 
+/// A reloadable, type-erased stack of subscribers.
+///
+/// While a [`reload::Subscriber`] swaps one subscriber for another value of
+/// the *same* type, a `Stack` boxes the subscriber tree it wraps, so the
+/// entire composed pipeline can be replaced at runtime by a
+/// differently-shaped one — for example, switching from plain text logging
+/// to a JSON stack without restarting the process.
+///
+/// A swap is atomic with respect to events: it blocks until events and spans
+/// currently being processed by the old stack have drained, and callsite
+/// interest (including the global max level) is re-computed for the new
+/// stack before [`StackHandle::swap`] returns. Spans opened by the old stack
+/// remain open across the swap; any state the old stack stored in those
+/// spans' extensions is still present, but the new stack will only observe
+/// their `on_exit` and `on_close` notifications.
+///
+/// # Examples
+///
+/// ```rust
+/// use tracing_subscriber::{prelude::*, reload};
+///
+/// let (stack, handle) = reload::Stack::wrap(tracing_subscriber::fmt::subscriber());
+/// let collector = tracing_subscriber::registry().with(stack);
+/// # let _ = collector;
+/// // ... some time later, replace the whole pipeline ...
+/// handle
+///     .swap(tracing_subscriber::fmt::subscriber().compact())
+///     .unwrap();
+/// ```
+///
+/// [`reload::Subscriber`]: Subscriber
+pub type Stack<C> = Subscriber<Box<dyn crate::Subscribe<C> + Send + Sync>>;
+
+/// Allows swapping the stack of subscribers wrapped by a [`Stack`].
+pub type StackHandle<C> = Handle<Box<dyn crate::Subscribe<C> + Send + Sync>>;
+
 /// Indicates that an error occurred when reloading a subscriber.
 #[derive(Debug)]
 pub struct Error {
@@ -247,6 +288,40 @@ impl<T> Subscriber<T> {
     }
 }
 
+// ===== impl Stack =====
+
+impl<C> Stack<C>
+where
+    C: Collect,
+{
+    /// Wraps the given subscriber tree, erasing its type, and returns a
+    /// `Stack` and a [`StackHandle`] that allows the entire tree to be
+    /// swapped at runtime.
+    pub fn wrap<S>(inner: S) -> (Self, StackHandle<C>)
+    where
+        S: crate::Subscribe<C> + Send + Sync + 'static,
+    {
+        Self::new(Box::new(inner))
+    }
+}
+
+impl<C> StackHandle<C>
+where
+    C: Collect,
+{
+    /// Atomically replaces the entire wrapped subscriber stack with `new`.
+    ///
+    /// This blocks until events currently being processed by the old stack
+    /// have drained, then re-runs callsite registration for the new stack.
+    /// The old stack is dropped once the swap completes.
+    pub fn swap<S>(&self, new: S) -> Result<(), Error>
+    where
+        S: crate::Subscribe<C> + Send + Sync + 'static,
+    {
+        self.reload(Box::new(new) as Box<dyn crate::Subscribe<C> + Send + Sync>)
+    }
+}
+
 // ===== impl Handle =====
 
 impl<T> Handle<T> {
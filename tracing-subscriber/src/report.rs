@@ -0,0 +1,527 @@
+//! Self-contained HTML trace reports.
+//!
+//! Log output is a poor medium for understanding the shape of a traced
+//! workload: span nesting is flattened into enter/exit lines, and timing
+//! information is scattered across close events. This module provides a
+//! [`Subscriber`] that records the full span tree instead, and renders it as
+//! a standalone HTML document — with collapsible spans, per-span timing
+//! bars, level color coding, and client-side filtering — that can be
+//! attached to a CI failure or a bug report and opened in any browser,
+//! without an external trace viewer.
+//!
+//! The report is deliberately self-contained: all styling and scripting is
+//! embedded in the document, and no network access is required to view it.
+//!
+//! # Examples
+//!
+//! ```
+//! use tracing_subscriber::{prelude::*, report};
+//!
+//! let (report, handle) = report::Subscriber::new();
+//! let collector = tracing_subscriber::registry().with(report);
+//! # let _ = collector;
+//! // ... after the traced workload completes ...
+//! let html = handle.render();
+//! # drop(html);
+//! ```
+//!
+//! To write the report to a file, use [`Handle::write_to`]:
+//!
+//! ```no_run
+//! # use tracing_subscriber::{prelude::*, report};
+//! # let (report, handle) = report::Subscriber::new();
+//! # let _ = tracing_subscriber::registry().with(report);
+//! let file = std::fs::File::create("trace-report.html")?;
+//! handle.write_to(file)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    fmt::Write as _,
+    io,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use tracing_core::{field, span, Collect, Event, Level};
+
+/// A [`Subscribe`] implementation that records spans and events into an
+/// in-memory trace tree, for rendering as an HTML report.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    shared: Arc<Shared>,
+}
+
+/// Provides access to the trace tree recorded by a report [`Subscriber`].
+///
+/// This is returned by [`Subscriber::new`], and may be cloned and sent to
+/// other threads freely.
+#[derive(Debug, Clone)]
+pub struct Handle {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    start: Instant,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+}
+
+#[derive(Debug)]
+struct Node {
+    kind: Kind,
+    name: &'static str,
+    target: &'static str,
+    level: Level,
+    fields: String,
+    start_us: u64,
+    duration_us: Option<u64>,
+    children: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Span,
+    Event,
+}
+
+/// The index of a span's [`Node`], stored in that span's extensions.
+#[derive(Debug, Clone, Copy)]
+struct NodeId(usize);
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new report subscriber, and a [`Handle`] that renders the
+    /// trace tree it records.
+    pub fn new() -> (Self, Handle) {
+        let shared = Arc::new(Shared {
+            start: Instant::now(),
+            state: Mutex::new(State::default()),
+        });
+        let handle = Handle {
+            shared: shared.clone(),
+        };
+        (Self { shared }, handle)
+    }
+
+    /// Adds a node to the trace tree, under `parent` if one is given, and
+    /// returns its index.
+    fn push_node(&self, node: Node, parent: Option<NodeId>) -> NodeId {
+        let mut state = self.shared.state.lock().expect("report state poisoned");
+        let id = state.nodes.len();
+        state.nodes.push(node);
+        match parent {
+            Some(NodeId(parent)) => state.nodes[parent].children.push(id),
+            None => state.roots.push(id),
+        }
+        NodeId(id)
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut fields = String::new();
+        attrs.record(&mut FieldVisitor::new(&mut fields));
+        let parent = span
+            .parent()
+            .and_then(|parent| parent.extensions().get::<NodeId>().copied());
+        let node = self.push_node(
+            Node {
+                kind: Kind::Span,
+                name: span.metadata().name(),
+                target: span.metadata().target(),
+                level: *span.metadata().level(),
+                fields,
+                start_us: self.shared.elapsed_us(),
+                duration_us: None,
+                children: Vec::new(),
+            },
+            parent,
+        );
+        span.extensions_mut().insert(node);
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let node = span.extensions().get::<NodeId>().copied();
+        if let Some(NodeId(node)) = node {
+            let mut state = self.shared.state.lock().expect("report state poisoned");
+            values.record(&mut FieldVisitor::new(&mut state.nodes[node].fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let metadata = event.metadata();
+        let mut fields = String::new();
+        event.record(&mut FieldVisitor::new(&mut fields));
+        let parent = ctx
+            .event_span(event)
+            .and_then(|span| span.extensions().get::<NodeId>().copied());
+        self.push_node(
+            Node {
+                kind: Kind::Event,
+                name: metadata.name(),
+                target: metadata.target(),
+                level: *metadata.level(),
+                fields,
+                start_us: self.shared.elapsed_us(),
+                duration_us: None,
+                children: Vec::new(),
+            },
+            parent,
+        );
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(&id).expect("Span not found, this is a bug");
+        let node = span.extensions().get::<NodeId>().copied();
+        if let Some(NodeId(node)) = node {
+            let now = self.shared.elapsed_us();
+            let mut state = self.shared.state.lock().expect("report state poisoned");
+            let node = &mut state.nodes[node];
+            node.duration_us = Some(now.saturating_sub(node.start_us));
+        }
+    }
+}
+
+// === impl Handle ===
+
+impl Handle {
+    /// Renders the trace tree recorded so far as a standalone HTML document.
+    ///
+    /// The report is a snapshot: spans and events recorded after this method
+    /// returns are not reflected in it. Spans that have not yet closed are
+    /// included, marked as still open.
+    pub fn render(&self) -> String {
+        let total_us = self.shared.elapsed_us().max(1);
+        let state = self.shared.state.lock().expect("report state poisoned");
+
+        let mut spans = 0;
+        let mut events = 0;
+        for node in &state.nodes {
+            match node.kind {
+                Kind::Span => spans += 1,
+                Kind::Event => events += 1,
+            }
+        }
+
+        let mut out = String::new();
+        out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        out.push_str("<title>Trace report</title>\n<style>\n");
+        out.push_str(STYLE);
+        out.push_str("</style>\n</head>\n<body>\n<h1>Trace report</h1>\n");
+        let _ = writeln!(
+            out,
+            "<p class=\"meta\">{} spans, {} events, captured over {}</p>",
+            spans,
+            events,
+            duration(total_us),
+        );
+        out.push_str(CONTROLS);
+        out.push_str("<div id=\"trace\">\n");
+        for &root in &state.roots {
+            render_node(&state.nodes, root, total_us, &mut out);
+        }
+        out.push_str("</div>\n<script>\n");
+        out.push_str(SCRIPT);
+        out.push_str("</script>\n</body>\n</html>\n");
+        out
+    }
+
+    /// Renders the trace tree recorded so far and writes it to `writer`.
+    ///
+    /// This is a convenience for writing the output of [`render`](Self::render)
+    /// to a file.
+    pub fn write_to(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writer.write_all(self.render().as_bytes())?;
+        writer.flush()
+    }
+}
+
+impl Shared {
+    fn elapsed_us(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+}
+
+/// Renders one node (and, for spans, its subtree) as HTML.
+fn render_node(nodes: &[Node], id: usize, total_us: u64, out: &mut String) {
+    let node = &nodes[id];
+    let mut text = String::new();
+    escape(node.name, &mut text);
+    text.push(' ');
+    escape(node.target, &mut text);
+    text.push(' ');
+    escape(&node.fields, &mut text);
+    let text = text.to_lowercase();
+
+    match node.kind {
+        Kind::Span => {
+            let end_us = node
+                .duration_us
+                .map(|duration| node.start_us + duration)
+                .unwrap_or(total_us);
+            let left = node.start_us as f64 * 100.0 / total_us as f64;
+            let width = (end_us.saturating_sub(node.start_us)) as f64 * 100.0 / total_us as f64;
+            let _ = write!(
+                out,
+                "<details class=\"span\" open data-level=\"{}\" data-text=\"{}\"><summary>",
+                node.level, text,
+            );
+            let _ = write!(out, "<span class=\"name\">");
+            escape(node.name, out);
+            out.push_str("</span>");
+            if !node.fields.is_empty() {
+                out.push_str(" <span class=\"fields\">");
+                escape(&node.fields, out);
+                out.push_str("</span>");
+            }
+            let _ = write!(
+                out,
+                " <span class=\"dur\">{}</span>",
+                match node.duration_us {
+                    Some(us) => duration(us),
+                    None => String::from("open"),
+                },
+            );
+            let _ = write!(
+                out,
+                "<span class=\"track\"><span class=\"bar\" style=\"left:{:.2}%;width:{:.2}%\"></span></span>",
+                left,
+                width.max(0.25),
+            );
+            out.push_str("</summary>\n");
+            for &child in &node.children {
+                render_node(nodes, child, total_us, out);
+            }
+            out.push_str("</details>\n");
+        }
+        Kind::Event => {
+            let _ = write!(
+                out,
+                "<div class=\"event level-{}\" data-level=\"{}\" data-text=\"{}\">",
+                node.level.as_str().to_lowercase(),
+                node.level,
+                text,
+            );
+            let _ = write!(
+                out,
+                "<span class=\"time\">+{}</span> <span class=\"level\">{}</span> <span class=\"target\">",
+                duration(node.start_us),
+                node.level,
+            );
+            escape(node.target, out);
+            out.push_str("</span> ");
+            escape(&node.fields, out);
+            out.push_str("</div>\n");
+        }
+    }
+}
+
+/// Escapes `s` for inclusion in HTML text or attribute values.
+fn escape(s: &str, out: &mut String) {
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            ch => out.push(ch),
+        }
+    }
+}
+
+/// Formats a duration in microseconds with a human-readable unit.
+fn duration(us: u64) -> String {
+    if us >= 1_000_000 {
+        format!("{:.2}s", us as f64 / 1_000_000.0)
+    } else if us >= 1_000 {
+        format!("{:.2}ms", us as f64 / 1_000.0)
+    } else {
+        format!("{}\u{b5}s", us)
+    }
+}
+
+/// Records a node's fields as a space-separated `key=value` string, with the
+/// `message` field written first and without a key, matching the default
+/// `fmt` output.
+struct FieldVisitor<'a> {
+    out: &'a mut String,
+}
+
+impl<'a> FieldVisitor<'a> {
+    fn new(out: &'a mut String) -> Self {
+        Self { out }
+    }
+
+    fn pad(&mut self) {
+        if !self.out.is_empty() {
+            self.out.push(' ');
+        }
+    }
+}
+
+impl field::Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.record_debug(field, &value)
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.pad();
+        if field.name() == "message" {
+            let _ = write!(self.out, "{:?}", value);
+        } else {
+            let _ = write!(self.out, "{}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// The embedded stylesheet, including the level color coding.
+const STYLE: &str = r#"body { font-family: ui-monospace, monospace; font-size: 13px; margin: 1em 2em; color: #222; }
+h1 { font-size: 16px; }
+.meta { color: #777; }
+#controls { margin-bottom: 1em; }
+#controls input { font: inherit; width: 24em; }
+#controls select { font: inherit; }
+details.span { margin: 2px 0 2px 1.25em; border-left: 1px solid #ddd; padding-left: 0.5em; }
+#trace > details.span { margin-left: 0; }
+summary { cursor: pointer; white-space: nowrap; }
+summary .name { font-weight: bold; }
+summary .fields { color: #555; }
+summary .dur { color: #777; }
+.track { display: inline-block; position: relative; width: 160px; height: 8px; margin-left: 0.75em; background: #eee; border-radius: 2px; vertical-align: middle; overflow: hidden; }
+.bar { position: absolute; top: 0; height: 100%; background: #4a90d9; border-radius: 2px; }
+.event { margin: 1px 0 1px 1.25em; white-space: pre-wrap; }
+#trace > .event { margin-left: 0; }
+.event .time { color: #999; }
+.event .target { color: #999; }
+.event .level { font-weight: bold; }
+.level-error .level { color: #c0392b; }
+.level-warn .level { color: #b8860b; }
+.level-info .level { color: #2e8b57; }
+.level-debug .level { color: #4169e1; }
+.level-trace .level { color: #8e44ad; }
+"#;
+
+/// The filter controls rendered above the trace tree.
+const CONTROLS: &str = r#"<div id="controls">
+<input id="filter" type="text" placeholder="filter spans and events...">
+<select id="level">
+<option value="TRACE" selected>TRACE</option>
+<option value="DEBUG">DEBUG</option>
+<option value="INFO">INFO</option>
+<option value="WARN">WARN</option>
+<option value="ERROR">ERROR</option>
+</select>
+</div>
+"#;
+
+/// The embedded client-side filtering script.
+const SCRIPT: &str = r#"(function () {
+    var filter = document.getElementById('filter');
+    var level = document.getElementById('level');
+    var ranks = { TRACE: 0, DEBUG: 1, INFO: 2, WARN: 3, ERROR: 4 };
+    function apply() {
+        var text = filter.value.toLowerCase();
+        var min = ranks[level.value];
+        var events = document.querySelectorAll('#trace .event');
+        for (var i = 0; i < events.length; i++) {
+            var el = events[i];
+            var visible = el.dataset.text.indexOf(text) !== -1
+                && ranks[el.dataset.level] >= min;
+            el.style.display = visible ? '' : 'none';
+        }
+        // Walk spans leaves-first, so that a span is kept visible whenever
+        // any of its descendants is.
+        var spans = Array.prototype.slice
+            .call(document.querySelectorAll('#trace details.span'))
+            .reverse();
+        for (var i = 0; i < spans.length; i++) {
+            var el = spans[i];
+            var children = el.children;
+            var hasVisibleChild = false;
+            for (var j = 1; j < children.length; j++) {
+                if (children[j].style.display !== 'none') {
+                    hasVisibleChild = true;
+                    break;
+                }
+            }
+            var matches = el.dataset.text.indexOf(text) !== -1;
+            el.style.display = hasVisibleChild || matches ? '' : 'none';
+        }
+    }
+    filter.addEventListener('input', apply);
+    level.addEventListener('change', apply);
+})();
+"#;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn renders_nested_spans_and_events() {
+        let (report, handle) = Subscriber::new();
+        let collector = crate::registry().with(report);
+        with_default(collector, || {
+            let outer = tracing::info_span!("outer", request = 7);
+            let _outer = outer.enter();
+            tracing::info!("starting");
+            let inner = tracing::debug_span!("inner");
+            let _inner = inner.enter();
+            tracing::warn!(code = 418, "something odd");
+        });
+
+        let html = handle.render();
+        let outer = html.find("outer").expect("outer span should be rendered");
+        let inner = html.find("inner").expect("inner span should be rendered");
+        assert!(outer < inner, "outer span should precede inner span");
+        assert!(html.contains("request=7"));
+        assert!(html.contains("starting"));
+        assert!(html.contains("code=418"));
+        assert!(html.contains("level-warn"));
+    }
+
+    #[test]
+    fn field_values_are_escaped() {
+        let (report, handle) = Subscriber::new();
+        let collector = crate::registry().with(report);
+        with_default(collector, || {
+            tracing::info!(payload = "<script>alert(1)</script>", "sneaky");
+        });
+
+        let html = handle.render();
+        assert!(!html.contains("<script>alert(1)</script>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+    }
+
+    #[test]
+    fn open_spans_are_marked() {
+        let (report, handle) = Subscriber::new();
+        let collector = crate::registry().with(report);
+        with_default(collector, || {
+            let span = tracing::info_span!("unfinished");
+            let _entered = span.enter();
+            let html = handle.render();
+            assert!(html.contains("unfinished"));
+            assert!(html.contains("<span class=\"dur\">open</span>"));
+        });
+    }
+}
@@ -0,0 +1,451 @@
+//! Periodic roll-up of repetitive, high-frequency events.
+//!
+//! Progress ticks, heartbeats, and per-item loop events are often emitted
+//! thousands of times per second, yet nobody reads them individually —
+//! they matter in aggregate. This module provides a [`Subscriber`] that
+//! suppresses events from configured targets and accumulates them
+//! per-callsite instead: a count, min/max/average of every numeric field,
+//! and a small sample of messages. A background thread then emits one
+//! summary event per callsite at a fixed interval, so a hot loop costs
+//! one log line per window instead of one per iteration.
+//!
+//! Suppressed events are vetoed for the entire collector, so they cost
+//! neither formatting nor export. Events whose target does not match any
+//! configured prefix pass through untouched. (The summaries are emitted
+//! from a background thread because dispatching an event from within
+//! event dispatch is a no-op.)
+//!
+//! # Examples
+//!
+//! ```
+//! use tracing_subscriber::{prelude::*, rollup};
+//!
+//! let collector = tracing_subscriber::registry()
+//!     .with(
+//!         rollup::Subscriber::builder()
+//!             .aggregate_target("my_crate::sync::progress")
+//!             .with_interval(std::time::Duration::from_secs(30))
+//!             .finish(),
+//!     )
+//!     .with(tracing_subscriber::fmt::subscriber());
+//! # let _ = collector;
+//! ```
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    fmt::Write as _,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use tracing_core::{callsite, dispatch, field, Collect, Event};
+
+/// The default summary interval.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+/// The number of sample messages retained per callsite per window.
+const MAX_SAMPLES: usize = 3;
+
+thread_local! {
+    /// Set while emitting summary events, so the summaries themselves are
+    /// not rolled up.
+    static IN_SUMMARY: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A [`Subscribe`] implementation that rolls repetitive events up into
+/// periodic summaries.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    targets: Vec<String>,
+    shared: Arc<Shared>,
+}
+
+/// Configures a roll-up [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    targets: Vec<String>,
+    interval: Duration,
+}
+
+/// State shared between the subscriber and the summary thread.
+#[derive(Debug, Default)]
+struct Shared {
+    aggregates: Mutex<HashMap<callsite::Identifier, Aggregate>>,
+    /// The dispatch the subscriber is installed in, captured when the
+    /// collector is registered, so the summary thread can emit through it.
+    dispatch: Mutex<Option<dispatch::WeakDispatch>>,
+}
+
+/// The accumulated state of one callsite over the current window.
+#[derive(Debug)]
+struct Aggregate {
+    target: &'static str,
+    count: u64,
+    fields: Vec<(&'static str, NumStat)>,
+    samples: Vec<String>,
+}
+
+/// Running min/max/sum statistics for one numeric field.
+#[derive(Debug, Clone, Copy)]
+struct NumStat {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: u64,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a [`Builder`] for configuring a roll-up subscriber.
+    pub fn builder() -> Builder {
+        Builder {
+            targets: Vec::new(),
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Returns whether events with the given target are rolled up.
+    fn matches(&self, target: &str) -> bool {
+        self.targets.iter().any(|prefix| target.starts_with(prefix))
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_register_dispatch(&self, collector: &dispatch::Dispatch) {
+        // Keep a weak handle to the dispatch, so the summary thread can
+        // emit events through the collector this subscriber is part of.
+        let mut slot = self.shared.dispatch.lock().expect("rollup state poisoned");
+        *slot = Some(collector.downgrade());
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, _ctx: Context<'_, C>) -> bool {
+        if IN_SUMMARY.with(Cell::get) {
+            return true;
+        }
+        let metadata = event.metadata();
+        if !self.matches(metadata.target()) {
+            return true;
+        }
+
+        let mut aggregates = self
+            .shared
+            .aggregates
+            .lock()
+            .expect("rollup state poisoned");
+        let aggregate = aggregates
+            .entry(metadata.callsite())
+            .or_insert_with(|| Aggregate {
+                target: metadata.target(),
+                count: 0,
+                fields: Vec::new(),
+                samples: Vec::new(),
+            });
+        aggregate.count += 1;
+        event.record(&mut AggregateVisitor { aggregate });
+        false
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Adds a target prefix whose events are rolled up.
+    ///
+    /// Events whose target starts with `prefix` are suppressed and
+    /// aggregated; all other events pass through. May be called multiple
+    /// times.
+    pub fn aggregate_target(mut self, prefix: impl Into<String>) -> Self {
+        self.targets.push(prefix.into());
+        self
+    }
+
+    /// Sets how often summaries are emitted.
+    ///
+    /// Every interval, one summary event is emitted for each callsite
+    /// that recorded events since the previous summary. The default is 30
+    /// seconds.
+    pub fn with_interval(self, interval: Duration) -> Self {
+        Self {
+            interval: interval.max(Duration::from_millis(1)),
+            ..self
+        }
+    }
+
+    /// Returns the configured roll-up [`Subscriber`], spawning its
+    /// summary thread.
+    pub fn finish(self) -> Subscriber {
+        let shared = Arc::new(Shared::default());
+        let worker = SummaryWorker {
+            shared: shared.clone(),
+            interval: self.interval,
+        };
+        // If the thread cannot be spawned, events are still suppressed;
+        // only the summaries are lost.
+        let _ = thread::Builder::new()
+            .name("tracing-rollup".into())
+            .spawn(move || worker.run());
+        Subscriber {
+            targets: self.targets,
+            shared,
+        }
+    }
+}
+
+/// Emits periodic summaries of the accumulated aggregates.
+struct SummaryWorker {
+    shared: Arc<Shared>,
+    interval: Duration,
+}
+
+// === impl SummaryWorker ===
+
+impl SummaryWorker {
+    fn run(&self) {
+        loop {
+            thread::sleep(self.interval);
+            let weak = self
+                .shared
+                .dispatch
+                .lock()
+                .expect("rollup state poisoned")
+                .clone();
+            let dispatch = match weak.and_then(|weak| weak.upgrade()) {
+                Some(dispatch) => dispatch,
+                // The collector is gone (or not yet registered); the
+                // aggregates keep accumulating until it is available.
+                None => continue,
+            };
+            let pending: Vec<Aggregate> = {
+                let mut aggregates = self
+                    .shared
+                    .aggregates
+                    .lock()
+                    .expect("rollup state poisoned");
+                aggregates.drain().map(|(_, aggregate)| aggregate).collect()
+            };
+            if pending.is_empty() {
+                continue;
+            }
+            IN_SUMMARY.with(|flag| flag.set(true));
+            dispatch::with_default(&dispatch, || {
+                for aggregate in pending {
+                    tracing::info!(
+                        target: "tracing_subscriber::rollup",
+                        target = aggregate.target,
+                        count = aggregate.count,
+                        stats = %aggregate.stats(),
+                        samples = %aggregate.samples.join(" | "),
+                        "rolled-up event summary"
+                    );
+                }
+            });
+            IN_SUMMARY.with(|flag| flag.set(false));
+        }
+    }
+}
+
+// === impl Aggregate ===
+
+impl Aggregate {
+    /// Records one numeric field observation.
+    fn observe(&mut self, name: &'static str, value: f64) {
+        match self.fields.iter_mut().find(|(field, _)| *field == name) {
+            Some((_, stat)) => {
+                stat.min = stat.min.min(value);
+                stat.max = stat.max.max(value);
+                stat.sum += value;
+                stat.count += 1;
+            }
+            None => self.fields.push((
+                name,
+                NumStat {
+                    min: value,
+                    max: value,
+                    sum: value,
+                    count: 1,
+                },
+            )),
+        }
+    }
+
+    /// Renders the numeric field statistics as a single line, such as
+    /// `items min=1 max=10 avg=5.50`.
+    fn stats(&self) -> String {
+        let mut out = String::new();
+        for (name, stat) in &self.fields {
+            if !out.is_empty() {
+                out.push_str(", ");
+            }
+            let _ = write!(
+                out,
+                "{} min={} max={} avg={:.2}",
+                name,
+                stat.min,
+                stat.max,
+                stat.sum / stat.count as f64
+            );
+        }
+        out
+    }
+}
+
+/// Feeds an event's fields into its callsite's [`Aggregate`].
+struct AggregateVisitor<'a> {
+    aggregate: &'a mut Aggregate,
+}
+
+impl field::Visit for AggregateVisitor<'_> {
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.aggregate.observe(field.name(), value as f64);
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.aggregate.observe(field.name(), value as f64);
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.aggregate.observe(field.name(), value);
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" && self.aggregate.samples.len() < MAX_SAMPLES {
+            let message = format!("{:?}", value);
+            if !self.aggregate.samples.contains(&message) {
+                self.aggregate.samples.push(message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    /// Records the target and rendered fields of every event it sees.
+    #[derive(Clone, Default)]
+    struct Sink(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl<C> Subscribe<C> for Sink
+    where
+        C: Collect + for<'a> LookupSpan<'a>,
+    {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+            let mut fields = String::new();
+            event.record(&mut |field: &field::Field, value: &dyn std::fmt::Debug| {
+                let _ = write!(fields, "{}={:?} ", field.name(), value);
+            });
+            self.0
+                .lock()
+                .unwrap()
+                .push((event.metadata().target().to_string(), fields));
+        }
+    }
+
+    impl Sink {
+        fn events(&self) -> Vec<(String, String)> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn matching_events_are_rolled_up() {
+        let sink = Sink::default();
+        let rollup = Subscriber::builder()
+            .aggregate_target(module_path!())
+            .with_interval(Duration::from_millis(20))
+            .finish();
+        let collector = crate::registry().with(rollup).with(sink.clone());
+        with_default(collector, || {
+            for items in [1_u64, 2, 3] {
+                tracing::debug!(items, "synced a batch");
+            }
+            // Give the summary thread a couple of intervals to report.
+            thread::sleep(Duration::from_millis(100));
+        });
+
+        let events = sink.events();
+        assert!(
+            events.iter().all(|(target, _)| target != module_path!()),
+            "raw events leaked through: {:?}",
+            events
+        );
+        let summaries: Vec<_> = events
+            .iter()
+            .filter(|(target, _)| target == "tracing_subscriber::rollup")
+            .collect();
+        assert_eq!(summaries.len(), 1, "{:?}", events);
+        let fields = &summaries[0].1;
+        assert!(fields.contains("count=3"), "{}", fields);
+        assert!(fields.contains("items min=1 max=3 avg=2.00"), "{}", fields);
+        assert!(fields.contains("synced a batch"), "{}", fields);
+    }
+
+    #[test]
+    fn non_matching_events_pass_through() {
+        let sink = Sink::default();
+        let rollup = Subscriber::builder()
+            .aggregate_target("some_other_crate")
+            .with_interval(Duration::from_millis(20))
+            .finish();
+        let collector = crate::registry().with(rollup).with(sink.clone());
+        with_default(collector, || {
+            tracing::info!("not repetitive at all");
+        });
+        let events = sink.events();
+        assert_eq!(events.len(), 1, "{:?}", events);
+        assert!(events[0].1.contains("not repetitive"), "{:?}", events);
+    }
+
+    #[test]
+    fn samples_are_bounded_and_deduplicated() {
+        let mut aggregate = Aggregate {
+            target: "t",
+            count: 0,
+            fields: Vec::new(),
+            samples: Vec::new(),
+        };
+        let mut record = |message: &str| {
+            let message = format!("{:?}", message);
+            if aggregate.samples.len() < MAX_SAMPLES && !aggregate.samples.contains(&message) {
+                aggregate.samples.push(message);
+            }
+        };
+        for message in ["a", "a", "b", "c", "d"] {
+            record(message);
+        }
+        assert_eq!(aggregate.samples.len(), MAX_SAMPLES);
+        assert_eq!(aggregate.samples, ["\"a\"", "\"b\"", "\"c\""]);
+    }
+
+    #[test]
+    fn stats_track_min_max_and_average() {
+        let mut aggregate = Aggregate {
+            target: "t",
+            count: 0,
+            fields: Vec::new(),
+            samples: Vec::new(),
+        };
+        for value in [4.0, 1.0, 7.0] {
+            aggregate.observe("latency_ms", value);
+        }
+        aggregate.observe("bytes", 10.0);
+        let stats = aggregate.stats();
+        assert_eq!(
+            stats,
+            "latency_ms min=1 max=7 avg=4.00, bytes min=10 max=10 avg=10.00"
+        );
+    }
+}
@@ -0,0 +1,805 @@
+//! Reports error events to [Sentry], with lower-severity events as
+//! breadcrumbs.
+//!
+//! Sentry is an error-tracking service: it aggregates error reports,
+//! deduplicates them into issues, and shows the *breadcrumbs* — the trail of
+//! ordinary events that preceded each error. Wiring `tracing` into Sentry is
+//! usually done ad hoc in application code; this module provides it as a
+//! [`Subscriber`] that belongs in the subscriber stack alongside the other
+//! exporters, with no dependencies beyond the standard library.
+//!
+//! Events below `ERROR` are recorded into a bounded breadcrumb trail.
+//! `ERROR`-level events, and spans that record an `error` field, are
+//! converted into Sentry events carrying the current breadcrumbs, with the
+//! fields of every span in scope attached as tags. Reporting can be sampled
+//! down with [`Builder::with_sample_rate`] for high-volume services. Failed
+//! reports are retried with exponential backoff before being dropped.
+//!
+//! # Limitations
+//!
+//! - Only plaintext `http://` DSNs are supported; there is no TLS, so
+//!   reports must go through a local [Sentry Relay] or proxy rather than
+//!   directly to sentry.io.
+//! - Breadcrumbs are global rather than per-request: in a concurrent
+//!   service, an error's trail may include breadcrumbs from unrelated
+//!   requests.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{sentry, prelude::*};
+//!
+//! let reporter = sentry::Subscriber::builder()
+//!     .with_dsn("http://public-key@127.0.0.1:8001/42")
+//!     .with_release("my-service@1.2.3")
+//!     .with_sample_rate(0.25)
+//!     .finish()
+//!     .expect("failed to start the Sentry reporter");
+//! let collector = tracing_subscriber::registry().with(reporter);
+//! # let _ = collector;
+//! ```
+//!
+//! [Sentry]: https://sentry.io/
+//! [Sentry Relay]: https://docs.sentry.io/product/relay/
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, SystemTime},
+};
+use tracing_core::{field, span, Collect, Event, Level};
+
+/// A [`Subscribe`] implementation that reports error events to Sentry.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    // The standard library's channel sender is not `Sync` on our minimum
+    // supported Rust version, so sends are serialized through a mutex.
+    sender: Mutex<mpsc::Sender<String>>,
+    breadcrumbs: Mutex<VecDeque<Breadcrumb>>,
+    max_breadcrumbs: usize,
+    sample_rate: f64,
+    release: Option<String>,
+    environment: Option<String>,
+}
+
+/// Configures a Sentry reporter [`Subscriber`].
+///
+/// This is returned by [`Subscriber::builder`].
+#[derive(Debug)]
+pub struct Builder {
+    dsn: String,
+    sample_rate: f64,
+    max_breadcrumbs: usize,
+    release: Option<String>,
+    environment: Option<String>,
+    max_retries: u32,
+}
+
+/// A parsed DSN: where to report, and the key to authenticate with.
+#[derive(Debug, PartialEq, Eq)]
+struct Dsn {
+    /// The `host:port` of the Sentry server or relay.
+    host: String,
+    /// The public key from the DSN's userinfo part.
+    key: String,
+    /// The project ID from the DSN's path.
+    project: String,
+}
+
+/// One entry in the breadcrumb trail.
+#[derive(Debug)]
+struct Breadcrumb {
+    /// Seconds since the Unix epoch, with millisecond precision.
+    timestamp: f64,
+    level: &'static str,
+    category: String,
+    message: String,
+    /// The event's fields, rendered as a JSON object.
+    data: String,
+}
+
+/// The fields of an open span, stored in its extensions for tagging
+/// error events.
+struct SentrySpan {
+    name: &'static str,
+    fields: Vec<(&'static str, String)>,
+    /// The value of the span's `error` field, if one was recorded.
+    error: Option<String>,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a [`Builder`] for configuring a Sentry reporter.
+    pub fn builder() -> Builder {
+        Builder {
+            dsn: String::new(),
+            sample_rate: 1.0,
+            max_breadcrumbs: 100,
+            release: None,
+            environment: None,
+            max_retries: 3,
+        }
+    }
+
+    /// Records a breadcrumb, discarding the oldest once the trail is full.
+    fn push_breadcrumb(&self, breadcrumb: Breadcrumb) {
+        let mut breadcrumbs = self.breadcrumbs.lock().expect("breadcrumbs poisoned");
+        while breadcrumbs.len() >= self.max_breadcrumbs {
+            breadcrumbs.pop_front();
+        }
+        breadcrumbs.push_back(breadcrumb);
+    }
+
+    /// Builds and submits one Sentry event.
+    fn report(
+        &self,
+        message: &str,
+        logger: &str,
+        fields: &[(&'static str, String)],
+        tags: BTreeMap<&'static str, String>,
+    ) {
+        if !should_sample(self.sample_rate) {
+            return;
+        }
+
+        let mut json = String::from("{\"event_id\":\"");
+        for byte in random_bytes::<16>() {
+            let _ = write!(json, "{:02x}", byte);
+        }
+        json.push_str("\",\"platform\":\"other\",\"level\":\"error\"");
+        let _ = write!(json, ",\"timestamp\":{:.3}", unix_seconds());
+        json.push_str(",\"logger\":\"");
+        escape_into(&mut json, logger);
+        json.push_str("\",\"message\":{\"formatted\":\"");
+        escape_into(&mut json, message);
+        json.push_str("\"}");
+        if let Some(release) = &self.release {
+            json.push_str(",\"release\":\"");
+            escape_into(&mut json, release);
+            json.push('"');
+        }
+        if let Some(environment) = &self.environment {
+            json.push_str(",\"environment\":\"");
+            escape_into(&mut json, environment);
+            json.push('"');
+        }
+        if !tags.is_empty() {
+            json.push_str(",\"tags\":{");
+            for (i, (name, value)) in tags.iter().enumerate() {
+                if i != 0 {
+                    json.push(',');
+                }
+                json.push('"');
+                escape_into(&mut json, name);
+                json.push_str("\":\"");
+                escape_into(&mut json, value);
+                json.push('"');
+            }
+            json.push('}');
+        }
+        if !fields.is_empty() {
+            json.push_str(",\"extra\":{");
+            for (i, (name, value)) in fields.iter().enumerate() {
+                if i != 0 {
+                    json.push(',');
+                }
+                json.push('"');
+                escape_into(&mut json, name);
+                json.push_str("\":\"");
+                escape_into(&mut json, value);
+                json.push('"');
+            }
+            json.push('}');
+        }
+        {
+            let breadcrumbs = self.breadcrumbs.lock().expect("breadcrumbs poisoned");
+            if !breadcrumbs.is_empty() {
+                json.push_str(",\"breadcrumbs\":{\"values\":[");
+                for (i, breadcrumb) in breadcrumbs.iter().enumerate() {
+                    if i != 0 {
+                        json.push(',');
+                    }
+                    let _ = write!(json, "{{\"timestamp\":{:.3}", breadcrumb.timestamp);
+                    json.push_str(",\"level\":\"");
+                    json.push_str(breadcrumb.level);
+                    json.push_str("\",\"category\":\"");
+                    escape_into(&mut json, &breadcrumb.category);
+                    json.push_str("\",\"message\":\"");
+                    escape_into(&mut json, &breadcrumb.message);
+                    json.push('"');
+                    if breadcrumb.data != "{}" {
+                        json.push_str(",\"data\":");
+                        json.push_str(&breadcrumb.data);
+                    }
+                    json.push('}');
+                }
+                json.push_str("]}");
+            }
+        }
+        json.push('}');
+
+        // The only send error is a disconnected worker; the report is
+        // dropped in that case, as there is nowhere to send it to.
+        let _ = self
+            .sender
+            .lock()
+            .expect("sentry sender poisoned")
+            .send(json);
+    }
+
+    /// Collects tags from every span in `scope`, innermost values winning.
+    fn scope_tags<'a, C>(
+        scope: impl Iterator<Item = crate::registry::SpanRef<'a, C>>,
+    ) -> BTreeMap<&'static str, String>
+    where
+        C: Collect + for<'b> LookupSpan<'b> + 'a,
+    {
+        let mut tags = BTreeMap::new();
+        for span in scope {
+            let extensions = span.extensions();
+            if let Some(data) = extensions.get::<SentrySpan>() {
+                tags.insert("span", data.name.to_owned());
+                for (name, value) in &data.fields {
+                    tags.insert(*name, value.clone());
+                }
+            }
+        }
+        tags
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut data = SentrySpan {
+            name: span.name(),
+            fields: Vec::new(),
+            error: None,
+        };
+        attrs.record(&mut SpanVisitor { data: &mut data });
+        span.extensions_mut().insert(data);
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(data) = extensions.get_mut::<SentrySpan>() {
+            values.record(&mut SpanVisitor { data });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let metadata = event.metadata();
+        let mut visitor = EventVisitor {
+            message: None,
+            fields: Vec::new(),
+        };
+        event.record(&mut visitor);
+        let message = visitor.message.unwrap_or_default();
+
+        if *metadata.level() == Level::ERROR {
+            let tags = match ctx.event_span(event) {
+                Some(span) => Self::scope_tags(span.scope().from_root()),
+                None => BTreeMap::new(),
+            };
+            self.report(&message, metadata.target(), &visitor.fields, tags);
+            return;
+        }
+
+        let mut data = String::from("{");
+        for (i, (name, value)) in visitor.fields.iter().enumerate() {
+            if i != 0 {
+                data.push(',');
+            }
+            data.push('"');
+            escape_into(&mut data, name);
+            data.push_str("\":\"");
+            escape_into(&mut data, value);
+            data.push('"');
+        }
+        data.push('}');
+        self.push_breadcrumb(Breadcrumb {
+            timestamp: unix_seconds(),
+            level: match *metadata.level() {
+                Level::WARN => "warning",
+                Level::INFO => "info",
+                _ => "debug",
+            },
+            category: metadata.target().to_owned(),
+            message,
+            data,
+        });
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(&id).expect("Span not found, this is a bug");
+        let error = {
+            let extensions = span.extensions();
+            match extensions.get::<SentrySpan>() {
+                Some(data) => match &data.error {
+                    Some(error) => error.clone(),
+                    None => return,
+                },
+                None => return,
+            }
+        };
+        let tags = Self::scope_tags(span.scope().from_root());
+        self.report(&error, span.metadata().target(), &[], tags);
+    }
+}
+
+// === impl Builder ===
+
+impl Builder {
+    /// Sets the DSN identifying the project to report to.
+    ///
+    /// The DSN comes from the Sentry project settings and has the form
+    /// `http://<public key>@<host>/<project id>`. Only `http://` DSNs are
+    /// accepted; see the [module-level documentation](self) for the TLS
+    /// limitations.
+    pub fn with_dsn(self, dsn: impl Into<String>) -> Self {
+        Self {
+            dsn: dsn.into(),
+            ..self
+        }
+    }
+
+    /// Sets the fraction of error events that are reported.
+    ///
+    /// The rate is clamped between 0.0 (report nothing) and 1.0 (report
+    /// everything, the default). Sampling is decided independently per
+    /// event; breadcrumbs are always recorded.
+    pub fn with_sample_rate(self, sample_rate: f64) -> Self {
+        Self {
+            sample_rate: sample_rate.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Sets how many breadcrumbs are kept for attachment to error events.
+    ///
+    /// The default is 100 breadcrumbs, matching the Sentry SDK convention.
+    pub fn with_max_breadcrumbs(self, max_breadcrumbs: usize) -> Self {
+        Self {
+            max_breadcrumbs: max_breadcrumbs.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the `release` attached to every report, such as
+    /// `my-service@1.2.3`.
+    ///
+    /// Sentry uses releases to associate issues with deploys and to power
+    /// regression detection.
+    pub fn with_release(self, release: impl Into<String>) -> Self {
+        Self {
+            release: Some(release.into()),
+            ..self
+        }
+    }
+
+    /// Sets the `environment` attached to every report, such as
+    /// `production` or `staging`.
+    pub fn with_environment(self, environment: impl Into<String>) -> Self {
+        Self {
+            environment: Some(environment.into()),
+            ..self
+        }
+    }
+
+    /// Sets how many times a failed report is retried before being
+    /// dropped.
+    ///
+    /// Retries back off exponentially, starting at 100 milliseconds. The
+    /// default is 3 retries.
+    pub fn with_max_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Returns the configured reporter [`Subscriber`], spawning its report
+    /// thread.
+    ///
+    /// Fails if the DSN is malformed. The thread runs until the
+    /// `Subscriber` is dropped; any reports still queued at that point are
+    /// sent before it exits.
+    pub fn finish(self) -> io::Result<Subscriber> {
+        let dsn = parse_dsn(&self.dsn).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("malformed Sentry DSN {:?}", self.dsn),
+            )
+        })?;
+        let (sender, receiver) = mpsc::channel();
+        let worker = Worker {
+            dsn,
+            max_retries: self.max_retries,
+        };
+        thread::Builder::new()
+            .name("tracing-sentry".into())
+            .spawn(move || worker.run(receiver))?;
+        Ok(Subscriber {
+            sender: Mutex::new(sender),
+            breadcrumbs: Mutex::new(VecDeque::new()),
+            max_breadcrumbs: self.max_breadcrumbs,
+            sample_rate: self.sample_rate,
+            release: self.release,
+            environment: self.environment,
+        })
+    }
+}
+
+/// Parses an `http://<key>@<host>/<project>` DSN.
+fn parse_dsn(dsn: &str) -> Option<Dsn> {
+    let rest = dsn.strip_prefix("http://")?;
+    let at = rest.find('@')?;
+    let (key, rest) = (&rest[..at], &rest[at + 1..]);
+    let slash = rest.rfind('/')?;
+    let (host, project) = (&rest[..slash], &rest[slash + 1..]);
+    if key.is_empty() || host.is_empty() || project.is_empty() {
+        return None;
+    }
+    Some(Dsn {
+        host: host.to_owned(),
+        key: key.to_owned(),
+        project: project.to_owned(),
+    })
+}
+
+// === impl Worker ===
+
+/// The report thread: posts each event to the project's store endpoint.
+struct Worker {
+    dsn: Dsn,
+    max_retries: u32,
+}
+
+impl Worker {
+    fn run(&self, receiver: mpsc::Receiver<String>) {
+        for report in receiver {
+            self.submit(&report);
+        }
+    }
+
+    /// Submits one report, retrying with exponential backoff; the report
+    /// is dropped either way.
+    fn submit(&self, report: &str) {
+        let mut backoff = Duration::from_millis(100);
+        for attempt in 0..=self.max_retries {
+            if self.post(report).is_ok() {
+                return;
+            }
+            if attempt < self.max_retries {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+    }
+
+    /// Posts one report, returning an error unless the server responds
+    /// with a success status.
+    fn post(&self, report: &str) -> io::Result<()> {
+        let mut stream = TcpStream::connect(&self.dsn.host)?;
+        let request = format!(
+            "POST /api/{}/store/ HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Content-Type: application/json\r\n\
+             X-Sentry-Auth: Sentry sentry_version=7, \
+             sentry_client=tracing-subscriber/0.3, sentry_key={}\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n",
+            self.dsn.project,
+            self.dsn.host,
+            self.dsn.key,
+            report.len(),
+        );
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(report.as_bytes())?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        // "HTTP/1.1 200 OK" => "200"
+        let code = status.split_whitespace().nth(1).unwrap_or("");
+        if code.starts_with('2') {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Sentry responded with status {:?}", status.trim()),
+            ))
+        }
+    }
+}
+
+/// Decides whether to report, with probability `rate`.
+fn should_sample(rate: f64) -> bool {
+    if rate >= 1.0 {
+        return true;
+    }
+    if rate <= 0.0 {
+        return false;
+    }
+    let roll = u64::from_le_bytes(random_bytes::<8>());
+    (roll as f64) < rate * (u64::MAX as f64)
+}
+
+/// Returns the current wall-clock time in seconds since the Unix epoch.
+fn unix_seconds() -> f64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as f64 / 1000.0)
+        .unwrap_or(0.0)
+}
+
+/// Returns `N` pseudo-random bytes, suitable for event IDs and sampling.
+///
+/// This uses the standard library's randomly-seeded hasher rather than a
+/// cryptographic RNG; IDs are unique in practice but not unpredictable.
+fn random_bytes<const N: usize>() -> [u8; N] {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+        sync::atomic::{AtomicU64, Ordering},
+    };
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut bytes = [0; N];
+    for chunk in bytes.chunks_mut(8) {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        let word = hasher.finish().to_le_bytes();
+        chunk.copy_from_slice(&word[..chunk.len()]);
+    }
+    bytes
+}
+
+/// Writes `value` into `out` with JSON string escaping.
+fn escape_into(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+/// Records span fields, capturing the `error` field separately.
+struct SpanVisitor<'a> {
+    data: &'a mut SentrySpan,
+}
+
+impl SpanVisitor<'_> {
+    fn record(&mut self, field: &field::Field, value: String) {
+        if field.name() == "error" {
+            self.data.error = Some(value);
+            return;
+        }
+        match self
+            .data
+            .fields
+            .iter_mut()
+            .find(|(name, _)| *name == field.name())
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.data.fields.push((field.name(), value)),
+        }
+    }
+}
+
+impl field::Visit for SpanVisitor<'_> {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.record(field, value.to_owned());
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.record(field, format!("{:?}", value));
+    }
+}
+
+/// Records event fields, extracting the `message` field.
+struct EventVisitor {
+    message: Option<String>,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl field::Visit for EventVisitor {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_owned());
+            return;
+        }
+        self.fields.push((field.name(), value.to_owned()));
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        let rendered = format!("{:?}", value);
+        if field.name() == "message" {
+            self.message = Some(rendered);
+            return;
+        }
+        self.fields.push((field.name(), rendered));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use std::net::TcpListener;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn dsns_parse_correctly() {
+        assert_eq!(
+            parse_dsn("http://abc123@127.0.0.1:8001/42"),
+            Some(Dsn {
+                host: "127.0.0.1:8001".to_owned(),
+                key: "abc123".to_owned(),
+                project: "42".to_owned(),
+            }),
+        );
+        assert_eq!(parse_dsn("https://abc@host/1"), None);
+        assert_eq!(parse_dsn("http://host/1"), None);
+        assert_eq!(parse_dsn("http://key@host"), None);
+    }
+
+    #[test]
+    fn sample_rate_edges_are_deterministic() {
+        assert!(should_sample(1.0));
+        assert!(should_sample(2.0));
+        assert!(!should_sample(0.0));
+        assert!(!should_sample(-1.0));
+    }
+
+    /// Accepts one report and returns its request line, headers, and body.
+    fn accept_report(listener: &TcpListener) -> (String, String, String) {
+        let (stream, _) = listener.accept().expect("no report received");
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader
+            .read_line(&mut request_line)
+            .expect("failed to read request line");
+        let mut headers = String::new();
+        let mut content_length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).expect("failed to read header");
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            headers.push_str(header);
+            headers.push('\n');
+            if let Some(length) = header
+                .to_ascii_lowercase()
+                .strip_prefix("content-length:")
+                .map(str::trim)
+            {
+                content_length = length.parse().expect("invalid content-length");
+            }
+        }
+        let mut body = vec![0; content_length];
+        io::Read::read_exact(&mut reader, &mut body).expect("failed to read body");
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .expect("failed to respond");
+        (
+            request_line.trim().to_owned(),
+            headers,
+            String::from_utf8(body).expect("body was not UTF-8"),
+        )
+    }
+
+    #[test]
+    fn errors_are_reported_with_breadcrumbs_and_tags() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let reporter = Subscriber::builder()
+            .with_dsn(format!("http://test-key@{}/42", addr))
+            .with_release("report-test@0.1")
+            .finish()
+            .expect("failed to start reporter");
+        let collector = crate::registry().with(reporter);
+
+        with_default(collector, || {
+            let span = tracing::info_span!("handle_request", method = "GET");
+            let _entered = span.enter();
+            tracing::info!(step = 1, "validated input");
+            tracing::error!(code = 500, "request failed");
+        });
+
+        let (request_line, headers, body) = accept_report(&listener);
+        assert_eq!(request_line, "POST /api/42/store/ HTTP/1.1");
+        assert!(
+            headers.contains("sentry_key=test-key"),
+            "missing auth header: {}",
+            headers,
+        );
+        assert!(body.contains("\"message\":{\"formatted\":\"request failed\"}"));
+        assert!(body.contains("\"release\":\"report-test@0.1\""));
+        assert!(body.contains("\"method\":\"GET\""), "missing span tag");
+        assert!(body.contains("\"span\":\"handle_request\""));
+        assert!(
+            body.contains("\"message\":\"validated input\""),
+            "missing breadcrumb",
+        );
+        assert!(body.contains("\"code\":\"500\""), "missing extra field");
+    }
+
+    #[test]
+    fn span_error_fields_are_reported_on_close() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let reporter = Subscriber::builder()
+            .with_dsn(format!("http://test-key@{}/7", addr))
+            .finish()
+            .expect("failed to start reporter");
+        let collector = crate::registry().with(reporter);
+
+        with_default(collector, || {
+            let span = tracing::info_span!("flush_cache", shard = 3, error = tracing::field::Empty);
+            span.in_scope(|| {});
+            span.record("error", "flush timed out");
+            drop(span);
+        });
+
+        let (request_line, _, body) = accept_report(&listener);
+        assert_eq!(request_line, "POST /api/7/store/ HTTP/1.1");
+        assert!(body.contains("\"message\":{\"formatted\":\"flush timed out\"}"));
+        assert!(body.contains("\"span\":\"flush_cache\""));
+        assert!(body.contains("\"shard\":\"3\""));
+    }
+
+    #[test]
+    fn breadcrumb_trails_are_bounded() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let reporter = Subscriber::builder()
+            .with_dsn(format!("http://test-key@{}/1", addr))
+            .with_max_breadcrumbs(2)
+            .finish()
+            .expect("failed to start reporter");
+        let collector = crate::registry().with(reporter);
+
+        with_default(collector, || {
+            tracing::info!("first");
+            tracing::info!("second");
+            tracing::info!("third");
+            tracing::error!("boom");
+        });
+
+        let (_, _, body) = accept_report(&listener);
+        assert!(!body.contains("\"message\":\"first\""), "trail not bounded");
+        assert!(body.contains("\"message\":\"second\""));
+        assert!(body.contains("\"message\":\"third\""));
+    }
+}
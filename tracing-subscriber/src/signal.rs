@@ -0,0 +1,304 @@
+//! Signal-driven verbosity control for an [`EnvFilter`] (Unix only).
+//!
+//! This module installs handlers for `SIGUSR1` and `SIGUSR2`, wired to a
+//! [`reload::Handle`]`<`[`EnvFilter`]`>`:
+//!
+//! - `SIGUSR1` cycles the active filter through a list of configured
+//!   presets. The filter that was active when the handler was installed is
+//!   always the first entry of the cycle, so with a single preset the
+//!   signal toggles between the service's normal verbosity and, say,
+//!   `trace`.
+//! - `SIGUSR2` re-reads a filter file and applies its directives.
+//!
+//! Each change is announced with an `INFO` event carrying the previous and
+//! new directives, so the switch itself is visible in the logs. This lets
+//! an operator turn up logging on a wedged process with nothing but
+//! `kill -USR1 <pid>` — no admin endpoint, no restart, no config reload
+//! machinery. For a richer control surface, see the [`admin`] module.
+//!
+//! # Limitations
+//!
+//! Signal handlers and the pending-signal flags they set are process-wide,
+//! so only one `Handler` should be installed per process; installing a
+//! second one replaces the first. Signals are observed by a background
+//! thread polling at the configured interval, so a change takes effect
+//! shortly after the signal arrives rather than synchronously within it.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{filter::EnvFilter, reload, signal, prelude::*};
+//!
+//! let (filter, handle) = reload::Subscriber::new(EnvFilter::new("info"));
+//! tracing_subscriber::registry()
+//!     .with(filter)
+//!     .with(tracing_subscriber::fmt::subscriber())
+//!     .init();
+//!
+//! signal::Handler::new(handle)
+//!     .with_preset("debug")
+//!     .with_filter_file("/etc/myservice/log-filter")
+//!     .install()
+//!     .expect("failed to install signal handlers");
+//! ```
+//!
+//! [`admin`]: crate::admin
+//! [`reload::Handle`]: crate::reload::Handle
+use crate::{filter::EnvFilter, reload};
+use std::{
+    fs, io,
+    os::raw::c_int,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+};
+
+/// Pending-signal flags set by the handlers and drained by the watcher
+/// thread. Atomics are async-signal-safe, unlike almost everything else.
+static PENDING: AtomicUsize = AtomicUsize::new(0);
+
+const USR1: usize = 1;
+const USR2: usize = 1 << 1;
+
+/// Configures and installs the signal handlers.
+///
+/// Returned by [`Handler::new`]; see the [module-level
+/// documentation](self) for details.
+#[derive(Debug)]
+pub struct Handler {
+    handle: reload::Handle<EnvFilter>,
+    presets: Vec<String>,
+    filter_file: Option<PathBuf>,
+    poll_interval: Duration,
+}
+
+// === impl Handler ===
+
+impl Handler {
+    /// Returns a new `Handler` controlling the filter behind `handle`,
+    /// with no presets and no filter file.
+    ///
+    /// At least one of [`with_preset`] or [`with_filter_file`] must be
+    /// called before [`install`], or there is nothing for the signals to
+    /// do.
+    ///
+    /// [`with_preset`]: Handler::with_preset
+    /// [`with_filter_file`]: Handler::with_filter_file
+    /// [`install`]: Handler::install
+    pub fn new(handle: reload::Handle<EnvFilter>) -> Self {
+        Self {
+            handle,
+            presets: Vec::new(),
+            filter_file: None,
+            poll_interval: Duration::from_millis(100),
+        }
+    }
+
+    /// Adds a preset to the `SIGUSR1` cycle.
+    ///
+    /// The cycle always starts with the directives that were active when
+    /// [`install`](Handler::install) was called, followed by the presets
+    /// in the order they were added; each `SIGUSR1` advances to the next
+    /// entry, wrapping around.
+    pub fn with_preset(mut self, directives: impl Into<String>) -> Self {
+        self.presets.push(directives.into());
+        self
+    }
+
+    /// Sets the file whose directives `SIGUSR2` applies.
+    ///
+    /// The file is read when the signal arrives, not when the handler is
+    /// installed, so it does not need to exist yet. If reading or parsing
+    /// fails, a `WARN` event is emitted and the active filter is left
+    /// unchanged.
+    pub fn with_filter_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.filter_file = Some(path.into());
+        self
+    }
+
+    /// Sets how often the watcher thread checks for received signals.
+    ///
+    /// Defaults to 100 milliseconds.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Validates the configuration, registers the signal handlers, and
+    /// spawns the watcher thread.
+    ///
+    /// Presets are parsed eagerly, so a typo fails here rather than when
+    /// the signal arrives. Returns an error if neither a preset nor a
+    /// filter file was configured, if a preset does not parse, or if a
+    /// handler cannot be registered.
+    pub fn install(self) -> io::Result<()> {
+        if self.presets.is_empty() && self.filter_file.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "signal handler has no presets and no filter file; \
+                 there is nothing for the signals to do",
+            ));
+        }
+        for preset in &self.presets {
+            preset.parse::<EnvFilter>().map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid preset {:?}: {}", preset, e),
+                )
+            })?;
+        }
+        let initial = self
+            .handle
+            .with_current(ToString::to_string)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+
+        if !self.presets.is_empty() {
+            register(libc::SIGUSR1)?;
+        }
+        if self.filter_file.is_some() {
+            register(libc::SIGUSR2)?;
+        }
+
+        // The initial directives are entry 0 of the cycle and are already
+        // active, so the first SIGUSR1 applies the first preset.
+        let mut cycle = vec![initial];
+        cycle.extend(self.presets);
+        let mut position = 0;
+        let handle = self.handle;
+        let filter_file = self.filter_file;
+        let poll_interval = self.poll_interval;
+        thread::Builder::new()
+            .name("tracing-signal".into())
+            .spawn(move || loop {
+                thread::sleep(poll_interval);
+                let pending = PENDING.swap(0, Ordering::SeqCst);
+                if pending & USR1 != 0 && cycle.len() > 1 {
+                    position = (position + 1) % cycle.len();
+                    apply(&handle, "SIGUSR1", &cycle[position]);
+                }
+                if pending & USR2 != 0 {
+                    if let Some(path) = &filter_file {
+                        match fs::read_to_string(path) {
+                            Ok(directives) => apply(&handle, "SIGUSR2", directives.trim()),
+                            Err(error) => tracing::warn!(
+                                signal = "SIGUSR2",
+                                file = %path.display(),
+                                %error,
+                                "failed to read the filter file"
+                            ),
+                        }
+                    }
+                }
+            })?;
+        Ok(())
+    }
+}
+
+/// Registers the pending-flag handler for `signum`.
+fn register(signum: c_int) -> io::Result<()> {
+    // Safety: `on_signal` only touches an atomic, which is
+    // async-signal-safe.
+    let handler = on_signal as extern "C" fn(c_int) as *const ();
+    let previous = unsafe { libc::signal(signum, handler as libc::sighandler_t) };
+    if previous == libc::SIG_ERR {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+extern "C" fn on_signal(signum: c_int) {
+    let flag = match signum {
+        libc::SIGUSR1 => USR1,
+        libc::SIGUSR2 => USR2,
+        _ => return,
+    };
+    PENDING.fetch_or(flag, Ordering::SeqCst);
+}
+
+/// Parses and applies `directives`, announcing the outcome as an event.
+fn apply(handle: &reload::Handle<EnvFilter>, signal: &str, directives: &str) {
+    let previous = handle.with_current(ToString::to_string).unwrap_or_default();
+    let filter = match directives.parse::<EnvFilter>() {
+        Ok(filter) => filter,
+        Err(error) => {
+            tracing::warn!(signal, directives, %error, "invalid filter directives");
+            return;
+        }
+    };
+    let current = filter.to_string();
+    match handle.reload(filter) {
+        Ok(()) => tracing::info!(signal, %previous, %current, "filter changed by signal"),
+        Err(error) => tracing::warn!(signal, %error, "failed to reload the filter"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    fn wait_for(handle: &reload::Handle<EnvFilter>, directives: &str) {
+        for _ in 0..500 {
+            let current = handle.with_current(ToString::to_string).unwrap();
+            if current == directives {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!(
+            "filter never became {:?}; current is {:?}",
+            directives,
+            handle.with_current(ToString::to_string).unwrap()
+        );
+    }
+
+    #[test]
+    fn empty_configurations_are_rejected() {
+        let (_filter, handle) = reload::Subscriber::new(EnvFilter::new("info"));
+        let err = Handler::new(handle).install().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn invalid_presets_are_rejected() {
+        let (_filter, handle) = reload::Subscriber::new(EnvFilter::new("info"));
+        let err = Handler::new(handle)
+            .with_preset("not a [valid filter")
+            .install()
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("invalid preset"));
+    }
+
+    // Signal handlers and the pending flags are process-wide, so all
+    // signal-raising assertions live in this one test.
+    #[test]
+    fn signals_cycle_presets_and_reread_the_filter_file() {
+        let (filter, handle) = reload::Subscriber::new(EnvFilter::new("info"));
+        let collector = crate::registry().with(filter);
+        with_default(collector, || {
+            let file = std::env::temp_dir()
+                .join(format!("tracing-signal-test-{}.filter", std::process::id()));
+            Handler::new(handle.clone())
+                .with_preset("debug")
+                .with_filter_file(&file)
+                .with_poll_interval(Duration::from_millis(10))
+                .install()
+                .expect("failed to install");
+
+            // SIGUSR1 advances to the preset, then wraps back around.
+            unsafe { libc::raise(libc::SIGUSR1) };
+            wait_for(&handle, "debug");
+            unsafe { libc::raise(libc::SIGUSR1) };
+            wait_for(&handle, "info");
+
+            // SIGUSR2 applies whatever the file says at signal time.
+            fs::write(&file, "warn\n").unwrap();
+            unsafe { libc::raise(libc::SIGUSR2) };
+            wait_for(&handle, "warn");
+            let _ = fs::remove_file(&file);
+        });
+    }
+}
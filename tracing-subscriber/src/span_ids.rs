@@ -0,0 +1,157 @@
+//! Layer-agnostic access to a span's wire trace and span IDs.
+//!
+//! Subscribers like [`trace_context`](crate::trace_context) assign spans
+//! the 128-bit trace IDs and 64-bit span IDs that go over the wire, but
+//! store them in their own private extension types. Application code also
+//! needs those IDs — to stamp responses with an `X-Trace-Id` header, or to
+//! include in error messages — without caring which subscriber assigned
+//! them.
+//!
+//! This module standardizes that handoff as a small extension protocol:
+//! any subscriber that assigns wire IDs inserts a [`SpanIds`] into the
+//! span's extensions, and applications read them back with [`current`] or
+//! [`find`]. The [`trace_context`](crate::trace_context) subscriber
+//! participates; custom subscribers can join with one
+//! `extensions_mut().insert(SpanIds::new(..))` call.
+//!
+//! # Examples
+//!
+//! With the `trace-context` feature enabled:
+//!
+//! ```no_run
+//! use tracing_subscriber::{span_ids, trace_context, prelude::*};
+//!
+//! tracing_subscriber::registry()
+//!     .with(trace_context::Subscriber::new())
+//!     .init();
+//!
+//! let span = tracing::info_span!("handle_request");
+//! let _entered = span.enter();
+//!
+//! if let Some(ids) = span_ids::current() {
+//!     println!("X-Trace-Id: {}", ids.trace_id_hex());
+//! }
+//! ```
+use crate::registry::{LookupSpan, Registry, SpanData};
+use tracing_core::{dispatch, span, Dispatch};
+
+/// The wire identifiers of a span, as assigned by an ID-assigning
+/// subscriber.
+///
+/// Subscribers insert this into a span's extensions; applications read it
+/// back with [`current`] or [`find`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpanIds {
+    trace_id: u128,
+    span_id: u64,
+}
+
+// === impl SpanIds ===
+
+impl SpanIds {
+    /// Returns a new `SpanIds` with the given wire identifiers.
+    pub fn new(trace_id: u128, span_id: u64) -> Self {
+        Self { trace_id, span_id }
+    }
+
+    /// Returns the 128-bit trace ID shared by every span in the trace.
+    pub fn trace_id(&self) -> u128 {
+        self.trace_id
+    }
+
+    /// Returns the 64-bit ID of this span.
+    pub fn span_id(&self) -> u64 {
+        self.span_id
+    }
+
+    /// Returns the trace ID as 32 lowercase hex characters, the form used
+    /// in `traceparent` headers.
+    pub fn trace_id_hex(&self) -> String {
+        format!("{:032x}", self.trace_id)
+    }
+
+    /// Returns the span ID as 16 lowercase hex characters.
+    pub fn span_id_hex(&self) -> String {
+        format!("{:016x}", self.span_id)
+    }
+}
+
+/// Returns the wire IDs of the current span, if the default collector has
+/// a [`Registry`] whose ID-assigning subscriber recorded them.
+pub fn current() -> Option<SpanIds> {
+    dispatch::get_default(|dispatch| {
+        let current = dispatch.current_span();
+        let id = current.id()?;
+        find_in(dispatch, id)
+    })
+}
+
+/// Returns the wire IDs of the span with the given [`span::Id`], such as
+/// one obtained from [`Span::id`].
+///
+/// [`Span::id`]: https://docs.rs/tracing/latest/tracing/struct.Span.html#method.id
+pub fn find(id: &span::Id) -> Option<SpanIds> {
+    dispatch::get_default(|dispatch| find_in(dispatch, id))
+}
+
+/// Looks a span's IDs up in the registry behind `dispatch`.
+fn find_in(dispatch: &Dispatch, id: &span::Id) -> Option<SpanIds> {
+    let registry = dispatch.downcast_ref::<Registry>()?;
+    let span = registry.span_data(id)?;
+    let extensions = span.extensions();
+    extensions.get::<SpanIds>().copied()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ids_render_as_padded_hex() {
+        let ids = SpanIds::new(0x4bf92f3577b34da6a3ce929d0e0e4736, 0x00f067aa0ba902b7);
+        assert_eq!(ids.trace_id_hex(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(ids.span_id_hex(), "00f067aa0ba902b7");
+        assert_eq!(SpanIds::new(1, 1).trace_id_hex().len(), 32);
+        assert_eq!(SpanIds::new(1, 1).span_id_hex().len(), 16);
+    }
+
+    #[test]
+    fn lookups_without_a_registry_return_none() {
+        assert_eq!(current(), None);
+        assert_eq!(find(&span::Id::from_u64(1)), None);
+    }
+
+    #[cfg(feature = "trace-context")]
+    mod with_trace_context {
+        use super::*;
+        use crate::{prelude::*, trace_context};
+        use tracing::collect::with_default;
+
+        #[test]
+        fn current_reads_ids_assigned_by_the_subscriber() {
+            let collector = crate::registry().with(trace_context::Subscriber::new());
+            with_default(collector, || {
+                assert_eq!(current(), None);
+
+                let span = tracing::info_span!("handle_request");
+                let _entered = span.enter();
+                let ids = current().expect("no ids for entered span");
+                let context = trace_context::current().expect("no trace context");
+                assert_eq!(ids.trace_id(), context.trace_id());
+                assert_eq!(ids.span_id(), context.span_id());
+            });
+        }
+
+        #[test]
+        fn find_reads_ids_of_unentered_spans() {
+            let collector = crate::registry().with(trace_context::Subscriber::new());
+            with_default(collector, || {
+                let span = tracing::info_span!("background_work");
+                let id = span.id().expect("span was disabled");
+                let ids = find(&id).expect("no ids for span");
+                assert_ne!(ids.trace_id(), 0);
+                assert_ne!(ids.span_id(), 0);
+            });
+        }
+    }
+}
@@ -0,0 +1,620 @@
+//! Writes events and completed spans into a SQLite database.
+//!
+//! This module provides a [`Subscriber`] that records events and spans
+//! into two tables of a SQLite database, so local traces can be queried
+//! with SQL instead of grepped out of log files — a natural fit for
+//! desktop applications and CLI tools:
+//!
+//! ```text
+//! $ sqlite3 trace.db \
+//!     'SELECT name, busy_nanos FROM spans ORDER BY busy_nanos DESC LIMIT 3'
+//! ```
+//!
+//! The schema is:
+//!
+//! ```text
+//! CREATE TABLE spans (
+//!     id            INTEGER PRIMARY KEY, -- referenced by `parent_id` and `span_id`
+//!     parent_id     INTEGER,             -- NULL for root spans
+//!     name          TEXT NOT NULL,
+//!     target        TEXT NOT NULL,
+//!     level         TEXT NOT NULL,
+//!     opened_millis INTEGER NOT NULL,    -- Unix milliseconds
+//!     closed_millis INTEGER NOT NULL,
+//!     busy_nanos    INTEGER NOT NULL,    -- total time the span was entered
+//!     fields        TEXT NOT NULL        -- JSON object
+//! );
+//! CREATE TABLE events (
+//!     timestamp_millis INTEGER NOT NULL,
+//!     level            TEXT NOT NULL,
+//!     target           TEXT NOT NULL,
+//!     message          TEXT,
+//!     span_id          INTEGER,          -- NULL outside any span
+//!     fields           TEXT NOT NULL     -- JSON object
+//! );
+//! ```
+//!
+//! Spans are inserted when they *close*, so the `spans` table holds
+//! completed spans with their timing; an event's `span_id` may therefore
+//! reference a span that appears later in the table.
+//!
+//! Writes happen on a background thread in batched transactions, and the
+//! database runs in WAL mode, so recording does not block the
+//! instrumented program on disk I/O and readers can query the database
+//! while it is being written. Dropping the [`Guard`] returned by
+//! [`open`] flushes the remaining batch.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{prelude::*, sqlite};
+//!
+//! let (subscriber, guard) = sqlite::open("./trace.db")
+//!     .expect("failed to open trace database");
+//! tracing_subscriber::registry().with(subscriber).init();
+//! // ...
+//! drop(guard); // flush before exiting
+//! ```
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use rusqlite::Connection;
+use std::{
+    fmt::Write as _,
+    path::Path,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        mpsc::{channel, Receiver, Sender, SyncSender},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+use tracing_core::{field, span, Collect, Event};
+
+/// How many records are written per transaction at most.
+const BATCH_SIZE: usize = 512;
+
+/// A [`Subscribe`] implementation that records events and completed
+/// spans into a SQLite database.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    // `Sender` is not `Sync` on this crate's MSRV, and subscribers must
+    // be; the mutex is uncontended in practice.
+    sender: Mutex<Sender<Record>>,
+    next_span: AtomicI64,
+}
+
+/// Flushes the database when dropped.
+///
+/// This is returned by [`open`]. Records are written on a background
+/// thread; drop the guard (or call [`flush`](Guard::flush)) before the
+/// process exits to make sure the last batch lands in the database.
+#[derive(Debug)]
+#[must_use = "dropping the guard flushes the database"]
+pub struct Guard {
+    sender: Mutex<Sender<Record>>,
+}
+
+#[derive(Debug)]
+enum Record {
+    Event {
+        timestamp_millis: i64,
+        level: &'static str,
+        target: String,
+        message: Option<String>,
+        span_id: Option<i64>,
+        fields: String,
+    },
+    Span {
+        id: i64,
+        parent_id: Option<i64>,
+        name: &'static str,
+        target: String,
+        level: &'static str,
+        opened_millis: i64,
+        closed_millis: i64,
+        busy_nanos: i64,
+        fields: String,
+    },
+    Flush(SyncSender<()>),
+}
+
+/// Per-span state carried in the registry's extensions until the span
+/// closes.
+struct SpanState {
+    id: i64,
+    opened_millis: i64,
+    busy: Duration,
+    entered_at: Option<Instant>,
+    fields: String,
+}
+
+/// Opens (or creates) the database at `path` and returns a `Subscriber`
+/// recording into it, plus the [`Guard`] that flushes it.
+///
+/// The schema is created if missing, and the database is put into WAL
+/// mode.
+pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<(Subscriber, Guard)> {
+    let conn = Connection::open(path)?;
+    // `PRAGMA journal_mode` returns the resulting mode as a row.
+    conn.query_row("PRAGMA journal_mode=WAL", [], |_| Ok(()))?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS spans (
+            id            INTEGER PRIMARY KEY,
+            parent_id     INTEGER,
+            name          TEXT NOT NULL,
+            target        TEXT NOT NULL,
+            level         TEXT NOT NULL,
+            opened_millis INTEGER NOT NULL,
+            closed_millis INTEGER NOT NULL,
+            busy_nanos    INTEGER NOT NULL,
+            fields        TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS events (
+            timestamp_millis INTEGER NOT NULL,
+            level            TEXT NOT NULL,
+            target           TEXT NOT NULL,
+            message          TEXT,
+            span_id          INTEGER,
+            fields           TEXT NOT NULL
+        );",
+    )?;
+
+    let (sender, receiver) = channel();
+    thread::Builder::new()
+        .name("tracing-sqlite".into())
+        .spawn(move || write_batches(conn, receiver))
+        .expect("failed to spawn the tracing-sqlite thread");
+    Ok((
+        Subscriber {
+            sender: Mutex::new(sender.clone()),
+            next_span: AtomicI64::new(1),
+        },
+        Guard {
+            sender: Mutex::new(sender),
+        },
+    ))
+}
+
+/// The background writer: batches queued records into transactions.
+fn write_batches(mut conn: Connection, receiver: Receiver<Record>) {
+    while let Ok(first) = receiver.recv() {
+        let mut batch = vec![first];
+        while batch.len() < BATCH_SIZE {
+            match receiver.try_recv() {
+                Ok(record) => batch.push(record),
+                Err(_) => break,
+            }
+        }
+        let tx = match conn.transaction() {
+            Ok(tx) => tx,
+            Err(_) => return,
+        };
+        let mut flushes = Vec::new();
+        for record in batch {
+            match record {
+                Record::Event {
+                    timestamp_millis,
+                    level,
+                    target,
+                    message,
+                    span_id,
+                    fields,
+                } => {
+                    let _ = tx.execute(
+                        "INSERT INTO events \
+                         (timestamp_millis, level, target, message, span_id, fields) \
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        rusqlite::params![
+                            timestamp_millis,
+                            level,
+                            target,
+                            message,
+                            span_id,
+                            fields
+                        ],
+                    );
+                }
+                Record::Span {
+                    id,
+                    parent_id,
+                    name,
+                    target,
+                    level,
+                    opened_millis,
+                    closed_millis,
+                    busy_nanos,
+                    fields,
+                } => {
+                    let _ = tx.execute(
+                        "INSERT INTO spans \
+                         (id, parent_id, name, target, level, \
+                          opened_millis, closed_millis, busy_nanos, fields) \
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                        rusqlite::params![
+                            id,
+                            parent_id,
+                            name,
+                            target,
+                            level,
+                            opened_millis,
+                            closed_millis,
+                            busy_nanos,
+                            fields
+                        ],
+                    );
+                }
+                Record::Flush(ack) => flushes.push(ack),
+            }
+        }
+        let _ = tx.commit();
+        // Acknowledge flushes only once their batch is committed.
+        for ack in flushes {
+            let _ = ack.send(());
+        }
+    }
+}
+
+// === impl Guard ===
+
+impl Guard {
+    /// Blocks until every record sent so far has been committed.
+    pub fn flush(&self) {
+        let (ack, done) = std::sync::mpsc::sync_channel(0);
+        let sent = self
+            .sender
+            .lock()
+            .expect("sqlite lock poisoned")
+            .send(Record::Flush(ack))
+            .is_ok();
+        if sent {
+            let _ = done.recv();
+        }
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+// === impl Subscriber ===
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut visitor = FieldVisitor::new();
+        attrs.record(&mut visitor);
+        span.extensions_mut().insert(SpanState {
+            id: self.next_span.fetch_add(1, Ordering::Relaxed),
+            opened_millis: unix_millis(),
+            busy: Duration::ZERO,
+            entered_at: None,
+            fields: visitor.finish().1,
+        });
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(state) = extensions.get_mut::<SpanState>() {
+            let mut visitor = FieldVisitor::new();
+            values.record(&mut visitor);
+            let (_, added) = visitor.finish();
+            // Merge the new members into the existing JSON object.
+            if added != "{}" {
+                if state.fields == "{}" {
+                    state.fields = added;
+                } else {
+                    state.fields.pop();
+                    state.fields.push(',');
+                    state.fields.push_str(&added[1..]);
+                }
+            }
+        }
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(state) = extensions.get_mut::<SpanState>() {
+            state.entered_at = Some(Instant::now());
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(state) = extensions.get_mut::<SpanState>() {
+            if let Some(entered_at) = state.entered_at.take() {
+                state.busy += entered_at.elapsed();
+            }
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(&id).expect("Span not found, this is a bug");
+        let parent_id = span
+            .parent()
+            .and_then(|parent| parent.extensions().get::<SpanState>().map(|state| state.id));
+        let metadata = span.metadata();
+        let state = match span.extensions_mut().remove::<SpanState>() {
+            Some(state) => state,
+            None => return,
+        };
+        self.send(Record::Span {
+            id: state.id,
+            parent_id,
+            name: metadata.name(),
+            target: metadata.target().to_owned(),
+            level: metadata.level().as_str(),
+            opened_millis: state.opened_millis,
+            closed_millis: unix_millis(),
+            busy_nanos: state.busy.as_nanos() as i64,
+            fields: state.fields,
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let span_id = ctx
+            .event_span(event)
+            .and_then(|span| span.extensions().get::<SpanState>().map(|state| state.id));
+        let metadata = event.metadata();
+        let mut visitor = FieldVisitor::new();
+        event.record(&mut visitor);
+        let (message, fields) = visitor.finish();
+        self.send(Record::Event {
+            timestamp_millis: unix_millis(),
+            level: metadata.level().as_str(),
+            target: metadata.target().to_owned(),
+            message,
+            span_id,
+            fields,
+        });
+    }
+}
+
+impl Subscriber {
+    fn send(&self, record: Record) {
+        // Failure means the writer thread is gone; nothing to do.
+        let _ = self
+            .sender
+            .lock()
+            .expect("sqlite lock poisoned")
+            .send(record);
+    }
+}
+
+/// Returns the current time as milliseconds since the Unix epoch.
+fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Renders fields as a JSON object, extracting the `message` field.
+struct FieldVisitor {
+    message: Option<String>,
+    json: String,
+    first: bool,
+}
+
+impl FieldVisitor {
+    fn new() -> Self {
+        Self {
+            message: None,
+            json: String::from("{"),
+            first: true,
+        }
+    }
+
+    fn finish(mut self) -> (Option<String>, String) {
+        self.json.push('}');
+        (self.message, self.json)
+    }
+
+    fn member(&mut self, field: &field::Field) {
+        if !self.first {
+            self.json.push(',');
+        }
+        self.first = false;
+        self.json.push('"');
+        escape_into(&mut self.json, field.name());
+        self.json.push_str("\":");
+    }
+}
+
+impl field::Visit for FieldVisitor {
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.member(field);
+        if value.is_finite() {
+            let _ = write!(self.json, "{}", value);
+        } else {
+            let _ = write!(self.json, "\"{}\"", value);
+        }
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_owned());
+            return;
+        }
+        self.member(field);
+        self.json.push('"');
+        escape_into(&mut self.json, value);
+        self.json.push('"');
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        let rendered = format!("{:?}", value);
+        if field.name() == "message" {
+            self.message = Some(rendered);
+            return;
+        }
+        self.member(field);
+        self.json.push('"');
+        escape_into(&mut self.json, &rendered);
+        self.json.push('"');
+    }
+}
+
+/// Appends `value` to `out` with JSON string escaping.
+fn escape_into(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    struct TempDb(std::path::PathBuf);
+
+    impl TempDb {
+        fn new(name: &str) -> Self {
+            let mut path = std::env::temp_dir();
+            path.push(format!(
+                "tracing-sqlite-test-{}-{}.db",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_file(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+            let _ = std::fs::remove_file(self.0.with_extension("db-wal"));
+            let _ = std::fs::remove_file(self.0.with_extension("db-shm"));
+        }
+    }
+
+    #[test]
+    fn events_and_spans_are_queryable() {
+        let db = TempDb::new("roundtrip");
+        let (subscriber, guard) = open(&db.0).expect("failed to open database");
+        let collector = crate::registry().with(subscriber);
+
+        with_default(collector, || {
+            let outer = tracing::info_span!(target: "app", "request", method = "GET");
+            let _outer = outer.enter();
+            let inner = tracing::info_span!(target: "app", "query");
+            inner.in_scope(|| {
+                tracing::warn!(target: "app::db", rows = 0, "empty result");
+            });
+            tracing::info!(target: "app", "done");
+        });
+        guard.flush();
+
+        let conn = Connection::open(&db.0).expect("failed to reopen database");
+        let mode: String = conn
+            .pragma_query_value(None, "journal_mode", |row| row.get(0))
+            .expect("failed to query journal mode");
+        assert_eq!(mode.to_ascii_lowercase(), "wal");
+
+        let events: i64 = conn
+            .query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))
+            .expect("failed to count events");
+        assert_eq!(events, 2);
+
+        let (message, span_id, fields): (String, i64, String) = conn
+            .query_row(
+                "SELECT message, span_id, fields FROM events WHERE level = 'WARN'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .expect("warn event missing");
+        assert_eq!(message, "empty result");
+        assert_eq!(fields, r#"{"rows":0}"#);
+
+        // The warn event was emitted inside `query`, whose parent is
+        // `request`.
+        let (name, parent_id): (String, Option<i64>) = conn
+            .query_row(
+                "SELECT name, parent_id FROM spans WHERE id = ?1",
+                [span_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("event's span missing");
+        assert_eq!(name, "query");
+        let parent_id = parent_id.expect("`query` has no parent");
+        let (parent, parent_fields, busy): (String, String, i64) = conn
+            .query_row(
+                "SELECT name, fields, busy_nanos FROM spans WHERE id = ?1",
+                [parent_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .expect("parent span missing");
+        assert_eq!(parent, "request");
+        assert_eq!(parent_fields, r#"{"method":"GET"}"#);
+        assert!(busy > 0, "busy_nanos = {}", busy);
+    }
+
+    #[test]
+    fn flush_makes_records_visible_while_recording_continues() {
+        let db = TempDb::new("flush");
+        let (subscriber, guard) = open(&db.0).expect("failed to open database");
+        let collector = crate::registry().with(subscriber);
+
+        with_default(collector, || {
+            tracing::info!(target: "app", "first");
+            guard.flush();
+
+            // A reader sees the first batch while the collector is still
+            // installed.
+            let conn = Connection::open(&db.0).expect("failed to reopen database");
+            let count: i64 = conn
+                .query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))
+                .expect("failed to count events");
+            assert_eq!(count, 1);
+
+            tracing::info!(target: "app", "second");
+        });
+        guard.flush();
+
+        let conn = Connection::open(&db.0).expect("failed to reopen database");
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))
+            .expect("failed to count events");
+        assert_eq!(count, 2);
+    }
+}
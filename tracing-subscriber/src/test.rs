@@ -0,0 +1,620 @@
+//! Utilities for asserting on tracing output in tests.
+//!
+//! This module provides [`capture`], which returns a
+//! [`CaptureSubscriber`] recording events and span lifecycles into a
+//! thread-safe store, and a [`Captured`] handle for inspecting that
+//! store — so tests can assert on what a piece of code emitted without
+//! hand-writing a mock collector:
+//!
+//! ```
+//! use tracing_subscriber::{prelude::*, test};
+//!
+//! let (subscriber, captured) = test::capture();
+//! let _guard = tracing::collect::set_default(
+//!     tracing_subscriber::registry().with(subscriber),
+//! );
+//!
+//! tracing::info!(target: "app::db", rows = 3, "queried");
+//!
+//! captured
+//!     .events()
+//!     .with_target("app::db")
+//!     .with_field("rows", 3)
+//!     .assert_count(1);
+//! ```
+//!
+//! Queries are taken over a snapshot, so a test can keep narrowing one
+//! query or start fresh from [`Captured::events`] at any point. The
+//! `assert_*` methods panic with the matching records in the message,
+//! which makes a failing assertion readable without re-running under a
+//! debugger.
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+use tracing_core::{field, span, Collect, Event, Level};
+
+/// Returns a new [`CaptureSubscriber`] and the [`Captured`] handle
+/// inspecting what it records.
+pub fn capture() -> (CaptureSubscriber, Captured) {
+    let shared = Arc::new(Shared::default());
+    (
+        CaptureSubscriber {
+            shared: shared.clone(),
+        },
+        Captured { shared },
+    )
+}
+
+/// A [`Subscribe`] implementation that records events and span
+/// lifecycles for test assertions.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct CaptureSubscriber {
+    shared: Arc<Shared>,
+}
+
+/// An inspectable handle to the records of a [`CaptureSubscriber`].
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct Captured {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    events: Mutex<Vec<CapturedEvent>>,
+    spans: Mutex<Vec<CapturedSpan>>,
+    // Maps live span IDs to indices in `spans`; entries are removed on
+    // close, since the registry may reuse IDs.
+    live: Mutex<HashMap<u64, usize>>,
+}
+
+/// One recorded event.
+#[derive(Debug, Clone)]
+pub struct CapturedEvent {
+    level: Level,
+    target: String,
+    fields: Vec<(String, CapturedValue)>,
+}
+
+/// One recorded span, including its lifecycle counts.
+#[derive(Debug, Clone)]
+pub struct CapturedSpan {
+    name: &'static str,
+    target: String,
+    level: Level,
+    fields: Vec<(String, CapturedValue)>,
+    times_entered: usize,
+    closed: bool,
+}
+
+/// A recorded field value.
+///
+/// Values recorded through `fmt::Debug` are captured as their rendered
+/// representation, so they compare equal to the equivalent string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CapturedValue {
+    /// A boolean value.
+    Bool(bool),
+    /// A signed integer value.
+    I64(i64),
+    /// An unsigned integer value.
+    U64(u64),
+    /// A floating-point value.
+    F64(f64),
+    /// A string value, or the rendering of a `fmt::Debug` value.
+    Str(String),
+}
+
+/// A filtered view of recorded events.
+///
+/// Returned by [`Captured::events`]; each `with_*` method narrows the
+/// view further.
+#[derive(Debug)]
+pub struct CapturedEvents {
+    events: Vec<CapturedEvent>,
+}
+
+/// A filtered view of recorded spans.
+///
+/// Returned by [`Captured::spans`]; each `with_*` method narrows the
+/// view further.
+#[derive(Debug)]
+pub struct CapturedSpans {
+    spans: Vec<CapturedSpan>,
+}
+
+// === impl Captured ===
+
+impl Captured {
+    /// Returns a query over a snapshot of the recorded events.
+    pub fn events(&self) -> CapturedEvents {
+        CapturedEvents {
+            events: self
+                .shared
+                .events
+                .lock()
+                .expect("capture lock poisoned")
+                .clone(),
+        }
+    }
+
+    /// Returns a query over a snapshot of the recorded spans.
+    pub fn spans(&self) -> CapturedSpans {
+        CapturedSpans {
+            spans: self
+                .shared
+                .spans
+                .lock()
+                .expect("capture lock poisoned")
+                .clone(),
+        }
+    }
+
+    /// Forgets everything recorded so far.
+    ///
+    /// This is useful for asserting that a *section* of a test emits
+    /// something, independently of its setup.
+    pub fn clear(&self) {
+        self.shared
+            .events
+            .lock()
+            .expect("capture lock poisoned")
+            .clear();
+        self.shared
+            .spans
+            .lock()
+            .expect("capture lock poisoned")
+            .clear();
+        // Live spans keep their (now dangling) indices; drop them too so
+        // later lifecycle updates don't touch unrelated records.
+        self.shared
+            .live
+            .lock()
+            .expect("capture lock poisoned")
+            .clear();
+    }
+}
+
+// === impl CapturedEvents ===
+
+impl CapturedEvents {
+    /// Keeps only events with exactly this target.
+    pub fn with_target(mut self, target: &str) -> Self {
+        self.events.retain(|event| event.target == target);
+        self
+    }
+
+    /// Keeps only events at this level.
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.events.retain(|event| event.level == level);
+        self
+    }
+
+    /// Keeps only events whose message is exactly `message`.
+    pub fn with_message(self, message: &str) -> Self {
+        self.with_field("message", message)
+    }
+
+    /// Keeps only events that recorded `name` with this value.
+    pub fn with_field(mut self, name: &str, value: impl Into<CapturedValue>) -> Self {
+        let value = value.into();
+        self.events
+            .retain(|event| event.field(name) == Some(&value));
+        self
+    }
+
+    /// Returns how many events match.
+    pub fn count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns the matching events.
+    pub fn all(self) -> Vec<CapturedEvent> {
+        self.events
+    }
+
+    /// Asserts that exactly `expected` events match, panicking with the
+    /// matching events otherwise.
+    #[track_caller]
+    pub fn assert_count(&self, expected: usize) {
+        assert!(
+            self.events.len() == expected,
+            "expected {} matching events, found {}: {:#?}",
+            expected,
+            self.events.len(),
+            self.events,
+        );
+    }
+}
+
+// === impl CapturedSpans ===
+
+impl CapturedSpans {
+    /// Keeps only spans with exactly this name.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.spans.retain(|span| span.name == name);
+        self
+    }
+
+    /// Keeps only spans with exactly this target.
+    pub fn with_target(mut self, target: &str) -> Self {
+        self.spans.retain(|span| span.target == target);
+        self
+    }
+
+    /// Keeps only spans that recorded `name` with this value.
+    pub fn with_field(mut self, name: &str, value: impl Into<CapturedValue>) -> Self {
+        let value = value.into();
+        self.spans.retain(|span| span.field(name) == Some(&value));
+        self
+    }
+
+    /// Returns how many spans match.
+    pub fn count(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Returns the matching spans.
+    pub fn all(self) -> Vec<CapturedSpan> {
+        self.spans
+    }
+
+    /// Asserts that exactly `expected` spans match, panicking with the
+    /// matching spans otherwise.
+    #[track_caller]
+    pub fn assert_count(&self, expected: usize) {
+        assert!(
+            self.spans.len() == expected,
+            "expected {} matching spans, found {}: {:#?}",
+            expected,
+            self.spans.len(),
+            self.spans,
+        );
+    }
+}
+
+// === impl CapturedEvent ===
+
+impl CapturedEvent {
+    /// Returns the event's level.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Returns the event's target.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the event's message, if it recorded one.
+    pub fn message(&self) -> Option<&str> {
+        match self.field("message") {
+            Some(CapturedValue::Str(message)) => Some(message),
+            _ => None,
+        }
+    }
+
+    /// Returns the value the event recorded for `name`.
+    pub fn field(&self, name: &str) -> Option<&CapturedValue> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+}
+
+// === impl CapturedSpan ===
+
+impl CapturedSpan {
+    /// Returns the span's name.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Returns the span's target.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the span's level.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Returns the value the span recorded for `name`.
+    pub fn field(&self, name: &str) -> Option<&CapturedValue> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns how many times the span was entered.
+    pub fn times_entered(&self) -> usize {
+        self.times_entered
+    }
+
+    /// Returns whether the span has closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+// === impl CapturedValue ===
+
+impl fmt::Display for CapturedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => value.fmt(f),
+            Self::I64(value) => value.fmt(f),
+            Self::U64(value) => value.fmt(f),
+            Self::F64(value) => value.fmt(f),
+            Self::Str(value) => value.fmt(f),
+        }
+    }
+}
+
+impl From<bool> for CapturedValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for CapturedValue {
+    fn from(value: i32) -> Self {
+        Self::I64(value.into())
+    }
+}
+
+impl From<i64> for CapturedValue {
+    fn from(value: i64) -> Self {
+        Self::I64(value)
+    }
+}
+
+impl From<u32> for CapturedValue {
+    fn from(value: u32) -> Self {
+        Self::U64(value.into())
+    }
+}
+
+impl From<u64> for CapturedValue {
+    fn from(value: u64) -> Self {
+        Self::U64(value)
+    }
+}
+
+impl From<f64> for CapturedValue {
+    fn from(value: f64) -> Self {
+        Self::F64(value)
+    }
+}
+
+impl From<&str> for CapturedValue {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_owned())
+    }
+}
+
+impl From<String> for CapturedValue {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+// === impl CaptureSubscriber ===
+
+impl<C> Subscribe<C> for CaptureSubscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, _ctx: Context<'_, C>) {
+        let metadata = attrs.metadata();
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        let mut spans = self.shared.spans.lock().expect("capture lock poisoned");
+        spans.push(CapturedSpan {
+            name: metadata.name(),
+            target: metadata.target().to_owned(),
+            level: *metadata.level(),
+            fields: visitor.fields,
+            times_entered: 0,
+            closed: false,
+        });
+        self.shared
+            .live
+            .lock()
+            .expect("capture lock poisoned")
+            .insert(id.into_u64(), spans.len() - 1);
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, _ctx: Context<'_, C>) {
+        let mut visitor = FieldVisitor::default();
+        values.record(&mut visitor);
+        self.with_span(id, |span| span.fields.append(&mut visitor.fields));
+    }
+
+    fn on_enter(&self, id: &span::Id, _ctx: Context<'_, C>) {
+        self.with_span(id, |span| span.times_entered += 1);
+    }
+
+    fn on_close(&self, id: span::Id, _ctx: Context<'_, C>) {
+        self.with_span(&id, |span| span.closed = true);
+        self.shared
+            .live
+            .lock()
+            .expect("capture lock poisoned")
+            .remove(&id.into_u64());
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        let metadata = event.metadata();
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        self.shared
+            .events
+            .lock()
+            .expect("capture lock poisoned")
+            .push(CapturedEvent {
+                level: *metadata.level(),
+                target: metadata.target().to_owned(),
+                fields: visitor.fields,
+            });
+    }
+}
+
+impl CaptureSubscriber {
+    fn with_span(&self, id: &span::Id, f: impl FnOnce(&mut CapturedSpan)) {
+        let live = self.shared.live.lock().expect("capture lock poisoned");
+        if let Some(&index) = live.get(&id.into_u64()) {
+            let mut spans = self.shared.spans.lock().expect("capture lock poisoned");
+            if let Some(span) = spans.get_mut(index) {
+                f(span);
+            }
+        }
+    }
+}
+
+/// Records fields as [`CapturedValue`]s.
+#[derive(Default)]
+struct FieldVisitor {
+    fields: Vec<(String, CapturedValue)>,
+}
+
+impl field::Visit for FieldVisitor {
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.fields.push((field.name().to_owned(), value.into()));
+    }
+
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.fields.push((field.name().to_owned(), value.into()));
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.fields.push((field.name().to_owned(), value.into()));
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.fields.push((field.name().to_owned(), value.into()));
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.fields.push((field.name().to_owned(), value.into()));
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn fmt::Debug) {
+        self.fields
+            .push((field.name().to_owned(), format!("{:?}", value).into()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn events_are_queryable_by_target_level_and_fields() {
+        let (subscriber, captured) = capture();
+        let collector = crate::registry().with(subscriber);
+        with_default(collector, || {
+            tracing::info!(target: "app::db", rows = 3, "queried");
+            tracing::warn!(target: "app::db", rows = 0, "empty result");
+            tracing::info!(target: "app::http", "request");
+        });
+
+        captured.events().assert_count(3);
+        captured.events().with_target("app::db").assert_count(2);
+        captured
+            .events()
+            .with_target("app::db")
+            .with_level(Level::INFO)
+            .with_field("rows", 3)
+            .assert_count(1);
+        captured.events().with_message("request").assert_count(1);
+
+        let event = captured
+            .events()
+            .with_message("queried")
+            .all()
+            .pop()
+            .expect("event missing");
+        assert_eq!(event.level(), Level::INFO);
+        assert_eq!(event.field("rows"), Some(&CapturedValue::I64(3)));
+    }
+
+    #[test]
+    fn span_lifecycles_are_recorded() {
+        let (subscriber, captured) = capture();
+        let collector = crate::registry().with(subscriber);
+        with_default(collector, || {
+            let span = tracing::info_span!(target: "app", "request", method = "GET");
+            span.in_scope(|| {});
+            span.in_scope(|| {});
+            drop(span);
+
+            let open = tracing::info_span!(target: "app", "open");
+            let _guard = open.enter();
+
+            let spans = captured.spans().with_target("app");
+            spans.assert_count(2);
+            let request = captured
+                .spans()
+                .with_name("request")
+                .all()
+                .pop()
+                .expect("span missing");
+            assert_eq!(request.times_entered(), 2);
+            assert!(request.is_closed());
+            assert_eq!(
+                request.field("method"),
+                Some(&CapturedValue::Str("GET".into()))
+            );
+            let open = captured
+                .spans()
+                .with_name("open")
+                .all()
+                .pop()
+                .expect("span missing");
+            assert!(!open.is_closed());
+        });
+    }
+
+    #[test]
+    fn clear_forgets_earlier_records() {
+        let (subscriber, captured) = capture();
+        let collector = crate::registry().with(subscriber);
+        with_default(collector, || {
+            tracing::info!("setup noise");
+            captured.clear();
+            tracing::info!("the real thing");
+        });
+
+        captured.events().assert_count(1);
+        captured
+            .events()
+            .with_message("the real thing")
+            .assert_count(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 2 matching events, found 1")]
+    fn assert_count_panics_with_the_matching_events() {
+        let (subscriber, captured) = capture();
+        let collector = crate::registry().with(subscriber);
+        with_default(collector, || {
+            tracing::info!("only one");
+        });
+        captured.events().assert_count(2);
+    }
+}
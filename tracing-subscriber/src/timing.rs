@@ -0,0 +1,710 @@
+//! Aggregated span latency histograms.
+//!
+//! The [`fmt`] subscriber can log the busy and idle time of each span as it
+//! closes, but turning those per-span events into percentiles requires an
+//! external metrics pipeline. This module provides a [`Subscriber`] that
+//! aggregates span timings in-process instead: it records the busy and idle
+//! time of every closed span into a per-callsite latency [histogram], so that
+//! the p50/p99 latency of instrumented functions is available directly from
+//! the process.
+//!
+//! Aggregated timings can be consumed in two ways:
+//!
+//! - a [`Handle`] returned by [`Subscriber::new`] exposes a [snapshot
+//!   API](Handle::summaries), returning a [`Summary`] of every callsite
+//!   recorded so far, and
+//! - [`Subscriber::with_summary_interval`] configures the subscriber to
+//!   periodically emit one summary event per callsite, with the target
+//!   `tracing_subscriber::timing`, so summaries show up in ordinary log
+//!   output.
+//!
+//! This module also provides [`SpanTimer`], which records each span's raw
+//! timestamps and busy/idle totals into the span's extensions as a shared
+//! [`Timings`] value, so that exporter subscribers can read one consistent
+//! set of timings (via [`SpanRef::timings`]) instead of each recording their
+//! own.
+//!
+//! The histograms use logarithmic bucketing (like the [HDR histogram]), so
+//! recording is constant-time and allocation-free after the first span of a
+//! callsite closes, and reported percentiles have a relative error of at most
+//! a few percent regardless of magnitude.
+//!
+//! # Examples
+//!
+//! ```
+//! use tracing_subscriber::{prelude::*, timing};
+//!
+//! let (timing, handle) = timing::Subscriber::new();
+//! let collector = tracing_subscriber::registry().with(timing);
+//! # let _ = collector;
+//! // ... some time later ...
+//! for summary in handle.summaries() {
+//!     println!(
+//!         "{}: count={} p50={:?} p99={:?}",
+//!         summary.name(),
+//!         summary.busy().count(),
+//!         summary.busy().percentile(50.0),
+//!         summary.busy().percentile(99.0),
+//!     );
+//! }
+//! ```
+//!
+//! [`fmt`]: mod@crate::fmt
+//! [histogram]: Latencies
+//! [HDR histogram]: https://hdrhistogram.org/
+//! [`SpanRef::timings`]: crate::registry::SpanRef::timings
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing_core::{callsite, span, Collect};
+
+/// A [`Subscribe`] implementation that aggregates span busy and idle times
+/// into per-callsite latency histograms.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    shared: Arc<Shared>,
+    summary_interval: Option<Duration>,
+    last_summary: Mutex<Instant>,
+}
+
+/// Provides access to the latency histograms aggregated by a timing
+/// [`Subscriber`].
+///
+/// This is returned by [`Subscriber::new`], and may be cloned and sent to
+/// other threads freely.
+#[derive(Debug, Clone)]
+pub struct Handle {
+    shared: Arc<Shared>,
+}
+
+/// A snapshot of the latencies recorded for a single callsite.
+///
+/// This is returned by [`Handle::summaries`].
+#[derive(Debug, Clone)]
+pub struct Summary {
+    name: &'static str,
+    target: &'static str,
+    busy: Latencies,
+    idle: Latencies,
+}
+
+/// A snapshot of a single latency histogram.
+///
+/// Because the histogram buckets values logarithmically, the durations
+/// returned by [`percentile`](Latencies::percentile) and
+/// [`max`](Latencies::max) are approximate, with a relative error of at most
+/// a few percent.
+#[derive(Debug, Clone)]
+pub struct Latencies {
+    histogram: Histogram,
+}
+
+/// A [`Subscribe`] implementation that records each span's raw timings into
+/// its extensions, as a [`Timings`] value shared by every subscriber in the
+/// stack.
+///
+/// Exporters frequently need to know when a span was created, entered, and
+/// exited. Without a shared source, each subscriber stores its own `Instant`s
+/// in the span's extensions, duplicating both the storage and the clock
+/// reads, and no two subscribers agree on the exact timestamps. Adding a
+/// single `SpanTimer` to the stack records the timings once; other
+/// subscribers then read the same [`Timings`] value through
+/// [`SpanRef::timings`], so every consumer sees a consistent view.
+///
+/// The recorded [`Timings`] remain readable from a subscriber's `on_close`
+/// implementation, because a span's data is not cleared until every
+/// subscriber has been notified of the close.
+///
+/// Unlike the timing [`Subscriber`], which aggregates latencies across spans
+/// into per-callsite histograms, a `SpanTimer` only records the raw
+/// timestamps and totals of each individual span.
+///
+/// # Examples
+///
+/// ```
+/// use tracing_subscriber::{prelude::*, timing};
+///
+/// let collector = tracing_subscriber::registry()
+///     .with(timing::SpanTimer::new())
+///     .with(an_exporter_reading_timings());
+/// # fn an_exporter_reading_timings<C: tracing_core::Collect>() -> impl tracing_subscriber::Subscribe<C> {
+/// #     tracing_subscriber::subscribe::Identity::new()
+/// # }
+/// # let _ = collector;
+/// ```
+///
+/// [`SpanRef::timings`]: crate::registry::SpanRef::timings
+#[derive(Debug, Default)]
+pub struct SpanTimer {
+    _p: (),
+}
+
+/// The timings recorded for a single span by a [`SpanTimer`].
+///
+/// All timestamps are monotonic [`Instant`]s taken by the [`SpanTimer`], so
+/// timestamps from different spans (and the busy and idle totals) are
+/// mutually consistent.
+#[derive(Debug, Clone)]
+pub struct Timings {
+    created: Instant,
+    first_entered: Option<Instant>,
+    last_exited: Option<Instant>,
+    busy: Duration,
+    idle: Duration,
+    last: Instant,
+}
+
+#[derive(Debug)]
+struct Shared {
+    histograms: Mutex<HashMap<callsite::Identifier, CallsiteTimings>>,
+}
+
+#[derive(Debug)]
+struct CallsiteTimings {
+    name: &'static str,
+    target: &'static str,
+    busy: Histogram,
+    idle: Histogram,
+}
+
+/// The busy and idle time accumulated by a single span, stored in that span's
+/// extensions.
+struct SpanTimings {
+    idle: u64,
+    busy: u64,
+    last: Instant,
+}
+
+/// A latency histogram with logarithmic buckets.
+///
+/// Values are bucketed by their power of two, with each power of two divided
+/// into 16 linear sub-buckets, bounding the relative error of any recorded
+/// value by 1/16th.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Histogram {
+    counts: Vec<u64>,
+    pub(crate) count: u64,
+    pub(crate) sum: u64,
+    pub(crate) max: u64,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new timing subscriber, and a [`Handle`] that provides access
+    /// to the latencies it aggregates.
+    pub fn new() -> (Self, Handle) {
+        let shared = Arc::new(Shared {
+            histograms: Mutex::new(HashMap::new()),
+        });
+        let handle = Handle {
+            shared: shared.clone(),
+        };
+        let this = Self {
+            shared,
+            summary_interval: None,
+            last_summary: Mutex::new(Instant::now()),
+        };
+        (this, handle)
+    }
+
+    /// Configures the subscriber to periodically emit one summary event per
+    /// recorded callsite.
+    ///
+    /// Summary events are emitted at the `INFO` level with the target
+    /// `tracing_subscriber::timing`, and include the span's name and target,
+    /// the number of closed spans, and the p50, p90, p99, and maximum busy
+    /// time. The interval is checked whenever a span closes, so summaries may
+    /// be emitted late (or not at all) if spans close infrequently.
+    ///
+    /// By default, no summary events are emitted.
+    pub fn with_summary_interval(self, interval: Duration) -> Self {
+        Self {
+            summary_interval: Some(interval),
+            ..self
+        }
+    }
+
+    /// Records the timings of a closed span, and returns summaries to emit if
+    /// the summary interval has elapsed.
+    fn record_close(&self, metadata: &tracing_core::Metadata<'static>, timings: &SpanTimings) {
+        let mut idle = timings.idle;
+        let busy = timings.busy;
+        idle += (Instant::now() - timings.last).as_nanos() as u64;
+
+        let mut histograms = self
+            .shared
+            .histograms
+            .lock()
+            .expect("timing histograms poisoned");
+        let callsite = histograms
+            .entry(metadata.callsite())
+            .or_insert_with(|| CallsiteTimings {
+                name: metadata.name(),
+                target: metadata.target(),
+                busy: Histogram::default(),
+                idle: Histogram::default(),
+            });
+        callsite.busy.record(busy);
+        callsite.idle.record(idle);
+        drop(histograms);
+
+        if let Some(interval) = self.summary_interval {
+            let now = Instant::now();
+            let mut last = self
+                .last_summary
+                .lock()
+                .expect("timing summary timestamp poisoned");
+            if now.saturating_duration_since(*last) < interval {
+                return;
+            }
+            *last = now;
+            drop(last);
+            self.emit_summaries();
+        }
+    }
+
+    /// Emits one summary event per recorded callsite.
+    ///
+    /// The summaries are collected before any events are emitted, so that the
+    /// histogram lock is not held while the rest of the collector runs.
+    fn emit_summaries(&self) {
+        for summary in self.shared.summaries() {
+            let busy = summary.busy();
+            tracing::info!(
+                target: "tracing_subscriber::timing",
+                span = summary.name(),
+                span.target = summary.target(),
+                count = busy.count(),
+                busy.p50 = ?busy.percentile(50.0),
+                busy.p90 = ?busy.percentile(90.0),
+                busy.p99 = ?busy.percentile(99.0),
+                busy.max = ?busy.max(),
+                "span timing summary"
+            );
+        }
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if extensions.get_mut::<SpanTimings>().is_none() {
+            extensions.insert(SpanTimings {
+                idle: 0,
+                busy: 0,
+                last: Instant::now(),
+            });
+        }
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(timings) = extensions.get_mut::<SpanTimings>() {
+            let now = Instant::now();
+            timings.idle += (now - timings.last).as_nanos() as u64;
+            timings.last = now;
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(timings) = extensions.get_mut::<SpanTimings>() {
+            let now = Instant::now();
+            timings.busy += (now - timings.last).as_nanos() as u64;
+            timings.last = now;
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(&id).expect("Span not found, this is a bug");
+        let extensions = span.extensions();
+        if let Some(timings) = extensions.get::<SpanTimings>() {
+            let metadata = span.metadata();
+            self.record_close(metadata, timings);
+        }
+    }
+}
+
+// === impl SpanTimer ===
+
+impl SpanTimer {
+    /// Returns a new `SpanTimer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<C> Subscribe<C> for SpanTimer
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if extensions.get_mut::<Timings>().is_none() {
+            extensions.insert(Timings::new());
+        }
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(timings) = extensions.get_mut::<Timings>() {
+            let now = Instant::now();
+            if timings.first_entered.is_none() {
+                timings.first_entered = Some(now);
+            }
+            timings.idle += now - timings.last;
+            timings.last = now;
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let mut extensions = span.extensions_mut();
+        if let Some(timings) = extensions.get_mut::<Timings>() {
+            let now = Instant::now();
+            timings.busy += now - timings.last;
+            timings.last = now;
+            timings.last_exited = Some(now);
+        }
+    }
+}
+
+// === impl Timings ===
+
+impl Timings {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            created: now,
+            first_entered: None,
+            last_exited: None,
+            busy: Duration::ZERO,
+            idle: Duration::ZERO,
+            last: now,
+        }
+    }
+
+    /// Returns the instant at which the span was created.
+    pub fn created(&self) -> Instant {
+        self.created
+    }
+
+    /// Returns the instant at which the span was first entered, or `None` if
+    /// it has never been entered.
+    pub fn first_entered(&self) -> Option<Instant> {
+        self.first_entered
+    }
+
+    /// Returns the instant at which the span was most recently exited, or
+    /// `None` if it has never been exited.
+    pub fn last_exited(&self) -> Option<Instant> {
+        self.last_exited
+    }
+
+    /// Returns the total time for which the span was entered.
+    pub fn busy(&self) -> Duration {
+        self.busy
+    }
+
+    /// Returns the total time for which the span existed but was not
+    /// entered.
+    ///
+    /// The idle total is accumulated when the span is entered, so it does
+    /// not include time elapsed since the span was last exited.
+    pub fn idle(&self) -> Duration {
+        self.idle
+    }
+}
+
+// === impl Handle ===
+
+impl Handle {
+    /// Returns a [`Summary`] of the latencies recorded for each callsite, in
+    /// an unspecified order.
+    ///
+    /// Each summary is a snapshot: latencies recorded after this method
+    /// returns are not reflected in it.
+    pub fn summaries(&self) -> Vec<Summary> {
+        self.shared.summaries()
+    }
+}
+
+impl Shared {
+    fn summaries(&self) -> Vec<Summary> {
+        let histograms = self.histograms.lock().expect("timing histograms poisoned");
+        histograms
+            .values()
+            .map(|callsite| Summary {
+                name: callsite.name,
+                target: callsite.target,
+                busy: Latencies {
+                    histogram: callsite.busy.clone(),
+                },
+                idle: Latencies {
+                    histogram: callsite.idle.clone(),
+                },
+            })
+            .collect()
+    }
+}
+
+// === impl Summary ===
+
+impl Summary {
+    /// Returns the name of the spans summarized by this callsite.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the target of the spans summarized by this callsite.
+    pub fn target(&self) -> &'static str {
+        self.target
+    }
+
+    /// Returns the distribution of time for which these spans were entered.
+    pub fn busy(&self) -> &Latencies {
+        &self.busy
+    }
+
+    /// Returns the distribution of time for which these spans existed without
+    /// being entered.
+    pub fn idle(&self) -> &Latencies {
+        &self.idle
+    }
+}
+
+// === impl Latencies ===
+
+impl Latencies {
+    /// Returns the number of spans recorded in this histogram.
+    pub fn count(&self) -> u64 {
+        self.histogram.count
+    }
+
+    /// Returns the approximate value at the given percentile of this
+    /// histogram, where `percentile` is in the range `0.0..=100.0`.
+    ///
+    /// Returns [`Duration::ZERO`] if no spans have been recorded.
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        Duration::from_nanos(self.histogram.percentile(percentile))
+    }
+
+    /// Returns the approximate maximum recorded value.
+    ///
+    /// Returns [`Duration::ZERO`] if no spans have been recorded.
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.histogram.max)
+    }
+
+    /// Returns the mean of the recorded values.
+    ///
+    /// Returns [`Duration::ZERO`] if no spans have been recorded.
+    pub fn mean(&self) -> Duration {
+        if self.histogram.count == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos(self.histogram.sum / self.histogram.count)
+    }
+}
+
+// === impl Histogram ===
+
+/// The number of linear sub-buckets per power of two.
+const SUB_BUCKETS: u64 = 16;
+
+impl Histogram {
+    pub(crate) fn record(&mut self, value: u64) {
+        let index = Self::index_for(value);
+        if self.counts.len() <= index {
+            self.counts.resize(index + 1, 0);
+        }
+        self.counts[index] += 1;
+        self.count += 1;
+        self.sum = self.sum.saturating_add(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Returns the bucket index for `value`.
+    ///
+    /// Values below `SUB_BUCKETS` each get their own bucket; above that, each
+    /// power of two is divided into `SUB_BUCKETS` linear sub-buckets.
+    fn index_for(value: u64) -> usize {
+        if value < SUB_BUCKETS {
+            return value as usize;
+        }
+        let exponent = 63 - value.leading_zeros() as u64;
+        let sub_bucket = (value >> (exponent - 4)) & (SUB_BUCKETS - 1);
+        (SUB_BUCKETS + (exponent - 4) * SUB_BUCKETS + sub_bucket) as usize
+    }
+
+    /// Returns a representative value (the bucket midpoint) for the bucket at
+    /// `index`.
+    fn value_for(index: usize) -> u64 {
+        let index = index as u64;
+        if index < SUB_BUCKETS {
+            return index;
+        }
+        let exponent = 4 + (index - SUB_BUCKETS) / SUB_BUCKETS;
+        let sub_bucket = (index - SUB_BUCKETS) % SUB_BUCKETS;
+        let lower = (SUB_BUCKETS + sub_bucket) << (exponent - 4);
+        lower + (1 << (exponent - 4)) / 2
+    }
+
+    pub(crate) fn percentile(&self, percentile: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let rank = ((percentile / 100.0) * self.count as f64).ceil() as u64;
+        let rank = rank.clamp(1, self.count);
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Self::value_for(index);
+            }
+        }
+        self.max
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn histogram_percentiles() {
+        let mut histogram = Histogram::default();
+        for value in 1..=1000 {
+            histogram.record(value);
+        }
+        assert_eq!(histogram.count, 1000);
+        assert_eq!(histogram.max, 1000);
+
+        // Logarithmic bucketing bounds the relative error by 1/16th.
+        let p50 = histogram.percentile(50.0);
+        assert!((468..=532).contains(&p50), "p50 was {}", p50);
+        let p99 = histogram.percentile(99.0);
+        assert!((928..=1052).contains(&p99), "p99 was {}", p99);
+        assert_eq!(histogram.percentile(100.0), histogram.percentile(99.9));
+    }
+
+    #[test]
+    fn histogram_small_values_are_exact() {
+        let mut histogram = Histogram::default();
+        for value in 0..16 {
+            histogram.record(value);
+        }
+        for (index, &count) in histogram.counts.iter().enumerate() {
+            assert_eq!(count, 1, "bucket {} should have one value", index);
+            assert_eq!(Histogram::value_for(index), index as u64);
+        }
+    }
+
+    #[test]
+    fn records_closed_spans() {
+        let (timing, handle) = Subscriber::new();
+        let collector = crate::registry().with(timing);
+        with_default(collector, || {
+            for _ in 0..3 {
+                let span = tracing::info_span!("my_span");
+                let _entered = span.enter();
+            }
+            tracing::info_span!("other_span");
+        });
+
+        let mut summaries = handle.summaries();
+        summaries.sort_by_key(|summary| summary.name());
+
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].name(), "my_span");
+        assert_eq!(summaries[0].target(), module_path!());
+        assert_eq!(summaries[0].busy().count(), 3);
+        assert_eq!(summaries[1].name(), "other_span");
+        assert_eq!(summaries[1].busy().count(), 1);
+    }
+
+    /// Captures the closed span's [`Timings`] so the test can inspect them.
+    struct Probe {
+        seen: Arc<Mutex<Option<Timings>>>,
+    }
+
+    impl<C> Subscribe<C> for Probe
+    where
+        C: Collect + for<'a> LookupSpan<'a>,
+    {
+        fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+            let span = ctx.span(&id).expect("Span not found, this is a bug");
+            *self.seen.lock().unwrap() = span.timings();
+        }
+    }
+
+    #[test]
+    fn span_timer_records_timings() {
+        let seen = Arc::new(Mutex::new(None));
+        let collector = crate::registry()
+            .with(SpanTimer::new())
+            .with(Probe { seen: seen.clone() });
+
+        with_default(collector, || {
+            let span = tracing::info_span!("timed");
+            {
+                let _entered = span.enter();
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            std::thread::sleep(Duration::from_millis(5));
+            let _entered = span.enter();
+        });
+
+        let timings = seen
+            .lock()
+            .unwrap()
+            .take()
+            .expect("timings should have been recorded");
+        let first_entered = timings.first_entered().expect("span was entered");
+        let last_exited = timings.last_exited().expect("span was exited");
+        assert!(first_entered >= timings.created());
+        assert!(last_exited >= first_entered);
+        assert!(timings.busy() >= Duration::from_millis(5));
+        assert!(timings.idle() >= Duration::from_millis(5));
+    }
+
+    #[test]
+    fn span_timer_never_entered() {
+        let seen = Arc::new(Mutex::new(None));
+        let collector = crate::registry()
+            .with(SpanTimer::new())
+            .with(Probe { seen: seen.clone() });
+
+        with_default(collector, || {
+            tracing::info_span!("unentered");
+        });
+
+        let timings = seen
+            .lock()
+            .unwrap()
+            .take()
+            .expect("timings should have been recorded");
+        assert!(timings.first_entered().is_none());
+        assert!(timings.last_exited().is_none());
+        assert_eq!(timings.busy(), Duration::ZERO);
+    }
+}
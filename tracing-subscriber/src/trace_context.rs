@@ -0,0 +1,424 @@
+//! [W3C Trace Context] propagation for distributed tracing.
+//!
+//! When a request crosses a process boundary, the services on either side
+//! can only correlate their spans if they agree on a trace identity. The
+//! W3C Trace Context specification standardizes that identity as a pair of
+//! HTTP headers: `traceparent`, carrying a 128-bit trace ID, a 64-bit span
+//! ID, and flags; and `tracestate`, carrying vendor-specific baggage. This
+//! module provides the propagation mechanics without the full
+//! OpenTelemetry SDK.
+//!
+//! The [`Subscriber`] assigns each root span a fresh trace ID (or adopts
+//! one from a remote parent established with [`with_remote_parent`]) and
+//! each span a span ID, stored in the span's extensions. On the way out of
+//! a process, [`current`] returns the entered span's [`TraceContext`] and
+//! [`inject`] writes it into a header carrier; on the way in, [`extract`]
+//! parses the carrier back into a context.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::collections::HashMap;
+//! use tracing_subscriber::{trace_context, prelude::*};
+//!
+//! tracing_subscriber::registry()
+//!     .with(trace_context::Subscriber::new())
+//!     .init();
+//!
+//! // Server side: adopt the caller's trace before opening the root span.
+//! let headers: HashMap<String, String> = HashMap::new();
+//! let parent = trace_context::extract(&headers);
+//! trace_context::with_remote_parent(parent, || {
+//!     let span = tracing::info_span!("handle_request");
+//!     let _entered = span.enter();
+//!
+//!     // Client side: pass the current context on to the next service.
+//!     let mut outgoing = HashMap::new();
+//!     if let Some(context) = trace_context::current() {
+//!         trace_context::inject(&context, &mut outgoing);
+//!     }
+//! });
+//! ```
+//!
+//! [W3C Trace Context]: https://www.w3.org/TR/trace-context/
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{cell::RefCell, collections::HashMap};
+use tracing_core::{span, Collect};
+
+thread_local! {
+    /// The contexts of the entered spans on this thread, innermost last.
+    static CURRENT: RefCell<Vec<TraceContext>> = const { RefCell::new(Vec::new()) };
+    /// A remote parent for root spans, established by
+    /// [`with_remote_parent`].
+    static REMOTE: RefCell<Option<TraceContext>> = const { RefCell::new(None) };
+}
+
+/// A span's identity within a distributed trace.
+///
+/// This is what travels in the `traceparent` and `tracestate` headers:
+/// the trace ID shared by every span in the request, the ID of one
+/// specific span, the trace flags, and any vendor baggage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    trace_id: u128,
+    span_id: u64,
+    flags: u8,
+    tracestate: String,
+}
+
+/// A [`Subscribe`] implementation that assigns trace contexts to spans.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Default)]
+pub struct Subscriber {
+    _private: (),
+}
+
+/// A carrier that trace context headers can be written into.
+///
+/// This is implemented for `HashMap<String, String>`; HTTP clients with
+/// their own header types can implement it in one line.
+pub trait Injector {
+    /// Sets a header on the outgoing request.
+    fn set(&mut self, name: &'static str, value: String);
+}
+
+/// A carrier that trace context headers can be read from.
+///
+/// This is implemented for `HashMap<String, String>`, keyed by lowercase
+/// header name; HTTP servers with their own header types can implement it
+/// in one line.
+pub trait Extractor {
+    /// Returns the value of a header on the incoming request, if present.
+    fn get(&self, name: &str) -> Option<&str>;
+}
+
+// === impl TraceContext ===
+
+impl TraceContext {
+    /// Returns the 128-bit trace ID shared by every span in the trace.
+    pub fn trace_id(&self) -> u128 {
+        self.trace_id
+    }
+
+    /// Returns the 64-bit ID of this context's span.
+    pub fn span_id(&self) -> u64 {
+        self.span_id
+    }
+
+    /// Returns whether the sampled flag is set.
+    pub fn is_sampled(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    /// Returns the `tracestate` baggage, which may be empty.
+    pub fn tracestate(&self) -> &str {
+        &self.tracestate
+    }
+
+    /// Renders this context as a `traceparent` header value, such as
+    /// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+    pub fn traceparent(&self) -> String {
+        format!(
+            "00-{:032x}-{:016x}-{:02x}",
+            self.trace_id, self.span_id, self.flags,
+        )
+    }
+
+    /// Parses a `traceparent` header value, returning `None` if it is
+    /// malformed.
+    pub fn parse(traceparent: &str) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next()?;
+        if version.len() != 2 || version == "ff" || !is_hex(version) {
+            return None;
+        }
+        let trace_id = parts.next()?;
+        if trace_id.len() != 32 || !is_hex(trace_id) {
+            return None;
+        }
+        let span_id = parts.next()?;
+        if span_id.len() != 16 || !is_hex(span_id) {
+            return None;
+        }
+        let flags = parts.next()?;
+        if flags.len() != 2 || !is_hex(flags) {
+            return None;
+        }
+        // Unknown future versions may append fields, but version 00 ends
+        // here.
+        if version == "00" && parts.next().is_some() {
+            return None;
+        }
+        let trace_id = u128::from_str_radix(trace_id, 16).ok()?;
+        let span_id = u64::from_str_radix(span_id, 16).ok()?;
+        // All-zero identifiers are explicitly invalid.
+        if trace_id == 0 || span_id == 0 {
+            return None;
+        }
+        Some(Self {
+            trace_id,
+            span_id,
+            flags: u8::from_str_radix(flags, 16).ok()?,
+            tracestate: String::new(),
+        })
+    }
+}
+
+/// Returns the context of the most recently entered span on this thread,
+/// if any.
+pub fn current() -> Option<TraceContext> {
+    CURRENT.with(|current| current.borrow().last().cloned())
+}
+
+/// Runs `f` with `parent` as the remote parent: root spans created inside
+/// adopt its trace ID, flags, and `tracestate` rather than starting a new
+/// trace.
+///
+/// Passing `None` is allowed so that the result of [`extract`] can be
+/// forwarded unconditionally.
+pub fn with_remote_parent<T>(parent: Option<TraceContext>, f: impl FnOnce() -> T) -> T {
+    let previous = REMOTE.with(|remote| remote.replace(parent));
+    let result = f();
+    REMOTE.with(|remote| remote.replace(previous));
+    result
+}
+
+/// Writes `context` into a header carrier as `traceparent` and, if it has
+/// baggage, `tracestate`.
+pub fn inject(context: &TraceContext, carrier: &mut impl Injector) {
+    carrier.set("traceparent", context.traceparent());
+    if !context.tracestate.is_empty() {
+        carrier.set("tracestate", context.tracestate.clone());
+    }
+}
+
+/// Reads a context from a header carrier, returning `None` if there is no
+/// valid `traceparent`.
+///
+/// A `tracestate` header is carried along verbatim; per the
+/// specification, it is ignored without a valid `traceparent`.
+pub fn extract(carrier: &impl Extractor) -> Option<TraceContext> {
+    let mut context = TraceContext::parse(carrier.get("traceparent")?)?;
+    if let Some(tracestate) = carrier.get("tracestate") {
+        context.tracestate = tracestate.trim().to_owned();
+    }
+    Some(context)
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new trace context subscriber.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+
+        let parent = if attrs.is_root() {
+            None
+        } else if attrs.is_contextual() {
+            ctx.lookup_current()
+        } else {
+            attrs.parent().and_then(|id| ctx.span(id))
+        };
+        let inherited = match parent {
+            Some(parent) => parent.extensions().get::<TraceContext>().cloned(),
+            None => REMOTE.with(|remote| remote.borrow().clone()),
+        };
+        let context = match inherited {
+            Some(parent) => TraceContext {
+                trace_id: parent.trace_id,
+                span_id: random_id(),
+                flags: parent.flags,
+                tracestate: parent.tracestate,
+            },
+            None => TraceContext {
+                trace_id: (u128::from(random_id()) << 64) | u128::from(random_id()),
+                span_id: random_id(),
+                flags: 0x01,
+                tracestate: String::new(),
+            },
+        };
+        // Publish the identifiers through the layer-agnostic protocol as
+        // well, so application code can read them back without knowing
+        // which subscriber assigned them.
+        let mut extensions = span.extensions_mut();
+        extensions.insert(crate::span_ids::SpanIds::new(
+            context.trace_id,
+            context.span_id,
+        ));
+        extensions.insert(context);
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let extensions = span.extensions();
+        if let Some(context) = extensions.get::<TraceContext>() {
+            let context = context.clone();
+            CURRENT.with(|current| current.borrow_mut().push(context));
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        if span.extensions().get::<TraceContext>().is_some() {
+            CURRENT.with(|current| current.borrow_mut().pop());
+        }
+    }
+}
+
+impl Injector for HashMap<String, String> {
+    fn set(&mut self, name: &'static str, value: String) {
+        self.insert(name.to_owned(), value);
+    }
+}
+
+impl Extractor for HashMap<String, String> {
+    fn get(&self, name: &str) -> Option<&str> {
+        HashMap::get(self, name).map(String::as_str)
+    }
+}
+
+/// Returns whether `value` is entirely lowercase hex digits.
+fn is_hex(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
+}
+
+/// Returns a pseudo-random non-zero 64-bit identifier.
+///
+/// This uses the standard library's randomly-seeded hasher rather than a
+/// cryptographic RNG; IDs are unique in practice but not unpredictable.
+fn random_id() -> u64 {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+        sync::atomic::{AtomicU64, Ordering},
+    };
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish().max(1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn traceparents_round_trip() {
+        let context = TraceContext::parse(TRACEPARENT).expect("failed to parse");
+        assert_eq!(context.trace_id(), 0x4bf92f3577b34da6a3ce929d0e0e4736);
+        assert_eq!(context.span_id(), 0x00f067aa0ba902b7);
+        assert!(context.is_sampled());
+        assert_eq!(context.traceparent(), TRACEPARENT);
+    }
+
+    #[test]
+    fn malformed_traceparents_are_rejected() {
+        // Truncated, bad version, zero trace ID, zero span ID, uppercase
+        // hex, and trailing fields on version 00.
+        for invalid in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        ] {
+            assert_eq!(TraceContext::parse(invalid), None, "accepted {:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn headers_inject_and_extract() {
+        let mut headers = HashMap::new();
+        headers.insert("traceparent".to_owned(), TRACEPARENT.to_owned());
+        headers.insert("tracestate".to_owned(), "vendor=opaque".to_owned());
+
+        let context = extract(&headers).expect("failed to extract");
+        assert_eq!(context.tracestate(), "vendor=opaque");
+
+        let mut outgoing = HashMap::new();
+        inject(&context, &mut outgoing);
+        assert_eq!(
+            outgoing.get("traceparent").map(String::as_str),
+            Some(TRACEPARENT)
+        );
+        assert_eq!(
+            outgoing.get("tracestate").map(String::as_str),
+            Some("vendor=opaque"),
+        );
+    }
+
+    #[test]
+    fn spans_share_a_trace_and_get_unique_ids() {
+        let collector = crate::registry().with(Subscriber::new());
+        with_default(collector, || {
+            assert_eq!(current(), None);
+
+            let parent = tracing::info_span!("parent");
+            let _entered = parent.enter();
+            let outer = current().expect("no context for parent span");
+            assert_ne!(outer.trace_id(), 0);
+            assert!(outer.is_sampled());
+
+            {
+                let child = tracing::info_span!("child");
+                let _entered = child.enter();
+                let inner = current().expect("no context for child span");
+                assert_eq!(inner.trace_id(), outer.trace_id());
+                assert_ne!(inner.span_id(), outer.span_id());
+            }
+
+            assert_eq!(current(), Some(outer));
+        });
+    }
+
+    #[test]
+    fn root_spans_adopt_remote_parents() {
+        let remote = {
+            let mut headers = HashMap::new();
+            headers.insert("traceparent".to_owned(), TRACEPARENT.to_owned());
+            headers.insert("tracestate".to_owned(), "vendor=opaque".to_owned());
+            extract(&headers)
+        };
+
+        let collector = crate::registry().with(Subscriber::new());
+        with_default(collector, || {
+            with_remote_parent(remote, || {
+                let span = tracing::info_span!("handle_request");
+                let _entered = span.enter();
+                let context = current().expect("no context for root span");
+                assert_eq!(context.trace_id(), 0x4bf92f3577b34da6a3ce929d0e0e4736);
+                assert_ne!(context.span_id(), 0x00f067aa0ba902b7);
+                assert_eq!(context.tracestate(), "vendor=opaque");
+            });
+
+            // Outside the closure, roots start fresh traces again.
+            let span = tracing::info_span!("background_work");
+            let _entered = span.enter();
+            let context = current().expect("no context for root span");
+            assert_ne!(context.trace_id(), 0x4bf92f3577b34da6a3ce929d0e0e4736);
+        });
+    }
+}
@@ -0,0 +1,421 @@
+//! A live terminal viewer for events and currently-open spans.
+//!
+//! This module provides a [`Subscriber`] and [`Viewer`] pair: the
+//! subscriber records recent events and the tree of currently-open spans,
+//! and the viewer repeatedly redraws them in the terminal — a lightweight,
+//! [`tokio-console`]-style live view for any `tracing`-using program, with
+//! no connection setup and no extra process. The view shows:
+//!
+//! - the tree of currently-open spans, indented by parentage, with each
+//!   span's elapsed time;
+//! - the most recent events, colored by level, with their target and
+//!   message;
+//! - the active filter, adjustable from the keyboard while the program
+//!   runs.
+//!
+//! Keyboard commands are line-based (type the command, then press enter):
+//! `e`, `w`, `i`, `d`, and `t` set the minimum level to `ERROR` through
+//! `TRACE`, `/foo` shows only events whose target contains `foo`, `c`
+//! clears the filters, and `q` quits the viewer. Filters affect only the
+//! view, never what the program records.
+//!
+//! The viewer draws with plain ANSI escape sequences, so it works in any
+//! ANSI-capable terminal and carries no UI dependencies.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{prelude::*, tui};
+//!
+//! let (subscriber, viewer) = tui::Subscriber::new();
+//! let collector = tracing_subscriber::registry().with(subscriber);
+//! tracing::collect::set_global_default(collector)
+//!     .expect("failed to set global default collector");
+//!
+//! // Run the workload on other threads, and the viewer on this one; it
+//! // returns when `q` is entered.
+//! viewer.run().expect("viewer failed");
+//! ```
+//!
+//! [`tokio-console`]: https://crates.io/crates/tokio-console
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Write as _,
+    io::{self, BufRead, Write},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing_core::{field, span, Collect, Event, Level};
+
+/// How many recent events are retained for display.
+const EVENT_CAPACITY: usize = 128;
+
+/// A [`Subscribe`] implementation that records live state for a
+/// [`Viewer`].
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    shared: Arc<Shared>,
+}
+
+/// Renders the live view recorded by a [`Subscriber`].
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Viewer {
+    shared: Arc<Shared>,
+    min_level: Level,
+    target_filter: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    events: Mutex<VecDeque<RecordedEvent>>,
+    spans: Mutex<HashMap<u64, OpenSpan>>,
+}
+
+#[derive(Debug, Clone)]
+struct RecordedEvent {
+    level: Level,
+    target: String,
+    message: String,
+}
+
+#[derive(Debug)]
+struct OpenSpan {
+    name: &'static str,
+    parent: Option<u64>,
+    opened: Instant,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new `Subscriber` and the [`Viewer`] displaying what it
+    /// records.
+    pub fn new() -> (Self, Viewer) {
+        let shared = Arc::new(Shared::default());
+        let viewer = Viewer {
+            shared: shared.clone(),
+            min_level: Level::TRACE,
+            target_filter: None,
+        };
+        (Self { shared }, viewer)
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, C>) {
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let parent = span.parent().map(|parent| parent.id().into_u64());
+        self.shared.spans.lock().expect("tui lock poisoned").insert(
+            id.into_u64(),
+            OpenSpan {
+                name: attrs.metadata().name(),
+                parent,
+                opened: Instant::now(),
+            },
+        );
+    }
+
+    fn on_close(&self, id: span::Id, _ctx: Context<'_, C>) {
+        self.shared
+            .spans
+            .lock()
+            .expect("tui lock poisoned")
+            .remove(&id.into_u64());
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let mut events = self.shared.events.lock().expect("tui lock poisoned");
+        if events.len() == EVENT_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(RecordedEvent {
+            level: *event.metadata().level(),
+            target: event.metadata().target().to_owned(),
+            message: visitor.message,
+        });
+    }
+}
+
+// === impl Viewer ===
+
+impl Viewer {
+    /// Runs the viewer on the current thread, redrawing to standard
+    /// output until `q` is entered.
+    ///
+    /// A second thread reads keyboard commands from standard input; see
+    /// the [module-level documentation](self) for the command set.
+    pub fn run(mut self) -> io::Result<()> {
+        let (tx, rx) = std::sync::mpsc::channel::<String>();
+        std::thread::Builder::new()
+            .name("tracing-tui-input".into())
+            .spawn(move || {
+                let stdin = io::stdin();
+                for line in stdin.lock().lines() {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(_) => return,
+                    };
+                    if tx.send(line).is_err() {
+                        return;
+                    }
+                }
+            })?;
+
+        let mut stdout = io::stdout();
+        loop {
+            while let Ok(command) = rx.try_recv() {
+                if !self.apply_command(command.trim()) {
+                    return Ok(());
+                }
+            }
+            let mut frame = String::new();
+            self.render(&mut frame);
+            stdout.write_all(b"\x1b[2J\x1b[H")?;
+            stdout.write_all(frame.as_bytes())?;
+            stdout.flush()?;
+            std::thread::sleep(Duration::from_millis(250));
+        }
+    }
+
+    /// Applies one keyboard command, returning `false` on quit.
+    fn apply_command(&mut self, command: &str) -> bool {
+        match command {
+            "q" => return false,
+            "e" => self.min_level = Level::ERROR,
+            "w" => self.min_level = Level::WARN,
+            "i" => self.min_level = Level::INFO,
+            "d" => self.min_level = Level::DEBUG,
+            "t" => self.min_level = Level::TRACE,
+            "c" => {
+                self.min_level = Level::TRACE;
+                self.target_filter = None;
+            }
+            _ => {
+                if let Some(target) = command.strip_prefix('/') {
+                    self.target_filter = Some(target.to_owned());
+                }
+            }
+        }
+        true
+    }
+
+    /// Renders one frame of the view into `out`.
+    fn render(&self, out: &mut String) {
+        let _ = writeln!(
+            out,
+            "tracing | level ≤ {} | target ~ {} | e/w/i/d/t level, /target, c clear, q quit",
+            self.min_level,
+            self.target_filter.as_deref().unwrap_or("*"),
+        );
+
+        let _ = writeln!(out, "\nopen spans:");
+        let spans = self.shared.spans.lock().expect("tui lock poisoned");
+        let mut children: HashMap<Option<u64>, Vec<u64>> = HashMap::new();
+        for (id, span) in spans.iter() {
+            // Treat spans whose parent has already closed as roots.
+            let parent = span.parent.filter(|parent| spans.contains_key(parent));
+            children.entry(parent).or_default().push(*id);
+        }
+        for ids in children.values_mut() {
+            ids.sort_by_key(|id| spans[id].opened);
+        }
+        let mut stack: Vec<(u64, usize)> = children
+            .get(&None)
+            .into_iter()
+            .flatten()
+            .rev()
+            .map(|id| (*id, 0))
+            .collect();
+        while let Some((id, depth)) = stack.pop() {
+            let span = &spans[&id];
+            let _ = writeln!(
+                out,
+                "  {:indent$}{} ({})",
+                "",
+                span.name,
+                Elapsed(span.opened.elapsed()),
+                indent = depth * 2,
+            );
+            if let Some(ids) = children.get(&Some(id)) {
+                stack.extend(ids.iter().rev().map(|id| (*id, depth + 1)));
+            }
+        }
+        drop(spans);
+
+        let _ = writeln!(out, "\nevents:");
+        let events = self.shared.events.lock().expect("tui lock poisoned");
+        for event in events.iter() {
+            if event.level > self.min_level {
+                continue;
+            }
+            if let Some(target) = &self.target_filter {
+                if !event.target.contains(target.as_str()) {
+                    continue;
+                }
+            }
+            let _ = writeln!(
+                out,
+                "  \x1b[{}m{:>5}\x1b[0m {}: {}",
+                level_color(&event.level),
+                event.level,
+                event.target,
+                event.message,
+            );
+        }
+    }
+}
+
+/// Returns the ANSI color code used for a level.
+fn level_color(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 31, // red
+        Level::WARN => 33,  // yellow
+        Level::INFO => 32,  // green
+        Level::DEBUG => 34, // blue
+        Level::TRACE => 35, // magenta
+    }
+}
+
+/// Formats an elapsed duration at a glanceable precision.
+struct Elapsed(Duration);
+
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let secs = self.0.as_secs_f64();
+        if secs >= 1.0 {
+            write!(f, "{:.1}s", secs)
+        } else {
+            write!(f, "{}ms", self.0.as_millis())
+        }
+    }
+}
+
+/// Records an event's `message` field.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl field::Visit for MessageVisitor {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_owned();
+        }
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn open_spans_render_as_a_tree() {
+        let (subscriber, viewer) = Subscriber::new();
+        let collector = crate::registry().with(subscriber);
+        with_default(collector, || {
+            let outer = tracing::info_span!("outer");
+            let _outer = outer.enter();
+            let inner = tracing::info_span!("inner");
+            let _inner = inner.enter();
+
+            let mut frame = String::new();
+            viewer.render(&mut frame);
+            let outer_line = frame
+                .lines()
+                .find(|line| line.contains("outer"))
+                .expect("outer span missing");
+            let inner_line = frame
+                .lines()
+                .find(|line| line.contains("inner"))
+                .expect("inner span missing");
+            assert!(outer_line.starts_with("  outer"), "{}", frame);
+            assert!(inner_line.starts_with("    inner"), "{}", frame);
+        });
+    }
+
+    #[test]
+    fn closed_spans_leave_the_view() {
+        let (subscriber, viewer) = Subscriber::new();
+        let collector = crate::registry().with(subscriber);
+        with_default(collector, || {
+            tracing::info_span!("ephemeral").in_scope(|| {});
+            let mut frame = String::new();
+            viewer.render(&mut frame);
+            assert!(!frame.contains("ephemeral"), "{}", frame);
+        });
+    }
+
+    #[test]
+    fn events_are_colored_by_level() {
+        let (subscriber, viewer) = Subscriber::new();
+        let collector = crate::registry().with(subscriber);
+        with_default(collector, || {
+            tracing::error!("broken");
+            tracing::info!("fine");
+        });
+
+        let mut frame = String::new();
+        viewer.render(&mut frame);
+        assert!(frame.contains("\x1b[31m"), "{}", frame);
+        assert!(frame.contains("broken"), "{}", frame);
+        assert!(frame.contains("\x1b[32m"), "{}", frame);
+        assert!(frame.contains("fine"), "{}", frame);
+    }
+
+    #[test]
+    fn keyboard_commands_filter_the_view() {
+        let (subscriber, mut viewer) = Subscriber::new();
+        let collector = crate::registry().with(subscriber);
+        with_default(collector, || {
+            tracing::info!(target: "app::db", "query");
+            tracing::debug!(target: "app::http", "request");
+        });
+
+        // Raise the minimum level to INFO.
+        assert!(viewer.apply_command("i"));
+        let mut frame = String::new();
+        viewer.render(&mut frame);
+        assert!(frame.contains("query"), "{}", frame);
+        assert!(!frame.contains("request"), "{}", frame);
+
+        // Filter by target substring.
+        assert!(viewer.apply_command("t"));
+        assert!(viewer.apply_command("/http"));
+        let mut frame = String::new();
+        viewer.render(&mut frame);
+        assert!(!frame.contains("query"), "{}", frame);
+        assert!(frame.contains("request"), "{}", frame);
+
+        // Clear filters; `q` quits.
+        assert!(viewer.apply_command("c"));
+        let mut frame = String::new();
+        viewer.render(&mut frame);
+        assert!(
+            frame.contains("query") && frame.contains("request"),
+            "{}",
+            frame
+        );
+        assert!(!viewer.apply_command("q"));
+    }
+}
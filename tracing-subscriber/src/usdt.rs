@@ -0,0 +1,380 @@
+//! Exposes span lifecycle and event dispatch as USDT probe points.
+//!
+//! USDT (user statically-defined tracing) probes are the SystemTap SDT
+//! mechanism consumed by `bpftrace`, BCC, and `perf probe`: each probe
+//! site compiles to a single `nop` plus an ELF note describing its address
+//! and arguments, so an eBPF script can attach to a production binary and
+//! observe tracing activity without that binary writing anything anywhere.
+//! This module provides a [`Subscriber`] that fires one probe per span
+//! lifecycle stage — `span_new`, `span_enter`, `span_exit`, `span_close` —
+//! and one for event dispatch, under the `tracing` provider.
+//!
+//! Each probe receives the span ID (or zero), a pointer to the
+//! NUL-terminated span name or event message, a pointer to the
+//! NUL-terminated target, and the verbosity level (1 for `ERROR` through 5
+//! for `TRACE`) as its four arguments:
+//!
+//! ```text
+//! bpftrace -e 'usdt:./myapp:tracing:span_enter {
+//!     printf("%s %s\n", str(arg1), str(arg2));
+//! }'
+//! ```
+//!
+//! Every probe carries an SDT *semaphore*, which attached tracers
+//! increment: while nothing is attached the subscriber skips argument
+//! setup after a single relaxed load, and the probe site itself is an
+//! untaken branch, so the cost in production is close to zero.
+//!
+//! The probe sites are emitted on Linux for the `x86_64` and `aarch64`
+//! architectures; elsewhere this module compiles to no-ops so that crates
+//! enabling it remain portable.
+//!
+//! # Examples
+//!
+//! ```
+//! use tracing_subscriber::{prelude::*, usdt};
+//!
+//! let collector = tracing_subscriber::registry().with(usdt::Subscriber::new());
+//! # let _ = collector;
+//! ```
+use crate::{
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    sync::{Arc, Mutex},
+};
+use tracing_core::{callsite, field, span, Collect, Event, Level, Metadata};
+
+/// A [`Subscribe`] implementation that fires USDT probes for span
+/// lifecycle transitions and event dispatch.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Default)]
+pub struct Subscriber {
+    /// NUL-terminated name and target strings per callsite, so probe
+    /// arguments can point at stable memory.
+    callsites: Mutex<HashMap<callsite::Identifier, Arc<CallsiteStrings>>>,
+}
+
+#[derive(Debug)]
+struct CallsiteStrings {
+    name: CString,
+    target: CString,
+}
+
+// === impl Subscriber ===
+
+impl Subscriber {
+    /// Returns a new `Subscriber`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached NUL-terminated strings for a callsite,
+    /// converting them on first use.
+    fn strings(&self, metadata: &Metadata<'_>) -> Arc<CallsiteStrings> {
+        let mut callsites = self.callsites.lock().expect("usdt lock poisoned");
+        callsites
+            .entry(metadata.callsite())
+            .or_insert_with(|| {
+                Arc::new(CallsiteStrings {
+                    name: to_cstring(metadata.name()),
+                    target: to_cstring(metadata.target()),
+                })
+            })
+            .clone()
+    }
+}
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, _ctx: Context<'_, C>) {
+        if !probes::span_new_enabled() {
+            return;
+        }
+        let strings = self.strings(attrs.metadata());
+        probes::span_new(
+            id.into_u64(),
+            &strings.name,
+            &strings.target,
+            level_value(attrs.metadata().level()),
+        );
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, C>) {
+        if !probes::span_enter_enabled() {
+            return;
+        }
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let strings = self.strings(span.metadata());
+        probes::span_enter(
+            id.into_u64(),
+            &strings.name,
+            &strings.target,
+            level_value(span.metadata().level()),
+        );
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, C>) {
+        if !probes::span_exit_enabled() {
+            return;
+        }
+        let span = ctx.span(id).expect("Span not found, this is a bug");
+        let strings = self.strings(span.metadata());
+        probes::span_exit(
+            id.into_u64(),
+            &strings.name,
+            &strings.target,
+            level_value(span.metadata().level()),
+        );
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, C>) {
+        if !probes::span_close_enabled() {
+            return;
+        }
+        let span = ctx.span(&id).expect("Span not found, this is a bug");
+        let strings = self.strings(span.metadata());
+        probes::span_close(
+            id.into_u64(),
+            &strings.name,
+            &strings.target,
+            level_value(span.metadata().level()),
+        );
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        if !probes::event_enabled() {
+            return;
+        }
+        let strings = self.strings(event.metadata());
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+        probes::event(
+            0,
+            &to_cstring(&message.message),
+            &strings.target,
+            level_value(event.metadata().level()),
+        );
+    }
+}
+
+/// Maps a level to its probe argument value (1 = `ERROR` … 5 = `TRACE`).
+fn level_value(level: &Level) -> u64 {
+    match *level {
+        Level::ERROR => 1,
+        Level::WARN => 2,
+        Level::INFO => 3,
+        Level::DEBUG => 4,
+        Level::TRACE => 5,
+    }
+}
+
+/// Converts a string to a `CString`, replacing any interior NULs.
+fn to_cstring(value: &str) -> CString {
+    CString::new(value.replace('\0', " ")).expect("interior NULs were replaced")
+}
+
+/// Records an event's `message` field.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl field::Visit for MessageVisitor {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_owned();
+        }
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        }
+    }
+}
+
+/// The probe sites themselves.
+///
+/// Each probe expands to a `nop` and a `.note.stapsdt` ELF note in the
+/// format consumed by SystemTap, `bpftrace`, and BCC, plus an SDT
+/// semaphore that attached tracers increment. The note carries the probe
+/// address, the semaphore address, the provider and probe names, and the
+/// argument layout (four 8-byte arguments).
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod probes {
+    use std::ffi::CStr;
+    use std::sync::atomic::{AtomicU16, Ordering};
+
+    /// The SDT argument layout: four 8-byte arguments. Register names in
+    /// notes are spelled the way the native assembler syntax spells them,
+    /// so the format differs per architecture.
+    #[cfg(target_arch = "x86_64")]
+    macro_rules! sdt_args {
+        () => {
+            r#".asciz "-8@%{0} -8@%{1} -8@%{2} -8@%{3}""#
+        };
+    }
+    #[cfg(target_arch = "aarch64")]
+    macro_rules! sdt_args {
+        () => {
+            r#".asciz "-8@{0} -8@{1} -8@{2} -8@{3}""#
+        };
+    }
+
+    macro_rules! usdt_probe {
+        ($name:ident, $enabled:ident, $semaphore:ident) => {
+            static $semaphore: AtomicU16 = AtomicU16::new(0);
+
+            /// Returns whether a tracer is currently attached to this
+            /// probe.
+            #[inline]
+            pub(super) fn $enabled() -> bool {
+                $semaphore.load(Ordering::Relaxed) != 0
+            }
+
+            /// Fires the probe.
+            #[inline]
+            pub(super) fn $name(id: u64, name: &CStr, target: &CStr, level: u64) {
+                // Safety: the asm is a single `nop`; everything else is
+                // assembler directives emitting the SDT note.
+                unsafe {
+                    core::arch::asm!(
+                        r#"
+                        990: nop
+                             .pushsection .note.stapsdt,"","note"
+                             .balign 4
+                             .4byte 992f-991f, 994f-993f, 3
+                        991: .asciz "stapsdt"
+                        992: .balign 4
+                        993: .8byte 990b
+                             .8byte _.stapsdt.base
+                             .8byte {semaphore}
+                             .asciz "tracing"
+                        "#,
+                        concat!(".asciz \"", stringify!($name), "\""),
+                        sdt_args!(),
+                        r#"
+                        994: .balign 4
+                             .popsection
+                             .ifndef _.stapsdt.base
+                             .pushsection .stapsdt.base,"aG","progbits",.stapsdt.base,comdat
+                             .weak _.stapsdt.base
+                             .hidden _.stapsdt.base
+                        _.stapsdt.base: .space 1
+                             .popsection
+                             .endif
+                        "#,
+                        in(reg) id,
+                        in(reg) name.as_ptr(),
+                        in(reg) target.as_ptr(),
+                        in(reg) level,
+                        semaphore = sym $semaphore,
+                        options(readonly, nostack, preserves_flags),
+                    );
+                }
+            }
+        };
+    }
+
+    usdt_probe!(span_new, span_new_enabled, SPAN_NEW_SEMAPHORE);
+    usdt_probe!(span_enter, span_enter_enabled, SPAN_ENTER_SEMAPHORE);
+    usdt_probe!(span_exit, span_exit_enabled, SPAN_EXIT_SEMAPHORE);
+    usdt_probe!(span_close, span_close_enabled, SPAN_CLOSE_SEMAPHORE);
+    usdt_probe!(event, event_enabled, EVENT_SEMAPHORE);
+}
+
+/// No-op stand-ins for platforms without SDT support.
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+mod probes {
+    use std::ffi::CStr;
+
+    macro_rules! usdt_stub {
+        ($name:ident, $enabled:ident) => {
+            #[inline]
+            pub(super) fn $enabled() -> bool {
+                false
+            }
+
+            #[inline]
+            pub(super) fn $name(_id: u64, _name: &CStr, _target: &CStr, _level: u64) {}
+        };
+    }
+
+    usdt_stub!(span_new, span_new_enabled);
+    usdt_stub!(span_enter, span_enter_enabled);
+    usdt_stub!(span_exit, span_exit_enabled);
+    usdt_stub!(span_close, span_close_enabled);
+    usdt_stub!(event, event_enabled);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn probes_are_cheap_noops_while_unattached() {
+        // Nothing increments the semaphores in a test process, so the
+        // subscriber must pass through every lifecycle stage without
+        // firing.
+        let collector = crate::registry().with(Subscriber::new());
+        with_default(collector, || {
+            let span = tracing::info_span!("lifecycle", answer = 42);
+            let _entered = span.enter();
+            tracing::info!("an event");
+        });
+        assert!(!probes::span_enter_enabled());
+        assert!(!probes::event_enabled());
+    }
+
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    #[test]
+    fn probe_notes_are_present_in_the_binary() {
+        // Force the probe sites to be codegenned into this test binary.
+        probes::span_enter(0, c_str("x"), c_str("y"), 3);
+        probes::event(0, c_str("x"), c_str("y"), 3);
+
+        // The SDT notes should now be discoverable the same way `bpftrace
+        // -l` finds them: by the "stapsdt" note name and the provider and
+        // probe names in the note body.
+        let binary = std::fs::read("/proc/self/exe").expect("failed to read own binary");
+        assert!(contains(&binary, b"stapsdt\0"));
+        assert!(contains(&binary, b"tracing\0span_enter\0"));
+        assert!(contains(&binary, b"tracing\0event\0"));
+    }
+
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    fn c_str(value: &str) -> &std::ffi::CStr {
+        Box::leak(to_cstring(value).into_boxed_c_str())
+    }
+
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+}
@@ -0,0 +1,663 @@
+//! Streams JSON-formatted events to WebSocket clients.
+//!
+//! This module provides a [`Subscriber`] that formats events as JSON
+//! lines and serves them over a WebSocket endpoint, so a running
+//! service's tracing output can be tailed on demand — from `wscat`, a
+//! browser devtools console, or a dashboard — without touching the
+//! service's file or exporter configuration:
+//!
+//! ```text
+//! $ wscat -c "ws://localhost:6670/?filter=info,my_crate=debug"
+//! < {"timestamp":1724909000123,"level":"INFO","target":"my_crate","fields":{"message":"listening"}}
+//! ```
+//!
+//! Each connection may pass a `filter` query parameter with
+//! comma-separated directives in the `target=level` form used by
+//! [`EnvFilter`] (a bare `level` applies to all targets; a `target`
+//! directive matches events whose target starts with it). Filters apply
+//! per connection, so one client can tail `debug` output while another
+//! sees only errors. Without a `filter` parameter, a connection receives
+//! every event the subscriber sees.
+//!
+//! The subscriber also buffers the most recent events, and replays them
+//! (through the connection's filter) when a client connects, so a tail
+//! started just *after* an incident still shows it.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tracing_subscriber::{prelude::*, websocket};
+//!
+//! let (subscriber, server) = websocket::serve("127.0.0.1:6670")
+//!     .expect("failed to bind the WebSocket endpoint");
+//! println!("tail me at ws://{}", server.local_addr().unwrap());
+//! tracing_subscriber::registry().with(subscriber).init();
+//! ```
+//!
+//! # Security
+//!
+//! The endpoint performs no authentication; anyone who can connect to it
+//! can read the process' tracing output. Bind it to a loopback address,
+//! never to a publicly reachable one.
+//!
+//! [`EnvFilter`]: crate::filter::EnvFilter
+use crate::{
+    registry::LookupSpan,
+    sha1::sha1,
+    subscribe::{Context, Subscribe},
+};
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime},
+};
+use tracing_core::{field, Collect, Event, Level};
+
+/// How many recent events are retained for replay to new connections.
+const BUFFER_CAPACITY: usize = 256;
+
+/// The fixed GUID appended to the client's key in the WebSocket
+/// handshake (RFC 6455, section 1.3).
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// How long a broadcast may block on one client before the client is
+/// disconnected, so a stalled tail cannot stall the traced program.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A [`Subscribe`] implementation that streams JSON-formatted events to
+/// WebSocket clients.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Subscriber {
+    shared: Arc<Shared>,
+}
+
+/// A running WebSocket endpoint.
+///
+/// This is returned by [`serve`]. The endpoint is served on background
+/// threads, which run until the process exits; dropping the `Server`
+/// does not shut it down.
+#[derive(Debug)]
+pub struct Server {
+    addr: Option<SocketAddr>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    clients: Mutex<Vec<Client>>,
+    buffer: Mutex<VecDeque<BufferedEvent>>,
+}
+
+#[derive(Debug)]
+struct Client {
+    id: u64,
+    stream: TcpStream,
+    filter: Filter,
+}
+
+#[derive(Debug, Clone)]
+struct BufferedEvent {
+    level: Level,
+    target: String,
+    json: String,
+}
+
+/// A per-connection filter parsed from the `filter` query parameter.
+#[derive(Debug, Default)]
+struct Filter {
+    /// `(target prefix, max level)` directives; an empty prefix matches
+    /// every target. Empty `directives` means "everything".
+    directives: Vec<(String, Level)>,
+}
+
+/// Starts serving the WebSocket endpoint on a TCP socket bound to
+/// `addr`, returning the subscriber that feeds it.
+///
+/// The returned [`Server`]'s [`local_addr`] method returns the address
+/// the listener was actually bound to, which is useful when binding to
+/// port 0.
+///
+/// [`local_addr`]: Server::local_addr
+pub fn serve(addr: impl ToSocketAddrs) -> io::Result<(Subscriber, Server)> {
+    let listener = TcpListener::bind(addr)?;
+    let addr = listener.local_addr().ok();
+    let shared = Arc::new(Shared::default());
+    let accept_shared = shared.clone();
+    thread::Builder::new()
+        .name("tracing-websocket".into())
+        .spawn(move || {
+            for (id, stream) in listener.incoming().flatten().enumerate() {
+                let id = id as u64;
+                let shared = accept_shared.clone();
+                let _ = thread::Builder::new()
+                    .name("tracing-websocket-conn".into())
+                    .spawn(move || {
+                        let _ = handle_connection(&shared, id, stream);
+                        shared.remove_client(id);
+                    });
+            }
+        })?;
+    Ok((Subscriber { shared }, Server { addr }))
+}
+
+// === impl Server ===
+
+impl Server {
+    /// Returns the local address of the TCP listener.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+}
+
+// === impl Subscriber ===
+
+impl<C> Subscribe<C> for Subscriber
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        let metadata = event.metadata();
+        let mut json = format!(
+            "{{\"timestamp\":{},\"level\":\"{}\",\"target\":\"",
+            unix_millis(),
+            metadata.level(),
+        );
+        escape_into(&mut json, metadata.target());
+        json.push_str("\",\"fields\":{");
+        let mut visitor = JsonVisitor {
+            json: &mut json,
+            first: true,
+        };
+        event.record(&mut visitor);
+        json.push_str("}}");
+
+        let buffered = BufferedEvent {
+            level: *metadata.level(),
+            target: metadata.target().to_owned(),
+            json,
+        };
+        {
+            let mut buffer = self.shared.buffer.lock().expect("websocket lock poisoned");
+            if buffer.len() == BUFFER_CAPACITY {
+                buffer.pop_front();
+            }
+            buffer.push_back(buffered.clone());
+        }
+        self.shared.broadcast(&buffered);
+    }
+}
+
+// === impl Shared ===
+
+impl Shared {
+    /// Sends an event to every connected client whose filter enables it,
+    /// disconnecting clients whose sockets fail.
+    fn broadcast(&self, event: &BufferedEvent) {
+        let frame = text_frame(event.json.as_bytes());
+        let mut clients = self.clients.lock().expect("websocket lock poisoned");
+        clients.retain_mut(|client| {
+            if !client.filter.enables(&event.level, &event.target) {
+                return true;
+            }
+            client.stream.write_all(&frame).is_ok()
+        });
+    }
+
+    fn remove_client(&self, id: u64) {
+        self.clients
+            .lock()
+            .expect("websocket lock poisoned")
+            .retain(|client| client.id != id);
+    }
+}
+
+// === impl Filter ===
+
+impl Filter {
+    /// Parses comma-separated `target=level` directives; a bare `level`
+    /// applies to every target.
+    fn parse(directives: &str) -> Result<Self, String> {
+        let mut parsed = Vec::new();
+        for directive in directives.split(',') {
+            let directive = directive.trim();
+            if directive.is_empty() {
+                continue;
+            }
+            let (target, level) = match directive.split_once('=') {
+                Some((target, level)) => (target, level),
+                None => ("", directive),
+            };
+            let level = level
+                .parse::<Level>()
+                .map_err(|_| format!("invalid level in directive {:?}", directive))?;
+            parsed.push((target.to_owned(), level));
+        }
+        Ok(Self { directives: parsed })
+    }
+
+    /// Returns whether an event with this level and target should be
+    /// sent. The longest matching target prefix decides; with no
+    /// directives at all, everything is enabled.
+    fn enables(&self, level: &Level, target: &str) -> bool {
+        if self.directives.is_empty() {
+            return true;
+        }
+        self.directives
+            .iter()
+            .filter(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, max_level)| level <= max_level)
+            .unwrap_or(false)
+    }
+}
+
+/// Performs the server side of one connection: the HTTP upgrade
+/// handshake, the buffered-event replay, then reading control frames
+/// until the peer disconnects.
+fn handle_connection(shared: &Shared, id: u64, stream: TcpStream) -> io::Result<()> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let (key, filter) = read_handshake(&mut reader)?;
+    let filter = match filter {
+        Ok(filter) => filter,
+        Err(message) => {
+            let mut stream = stream;
+            stream.write_all(
+                format!(
+                    "HTTP/1.1 400 Bad Request\r\ncontent-length: {}\r\n\r\n{}",
+                    message.len(),
+                    message
+                )
+                .as_bytes(),
+            )?;
+            return Ok(());
+        }
+    };
+
+    let mut writer = stream.try_clone()?;
+    writer.write_all(
+        format!(
+            "HTTP/1.1 101 Switching Protocols\r\n\
+             upgrade: websocket\r\n\
+             connection: Upgrade\r\n\
+             sec-websocket-accept: {}\r\n\r\n",
+            accept_key(&key)
+        )
+        .as_bytes(),
+    )?;
+
+    // Replay the buffer through the connection's filter, then register
+    // for live broadcasts. Both happen under the clients lock so no
+    // event is replayed *and* broadcast to this connection.
+    {
+        let mut clients = shared.clients.lock().expect("websocket lock poisoned");
+        let buffer = shared.buffer.lock().expect("websocket lock poisoned");
+        for event in buffer.iter() {
+            if filter.enables(&event.level, &event.target) {
+                writer.write_all(&text_frame(event.json.as_bytes()))?;
+            }
+        }
+        drop(buffer);
+        clients.push(Client { id, stream, filter });
+    }
+
+    // Consume the peer's frames, answering pings and stopping on close
+    // or disconnect. Text frames from the peer are ignored.
+    loop {
+        let (opcode, payload) = match read_frame(&mut reader) {
+            Ok(frame) => frame,
+            Err(_) => return Ok(()),
+        };
+        match opcode {
+            // Close: echo it back, then drop the connection.
+            0x8 => {
+                let _ = writer.write_all(&frame(0x8, &payload));
+                return Ok(());
+            }
+            // Ping: answer with a pong carrying the same payload.
+            0x9 => writer.write_all(&frame(0xa, &payload))?,
+            _ => {}
+        }
+    }
+}
+
+/// Reads the client's HTTP upgrade request, returning the
+/// `Sec-WebSocket-Key` value and the parsed `filter` query parameter.
+fn read_handshake(reader: &mut impl BufRead) -> io::Result<(String, Result<Filter, String>)> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or("/")
+        .to_owned();
+
+    let mut key = None;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_owned());
+            }
+        }
+    }
+    let key =
+        key.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Sec-WebSocket-Key"))?;
+
+    let filter = match path.split_once('?').map(|(_, query)| query) {
+        Some(query) => query
+            .split('&')
+            .find_map(|param| param.strip_prefix("filter="))
+            .map(Filter::parse)
+            .unwrap_or_else(|| Ok(Filter::default())),
+        None => Ok(Filter::default()),
+    };
+    Ok((key, filter))
+}
+
+/// Computes the `Sec-WebSocket-Accept` value for a client key.
+fn accept_key(key: &str) -> String {
+    let mut input = key.as_bytes().to_vec();
+    input.extend_from_slice(HANDSHAKE_GUID.as_bytes());
+    base64(&sha1(&input))
+}
+
+/// Encodes a server-to-client frame with the given opcode. Server
+/// frames are unmasked (RFC 6455, section 5.1).
+fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    if payload.len() < 126 {
+        frame.push(payload.len() as u8);
+    } else if payload.len() <= u16::MAX as usize {
+        frame.push(126);
+        frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    } else {
+        frame.push(127);
+        frame.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Encodes a text frame.
+fn text_frame(payload: &[u8]) -> Vec<u8> {
+    frame(0x1, payload)
+}
+
+/// Reads one client frame, unmasking its payload.
+fn read_frame(reader: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 2];
+    reader.read_exact(&mut header)?;
+    let opcode = header[0] & 0x0f;
+    let masked = header[1] & 0x80 != 0;
+    let mut len = (header[1] & 0x7f) as u64;
+    if len == 126 {
+        let mut bytes = [0u8; 2];
+        reader.read_exact(&mut bytes)?;
+        len = u16::from_be_bytes(bytes) as u64;
+    } else if len == 127 {
+        let mut bytes = [0u8; 8];
+        reader.read_exact(&mut bytes)?;
+        len = u64::from_be_bytes(bytes);
+    }
+    let mut mask = [0u8; 4];
+    if masked {
+        reader.read_exact(&mut mask)?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+    if masked {
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+    Ok((opcode, payload))
+}
+
+/// Encodes `data` with the standard base64 alphabet and padding.
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let mut bits = 0u32;
+        for (i, byte) in chunk.iter().enumerate() {
+            bits |= (*byte as u32) << (16 - 8 * i);
+        }
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((bits >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Returns the current time as milliseconds since the Unix epoch.
+fn unix_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or(0)
+}
+
+/// Appends `value` to `out` with JSON string escaping.
+fn escape_into(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+/// Records an event's fields as JSON object members.
+struct JsonVisitor<'a> {
+    json: &'a mut String,
+    first: bool,
+}
+
+impl JsonVisitor<'_> {
+    fn member(&mut self, field: &field::Field) {
+        if !self.first {
+            self.json.push(',');
+        }
+        self.first = false;
+        self.json.push('"');
+        escape_into(self.json, field.name());
+        self.json.push_str("\":");
+    }
+}
+
+impl field::Visit for JsonVisitor<'_> {
+    fn record_bool(&mut self, field: &field::Field, value: bool) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_i64(&mut self, field: &field::Field, value: i64) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_u64(&mut self, field: &field::Field, value: u64) {
+        self.member(field);
+        let _ = write!(self.json, "{}", value);
+    }
+
+    fn record_f64(&mut self, field: &field::Field, value: f64) {
+        self.member(field);
+        if value.is_finite() {
+            let _ = write!(self.json, "{}", value);
+        } else {
+            let _ = write!(self.json, "\"{}\"", value);
+        }
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        self.member(field);
+        self.json.push('"');
+        escape_into(self.json, value);
+        self.json.push('"');
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        self.member(field);
+        self.json.push('"');
+        escape_into(self.json, &format!("{:?}", value));
+        self.json.push('"');
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use tracing::collect::with_default;
+
+    #[test]
+    fn accept_key_matches_the_reference_vector() {
+        // The worked example from RFC 6455, section 1.3.
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn frames_use_the_right_length_form() {
+        let short = text_frame(b"hello");
+        assert_eq!(&short[..2], &[0x81, 5]);
+        assert_eq!(&short[2..], b"hello");
+
+        let medium = text_frame(&[b'x'; 300]);
+        assert_eq!(&medium[..4], &[0x81, 126, 0x01, 0x2c]);
+        assert_eq!(medium.len(), 4 + 300);
+    }
+
+    #[test]
+    fn filters_pick_the_longest_matching_prefix() {
+        let filter = Filter::parse("warn,my_crate=debug,my_crate::noisy=error").unwrap();
+        assert!(filter.enables(&Level::WARN, "other"));
+        assert!(!filter.enables(&Level::INFO, "other"));
+        assert!(filter.enables(&Level::DEBUG, "my_crate::db"));
+        assert!(!filter.enables(&Level::WARN, "my_crate::noisy"));
+        assert!(filter.enables(&Level::ERROR, "my_crate::noisy"));
+        assert!(Filter::parse("nope=bogus").is_err());
+
+        // No directives at all enables everything.
+        assert!(Filter::default().enables(&Level::TRACE, "any"));
+    }
+
+    #[test]
+    fn clients_receive_buffered_and_live_events() {
+        let (subscriber, server) = serve("127.0.0.1:0").expect("failed to bind");
+        let addr = server.local_addr().expect("no local addr");
+        let collector = crate::registry().with(subscriber);
+
+        with_default(collector, || {
+            tracing::info!(target: "app::db", rows = 3, "buffered");
+
+            let mut stream = TcpStream::connect(addr).expect("failed to connect");
+            stream
+                .write_all(
+                    b"GET /?filter=app=info HTTP/1.1\r\n\
+                      host: localhost\r\n\
+                      upgrade: websocket\r\n\
+                      connection: Upgrade\r\n\
+                      sec-websocket-key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                      sec-websocket-version: 13\r\n\r\n",
+                )
+                .expect("failed to send handshake");
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut status = String::new();
+            reader.read_line(&mut status).expect("no response");
+            assert!(status.starts_with("HTTP/1.1 101"), "{}", status);
+            let mut saw_accept = false;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).expect("no header");
+                if line
+                    .trim_end()
+                    .eq_ignore_ascii_case("sec-websocket-accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=")
+                {
+                    saw_accept = true;
+                }
+                if line.trim_end().is_empty() {
+                    break;
+                }
+            }
+            assert!(saw_accept);
+
+            // The buffered event is replayed on connect.
+            let (opcode, payload) = read_frame(&mut reader).expect("no replayed frame");
+            let replayed = String::from_utf8(payload).expect("payload is not utf8");
+            assert_eq!(opcode, 0x1);
+            assert!(
+                replayed.contains("\"message\":\"buffered\""),
+                "{}",
+                replayed
+            );
+            assert!(replayed.contains("\"rows\":3"), "{}", replayed);
+            assert!(replayed.contains("\"target\":\"app::db\""), "{}", replayed);
+
+            // Live events arrive once the connection is registered;
+            // filtered-out events do not.
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            let live = loop {
+                tracing::debug!(target: "app::db", "too verbose");
+                tracing::info!(target: "app::db", "live");
+                stream
+                    .set_read_timeout(Some(Duration::from_millis(100)))
+                    .unwrap();
+                match read_frame(&mut reader) {
+                    Ok((_, payload)) => break String::from_utf8(payload).unwrap(),
+                    Err(_) if std::time::Instant::now() < deadline => continue,
+                    Err(e) => panic!("no live frame: {}", e),
+                }
+            };
+            assert!(live.contains("\"message\":\"live\""), "{}", live);
+        });
+    }
+
+    #[test]
+    fn invalid_filters_are_rejected_before_the_upgrade() {
+        let (subscriber, server) = serve("127.0.0.1:0").expect("failed to bind");
+        let addr = server.local_addr().expect("no local addr");
+        let collector = crate::registry().with(subscriber);
+
+        with_default(collector, || {
+            let mut stream = TcpStream::connect(addr).expect("failed to connect");
+            stream
+                .write_all(
+                    b"GET /?filter=app=bogus HTTP/1.1\r\n\
+                      sec-websocket-key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+                )
+                .expect("failed to send handshake");
+            let mut status = String::new();
+            BufReader::new(stream)
+                .read_line(&mut status)
+                .expect("no response");
+            assert!(status.starts_with("HTTP/1.1 400"), "{}", status);
+        });
+    }
+}
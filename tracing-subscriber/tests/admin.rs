@@ -0,0 +1,50 @@
+#![cfg(feature = "admin")]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+use tracing_subscriber::{admin, filter::EnvFilter, prelude::*, reload};
+
+fn send(addr: std::net::SocketAddr, command: &str) -> String {
+    let mut stream = TcpStream::connect(addr).expect("should connect to the admin endpoint");
+    writeln!(stream, "{}", command).expect("should write the command");
+    let mut response = String::new();
+    BufReader::new(stream)
+        .read_line(&mut response)
+        .expect("should read a response line");
+    response.trim_end().to_string()
+}
+
+#[test]
+fn get_set_reset() {
+    let (filter, handle) = reload::Subscriber::new(EnvFilter::new("info"));
+    let collector = tracing_subscriber::registry().with(filter);
+    let _guard = tracing::collect::set_default(collector);
+
+    let server = admin::serve_tcp(handle, "127.0.0.1:0").expect("should bind the admin endpoint");
+    let addr = server.local_addr().expect("TCP endpoints have an address");
+
+    assert_eq!(send(addr, "get"), "OK info");
+
+    assert_eq!(send(addr, "set mycrate=debug"), "OK mycrate=debug");
+    assert_eq!(send(addr, "GET"), "OK mycrate=debug");
+
+    // Invalid directives are rejected and leave the filter unchanged.
+    let response = send(addr, "set not=a=filter");
+    assert!(
+        response.starts_with("ERR "),
+        "unexpected response: {}",
+        response
+    );
+    assert_eq!(send(addr, "get"), "OK mycrate=debug");
+
+    assert_eq!(send(addr, "reset"), "OK info");
+    assert_eq!(send(addr, "get"), "OK info");
+
+    let response = send(addr, "frobnicate");
+    assert!(
+        response.starts_with("ERR "),
+        "unexpected response: {}",
+        response
+    );
+}
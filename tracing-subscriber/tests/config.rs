@@ -0,0 +1,113 @@
+#![cfg(all(feature = "config", feature = "std"))]
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing_subscriber::{
+    config::{Config, Error},
+    prelude::*,
+};
+
+/// Returns a unique path in the temp directory for this test.
+fn temp_file(name: &str) -> std::path::PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!("tracing-config-{}-{}", name, nanos))
+}
+
+/// Builds `config` for a [`Registry`](tracing_subscriber::Registry),
+/// panicking with `message` if it unexpectedly succeeds.
+fn build_err(config: &Config, message: &str) -> Error {
+    match config.build::<tracing_subscriber::Registry>() {
+        Ok(_) => panic!("{}", message),
+        Err(error) => error,
+    }
+}
+
+#[test]
+fn builds_a_filtered_file_subscriber() {
+    let path = temp_file("output");
+    let config: Config = serde_json::from_value(serde_json::json!({
+        "filter": { "directives": "info" },
+        "fmt": { "format": "compact", "ansi": false },
+        "output": { "kind": "file", "path": path },
+    }))
+    .unwrap();
+
+    let subscriber = config.build().expect("the configuration is valid");
+    let _guard = tracing_subscriber::registry()
+        .with(subscriber)
+        .set_default();
+
+    tracing::info!("enabled by the configured filter");
+    tracing::debug!("disabled by the configured filter");
+    drop(_guard);
+
+    let output = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        output.contains("enabled by the configured filter"),
+        "the info event must reach the configured file: {:?}",
+        output,
+    );
+    assert!(
+        !output.contains("disabled by the configured filter"),
+        "the debug event must be filtered out: {:?}",
+        output,
+    );
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn defaults_build() {
+    let config = Config::default();
+    let subscriber = config.build().expect("the default configuration is valid");
+    let _guard = tracing_subscriber::registry()
+        .with(subscriber)
+        .set_default();
+}
+
+#[test]
+fn invalid_directives_name_the_field() {
+    let mut config = Config::default();
+    config.filter.directives = Some("not=a=filter".into());
+    let error = build_err(&config, "invalid directives must be rejected");
+    assert_eq!(error.path(), "filter.directives");
+}
+
+#[test]
+fn unknown_format_names_the_field() {
+    let mut config = Config::default();
+    config.fmt.format = "yaml".into();
+    let error = build_err(&config, "an unknown format must be rejected");
+    assert_eq!(error.path(), "fmt.format");
+}
+
+#[test]
+fn file_output_requires_a_path() {
+    let mut config = Config::default();
+    config.output.kind = "file".into();
+    let error = build_err(&config, "file output without a path must be rejected");
+    assert_eq!(error.path(), "output.path");
+}
+
+#[test]
+fn path_is_rejected_for_stream_output() {
+    let mut config = Config::default();
+    config.output.path = Some(temp_file("unused"));
+    let error = build_err(&config, "a path with stdout output must be rejected");
+    assert_eq!(error.path(), "output.path");
+}
+
+#[test]
+fn unknown_fields_are_rejected_by_serde() {
+    let result: Result<Config, _> = serde_json::from_value(serde_json::json!({
+        "fmt": { "colour": true },
+    }));
+    let error = result.expect_err("unknown fields must be rejected");
+    assert!(
+        error.to_string().contains("colour"),
+        "the error must name the unknown field: {}",
+        error,
+    );
+}
@@ -141,6 +141,126 @@ fn add_directive_enables_event() {
     finished.assert_finished();
 }
 
+#[test]
+fn event_pattern_filter_matches_message() {
+    let filter: EnvFilter = "[{message=~\"timeout|refused\"}]=trace"
+        .parse()
+        .expect("filter should parse");
+    let (subscriber, finished) = collector::mock()
+        .event(expect::event().at_level(Level::DEBUG))
+        .event(expect::event().at_level(Level::TRACE))
+        .only()
+        .run_with_handle();
+    let subscriber = subscriber.with(filter);
+
+    with_default(subscriber, || {
+        tracing::debug!("connection timeout after 30s");
+        tracing::debug!("connection established");
+        tracing::trace!("connection refused by peer");
+        tracing::trace!("retrying");
+    });
+
+    finished.assert_finished();
+}
+
+#[test]
+fn event_pattern_filter_adds_to_other_directives() {
+    let filter: EnvFilter = "info,[{message=~\"slow\"}]=debug"
+        .parse()
+        .expect("filter should parse");
+    let (subscriber, finished) = collector::mock()
+        .event(expect::event().at_level(Level::INFO))
+        .event(expect::event().at_level(Level::DEBUG))
+        .only()
+        .run_with_handle();
+    let subscriber = subscriber.with(filter);
+
+    with_default(subscriber, || {
+        // enabled by the `info` directive, regardless of its message.
+        tracing::info!("regular event");
+        // enabled by the message pattern.
+        tracing::debug!("slow query: took 2s");
+        // enabled by neither directive.
+        tracing::debug!("fast query");
+    });
+
+    finished.assert_finished();
+}
+
+#[test]
+fn negated_directive_excludes_target() {
+    let filter: EnvFilter = "info,!exiled=info".parse().expect("filter should parse");
+    let (subscriber, finished) = collector::mock()
+        .event(expect::event().at_level(Level::INFO))
+        .only()
+        .run_with_handle();
+    let subscriber = subscriber.with(filter);
+
+    with_default(subscriber, || {
+        tracing::info!("this should be enabled");
+        tracing::info!(target: "exiled", "this should be excluded");
+        tracing::warn!(target: "exiled", "this should be excluded too");
+    });
+
+    finished.assert_finished();
+}
+
+#[test]
+fn negated_directive_excludes_matching_field_values() {
+    let filter: EnvFilter = "info,![{tenant=acme}]=info"
+        .parse()
+        .expect("filter should parse");
+    let (subscriber, finished) = collector::mock()
+        .event(expect::event().at_level(Level::INFO))
+        .event(expect::event().at_level(Level::WARN))
+        .only()
+        .run_with_handle();
+    let subscriber = subscriber.with(filter);
+
+    with_default(subscriber, || {
+        tracing::info!(tenant = "globex", "this should be enabled");
+        tracing::info!(tenant = "acme", "this should be excluded");
+        tracing::warn!("this has no tenant at all");
+    });
+
+    finished.assert_finished();
+}
+
+#[test]
+fn field_expression_filter_matches_any_branch() {
+    let filter: EnvFilter = "[{x=1 or y=2}]=debug".parse().expect("filter should parse");
+    let (subscriber, finished) = collector::mock()
+        .enter(expect::span().named("matching"))
+        .event(expect::event().at_level(Level::DEBUG))
+        .exit(expect::span().named("matching"))
+        // The unmatching span is still created, since its callsite could
+        // match the expression, but no events inside it are enabled.
+        .enter(expect::span().named("unmatching"))
+        .exit(expect::span().named("unmatching"))
+        .only()
+        .run_with_handle();
+    let subscriber = subscriber.with(filter);
+
+    with_default(subscriber, || {
+        // Matches the `y=2` branch of the expression, even though it has no
+        // `x` field.
+        let span = tracing::debug_span!("matching", y = 2);
+        {
+            let _enter = span.enter();
+            tracing::debug!("i should be enabled");
+        }
+
+        // Neither branch matches this span's values.
+        let span = tracing::debug_span!("unmatching", x = 3, y = 4);
+        {
+            let _enter = span.enter();
+            tracing::debug!("i should be disabled");
+        }
+    });
+
+    finished.assert_finished();
+}
+
 #[test]
 fn span_name_filter_is_dynamic() {
     let filter: EnvFilter = "info,[cool_span]=debug"
@@ -0,0 +1,89 @@
+#![cfg(all(feature = "env-filter", feature = "registry"))]
+
+use tracing::Level;
+use tracing_mock::{expect, subscriber};
+use tracing_subscriber::{
+    filter::{self, EnvFilter},
+    prelude::*,
+};
+
+#[test]
+fn set_replaces_named_filter() {
+    let (mock, handle) = subscriber::named("main")
+        .event(expect::event().at_level(Level::INFO))
+        .event(expect::event().at_level(Level::DEBUG))
+        .only()
+        .run_with_handle();
+
+    let filter = filter::registry().register("set_replaces_named_filter", EnvFilter::new("info"));
+    let _guard = tracing_subscriber::registry()
+        .with(mock.with_filter(filter))
+        .set_default();
+
+    tracing::info!("enabled by the initial filter");
+    tracing::debug!("disabled by the initial filter");
+
+    filter::registry()
+        .set("set_replaces_named_filter", EnvFilter::new("debug"))
+        .expect("the filter should be registered");
+
+    tracing::debug!("enabled by the replacement filter");
+
+    handle.assert_finished();
+}
+
+#[test]
+fn get_returns_current_directives() {
+    let filter =
+        filter::registry().register("get_returns_current_directives", EnvFilter::new("info"));
+    let _guard = tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::subscriber()
+                .with_writer(std::io::sink)
+                .with_filter(filter),
+        )
+        .set_default();
+
+    let directives = filter::registry()
+        .get("get_returns_current_directives")
+        .expect("the filter should be registered");
+    assert_eq!(directives, "info");
+
+    filter::registry()
+        .set("get_returns_current_directives", EnvFilter::new("warn"))
+        .expect("the filter should be registered");
+    let directives = filter::registry()
+        .get("get_returns_current_directives")
+        .expect("the filter should be registered");
+    assert_eq!(directives, "warn");
+}
+
+#[test]
+fn unknown_names_are_errors() {
+    let err = filter::registry()
+        .set("filter_registry_no_such_name", EnvFilter::new("debug"))
+        .expect_err("no filter is registered under this name");
+    assert!(err.is_not_found());
+    assert!(err.to_string().contains("filter_registry_no_such_name"));
+
+    let err = filter::registry()
+        .get("filter_registry_no_such_name")
+        .expect_err("no filter is registered under this name");
+    assert!(err.is_not_found());
+}
+
+#[test]
+fn deregister_removes_the_name() {
+    let _filter =
+        filter::registry().register("deregister_removes_the_name", EnvFilter::new("info"));
+    assert!(filter::registry()
+        .names()
+        .contains(&"deregister_removes_the_name".to_string()));
+
+    assert!(filter::registry().deregister("deregister_removes_the_name"));
+    assert!(!filter::registry().deregister("deregister_removes_the_name"));
+    assert!(filter::registry()
+        .set("deregister_removes_the_name", EnvFilter::new("debug"))
+        .expect_err("the registration should be removed")
+        .is_not_found());
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "std")]
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing_subscriber::{
+    filter::{watch_file, LevelFilter, Targets},
+    reload,
+};
+
+/// Returns a unique path in the temp directory for this test.
+fn temp_file(name: &str) -> std::path::PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!("tracing-filter-watch-{}-{}", name, nanos))
+}
+
+/// Waits until the watched filter matches `expected`, panicking if it does
+/// not within a few seconds.
+fn await_filter(handle: &reload::Handle<Targets>, expected: &Targets) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if handle.clone_current().as_ref() == Some(expected) {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    panic!(
+        "the filter was not reloaded; current: {:?}, expected: {:?}",
+        handle.clone_current(),
+        expected
+    );
+}
+
+#[test]
+fn reloads_directives_from_the_file() {
+    let path = temp_file("reload");
+    std::fs::write(&path, "warn\n").unwrap();
+
+    let (subscriber, handle) =
+        reload::Subscriber::new(Targets::new().with_default(LevelFilter::INFO));
+    // The handle only lives as long as the subscriber it reloads.
+    let _subscriber = subscriber;
+
+    let _watcher = watch_file(&path, Duration::from_millis(10), handle.clone());
+
+    // The file is loaded once immediately...
+    await_filter(&handle, &Targets::new().with_default(LevelFilter::WARN));
+
+    // ...and reloaded when it changes.
+    std::fs::write(&path, "debug,hyper=off\n").unwrap();
+    await_filter(
+        &handle,
+        &Targets::new()
+            .with_default(LevelFilter::DEBUG)
+            .with_target("hyper", LevelFilter::OFF),
+    );
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn parse_errors_leave_the_filter_unchanged() {
+    let path = temp_file("parse-error");
+    std::fs::write(&path, "trace\n").unwrap();
+
+    let (subscriber, handle) =
+        reload::Subscriber::new(Targets::new().with_default(LevelFilter::INFO));
+    let _subscriber = subscriber;
+
+    let _watcher = watch_file(&path, Duration::from_millis(10), handle.clone());
+    await_filter(&handle, &Targets::new().with_default(LevelFilter::TRACE));
+
+    std::fs::write(&path, "not=a=filter\n").unwrap();
+    // Give the watcher a few polls to (not) apply the bad directives.
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(
+        handle.clone_current(),
+        Some(Targets::new().with_default(LevelFilter::TRACE)),
+        "a parse error must not change the active filter",
+    );
+
+    let _ = std::fs::remove_file(&path);
+}
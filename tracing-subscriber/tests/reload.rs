@@ -54,6 +54,47 @@ impl<S: Collect> tracing_subscriber::Subscribe<S> for NopSubscriber {
 fn run_all_reload_test() {
     reload_handle();
     reload_filter();
+    swap_stack();
+}
+
+fn swap_stack() {
+    static TEXT_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static JSON_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    // Two differently-typed subscribers, standing in for differently-shaped
+    // pipelines.
+    struct TextStack;
+    impl<S: Collect> tracing_subscriber::Subscribe<S> for TextStack {
+        fn on_event(&self, _: &Event<'_>, _: subscribe::Context<'_, S>) {
+            TEXT_CALLS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    struct JsonStack;
+    impl<S: Collect> tracing_subscriber::Subscribe<S> for JsonStack {
+        fn on_event(&self, _: &Event<'_>, _: subscribe::Context<'_, S>) {
+            JSON_CALLS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (stack, handle) = Stack::wrap(TextStack);
+
+    let dispatcher =
+        tracing_core::dispatch::Dispatch::new(tracing_subscriber::registry().with(stack));
+
+    tracing_core::dispatch::with_default(&dispatcher, || {
+        event();
+
+        assert_eq!(TEXT_CALLS.load(Ordering::SeqCst), 1);
+        assert_eq!(JSON_CALLS.load(Ordering::SeqCst), 0);
+
+        handle.swap(JsonStack).expect("should swap");
+
+        event();
+
+        assert_eq!(TEXT_CALLS.load(Ordering::SeqCst), 1);
+        assert_eq!(JSON_CALLS.load(Ordering::SeqCst), 1);
+    })
 }
 
 fn reload_handle() {
@@ -0,0 +1,115 @@
+#![cfg(feature = "span-children")]
+use std::sync::{Arc, Mutex};
+use tracing::Collect;
+use tracing_core::Event;
+use tracing_subscriber::{
+    filter::filter_fn,
+    prelude::*,
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+
+/// Records the children and descendants of each event's span.
+#[derive(Clone, Default)]
+struct TreeProbe {
+    children: Arc<Mutex<Vec<Vec<&'static str>>>>,
+    descendants: Arc<Mutex<Vec<Vec<&'static str>>>>,
+}
+
+impl TreeProbe {
+    fn children(&self) -> Vec<Vec<&'static str>> {
+        self.children.lock().unwrap().clone()
+    }
+
+    fn descendants(&self) -> Vec<Vec<&'static str>> {
+        self.descendants.lock().unwrap().clone()
+    }
+}
+
+impl<C> Subscribe<C> for TreeProbe
+where
+    C: Collect + for<'lookup> LookupSpan<'lookup>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let span = ctx.event_span(event).expect("event should have a span");
+        let children = span.children().map(|span| span.name()).collect();
+        self.children.lock().unwrap().push(children);
+        let descendants = span.descendants().map(|span| span.name()).collect();
+        self.descendants.lock().unwrap().push(descendants);
+    }
+}
+
+#[test]
+fn children_and_descendants() {
+    let probe = TreeProbe::default();
+
+    let _guard = tracing_subscriber::registry()
+        .with(probe.clone())
+        .set_default();
+
+    let root = tracing::info_span!("root");
+    let child1 = tracing::info_span!(parent: &root, "child1");
+    let _grandchild = tracing::info_span!(parent: &child1, "grandchild");
+    let _child2 = tracing::info_span!(parent: &root, "child2");
+
+    root.in_scope(|| {
+        tracing::info!("query");
+    });
+
+    assert_eq!(probe.children(), vec![vec!["child1", "child2"]]);
+    assert_eq!(
+        probe.descendants(),
+        vec![vec!["child1", "grandchild", "child2"]]
+    );
+}
+
+#[test]
+fn closed_children_are_unlinked() {
+    let probe = TreeProbe::default();
+
+    let _guard = tracing_subscriber::registry()
+        .with(probe.clone())
+        .set_default();
+
+    let root = tracing::info_span!("root");
+    {
+        let _child = tracing::info_span!(parent: &root, "child");
+    }
+
+    root.in_scope(|| {
+        tracing::info!("query");
+    });
+
+    assert_eq!(probe.children(), vec![Vec::<&str>::new()]);
+}
+
+/// A subscriber whose filter disables a span in the middle of the tree sees
+/// the disabled span's children as direct children in its place, while an
+/// unfiltered subscriber sees the whole tree.
+#[test]
+fn filtered_children_are_collapsed() {
+    let filtered = TreeProbe::default();
+    let unfiltered = TreeProbe::default();
+
+    let _guard = tracing_subscriber::registry()
+        .with(
+            filtered
+                .clone()
+                .with_filter(filter_fn(|meta| !meta.is_span() || meta.name() != "middle")),
+        )
+        .with(unfiltered.clone())
+        .set_default();
+
+    let root = tracing::info_span!("root");
+    let middle = tracing::info_span!(parent: &root, "middle");
+    let _inner = tracing::info_span!(parent: &middle, "inner");
+
+    root.in_scope(|| {
+        tracing::info!("query");
+    });
+
+    assert_eq!(filtered.children(), vec![vec!["inner"]]);
+    assert_eq!(filtered.descendants(), vec![vec!["inner"]]);
+    assert_eq!(unfiltered.children(), vec![vec!["middle"]]);
+    assert_eq!(unfiltered.descendants(), vec![vec!["middle", "inner"]]);
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+use tracing::Level;
+use tracing_mock::{expect, subscriber};
+use tracing_subscriber::{
+    filter::{BoostFilter, LevelFilter},
+    prelude::*,
+};
+
+#[test]
+fn boost_enables_and_expires() {
+    let (filter, handle) = BoostFilter::new(LevelFilter::INFO);
+
+    let (mock, mock_handle) = subscriber::named("main")
+        .event(expect::event().at_level(Level::INFO))
+        // Only the debug event emitted while the boost is active is
+        // enabled.
+        .event(expect::event().at_level(Level::DEBUG))
+        .event(expect::event().at_level(Level::INFO))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(mock.with_filter(filter))
+        .set_default();
+
+    tracing::debug!("below the base level");
+    tracing::info!("hello");
+
+    handle.boost(
+        module_path!(),
+        LevelFilter::DEBUG,
+        Duration::from_millis(20),
+    );
+    tracing::debug!("boosted");
+
+    std::thread::sleep(Duration::from_millis(50));
+    tracing::debug!("the boost has expired");
+    tracing::info!("hello again");
+
+    mock_handle.assert_finished();
+}
+
+#[test]
+fn boost_only_applies_to_its_target() {
+    let (filter, handle) = BoostFilter::new(LevelFilter::INFO);
+
+    let (mock, mock_handle) = subscriber::named("main")
+        .event(expect::event().with_target("noisy_dependency"))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(mock.with_filter(filter))
+        .set_default();
+
+    handle.boost(
+        "noisy_dependency",
+        LevelFilter::TRACE,
+        Duration::from_secs(3600),
+    );
+
+    tracing::trace!(target: "noisy_dependency", "boosted");
+    tracing::trace!("a different target");
+
+    mock_handle.assert_finished();
+}
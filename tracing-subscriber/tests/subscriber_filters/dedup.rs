@@ -0,0 +1,94 @@
+use std::time::Duration;
+use tracing::Level;
+use tracing_mock::{expect, subscriber};
+use tracing_subscriber::{filter::DedupFilter, prelude::*};
+
+#[test]
+fn collapses_identical_consecutive_events() {
+    // A window long enough that a run cannot expire during the test.
+    let filter = DedupFilter::new(Duration::from_secs(3600));
+
+    let (mock, handle) = subscriber::named("main")
+        .event(expect::event().at_level(Level::INFO))
+        // The run of duplicates is summarized when a different event
+        // arrives.
+        .event(
+            expect::event()
+                .at_level(Level::WARN)
+                .with_target("tracing_subscriber::filter::dedup")
+                .with_fields(expect::field("repeated").with_value(&2u64)),
+        )
+        .event(expect::event().at_level(Level::WARN))
+        .only()
+        .run_with_handle();
+
+    // Summaries are dispatched to the subscribers below the filter, so it
+    // is layered over the mock rather than attached as a per-subscriber
+    // filter.
+    let _guard = tracing_subscriber::registry()
+        .with(mock)
+        .with(filter)
+        .set_default();
+
+    // Duplicates must share a callsite, so all the events come from one
+    // place.
+    fn spin() {
+        tracing::info!("still retrying");
+    }
+
+    spin();
+    spin();
+    spin();
+    tracing::warn!("gave up");
+
+    handle.assert_finished();
+}
+
+#[test]
+fn different_field_values_are_not_duplicates() {
+    let filter = DedupFilter::new(Duration::from_secs(3600));
+
+    let (mock, handle) = subscriber::named("main")
+        .event(expect::event().with_fields(expect::field("attempt").with_value(&1u64)))
+        .event(expect::event().with_fields(expect::field("attempt").with_value(&2u64)))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(mock.with_filter(filter))
+        .set_default();
+
+    fn attempt(n: u64) {
+        tracing::info!(attempt = n, "retrying");
+    }
+
+    attempt(1);
+    attempt(2);
+
+    handle.assert_finished();
+}
+
+#[test]
+fn window_expiry_starts_a_new_run() {
+    let filter = DedupFilter::new(Duration::from_millis(10));
+
+    let (mock, handle) = subscriber::named("main")
+        .event(expect::event().at_level(Level::INFO))
+        .event(expect::event().at_level(Level::INFO))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(mock.with_filter(filter))
+        .set_default();
+
+    fn spin() {
+        tracing::info!("spinning");
+    }
+
+    spin();
+    std::thread::sleep(Duration::from_millis(50));
+    spin();
+
+    handle.assert_finished();
+}
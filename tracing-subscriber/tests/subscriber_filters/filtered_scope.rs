@@ -0,0 +1,108 @@
+use std::sync::{Arc, Mutex};
+use tracing::Collect;
+use tracing_core::{span, Event};
+use tracing_subscriber::{
+    filter::filter_fn,
+    prelude::*,
+    registry::LookupSpan,
+    subscribe::{Context, Subscribe},
+};
+
+/// Records the scope (from root to leaf) seen for each event.
+#[derive(Clone, Default)]
+struct ScopeProbe {
+    scopes: Arc<Mutex<Vec<Vec<&'static str>>>>,
+}
+
+impl ScopeProbe {
+    fn scopes(&self) -> Vec<Vec<&'static str>> {
+        self.scopes.lock().unwrap().clone()
+    }
+}
+
+impl<C> Subscribe<C> for ScopeProbe
+where
+    C: Collect + for<'lookup> LookupSpan<'lookup>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let scope = ctx
+            .event_scope(event)
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+            .map(|span| span.name())
+            .collect();
+        self.scopes.lock().unwrap().push(scope);
+    }
+}
+
+/// A subscriber whose filter disables a span in the middle of the scope
+/// should not see that span in its event scopes, while an unfiltered
+/// subscriber sees the whole scope.
+#[test]
+fn filtered_spans_are_skipped_in_scope() {
+    let filtered = ScopeProbe::default();
+    let unfiltered = ScopeProbe::default();
+
+    let _guard = tracing_subscriber::registry()
+        .with(
+            filtered
+                .clone()
+                .with_filter(filter_fn(|meta| !meta.is_span() || meta.name() != "middle")),
+        )
+        .with(unfiltered.clone())
+        .set_default();
+
+    tracing::info_span!("outer").in_scope(|| {
+        tracing::info_span!("middle").in_scope(|| {
+            tracing::info_span!("inner").in_scope(|| {
+                tracing::info!("hello");
+            });
+        });
+    });
+
+    assert_eq!(filtered.scopes(), vec![vec!["outer", "inner"]]);
+    assert_eq!(unfiltered.scopes(), vec![vec!["outer", "middle", "inner"]]);
+}
+
+/// The same applies to `Context::span_scope` for an explicitly looked-up
+/// span.
+#[test]
+fn filtered_spans_are_skipped_in_span_scope() {
+    #[derive(Clone, Default)]
+    struct SpanScopeProbe {
+        scopes: Arc<Mutex<Vec<Vec<&'static str>>>>,
+    }
+
+    impl<C> Subscribe<C> for SpanScopeProbe
+    where
+        C: Collect + for<'lookup> LookupSpan<'lookup>,
+    {
+        fn on_enter(&self, id: &span::Id, ctx: Context<'_, C>) {
+            let scope = ctx
+                .span_scope(id)
+                .into_iter()
+                .flat_map(|scope| scope.from_root())
+                .map(|span| span.name())
+                .collect();
+            self.scopes.lock().unwrap().push(scope);
+        }
+    }
+
+    let probe = SpanScopeProbe::default();
+    let scopes = probe.scopes.clone();
+
+    let _guard = tracing_subscriber::registry()
+        .with(probe.with_filter(filter_fn(|meta| !meta.is_span() || meta.name() != "middle")))
+        .set_default();
+
+    tracing::info_span!("outer").in_scope(|| {
+        tracing::info_span!("middle").in_scope(|| {
+            tracing::info_span!("inner").in_scope(|| {});
+        });
+    });
+
+    let scopes = scopes.lock().unwrap().clone();
+    // the filtered subscriber is never notified of entering `middle` at all,
+    // and `middle` is absent from `inner`'s scope.
+    assert_eq!(scopes, vec![vec!["outer"], vec!["outer", "inner"]]);
+}
@@ -1,7 +1,13 @@
 #![cfg(feature = "registry")]
+mod boost;
+mod dedup;
 mod filter_scopes;
+mod filtered_scope;
 mod option;
 mod per_event;
+mod rate_limit;
+mod sampling;
+mod span_scope;
 mod targets;
 mod trees;
 mod vec;
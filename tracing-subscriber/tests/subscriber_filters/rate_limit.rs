@@ -0,0 +1,96 @@
+use std::time::Duration;
+use tracing::Level;
+use tracing_mock::{expect, subscriber};
+use tracing_subscriber::{filter::RateLimitFilter, prelude::*};
+
+#[test]
+fn limits_events_per_callsite() {
+    // A window long enough that the bucket cannot refill during the test.
+    let filter = RateLimitFilter::new(2, Duration::from_secs(3600));
+
+    let (mock, handle) = subscriber::named("main")
+        .event(expect::event().at_level(Level::INFO))
+        .event(expect::event().at_level(Level::INFO))
+        // Other callsites draw from their own buckets.
+        .event(expect::event().at_level(Level::WARN))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(mock.with_filter(filter))
+        .set_default();
+
+    for _ in 0..5 {
+        tracing::info!("limited to two");
+    }
+    tracing::warn!("a different callsite");
+
+    handle.assert_finished();
+}
+
+#[test]
+fn emits_summary_after_refill() {
+    let filter = RateLimitFilter::new(1, Duration::from_millis(10));
+
+    let (mock, handle) = subscriber::named("main")
+        .event(expect::event().at_level(Level::INFO))
+        // Once the bucket refills, a summary of the suppressed events is
+        // emitted before the next enabled event.
+        .event(
+            expect::event()
+                .at_level(Level::WARN)
+                .with_target("tracing_subscriber::filter::rate_limit"),
+        )
+        .event(expect::event().at_level(Level::INFO))
+        .only()
+        .run_with_handle();
+
+    // Summaries are dispatched to the subscribers below the rate limiter, so
+    // the limiter is layered over the mock rather than attached as a
+    // per-subscriber filter.
+    let _guard = tracing_subscriber::registry()
+        .with(mock)
+        .with(filter)
+        .set_default();
+
+    // Buckets are per callsite, so all the events must come from one place.
+    fn spin() {
+        tracing::info!("spinning");
+    }
+
+    // The first call takes the only token; the next two are suppressed.
+    spin();
+    spin();
+    spin();
+
+    std::thread::sleep(Duration::from_millis(50));
+    spin();
+
+    handle.assert_finished();
+}
+
+#[test]
+fn limits_events_per_field_value() {
+    let filter = RateLimitFilter::new(1, Duration::from_secs(3600)).per_field("tenant");
+
+    let (mock, handle) = subscriber::named("main")
+        .event(expect::event().with_fields(expect::field("tenant").with_value(&"acme")))
+        .event(expect::event().with_fields(expect::field("tenant").with_value(&"globex")))
+        // Events without the keying field are not limited.
+        .event(expect::event().at_level(Level::INFO))
+        .event(expect::event().at_level(Level::INFO))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(mock.with_filter(filter))
+        .set_default();
+
+    tracing::info!(tenant = "acme", "allowed");
+    tracing::info!(tenant = "acme", "suppressed");
+    tracing::info!(tenant = "globex", "a different bucket");
+    tracing::info!("not keyed");
+    tracing::info!("not keyed either");
+
+    handle.assert_finished();
+}
@@ -0,0 +1,91 @@
+use tracing::Level;
+use tracing_mock::{expect, subscriber};
+use tracing_subscriber::{filter::SamplingFilter, prelude::*};
+
+#[test]
+fn ratio_one_keeps_every_trace() {
+    let (mock, handle) = subscriber::named("main")
+        .new_span(expect::span().named("root"))
+        .enter(expect::span().named("root"))
+        .new_span(expect::span().named("child"))
+        .enter(expect::span().named("child"))
+        .event(expect::event().at_level(Level::INFO))
+        .exit(expect::span().named("child"))
+        .exit(expect::span().named("root"))
+        .event(expect::event().at_level(Level::INFO))
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(mock.with_filter(SamplingFilter::new(1.0)))
+        .set_default();
+
+    // Bind the spans so that they are not dropped until the end of the test;
+    // the mock subscriber expects the notifications in order.
+    let root = tracing::info_span!("root");
+    root.in_scope(|| {
+        let child = tracing::info_span!("child");
+        child.in_scope(|| {
+            tracing::info!("recorded");
+        });
+    });
+    tracing::info!("outside any trace");
+
+    handle.assert_finished();
+}
+
+#[test]
+fn ratio_zero_drops_whole_traces() {
+    let (mock, handle) = subscriber::named("main")
+        // Only the event outside of any trace is recorded.
+        .event(expect::event().at_level(Level::INFO))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(mock.with_filter(SamplingFilter::new(0.0)))
+        .set_default();
+
+    let root = tracing::info_span!("root");
+    root.in_scope(|| {
+        tracing::info!("dropped with the trace");
+        let child = tracing::info_span!("child");
+        child.in_scope(|| {
+            tracing::info!("also dropped");
+        });
+    });
+    tracing::info!("outside any trace");
+
+    handle.assert_finished();
+}
+
+#[test]
+fn keyed_decision_applies_to_descendants() {
+    // With a ratio of zero, every keyed trace is sampled out. The root span
+    // itself is still recorded, as the keying field's value is only seen
+    // once the span has been created.
+    let filter = SamplingFilter::new(0.0).keyed_by("trace_id");
+
+    let (mock, handle) = subscriber::named("main")
+        .new_span(expect::span().named("root"))
+        .enter(expect::span().named("root"))
+        .exit(expect::span().named("root"))
+        .event(expect::event().at_level(Level::INFO))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(mock.with_filter(filter))
+        .set_default();
+
+    let root = tracing::info_span!("root", trace_id = "4bf92f3577b34da6");
+    root.in_scope(|| {
+        tracing::info!("dropped with the trace");
+        let child = tracing::info_span!("child");
+        child.in_scope(|| {
+            tracing::info!("also dropped");
+        });
+    });
+    tracing::info!("outside any trace");
+
+    handle.assert_finished();
+}
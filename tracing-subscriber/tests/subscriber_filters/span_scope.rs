@@ -0,0 +1,80 @@
+use tracing::Level;
+use tracing_mock::{expect, subscriber};
+use tracing_subscriber::{
+    filter::{CaptureSpanFields, LevelFilter, SpanScopeFilter},
+    prelude::*,
+};
+
+#[test]
+fn enables_debug_in_matching_scope() {
+    let filter = SpanScopeFilter::new("tenant_id", "acme", LevelFilter::DEBUG)
+        .with_base_level(LevelFilter::INFO);
+
+    let (expect, handle) = subscriber::named("main")
+        .enter(expect::span().named("acme_request"))
+        .event(expect::event().at_level(Level::DEBUG))
+        .event(expect::event().at_level(Level::INFO))
+        .exit(expect::span().named("acme_request"))
+        .enter(expect::span().named("globex_request"))
+        // only the INFO event inside the non-matching scope is enabled
+        .event(expect::event().at_level(Level::INFO))
+        .exit(expect::span().named("globex_request"))
+        // outside any span, only the base level is enabled
+        .event(expect::event().at_level(Level::INFO))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(CaptureSpanFields::new(["tenant_id"]))
+        .with(expect.with_filter(filter))
+        .set_default();
+
+    // Bind the spans so that they are not dropped until the end of the test;
+    // the mock subscriber expects the events in order.
+    let acme = tracing::debug_span!("acme_request", tenant_id = "acme");
+    acme.in_scope(|| {
+        tracing::debug!("enabled for acme");
+        tracing::info!("also enabled");
+    });
+
+    let globex = tracing::debug_span!("globex_request", tenant_id = "globex");
+    globex.in_scope(|| {
+        tracing::debug!("disabled for globex");
+        tracing::info!("still enabled");
+    });
+
+    tracing::debug!("disabled outside any scope");
+    tracing::info!("enabled outside any scope");
+
+    handle.assert_finished();
+}
+
+#[test]
+fn consults_ancestor_spans() {
+    let filter = SpanScopeFilter::new("tenant_id", "acme", LevelFilter::DEBUG);
+
+    let (expect, handle) = subscriber::named("main")
+        .enter(expect::span().named("request"))
+        .enter(expect::span().named("inner"))
+        // the matching value was recorded by the grandparent span
+        .event(expect::event().at_level(Level::DEBUG))
+        .exit(expect::span().named("inner"))
+        .exit(expect::span().named("request"))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(CaptureSpanFields::new(["tenant_id"]))
+        .with(expect.with_filter(filter))
+        .set_default();
+
+    tracing::debug_span!("request", tenant_id = "acme").in_scope(|| {
+        tracing::debug_span!("inner").in_scope(|| {
+            tracing::debug!("enabled via ancestor");
+        });
+    });
+
+    tracing::debug!("disabled outside any scope");
+
+    handle.assert_finished();
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "registry")]
+
+use std::time::Duration;
+use tracing::Level;
+use tracing_mock::{expect, subscriber};
+use tracing_subscriber::{
+    filter::{TailPolicy, TailSampling},
+    prelude::*,
+};
+
+#[test]
+fn error_traces_are_kept() {
+    let policy = TailPolicy::new().with_error_events();
+
+    let (mock, handle) = subscriber::named("main")
+        // The whole trace is replayed once the root span closes.
+        .new_span(expect::span().named("failed_request"))
+        .event(expect::event().at_level(Level::INFO))
+        .event(expect::event().at_level(Level::ERROR))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(TailSampling::new(mock, policy))
+        .set_default();
+
+    tracing::info_span!("ok_request").in_scope(|| {
+        tracing::info!("nothing went wrong");
+    });
+
+    tracing::info_span!("failed_request").in_scope(|| {
+        tracing::info!("about to fail");
+        tracing::error!("it failed");
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn slow_traces_are_kept() {
+    let policy = TailPolicy::new().with_latency(Duration::from_millis(10));
+
+    let (mock, handle) = subscriber::named("main")
+        .new_span(expect::span().named("slow_request"))
+        .event(expect::event().at_level(Level::INFO))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(TailSampling::new(mock, policy))
+        .set_default();
+
+    tracing::info_span!("fast_request").in_scope(|| {
+        tracing::info!("done in no time");
+    });
+
+    tracing::info_span!("slow_request").in_scope(|| {
+        tracing::info!("this is taking a while");
+        std::thread::sleep(Duration::from_millis(50));
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn field_rules_match_span_fields() {
+    let policy = TailPolicy::new().with_field("tenant", "acme");
+
+    let (mock, handle) = subscriber::named("main")
+        .new_span(expect::span().named("request"))
+        .new_span(expect::span().named("child"))
+        .event(expect::event().at_level(Level::INFO))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(TailSampling::new(mock, policy))
+        .set_default();
+
+    tracing::info_span!("request", tenant = "acme").in_scope(|| {
+        tracing::info_span!("child").in_scope(|| {
+            tracing::info!("kept via the root span's field");
+        });
+    });
+
+    tracing::info_span!("request", tenant = "globex").in_scope(|| {
+        tracing::info!("discarded");
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn events_outside_traces_pass_through() {
+    let policy = TailPolicy::new().with_error_events();
+
+    let (mock, handle) = subscriber::named("main")
+        .event(expect::event().at_level(Level::INFO))
+        .only()
+        .run_with_handle();
+
+    let _guard = tracing_subscriber::registry()
+        .with(TailSampling::new(mock, policy))
+        .set_default();
+
+    tracing::info!("not part of any trace");
+
+    handle.assert_finished();
+}
@@ -982,6 +982,10 @@ pub use tracing_core::{event, Level, Metadata};
 
 #[doc(inline)]
 pub use self::span::Span;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[doc(inline)]
+pub use self::thread::spawn_traced;
 #[cfg(feature = "attributes")]
 #[cfg_attr(docsrs, doc(cfg(feature = "attributes")))]
 #[doc(inline)]
@@ -997,6 +1001,10 @@ pub mod field;
 pub mod instrument;
 pub mod level_filters;
 pub mod span;
+/// Propagate the current span into spawned threads.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod thread;
 
 #[doc(hidden)]
 pub mod __macro_support {
@@ -0,0 +1,204 @@
+//! Propagating the tracing context into spawned threads.
+//!
+//! Spawning a thread with [`std::thread::spawn`] drops the tracing context:
+//! the new thread starts outside of any span, and without the spawning
+//! thread's default collector. Code that wants work on the new thread to be
+//! attributed to the caller's span typically captures the [current span] by
+//! hand:
+//!
+//! ```
+//! use tracing::Span;
+//!
+//! let span = Span::current();
+//! std::thread::spawn(move || {
+//!     let _entered = span.enter();
+//!     // ...
+//! });
+//! ```
+//!
+//! This module provides sanctioned versions of that boilerplate. The
+//! [`spawn_traced`] function behaves like [`std::thread::spawn`], but runs
+//! the provided closure inside the spawning thread's current span, with the
+//! spawning thread's default collector. For cases where the thread is not
+//! spawned directly — such as handing work to a thread pool, or using
+//! [`std::thread::Builder`] — the [`WithContext`] wrapper captures the
+//! context up front so the closure can be run elsewhere, and
+//! [`spawn_traced_scoped`] does the same for threads spawned on a
+//! [`std::thread::Scope`].
+//!
+//! [current span]: crate::Span::current
+//! [`WithContext`]: crate::thread::WithContext
+//! [`spawn_traced_scoped`]: crate::thread::spawn_traced_scoped
+use crate::{
+    dispatch::{self, Dispatch},
+    span::Span,
+};
+use std::thread;
+
+/// A closure bundled with the tracing context in which it was created.
+///
+/// A `WithContext` captures the [current span] and the current default
+/// [collector] when it is constructed. Calling [`run`] executes the wrapped
+/// closure with that collector set as the default and the span entered,
+/// regardless of which thread `run` is called on. This allows thread-pool
+/// and builder-based code to inherit the caller's span without writing the
+/// capture-and-enter boilerplate by hand.
+///
+/// # Examples
+///
+/// Spawning a named thread with [`std::thread::Builder`]:
+///
+/// ```
+/// use tracing::thread::WithContext;
+///
+/// let span = tracing::info_span!("my_span");
+/// let _entered = span.enter();
+///
+/// let work = WithContext::new(|| {
+///     tracing::info!("this event occurs inside `my_span`");
+/// });
+///
+/// std::thread::Builder::new()
+///     .name("worker".into())
+///     .spawn(move || work.run())
+///     .expect("failed to spawn thread")
+///     .join()
+///     .expect("thread panicked");
+/// ```
+///
+/// [current span]: crate::Span::current
+/// [collector]: crate::Collect
+/// [`run`]: Self::run
+#[derive(Debug, Clone)]
+#[must_use = "a `WithContext` does nothing unless its closure is `run`"]
+pub struct WithContext<F> {
+    inner: F,
+    span: Span,
+    dispatch: Dispatch,
+}
+
+// === impl WithContext ===
+
+impl<F> WithContext<F> {
+    /// Wraps `inner` with the [current span] and the current default
+    /// [collector].
+    ///
+    /// [current span]: crate::Span::current
+    /// [collector]: crate::Collect
+    pub fn new(inner: F) -> Self {
+        Self::with_span(inner, Span::current())
+    }
+
+    /// Wraps `inner` with the provided `span` and the current default
+    /// [collector].
+    ///
+    /// [collector]: crate::Collect
+    pub fn with_span(inner: F, span: Span) -> Self {
+        Self {
+            inner,
+            span,
+            dispatch: dispatch::get_default(|default| default.clone()),
+        }
+    }
+
+    /// Borrows the [`Span`] that the wrapped closure will be run inside of.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Borrows the wrapped closure.
+    pub fn inner(&self) -> &F {
+        &self.inner
+    }
+
+    /// Consumes the `WithContext`, returning the wrapped closure.
+    ///
+    /// Note that this drops the captured context.
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
+}
+
+impl<F, T> WithContext<F>
+where
+    F: FnOnce() -> T,
+{
+    /// Runs the wrapped closure in the captured context, returning its
+    /// result.
+    ///
+    /// The captured collector is set as the [default] and the captured span
+    /// is [entered] for the duration of the call.
+    ///
+    /// [default]: crate::dispatch#setting-the-default-collector
+    /// [entered]: crate::Span::enter
+    pub fn run(self) -> T {
+        let _default = dispatch::set_default(&self.dispatch);
+        let _entered = self.span.enter();
+        (self.inner)()
+    }
+}
+
+/// Spawns a new thread running `f` in the spawning thread's tracing context,
+/// like [`std::thread::spawn`].
+///
+/// The spawning thread's [current span] and default [collector] are captured
+/// when this function is called, and `f` runs with that collector set as the
+/// default and the span entered. Events emitted by the new thread are
+/// therefore attributed to the span that was current when it was spawned.
+///
+/// # Examples
+///
+/// ```
+/// let span = tracing::info_span!("my_span");
+/// let _entered = span.enter();
+///
+/// tracing::spawn_traced(|| {
+///     tracing::info!("this event occurs inside `my_span`");
+/// })
+/// .join()
+/// .expect("thread panicked");
+/// ```
+///
+/// [current span]: crate::Span::current
+/// [collector]: crate::Collect
+pub fn spawn_traced<F, T>(f: F) -> thread::JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let f = WithContext::new(f);
+    thread::spawn(move || f.run())
+}
+
+/// Spawns a new scoped thread running `f` in the spawning thread's tracing
+/// context, like [`std::thread::Scope::spawn`].
+///
+/// This is the [`spawn_traced`] equivalent for threads spawned within a
+/// [`std::thread::scope`] block.
+///
+/// # Examples
+///
+/// ```
+/// let span = tracing::info_span!("my_span");
+/// let _entered = span.enter();
+///
+/// let results = [1, 2, 3];
+/// std::thread::scope(|scope| {
+///     for result in &results {
+///         tracing::thread::spawn_traced_scoped(scope, move || {
+///             tracing::info!(result, "this event occurs inside `my_span`");
+///         });
+///     }
+/// });
+/// ```
+pub fn spawn_traced_scoped<'scope, F, T>(
+    scope: &'scope thread::Scope<'scope, '_>,
+    f: F,
+) -> thread::ScopedJoinHandle<'scope, T>
+where
+    F: FnOnce() -> T + Send + 'scope,
+    T: Send + 'scope,
+{
+    let f = WithContext::new(f);
+    scope.spawn(move || f.run())
+}
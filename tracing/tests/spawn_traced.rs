@@ -0,0 +1,83 @@
+// These tests require the thread-local scoped dispatcher, and `thread::spawn`,
+// which is not yet possible in WASM.
+#![cfg(feature = "std")]
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::thread;
+
+use tracing::{collect::with_default, thread::WithContext, Level};
+use tracing_mock::*;
+
+#[test]
+fn spawn_traced_propagates_the_current_span() {
+    let (collector, handle) = collector::mock()
+        .new_span(expect::span().named("parent"))
+        .enter(expect::span().named("parent"))
+        .enter(expect::span().named("parent"))
+        .event(expect::event().with_ancestry(expect::has_contextual_parent("parent")))
+        .exit(expect::span().named("parent"))
+        .exit(expect::span().named("parent"))
+        .run_with_handle();
+
+    with_default(collector, || {
+        let span = tracing::span!(Level::TRACE, "parent");
+        let _entered = span.enter();
+        tracing::spawn_traced(|| {
+            tracing::info!("in the parent span");
+        })
+        .join()
+        .unwrap();
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn with_context_runs_in_an_explicit_span() {
+    let (collector, handle) = collector::mock()
+        .new_span(expect::span().named("explicit"))
+        .enter(expect::span().named("explicit"))
+        .event(expect::event().with_ancestry(expect::has_contextual_parent("explicit")))
+        .exit(expect::span().named("explicit"))
+        .run_with_handle();
+
+    with_default(collector, || {
+        let span = tracing::span!(Level::TRACE, "explicit");
+        let work = WithContext::with_span(
+            || {
+                tracing::info!("in the explicit span");
+            },
+            span,
+        );
+
+        // The context travels with the closure, even though the new thread
+        // has no default collector of its own.
+        thread::spawn(move || work.run()).join().unwrap();
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn spawn_traced_scoped_propagates_the_current_span() {
+    let (collector, handle) = collector::mock()
+        .new_span(expect::span().named("parent"))
+        .enter(expect::span().named("parent"))
+        .enter(expect::span().named("parent"))
+        .event(expect::event().with_ancestry(expect::has_contextual_parent("parent")))
+        .exit(expect::span().named("parent"))
+        .exit(expect::span().named("parent"))
+        .run_with_handle();
+
+    with_default(collector, || {
+        let span = tracing::span!(Level::TRACE, "parent");
+        let _entered = span.enter();
+        thread::scope(|scope| {
+            tracing::thread::spawn_traced_scoped(scope, || {
+                tracing::info!("in the parent span");
+            });
+        });
+    });
+
+    handle.assert_finished();
+}